@@ -0,0 +1,144 @@
+//! Access to the runner's debug methods
+//!
+//! [`EXPORT_BUNDLE_METHOD`] and [`EXPORT_TRACE_METHOD`] expose logs, spans,
+//! provenance, and config, so every caller is denied them unless the runner
+//! is started with `--admin-tokens <file>`. The file maps bearer tokens to
+//! the principals they authenticate and the tenants those act for, e.g.
+//! `{ "<token>": { "principal": "ops", "tenants": ["acme"] } }`. Requests
+//! carry the token in an `Authorization` header or the `auth` param, and
+//! only the principals listed may call the debug methods.
+
+use baml_rt_a2a::{AccessRules, Authenticator, BearerTokens, Grants, TransportMetadata, authorize};
+use baml_rt_core::Result;
+use baml_rt_core::auth::{AccessPolicy, Caller, Principal};
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+
+/// Admin method that writes a diagnostics bundle and returns its location
+pub const EXPORT_BUNDLE_METHOD: &str = "debug.exportBundle";
+
+/// Admin method that returns the HTML timeline of one request
+pub const EXPORT_TRACE_METHOD: &str = "debug.exportTrace";
+
+const DEBUG_METHODS: [&str; 2] = [EXPORT_BUNDLE_METHOD, EXPORT_TRACE_METHOD];
+
+/// One entry of an `--admin-tokens` file
+#[derive(Debug, Deserialize)]
+struct AdminToken {
+    principal: String,
+    #[serde(default)]
+    tenants: Vec<String>,
+}
+
+/// Who may call the debug methods
+pub struct AdminAccess {
+    authenticator: Option<BearerTokens>,
+    policy: Arc<dyn AccessPolicy>,
+}
+
+impl AdminAccess {
+    /// Deny the debug methods to every caller
+    pub fn deny_all() -> Self {
+        Self {
+            authenticator: None,
+            policy: Arc::new(AccessRules::new().with_authenticated(Grants::none())),
+        }
+    }
+
+    /// Allow the debug methods to the principals of an `--admin-tokens` file
+    pub async fn load(path: &Path) -> Result<Self> {
+        let content = tokio::fs::read_to_string(path).await?;
+        let tokens: HashMap<String, AdminToken> = serde_json::from_str(&content)?;
+        let mut authenticator = BearerTokens::new();
+        let mut rules = AccessRules::new().with_authenticated(Grants::none());
+        for (token, admin) in tokens {
+            let principal = admin
+                .tenants
+                .into_iter()
+                .fold(Principal::new(&admin.principal), Principal::with_tenant);
+            authenticator = authenticator.with_token(token, principal);
+            rules =
+                rules.with_principal(admin.principal, Grants::none().with_methods(DEBUG_METHODS));
+        }
+        Ok(Self {
+            authenticator: Some(authenticator),
+            policy: Arc::new(rules),
+        })
+    }
+
+    /// Authenticate a debug request and check that its caller may make it
+    pub async fn authorize(
+        &self,
+        request: &mut Value,
+        transport: &TransportMetadata,
+    ) -> Result<Caller> {
+        let authenticator = self
+            .authenticator
+            .as_ref()
+            .map(|tokens| tokens as &dyn Authenticator);
+        authorize(request, transport, authenticator, self.policy.clone()).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use baml_rt_core::BamlRtError;
+    use serde_json::json;
+
+    fn export_trace(token: Option<&str>) -> Value {
+        let mut params = json!({ "correlation_id": "req-1" });
+        if let Some(token) = token {
+            params["auth"] = json!({ "token": token });
+        }
+        json!({ "jsonrpc": "2.0", "id": 1, "method": EXPORT_TRACE_METHOD, "params": params })
+    }
+
+    #[tokio::test]
+    async fn debug_methods_are_denied_without_admin_tokens() {
+        let access = AdminAccess::deny_all();
+        let transport = TransportMetadata::new();
+        assert!(matches!(
+            access.authorize(&mut export_trace(None), &transport).await,
+            Err(BamlRtError::Unauthenticated(_))
+        ));
+        assert!(
+            access
+                .authorize(&mut export_trace(Some("guess")), &transport)
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn admin_tokens_authenticate_principals_for_their_tenants() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("admin-tokens.json");
+        let tokens = json!({ "s3cret": { "principal": "ops", "tenants": ["acme"] } });
+        std::fs::write(&path, tokens.to_string()).unwrap();
+        let access = AdminAccess::load(&path).await.unwrap();
+        let transport = TransportMetadata::new();
+
+        let mut request = export_trace(Some("s3cret"));
+        let caller = access.authorize(&mut request, &transport).await.unwrap();
+        assert_eq!(caller.principal().map(Principal::id), Some("ops"));
+        assert_eq!(request["params"]["tenant"], "acme");
+        assert!(request["params"].get("auth").is_none());
+
+        let mut request = export_trace(Some("s3cret"));
+        request["params"]["tenant"] = json!("globex");
+        assert!(matches!(
+            access.authorize(&mut request, &transport).await,
+            Err(BamlRtError::Forbidden { .. })
+        ));
+        assert!(matches!(
+            access
+                .authorize(&mut export_trace(Some("guess")), &transport)
+                .await,
+            Err(BamlRtError::Unauthenticated(_))
+        ));
+    }
+}
@@ -0,0 +1,188 @@
+//! Diagnostics bundle export
+//!
+//! A bundle is a tar.gz holding everything a bug report usually needs: recent
+//! logs and span snapshots, runtime stats, the runner's configuration, loaded
+//! agent manifests, and the latest provenance events. Anything that looks like
+//! a credential is redacted before it is written.
+
+use baml_rt_core::{BamlRtError, Result};
+use flate2::Compression;
+use flate2::write::GzEncoder;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::fs;
+use std::path::Path;
+use tar::{Builder, Header};
+
+/// Provenance events included when the caller does not ask for a number
+pub const DEFAULT_PROVENANCE_LIMIT: usize = 200;
+
+/// Environment variable prefixes worth reporting
+const ENV_PREFIXES: &[&str] = &["BAML_", "OTEL_", "RUST_LOG"];
+
+/// Name fragments that mark a key as holding a secret
+const SECRET_MARKERS: &[&str] = &["KEY", "SECRET", "TOKEN", "PASSWORD", "CREDENTIAL", "AUTH"];
+
+const REDACTED: &str = "[redacted]";
+
+/// Files collected for a diagnostics bundle, all under one top-level directory
+pub struct DiagnosticsBundle {
+    root: String,
+    files: Vec<(String, Vec<u8>)>,
+}
+
+impl DiagnosticsBundle {
+    pub fn new(root: impl Into<String>) -> Self {
+        Self {
+            root: root.into(),
+            files: Vec::new(),
+        }
+    }
+
+    /// Add a pretty-printed JSON file
+    pub fn add_json<T: Serialize>(&mut self, path: &str, value: &T) -> Result<()> {
+        let mut content = serde_json::to_vec_pretty(value)?;
+        content.push(b'\n');
+        self.files.push((path.to_string(), content));
+        Ok(())
+    }
+
+    /// Add a JSON Lines file with one record per line
+    pub fn add_json_lines<T: Serialize>(&mut self, path: &str, records: &[T]) -> Result<()> {
+        let mut content = Vec::new();
+        for record in records {
+            serde_json::to_writer(&mut content, record)?;
+            content.push(b'\n');
+        }
+        self.files.push((path.to_string(), content));
+        Ok(())
+    }
+
+    /// Paths of the collected files, relative to the bundle root
+    pub fn file_names(&self) -> Vec<String> {
+        self.files.iter().map(|(path, _)| path.clone()).collect()
+    }
+
+    /// Write the bundle as a tar.gz, returning its size in bytes
+    pub fn write_tar_gz(&self, output: &Path) -> Result<u64> {
+        if let Some(parent) = output
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+        {
+            fs::create_dir_all(parent).map_err(BamlRtError::Io)?;
+        }
+
+        let file = fs::File::create(output).map_err(BamlRtError::Io)?;
+        let mut tar = Builder::new(GzEncoder::new(file, Compression::default()));
+        for (path, content) in &self.files {
+            let mut header = Header::new_gnu();
+            header
+                .set_path(format!("{}/{}", self.root, path))
+                .map_err(BamlRtError::TarHeaderPath)?;
+            header.set_size(content.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            tar.append(&header, content.as_slice())
+                .map_err(BamlRtError::Io)?;
+        }
+        tar.into_inner()
+            .and_then(|encoder| encoder.finish())
+            .map_err(BamlRtError::Io)?;
+
+        Ok(fs::metadata(output).map_err(BamlRtError::Io)?.len())
+    }
+}
+
+/// Relevant environment variables, with secret values redacted
+pub fn redacted_environment() -> Map<String, Value> {
+    let mut vars: Vec<(String, String)> = std::env::vars()
+        .filter(|(name, _)| {
+            ENV_PREFIXES.iter().any(|prefix| name.starts_with(prefix)) || is_secret_name(name)
+        })
+        .collect();
+    vars.sort();
+    vars.into_iter()
+        .map(|(name, value)| {
+            let value = if is_secret_name(&name) {
+                REDACTED.to_string()
+            } else {
+                value
+            };
+            (name, Value::String(value))
+        })
+        .collect()
+}
+
+/// Replace the values of secret-looking keys anywhere in `value`
+pub fn redact(value: Value) -> Value {
+    match value {
+        Value::Object(map) => Value::Object(
+            map.into_iter()
+                .map(|(key, value)| {
+                    let value = if is_secret_name(&key) && !value.is_null() {
+                        Value::String(REDACTED.to_string())
+                    } else {
+                        redact(value)
+                    };
+                    (key, value)
+                })
+                .collect(),
+        ),
+        Value::Array(values) => Value::Array(values.into_iter().map(redact).collect()),
+        other => other,
+    }
+}
+
+fn is_secret_name(name: &str) -> bool {
+    let name = name.to_ascii_uppercase();
+    SECRET_MARKERS.iter().any(|marker| name.contains(marker))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn redacts_secret_keys_at_any_depth() {
+        let redacted = redact(json!({
+            "name": "agent",
+            "api_key": "sk-123",
+            "clients": [{ "options": { "AuthToken": "abc", "model": "gpt" } }],
+            "secret": null,
+        }));
+        assert_eq!(
+            redacted,
+            json!({
+                "name": "agent",
+                "api_key": "[redacted]",
+                "clients": [{ "options": { "AuthToken": "[redacted]", "model": "gpt" } }],
+                "secret": null,
+            })
+        );
+    }
+
+    #[test]
+    fn writes_files_under_bundle_root() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let output = dir.path().join("bundle.tar.gz");
+        let mut bundle = DiagnosticsBundle::new("diag");
+        bundle
+            .add_json("stats.json", &json!({ "agents": 1 }))
+            .unwrap();
+        bundle
+            .add_json_lines("logs.jsonl", &[json!({ "a": 1 }), json!({ "b": 2 })])
+            .unwrap();
+
+        assert!(bundle.write_tar_gz(&output).unwrap() > 0);
+
+        let archive = flate2::read::GzDecoder::new(fs::File::open(&output).unwrap());
+        let mut paths: Vec<String> = tar::Archive::new(archive)
+            .entries()
+            .unwrap()
+            .map(|entry| entry.unwrap().path().unwrap().display().to_string())
+            .collect();
+        paths.sort();
+        assert_eq!(paths, vec!["diag/logs.jsonl", "diag/stats.json"]);
+    }
+}
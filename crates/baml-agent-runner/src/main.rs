@@ -1,90 +1,106 @@
 //! BAML Agent Runner
 //!
 //! This binary loads and executes one or more packaged agent applications.
-//! Each agent package is a tar.gz containing BAML schemas, compiled TypeScript,
-//! and metadata.
-
+//! Each agent package is a tar.gz or zip archive, or an OCI artifact holding
+//! one, containing BAML schemas, compiled TypeScript, and metadata.
+
+mod admin;
+mod diagnostics;
+mod orchestration;
+mod package;
+mod queue;
+mod record;
+mod schedule;
+
+use admin::{AdminAccess, EXPORT_BUNDLE_METHOD, EXPORT_TRACE_METHOD};
 use anyhow::Context;
-use baml_rt_a2a::a2a_types::JSONRPCId;
-use baml_rt_a2a::{A2aAgent, A2aRequestHandler, a2a};
+use baml_rt_a2a::a2a_types::{JSONRPCId, ROLE_USER};
+use baml_rt_a2a::{
+    A2aAgent, A2aMethod, BatchExecution, FORBIDDEN_CODE, RoutedRequest, RoutingTable,
+    TransportMetadata, UNAUTHENTICATED_CODE, a2a, normalizer_for,
+};
+use baml_rt_core::ids::{CorrelationId, TenantId};
+use baml_rt_core::manifest::{AgentManifest, MANIFEST_FILE, RUNTIME_VERSION};
 use baml_rt_core::{BamlRtError, Result};
-use baml_rt_observability::{spans, tracing_setup};
-use baml_rt_quickjs::{BamlRuntimeManager, QuickJSBridge};
-use serde_json::Value;
+use baml_rt_observability::{recent_activity, spans, tracing_setup};
+use baml_rt_provenance::{ProvenanceReader, timeline_html};
+use baml_rt_quickjs::{
+    BamlRuntimeManager, DryRun, FileKvStore, InMemoryKvStore, KvStore, QuickJSBridge,
+    QuickJSConfig, SharedQuickJsRuntime, ToolSchemaInjection,
+};
+use diagnostics::{DEFAULT_PROVENANCE_LIMIT, DiagnosticsBundle};
+use orchestration::LocalAgents;
+use queue::NatsConfig;
+use record::SessionRecorder;
+use schedule::ScheduleTrigger;
+use serde_json::{Value, json};
 use std::collections::HashMap;
-use std::path::Path;
+use std::io::Write;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use tokio::sync::Mutex;
-use tracing::{error, info};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tokio::sync::{Mutex, RwLock};
+use tracing::{Instrument, error, info};
 
-/// Agent package metadata
-#[derive(Debug, Clone)]
-struct AgentManifest {
-    version: String,
-    name: String,
-    entry_point: String,
-}
+/// How often each agent's QuickJS memory use is recorded as metrics
+const MEMORY_METRICS_INTERVAL: Duration = Duration::from_secs(30);
 
 /// Agent package loader and executor
 struct AgentPackage {
     name: String,
+    version: String,
+    package_path: PathBuf,
+    /// manifest.json as shipped in the package
+    manifest: AgentManifest,
+    /// Function calls the manifest schedules
+    schedules: Vec<ScheduleTrigger>,
     agent: A2aAgent,
+    /// Where the package was extracted, removed when the agent is dropped
+    _extract_dir: tempfile::TempDir,
 }
 
 impl AgentPackage {
-    /// Load an agent package from a tar.gz file
-    async fn load_from_file(package_path: &Path) -> Result<Self> {
+    /// Load an agent package from a tar.gz or zip file, or from an OCI
+    /// registry with an `oci://` reference
+    ///
+    /// The package is extracted to a directory of its own in `workdir`, or
+    /// in the system temp directory when there is none.
+    ///
+    /// With a `shared_runtime`, the agent runs in its own realm of that engine
+    /// instead of getting a QuickJS runtime of its own. Its JS calls other
+    /// agents through `local_agents`, which learns the routes listed in the
+    /// manifest. With `ignore_version`, an agent built for an incompatible
+    /// runtime version is loaded with a warning instead of refused. Its
+    /// `kvSet` state goes to `kv_store`, in namespaces of its own. With
+    /// `dry_run`, its BAML calls return their rendered requests instead of
+    /// reaching a provider.
+    async fn load_from_file(
+        package_path: &Path,
+        shared_runtime: Option<&SharedQuickJsRuntime>,
+        local_agents: &Arc<LocalAgents>,
+        ignore_version: bool,
+        kv_store: &Arc<dyn KvStore>,
+        dry_run: bool,
+        workdir: Option<&Path>,
+    ) -> Result<Self> {
         let span = spans::load_agent_package(package_path);
         let _guard = span.enter();
 
-        // Create temporary extraction directory
-        let epoch_secs = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)?
-            .as_secs();
-        let extract_dir = std::env::temp_dir().join(format!("baml-agent-{}", epoch_secs));
-        std::fs::create_dir_all(&extract_dir).map_err(BamlRtError::Io)?;
-
-        {
-            let extract_span = spans::extract_package(&extract_dir);
-            let _extract_guard = extract_span.enter();
-
-            // Extract tar.gz
-            let tar_gz = std::fs::File::open(package_path).map_err(BamlRtError::Io)?;
-            let tar = flate2::read::GzDecoder::new(tar_gz);
-            let mut archive = tar::Archive::new(tar);
+        let temp_dir = extraction_dir(workdir)?;
+        let extract_dir = temp_dir.path();
+        package::extract(package_path, extract_dir)
+            .instrument(spans::extract_package(extract_dir))
+            .await?;
 
-            archive.unpack(&extract_dir).map_err(BamlRtError::Io)?;
+        let manifest = AgentManifest::load(&extract_dir.join(MANIFEST_FILE))?;
+        match manifest.check_runtime_version(RUNTIME_VERSION) {
+            Err(err) if ignore_version => {
+                tracing::warn!(error = %err, "Loading agent despite runtime version mismatch");
+            }
+            result => result?,
         }
-
-        // Load manifest
-        let manifest_path = extract_dir.join("manifest.json");
-        let manifest_content = std::fs::read_to_string(&manifest_path).map_err(BamlRtError::Io)?;
-        let manifest_json: Value =
-            serde_json::from_str(&manifest_content).map_err(BamlRtError::Json)?;
-
-        let manifest = AgentManifest {
-            version: manifest_json
-                .get("version")
-                .and_then(|v| v.as_str())
-                .ok_or_else(|| {
-                    BamlRtError::InvalidArgument(
-                        "manifest.json missing 'version' field".to_string(),
-                    )
-                })?
-                .to_string(),
-            name: manifest_json
-                .get("name")
-                .and_then(|v| v.as_str())
-                .ok_or_else(|| {
-                    BamlRtError::InvalidArgument("manifest.json missing 'name' field".to_string())
-                })?
-                .to_string(),
-            entry_point: manifest_json
-                .get("entry_point")
-                .and_then(|v| v.as_str())
-                .unwrap_or("dist/index.js")
-                .to_string(),
-        };
+        manifest.check_environment()?;
+        manifest.check_capabilities()?;
 
         info!(
             name = manifest.name,
@@ -93,6 +109,9 @@ impl AgentPackage {
             "Agent manifest loaded"
         );
 
+        local_agents.allow(manifest.name.clone(), manifest.calls.clone());
+        let schedules = schedule::parse_triggers(&manifest.schedules)?;
+
         // Validate package structure
         let baml_src = extract_dir.join("baml_src");
         if !baml_src.exists() {
@@ -103,24 +122,37 @@ impl AgentPackage {
 
         // Create runtime manager
         let mut runtime_manager = BamlRuntimeManager::new()?;
+        runtime_manager.set_agent_name(manifest.name.clone());
+        runtime_manager.set_kv_store(kv_store.clone());
+        if dry_run {
+            runtime_manager.set_dry_run(Some(DryRun::new()));
+        }
+        if !manifest.tool_functions.is_empty() {
+            runtime_manager.set_tool_schema_injection(ToolSchemaInjection::new(
+                manifest.tool_functions.clone(),
+            ));
+        }
 
         // Load BAML schema
         {
             let schema_span = spans::load_baml_schema(&baml_src);
             let _schema_guard = schema_span.enter();
-            let baml_src_str = baml_src.to_str().ok_or_else(|| {
-                BamlRtError::InvalidArgument("BAML source path contains invalid UTF-8".to_string())
-            })?;
-            runtime_manager.load_schema(baml_src_str)?;
+            runtime_manager.load_schema(&baml_src)?;
             info!(agent = manifest.name, "BAML schema loaded");
         }
 
         // Create QuickJS bridge and expose BAML functions to it
-        let runtime_manager_arc = Arc::new(Mutex::new(runtime_manager));
+        let runtime_manager_arc = Arc::new(RwLock::new(runtime_manager));
         let bridge = {
             let bridge_span = spans::create_js_bridge();
             let _bridge_guard = bridge_span.enter();
-            let mut bridge = QuickJSBridge::new(runtime_manager_arc.clone()).await?;
+            let mut bridge = match shared_runtime {
+                Some(shared) => {
+                    QuickJSBridge::new_in_realm(runtime_manager_arc.clone(), shared, &manifest.name)
+                        .await?
+                }
+                None => QuickJSBridge::new(runtime_manager_arc.clone()).await?,
+            };
             bridge.register_baml_functions().await?;
             info!(
                 agent = manifest.name,
@@ -136,6 +168,10 @@ impl AgentPackage {
             let _eval_guard = eval_span.enter();
 
             let agent_code = std::fs::read_to_string(&entry_point_path).map_err(BamlRtError::Io)?;
+            // Packaged bundles carry a source map so errors point at the TypeScript
+            let mut source_map_path = entry_point_path.clone().into_os_string();
+            source_map_path.push(".map");
+            let source_map = std::fs::read_to_string(source_map_path).ok();
 
             info!(
                 entry_point = manifest.entry_point,
@@ -145,7 +181,12 @@ impl AgentPackage {
             // Execute the agent's code to initialize it
             // The code should expose functions that can be called later
             // We ignore the result since it's just initialization code
-            match bridge.lock().await.evaluate(&agent_code).await {
+            match bridge
+                .lock()
+                .await
+                .evaluate_script(&manifest.entry_point, &agent_code, source_map.as_deref())
+                .await
+            {
                 Ok(_) => info!("Agent code executed successfully"),
                 Err(e) => {
                     // Log warning but don't fail - the code might just not return a value
@@ -164,16 +205,28 @@ impl AgentPackage {
             );
         }
 
+        // The agent's code has run, so every tool it declares is registered
+        let tools = runtime_manager_arc.read().await.list_tools().await;
+        manifest.check_tools(&tools)?;
+
         let agent = A2aAgent::builder()
             .with_runtime_handle(runtime_manager_arc)
             .with_bridge_handle(bridge)
             .with_baml_helpers(false)
+            .with_agent_caller(Arc::new(local_agents.caller_for(manifest.name.clone())))
+            .with_memory_metrics(manifest.name.clone(), MEMORY_METRICS_INTERVAL)
+            .with_stream_normalizer(normalizer_for(manifest.stream_format.unwrap_or_default()))
             .build()
             .await?;
 
         Ok(Self {
-            name: manifest.name,
+            name: manifest.name.clone(),
+            version: manifest.version.clone(),
+            package_path: package_path.to_path_buf(),
+            manifest,
+            schedules,
             agent,
+            _extract_dir: temp_dir,
         })
     }
 
@@ -192,27 +245,103 @@ impl AgentPackage {
         js_bridge.invoke_js_function(function_name, args).await
     }
 
-    async fn handle_a2a(&self, request: Value) -> Result<Vec<Value>> {
-        self.agent.handle_a2a(request).await
+    /// BAML functions exposed by this agent
+    async fn function_names(&self) -> Vec<String> {
+        self.agent.runtime().read().await.list_functions()
+    }
+
+    async fn handle_routed(
+        &self,
+        routed: RoutedRequest,
+        transport: &TransportMetadata,
+    ) -> Result<Vec<Value>> {
+        self.agent
+            .handle_routed_with_transport(routed, transport)
+            .await
     }
 }
 
+/// A new directory, unique to one package, for it to be extracted to
+fn extraction_dir(workdir: Option<&Path>) -> Result<tempfile::TempDir> {
+    let mut builder = tempfile::Builder::new();
+    builder.prefix("baml-agent-");
+    let dir = match workdir {
+        Some(workdir) => {
+            std::fs::create_dir_all(workdir)?;
+            builder.tempdir_in(workdir)
+        }
+        None => builder.tempdir(),
+    };
+    Ok(dir?)
+}
+
 /// Agent runner that manages multiple agent packages
 struct AgentRunner {
     agents: HashMap<String, AgentPackage>,
+    routing: RoutingTable,
+    started: Instant,
+    a2a_stdio: bool,
+    /// How the members of JSON-RPC batches read from stdin or NATS are run
+    batch_execution: BatchExecution,
+    /// Engine hosting every agent in its own realm, when enabled
+    shared_runtime: Option<SharedQuickJsRuntime>,
+    /// Loaded agents and the routes they may call each other along
+    local_agents: Arc<LocalAgents>,
+    /// Load agents built for incompatible runtime versions
+    ignore_version: bool,
+    /// Key-value state of every agent, each in namespaces of its own
+    kv_store: Arc<dyn KvStore>,
+    /// Skip LLM calls, returning the requests they would send
+    dry_run: bool,
+    /// Where served requests and their responses are recorded, if anywhere
+    recorder: Option<SessionRecorder>,
+    /// Where packages are extracted instead of the system temp directory
+    workdir: Option<PathBuf>,
+    /// Who may call the debug methods
+    admin: AdminAccess,
 }
 
 impl AgentRunner {
     fn new() -> Self {
         Self {
             agents: HashMap::new(),
+            routing: RoutingTable::new(),
+            started: Instant::now(),
+            a2a_stdio: false,
+            batch_execution: BatchExecution::Sequential,
+            shared_runtime: None,
+            local_agents: Arc::new(LocalAgents::new()),
+            ignore_version: false,
+            kv_store: Arc::new(InMemoryKvStore::new()),
+            dry_run: false,
+            recorder: None,
+            workdir: None,
+            admin: AdminAccess::deny_all(),
         }
     }
 
+    /// Host all agents loaded from now on in one shared QuickJS runtime
+    fn use_shared_runtime(&mut self, config: QuickJSConfig) {
+        self.shared_runtime = Some(SharedQuickJsRuntime::new(config));
+    }
+
     /// Load an agent package
     async fn load_agent(&mut self, package_path: &Path) -> Result<()> {
-        let agent = AgentPackage::load_from_file(package_path).await?;
+        let agent = AgentPackage::load_from_file(
+            package_path,
+            self.shared_runtime.as_ref(),
+            &self.local_agents,
+            self.ignore_version,
+            &self.kv_store,
+            self.dry_run,
+            self.workdir.as_deref(),
+        )
+        .await?;
         let name = agent.name().to_string();
+        let functions = agent.function_names().await;
+        self.routing.register_agent(name.clone(), functions.clone());
+        self.local_agents
+            .register(name.clone(), agent.agent.clone(), functions);
         info!(agent = name, "Agent loaded successfully");
         self.agents.insert(name.clone(), agent);
         Ok(())
@@ -230,16 +359,234 @@ impl AgentRunner {
         agent.invoke_function(function_name, args).await
     }
 
+    /// Responses to an A2A message carrying `text`, sent with
+    /// `message.sendStream` when `stream` is set and `message.send` otherwise
+    ///
+    /// Without an `agent`, the message goes to the only loaded agent.
+    async fn send_message(&self, agent: Option<&str>, text: &str, stream: bool) -> Vec<Value> {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos();
+        let mut message = json!({
+            "messageId": format!("cli-{}", nanos),
+            "role": ROLE_USER,
+            "parts": [{ "text": text }],
+        });
+        if let Some(agent) = agent {
+            message["metadata"] = json!({ "agent": agent });
+        }
+        let method = if stream {
+            A2aMethod::MessageSendStream
+        } else {
+            A2aMethod::MessageSend
+        };
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": method.as_str(),
+            "params": { "message": message },
+        });
+        self.handle_stdio_request(request, &TransportMetadata::default())
+            .await
+    }
+
+    /// Start the schedules of every loaded agent
+    fn start_schedules(&self) {
+        for agent in self.sorted_agents() {
+            schedule::spawn_schedules(&agent.name, &agent.agent, agent.schedules.clone());
+        }
+    }
+
     /// List all loaded agents
     fn list_agents(&self) -> Vec<String> {
         self.agents.keys().cloned().collect()
     }
 
+    /// Loaded agents in name order
+    fn sorted_agents(&self) -> Vec<&AgentPackage> {
+        let mut agents: Vec<&AgentPackage> = self.agents.values().collect();
+        agents.sort_by(|a, b| a.name.cmp(&b.name));
+        agents
+    }
+
+    /// Gather logs, spans, stats, config, manifests, and provenance into a
+    /// tar.gz at `output`, returning a summary of what was written
+    async fn export_diagnostics(&self, output: &Path, provenance_limit: usize) -> Result<Value> {
+        let created_at_ms = SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis() as u64;
+        let mut bundle = DiagnosticsBundle::new(format!("baml-diagnostics-{}", created_at_ms));
+
+        let activity = recent_activity();
+        bundle.add_json_lines("logs.jsonl", &activity.logs())?;
+        bundle.add_json_lines("spans.jsonl", &activity.spans())?;
+
+        let mut agent_stats = Vec::new();
+        let mut provenance = Vec::new();
+        for agent in self.sorted_agents() {
+            agent_stats.push(json!({
+                "name": agent.name,
+                "version": agent.version,
+                "baml_functions": agent.function_names().await,
+                "quickjs_memory": agent.agent.bridge().lock().await.memory_stats(),
+            }));
+            bundle.add_json(
+                &format!("agents/{}/manifest.json", agent.name),
+                &diagnostics::redact(serde_json::to_value(&agent.manifest)?),
+            )?;
+            if let Some(reader) = agent.agent.provenance_reader() {
+                let events = reader
+                    .recent_events(provenance_limit)
+                    .await
+                    .map_err(|e| BamlRtError::ExecutionFailed { source: e.into() })?;
+                provenance.extend(
+                    events
+                        .into_iter()
+                        .map(|event| json!({ "agent": agent.name, "event": event })),
+                );
+            }
+        }
+        bundle.add_json_lines("provenance.jsonl", &provenance)?;
+
+        bundle.add_json(
+            "stats.json",
+            &json!({
+                "runner_version": env!("CARGO_PKG_VERSION"),
+                "pid": std::process::id(),
+                "uptime_secs": self.started.elapsed().as_secs_f64(),
+                "os": std::env::consts::OS,
+                "arch": std::env::consts::ARCH,
+                "agents": agent_stats,
+            }),
+        )?;
+        bundle.add_json(
+            "config.json",
+            &json!({
+                "packages": self
+                    .sorted_agents()
+                    .iter()
+                    .map(|agent| agent.package_path.display().to_string())
+                    .collect::<Vec<_>>(),
+                "a2a_stdio": self.a2a_stdio,
+                "agent_routes": self.local_agents.routes(),
+                "shared_runtime_realms": self
+                    .shared_runtime
+                    .as_ref()
+                    .map(SharedQuickJsRuntime::realms),
+                "environment": diagnostics::redacted_environment(),
+            }),
+        )?;
+
+        let bytes = bundle.write_tar_gz(output)?;
+        info!(path = %output.display(), bytes, "Diagnostics bundle written");
+        Ok(json!({
+            "path": output.display().to_string(),
+            "bytes": bytes,
+            "files": bundle.file_names(),
+        }))
+    }
+
+    /// Handle a debug method, once its caller is authenticated and allowed it
+    async fn handle_debug_request(
+        &self,
+        method: &str,
+        mut request: Value,
+        transport: &TransportMetadata,
+    ) -> Result<Value> {
+        self.admin.authorize(&mut request, transport).await?;
+        match method {
+            EXPORT_BUNDLE_METHOD => self.handle_export_bundle(&request).await,
+            _ => self.handle_export_trace(&request).await,
+        }
+    }
+
+    /// Handle `debug.exportBundle`; the bundle is written to the temp directory
+    async fn handle_export_bundle(&self, request: &Value) -> Result<Value> {
+        let provenance_limit = request
+            .get("params")
+            .and_then(|params| params.get("provenance_limit"))
+            .and_then(Value::as_u64)
+            .map(|limit| limit as usize)
+            .unwrap_or(DEFAULT_PROVENANCE_LIMIT);
+        let epoch_ms = SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis();
+        let output = std::env::temp_dir().join(format!("baml-diagnostics-{}.tar.gz", epoch_ms));
+        self.export_diagnostics(&output, provenance_limit).await
+    }
+
+    /// Handle `debug.exportTrace`: the HTML timeline of the request with
+    /// `params.correlation_id`, from the provenance every agent recorded for
+    /// it in the caller's tenant
+    ///
+    /// Spans carry no tenant, so the recent spans are only included when no
+    /// other tenant took part in the request.
+    async fn handle_export_trace(&self, request: &Value) -> Result<Value> {
+        let params = request.get("params");
+        let correlation_id = params
+            .and_then(|params| params.get("correlation_id"))
+            .and_then(Value::as_str)
+            .ok_or_else(|| {
+                BamlRtError::InvalidArgument(format!(
+                    "{} requires params.correlation_id",
+                    EXPORT_TRACE_METHOD
+                ))
+            })?;
+        let correlation_id = CorrelationId::from(correlation_id);
+        let tenant = params
+            .and_then(|params| params.get("tenant"))
+            .and_then(Value::as_str)
+            .map(TenantId::from);
+
+        let mut events = Vec::new();
+        for agent in self.sorted_agents() {
+            if let Some(reader) = agent.agent.provenance_reader() {
+                events.extend(
+                    reader
+                        .correlated_events(&correlation_id)
+                        .await
+                        .map_err(|e| BamlRtError::ExecutionFailed { source: e.into() })?,
+                );
+            }
+        }
+        let shared = events.iter().any(|event| event.tenant_id != tenant);
+        events.retain(|event| event.tenant_id == tenant);
+        let spans = if shared {
+            Vec::new()
+        } else {
+            recent_activity().spans()
+        };
+        let html = timeline_html(&correlation_id, &events, &spans);
+        info!(
+            correlation_id = correlation_id.as_str(),
+            events = events.len(),
+            "Trace timeline exported"
+        );
+        Ok(json!({
+            "html": html,
+            "events": events.len(),
+        }))
+    }
+
     async fn run_a2a_stdio(&self) -> Result<()> {
+        self.serve_lines(tokio::io::BufReader::new(tokio::io::stdin()), false)
+            .await
+    }
+
+    /// Serve the requests in a file, or replay a `--record`ed session
+    async fn run_a2a_file(&self, path: &Path) -> Result<()> {
+        let file = tokio::fs::File::open(path).await?;
+        self.serve_lines(tokio::io::BufReader::new(file), true)
+            .await
+    }
+
+    /// Answer each line of `input` on stdout; with `replay`, lines may be
+    /// recorded entries
+    async fn serve_lines(
+        &self,
+        input: impl tokio::io::AsyncBufRead + Unpin,
+        replay: bool,
+    ) -> Result<()> {
         use tokio::io::{self, AsyncBufReadExt, AsyncWriteExt};
 
-        let stdin = io::stdin();
-        let mut lines = io::BufReader::new(stdin).lines();
+        let mut lines = input.lines();
         let mut stdout = io::stdout();
 
         while let Some(line) = lines.next_line().await? {
@@ -247,63 +594,14 @@ impl AgentRunner {
             if line.is_empty() {
                 continue;
             }
-
-            let mut request_value: Value = match serde_json::from_str(line) {
-                Ok(value) => value,
-                Err(err) => {
-                    let response = a2a::error_response(
-                        None,
-                        -32700,
-                        "JSON parse error",
-                        Some(Value::String(err.to_string())),
-                    );
-                    let serialized = serde_json::to_string(&response)
-                        .unwrap_or_else(|_| "{\"error\":\"serialization failed\"}".to_string());
-                    stdout.write_all(serialized.as_bytes()).await?;
-                    stdout.write_all(b"\n").await?;
-                    stdout.flush().await?;
-                    continue;
-                }
+            let request = if replay {
+                record::replayed_request(line)
+            } else {
+                line.as_bytes().to_vec()
             };
 
-            let request_id = a2a::extract_jsonrpc_id(&request_value);
-            let (agent_name, prepared_request) = match self.prepare_a2a_request(&mut request_value)
-            {
-                Ok(result) => result,
-                Err(err) => {
-                    let response = map_a2a_error(request_id, err);
-                    let serialized = serde_json::to_string(&response)
-                        .unwrap_or_else(|_| "{\"error\":\"serialization failed\"}".to_string());
-                    stdout.write_all(serialized.as_bytes()).await?;
-                    stdout.write_all(b"\n").await?;
-                    stdout.flush().await?;
-                    continue;
-                }
-            };
-
-            let agent = match self.agents.get(&agent_name) {
-                Some(agent) => agent,
-                None => {
-                    let response = a2a::error_response(
-                        request_id,
-                        -32601,
-                        "Agent not found",
-                        Some(Value::String(agent_name)),
-                    );
-                    let serialized = serde_json::to_string(&response)
-                        .unwrap_or_else(|_| "{\"error\":\"serialization failed\"}".to_string());
-                    stdout.write_all(serialized.as_bytes()).await?;
-                    stdout.write_all(b"\n").await?;
-                    stdout.flush().await?;
-                    continue;
-                }
-            };
-
-            let responses = agent
-                .handle_a2a(prepared_request)
-                .await
-                .unwrap_or_else(|err| vec![map_a2a_error(request_id, err)]);
-            for response in responses {
+            let transport = TransportMetadata::default();
+            for response in self.handle_raw_request(&request, &transport).await {
                 let serialized = serde_json::to_string(&response)
                     .unwrap_or_else(|_| "{\"error\":\"serialization failed\"}".to_string());
                 stdout.write_all(serialized.as_bytes()).await?;
@@ -315,106 +613,126 @@ impl AgentRunner {
         Ok(())
     }
 
-    fn prepare_a2a_request(&self, request: &mut Value) -> Result<(String, Value)> {
-        let method = request
-            .get("method")
-            .and_then(|v| v.as_str())
-            .ok_or_else(|| BamlRtError::InvalidArgument("A2A request missing method".to_string()))?
-            .to_string();
+    /// Serve requests published to NATS until the subscription ends
+    async fn run_a2a_nats(&self, config: &NatsConfig) -> Result<()> {
+        queue::serve(config, |payload, transport| async move {
+            self.handle_raw_request(&payload, &transport).await
+        })
+        .await
+    }
 
-        if is_a2a_method(&method) {
-            let agent_name = a2a::extract_agent_name(request).or_else(|| {
-                request
-                    .get("params")
-                    .and_then(|params| params.get("agent"))
-                    .and_then(|agent| agent.as_str())
-                    .map(|agent| agent.to_string())
-            });
-            if let Some(agent_name) = agent_name {
-                return Ok((agent_name, request.clone()));
-            }
-            if self.agents.len() == 1 {
-                let agent_name = self.agents.keys().next().cloned().unwrap_or_default();
-                return Ok((agent_name, request.clone()));
-            }
-            return Err(BamlRtError::InvalidArgument(
-                "A2A request missing agent (set message metadata agent or params.agent)"
-                    .to_string(),
-            ));
+    /// Responses to the raw bytes of a JSON-RPC request or batch
+    async fn handle_raw_request(&self, raw: &[u8], transport: &TransportMetadata) -> Vec<Value> {
+        let responses = self.answer_raw_request(raw, transport).await;
+        if let Some(recorder) = &self.recorder {
+            recorder.record(raw, &responses).await;
         }
+        responses
+    }
 
-        let obj = request.as_object_mut().ok_or_else(|| {
-            BamlRtError::InvalidArgument("A2A request must be a JSON object".to_string())
-        })?;
-        let (method_base, had_stream_suffix) = strip_stream_suffix(&method);
-        let params_value = obj.remove("params").unwrap_or(Value::Null);
-        let mut params = match params_value {
-            Value::Object(map) => map,
-            other => {
-                let mut map = serde_json::Map::new();
-                map.insert("value".to_string(), other);
-                map
+    async fn answer_raw_request(&self, raw: &[u8], transport: &TransportMetadata) -> Vec<Value> {
+        match serde_json::from_slice(raw) {
+            Ok(Value::Array(requests)) => {
+                let batch = a2a::handle_batch(requests, self.batch_execution, |request| {
+                    self.handle_stdio_request(request, transport)
+                })
+                .await;
+                batch.into_iter().collect()
             }
-        };
+            Ok(request_value) => self.handle_stdio_request(request_value, transport).await,
+            Err(err) => vec![a2a::error_response(
+                None,
+                -32700,
+                "JSON parse error",
+                Some(Value::String(err.to_string())),
+            )],
+        }
+    }
 
-        let agent_name = if let Some(agent_value) = params.remove("agent") {
-            agent_value.as_str().map(|s| s.to_string())
-        } else {
-            None
-        };
+    /// Responses to one JSON-RPC request read from stdin or NATS; none for a
+    /// notification
+    async fn handle_stdio_request(
+        &self,
+        request_value: Value,
+        transport: &TransportMetadata,
+    ) -> Vec<Value> {
+        if !a2a::is_notification(&request_value) {
+            return self.serve_stdio_request(request_value, transport).await;
+        }
+        let method = request_value["method"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string();
+        let responses = self.serve_stdio_request(request_value, transport).await;
+        a2a::discard_notification_responses(&method, responses)
+    }
 
-        let (agent_name, method_name) = if let Some(agent_name) = agent_name {
-            (agent_name, method_base)
-        } else if let Some((agent_name, method_name)) =
-            split_agent_method(&method_base, &self.agents)
-        {
-            (agent_name, method_name)
-        } else if self.agents.len() == 1 {
-            let agent_name = self.agents.keys().next().cloned().unwrap_or_default();
-            (agent_name, method_base)
-        } else {
-            return Err(BamlRtError::InvalidArgument(
-                "A2A request missing agent (set params.agent or prefix method with agent name)"
-                    .to_string(),
-            ));
+    async fn serve_stdio_request(
+        &self,
+        request_value: Value,
+        transport: &TransportMetadata,
+    ) -> Vec<Value> {
+        let request_id = a2a::extract_jsonrpc_id(&request_value);
+        let debug_method = match request_value.get("method").and_then(Value::as_str) {
+            Some(EXPORT_BUNDLE_METHOD) => Some(EXPORT_BUNDLE_METHOD),
+            Some(EXPORT_TRACE_METHOD) => Some(EXPORT_TRACE_METHOD),
+            _ => None,
         };
-
-        if had_stream_suffix {
-            params.insert("stream".to_string(), Value::Bool(true));
+        if let Some(method) = debug_method {
+            let response = match self
+                .handle_debug_request(method, request_value, transport)
+                .await
+            {
+                Ok(result) => a2a::success_response(request_id, result),
+                Err(err) => map_a2a_error(request_id, err),
+            };
+            return vec![response];
         }
 
-        obj.insert("method".to_string(), Value::String(method_name));
-        obj.insert("params".to_string(), Value::Object(params));
+        let routed = match self.routing.resolve(request_value) {
+            Ok(routed) => routed,
+            Err(err) => return vec![map_a2a_error(request_id, err)],
+        };
 
-        Ok((agent_name, request.clone()))
-    }
-}
+        let Some(agent) = self.agents.get(&routed.agent) else {
+            return vec![a2a::error_response(
+                request_id,
+                -32601,
+                "Agent not found",
+                Some(Value::String(routed.agent)),
+            )];
+        };
 
-fn strip_stream_suffix(method: &str) -> (String, bool) {
-    for suffix in ["/stream", ".stream", ":stream"] {
-        if let Some(stripped) = method.strip_suffix(suffix) {
-            return (stripped.to_string(), true);
-        }
+        agent
+            .handle_routed(routed, transport)
+            .await
+            .unwrap_or_else(|err| vec![map_a2a_error(request_id, err)])
     }
-    (method.to_string(), false)
 }
 
-fn split_agent_method(
-    method: &str,
-    agents: &HashMap<String, AgentPackage>,
-) -> Option<(String, String)> {
-    for sep in ["::", "/", "."] {
-        if let Some((prefix, suffix)) = method.split_once(sep)
-            && agents.contains_key(prefix)
-        {
-            return Some((prefix.to_string(), suffix.to_string()));
+/// Print the results of a sent message, each streamed chunk as one JSON line
+///
+/// Stops at the first JSON-RPC error, printing it to stderr, and returns
+/// whether there was none.
+fn print_message_responses(responses: &[Value], stream: bool) -> anyhow::Result<bool> {
+    let mut stdout = std::io::stdout().lock();
+    for response in responses {
+        if let Some(error) = response.get("error") {
+            eprintln!("Error: {}", serde_json::to_string_pretty(error)?);
+            return Ok(false);
+        }
+        let Some(result) = response.get("result") else {
+            continue;
+        };
+        if stream {
+            let chunk = result.get("chunk").unwrap_or(result);
+            writeln!(stdout, "{}", serde_json::to_string(chunk)?)?;
+            stdout.flush()?;
+        } else {
+            writeln!(stdout, "{}", serde_json::to_string_pretty(result)?)?;
         }
     }
-    None
-}
-
-fn is_a2a_method(method: &str) -> bool {
-    method.starts_with("message/") || method.starts_with("tasks/") || method.starts_with("agent/")
+    Ok(true)
 }
 
 fn map_a2a_error(id: Option<JSONRPCId>, err: BamlRtError) -> Value {
@@ -428,6 +746,45 @@ fn map_a2a_error(id: Option<JSONRPCId>, err: BamlRtError) -> Value {
         BamlRtError::QuickJs(message) => {
             a2a::error_response(id, -32000, "QuickJS error", Some(Value::String(message)))
         }
+        BamlRtError::JsException(exception) => a2a::error_response(
+            id,
+            -32000,
+            "JS exception",
+            serde_json::to_value(&exception).ok(),
+        ),
+        BamlRtError::OutputRejected { guard, reason } => a2a::error_response(
+            id,
+            -32000,
+            "Output rejected",
+            Some(serde_json::json!({ "guard": guard, "details": reason })),
+        ),
+        BamlRtError::DeadlineExceeded { operation } => a2a::error_response(
+            id,
+            -32000,
+            "Deadline exceeded",
+            Some(Value::String(operation)),
+        ),
+        BamlRtError::Canceled { operation } => {
+            a2a::error_response(id, -32000, "Canceled", Some(Value::String(operation)))
+        }
+        BamlRtError::RateLimited { tenant } => a2a::error_response(
+            id,
+            -32000,
+            "Rate limit exceeded",
+            Some(Value::String(tenant)),
+        ),
+        BamlRtError::Unauthenticated(message) => a2a::error_response(
+            id,
+            UNAUTHENTICATED_CODE,
+            "Unauthenticated",
+            Some(Value::String(message)),
+        ),
+        BamlRtError::Forbidden { principal, action } => a2a::error_response(
+            id,
+            FORBIDDEN_CODE,
+            "Forbidden",
+            Some(serde_json::json!({ "principal": principal, "action": action })),
+        ),
         other => a2a::error_response(
             id,
             -32603,
@@ -448,22 +805,86 @@ async fn main() -> anyhow::Result<()> {
     let args: Vec<String> = std::env::args().collect();
     if args.len() < 2 {
         eprintln!(
-            "Usage: {} <agent-package.tar.gz> [agent-package2.tar.gz ...] [--invoke <agent> <function> <json-args>] [--a2a-stdio]",
+            "Usage: {} <agent-package.tar.gz|.zip|oci://<reference>> [agent-package2 ...] [--invoke <agent> <function> <json-args>] [--send-message <text> [--stream] [--agent <agent>]] [--a2a-stdio] [--a2a-file <requests.jsonl>] [--record <session.jsonl>] [--nats <url> [--nats-subject <subject>] [--nats-queue-group <group>]] [--concurrent-batches] [--shared-runtime] [--route <caller>=<callee>[,<callee>...]] [--ignore-version] [--kv-dir <dir>] [--workdir <dir>] [--dry-run] [--admin-tokens <tokens.json>] [--export-diagnostics <bundle.tar.gz>]",
             args[0]
         );
         eprintln!();
         eprintln!("Examples:");
-        eprintln!("  {} agent1.tar.gz agent2.tar.gz", args[0]);
+        eprintln!("  {} agent1.tar.gz agent2.zip", args[0]);
+        eprintln!("  {} oci://ghcr.io/acme/agent1:1.0.0 --a2a-stdio", args[0]);
         eprintln!(
             "  {} agent1.tar.gz --invoke agent1 SimpleGreeting '{{\"name\":\"World\"}}'",
             args[0]
         );
+        eprintln!(
+            "  {} agent1.tar.gz --send-message 'Summarize today' --stream",
+            args[0]
+        );
         eprintln!("  {} agent1.tar.gz --a2a-stdio", args[0]);
+        eprintln!(
+            "  {} agent1.tar.gz --a2a-stdio --record session.jsonl",
+            args[0]
+        );
+        eprintln!("  {} agent1.tar.gz --a2a-file session.jsonl", args[0]);
+        eprintln!(
+            "  {} agent1.tar.gz --nats nats://localhost:4222 --nats-subject agents.support",
+            args[0]
+        );
+        eprintln!(
+            "  {} agent1.tar.gz agent2.tar.gz --shared-runtime --a2a-stdio",
+            args[0]
+        );
+        eprintln!(
+            "  {} planner.tar.gz writer.tar.gz --route planner=writer --a2a-stdio",
+            args[0]
+        );
+        eprintln!("  {} agent1.tar.gz --kv-dir state --a2a-stdio", args[0]);
+        eprintln!(
+            "  {} agent1.tar.gz --workdir /var/tmp/agents --a2a-stdio",
+            args[0]
+        );
+        eprintln!(
+            "  {} agent1.tar.gz --dry-run --invoke agent1 SimpleGreeting '{{\"name\":\"World\"}}'",
+            args[0]
+        );
+        eprintln!(
+            "  {} agent1.tar.gz --admin-tokens admin-tokens.json --a2a-stdio",
+            args[0]
+        );
+        eprintln!(
+            "  {} agent1.tar.gz --export-diagnostics diagnostics.tar.gz",
+            args[0]
+        );
         std::process::exit(1);
     }
 
     let mut runner = AgentRunner::new();
     let mut a2a_stdio = false;
+    // Agents are loaded as their arguments are read, so decide up front
+    if args.iter().any(|arg| arg == "--shared-runtime") {
+        runner.use_shared_runtime(QuickJSConfig::default());
+    }
+    runner.ignore_version = args.iter().any(|arg| arg == "--ignore-version");
+    runner.dry_run = args.iter().any(|arg| arg == "--dry-run");
+    if let Some(position) = args.iter().position(|arg| arg == "--kv-dir") {
+        let Some(dir) = args.get(position + 1) else {
+            eprintln!("Error: --kv-dir requires <dir>");
+            std::process::exit(1);
+        };
+        runner.kv_store = Arc::new(FileKvStore::open(dir).await?);
+    }
+    if let Some(position) = args.iter().position(|arg| arg == "--workdir") {
+        let Some(dir) = args.get(position + 1) else {
+            eprintln!("Error: --workdir requires <dir>");
+            std::process::exit(1);
+        };
+        runner.workdir = Some(PathBuf::from(dir));
+    }
+    let mut export_diagnostics: Option<PathBuf> = None;
+    let mut a2a_file: Option<PathBuf> = None;
+    let (mut send_message, mut message_agent, mut stream) = (None, None, false);
+    let mut nats: Option<NatsConfig> = None;
+    let (mut nats_subject, mut nats_queue_group) = (None, None);
 
     // Parse arguments
     let mut i = 1;
@@ -491,10 +912,86 @@ async fn main() -> anyhow::Result<()> {
             return Ok(());
         } else if args[i] == "--a2a-stdio" {
             a2a_stdio = true;
+        } else if args[i] == "--send-message" || args[i] == "--agent" {
+            if i + 1 >= args.len() {
+                eprintln!("Error: {} requires a value", args[i]);
+                std::process::exit(1);
+            }
+            let value = args[i + 1].clone();
+            if args[i] == "--send-message" {
+                send_message = Some(value);
+            } else {
+                message_agent = Some(value);
+            }
+            i += 1;
+        } else if args[i] == "--stream" {
+            stream = true;
+        } else if args[i] == "--nats"
+            || args[i] == "--nats-subject"
+            || args[i] == "--nats-queue-group"
+        {
+            if i + 1 >= args.len() {
+                eprintln!("Error: {} requires a value", args[i]);
+                std::process::exit(1);
+            }
+            let value = args[i + 1].clone();
+            match args[i].as_str() {
+                "--nats" => nats = Some(NatsConfig::new(value)),
+                "--nats-subject" => nats_subject = Some(value),
+                _ => nats_queue_group = Some(value),
+            }
+            i += 1;
+        } else if args[i] == "--concurrent-batches" {
+            runner.batch_execution = BatchExecution::Concurrent;
+        } else if args[i] == "--shared-runtime"
+            || args[i] == "--ignore-version"
+            || args[i] == "--dry-run"
+        {
+            // Handled before agents are loaded
+        } else if args[i] == "--kv-dir" || args[i] == "--workdir" {
+            // Handled before agents are loaded
+            i += 1;
+        } else if args[i] == "--route" {
+            if i + 1 >= args.len() {
+                eprintln!("Error: --route requires <caller>=<callee>[,<callee>...]");
+                std::process::exit(1);
+            }
+            let (caller, callees) = orchestration::parse_route(&args[i + 1])?;
+            runner.local_agents.allow(caller, callees);
+            i += 1;
+        } else if args[i] == "--a2a-file" || args[i] == "--record" {
+            if i + 1 >= args.len() {
+                eprintln!("Error: {} requires <path>", args[i]);
+                std::process::exit(1);
+            }
+            let path = PathBuf::from(&args[i + 1]);
+            if args[i] == "--a2a-file" {
+                a2a_file = Some(path);
+            } else {
+                runner.recorder = Some(SessionRecorder::open(&path).await?);
+            }
+            i += 1;
+        } else if args[i] == "--admin-tokens" {
+            if i + 1 >= args.len() {
+                eprintln!("Error: --admin-tokens requires <tokens.json>");
+                std::process::exit(1);
+            }
+            let path = PathBuf::from(&args[i + 1]);
+            runner.admin = AdminAccess::load(&path)
+                .await
+                .with_context(|| format!("Failed to load admin tokens from {}", path.display()))?;
+            i += 1;
+        } else if args[i] == "--export-diagnostics" {
+            if i + 1 >= args.len() {
+                eprintln!("Error: --export-diagnostics requires <bundle.tar.gz>");
+                std::process::exit(1);
+            }
+            export_diagnostics = Some(PathBuf::from(&args[i + 1]));
+            i += 1;
         } else {
             // Load agent package
             let package_path = Path::new(&args[i]);
-            if !package_path.exists() {
+            if !args[i].starts_with(package::OCI_SCHEME) && !package_path.exists() {
                 eprintln!("Error: Agent package not found: {}", package_path.display());
                 std::process::exit(1);
             }
@@ -524,12 +1021,49 @@ async fn main() -> anyhow::Result<()> {
         std::process::exit(1);
     }
 
+    if let Some(text) = send_message {
+        let responses = runner
+            .send_message(message_agent.as_deref(), &text, stream)
+            .await;
+        if !print_message_responses(&responses, stream)? {
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
     println!("✅ Loaded {} agent(s):", agents.len());
     for agent_name in &agents {
         println!("  - {}", agent_name);
     }
 
+    runner.a2a_stdio = a2a_stdio;
+    if let Some(output) = export_diagnostics {
+        let summary = runner
+            .export_diagnostics(&output, DEFAULT_PROVENANCE_LIMIT)
+            .await
+            .context("Failed to export diagnostics bundle")?;
+        println!("{}", serde_json::to_string_pretty(&summary)?);
+        return Ok(());
+    }
+
+    if let Some(mut config) = nats {
+        config.subject = nats_subject.unwrap_or(config.subject);
+        config.queue_group = nats_queue_group.unwrap_or(config.queue_group);
+        runner.start_schedules();
+        runner.run_a2a_nats(&config).await?;
+        return Ok(());
+    }
+
+    if let Some(path) = a2a_file {
+        runner
+            .run_a2a_file(&path)
+            .await
+            .with_context(|| format!("Failed to serve requests from {}", path.display()))?;
+        return Ok(());
+    }
+
     if a2a_stdio {
+        runner.start_schedules();
         runner.run_a2a_stdio().await?;
         return Ok(());
     }
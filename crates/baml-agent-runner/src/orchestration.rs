@@ -0,0 +1,283 @@
+//! In-process calls between loaded agents
+//!
+//! Agents loaded into the same runner can call each other with
+//! `callAgent("agent:<name>", method, params)` without a network hop. The
+//! request is routed to the named agent as if it had arrived over
+//! `--a2a-stdio`: standard A2A methods reach its A2A handler, any other
+//! method calls one of its BAML or JS functions. A call is only allowed
+//! along a configured route, declared in the caller's manifest
+//! (`"calls": ["summarizer"]`) or with `--route <caller>=<callee>[,...]`.
+//! Targets without the `agent:` prefix are sent over HTTP.
+
+use async_trait::async_trait;
+use baml_rt_a2a::a2a_client::{self, A2aClient};
+use baml_rt_a2a::a2a_types::{JSONRPCId, JSONRPCRequest};
+use baml_rt_a2a::{A2aAgent, RoutingTable};
+use baml_rt_core::auth;
+use baml_rt_core::{BamlRtError, Result};
+use baml_rt_quickjs::{AgentCaller, AgentStream};
+use serde_json::Value;
+use std::collections::{BTreeSet, HashMap};
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::{Arc, RwLock};
+use tokio::sync::mpsc;
+
+/// Target prefix naming an agent loaded in this runner
+pub const LOCAL_TARGET_PREFIX: &str = "agent:";
+
+/// Manifest field listing the agents an agent may call
+pub const MANIFEST_CALLS_FIELD: &str = "calls";
+
+/// Parse a `--route` value, `<caller>=<callee>[,<callee>...]`
+pub fn parse_route(spec: &str) -> Result<(String, Vec<String>)> {
+    let invalid = || {
+        BamlRtError::InvalidArgument(format!(
+            "Invalid route '{}': expected <caller>=<callee>[,<callee>...]",
+            spec
+        ))
+    };
+    let (caller, callees) = spec.split_once('=').ok_or_else(invalid)?;
+    let callees: Vec<String> = callees
+        .split(',')
+        .map(str::trim)
+        .filter(|callee| !callee.is_empty())
+        .map(str::to_string)
+        .collect();
+    if caller.trim().is_empty() || callees.is_empty() {
+        return Err(invalid());
+    }
+    Ok((caller.trim().to_string(), callees))
+}
+
+/// A loaded agent and the BAML functions it exposes
+struct LocalAgent {
+    agent: A2aAgent,
+    functions: Vec<String>,
+}
+
+/// Agents of this runner that can be reached with in-process calls
+#[derive(Default)]
+pub struct LocalAgents {
+    agents: RwLock<HashMap<String, Arc<LocalAgent>>>,
+    routes: RwLock<HashMap<String, BTreeSet<String>>>,
+}
+
+impl LocalAgents {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Make `agent` reachable as `agent:<name>`
+    pub fn register(&self, name: impl Into<String>, agent: A2aAgent, functions: Vec<String>) {
+        self.agents
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(name.into(), Arc::new(LocalAgent { agent, functions }));
+    }
+
+    /// Allow `caller` to call each of `callees`
+    pub fn allow<I, S>(&self, caller: impl Into<String>, callees: I)
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.routes
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .entry(caller.into())
+            .or_default()
+            .extend(callees.into_iter().map(Into::into));
+    }
+
+    /// Routes configured so far, for diagnostics
+    pub fn routes(&self) -> HashMap<String, Vec<String>> {
+        self.routes
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .map(|(caller, callees)| (caller.clone(), callees.iter().cloned().collect()))
+            .collect()
+    }
+
+    fn is_allowed(&self, caller: &str, callee: &str) -> bool {
+        self.routes
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(caller)
+            .is_some_and(|callees| callees.contains(callee))
+    }
+
+    /// The [`AgentCaller`] for JS running in the agent named `caller`
+    pub fn caller_for(self: &Arc<Self>, caller: impl Into<String>) -> LocalAgentCaller {
+        LocalAgentCaller {
+            caller: caller.into(),
+            agents: self.clone(),
+            remote: A2aClient::new(),
+            next_id: AtomicI64::new(1),
+        }
+    }
+}
+
+/// Calls agents of the same runner in process, and other agents over HTTP
+pub struct LocalAgentCaller {
+    caller: String,
+    agents: Arc<LocalAgents>,
+    remote: A2aClient,
+    next_id: AtomicI64,
+}
+
+impl LocalAgentCaller {
+    /// Route a request to a loaded agent and return its JSON-RPC responses
+    /// along with the request id
+    async fn dispatch(
+        &self,
+        target: &str,
+        callee: &str,
+        method: &str,
+        mut params: Value,
+    ) -> Result<(Value, Vec<Value>)> {
+        let refused = |message: String| BamlRtError::AgentCall {
+            target: target.to_string(),
+            message,
+        };
+        if callee == self.caller {
+            return Err(refused("an agent cannot call itself".to_string()));
+        }
+        if !self.agents.is_allowed(&self.caller, callee) {
+            return Err(refused(format!(
+                "no route from '{caller}' to '{callee}'; list it under \"{MANIFEST_CALLS_FIELD}\" \
+                 in the manifest or pass --route {caller}={callee}",
+                caller = self.caller,
+            )));
+        }
+        let local = self
+            .agents
+            .agents
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(callee)
+            .cloned()
+            .ok_or_else(|| refused(format!("agent '{}' is not loaded", callee)))?;
+
+        if let Value::Object(map) = &mut params {
+            map.insert("agent".to_string(), Value::String(callee.to_string()));
+        }
+        let id = JSONRPCId::Integer(self.next_id.fetch_add(1, Ordering::Relaxed));
+        let request = serde_json::to_value(JSONRPCRequest {
+            jsonrpc: "2.0".to_string(),
+            method: method.to_string(),
+            params: Some(params),
+            id: Some(id.clone()),
+        })?;
+        let mut routing = RoutingTable::new();
+        routing.register_agent(callee, local.functions.iter().cloned());
+        let routed = routing.resolve(request)?;
+
+        // The agent's handlers are not Send, so drive them on a blocking thread,
+        // still on behalf of the caller
+        let caller = auth::current_caller();
+        let handle = tokio::runtime::Handle::current();
+        let responses = tokio::task::spawn_blocking(move || {
+            handle.block_on(auth::with_caller(caller, local.agent.handle_routed(routed)))
+        })
+        .await
+        .map_err(|err| BamlRtError::QuickJsWithSource {
+            context: "local agent call join error".to_string(),
+            source: Box::new(err),
+        })??;
+        Ok((serde_json::to_value(id)?, responses))
+    }
+}
+
+#[async_trait]
+impl AgentCaller for LocalAgentCaller {
+    async fn call(&self, target: &str, method: &str, params: Value) -> Result<Value> {
+        let Some(callee) = target.strip_prefix(LOCAL_TARGET_PREFIX) else {
+            return self.remote.call(target, method, params).await;
+        };
+        let (id, responses) = self.dispatch(target, callee, method, params).await?;
+        a2a_client::call_result(target, &id, responses)
+    }
+
+    async fn stream(&self, target: &str, method: &str, params: Value) -> Result<AgentStream> {
+        let Some(callee) = target.strip_prefix(LOCAL_TARGET_PREFIX) else {
+            return self.remote.stream(target, method, params).await;
+        };
+        let (id, responses) = self.dispatch(target, callee, method, params).await?;
+        let results = a2a_client::stream_results(target, &id, responses)?;
+        let (tx, rx) = mpsc::channel(results.len().max(1));
+        for result in results {
+            // Capacity covers every result, so this never waits
+            let _ = tx.send(Ok(result)).await;
+        }
+        Ok(rx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    async fn agent_with_js(caller: LocalAgentCaller, init_js: &str) -> A2aAgent {
+        A2aAgent::builder()
+            .with_agent_caller(Arc::new(caller))
+            .with_init_js(init_js)
+            .build()
+            .await
+            .expect("agent build")
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn agents_call_each_other_along_configured_routes() {
+        let agents = Arc::new(LocalAgents::new());
+        let planner = agent_with_js(
+            agents.caller_for("planner"),
+            r#"
+            globalThis.plan = async function(args) {
+                const draft = await callAgent("agent:writer", "draft", { topic: args.topic });
+                return { plan: `outline for ${draft}` };
+            };
+            "#,
+        )
+        .await;
+        let writer = agent_with_js(
+            agents.caller_for("writer"),
+            r#"
+            globalThis.draft = async function(args) {
+                return `draft about ${args.topic}`;
+            };
+            globalThis.steal = async function() {
+                return await callAgent("agent:planner", "plan", { topic: "x" });
+            };
+            "#,
+        )
+        .await;
+        agents.register("planner", planner, Vec::new());
+        agents.register("writer", writer, Vec::new());
+        agents.allow("cli", ["planner", "writer"]);
+
+        let (caller, callees) = parse_route("planner=writer").unwrap();
+        agents.allow(caller, callees);
+
+        let cli = agents.caller_for("cli");
+        let planned = cli
+            .call("agent:planner", "plan", json!({ "topic": "rust" }))
+            .await
+            .unwrap();
+        assert_eq!(planned, json!({ "plan": "outline for draft about rust" }));
+
+        let refused = cli
+            .call("agent:writer", "steal", json!({}))
+            .await
+            .unwrap_err();
+        assert!(
+            refused
+                .to_string()
+                .contains("no route from 'writer' to 'planner'")
+        );
+
+        assert!(parse_route("planner").is_err());
+        assert!(parse_route("planner=").is_err());
+    }
+}
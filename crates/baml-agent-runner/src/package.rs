@@ -0,0 +1,152 @@
+//! Agent package formats
+//!
+//! A package argument names either a file, a tar.gz or zip archive told
+//! apart by its first bytes, or an OCI artifact with an [`OCI_SCHEME`]
+//! reference, e.g. `oci://ghcr.io/acme/support-agent:1.2.0`. Artifacts are
+//! pulled with the credentials of [`registry_auth`]. An artifact holds one
+//! layer, a package archive, whose digest must match the one its manifest
+//! lists; a reference pinned with `@sha256:...` must also match the digest of
+//! the manifest pulled.
+
+use baml_rt_core::manifest::{PACKAGE_MEDIA_TYPE, ZIP_PACKAGE_MEDIA_TYPE};
+use baml_rt_core::registry::{parse_reference, registry_auth, registry_error};
+use baml_rt_core::{BamlRtError, Result};
+use oci_client::Client;
+use oci_client::client::ClientConfig;
+use sha2::{Digest, Sha256};
+use std::io::Cursor;
+use std::path::Path;
+
+/// Prefix of package arguments that name an OCI artifact
+pub const OCI_SCHEME: &str = "oci://";
+
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+const ZIP_MAGIC: &[u8] = b"PK\x03\x04";
+
+/// Extract the package `package` names into `dir`
+pub async fn extract(package: &Path, dir: &Path) -> Result<()> {
+    let reference = package
+        .to_str()
+        .and_then(|package| package.strip_prefix(OCI_SCHEME));
+    let archive = match reference {
+        Some(reference) => pull(reference).await?,
+        None => std::fs::read(package)?,
+    };
+    unpack(&archive, dir)
+}
+
+/// Unpack a tar.gz or zip archive into `dir`
+fn unpack(archive: &[u8], dir: &Path) -> Result<()> {
+    if archive.starts_with(GZIP_MAGIC) {
+        let mut tar = tar::Archive::new(flate2::read::GzDecoder::new(archive));
+        Ok(tar.unpack(dir)?)
+    } else if archive.starts_with(ZIP_MAGIC) {
+        let mut zip = zip::ZipArchive::new(Cursor::new(archive)).map_err(invalid_zip)?;
+        zip.extract(dir).map_err(invalid_zip)
+    } else {
+        Err(BamlRtError::InvalidArgument(
+            "Package is neither a tar.gz nor a zip archive".to_string(),
+        ))
+    }
+}
+
+fn invalid_zip(err: zip::result::ZipError) -> BamlRtError {
+    BamlRtError::InvalidArgumentWithSource {
+        message: "Invalid zip package".to_string(),
+        source: Box::new(err),
+    }
+}
+
+/// The package archive of the artifact `reference` names
+async fn pull(reference: &str) -> Result<Vec<u8>> {
+    let reference = parse_reference(reference)?;
+    let client = Client::new(ClientConfig::default());
+    let accepted = vec![PACKAGE_MEDIA_TYPE, ZIP_PACKAGE_MEDIA_TYPE];
+    let image = client
+        .pull(&reference, &registry_auth(), accepted)
+        .await
+        .map_err(registry_error)?;
+    tracing::info!(%reference, digest = ?image.digest, "Pulled agent package");
+
+    if let Some(pinned) = reference.digest()
+        && image.digest.as_deref() != Some(pinned)
+    {
+        return Err(BamlRtError::InvalidArgument(format!(
+            "{} resolved to manifest {}",
+            reference,
+            image.digest.as_deref().unwrap_or("without a digest")
+        )));
+    }
+    let listed: Vec<&str> = image
+        .manifest
+        .iter()
+        .flat_map(|manifest| &manifest.layers)
+        .map(|layer| layer.digest.as_str())
+        .collect();
+    let ([layer], [digest]) = (image.layers.as_slice(), listed.as_slice()) else {
+        return Err(BamlRtError::InvalidArgument(format!(
+            "{} must hold exactly one package layer",
+            reference
+        )));
+    };
+    verify_digest(&layer.data[..], digest)?;
+    Ok(layer.data.to_vec())
+}
+
+/// Fail unless `data` has the sha256 `digest`
+fn verify_digest(data: &[u8], digest: &str) -> Result<()> {
+    let actual = format!("sha256:{:x}", Sha256::digest(data));
+    if actual != digest {
+        return Err(BamlRtError::InvalidArgument(format!(
+            "Package layer has digest {}, but its manifest lists {}",
+            actual, digest
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn tar_gz(name: &str, contents: &[u8]) -> Vec<u8> {
+        let gz = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        let mut tar = tar::Builder::new(gz);
+        let mut header = tar::Header::new_gnu();
+        header.set_size(contents.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        tar.append_data(&mut header, name, contents).unwrap();
+        tar.into_inner().unwrap().finish().unwrap()
+    }
+
+    fn zip(name: &str, contents: &[u8]) -> Vec<u8> {
+        let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        zip.start_file(name, zip::write::SimpleFileOptions::default())
+            .unwrap();
+        zip.write_all(contents).unwrap();
+        zip.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn archives_are_unpacked_by_their_format() {
+        for archive in [tar_gz("manifest.json", b"{}"), zip("manifest.json", b"{}")] {
+            let dir = tempfile::tempdir().unwrap();
+            unpack(&archive, dir.path()).unwrap();
+            let manifest = std::fs::read(dir.path().join("manifest.json")).unwrap();
+            assert_eq!(manifest, b"{}");
+        }
+
+        let dir = tempfile::tempdir().unwrap();
+        assert!(unpack(b"manifest.json", dir.path()).is_err());
+        assert!(unpack(&zip("../escape.json", b"{}"), dir.path()).is_err());
+    }
+
+    #[test]
+    fn layers_must_match_their_digest() {
+        let digest = "sha256:2c26b46b68ffc68ff99b453c1d30413413422d706483bfa0f98a5e886266e7ae";
+        assert!(verify_digest(b"foo", digest).is_ok());
+        assert!(verify_digest(b"bar", digest).is_err());
+    }
+}
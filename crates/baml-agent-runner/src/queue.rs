@@ -0,0 +1,148 @@
+//! NATS transport for agent requests
+//!
+//! With `--nats <url>` the runner joins a queue group on a request subject,
+//! so any number of runners behind one NATS server share the requests
+//! published to it. Each message carries a JSON-RPC request or batch, and
+//! every response is published as its own message to the message's reply
+//! subject: a streaming request yields one message per chunk. The final
+//! message of a request carries the [`LAST_HEADER`] header, so a client
+//! subscribed to an inbox knows when to stop reading; a plain NATS request
+//! just receives the first response. Messages without a reply subject are
+//! handled like notifications and answered with nothing. Message headers,
+//! such as `Authorization`, are passed along with the request.
+
+use baml_rt_a2a::TransportMetadata;
+use baml_rt_core::{BamlRtError, Result};
+use futures_util::StreamExt;
+use serde_json::Value;
+use std::future::Future;
+use tracing::{debug, info, warn};
+
+/// Subject requests are read from unless `--nats-subject` is given
+pub const DEFAULT_SUBJECT: &str = "baml.agents.requests";
+
+/// Queue group runners join unless `--nats-queue-group` is given
+pub const DEFAULT_QUEUE_GROUP: &str = "baml-agent-runner";
+
+/// Header set to `true` on the last response to a request
+pub const LAST_HEADER: &str = "A2a-Last";
+
+/// Header holding the 1-based position of a response within its request
+pub const SEQUENCE_HEADER: &str = "A2a-Sequence";
+
+/// Requests a runner works on at once
+const MAX_IN_FLIGHT: usize = 32;
+
+/// Where the runner reads requests from
+#[derive(Debug, Clone)]
+pub struct NatsConfig {
+    pub url: String,
+    pub subject: String,
+    pub queue_group: String,
+}
+
+impl NatsConfig {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            subject: DEFAULT_SUBJECT.to_string(),
+            queue_group: DEFAULT_QUEUE_GROUP.to_string(),
+        }
+    }
+}
+
+fn nats_error(err: impl std::error::Error + Send + Sync + 'static) -> BamlRtError {
+    BamlRtError::Io(std::io::Error::other(err))
+}
+
+/// Headers of the `sequence`th response out of `total`
+fn reply_headers(sequence: usize, total: usize) -> async_nats::HeaderMap {
+    let mut headers = async_nats::HeaderMap::new();
+    headers.insert(SEQUENCE_HEADER, sequence.to_string().as_str());
+    if sequence == total {
+        headers.insert(LAST_HEADER, "true");
+    }
+    headers
+}
+
+/// Transport metadata carrying a message's headers
+fn transport_metadata(headers: Option<&async_nats::HeaderMap>) -> TransportMetadata {
+    headers
+        .into_iter()
+        .flat_map(|headers| headers.iter())
+        .filter_map(|(name, values)| Some((name, values.first()?)))
+        .fold(TransportMetadata::new(), |transport, (name, value)| {
+            transport.with_header(name.as_str(), value.as_str())
+        })
+}
+
+/// Serve requests from `config` with `handle`, which answers the raw bytes
+/// of a request and its headers, until the subscription ends
+pub async fn serve<F, Fut>(config: &NatsConfig, handle: F) -> Result<()>
+where
+    F: Fn(Vec<u8>, TransportMetadata) -> Fut,
+    Fut: Future<Output = Vec<Value>>,
+{
+    let client = async_nats::connect(config.url.as_str())
+        .await
+        .map_err(nats_error)?;
+    let subscriber = client
+        .queue_subscribe(config.subject.clone(), config.queue_group.clone())
+        .await
+        .map_err(nats_error)?;
+    info!(
+        url = config.url,
+        subject = config.subject,
+        queue_group = config.queue_group,
+        "Serving A2A requests from NATS"
+    );
+
+    subscriber
+        .for_each_concurrent(MAX_IN_FLIGHT, |message| {
+            let client = &client;
+            let handle = &handle;
+            async move {
+                let transport = transport_metadata(message.headers.as_ref());
+                let responses = handle(message.payload.to_vec(), transport).await;
+                let Some(reply) = message.reply else {
+                    debug!(
+                        subject = %message.subject,
+                        responses = responses.len(),
+                        "Dropping responses to a message without a reply subject"
+                    );
+                    return;
+                };
+                let total = responses.len();
+                for (index, response) in responses.into_iter().enumerate() {
+                    let payload = serde_json::to_vec(&response).unwrap_or_default();
+                    let headers = reply_headers(index + 1, total);
+                    if let Err(err) = client
+                        .publish_with_headers(reply.clone(), headers, payload.into())
+                        .await
+                    {
+                        warn!(error = %err, reply = %reply, "Failed to publish A2A response");
+                        return;
+                    }
+                }
+            }
+        })
+        .await;
+
+    client.flush().await.map_err(nats_error)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_the_last_response_is_marked_last() {
+        let first = reply_headers(1, 2);
+        assert_eq!(first.get(SEQUENCE_HEADER).map(|v| v.as_str()), Some("1"));
+        assert!(first.get(LAST_HEADER).is_none());
+
+        let last = reply_headers(2, 2);
+        assert_eq!(last.get(LAST_HEADER).map(|v| v.as_str()), Some("true"));
+    }
+}
@@ -0,0 +1,109 @@
+//! Recording and replay of A2A sessions
+//!
+//! With `--record <path>` the runner appends one JSON line per request it
+//! serves over stdio, NATS, or a file: `{ "timestamp_ms", "request",
+//! "responses" }`. A request that was not valid JSON is recorded as a
+//! string. `--a2a-file <path>` serves the requests in a file, one per line,
+//! and accepts a recording as well, so a session captured in production can
+//! be replayed against a local build of the agent.
+
+use baml_rt_core::{BamlRtError, Result};
+use serde_json::{Value, json};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::fs::{File, OpenOptions};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::Mutex;
+use tracing::warn;
+
+/// Appends served requests and their responses to a file
+pub struct SessionRecorder {
+    file: Mutex<File>,
+}
+
+impl SessionRecorder {
+    /// Record to `path`, after any entries it already holds
+    pub async fn open(path: &Path) -> Result<Self> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .await
+            .map_err(BamlRtError::Io)?;
+        Ok(Self {
+            file: Mutex::new(file),
+        })
+    }
+
+    /// Record the raw bytes of a request and the responses it got
+    ///
+    /// Failing to write is logged rather than failing the request.
+    pub async fn record(&self, raw: &[u8], responses: &[Value]) {
+        let request = serde_json::from_slice(raw)
+            .unwrap_or_else(|_| Value::String(String::from_utf8_lossy(raw).into_owned()));
+        let timestamp_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64;
+        let mut line = json!({
+            "timestamp_ms": timestamp_ms,
+            "request": request,
+            "responses": responses,
+        })
+        .to_string();
+        line.push('\n');
+        let mut file = self.file.lock().await;
+        let written = match file.write_all(line.as_bytes()).await {
+            Ok(()) => file.flush().await,
+            Err(e) => Err(e),
+        };
+        if let Err(e) = written {
+            warn!(error = %e, "Failed to record A2A request");
+        }
+    }
+}
+
+/// The request a line of an `--a2a-file` holds: the line itself, or the
+/// request of a recorded entry
+pub fn replayed_request(line: &str) -> Vec<u8> {
+    let Ok(Value::Object(entry)) = serde_json::from_str::<Value>(line) else {
+        return line.as_bytes().to_vec();
+    };
+    match entry.get("request") {
+        Some(Value::String(raw)) if entry.contains_key("responses") => raw.as_bytes().to_vec(),
+        Some(request) if entry.contains_key("responses") => request.to_string().into_bytes(),
+        _ => line.as_bytes().to_vec(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn recorded_entries_replay_as_their_requests() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("session.jsonl");
+        let recorder = SessionRecorder::open(&path).await.unwrap();
+        let request = br#"{"jsonrpc":"2.0","id":1,"method":"tasks.list"}"#;
+        recorder
+            .record(
+                request,
+                &[json!({ "jsonrpc": "2.0", "id": 1, "result": {} })],
+            )
+            .await;
+        recorder.record(b"not json", &[]).await;
+        drop(recorder);
+
+        let recording = std::fs::read_to_string(&path).unwrap();
+        let replayed: Vec<Vec<u8>> = recording.lines().map(replayed_request).collect();
+        assert_eq!(
+            serde_json::from_slice::<Value>(&replayed[0]).unwrap(),
+            serde_json::from_slice::<Value>(request).unwrap()
+        );
+        assert_eq!(replayed[1], b"not json");
+
+        let plain = r#"{"jsonrpc":"2.0","id":2,"method":"tasks.get","params":{"id":"t"}}"#;
+        assert_eq!(replayed_request(plain), plain.as_bytes());
+    }
+}
@@ -0,0 +1,461 @@
+//! Scheduled function calls declared in agent manifests
+//!
+//! A manifest lists triggers under `"schedules"`, each calling one of the
+//! agent's BAML or JS functions on a cron schedule:
+//!
+//! ```json
+//! "schedules": [
+//!   { "name": "digest", "cron": "0 7 * * 1-5", "function": "DailyDigest", "args": {} }
+//! ]
+//! ```
+//!
+//! `cron` takes the five standard fields (minute, hour, day of month, month,
+//! day of week) in UTC, with `*`, ranges, lists and `/` steps, or one of
+//! `@hourly`, `@daily`, `@weekly`, `@monthly` and `@yearly`. Every run is
+//! recorded as a task of the agent, so runs show up in `tasks.list`: the
+//! result becomes an artifact of a completed task, an error fails the task.
+//! Schedules run while the runner serves `--a2a-stdio` or `--nats`.
+
+use baml_rt_a2a::A2aAgent;
+use baml_rt_a2a::a2a_store::{TaskRepository, TaskState, generate_task_id};
+use baml_rt_a2a::a2a_types::{Artifact, Message, MessageRole, Part, ROLE_AGENT, Task, TaskStatus};
+use baml_rt_core::ids::{ContextId, MessageId};
+use baml_rt_core::manifest::ManifestSchedule;
+use baml_rt_core::{BamlRtError, Result, context};
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+
+/// Manifest field listing an agent's scheduled function calls
+pub const MANIFEST_SCHEDULES_FIELD: &str = "schedules";
+
+/// Days searched for the next matching minute before a schedule is
+/// considered never to fire (covers leap days)
+const SEARCH_DAYS: i64 = 366 * 8;
+
+/// A five-field cron expression, evaluated in UTC
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CronSchedule {
+    minutes: Vec<bool>,
+    hours: Vec<bool>,
+    days_of_month: Vec<bool>,
+    months: Vec<bool>,
+    days_of_week: Vec<bool>,
+    /// Whether day of month and day of week were both restricted, in which
+    /// case a day matching either fires
+    either_day: bool,
+}
+
+impl CronSchedule {
+    pub fn parse(expression: &str) -> Result<Self> {
+        let expanded = match expression.trim() {
+            "@yearly" | "@annually" => "0 0 1 1 *",
+            "@monthly" => "0 0 1 * *",
+            "@weekly" => "0 0 * * 0",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@hourly" => "0 * * * *",
+            other => other,
+        };
+        let fields: Vec<&str> = expanded.split_whitespace().collect();
+        let &[minute, hour, day_of_month, month, day_of_week] = fields.as_slice() else {
+            return Err(invalid_cron(expression, "expected 5 fields"));
+        };
+        let field = |spec: &str, min: u32, max: u32| {
+            parse_field(spec, min, max).map_err(|reason| invalid_cron(expression, &reason))
+        };
+        let mut days_of_week = field(day_of_week, 0, 7)?;
+        // 7 is Sunday as well as 0
+        if days_of_week[7] {
+            days_of_week[0] = true;
+        }
+        days_of_week.truncate(7);
+        Ok(Self {
+            minutes: field(minute, 0, 59)?,
+            hours: field(hour, 0, 23)?,
+            days_of_month: field(day_of_month, 1, 31)?,
+            months: field(month, 1, 12)?,
+            days_of_week,
+            either_day: day_of_month != "*" && day_of_week != "*",
+        })
+    }
+
+    /// The first minute strictly after `after_secs` (Unix seconds) the
+    /// schedule fires at, in Unix seconds
+    pub fn next_after(&self, after_secs: u64) -> Option<u64> {
+        let start_minute = after_secs as i64 / 60 + 1;
+        let mut day = start_minute.div_euclid(1440);
+        let mut minute_of_day = start_minute.rem_euclid(1440);
+        let last_day = day + SEARCH_DAYS;
+        while day <= last_day {
+            if self.matches_day(day) {
+                for minute in minute_of_day..1440 {
+                    if self.hours[(minute / 60) as usize] && self.minutes[(minute % 60) as usize] {
+                        return Some(((day * 1440 + minute) * 60) as u64);
+                    }
+                }
+            }
+            day += 1;
+            minute_of_day = 0;
+        }
+        None
+    }
+
+    fn matches_day(&self, days_since_epoch: i64) -> bool {
+        let (_, month, day) = civil_from_days(days_since_epoch);
+        // 1970-01-01 was a Thursday
+        let weekday = (days_since_epoch + 4).rem_euclid(7) as usize;
+        if !self.months[month as usize] {
+            return false;
+        }
+        let by_month_day = self.days_of_month[day as usize];
+        let by_weekday = self.days_of_week[weekday];
+        if self.either_day {
+            by_month_day || by_weekday
+        } else {
+            by_month_day && by_weekday
+        }
+    }
+}
+
+/// Values `spec` selects, indexed by value, for a field ranging from `min`
+/// to `max`
+fn parse_field(spec: &str, min: u32, max: u32) -> std::result::Result<Vec<bool>, String> {
+    let mut selected = vec![false; max as usize + 1];
+    for item in spec.split(',') {
+        let (range, step) = match item.split_once('/') {
+            Some((range, step)) => {
+                let step: u32 = step
+                    .parse()
+                    .map_err(|_| format!("invalid step in '{}'", item))?;
+                if step == 0 {
+                    return Err(format!("zero step in '{}'", item));
+                }
+                (range, step)
+            }
+            None => (item, 1),
+        };
+        let number = |value: &str| {
+            value
+                .parse::<u32>()
+                .ok()
+                .filter(|value| (min..=max).contains(value))
+                .ok_or_else(|| format!("'{}' is not between {} and {}", value, min, max))
+        };
+        let (first, last) = match range {
+            "*" => (min, max),
+            range => match range.split_once('-') {
+                Some((first, last)) => (number(first)?, number(last)?),
+                // `5/15` runs from 5 to the end of the range
+                None if step > 1 => (number(range)?, max),
+                None => (number(range)?, number(range)?),
+            },
+        };
+        if first > last {
+            return Err(format!("empty range '{}'", range));
+        }
+        for value in (first..=last).step_by(step as usize) {
+            selected[value as usize] = true;
+        }
+    }
+    Ok(selected)
+}
+
+fn invalid_cron(expression: &str, reason: &str) -> BamlRtError {
+    BamlRtError::InvalidArgument(format!(
+        "Invalid cron expression '{}': {}",
+        expression, reason
+    ))
+}
+
+/// Year, month (1-12) and day (1-31) of a day counted from 1970-01-01
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * shifted_month + 2) / 5 + 1) as u32;
+    let month = (shifted_month + if shifted_month < 10 { 3 } else { -9 }) as u32;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+/// A function call an agent runs on a schedule
+#[derive(Debug, Clone)]
+pub struct ScheduleTrigger {
+    pub name: String,
+    pub cron: CronSchedule,
+    pub function: String,
+    pub args: Value,
+}
+
+/// The triggers for a manifest's `schedules`
+pub fn parse_triggers(schedules: &[ManifestSchedule]) -> Result<Vec<ScheduleTrigger>> {
+    schedules
+        .iter()
+        .enumerate()
+        .map(|(index, schedule)| {
+            let cron = CronSchedule::parse(&schedule.cron).map_err(|err| {
+                BamlRtError::InvalidArgument(format!(
+                    "manifest.json '{}' entry {}: {}",
+                    MANIFEST_SCHEDULES_FIELD, index, err
+                ))
+            })?;
+            Ok(ScheduleTrigger {
+                name: schedule
+                    .name
+                    .clone()
+                    .unwrap_or_else(|| schedule.function.clone()),
+                cron,
+                function: schedule.function.clone(),
+                args: schedule.args.clone().unwrap_or_else(|| json!({})),
+            })
+        })
+        .collect()
+}
+
+/// Run `triggers` against `agent` until the runner exits
+pub fn spawn_schedules(
+    agent_name: &str,
+    agent: &A2aAgent,
+    triggers: Vec<ScheduleTrigger>,
+) -> Vec<JoinHandle<()>> {
+    triggers
+        .into_iter()
+        .map(|trigger| {
+            info!(
+                agent = agent_name,
+                schedule = trigger.name,
+                function = trigger.function,
+                "Schedule started"
+            );
+            let agent_name = agent_name.to_string();
+            let agent = agent.clone();
+            tokio::spawn(async move {
+                loop {
+                    let now = epoch_secs();
+                    let Some(next) = trigger.cron.next_after(now) else {
+                        warn!(schedule = trigger.name, "Schedule never fires again");
+                        return;
+                    };
+                    tokio::time::sleep(Duration::from_secs(next.saturating_sub(now))).await;
+                    let run = ScheduledRun {
+                        agent: agent.clone(),
+                        trigger: trigger.clone(),
+                        scheduled_at: next,
+                    };
+                    // Agent functions are not Send, so run them on a blocking thread
+                    let handle = tokio::runtime::Handle::current();
+                    let finished =
+                        tokio::task::spawn_blocking(move || handle.block_on(run.execute())).await;
+                    match finished {
+                        Ok(Ok(task_id)) => info!(
+                            agent = agent_name,
+                            schedule = trigger.name,
+                            task_id,
+                            "Scheduled run finished"
+                        ),
+                        Ok(Err(err)) => warn!(
+                            agent = agent_name,
+                            schedule = trigger.name,
+                            error = %err,
+                            "Scheduled run could not be recorded"
+                        ),
+                        Err(err) => warn!(
+                            agent = agent_name,
+                            schedule = trigger.name,
+                            error = %err,
+                            "Scheduled run panicked"
+                        ),
+                    }
+                }
+            })
+        })
+        .collect()
+}
+
+fn epoch_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or(0)
+}
+
+/// One firing of a trigger, recorded as a task
+struct ScheduledRun {
+    agent: A2aAgent,
+    trigger: ScheduleTrigger,
+    scheduled_at: u64,
+}
+
+impl ScheduledRun {
+    /// Call the function and record the run, returning the task id
+    async fn execute(self) -> Result<String> {
+        let context_id = context::generate_context_id();
+        let mut task = Task {
+            id: Some(generate_task_id()),
+            context_id: Some(context_id.clone()),
+            status: Some(status(TaskState::Working, None)),
+            metadata: Some(HashMap::from([(
+                "schedule".to_string(),
+                json!({
+                    "name": self.trigger.name,
+                    "function": self.trigger.function,
+                    "scheduledAt": self.scheduled_at,
+                }),
+            )])),
+            ..Task::default()
+        };
+        let task_id = task
+            .id
+            .clone()
+            .map(|id| id.into_string())
+            .unwrap_or_default();
+        let store = self.agent.task_store();
+        store.upsert(task.clone()).await?;
+
+        let bridge = self.agent.bridge();
+        let outcome = context::with_context_id(context_id.clone(), async {
+            let mut bridge = bridge.lock().await;
+            bridge
+                .invoke_js_function(&self.trigger.function, self.trigger.args.clone())
+                .await
+        })
+        .await;
+        match outcome {
+            Ok(result) => {
+                task.artifacts.push(Artifact {
+                    name: Some(self.trigger.function.clone()),
+                    parts: vec![Part {
+                        data: Some(result),
+                        ..Part::default()
+                    }],
+                    ..Artifact::default()
+                });
+                task.status = Some(status(TaskState::Completed, None));
+            }
+            Err(err) => {
+                let message = error_message(&context_id, &task_id, err.to_string());
+                task.status = Some(status(TaskState::Failed, Some(message)));
+            }
+        }
+        store.upsert(task).await?;
+        Ok(task_id)
+    }
+}
+
+fn status(state: TaskState, message: Option<Message>) -> TaskStatus {
+    TaskStatus {
+        state: Some(state.into()),
+        message,
+        ..TaskStatus::default()
+    }
+}
+
+fn error_message(context_id: &ContextId, task_id: &str, error: String) -> Message {
+    Message {
+        message_id: MessageId::from(format!("{}-error", task_id)),
+        role: MessageRole::String(ROLE_AGENT.to_string()),
+        parts: vec![Part {
+            text: Some(error),
+            ..Part::default()
+        }],
+        context_id: Some(context_id.clone()),
+        task_id: Some(task_id.into()),
+        reference_task_ids: Vec::new(),
+        extensions: Vec::new(),
+        metadata: None,
+        extra: HashMap::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use baml_rt_core::manifest::AgentManifest;
+
+    /// Unix seconds of a UTC date and time
+    fn at(year: i64, month: u32, day: u32, hour: u64, minute: u64) -> u64 {
+        let days = (0..)
+            .find(|days| civil_from_days(*days) == (year, month, day))
+            .expect("date after 1970");
+        days as u64 * 86_400 + hour * 3600 + minute * 60
+    }
+
+    #[test]
+    fn next_run_follows_the_cron_fields() {
+        let weekdays = CronSchedule::parse("30 7 * * 1-5").unwrap();
+        // Friday 2024-03-01 08:00 -> Monday 2024-03-04 07:30
+        assert_eq!(
+            weekdays.next_after(at(2024, 3, 1, 8, 0)),
+            Some(at(2024, 3, 4, 7, 30))
+        );
+
+        let quarter_hours = CronSchedule::parse("*/15 * * * *").unwrap();
+        assert_eq!(
+            quarter_hours.next_after(at(2024, 3, 1, 8, 0)),
+            Some(at(2024, 3, 1, 8, 15))
+        );
+
+        let leap_day = CronSchedule::parse("0 0 29 2 *").unwrap();
+        assert_eq!(
+            leap_day.next_after(at(2024, 3, 1, 0, 0)),
+            Some(at(2028, 2, 29, 0, 0))
+        );
+
+        // Day of month and day of week together match either
+        let first_or_sunday = CronSchedule::parse("0 12 1 * 7").unwrap();
+        assert_eq!(
+            first_or_sunday.next_after(at(2024, 3, 1, 13, 0)),
+            Some(at(2024, 3, 3, 12, 0))
+        );
+
+        assert_eq!(
+            CronSchedule::parse("@daily").unwrap(),
+            CronSchedule::parse("0 0 * * *").unwrap()
+        );
+        assert!(CronSchedule::parse("61 * * * *").is_err());
+        assert!(CronSchedule::parse("* * * *").is_err());
+        assert!(CronSchedule::parse("*/0 * * * *").is_err());
+    }
+
+    #[test]
+    fn manifest_schedules_are_parsed() {
+        let manifest = AgentManifest::from_json(
+            r#"{
+                "name": "reporter",
+                "version": "1.0.0",
+                "schedules": [
+                    { "cron": "@hourly", "function": "Summarize", "args": { "limit": 5 } },
+                    { "name": "nightly", "cron": "0 2 * * *", "function": "Cleanup" }
+                ]
+            }"#,
+        )
+        .unwrap();
+        let triggers = parse_triggers(&manifest.schedules).unwrap();
+        assert_eq!(triggers.len(), 2);
+        assert_eq!(triggers[0].name, "Summarize");
+        assert_eq!(triggers[0].args, json!({ "limit": 5 }));
+        assert_eq!(triggers[1].name, "nightly");
+        assert_eq!(triggers[1].args, json!({}));
+
+        let missing_cron = r#"{
+            "name": "reporter",
+            "version": "1.0.0",
+            "schedules": [{ "function": "Summarize" }]
+        }"#;
+        assert!(AgentManifest::from_json(missing_cron).is_err());
+        let bad_cron = ManifestSchedule {
+            name: None,
+            cron: "61 * * * *".to_string(),
+            function: "Summarize".to_string(),
+            args: None,
+        };
+        assert!(parse_triggers(&[bad_cron]).is_err());
+        assert!(parse_triggers(&[]).unwrap().is_empty());
+    }
+}
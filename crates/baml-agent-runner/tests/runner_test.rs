@@ -15,7 +15,10 @@ use std::path::Path;
 use std::process::Command;
 use tar::Builder;
 
-use test_support::common::{CalculatorTool, agent_fixture, ensure_baml_src_exists, workspace_root};
+use test_support::common::{
+    CalculatorTool, agent_fixture, ensure_baml_src_exists, point_at_mock_llm, require_api_key,
+    workspace_root,
+};
 /// Create a test agent package from a fixture agent
 fn create_test_agent_package(output_path: &Path) -> Result<(), Box<dyn std::error::Error>> {
     // Use the voidship-rites fixture
@@ -38,6 +41,7 @@ fn create_test_agent_package(output_path: &Path) -> Result<(), Box<dyn std::erro
     let fixture_baml_src = agent_dir.join("baml_src");
     if fixture_baml_src.exists() {
         copy_dir_all(&fixture_baml_src, &baml_src)?;
+        point_at_mock_llm(&baml_src)?;
     } else {
         return Err("Fixture agent baml_src not found".into());
     }
@@ -276,10 +280,36 @@ async fn test_e2e_agent_runner_load_package() {
     fs::remove_file(&package_path).ok();
 }
 
+#[tokio::test]
+async fn test_e2e_agent_runner_extracts_packages_to_workdir() {
+    let package_path = std::env::temp_dir().join("e2e-test-agent-workdir.tar.gz");
+    create_test_agent_package(&package_path).expect("Failed to create test agent package");
+
+    // Both copies load within the same second, each into a directory of its own
+    let workdir = tempfile::TempDir::new().unwrap();
+    let output = agent_runner_command()
+        .arg(&package_path)
+        .arg(&package_path)
+        .arg("--workdir")
+        .arg(workdir.path())
+        .output()
+        .expect("Failed to execute binary");
+    assert!(
+        output.status.success(),
+        "Runner failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let left = fs::read_dir(workdir.path()).unwrap().count();
+    assert_eq!(left, 0, "Extracted packages should be removed on exit");
+
+    fs::remove_file(&package_path).ok();
+}
+
 #[tokio::test]
 async fn test_e2e_agent_runner_invoke_function() {
-    // Skip if no API key (we'll get auth errors, but that's okay for structure testing)
-    let _ = dotenvy::dotenv();
+    // The runner inherits the API key, and the package's clients point at
+    // the mock LLM unless the tests run live
+    let _ = require_api_key();
     let has_api_key = std::env::var("OPENROUTER_API_KEY").is_ok();
 
     // Create a test agent package
@@ -328,6 +358,85 @@ async fn test_e2e_agent_runner_invoke_function() {
     fs::remove_file(&package_path).ok();
 }
 
+#[tokio::test]
+async fn test_e2e_agent_runner_send_message_fails_on_rpc_error() {
+    // The test package has no entry point, so nothing handles A2A messages
+    let package_path = std::env::temp_dir().join("e2e-test-agent-send-message.tar.gz");
+    create_test_agent_package(&package_path).expect("Failed to create test agent package");
+
+    let output = agent_runner_command()
+        .arg(package_path.to_str().unwrap())
+        .arg("--send-message")
+        .arg("hello")
+        .arg("--stream")
+        .output()
+        .expect("Failed to execute binary");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        !output.status.success(),
+        "JSON-RPC errors should fail the run"
+    );
+    assert!(
+        stderr.contains("\"code\""),
+        "Error should be printed: {}",
+        stderr
+    );
+
+    fs::remove_file(&package_path).ok();
+}
+
+#[tokio::test]
+async fn test_e2e_agent_runner_exports_diagnostics_bundle() {
+    let package_path = std::env::temp_dir().join("e2e-test-agent-diagnostics.tar.gz");
+    create_test_agent_package(&package_path).expect("Failed to create test agent package");
+
+    let output_dir = tempfile::TempDir::new().unwrap();
+    let bundle_path = output_dir.path().join("diagnostics.tar.gz");
+    let output = agent_runner_command()
+        .arg(package_path.to_str().unwrap())
+        .arg("--export-diagnostics")
+        .arg(&bundle_path)
+        .env("OPENROUTER_API_KEY", "sk-should-not-leak")
+        .output()
+        .expect("Failed to execute binary");
+    assert!(
+        output.status.success(),
+        "Diagnostics export failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let archive = flate2::read::GzDecoder::new(fs::File::open(&bundle_path).unwrap());
+    let mut files = std::collections::HashMap::new();
+    for entry in tar::Archive::new(archive).entries().unwrap() {
+        let mut entry = entry.unwrap();
+        let path = entry.path().unwrap().display().to_string();
+        let mut contents = String::new();
+        std::io::Read::read_to_string(&mut entry, &mut contents).unwrap();
+        let name = path
+            .split_once('/')
+            .map(|(_, name)| name.to_string())
+            .unwrap();
+        files.insert(name, contents);
+    }
+
+    for name in [
+        "logs.jsonl",
+        "spans.jsonl",
+        "provenance.jsonl",
+        "stats.json",
+        "config.json",
+        "agents/test-agent/manifest.json",
+    ] {
+        assert!(files.contains_key(name), "Bundle should contain {}", name);
+    }
+    let stats: serde_json::Value = serde_json::from_str(&files["stats.json"]).unwrap();
+    assert_eq!(stats["agents"][0]["name"], "test-agent");
+    assert!(files["config.json"].contains("OPENROUTER_API_KEY"));
+    assert!(!files["config.json"].contains("sk-should-not-leak"));
+
+    fs::remove_file(&package_path).ok();
+}
+
 fn agent_runner_command() -> Command {
     let mut command = Command::new("cargo");
     command
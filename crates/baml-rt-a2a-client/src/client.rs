@@ -0,0 +1,332 @@
+//! Typed A2A client for one agent.
+
+use crate::a2a_types::{
+    CancelTaskRequest, GetTaskRequest, ListTasksRequest, ListTasksResponse, SendMessageRequest,
+    SendMessageResponse, StreamResponse, SubscribeToTaskRequest, Task,
+};
+use crate::jsonrpc::{Failure, JsonRpcTransport};
+use baml_rt_core::{BamlRtError, Result};
+use futures_util::Stream;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+/// How often, and how patiently, a failed request is sent again
+///
+/// Only transport failures are retried: an unreachable agent, a 5xx or 429
+/// response, a timeout, or a connection closed before the result. Errors the
+/// agent answers with, and 4xx responses, fail at once. An agent that timed
+/// out may still have handled the request, so a retried `message.send` can
+/// reach it twice; give messages stable IDs when that matters.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetryPolicy {
+    /// Attempts per request, including the first
+    pub max_attempts: u32,
+    /// Delay before the first retry; doubled after each failure
+    pub initial_backoff: Duration,
+    /// Upper bound on the retry delay
+    pub max_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(200),
+            max_backoff: Duration::from_secs(5),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Send each request once
+    pub fn none() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Self::default()
+        }
+    }
+
+    pub fn with_max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    pub fn with_backoff(mut self, initial: Duration, max: Duration) -> Self {
+        self.initial_backoff = initial;
+        self.max_backoff = max;
+        self
+    }
+
+    fn backoff_for(&self, attempts: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempts.saturating_sub(1));
+        self.initial_backoff
+            .saturating_mul(factor)
+            .min(self.max_backoff)
+    }
+}
+
+/// Typed client for the A2A methods of one agent
+///
+/// ```rust,no_run
+/// # async fn run(request: baml_rt_a2a_client::a2a_types::SendMessageRequest)
+/// # -> baml_rt_core::Result<()> {
+/// use baml_rt_a2a_client::{AgentClient, RetryPolicy};
+/// use std::time::Duration;
+///
+/// let client = AgentClient::new("http://localhost:8080/a2a")?
+///     .with_retry(RetryPolicy::default())
+///     .with_timeout(Duration::from_secs(30));
+/// let response = client.send_message(request).await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct AgentClient {
+    target: String,
+    transport: JsonRpcTransport,
+    retry: RetryPolicy,
+}
+
+impl AgentClient {
+    /// A client for the agent at `target`, an `http(s)://` URL or a
+    /// `stdio:<program> [args...]` command
+    ///
+    /// Requests are sent once and wait for results indefinitely until a
+    /// retry policy or timeout is set.
+    pub fn new(target: impl Into<String>) -> Result<Self> {
+        let target = target.into();
+        crate::AgentTarget::parse(&target)?;
+        Ok(Self {
+            target,
+            transport: JsonRpcTransport::new(),
+            retry: RetryPolicy::none(),
+        })
+    }
+
+    /// Retry requests that fail in transport according to `retry`
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Fail an attempt, or a stream waiting for its next event, after `timeout`
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.transport = self.transport.with_timeout(timeout);
+        self
+    }
+
+    /// Allow a `stdio:` target, which runs a program on this host
+    pub fn with_stdio(mut self, enabled: bool) -> Self {
+        self.transport = self.transport.with_stdio(enabled);
+        self
+    }
+
+    pub fn target(&self) -> &str {
+        &self.target
+    }
+
+    /// Send a message and wait for the agent's reply or task
+    pub async fn send_message(&self, request: SendMessageRequest) -> Result<SendMessageResponse> {
+        self.call("message.send", &request).await
+    }
+
+    /// Send a message and receive the agent's events as it works on it
+    pub async fn send_message_stream(
+        &self,
+        request: SendMessageRequest,
+    ) -> Result<impl Stream<Item = Result<StreamResponse>> + use<>> {
+        self.stream("message.sendStream", &request).await
+    }
+
+    pub async fn get_task(&self, request: GetTaskRequest) -> Result<Task> {
+        self.call("tasks.get", &request).await
+    }
+
+    pub async fn list_tasks(&self, request: ListTasksRequest) -> Result<ListTasksResponse> {
+        self.call("tasks.list", &request).await
+    }
+
+    pub async fn cancel_task(&self, request: CancelTaskRequest) -> Result<Task> {
+        self.call("tasks.cancel", &request).await
+    }
+
+    /// Receive the events of a running task until it finishes
+    pub async fn subscribe_task(
+        &self,
+        mut request: SubscribeToTaskRequest,
+    ) -> Result<impl Stream<Item = Result<StreamResponse>> + use<>> {
+        request
+            .extra
+            .insert("stream".to_string(), Value::Bool(true));
+        self.stream("tasks.subscribe", &request).await
+    }
+
+    /// Call `method`, retrying transport failures
+    async fn call<T: DeserializeOwned>(&self, method: &str, params: &impl Serialize) -> Result<T> {
+        let params = serde_json::to_value(params)?;
+        let mut attempts = 0;
+        let result = loop {
+            attempts += 1;
+            match self
+                .transport
+                .try_call(&self.target, method, params.clone())
+                .await
+            {
+                Err(failure) if self.should_retry(&failure, attempts) => {
+                    self.back_off(method, attempts, failure).await
+                }
+                result => break result.map_err(Failure::into_error)?,
+            }
+        };
+        parse_result(&self.target, method, result)
+    }
+
+    /// Start streaming `method`, retrying transport failures until the
+    /// agent has accepted the request; later failures end the stream
+    async fn stream<T: DeserializeOwned, P: Serialize>(
+        &self,
+        method: &str,
+        params: &P,
+    ) -> Result<impl Stream<Item = Result<T>> + use<T, P>> {
+        let params = serde_json::to_value(params)?;
+        let mut attempts = 0;
+        let exchange = loop {
+            attempts += 1;
+            match self
+                .transport
+                .open(&self.target, method, params.clone())
+                .await
+            {
+                Err(failure) if self.should_retry(&failure, attempts) => {
+                    self.back_off(method, attempts, failure).await
+                }
+                exchange => break exchange.map_err(Failure::into_error)?,
+            }
+        };
+        let target = self.target.clone();
+        let method = method.to_string();
+        let events = exchange.into_stream();
+        Ok(futures_util::stream::unfold(
+            events,
+            move |mut events: mpsc::Receiver<Result<Value>>| {
+                let (target, method) = (target.clone(), method.clone());
+                async move {
+                    let event = events.recv().await?;
+                    let event = event.and_then(|value| parse_result(&target, &method, value));
+                    Some((event, events))
+                }
+            },
+        ))
+    }
+
+    fn should_retry(&self, failure: &Failure, attempts: u32) -> bool {
+        failure.is_transient() && attempts < self.retry.max_attempts
+    }
+
+    async fn back_off(&self, method: &str, attempts: u32, failure: Failure) {
+        let delay = self.retry.backoff_for(attempts);
+        tracing::debug!(
+            agent = %self.target,
+            method,
+            attempts,
+            delay_ms = delay.as_millis() as u64,
+            error = %failure.into_error(),
+            "Retrying agent request"
+        );
+        tokio::time::sleep(delay).await;
+    }
+}
+
+fn parse_result<T: DeserializeOwned>(target: &str, method: &str, result: Value) -> Result<T> {
+    serde_json::from_value(result).map_err(|e| BamlRtError::AgentCall {
+        target: target.to_string(),
+        message: format!("unexpected {} result: {}", method, e),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::a2a_types::{Message, MessageRole, Part, ROLE_USER};
+    use baml_rt_core::ids::{MessageId, TaskId};
+    use futures_util::StreamExt;
+
+    const FLAKY_AGENT: &str = r#"read request
+if [ ! -f "$0.seen" ]; then touch "$0.seen"; exit 0; fi
+echo '{"id":2,"result":{"message":{"messageId":"m-2","role":"ROLE_AGENT","parts":[{"text":"hi"}]}}}'
+"#;
+
+    const STREAMING_AGENT: &str = r#"read request
+echo '{"id":1,"result":{"stream":true,"chunk":{"statusUpdate":{"taskId":"t-1"}}}}'
+echo '{"id":1,"result":{"stream":true,"final":true,"chunk":{"task":{"id":"t-1"}}}}'
+"#;
+
+    fn agent(dir: &tempfile::TempDir, script: &str) -> String {
+        let path = dir.path().join("agent.sh");
+        std::fs::write(&path, script).unwrap();
+        format!("stdio:sh {}", path.display())
+    }
+
+    fn request() -> SendMessageRequest {
+        SendMessageRequest {
+            message: Message {
+                message_id: MessageId::from("m-1".to_string()),
+                role: MessageRole::String(ROLE_USER.to_string()),
+                parts: vec![Part {
+                    text: Some("hello".to_string()),
+                    ..Default::default()
+                }],
+                context_id: None,
+                task_id: None,
+                reference_task_ids: Vec::new(),
+                extensions: Vec::new(),
+                metadata: None,
+                extra: Default::default(),
+            },
+            configuration: None,
+            metadata: None,
+            tenant: None,
+            extra: Default::default(),
+        }
+    }
+
+    #[tokio::test]
+    async fn transport_failures_are_retried_and_streams_are_typed() {
+        let dir = tempfile::tempdir().unwrap();
+        let target = agent(&dir, FLAKY_AGENT);
+        assert!(
+            AgentClient::new(&target)
+                .unwrap()
+                .with_stdio(true)
+                .send_message(request())
+                .await
+                .is_err()
+        );
+
+        std::fs::remove_file(dir.path().join("agent.sh.seen")).unwrap();
+        let client = AgentClient::new(&target)
+            .unwrap()
+            .with_stdio(true)
+            .with_retry(RetryPolicy::default().with_backoff(Duration::ZERO, Duration::ZERO));
+        let response = client.send_message(request()).await.unwrap();
+        assert_eq!(
+            response.message.unwrap().parts[0].text.as_deref(),
+            Some("hi")
+        );
+
+        let target = agent(&dir, STREAMING_AGENT);
+        let client = AgentClient::new(&target).unwrap().with_stdio(true);
+        let events: Vec<_> = client
+            .send_message_stream(request())
+            .await
+            .unwrap()
+            .collect()
+            .await;
+        assert_eq!(events.len(), 2);
+        let update = events[0].as_ref().unwrap().status_update.as_ref().unwrap();
+        assert_eq!(update.task_id, Some(TaskId::from("t-1".to_string())));
+        assert!(events[1].as_ref().unwrap().task.is_some());
+    }
+}
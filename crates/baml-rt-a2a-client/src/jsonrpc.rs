@@ -0,0 +1,595 @@
+//! JSON-RPC transport to other agents.
+//!
+//! Targets starting with `http://` or `https://` are posted to over HTTP;
+//! responses may be a JSON body, a JSON array, newline-delimited JSON, or
+//! server-sent events. With stdio enabled, `stdio:<program> [args...]`
+//! spawns the program (for example `baml-agent-runner agent.tar.gz
+//! --a2a-stdio`) and exchanges one JSON-RPC message per line.
+
+use crate::a2a_types::{JSONRPCId, JSONRPCRequest};
+use baml_rt_core::{BamlRtError, Result};
+use serde_json::Value;
+use std::process::Stdio;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::process::Command;
+use tokio::sync::mpsc;
+
+const JSONRPC_VERSION: &str = "2.0";
+const STDIO_PREFIX: &str = "stdio:";
+
+/// Where an agent call is sent
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AgentTarget {
+    /// JSON-RPC over HTTP POST
+    Http(String),
+    /// JSON-RPC lines over a subprocess's stdin and stdout
+    Stdio { program: String, args: Vec<String> },
+}
+
+impl AgentTarget {
+    /// Parse an `http(s)://` URL or a `stdio:<program> [args...]` command
+    ///
+    /// Stdio arguments are split on whitespace; quoting is not supported.
+    pub fn parse(target: &str) -> Result<Self> {
+        if target.starts_with("http://") || target.starts_with("https://") {
+            return Ok(AgentTarget::Http(target.to_string()));
+        }
+        if let Some(command) = target.strip_prefix(STDIO_PREFIX) {
+            let mut words = command.split_whitespace().map(str::to_string);
+            if let Some(program) = words.next() {
+                return Ok(AgentTarget::Stdio {
+                    program,
+                    args: words.collect(),
+                });
+            }
+        }
+        Err(BamlRtError::InvalidArgument(format!(
+            "Unsupported agent target '{}': expected an http(s) URL or stdio:<command>",
+            target
+        )))
+    }
+}
+
+/// A response to a call, or one result of a streaming call
+enum Reply {
+    Result(Value),
+    Chunk { value: Value, is_final: bool },
+}
+
+/// Why a request failed
+#[derive(Debug)]
+pub(crate) enum Failure {
+    /// The agent could not be reached or stopped answering; trying again
+    /// may succeed
+    Transport(BamlRtError),
+    /// The request was refused, by the agent or before it was sent
+    Rejected(BamlRtError),
+}
+
+impl Failure {
+    pub(crate) fn is_transient(&self) -> bool {
+        matches!(self, Failure::Transport(_))
+    }
+
+    pub(crate) fn into_error(self) -> BamlRtError {
+        match self {
+            Failure::Transport(error) | Failure::Rejected(error) => error,
+        }
+    }
+}
+
+impl From<BamlRtError> for Failure {
+    fn from(error: BamlRtError) -> Self {
+        Failure::Rejected(error)
+    }
+}
+
+impl From<serde_json::Error> for Failure {
+    fn from(error: serde_json::Error) -> Self {
+        Failure::Rejected(error.into())
+    }
+}
+
+/// JSON-RPC messages received for a request
+type Messages = mpsc::Receiver<Result<Value>>;
+
+/// Results of one request as they arrive
+pub(crate) struct Exchange {
+    target: String,
+    id: Value,
+    timeout: Option<Duration>,
+    messages: Messages,
+}
+
+impl Exchange {
+    /// The next reply to the request, skipping messages for others
+    async fn next(&mut self) -> std::result::Result<Option<Reply>, Failure> {
+        loop {
+            let Some(message) = next_message(&self.target, self.timeout, &mut self.messages)
+                .await
+                .map_err(Failure::Transport)?
+            else {
+                return Ok(None);
+            };
+            if let Some(reply) = reply(&self.target, &self.id, message)? {
+                return Ok(Some(reply));
+            }
+        }
+    }
+
+    /// The result of the request; streamed results are collected into an
+    /// array
+    pub(crate) async fn result(mut self) -> std::result::Result<Value, Failure> {
+        let mut chunks = Vec::new();
+        while let Some(reply) = self.next().await? {
+            match reply {
+                Reply::Result(result) => return Ok(result),
+                Reply::Chunk { value, is_final } => {
+                    chunks.push(value);
+                    if is_final {
+                        break;
+                    }
+                }
+            }
+        }
+        collected_chunks(&self.target, chunks).map_err(Failure::Transport)
+    }
+
+    /// Forward each result to a channel until the last one
+    pub(crate) fn into_stream(mut self) -> mpsc::Receiver<Result<Value>> {
+        let (tx, rx) = mpsc::channel(32);
+        tokio::spawn(async move {
+            loop {
+                let (value, done) = match self.next().await {
+                    Ok(None) => break,
+                    Ok(Some(Reply::Result(result))) => (result, true),
+                    Ok(Some(Reply::Chunk { value, is_final })) => (value, is_final),
+                    Err(failure) => {
+                        let _ = tx.send(Err(failure.into_error())).await;
+                        break;
+                    }
+                };
+                if tx.send(Ok(value)).await.is_err() || done {
+                    break;
+                }
+            }
+        });
+        rx
+    }
+}
+
+/// Sends JSON-RPC requests to agents
+pub struct JsonRpcTransport {
+    http: reqwest::Client,
+    allow_stdio: bool,
+    timeout: Option<Duration>,
+    next_id: AtomicI64,
+}
+
+impl Default for JsonRpcTransport {
+    fn default() -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            allow_stdio: false,
+            timeout: None,
+            next_id: AtomicI64::new(1),
+        }
+    }
+}
+
+impl JsonRpcTransport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Allow `stdio:` targets, which run programs on this host
+    pub fn with_stdio(mut self, enabled: bool) -> Self {
+        self.allow_stdio = enabled;
+        self
+    }
+
+    /// Fail a call, or a stream waiting for its next result, after `timeout`
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Send a request and wait for its result
+    ///
+    /// Results of a streaming method are collected into an array.
+    pub async fn call(&self, target: &str, method: &str, params: Value) -> Result<Value> {
+        self.try_call(target, method, params)
+            .await
+            .map_err(Failure::into_error)
+    }
+
+    /// Send a request and receive each of its results; a method that does
+    /// not stream yields its single result
+    pub async fn stream(
+        &self,
+        target: &str,
+        method: &str,
+        params: Value,
+    ) -> Result<mpsc::Receiver<Result<Value>>> {
+        self.open(target, method, params)
+            .await
+            .map(Exchange::into_stream)
+            .map_err(Failure::into_error)
+    }
+
+    pub(crate) async fn try_call(
+        &self,
+        target: &str,
+        method: &str,
+        params: Value,
+    ) -> std::result::Result<Value, Failure> {
+        self.open(target, method, params).await?.result().await
+    }
+
+    /// Send a request, returning every JSON-RPC message the agent sends back
+    pub(crate) async fn open(
+        &self,
+        target: &str,
+        method: &str,
+        params: Value,
+    ) -> std::result::Result<Exchange, Failure> {
+        let id = JSONRPCId::Integer(self.next_id.fetch_add(1, Ordering::Relaxed));
+        let request = serde_json::to_value(JSONRPCRequest {
+            jsonrpc: JSONRPC_VERSION.to_string(),
+            method: method.to_string(),
+            params: Some(params),
+            id: Some(id.clone()),
+        })?;
+        let messages = match AgentTarget::parse(target)? {
+            AgentTarget::Http(url) => self.send_http(target, &url, &request).await?,
+            AgentTarget::Stdio { .. } if !self.allow_stdio => {
+                return Err(Failure::Rejected(BamlRtError::InvalidArgument(format!(
+                    "Agent target '{}' uses stdio, which is not enabled",
+                    target
+                ))));
+            }
+            AgentTarget::Stdio { program, args } => send_stdio(target, &program, &args, &request)
+                .await
+                .map_err(Failure::Transport)?,
+        };
+        Ok(Exchange {
+            target: target.to_string(),
+            id: serde_json::to_value(id)?,
+            timeout: self.timeout,
+            messages,
+        })
+    }
+
+    async fn send_http(
+        &self,
+        target: &str,
+        url: &str,
+        request: &Value,
+    ) -> std::result::Result<Messages, Failure> {
+        let mut response = self
+            .http
+            .post(url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(
+                reqwest::header::ACCEPT,
+                "application/json, text/event-stream",
+            )
+            .body(serde_json::to_vec(request)?)
+            .send()
+            .await
+            .and_then(reqwest::Response::error_for_status)
+            .map_err(|e| {
+                // Client errors other than throttling will fail the same way again
+                let refused = e.status().is_some_and(|status| {
+                    status.is_client_error()
+                        && status != reqwest::StatusCode::TOO_MANY_REQUESTS
+                        && status != reqwest::StatusCode::REQUEST_TIMEOUT
+                });
+                let error = call_error(target, e);
+                if refused {
+                    Failure::Rejected(error)
+                } else {
+                    Failure::Transport(error)
+                }
+            })?;
+        let event_stream = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|value| value.starts_with("text/event-stream"));
+
+        let (tx, rx) = mpsc::channel(32);
+        let target = target.to_string();
+        tokio::spawn(async move {
+            let mut buffer = Vec::new();
+            loop {
+                match response.chunk().await {
+                    Ok(Some(chunk)) => buffer.extend_from_slice(&chunk),
+                    Ok(None) => break,
+                    Err(e) => {
+                        let _ = tx.send(Err(call_error(&target, e))).await;
+                        return;
+                    }
+                }
+                if !event_stream {
+                    continue;
+                }
+                while let Some(end) = buffer.iter().position(|byte| *byte == b'\n') {
+                    let line: Vec<u8> = buffer.drain(..=end).collect();
+                    if let Some(message) = event_data(&line)
+                        && tx.send(message).await.is_err()
+                    {
+                        return;
+                    }
+                }
+            }
+            let messages = if event_stream {
+                event_data(&buffer).into_iter().collect()
+            } else {
+                body_messages(&buffer)
+            };
+            for message in messages {
+                if tx.send(message).await.is_err() {
+                    return;
+                }
+            }
+        });
+        Ok(rx)
+    }
+}
+
+async fn send_stdio(
+    target: &str,
+    program: &str,
+    args: &[String],
+    request: &Value,
+) -> Result<Messages> {
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| call_error(target, e))?;
+    let (Some(mut stdin), Some(stdout)) = (child.stdin.take(), child.stdout.take()) else {
+        return Err(call_error(target, "agent process has no stdio pipes"));
+    };
+
+    let mut line = serde_json::to_vec(request)?;
+    line.push(b'\n');
+    stdin
+        .write_all(&line)
+        .await
+        .map_err(|e| call_error(target, e))?;
+    // Closing stdin lets a `--a2a-stdio` runner exit once it has replied
+    drop(stdin);
+
+    let (tx, rx) = mpsc::channel(32);
+    let target = target.to_string();
+    tokio::spawn(async move {
+        let mut lines = BufReader::new(stdout).lines();
+        loop {
+            let line = match lines.next_line().await {
+                Ok(Some(line)) => line,
+                Ok(None) => break,
+                Err(e) => {
+                    let _ = tx.send(Err(call_error(&target, e))).await;
+                    break;
+                }
+            };
+            if line.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str::<Value>(&line) {
+                Ok(message) => {
+                    if tx.send(Ok(message)).await.is_err() {
+                        break;
+                    }
+                }
+                Err(_) => {
+                    tracing::debug!(agent = %target, line = %line, "Ignoring non-JSON agent output")
+                }
+            }
+        }
+        let _ = child.wait().await;
+    });
+    Ok(rx)
+}
+
+pub(crate) fn call_error(target: &str, error: impl std::fmt::Display) -> BamlRtError {
+    BamlRtError::AgentCall {
+        target: target.to_string(),
+        message: error.to_string(),
+    }
+}
+
+/// The JSON payload of a server-sent event `data:` line
+fn event_data(line: &[u8]) -> Option<Result<Value>> {
+    let line = std::str::from_utf8(line).ok()?.trim();
+    let data = line.strip_prefix("data:")?.trim();
+    if data.is_empty() {
+        return None;
+    }
+    Some(serde_json::from_str(data).map_err(BamlRtError::Json))
+}
+
+/// Messages in a plain response body: one JSON value, an array of them, or
+/// one per line
+fn body_messages(body: &[u8]) -> Vec<Result<Value>> {
+    match serde_json::from_slice(body) {
+        Ok(Value::Array(messages)) => messages.into_iter().map(Ok).collect(),
+        Ok(message) => vec![Ok(message)],
+        Err(_) => String::from_utf8_lossy(body)
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| serde_json::from_str(line).map_err(BamlRtError::Json))
+            .collect(),
+    }
+}
+
+/// Wait for the next message, giving up after `timeout`
+async fn next_message(
+    target: &str,
+    timeout: Option<Duration>,
+    messages: &mut Messages,
+) -> Result<Option<Value>> {
+    let next = match timeout {
+        Some(timeout) => tokio::time::timeout(timeout, messages.recv())
+            .await
+            .map_err(|_| call_error(target, format!("no response within {:?}", timeout)))?,
+        None => messages.recv().await,
+    };
+    next.transpose()
+}
+
+/// Interpret a message, or `None` when it answers a different request
+fn reply(target: &str, id: &Value, message: Value) -> Result<Option<Reply>> {
+    let message_id = message.get("id").unwrap_or(&Value::Null);
+    if let Some(error) = message.get("error") {
+        if message_id != id && !message_id.is_null() {
+            return Ok(None);
+        }
+        let text = error
+            .get("message")
+            .and_then(Value::as_str)
+            .unwrap_or("unknown error");
+        let code = error.get("code").and_then(Value::as_i64).unwrap_or(0);
+        let detail = error
+            .get("data")
+            .and_then(|data| data.get("error").unwrap_or(data).as_str());
+        let message = match detail {
+            Some(detail) => format!("{} (code {}): {}", text, code, detail),
+            None => format!("{} (code {})", text, code),
+        };
+        return Err(call_error(target, message));
+    }
+    if message_id != id {
+        return Ok(None);
+    }
+    let Some(result) = message.get("result") else {
+        return Ok(None);
+    };
+    if result.get("stream").and_then(Value::as_bool) == Some(true) {
+        return Ok(Some(Reply::Chunk {
+            value: result.get("chunk").cloned().unwrap_or(Value::Null),
+            is_final: result.get("final").and_then(Value::as_bool) == Some(true),
+        }));
+    }
+    Ok(Some(Reply::Result(result.clone())))
+}
+
+/// Streamed results as the value of a call
+fn collected_chunks(target: &str, chunks: Vec<Value>) -> Result<Value> {
+    if chunks.is_empty() {
+        return Err(call_error(
+            target,
+            "agent closed the connection without responding",
+        ));
+    }
+    Ok(Value::Array(chunks))
+}
+
+/// The result of request `id` among already received JSON-RPC `responses`
+///
+/// Results of a streaming method are collected into an array, as
+/// [`JsonRpcTransport::call`] does.
+pub fn call_result(target: &str, id: &Value, responses: Vec<Value>) -> Result<Value> {
+    let mut chunks = Vec::new();
+    for message in responses {
+        match reply(target, id, message)? {
+            None => {}
+            Some(Reply::Result(result)) => return Ok(result),
+            Some(Reply::Chunk { value, is_final }) => {
+                chunks.push(value);
+                if is_final {
+                    break;
+                }
+            }
+        }
+    }
+    collected_chunks(target, chunks)
+}
+
+/// Each streamed result of request `id` among already received JSON-RPC
+/// `responses`; a method that does not stream yields its single result
+pub fn stream_results(target: &str, id: &Value, responses: Vec<Value>) -> Result<Vec<Value>> {
+    let mut results = Vec::new();
+    for message in responses {
+        match reply(target, id, message)? {
+            None => {}
+            Some(Reply::Result(result)) => return Ok(vec![result]),
+            Some(Reply::Chunk { value, is_final }) => {
+                results.push(value);
+                if is_final {
+                    break;
+                }
+            }
+        }
+    }
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const STREAMING_AGENT: &str = r#"read request
+echo 'agent starting'
+echo '{"jsonrpc":"2.0","id":99,"result":"someone else"}'
+echo '{"jsonrpc":"2.0","id":1,"result":{"stream":true,"index":0,"final":false,"chunk":"a"}}'
+echo '{"jsonrpc":"2.0","id":1,"result":{"stream":true,"index":1,"final":true,"chunk":"b"}}'
+"#;
+
+    #[tokio::test]
+    async fn stdio_target_streams_results_for_the_request() {
+        let dir = tempfile::tempdir().unwrap();
+        let script = dir.path().join("agent.sh");
+        std::fs::write(&script, STREAMING_AGENT).unwrap();
+        let target = format!("stdio:sh {}", script.display());
+
+        let disabled = JsonRpcTransport::new().call(&target, "message.sendStream", json!({}));
+        assert!(matches!(
+            disabled.await,
+            Err(BamlRtError::InvalidArgument(_))
+        ));
+
+        let transport = JsonRpcTransport::new().with_stdio(true);
+        let mut stream = transport
+            .stream(&target, "message.sendStream", json!({}))
+            .await
+            .unwrap();
+        assert_eq!(stream.recv().await.unwrap().unwrap(), json!("a"));
+        assert_eq!(stream.recv().await.unwrap().unwrap(), json!("b"));
+        assert!(stream.recv().await.is_none());
+
+        let transport = JsonRpcTransport::new().with_stdio(true);
+        let collected = transport
+            .call(&target, "message.sendStream", json!({}))
+            .await
+            .unwrap();
+        assert_eq!(collected, json!(["a", "b"]));
+    }
+
+    #[test]
+    fn http_bodies_and_events_yield_messages() {
+        let error = br#"{"jsonrpc":"2.0","id":1,"error":{"code":-32601,"message":"nope"}}"#;
+        let messages = body_messages(error);
+        let id = json!(1);
+        let Err(BamlRtError::AgentCall { message, .. }) =
+            reply("http://agent", &id, messages[0].as_ref().unwrap().clone())
+        else {
+            panic!("expected an agent call error");
+        };
+        assert_eq!(message, "nope (code -32601)");
+
+        assert_eq!(body_messages(b"{\"id\":1}\n{\"id\":2}\n").len(), 2);
+        assert_eq!(
+            event_data(b"data: {\"id\":1}\n").unwrap().unwrap(),
+            json!({ "id": 1 })
+        );
+        assert!(event_data(b"event: message\n").is_none());
+    }
+}
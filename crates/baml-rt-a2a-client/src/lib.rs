@@ -0,0 +1,18 @@
+//! A2A client for Rust services and agents.
+//!
+//! [`AgentClient`] calls the A2A methods of another agent with typed
+//! requests and responses: `message.send`, `message.sendStream` as a
+//! [`Stream`](futures_util::Stream) of events, and the tasks API. Requests
+//! that fail in transport can be retried with a [`RetryPolicy`], and each
+//! attempt can be bounded by a timeout. [`JsonRpcTransport`] underneath
+//! sends raw JSON-RPC requests, for callers that pick methods at runtime.
+//!
+//! This crate does not depend on the QuickJS runtime, so it can be used by
+//! services that only talk to agents.
+
+pub mod a2a_types;
+pub mod client;
+pub mod jsonrpc;
+
+pub use client::{AgentClient, RetryPolicy};
+pub use jsonrpc::{AgentTarget, JsonRpcTransport, call_result, stream_results};
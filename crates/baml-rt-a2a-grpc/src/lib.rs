@@ -0,0 +1,319 @@
+//! gRPC transport for A2A
+//!
+//! [`A2aGrpcService`] maps each A2A method to an RPC of the
+//! `baml_rt.a2a.v1.A2aService` service (`proto/a2a.proto`) and answers it
+//! with the same [`A2aRequestHandler`] that serves JSON-RPC, so agents need
+//! no gRPC-specific code. Params and results travel as JSON strings;
+//! `message.sendStream` and `tasks.subscribe` are server-streaming, one
+//! message per event, each sent as soon as the handler passes it to
+//! [`A2aRequestHandler::handle_a2a_streaming`]. JSON-RPC errors become gRPC
+//! statuses. Request metadata, such as an `authorization` entry, is passed
+//! to the handler as transport metadata.
+//!
+//! ```rust,no_run
+//! # async fn run(agent: baml_rt_a2a::A2aAgent) -> baml_rt_core::Result<()> {
+//! baml_rt_a2a_grpc::serve(agent, "127.0.0.1:50051".parse().unwrap()).await
+//! # }
+//! ```
+//!
+//! Handlers return futures that are not `Send`, while tonic runs RPCs on a
+//! multi-threaded runtime, so the handler lives on a dedicated thread and
+//! RPCs are passed to it over a channel.
+
+use baml_rt_a2a::{
+    A2aMethod, A2aRequestHandler, FORBIDDEN_CODE, TransportMetadata, UNAUTHENTICATED_CODE,
+};
+use baml_rt_core::{BamlRtError, Result};
+use futures_util::Stream;
+use serde_json::{Value, json};
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use tokio::sync::{mpsc, oneshot};
+use tonic::metadata::MetadataMap;
+use tonic::{Code, Request, Response, Status};
+
+/// Types generated from `proto/a2a.proto`
+pub mod proto {
+    tonic::include_proto!("baml_rt.a2a.v1");
+}
+
+pub use proto::a2a_service_client::A2aServiceClient;
+pub use proto::a2a_service_server::A2aServiceServer;
+
+/// A stream of responses to a server-streaming RPC
+pub type ResponseStream =
+    Pin<Box<dyn Stream<Item = std::result::Result<proto::A2aResponse, Status>> + Send>>;
+
+/// A JSON-RPC request for the handler thread
+struct Job {
+    request: Value,
+    transport: TransportMetadata,
+    /// Receives each response as the handler produces it
+    responses: mpsc::UnboundedSender<Value>,
+    /// Receives how handling ended, after the last response
+    outcome: oneshot::Sender<Result<()>>,
+}
+
+/// The responses to a request still being handled, and how handling ended
+struct Reply {
+    responses: mpsc::UnboundedReceiver<Value>,
+    outcome: oneshot::Receiver<Result<()>>,
+}
+
+impl Reply {
+    /// The next response, or `None` once the handler finished successfully
+    async fn next(&mut self) -> Option<std::result::Result<Value, Status>> {
+        if let Some(response) = self.responses.recv().await {
+            return Some(Ok(response));
+        }
+        match (&mut self.outcome).await {
+            Ok(Ok(())) => None,
+            Ok(Err(err)) => Some(Err(Status::internal(err.to_string()))),
+            Err(_) => Some(Err(Status::internal("A2A handler dropped the request"))),
+        }
+    }
+}
+
+/// The A2A gRPC service, backed by an [`A2aRequestHandler`]
+///
+/// Clones share the handler. Its thread stops once every clone is dropped.
+#[derive(Clone)]
+pub struct A2aGrpcService {
+    jobs: mpsc::UnboundedSender<Job>,
+    next_id: Arc<AtomicU64>,
+}
+
+impl A2aGrpcService {
+    /// Start the thread running `handler`
+    pub fn new<H>(handler: H) -> Result<Self>
+    where
+        H: A2aRequestHandler + 'static,
+    {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()?;
+        let (jobs, mut received) = mpsc::unbounded_channel::<Job>();
+        std::thread::Builder::new()
+            .name("a2a-grpc-handler".to_string())
+            .spawn(move || {
+                let handler = Rc::new(handler);
+                let local = tokio::task::LocalSet::new();
+                local.block_on(&runtime, async move {
+                    while let Some(job) = received.recv().await {
+                        let handler = handler.clone();
+                        tokio::task::spawn_local(async move {
+                            // The RPC may have been abandoned by its caller
+                            let outcome = handler
+                                .handle_a2a_streaming(job.request, &job.transport, job.responses)
+                                .await;
+                            let _ = job.outcome.send(outcome);
+                        });
+                    }
+                });
+            })?;
+        Ok(Self {
+            jobs,
+            next_id: Arc::new(AtomicU64::new(1)),
+        })
+    }
+
+    /// The service, ready to add to a tonic server
+    pub fn into_server(self) -> A2aServiceServer<Self> {
+        A2aServiceServer::new(self)
+    }
+
+    /// Start `request` as the JSON-RPC call `method`
+    fn call(
+        &self,
+        method: A2aMethod,
+        request: Request<proto::A2aRequest>,
+    ) -> std::result::Result<Reply, Status> {
+        let transport = transport_metadata(request.metadata());
+        let message = request.into_inner();
+        let params: Value = if message.params_json.is_empty() {
+            json!({})
+        } else {
+            serde_json::from_str(&message.params_json).map_err(|err| {
+                Status::invalid_argument(format!("params_json is not JSON: {}", err))
+            })?
+        };
+        let id = if message.request_id.is_empty() {
+            format!("grpc-{}", self.next_id.fetch_add(1, Ordering::Relaxed))
+        } else {
+            message.request_id
+        };
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": method.as_str(),
+            "params": params,
+        });
+        tracing::debug!(method = method.as_str(), id = %request["id"], "A2A gRPC request");
+
+        let (responses, received) = mpsc::unbounded_channel();
+        let (outcome, finished) = oneshot::channel();
+        self.jobs
+            .send(Job {
+                request,
+                transport,
+                responses,
+                outcome,
+            })
+            .map_err(|_| Status::unavailable("A2A handler has stopped"))?;
+        Ok(Reply {
+            responses: received,
+            outcome: finished,
+        })
+    }
+
+    async fn unary(
+        &self,
+        method: A2aMethod,
+        request: Request<proto::A2aRequest>,
+    ) -> std::result::Result<Response<proto::A2aResponse>, Status> {
+        let response = self
+            .call(method, request)?
+            .next()
+            .await
+            .unwrap_or_else(|| Err(Status::internal("A2A handler returned no response")))?;
+        to_response(response).map(Response::new)
+    }
+
+    async fn streaming(
+        &self,
+        method: A2aMethod,
+        request: Request<proto::A2aRequest>,
+    ) -> std::result::Result<Response<ResponseStream>, Status> {
+        let reply = self.call(method, request)?;
+        let stream: ResponseStream = Box::pin(futures_util::stream::unfold(
+            Some(reply),
+            |reply| async move {
+                let mut reply = reply?;
+                let response = reply.next().await?.and_then(to_response);
+                // Nothing follows an error
+                let reply = response.is_ok().then_some(reply);
+                Some((response, reply))
+            },
+        ));
+        Ok(Response::new(stream))
+    }
+}
+
+/// The ASCII entries of a request's metadata
+fn transport_metadata(metadata: &MetadataMap) -> TransportMetadata {
+    metadata
+        .clone()
+        .into_headers()
+        .iter()
+        .filter_map(|(name, value)| Some((name, value.to_str().ok()?)))
+        .fold(TransportMetadata::new(), |transport, (name, value)| {
+            transport.with_header(name, value)
+        })
+}
+
+/// The result of a JSON-RPC response, or its error as a status
+fn to_response(response: Value) -> std::result::Result<proto::A2aResponse, Status> {
+    if let Some(error) = response.get("error") {
+        return Err(to_status(error));
+    }
+    let result = response.get("result").cloned().unwrap_or(Value::Null);
+    Ok(proto::A2aResponse {
+        result_json: result.to_string(),
+    })
+}
+
+/// The gRPC status for a JSON-RPC error object
+fn to_status(error: &Value) -> Status {
+    let message = error
+        .get("message")
+        .and_then(Value::as_str)
+        .unwrap_or("A2A error");
+    let details = error.pointer("/data/details").and_then(Value::as_str);
+    let code = match (error.get("code").and_then(Value::as_i64), message, details) {
+        (_, _, Some("Task not found")) => Code::NotFound,
+        (Some(-32700 | -32600 | -32602), _, _) => Code::InvalidArgument,
+        (Some(-32601), _, _) => Code::Unimplemented,
+        (Some(-32002), _, _) => Code::FailedPrecondition,
+        (Some(UNAUTHENTICATED_CODE), _, _) => Code::Unauthenticated,
+        (Some(FORBIDDEN_CODE), _, _) => Code::PermissionDenied,
+        (_, "Deadline exceeded", _) => Code::DeadlineExceeded,
+        (_, "Canceled", _) => Code::Cancelled,
+        _ => Code::Internal,
+    };
+    match error.get("data") {
+        Some(data) => Status::new(code, format!("{}: {}", message, data)),
+        None => Status::new(code, message),
+    }
+}
+
+#[tonic::async_trait]
+impl proto::a2a_service_server::A2aService for A2aGrpcService {
+    type SendStreamingMessageStream = ResponseStream;
+    type SubscribeToTaskStream = ResponseStream;
+
+    async fn send_message(
+        &self,
+        request: Request<proto::A2aRequest>,
+    ) -> std::result::Result<Response<proto::A2aResponse>, Status> {
+        self.unary(A2aMethod::MessageSend, request).await
+    }
+
+    async fn send_streaming_message(
+        &self,
+        request: Request<proto::A2aRequest>,
+    ) -> std::result::Result<Response<ResponseStream>, Status> {
+        self.streaming(A2aMethod::MessageSendStream, request).await
+    }
+
+    async fn get_task(
+        &self,
+        request: Request<proto::A2aRequest>,
+    ) -> std::result::Result<Response<proto::A2aResponse>, Status> {
+        self.unary(A2aMethod::TasksGet, request).await
+    }
+
+    async fn list_tasks(
+        &self,
+        request: Request<proto::A2aRequest>,
+    ) -> std::result::Result<Response<proto::A2aResponse>, Status> {
+        self.unary(A2aMethod::TasksList, request).await
+    }
+
+    async fn cancel_task(
+        &self,
+        request: Request<proto::A2aRequest>,
+    ) -> std::result::Result<Response<proto::A2aResponse>, Status> {
+        self.unary(A2aMethod::TasksCancel, request).await
+    }
+
+    async fn subscribe_to_task(
+        &self,
+        request: Request<proto::A2aRequest>,
+    ) -> std::result::Result<Response<ResponseStream>, Status> {
+        self.streaming(A2aMethod::TasksSubscribe, request).await
+    }
+
+    async fn get_artifact(
+        &self,
+        request: Request<proto::A2aRequest>,
+    ) -> std::result::Result<Response<proto::A2aResponse>, Status> {
+        self.unary(A2aMethod::ArtifactsGet, request).await
+    }
+}
+
+/// Serve A2A over gRPC at `addr` with `handler` until the server fails
+pub async fn serve<H>(handler: H, addr: SocketAddr) -> Result<()>
+where
+    H: A2aRequestHandler + 'static,
+{
+    let service = A2aGrpcService::new(handler)?;
+    tracing::info!(%addr, "Serving A2A over gRPC");
+    tonic::transport::Server::builder()
+        .add_service(service.into_server())
+        .serve(addr)
+        .await
+        .map_err(|err| BamlRtError::Io(std::io::Error::other(err)))
+}
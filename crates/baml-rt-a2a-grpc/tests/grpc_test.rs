@@ -0,0 +1,135 @@
+use async_trait::async_trait;
+use baml_rt_a2a::a2a::{error_response, extract_jsonrpc_id};
+use baml_rt_a2a::{A2aRequestHandler, TransportMetadata};
+use baml_rt_a2a_grpc::A2aGrpcService;
+use baml_rt_a2a_grpc::proto::A2aRequest;
+use baml_rt_a2a_grpc::proto::a2a_service_server::A2aService;
+use baml_rt_core::Result;
+use futures_util::StreamExt;
+use serde_json::{Value, json};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{Notify, mpsc};
+use tonic::{Code, Request};
+
+/// Answers `message.send` with its params, streams two events for
+/// `message.sendStream`, and reports every task as missing
+struct EchoHandler;
+
+#[async_trait(?Send)]
+impl A2aRequestHandler for EchoHandler {
+    async fn handle_a2a(&self, request: Value) -> Result<Vec<Value>> {
+        let id = request["id"].clone();
+        let response = |result: Value| json!({ "jsonrpc": "2.0", "id": id, "result": result });
+        Ok(match request["method"].as_str() {
+            Some("message.send") => vec![response(request["params"].clone())],
+            Some("message.sendStream") => vec![response(json!(1)), response(json!(2))],
+            _ => vec![error_response(
+                extract_jsonrpc_id(&request),
+                -32600,
+                "Invalid request",
+                Some(json!({ "details": "Task not found" })),
+            )],
+        })
+    }
+}
+
+/// Streams one event, then another once released
+struct GatedHandler {
+    release: Arc<Notify>,
+}
+
+#[async_trait(?Send)]
+impl A2aRequestHandler for GatedHandler {
+    async fn handle_a2a(&self, _request: Value) -> Result<Vec<Value>> {
+        unreachable!("the gRPC service streams every request")
+    }
+
+    async fn handle_a2a_streaming(
+        &self,
+        request: Value,
+        _transport: &TransportMetadata,
+        responses: mpsc::UnboundedSender<Value>,
+    ) -> Result<()> {
+        let id = request["id"].clone();
+        let response = |result: Value| json!({ "jsonrpc": "2.0", "id": id, "result": result });
+        let _ = responses.send(response(json!(1)));
+        self.release.notified().await;
+        let _ = responses.send(response(json!(2)));
+        Ok(())
+    }
+}
+
+fn request(params: Value) -> Request<A2aRequest> {
+    Request::new(A2aRequest {
+        params_json: params.to_string(),
+        request_id: String::new(),
+    })
+}
+
+#[tokio::test]
+async fn test_rpcs_are_answered_by_the_json_rpc_handler() {
+    let service = A2aGrpcService::new(EchoHandler).expect("service");
+
+    let sent = service
+        .send_message(request(json!({ "message": "hi" })))
+        .await
+        .expect("send message")
+        .into_inner();
+    let result: Value = serde_json::from_str(&sent.result_json).expect("result JSON");
+    assert_eq!(result, json!({ "message": "hi" }));
+
+    let stream = service
+        .send_streaming_message(request(json!({})))
+        .await
+        .expect("send streaming message")
+        .into_inner();
+    let events: Vec<_> = stream
+        .map(|event| event.expect("event").result_json)
+        .collect()
+        .await;
+    assert_eq!(events, ["1", "2"]);
+
+    let missing = service
+        .get_task(request(json!({ "id": "task-1" })))
+        .await
+        .expect_err("missing task");
+    assert_eq!(missing.code(), Code::NotFound);
+
+    let invalid = service
+        .send_message(Request::new(A2aRequest {
+            params_json: "{".to_string(),
+            request_id: String::new(),
+        }))
+        .await
+        .expect_err("invalid params");
+    assert_eq!(invalid.code(), Code::InvalidArgument);
+}
+
+#[tokio::test]
+async fn test_stream_events_are_sent_as_they_are_produced() {
+    let release = Arc::new(Notify::new());
+    let service = A2aGrpcService::new(GatedHandler {
+        release: release.clone(),
+    })
+    .expect("service");
+
+    let mut stream = service
+        .send_streaming_message(request(json!({})))
+        .await
+        .expect("send streaming message")
+        .into_inner();
+    let first = tokio::time::timeout(Duration::from_secs(5), stream.next())
+        .await
+        .expect("first event arrives before the handler finishes")
+        .expect("event")
+        .expect("ok");
+    assert_eq!(first.result_json, "1");
+
+    release.notify_one();
+    let rest: Vec<_> = stream
+        .map(|event| event.expect("event").result_json)
+        .collect()
+        .await;
+    assert_eq!(rest, ["2"]);
+}
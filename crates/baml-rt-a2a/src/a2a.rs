@@ -6,13 +6,20 @@ use crate::a2a_types::{
     JSONRPCError, JSONRPCErrorResponse, JSONRPCId, JSONRPCRequest, JSONRPCSuccessResponse,
     ListTasksRequest, Message, SendMessageRequest,
 };
+use crate::tenancy::TENANT_PARAM;
 use baml_rt_core::context;
-use baml_rt_core::ids::ContextId;
+use baml_rt_core::deadline::Deadline;
+use baml_rt_core::ids::{ContextId, TenantId};
 use baml_rt_core::{BamlRtError, Result};
 use serde_json::{Map, Value, json};
+use std::future::Future;
+use std::time::Duration;
 
 const JSONRPC_VERSION: &str = "2.0";
 
+/// JSON-RPC error code for a request that is not a request object.
+const INVALID_REQUEST: i64 = -32600;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum A2aMethod {
     MessageSend,
@@ -21,6 +28,7 @@ pub enum A2aMethod {
     TasksList,
     TasksCancel,
     TasksSubscribe,
+    ArtifactsGet,
 }
 
 impl A2aMethod {
@@ -32,8 +40,21 @@ impl A2aMethod {
             A2aMethod::TasksList => "tasks.list",
             A2aMethod::TasksCancel => "tasks.cancel",
             A2aMethod::TasksSubscribe => "tasks.subscribe",
+            A2aMethod::ArtifactsGet => "artifacts.get",
         }
     }
+
+    /// Whether repeating the method has no additional side effects.
+    pub fn is_idempotent(&self) -> bool {
+        matches!(
+            self,
+            A2aMethod::TasksGet
+                | A2aMethod::TasksList
+                | A2aMethod::TasksCancel
+                | A2aMethod::TasksSubscribe
+                | A2aMethod::ArtifactsGet
+        )
+    }
 }
 
 impl std::str::FromStr for A2aMethod {
@@ -47,6 +68,7 @@ impl std::str::FromStr for A2aMethod {
             "tasks.list" => Ok(A2aMethod::TasksList),
             "tasks.cancel" => Ok(A2aMethod::TasksCancel),
             "tasks.subscribe" => Ok(A2aMethod::TasksSubscribe),
+            "artifacts.get" => Ok(A2aMethod::ArtifactsGet),
             _ => Err(BamlRtError::InvalidArgument(
                 "Unsupported A2A request method".to_string(),
             )),
@@ -61,10 +83,13 @@ pub struct A2aRequest {
     pub params: Value,
     pub is_stream: bool,
     pub context_id: Option<ContextId>,
+    /// Sent without an `id`; the request is served but not answered.
+    pub is_notification: bool,
 }
 
 impl A2aRequest {
     pub fn from_value(value: Value) -> Result<Self> {
+        let is_notification = is_notification(&value);
         let request: JSONRPCRequest = serde_json::from_value(value).map_err(BamlRtError::Json)?;
         if request.jsonrpc != JSONRPC_VERSION {
             return Err(BamlRtError::InvalidArgument(format!(
@@ -103,7 +128,8 @@ impl A2aRequest {
             A2aMethod::TasksGet
             | A2aMethod::TasksList
             | A2aMethod::TasksCancel
-            | A2aMethod::TasksSubscribe => {
+            | A2aMethod::TasksSubscribe
+            | A2aMethod::ArtifactsGet => {
                 if method == A2aMethod::TasksList {
                     if let Ok(params) =
                         serde_json::from_value::<ListTasksRequest>(params_value.clone())
@@ -131,12 +157,55 @@ impl A2aRequest {
             params: params_value,
             is_stream,
             context_id,
+            is_notification,
         })
     }
 
     pub fn correlation_id(&self) -> Option<String> {
         self.id.as_ref().map(id_to_string)
     }
+
+    /// Whether the request may be executed again after a failure.
+    ///
+    /// Read-only task methods are always safe. Message requests are only safe
+    /// when the caller marks them with `metadata.idempotent = true`.
+    pub fn is_replay_safe(&self) -> bool {
+        self.method.is_idempotent()
+            || self
+                .params
+                .get("metadata")
+                .and_then(|metadata| metadata.get("idempotent"))
+                .and_then(Value::as_bool)
+                .unwrap_or(false)
+    }
+
+    /// The end-to-end deadline the caller set with `metadata.timeout_ms`,
+    /// counted from when the request was parsed.
+    pub fn deadline(&self) -> Option<Deadline> {
+        self.params
+            .get("metadata")
+            .and_then(|metadata| metadata.get("timeout_ms"))
+            .and_then(Value::as_u64)
+            .map(|timeout_ms| Deadline::after(Duration::from_millis(timeout_ms)))
+    }
+
+    /// ID of the message a `message/send` or `message/stream` request delivers
+    pub fn message_id(&self) -> Option<&str> {
+        self.params
+            .get("message")
+            .and_then(|message| message.get("messageId"))
+            .and_then(Value::as_str)
+            .filter(|message_id| !message_id.is_empty())
+    }
+
+    /// The tenant the request is made for, from its `tenant` param
+    pub fn tenant(&self) -> Option<TenantId> {
+        self.params
+            .get(TENANT_PARAM)
+            .and_then(Value::as_str)
+            .filter(|tenant| !tenant.is_empty())
+            .map(TenantId::from)
+    }
 }
 
 #[derive(Debug)]
@@ -145,6 +214,87 @@ pub enum A2aOutcome {
     Stream(Vec<Value>),
 }
 
+/// Whether `value` is a JSON-RPC notification: a request without an `id`
+/// member, which gets no response, not even one with a null `id`.
+pub fn is_notification(value: &Value) -> bool {
+    value.as_object().is_some_and(|request| {
+        request.get("method").is_some_and(Value::is_string) && !request.contains_key("id")
+    })
+}
+
+/// Drop the responses to a notification, logging any errors among them
+/// since the sender will not see them.
+pub fn discard_notification_responses(method: &str, responses: Vec<Value>) -> Vec<Value> {
+    for response in responses {
+        if let Some(error) = response.get("error") {
+            tracing::warn!(method, error = %error, "Notification failed");
+        }
+    }
+    Vec::new()
+}
+
+/// How the members of a JSON-RPC batch are executed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BatchExecution {
+    /// One member after another.
+    #[default]
+    Sequential,
+    /// All members at once; their responses are still returned in order.
+    Concurrent,
+}
+
+/// Serve the members of a JSON-RPC batch with `handle`.
+///
+/// Returns the batch response: the responses of every member, in request
+/// order, with the stream chunks of a member kept together. Members that are
+/// not request objects are answered with an Invalid Request error, and so is
+/// an empty batch, with a single error object rather than an array. A batch
+/// of notifications only is not answered at all.
+pub async fn handle_batch<F, Fut>(
+    requests: Vec<Value>,
+    execution: BatchExecution,
+    handle: F,
+) -> Option<Value>
+where
+    F: Fn(Value) -> Fut,
+    Fut: Future<Output = Vec<Value>>,
+{
+    if requests.is_empty() {
+        return Some(invalid_request("Empty batch"));
+    }
+    let handle = &handle;
+    let member = |request: Value| async move {
+        if request.is_object() {
+            handle(request).await
+        } else {
+            vec![invalid_request("Batch member is not a request object")]
+        }
+    };
+    let responses = match execution {
+        BatchExecution::Sequential => {
+            let mut responses = Vec::with_capacity(requests.len());
+            for request in requests {
+                responses.push(member(request).await);
+            }
+            responses
+        }
+        BatchExecution::Concurrent => {
+            futures_util::future::join_all(requests.into_iter().map(member)).await
+        }
+    };
+    let responses: Vec<Value> = responses.into_iter().flatten().collect();
+    (!responses.is_empty()).then_some(Value::Array(responses))
+}
+
+fn invalid_request(reason: &str) -> Value {
+    error_response(
+        None,
+        INVALID_REQUEST,
+        "Invalid Request",
+        Some(Value::String(reason.to_string())),
+    )
+}
+
 pub fn success_response(id: Option<JSONRPCId>, result: Value) -> Value {
     serde_json::to_value(JSONRPCSuccessResponse {
         jsonrpc: JSONRPC_VERSION.to_string(),
@@ -229,7 +379,7 @@ fn normalize_params(value: Value) -> Value {
     }
 }
 
-fn id_to_string(value: &JSONRPCId) -> String {
+pub(crate) fn id_to_string(value: &JSONRPCId) -> String {
     match value {
         JSONRPCId::String(s) => s.clone(),
         JSONRPCId::Integer(n) => n.to_string(),
@@ -310,13 +460,17 @@ fn stream_from_message_request(params: &SendMessageRequest, params_value: &Value
         || metadata_value_as_bool(params.message.metadata.as_ref(), "stream").unwrap_or(false)
 }
 
+/// The request as passed to the JS handler; notifications have no `id`.
 pub fn request_to_js_value(request: &A2aRequest) -> Value {
-    json!({
+    let mut value = json!({
         "jsonrpc": JSONRPC_VERSION,
-        "id": request.id.as_ref().map(id_to_value).unwrap_or(Value::Null),
         "method": request.method.as_str(),
         "params": request.params,
-    })
+    });
+    if !request.is_notification {
+        value["id"] = request.id.as_ref().map(id_to_value).unwrap_or(Value::Null);
+    }
+    value
 }
 
 fn id_to_value(value: &JSONRPCId) -> Value {
@@ -329,12 +483,14 @@ fn id_to_value(value: &JSONRPCId) -> Value {
 
 #[cfg(test)]
 mod tests {
-    use super::A2aRequest;
+    use super::{A2aRequest, BatchExecution};
+    use crate::a2a_store::{TaskRepository, TaskState, TaskUpdateEvent};
     use crate::a2a_types::{
         JSONRPCId, JSONRPCRequest, Message, MessageRole, Part, ROLE_USER, SendMessageRequest,
     };
-    use crate::{A2aAgent, A2aRequestHandler};
-    use baml_rt_core::BamlRtError;
+    use crate::background::BackgroundConfig;
+    use crate::{A2aAgent, A2aAgentBuilder, A2aRequestHandler};
+    use baml_rt_core::{BamlRtError, Result};
     use opentelemetry::global;
     use opentelemetry::trace::TracerProvider as _;
     use opentelemetry_sdk::testing::trace::InMemorySpanExporterBuilder;
@@ -396,6 +552,10 @@ mod tests {
     }
 
     async fn setup_agent_with_js() -> A2aAgent {
+        agent_builder_with_js().build().await.expect("agent build")
+    }
+
+    fn agent_builder_with_js() -> A2aAgentBuilder {
         let js_code = r#"
             globalThis.handle_a2a_request = async function(request) {
                 const method = request && request.method;
@@ -448,11 +608,7 @@ mod tests {
                 };
             };
         "#;
-        A2aAgent::builder()
-            .with_init_js(js_code)
-            .build()
-            .await
-            .expect("agent build")
+        A2aAgent::builder().with_init_js(js_code)
     }
 
     fn expect_success_result(responses: Vec<Value>) -> Value {
@@ -650,6 +806,367 @@ mod tests {
         assert!(any_final, "stream should include a final chunk");
     }
 
+    #[tokio::test]
+    async fn test_batch_responses_follow_request_order() {
+        let request = |id: &str, method: &str, text: &str| {
+            json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "method": method,
+                "params": { "message": user_message(id, text) }
+            })
+        };
+        let batch = json!([
+            request("first", "message.send", "Ada"),
+            request("second", "message.sendStream", "Grace"),
+            42,
+            request("fourth", "message.send", "Alan"),
+        ]);
+
+        for execution in [BatchExecution::Sequential, BatchExecution::Concurrent] {
+            let agent = agent_builder_with_js()
+                .with_batch_execution(execution)
+                .build()
+                .await
+                .expect("agent build");
+            let responses = agent.handle_a2a(batch.clone()).await.expect("a2a handle");
+            assert_eq!(responses.len(), 1, "a batch is answered with one array");
+            let members = responses[0].as_array().expect("batch response array");
+
+            let ids: Vec<&Value> = members.iter().map(|member| &member["id"]).collect();
+            assert_eq!(
+                ids,
+                [
+                    &json!("first"),
+                    &json!("second"),
+                    &json!("second"),
+                    &Value::Null,
+                    &json!("fourth")
+                ]
+            );
+            assert_eq!(
+                members[0]["result"]["message"]["parts"][0]["text"],
+                "hi Ada"
+            );
+            assert_eq!(members[3]["error"]["code"], -32600);
+            assert_eq!(
+                members[4]["result"]["message"]["parts"][0]["text"],
+                "hi Alan"
+            );
+        }
+
+        let agent = setup_agent_with_js().await;
+        let responses = agent.handle_a2a(json!([])).await.expect("a2a handle");
+        assert_eq!(responses[0]["error"]["code"], -32600);
+    }
+
+    #[tokio::test]
+    async fn test_notifications_are_served_without_responses() {
+        let agent = agent_builder_with_js()
+            .with_init_js(
+                r#"
+                const handle = globalThis.handle_a2a_request;
+                globalThis.pings = [];
+                globalThis.handle_a2a_request = async function(request) {
+                    if (!("id" in request)) {
+                        globalThis.pings.push(request.params.text);
+                    }
+                    return handle(request);
+                };
+                "#,
+            )
+            .build()
+            .await
+            .expect("agent build");
+        let notification = |text: &str| {
+            json!({
+                "jsonrpc": "2.0",
+                "method": "message.send",
+                "params": { "message": user_message(text, text) }
+            })
+        };
+
+        let responses = agent
+            .handle_a2a(notification("ping"))
+            .await
+            .expect("a2a handle");
+        assert!(responses.is_empty());
+
+        let mut answered = notification("Ada");
+        answered["id"] = json!("req-1");
+        let batch = json!([notification("pong"), answered]);
+        let responses = agent.handle_a2a(batch).await.expect("a2a handle");
+        let members = responses[0].as_array().expect("batch response array");
+        assert_eq!(members.len(), 1);
+        assert_eq!(members[0]["id"], "req-1");
+
+        let only_notifications = json!([notification("one"), notification("two")]);
+        let responses = agent
+            .handle_a2a(only_notifications)
+            .await
+            .expect("a2a handle");
+        assert!(responses.is_empty());
+
+        let pings = agent
+            .evaluate_js("return JSON.stringify(globalThis.pings);")
+            .await
+            .expect("read pings");
+        assert_eq!(pings, json!(["ping", "pong", "one", "two"]));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_non_blocking_message_send_runs_in_background() {
+        let agent = agent_builder_with_js()
+            .with_background_tasks(BackgroundConfig::default())
+            .build()
+            .await
+            .expect("agent build");
+        let mut updates = agent.subscribe_task_updates();
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": "bg-1",
+            "method": "message.send",
+            "params": {
+                "message": user_message("msg-bg", "Ada"),
+                "configuration": { "blocking": false }
+            }
+        });
+        let result = expect_success_result(agent.handle_a2a(request).await.expect("a2a handle"));
+        assert_eq!(result["task"]["status"]["state"], "TASK_STATE_SUBMITTED");
+        let task_id = result["task"]["id"].as_str().expect("task id").to_string();
+
+        let mut states = Vec::new();
+        while states.last() != Some(&TaskState::Completed) {
+            let update = tokio::time::timeout(std::time::Duration::from_secs(5), updates.recv())
+                .await
+                .expect("task update")
+                .expect("update channel");
+            if let TaskUpdateEvent::Status(update) = update {
+                assert_eq!(
+                    update.task_id.as_ref().map(|id| id.as_str()),
+                    Some(&*task_id)
+                );
+                let state = update
+                    .status
+                    .and_then(|status| status.state)
+                    .expect("state");
+                states.push(TaskState::from_wire(&state).expect("known state"));
+            }
+        }
+        assert_eq!(
+            states,
+            [
+                TaskState::Submitted,
+                TaskState::Working,
+                TaskState::Completed
+            ]
+        );
+
+        let get = json!({
+            "jsonrpc": "2.0",
+            "id": "bg-2",
+            "method": "tasks.get",
+            "params": { "id": task_id }
+        });
+        let task = expect_success_result(agent.handle_a2a(get).await.expect("a2a handle"));
+        assert_eq!(task["status"]["state"], "TASK_STATE_COMPLETED");
+        assert_eq!(task["status"]["message"]["parts"][0]["text"], "hi Ada");
+    }
+
+    #[tokio::test]
+    async fn test_input_required_task_resumes_with_the_next_message() {
+        let agent = A2aAgent::builder()
+            .with_init_js(
+                r#"
+                globalThis.handle_a2a_request = async function(request) {
+                    const params = request.params;
+                    const text = params.message.parts[0].text;
+                    if (!params.resume) {
+                        return requireInput("Which city?", { greeting: text });
+                    }
+                    return {
+                        message: {
+                            messageId: "resp-city",
+                            role: "ROLE_AGENT",
+                            parts: [{ text: `${params.resume.state.greeting}, ${text}` }]
+                        }
+                    };
+                };
+                "#,
+            )
+            .build()
+            .await
+            .expect("agent build");
+        let send = |id: &str, message: Message| {
+            json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "method": "message.send",
+                "params": { "message": message }
+            })
+        };
+
+        let result = expect_success_result(
+            agent
+                .handle_a2a(send("req-1", user_message("msg-1", "Hello")))
+                .await
+                .expect("a2a handle"),
+        );
+        let task = &result["task"];
+        assert_eq!(task["status"]["state"], "TASK_STATE_INPUT_REQUIRED");
+        assert_eq!(task["status"]["message"]["parts"][0]["text"], "Which city?");
+        let task_id = task["id"].as_str().expect("task id").to_string();
+        let pending = agent
+            .task_store()
+            .get(&task_id, Some(0))
+            .await
+            .as_ref()
+            .and_then(crate::PendingInput::of)
+            .expect("pending input");
+        assert_eq!(pending.state, Some(json!({ "greeting": "Hello" })));
+
+        let mut answer = user_message("msg-2", "Paris");
+        answer.task_id = Some(task_id.as_str().into());
+        let result = expect_success_result(
+            agent
+                .handle_a2a(send("req-2", answer))
+                .await
+                .expect("a2a handle"),
+        );
+        assert_eq!(result["message"]["parts"][0]["text"], "Hello, Paris");
+
+        let task = agent
+            .task_store()
+            .get(&task_id, Some(0))
+            .await
+            .expect("stored task");
+        let state = task.status.and_then(|status| status.state).expect("state");
+        assert_eq!(
+            TaskState::from_wire(&state).expect("known state"),
+            TaskState::Completed
+        );
+    }
+
+    struct Pay;
+
+    #[async_trait::async_trait]
+    impl baml_rt_tools::ToolExecutor for Pay {
+        async fn execute(&self, args: Value) -> Result<Value> {
+            Ok(json!({ "paid": args["amount"] }))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_tool_calls_wait_for_approval_in_paused_tasks() {
+        let agent = A2aAgent::builder()
+            .with_init_js(
+                r#"
+                globalThis.handle_a2a_request = async function(request) {
+                    const result = await invokeTool("pay", { amount: 5 });
+                    return {
+                        message: {
+                            messageId: "resp-pay",
+                            role: "ROLE_AGENT",
+                            parts: [{ text: `paid ${result.paid}` }]
+                        }
+                    };
+                };
+                "#,
+            )
+            .build()
+            .await
+            .expect("agent build");
+        let metadata = baml_rt_tools::ToolMetadata {
+            name: "pay".to_string(),
+            description: "Sends a payment".to_string(),
+            input_schema: json!({ "type": "object" }),
+            requires_approval: true,
+        };
+        let registry = agent.runtime().read().await.tool_registry();
+        registry
+            .lock()
+            .await
+            .register_dynamic(metadata, std::sync::Arc::new(Pay))
+            .expect("register tool");
+        let send = |id: &str, message: Message| {
+            json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "method": "message.send",
+                "params": { "message": message }
+            })
+        };
+        let reply = |task_id: &str, id: &str, text: &str| {
+            let mut message = user_message(id, text);
+            message.task_id = Some(task_id.into());
+            message
+        };
+        let state = |task: crate::a2a_types::Task| {
+            let state = task.status.and_then(|status| status.state).expect("state");
+            TaskState::from_wire(&state).expect("known state")
+        };
+
+        let result = expect_success_result(
+            agent
+                .handle_a2a(send("req-1", user_message("msg-1", "pay")))
+                .await
+                .expect("a2a handle"),
+        );
+        let task = &result["task"];
+        assert_eq!(task["status"]["state"], "TASK_STATE_INPUT_REQUIRED");
+        let call = &task["status"]["message"]["parts"][1]["data"];
+        assert_eq!(call["tool"], "rust:pay");
+        assert_eq!(call["args"], json!({ "amount": 5 }));
+        let task_id = task["id"].as_str().expect("task id").to_string();
+
+        let unclear = agent
+            .handle_a2a(send("req-2", reply(&task_id, "msg-2", "maybe")))
+            .await
+            .expect("a2a handle");
+        assert!(unclear[0].get("error").is_some());
+
+        let result = expect_success_result(
+            agent
+                .handle_a2a(send("req-3", reply(&task_id, "msg-3", "approve")))
+                .await
+                .expect("a2a handle"),
+        );
+        assert_eq!(result["message"]["parts"][0]["text"], "paid 5");
+        let task = agent.task_store().get(&task_id, Some(0)).await;
+        assert_eq!(state(task.expect("stored task")), TaskState::Completed);
+
+        let result = expect_success_result(
+            agent
+                .handle_a2a(send("req-4", user_message("msg-4", "pay")))
+                .await
+                .expect("a2a handle"),
+        );
+        let task_id = result["task"]["id"].as_str().expect("task id").to_string();
+        expect_success_result(
+            agent
+                .handle_a2a(send("req-5", reply(&task_id, "msg-5", "deny")))
+                .await
+                .expect("a2a handle"),
+        );
+        let task = agent.task_store().get(&task_id, Some(0)).await;
+        assert_eq!(state(task.expect("stored task")), TaskState::Canceled);
+
+        let events = agent
+            .provenance_reader()
+            .expect("provenance reader")
+            .recent_events(usize::MAX)
+            .await
+            .expect("provenance events");
+        let decisions: Vec<bool> = events
+            .iter()
+            .filter_map(|event| match &event.data {
+                baml_rt_provenance::ProvEventData::ToolApproval { approved, .. } => Some(*approved),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(decisions, vec![true, false]);
+    }
+
     #[tokio::test]
     async fn test_tasks_get_list_cancel() {
         let agent = setup_agent_with_js().await;
@@ -787,6 +1304,338 @@ mod tests {
         assert!(any_final, "subscribe stream should include a final chunk");
     }
 
+    #[tokio::test]
+    async fn test_message_send_includes_context_history() {
+        let agent = A2aAgent::builder()
+            .with_init_js(
+                r#"
+                globalThis.handle_a2a_request = async function(request) {
+                    const history = request.params.history || [];
+                    const seen = history.map((m) => m.parts[0].text).join(",");
+                    return {
+                        message: {
+                            messageId: `reply-${history.length}`,
+                            role: "ROLE_AGENT",
+                            parts: [{ text: `${history.length}:${seen}` }]
+                        }
+                    };
+                };
+                "#,
+            )
+            .build()
+            .await
+            .expect("agent build");
+
+        let mut replies = Vec::new();
+        for (idx, text) in ["hello", "again", "bye"].into_iter().enumerate() {
+            let mut message = user_message(&format!("msg-turn-{idx}"), text);
+            message.context_id = Some(baml_rt_core::ids::ContextId::from("ctx-chat"));
+            let params = SendMessageRequest {
+                message,
+                configuration: None,
+                metadata: None,
+                tenant: None,
+                extra: HashMap::new(),
+            };
+            let request = JSONRPCRequest {
+                jsonrpc: "2.0".to_string(),
+                method: "message.send".to_string(),
+                params: Some(serde_json::to_value(params).expect("serialize params")),
+                id: Some(JSONRPCId::String(format!("turn-{idx}"))),
+            };
+            let responses = agent
+                .handle_a2a(serde_json::to_value(request).expect("serialize request"))
+                .await
+                .expect("a2a handle");
+            let result = expect_success_result(responses);
+            replies.push(result["message"]["parts"][0]["text"].clone());
+        }
+
+        assert_eq!(replies[0], json!("0:"));
+        assert_eq!(replies[1], json!("2:hello,0:"));
+        assert_eq!(replies[2], json!("4:hello,0:,again,2:hello,0:"));
+    }
+
+    #[tokio::test]
+    async fn test_artifacts_stored_by_js_are_served_by_artifacts_get() {
+        let agent = A2aAgent::builder()
+            .with_artifact_store(std::sync::Arc::new(
+                baml_rt_quickjs::InMemoryArtifactStore::new(),
+            ))
+            .with_init_js(
+                r#"
+                globalThis.handle_a2a_request = async function(request) {
+                    const info = await artifacts.put("name,score\nAda,3\n", {
+                        mediaType: "text/csv",
+                        name: "scores.csv"
+                    });
+                    return {
+                        task: {
+                            id: "task-artifact",
+                            contextId: "ctx-artifact",
+                            status: { state: "TASK_STATE_COMPLETED" },
+                            artifacts: [{ artifactId: info.artifactId, name: info.name, parts: [] }]
+                        }
+                    };
+                };
+                "#,
+            )
+            .build()
+            .await
+            .expect("agent build");
+
+        let params = SendMessageRequest {
+            message: user_message("msg-artifact", "report"),
+            configuration: None,
+            metadata: None,
+            tenant: None,
+            extra: HashMap::new(),
+        };
+        let request = JSONRPCRequest {
+            jsonrpc: "2.0".to_string(),
+            method: "message.send".to_string(),
+            params: Some(serde_json::to_value(params).expect("serialize params")),
+            id: Some(JSONRPCId::String("artifact-create".to_string())),
+        };
+        let result = expect_success_result(
+            agent
+                .handle_a2a(serde_json::to_value(request).expect("serialize request"))
+                .await
+                .expect("a2a handle"),
+        );
+        let artifact_id = result["task"]["artifacts"][0]["artifactId"]
+            .as_str()
+            .expect("artifact id")
+            .to_string();
+        assert!(artifact_id.starts_with("sha256-"));
+
+        let get_request = JSONRPCRequest {
+            jsonrpc: "2.0".to_string(),
+            method: "artifacts.get".to_string(),
+            params: Some(json!({ "id": artifact_id })),
+            id: Some(JSONRPCId::String("artifact-get".to_string())),
+        };
+        let artifact = expect_success_result(
+            agent
+                .handle_a2a(serde_json::to_value(get_request).expect("serialize request"))
+                .await
+                .expect("artifacts.get"),
+        );
+        assert_eq!(artifact["artifactId"], json!(artifact_id));
+        let part = &artifact["parts"][0];
+        assert_eq!(part["mediaType"], json!("text/csv"));
+        assert_eq!(part["filename"], json!("scores.csv"));
+        // base64 of "name,score\nAda,3\n"
+        assert_eq!(part["raw"], json!("bmFtZSxzY29yZQpBZGEsMwo="));
+    }
+
+    /// Answers calls with the target and method, and streams each word of
+    /// `params.text`
+    struct EchoAgentCaller;
+
+    #[async_trait::async_trait]
+    impl baml_rt_quickjs::AgentCaller for EchoAgentCaller {
+        async fn call(&self, target: &str, method: &str, params: Value) -> Result<Value> {
+            Ok(json!({ "from": target, "method": method, "params": params }))
+        }
+
+        async fn stream(
+            &self,
+            _target: &str,
+            _method: &str,
+            params: Value,
+        ) -> Result<baml_rt_quickjs::AgentStream> {
+            let (tx, rx) = tokio::sync::mpsc::channel(8);
+            let text = params["text"].as_str().unwrap_or_default().to_string();
+            for word in text.split_whitespace() {
+                tx.send(Ok(json!(word))).await.expect("send chunk");
+            }
+            Ok(rx)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_js_handler_delegates_with_call_agent() {
+        let agent = A2aAgent::builder()
+            .with_agent_caller(std::sync::Arc::new(EchoAgentCaller))
+            .with_init_js(
+                r#"
+                globalThis.handle_a2a_request = async function(request) {
+                    const answer = await callAgent("https://peer.example", "message.send", {
+                        text: request.params.text
+                    });
+                    const summary = `${answer.from} ${answer.params.text}`;
+                    const words = [];
+                    for await (const word of callAgent.stream("stdio:peer", "message.sendStream", {
+                        text: "one two three"
+                    })) {
+                        words.push(word);
+                    }
+                    return {
+                        message: {
+                            messageId: "reply-delegate",
+                            role: "ROLE_AGENT",
+                            parts: [{ text: `${summary} ${words.join("+")}` }]
+                        }
+                    };
+                };
+                "#,
+            )
+            .build()
+            .await
+            .expect("agent build");
+
+        let params = SendMessageRequest {
+            message: user_message("msg-delegate", "sub-task"),
+            configuration: None,
+            metadata: None,
+            tenant: None,
+            extra: HashMap::new(),
+        };
+        let request = JSONRPCRequest {
+            jsonrpc: "2.0".to_string(),
+            method: "message.send".to_string(),
+            params: Some(serde_json::to_value(params).expect("serialize params")),
+            id: Some(JSONRPCId::String("delegate".to_string())),
+        };
+        let result = expect_success_result(
+            agent
+                .handle_a2a(serde_json::to_value(request).expect("serialize request"))
+                .await
+                .expect("a2a handle"),
+        );
+        assert_eq!(
+            result["message"]["parts"][0]["text"],
+            json!("https://peer.example sub-task one+two+three")
+        );
+    }
+
+    /// Reports the agent's BAML function count, or streams two chunks when
+    /// asked to
+    struct AgentInfoMethod;
+
+    #[async_trait::async_trait(?Send)]
+    impl crate::MethodHandler for AgentInfoMethod {
+        async fn handle(
+            &self,
+            agent: &A2aAgent,
+            request: crate::MethodRequest,
+        ) -> Result<super::A2aOutcome> {
+            if request.params["stream"] == json!(true) {
+                return Ok(super::A2aOutcome::Stream(vec![json!("a"), json!("b")]));
+            }
+            let functions = agent.runtime().read().await.list_functions();
+            Ok(super::A2aOutcome::Response(json!({
+                "method": request.method,
+                "functions": functions.len(),
+                "echo": request.params["echo"],
+            })))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_custom_method_handlers_serve_registered_methods() {
+        let agent = A2aAgent::builder()
+            .with_method_handler("myorg.info", std::sync::Arc::new(AgentInfoMethod))
+            .build()
+            .await
+            .expect("agent build");
+
+        let result = expect_success_result(
+            agent
+                .handle_a2a(json!({
+                    "jsonrpc": "2.0",
+                    "id": "info-1",
+                    "method": "myorg.info",
+                    "params": { "echo": "hi" }
+                }))
+                .await
+                .expect("a2a handle"),
+        );
+        assert_eq!(
+            result,
+            json!({ "method": "myorg.info", "functions": 0, "echo": "hi" })
+        );
+
+        let chunks = agent
+            .handle_a2a(json!({
+                "jsonrpc": "2.0",
+                "id": "info-2",
+                "method": "myorg.info",
+                "params": { "stream": true }
+            }))
+            .await
+            .expect("a2a handle");
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks[1]["result"]["final"], json!(true));
+
+        let unknown = agent
+            .handle_a2a(json!({ "jsonrpc": "2.0", "id": "x", "method": "myorg.other" }))
+            .await
+            .expect("a2a handle");
+        assert!(unknown[0].get("error").is_some());
+
+        let err = A2aAgent::builder()
+            .with_method_handler("tasks.get", std::sync::Arc::new(AgentInfoMethod))
+            .build()
+            .await
+            .err()
+            .expect("standard methods cannot be replaced");
+        assert!(matches!(err, BamlRtError::Configuration(_)));
+    }
+
+    /// A provider that is always down
+    struct DownProvider;
+
+    #[async_trait::async_trait]
+    impl crate::ProviderProbe for DownProvider {
+        async fn probe(&self) -> crate::health::ProviderHealth {
+            crate::health::ProviderHealth {
+                name: "openai".to_string(),
+                reachable: false,
+                error: Some("connection refused".to_string()),
+                latency_ms: 0,
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_agent_health_reports_runtime_and_providers() {
+        let agent = A2aAgent::builder()
+            .with_health_probe(std::sync::Arc::new(DownProvider))
+            .build()
+            .await
+            .expect("agent build");
+
+        let report = expect_success_result(
+            agent
+                .handle_a2a(json!({ "jsonrpc": "2.0", "id": "h-1", "method": "agent.health" }))
+                .await
+                .expect("a2a handle"),
+        );
+        assert_eq!(report["status"], json!("degraded"));
+        assert_eq!(report["ready"], json!(true));
+        assert_eq!(report["schemaLoaded"], json!(false));
+        assert_eq!(report["functions"], json!(0));
+        assert_eq!(report["bridgeHealthy"], json!(true));
+        assert!(report["quickjsMemory"]["malloc_size"].as_u64().is_some());
+        assert_eq!(report["llmProviders"][0]["reachable"], json!(false));
+
+        let report = expect_success_result(
+            agent
+                .handle_a2a(json!({
+                    "jsonrpc": "2.0",
+                    "id": "h-2",
+                    "method": "agent.health",
+                    "params": { "probeProviders": false }
+                }))
+                .await
+                .expect("a2a handle"),
+        );
+        assert_eq!(report["status"], json!("ok"));
+        assert_eq!(report["llmProviders"], json!([]));
+    }
+
     #[test]
     fn test_a2a_jsonrpc_version_validation() {
         let request = json!({
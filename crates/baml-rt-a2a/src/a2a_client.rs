@@ -0,0 +1,53 @@
+//! Outbound A2A client.
+//!
+//! [`A2aClient`] sends JSON-RPC requests to other agents and implements
+//! [`AgentCaller`], so JS handlers can delegate work with `callAgent`.
+//! Requests go through the [`JsonRpcTransport`] of `baml-rt-a2a-client`,
+//! which accepts `http(s)://` URLs and, when enabled, `stdio:` commands.
+//! Rust code calling a known agent can use the typed [`AgentClient`]
+//! instead.
+
+use async_trait::async_trait;
+use baml_rt_core::Result;
+use baml_rt_quickjs::{AgentCaller, AgentStream};
+use serde_json::Value;
+use std::time::Duration;
+
+pub use baml_rt_a2a_client::jsonrpc::{AgentTarget, JsonRpcTransport, call_result, stream_results};
+pub use baml_rt_a2a_client::{AgentClient, RetryPolicy};
+
+/// JSON-RPC client for calling other agents
+#[derive(Default)]
+pub struct A2aClient {
+    transport: JsonRpcTransport,
+}
+
+impl A2aClient {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Allow `stdio:` targets, which run programs on this host
+    pub fn with_stdio(mut self, enabled: bool) -> Self {
+        self.transport = self.transport.with_stdio(enabled);
+        self
+    }
+
+    /// Fail a call, or a stream waiting for its next result, after `timeout`
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.transport = self.transport.with_timeout(timeout);
+        self
+    }
+}
+
+#[async_trait]
+impl AgentCaller for A2aClient {
+    /// Results of a streaming method are collected into an array
+    async fn call(&self, target: &str, method: &str, params: Value) -> Result<Value> {
+        self.transport.call(target, method, params).await
+    }
+
+    async fn stream(&self, target: &str, method: &str, params: Value) -> Result<AgentStream> {
+        self.transport.stream(target, method, params).await
+    }
+}
@@ -1,15 +1,140 @@
 use crate::a2a_types::{
-    Artifact, ListTasksRequest, ListTasksResponse, Message, TASK_STATE_CANCELED, Task,
-    TaskArtifactUpdateEvent, TaskState, TaskStatus, TaskStatusUpdateEvent,
+    self, Artifact, ListTasksRequest, ListTasksResponse, Message, TASK_STATE_CANCELED, Task,
+    TaskArtifactUpdateEvent, TaskStatus, TaskStatusUpdateEvent,
 };
+use crate::input_required::PendingInput;
+use crate::outbox::{OutboxEntry, OutboxLog, OutboxStore};
 use async_trait::async_trait;
-use baml_rt_core::context;
-use baml_rt_core::ids::{ContextId, TaskId};
+use baml_rt_core::ids::{ContextId, TaskId, TenantId};
+use baml_rt_core::{BamlRtError, Result};
+use baml_rt_core::{context, tenant};
 use baml_rt_provenance::{ProvEvent, ProvenanceWriter};
+use serde_json::Value;
 use std::collections::HashMap;
+use std::fmt;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
 use tokio::sync::Mutex;
 
+static TASK_COUNTER: AtomicU64 = AtomicU64::new(1);
+
+/// A new task id, for tasks the runtime creates itself
+pub fn generate_task_id() -> TaskId {
+    let millis = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0);
+    let counter = TASK_COUNTER.fetch_add(1, Ordering::Relaxed);
+    TaskId::new(format!("task-{}-{}", millis, counter))
+}
+
+/// Where a task is in its lifecycle.
+///
+/// A task is `Submitted`, then `Working` while the agent handles it. It may
+/// pause in `InputRequired` or `AuthRequired` until the client answers, and
+/// ends `Completed`, `Canceled`, `Failed` or `Rejected`; terminal states are
+/// never left. Tasks first stored in any state are accepted, and repeating
+/// the current state is always allowed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TaskState {
+    Submitted,
+    Working,
+    InputRequired,
+    AuthRequired,
+    Completed,
+    Canceled,
+    Failed,
+    Rejected,
+}
+
+impl TaskState {
+    /// The state's name on the wire, e.g. `TASK_STATE_WORKING`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TaskState::Submitted => "TASK_STATE_SUBMITTED",
+            TaskState::Working => "TASK_STATE_WORKING",
+            TaskState::InputRequired => "TASK_STATE_INPUT_REQUIRED",
+            TaskState::AuthRequired => "TASK_STATE_AUTH_REQUIRED",
+            TaskState::Completed => "TASK_STATE_COMPLETED",
+            TaskState::Canceled => TASK_STATE_CANCELED,
+            TaskState::Failed => "TASK_STATE_FAILED",
+            TaskState::Rejected => "TASK_STATE_REJECTED",
+        }
+    }
+
+    pub fn is_terminal(&self) -> bool {
+        matches!(
+            self,
+            TaskState::Completed | TaskState::Canceled | TaskState::Failed | TaskState::Rejected
+        )
+    }
+
+    /// Whether a task in this state may move to `next`.
+    pub fn can_transition_to(&self, next: TaskState) -> bool {
+        use TaskState::*;
+        *self == next
+            || match self {
+                Submitted => matches!(next, Working | Canceled | Failed | Rejected),
+                Working => matches!(
+                    next,
+                    InputRequired | AuthRequired | Completed | Canceled | Failed
+                ),
+                InputRequired | AuthRequired => matches!(next, Working | Canceled | Failed),
+                Completed | Canceled | Failed | Rejected => false,
+            }
+    }
+
+    /// The state a wire value names.
+    ///
+    /// Accepts `TASK_STATE_INPUT_REQUIRED` as well as the older
+    /// `input-required` form, and the numbers of the protobuf enum.
+    pub fn from_wire(state: &a2a_types::TaskState) -> Result<Self> {
+        let parsed = match state {
+            a2a_types::TaskState::String(name) => {
+                let name = name.to_ascii_uppercase().replace('-', "_");
+                match name.strip_prefix("TASK_STATE_").unwrap_or(&name) {
+                    "SUBMITTED" => Some(TaskState::Submitted),
+                    "WORKING" => Some(TaskState::Working),
+                    "INPUT_REQUIRED" => Some(TaskState::InputRequired),
+                    "AUTH_REQUIRED" => Some(TaskState::AuthRequired),
+                    "COMPLETED" => Some(TaskState::Completed),
+                    "CANCELED" | "CANCELLED" => Some(TaskState::Canceled),
+                    "FAILED" => Some(TaskState::Failed),
+                    "REJECTED" => Some(TaskState::Rejected),
+                    _ => None,
+                }
+            }
+            a2a_types::TaskState::Integer(number) => match number {
+                1 => Some(TaskState::Submitted),
+                2 => Some(TaskState::Working),
+                3 => Some(TaskState::Completed),
+                4 => Some(TaskState::Failed),
+                5 => Some(TaskState::Canceled),
+                6 => Some(TaskState::InputRequired),
+                7 => Some(TaskState::Rejected),
+                8 => Some(TaskState::AuthRequired),
+                _ => None,
+            },
+        };
+        parsed.ok_or_else(|| {
+            BamlRtError::InvalidArgument(format!("Unknown task state: {}", wire_name(state)))
+        })
+    }
+}
+
+impl fmt::Display for TaskState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl From<TaskState> for a2a_types::TaskState {
+    fn from(state: TaskState) -> Self {
+        a2a_types::TaskState::String(state.as_str().to_string())
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum TaskUpdateEvent {
     Status(TaskStatusUpdateEvent),
@@ -25,30 +150,56 @@ impl TaskUpdateEvent {
     }
 }
 
+/// Tasks, updates, and conversations, kept apart per tenant
+///
+/// Every method works on the partition of the tenant in scope (see
+/// [`baml_rt_core::tenant`]), so one tenant can never read, list, or cancel
+/// another's tasks, even when both use the same task ID. Requests made for
+/// no tenant share a partition of their own. The outbox is shared.
 #[derive(Debug, Default)]
 pub struct TaskStore {
+    partitions: HashMap<Option<TenantId>, TaskPartition>,
+    outbox: OutboxLog,
+}
+
+/// The tasks of one tenant
+#[derive(Debug, Default)]
+struct TaskPartition {
     tasks: HashMap<String, Task>,
     order: Vec<String>,
     updates: HashMap<String, Vec<TaskUpdateEvent>>,
+    /// Messages exchanged under each context, oldest first
+    contexts: HashMap<String, Vec<Message>>,
 }
 
 #[async_trait]
 pub trait TaskRepository: Send + Sync {
-    async fn upsert(&self, task: Task) -> Option<Task>;
+    /// Store `task`, failing if its state cannot follow the stored one
+    async fn upsert(&self, task: Task) -> Result<Option<Task>>;
     async fn get(&self, id: &str, history_length: Option<usize>) -> Option<Task>;
     async fn list(&self, request: &ListTasksRequest) -> ListTasksResponse;
-    async fn cancel(&self, id: &str) -> Option<Task>;
+    /// Cancel a task, failing if it already ended
+    async fn cancel(&self, id: &str) -> Result<Option<Task>>;
     async fn insert_message(&self, message: &Message);
+    /// Messages recorded for a context, oldest first, keeping the last
+    /// `history_length` when set
+    async fn context_history(
+        &self,
+        context_id: &str,
+        history_length: Option<usize>,
+    ) -> Vec<Message>;
 }
 
 #[async_trait]
 pub trait TaskEventRecorder: Send + Sync {
+    /// Move a task to `status`, failing if its state cannot follow the
+    /// stored one
     async fn record_status_update(
         &self,
         task_id: Option<TaskId>,
         context_id: Option<ContextId>,
         status: TaskStatus,
-    ) -> Option<TaskUpdateEvent>;
+    ) -> Result<Option<TaskUpdateEvent>>;
     async fn record_artifact_update(
         &self,
         task_id: Option<TaskId>,
@@ -71,7 +222,7 @@ impl<T> TaskStoreBackend for T where T: TaskRepository + TaskEventRecorder + Tas
 
 #[async_trait]
 impl TaskRepository for Mutex<TaskStore> {
-    async fn upsert(&self, task: Task) -> Option<Task> {
+    async fn upsert(&self, task: Task) -> Result<Option<Task>> {
         let mut store = self.lock().await;
         store.upsert(task)
     }
@@ -86,7 +237,7 @@ impl TaskRepository for Mutex<TaskStore> {
         store.list(request)
     }
 
-    async fn cancel(&self, id: &str) -> Option<Task> {
+    async fn cancel(&self, id: &str) -> Result<Option<Task>> {
         let mut store = self.lock().await;
         store.cancel(id)
     }
@@ -95,6 +246,15 @@ impl TaskRepository for Mutex<TaskStore> {
         let mut store = self.lock().await;
         store.insert_message(message);
     }
+
+    async fn context_history(
+        &self,
+        context_id: &str,
+        history_length: Option<usize>,
+    ) -> Vec<Message> {
+        let store = self.lock().await;
+        store.context_history(context_id, history_length)
+    }
 }
 
 #[async_trait]
@@ -104,7 +264,7 @@ impl TaskEventRecorder for Mutex<TaskStore> {
         task_id: Option<TaskId>,
         context_id: Option<ContextId>,
         status: TaskStatus,
-    ) -> Option<TaskUpdateEvent> {
+    ) -> Result<Option<TaskUpdateEvent>> {
         let mut store = self.lock().await;
         store.record_status_update(task_id, context_id, status)
     }
@@ -130,6 +290,39 @@ impl TaskUpdateQueue for Mutex<TaskStore> {
     }
 }
 
+#[async_trait]
+impl OutboxStore for Mutex<TaskStore> {
+    async fn record_intent(&self, entry: OutboxEntry) -> OutboxEntry {
+        let mut store = self.lock().await;
+        store.outbox.record_intent(entry)
+    }
+
+    async fn get_entry(&self, id: &str) -> Option<OutboxEntry> {
+        let store = self.lock().await;
+        store.outbox.get(id)
+    }
+
+    async fn claim_due(&self, now_ms: u64) -> Vec<OutboxEntry> {
+        let mut store = self.lock().await;
+        store.outbox.claim_due(now_ms)
+    }
+
+    async fn mark_dispatched(&self, id: &str, result: Value) -> Option<OutboxEntry> {
+        let mut store = self.lock().await;
+        store.outbox.mark_dispatched(id, result)
+    }
+
+    async fn mark_attempt_failed(
+        &self,
+        id: &str,
+        error: String,
+        retry_at_ms: Option<u64>,
+    ) -> Option<OutboxEntry> {
+        let mut store = self.lock().await;
+        store.outbox.mark_attempt_failed(id, error, retry_at_ms)
+    }
+}
+
 pub struct ProvenanceTaskStore {
     inner: Mutex<TaskStore>,
     writer: Option<Arc<dyn ProvenanceWriter>>,
@@ -150,21 +343,49 @@ impl ProvenanceTaskStore {
                 .await;
         }
     }
+
+    /// Record a change of state of task `task_id`, if there was one
+    async fn record_transition(
+        &self,
+        context_id: Option<ContextId>,
+        task_id: TaskId,
+        from: Option<TaskState>,
+        to: Option<TaskState>,
+    ) {
+        if to.is_none() || from == to {
+            return;
+        }
+        let event = ProvEvent::task_status_changed(
+            context_id.unwrap_or_else(context::current_or_new),
+            task_id,
+            from.map(|state| state.as_str().to_string()),
+            to.map(|state| state.as_str().to_string()),
+        );
+        self.record_event(event).await;
+    }
 }
 
 #[async_trait]
 impl TaskRepository for ProvenanceTaskStore {
-    async fn upsert(&self, task: Task) -> Option<Task> {
-        let context_id = task
-            .context_id
-            .clone()
-            .unwrap_or_else(context::current_or_new);
-        if let Some(task_id) = task.id.clone() {
-            let event = ProvEvent::task_created(context_id, task_id, None);
+    async fn upsert(&self, task: Task) -> Result<Option<Task>> {
+        let Some(task_id) = task.id.clone() else {
+            return Ok(None);
+        };
+        let context_id = task.context_id.clone();
+        let (existed, from, stored) = {
+            let mut store = self.inner.lock().await;
+            let existed = store.get(task_id.as_str(), Some(0)).is_some();
+            let from = store.state(task_id.as_str());
+            (existed, from, store.upsert(task)?)
+        };
+        if !existed {
+            let context_id = context_id.clone().unwrap_or_else(context::current_or_new);
+            let event = ProvEvent::task_created(context_id, task_id.clone(), None);
             self.record_event(event).await;
         }
-        let mut store = self.inner.lock().await;
-        store.upsert(task)
+        let to = stored.as_ref().and_then(task_state);
+        self.record_transition(context_id, task_id, from, to).await;
+        Ok(stored)
     }
 
     async fn get(&self, id: &str, history_length: Option<usize>) -> Option<Task> {
@@ -177,15 +398,34 @@ impl TaskRepository for ProvenanceTaskStore {
         store.list(request)
     }
 
-    async fn cancel(&self, id: &str) -> Option<Task> {
-        let mut store = self.inner.lock().await;
-        store.cancel(id)
+    async fn cancel(&self, id: &str) -> Result<Option<Task>> {
+        let (from, canceled) = {
+            let mut store = self.inner.lock().await;
+            (store.state(id), store.cancel(id)?)
+        };
+        if let Some(task) = &canceled
+            && let Some(task_id) = task.id.clone()
+        {
+            let to = Some(TaskState::Canceled);
+            self.record_transition(task.context_id.clone(), task_id, from, to)
+                .await;
+        }
+        Ok(canceled)
     }
 
     async fn insert_message(&self, message: &Message) {
         let mut store = self.inner.lock().await;
         store.insert_message(message);
     }
+
+    async fn context_history(
+        &self,
+        context_id: &str,
+        history_length: Option<usize>,
+    ) -> Vec<Message> {
+        let store = self.inner.lock().await;
+        store.context_history(context_id, history_length)
+    }
 }
 
 #[async_trait]
@@ -195,18 +435,22 @@ impl TaskEventRecorder for ProvenanceTaskStore {
         task_id: Option<TaskId>,
         context_id: Option<ContextId>,
         status: TaskStatus,
-    ) -> Option<TaskUpdateEvent> {
-        if let Some(task_id) = task_id.clone() {
-            let event = ProvEvent::task_status_changed(
-                context_id.clone().unwrap_or_else(context::current_or_new),
-                task_id,
-                None,
-                status_to_string(&status),
-            );
-            self.record_event(event).await;
+    ) -> Result<Option<TaskUpdateEvent>> {
+        let to = status
+            .state
+            .as_ref()
+            .map(TaskState::from_wire)
+            .transpose()?;
+        let (from, event) = {
+            let mut store = self.inner.lock().await;
+            let from = task_id.as_ref().and_then(|id| store.state(id.as_str()));
+            let event = store.record_status_update(task_id.clone(), context_id.clone(), status)?;
+            (from, event)
+        };
+        if let Some(task_id) = task_id {
+            self.record_transition(context_id, task_id, from, to).await;
         }
-        let mut store = self.inner.lock().await;
-        store.record_status_update(task_id, context_id, status)
+        Ok(event)
     }
 
     async fn record_artifact_update(
@@ -239,11 +483,45 @@ impl TaskUpdateQueue for ProvenanceTaskStore {
     }
 }
 
-fn status_to_string(status: &TaskStatus) -> Option<String> {
-    status.state.as_ref().map(|state| match state {
-        TaskState::String(value) => value.clone(),
-        TaskState::Integer(value) => value.to_string(),
-    })
+#[async_trait]
+impl OutboxStore for ProvenanceTaskStore {
+    async fn record_intent(&self, entry: OutboxEntry) -> OutboxEntry {
+        self.inner.record_intent(entry).await
+    }
+
+    async fn get_entry(&self, id: &str) -> Option<OutboxEntry> {
+        self.inner.get_entry(id).await
+    }
+
+    async fn claim_due(&self, now_ms: u64) -> Vec<OutboxEntry> {
+        self.inner.claim_due(now_ms).await
+    }
+
+    async fn mark_dispatched(&self, id: &str, result: Value) -> Option<OutboxEntry> {
+        self.inner.mark_dispatched(id, result).await
+    }
+
+    async fn mark_attempt_failed(
+        &self,
+        id: &str,
+        error: String,
+        retry_at_ms: Option<u64>,
+    ) -> Option<OutboxEntry> {
+        self.inner.mark_attempt_failed(id, error, retry_at_ms).await
+    }
+}
+
+fn wire_name(state: &a2a_types::TaskState) -> String {
+    match state {
+        a2a_types::TaskState::String(value) => value.clone(),
+        a2a_types::TaskState::Integer(value) => value.to_string(),
+    }
+}
+
+/// The state of a stored task; stored states have been validated
+pub(crate) fn task_state(task: &Task) -> Option<TaskState> {
+    let state = task.status.as_ref()?.state.as_ref()?;
+    TaskState::from_wire(state).ok()
 }
 
 impl TaskStore {
@@ -251,17 +529,139 @@ impl TaskStore {
         Self::default()
     }
 
-    pub fn upsert(&mut self, task: Task) -> Option<Task> {
-        let id = task.id.clone()?;
+    /// Tasks of the tenant in scope, if it stored any
+    fn partition(&self) -> Option<&TaskPartition> {
+        self.partitions.get(&tenant::current_tenant_id())
+    }
+
+    fn partition_mut(&mut self) -> &mut TaskPartition {
+        self.partitions
+            .entry(tenant::current_tenant_id())
+            .or_default()
+    }
+
+    /// Store `task`, failing if its state cannot follow the stored one
+    pub fn upsert(&mut self, task: Task) -> Result<Option<Task>> {
+        self.partition_mut().upsert(task)
+    }
+
+    /// The current state of task `id`
+    pub fn state(&self, id: &str) -> Option<TaskState> {
+        self.partition()?.state(id)
+    }
+
+    /// What task `id` waits for, if it is paused for input
+    pub fn pending_input(&self, id: &str) -> Option<PendingInput> {
+        self.partition()?.pending_input(id)
+    }
+
+    pub fn get(&self, id: &str, history_length: Option<usize>) -> Option<Task> {
+        self.partition()?.get(id, history_length)
+    }
+
+    pub fn list(&self, request: &ListTasksRequest) -> ListTasksResponse {
+        match self.partition() {
+            Some(partition) => partition.list(request),
+            None => TaskPartition::default().list(request),
+        }
+    }
+
+    /// Cancel task `id`, failing if it already ended
+    pub fn cancel(&mut self, id: &str) -> Result<Option<Task>> {
+        self.partition_mut().cancel(id)
+    }
+
+    /// Record a message in its task's history and its context's conversation
+    pub fn insert_message(&mut self, message: &Message) {
+        self.partition_mut().insert_message(message);
+    }
+
+    /// Messages recorded for `context_id`, oldest first
+    pub fn context_history(&self, context_id: &str, history_length: Option<usize>) -> Vec<Message> {
+        self.partition()
+            .map(|partition| partition.context_history(context_id, history_length))
+            .unwrap_or_default()
+    }
+
+    /// Move task `task_id` to `status` and queue the update for subscribers
+    pub fn record_status_update(
+        &mut self,
+        task_id: Option<TaskId>,
+        context_id: Option<ContextId>,
+        status: TaskStatus,
+    ) -> Result<Option<TaskUpdateEvent>> {
+        self.partition_mut()
+            .record_status_update(task_id, context_id, status)
+    }
+
+    pub fn record_artifact_update(
+        &mut self,
+        task_id: Option<TaskId>,
+        context_id: Option<ContextId>,
+        artifact: Artifact,
+        append: Option<bool>,
+        last_chunk: Option<bool>,
+    ) -> Option<TaskUpdateEvent> {
+        self.partition_mut()
+            .record_artifact_update(task_id, context_id, artifact, append, last_chunk)
+    }
+
+    pub fn drain_updates(&mut self, task_id: &str) -> Vec<TaskUpdateEvent> {
+        match self.partitions.get_mut(&tenant::current_tenant_id()) {
+            Some(partition) => partition.drain_updates(task_id),
+            None => Vec::new(),
+        }
+    }
+}
+
+impl TaskPartition {
+    fn upsert(&mut self, task: Task) -> Result<Option<Task>> {
+        let Some(id) = task.id.clone() else {
+            return Ok(None);
+        };
         let id_str = id.as_str();
+        self.check_transition(id_str, task.status.as_ref())?;
         if !self.tasks.contains_key(id_str) {
             self.order.push(id_str.to_string());
         }
+        if let Some(context_id) = &task.context_id {
+            for message in &task.history {
+                self.record_in_context(context_id.as_str(), message);
+            }
+        }
         self.tasks.insert(id_str.to_string(), task.clone());
-        Some(task)
+        Ok(Some(task))
     }
 
-    pub fn get(&self, id: &str, history_length: Option<usize>) -> Option<Task> {
+    /// The current state of task `id`
+    fn state(&self, id: &str) -> Option<TaskState> {
+        self.tasks.get(id).and_then(task_state)
+    }
+
+    /// What task `id` waits for, if it is paused for input
+    fn pending_input(&self, id: &str) -> Option<PendingInput> {
+        self.tasks.get(id).and_then(PendingInput::of)
+    }
+
+    /// Fail unless task `id` may move to the state of `status`
+    ///
+    /// Unknown tasks may start in any state; unknown states are rejected.
+    fn check_transition(&self, id: &str, status: Option<&TaskStatus>) -> Result<()> {
+        let Some(next) = status.and_then(|status| status.state.as_ref()) else {
+            return Ok(());
+        };
+        let next = TaskState::from_wire(next)?;
+        match self.state(id) {
+            Some(current) if !current.can_transition_to(next) => Err(BamlRtError::TaskTransition {
+                task_id: id.to_string(),
+                from: current.to_string(),
+                to: next.to_string(),
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    fn get(&self, id: &str, history_length: Option<usize>) -> Option<Task> {
         let mut task = self.tasks.get(id).cloned()?;
         if let Some(limit) = history_length {
             truncate_history(&mut task, limit);
@@ -269,7 +669,7 @@ impl TaskStore {
         Some(task)
     }
 
-    pub fn list(&self, request: &ListTasksRequest) -> ListTasksResponse {
+    fn list(&self, request: &ListTasksRequest) -> ListTasksResponse {
         let mut tasks: Vec<Task> = self
             .order
             .iter()
@@ -337,29 +737,74 @@ impl TaskStore {
         }
     }
 
-    pub fn cancel(&mut self, id: &str) -> Option<Task> {
-        let task = self.tasks.get_mut(id)?;
+    /// Cancel task `id`, failing if it already ended
+    fn cancel(&mut self, id: &str) -> Result<Option<Task>> {
+        let canceled = TaskStatus {
+            state: Some(TaskState::Canceled.into()),
+            ..TaskStatus::default()
+        };
+        self.check_transition(id, Some(&canceled))?;
+        let Some(task) = self.tasks.get_mut(id) else {
+            return Ok(None);
+        };
         let status = task.status.get_or_insert_with(TaskStatus::default);
-        status.state = Some(TaskState::String(TASK_STATE_CANCELED.to_string()));
-        Some(task.clone())
+        status.state = canceled.state;
+        Ok(Some(task.clone()))
     }
 
-    pub fn insert_message(&mut self, message: &Message) {
+    /// Record a message in its task's history and its context's conversation
+    ///
+    /// A message without a context ID joins the context of its task.
+    fn insert_message(&mut self, message: &Message) {
+        let mut context_id = message.context_id.clone();
         if let Some(task_id) = &message.task_id
             && let Some(task) = self.tasks.get_mut(task_id.as_str())
         {
             task.history.push(message.clone());
+            context_id = context_id.or_else(|| task.context_id.clone());
+        }
+        if let Some(context_id) = context_id {
+            self.record_in_context(context_id.as_str(), message);
         }
     }
 
-    pub fn record_status_update(
+    /// Messages recorded for `context_id`, oldest first
+    fn context_history(&self, context_id: &str, history_length: Option<usize>) -> Vec<Message> {
+        let messages = self
+            .contexts
+            .get(context_id)
+            .map(Vec::as_slice)
+            .unwrap_or(&[]);
+        let start = history_length.map_or(0, |limit| messages.len().saturating_sub(limit));
+        messages[start..].to_vec()
+    }
+
+    /// Append a message to a context's conversation unless it is already there
+    fn record_in_context(&mut self, context_id: &str, message: &Message) {
+        let messages = self.contexts.entry(context_id.to_string()).or_default();
+        if !messages
+            .iter()
+            .any(|existing| existing.message_id == message.message_id)
+        {
+            messages.push(message.clone());
+        }
+    }
+
+    /// Move task `task_id` to `status` and queue the update for subscribers
+    ///
+    /// Fails if the new state cannot follow the stored one.
+    fn record_status_update(
         &mut self,
         task_id: Option<TaskId>,
         context_id: Option<ContextId>,
         status: TaskStatus,
-    ) -> Option<TaskUpdateEvent> {
+    ) -> Result<Option<TaskUpdateEvent>> {
         if let Some(task_id) = task_id {
             let task_id_str = task_id.as_str().to_string();
+            self.check_transition(&task_id_str, Some(&status))?;
+            if let Some(task) = self.tasks.get_mut(&task_id_str) {
+                task.status = Some(status.clone());
+            }
             let update = TaskStatusUpdateEvent {
                 context_id,
                 task_id: Some(task_id.clone()),
@@ -372,12 +817,12 @@ impl TaskStore {
                 .entry(task_id_str)
                 .or_default()
                 .push(event.clone());
-            return Some(event);
+            return Ok(Some(event));
         }
-        None
+        Ok(None)
     }
 
-    pub fn record_artifact_update(
+    fn record_artifact_update(
         &mut self,
         task_id: Option<TaskId>,
         context_id: Option<ContextId>,
@@ -406,7 +851,7 @@ impl TaskStore {
         None
     }
 
-    pub fn drain_updates(&mut self, task_id: &str) -> Vec<TaskUpdateEvent> {
+    fn drain_updates(&mut self, task_id: &str) -> Vec<TaskUpdateEvent> {
         self.updates.remove(task_id).unwrap_or_default()
     }
 }
@@ -422,16 +867,104 @@ fn truncate_history(task: &mut Task, limit: usize) {
     }
 }
 
-fn matches_task_state(task: &Task, desired: &TaskState) -> bool {
-    let Some(status) = &task.status else {
-        return false;
-    };
-    let Some(state) = &status.state else {
-        return false;
-    };
-    match (state, desired) {
-        (TaskState::String(current), TaskState::String(target)) => current == target,
-        (TaskState::Integer(current), TaskState::Integer(target)) => current == target,
-        _ => false,
+fn matches_task_state(task: &Task, desired: &a2a_types::TaskState) -> bool {
+    match TaskState::from_wire(desired) {
+        Ok(desired) => task_state(task) == Some(desired),
+        Err(_) => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn task_in(state: TaskState) -> Task {
+        Task {
+            id: Some(TaskId::from("task-1")),
+            status: Some(TaskStatus {
+                state: Some(state.into()),
+                ..TaskStatus::default()
+            }),
+            ..Task::default()
+        }
+    }
+
+    #[test]
+    fn finished_tasks_cannot_be_reopened() {
+        let mut store = TaskStore::new();
+        store.upsert(task_in(TaskState::Submitted)).unwrap();
+        store.upsert(task_in(TaskState::Working)).unwrap();
+        store.upsert(task_in(TaskState::Working)).unwrap();
+        store.upsert(task_in(TaskState::Completed)).unwrap();
+
+        let error = store.upsert(task_in(TaskState::Working)).unwrap_err();
+        assert!(matches!(
+            &error,
+            BamlRtError::TaskTransition { task_id, from, to }
+                if task_id == "task-1"
+                    && from == "TASK_STATE_COMPLETED"
+                    && to == "TASK_STATE_WORKING"
+        ));
+        assert!(store.cancel("task-1").is_err());
+        assert_eq!(store.state("task-1"), Some(TaskState::Completed));
+    }
+
+    #[test]
+    fn status_updates_move_the_stored_task() {
+        let mut store = TaskStore::new();
+        store.upsert(task_in(TaskState::Working)).unwrap();
+        let input_required = TaskStatus {
+            state: Some(a2a_types::TaskState::String("input-required".to_string())),
+            ..TaskStatus::default()
+        };
+        let id = Some(TaskId::from("task-1"));
+        assert!(
+            store
+                .record_status_update(id.clone(), None, input_required)
+                .unwrap()
+                .is_some()
+        );
+        assert_eq!(store.state("task-1"), Some(TaskState::InputRequired));
+
+        let completed = TaskStatus {
+            state: Some(a2a_types::TaskState::Integer(3)),
+            ..TaskStatus::default()
+        };
+        assert!(store.record_status_update(id, None, completed).is_err());
+        assert_eq!(
+            store
+                .cancel("task-1")
+                .unwrap()
+                .and_then(|task| task_state(&task)),
+            Some(TaskState::Canceled)
+        );
+    }
+
+    #[test]
+    fn tenants_only_see_their_own_tasks() {
+        use baml_rt_core::tenant::{self, Tenant, TenantConfig};
+
+        let scoped = |id: &str| {
+            Some(Tenant::new(
+                TenantId::from(id),
+                Arc::new(TenantConfig::new()),
+            ))
+        };
+        let mut store = TaskStore::new();
+        tenant::with_tenant_sync(scoped("acme"), || {
+            store.upsert(task_in(TaskState::Working)).unwrap();
+        });
+
+        tenant::with_tenant_sync(scoped("globex"), || {
+            assert!(store.get("task-1", None).is_none());
+            assert_eq!(store.list(&ListTasksRequest::default()).tasks.len(), 0);
+            // The same ID names a different task here
+            store.upsert(task_in(TaskState::Submitted)).unwrap();
+            store.cancel("task-1").unwrap();
+        });
+        assert!(store.get("task-1", None).is_none());
+
+        let state = tenant::with_tenant_sync(scoped("acme"), || store.state("task-1"));
+        assert_eq!(state, Some(TaskState::Working));
     }
 }
@@ -2,44 +2,94 @@
 
 use crate::a2a;
 use crate::a2a_store::{
-    ProvenanceTaskStore, TaskEventRecorder, TaskRepository, TaskStoreBackend, TaskUpdateEvent,
-    TaskUpdateQueue,
+    ProvenanceTaskStore, TaskEventRecorder, TaskRepository, TaskStoreBackend, TaskUpdateQueue,
+};
+use crate::a2a_types::{JSONRPCId, JSONRPCRequest};
+use crate::approval::ProvenanceApprovals;
+use crate::auth::{
+    AUTH_PARAM, Authenticator, TransportMetadata, authorize, stamp_params_principal,
+    stamp_principal,
+};
+use crate::background::{BackgroundConfig, BackgroundExecutor};
+use crate::bridge_supervisor::{
+    BridgeEvent, BridgeFailoverConfig, BridgeSupervisor, is_fatal_engine_error,
 };
 use crate::error_classifier::{A2aErrorClassifier, ErrorClassifier};
-use crate::events::{BroadcastEventEmitter, EventEmitter};
+use crate::error_mapper::{ErrorMapper, ErrorMappingFormatter};
+use crate::events::{BusEventEmitter, EventEmitter, TaskUpdateReceiver};
+use crate::functions::{FUNCTIONS_METHOD, FunctionListing};
 use crate::handlers::{DefaultTaskHandler, TaskHandler};
+use crate::health::{HEALTH_METHOD, HealthCheck, ProviderProbe};
+use crate::input_required::REQUIRE_INPUT_JS;
+use crate::method_handlers::{MethodHandler, MethodHandlers, MethodRequest};
+use crate::method_routing::{RouteHandler, RoutedRequest};
+use crate::outbox::{
+    DispatchLoop, OutboxConfig, OutboxDispatcher, OutboxStore, OutboxToolExecutor,
+};
+use crate::parts::{FilePolicy, PartResolver};
 use crate::request_router::{MethodBasedRouter, QuickJsInvoker, RequestRouter};
 use crate::response::{JsonRpcResponseFormatter, ResponseFormatter};
 use crate::result_deduplicator::{
-    DeduplicatingPipeline, HashResultDeduplicator, ResultDeduplicator,
+    DeduplicatingPipeline, DeduplicationConfig, HashResultDeduplicator, ResultDeduplicator,
 };
 use crate::result_pipeline::{A2aResultPipeline, ResultStoragePipeline};
 use crate::stream_normalizer::{A2aStreamNormalizer, StreamNormalizer};
+use crate::task_cancellation::TaskCancellations;
+use crate::task_operations::StoreTaskOperations;
+use crate::tenancy::TenantRegistry;
+use crate::verbosity::{DenyVerbosityOverrides, VerbosityAuthorizer, requested_verbosity};
 
 use async_trait::async_trait;
+use baml_rt_core::auth::{self, Access, AccessPolicy, AllowAll, Caller};
 use baml_rt_core::context;
 use baml_rt_core::correlation;
-use baml_rt_core::{BamlRtError, Result};
+use baml_rt_core::deadline;
+use baml_rt_core::events::{EventBus, RuntimeEvent};
+use baml_rt_core::media::MediaPolicy;
+use baml_rt_core::tenant;
+use baml_rt_core::verbosity;
+use baml_rt_core::{BamlRtError, JsException, Result};
 use baml_rt_observability::{metrics, spans};
-use baml_rt_provenance::{InMemoryProvenanceStore, ProvenanceInterceptor, ProvenanceWriter};
-use baml_rt_quickjs::{BamlRuntimeManager, QuickJSBridge, QuickJSConfig};
-use baml_rt_tools::{ToolExecutor, ToolMetadata};
+use baml_rt_provenance::{
+    InMemoryProvenanceStore, ProvEvent, ProvenanceInterceptor, ProvenanceReader,
+    ProvenanceSubscriber, ProvenanceWriter, RedactionPolicy,
+};
+use baml_rt_quickjs::{
+    AgentCaller, ArtifactStore, BamlRuntimeManager, KvStore, QuickJSBridge, QuickJSConfig,
+    SchemaReload, ToolSchemaInjection, spawn_memory_reporter,
+};
+use baml_rt_tools::{
+    ApprovalGate, JS_NAMESPACE, ToolApprover, ToolExecutor, ToolMetadata, qualified_tool_name,
+};
 use serde_json::Value;
 use std::sync::Arc;
-use tokio::sync::Mutex;
+use std::time::Duration;
 use tokio::sync::broadcast;
+use tokio::sync::{Mutex, RwLock, mpsc};
 
 /// Top-level agent type that owns runtime, JS bridge, and A2A comms.
 #[derive(Clone)]
 pub struct A2aAgent {
-    runtime: Arc<Mutex<BamlRuntimeManager>>,
+    runtime: Arc<RwLock<BamlRuntimeManager>>,
     bridge: Arc<Mutex<QuickJSBridge>>,
     task_store: Arc<dyn TaskStoreBackend>,
     provenance_writer: Option<Arc<dyn ProvenanceWriter>>,
+    provenance_reader: Option<Arc<dyn ProvenanceReader>>,
+    provenance_subscriber: Option<Arc<dyn ProvenanceSubscriber>>,
     response_formatter: Arc<dyn ResponseFormatter>,
     request_router: Arc<dyn RequestRouter>,
     error_classifier: Arc<dyn ErrorClassifier>,
-    update_tx: broadcast::Sender<TaskUpdateEvent>,
+    events: EventBus,
+    bridge_supervisor: Arc<BridgeSupervisor>,
+    verbosity_authorizer: Arc<dyn VerbosityAuthorizer>,
+    outbox: Option<Arc<OutboxDispatcher>>,
+    /// Stops the outbox dispatch loop once the last clone of the agent drops.
+    _outbox_loop: Option<Arc<DispatchLoop>>,
+    method_handlers: Arc<MethodHandlers>,
+    batch_execution: a2a::BatchExecution,
+    tenants: Arc<TenantRegistry>,
+    authenticator: Option<Arc<dyn Authenticator>>,
+    access_policy: Option<Arc<dyn AccessPolicy>>,
 }
 
 impl A2aAgent {
@@ -54,7 +104,7 @@ impl A2aAgent {
     }
 
     /// Access the underlying runtime manager.
-    pub fn runtime(&self) -> Arc<Mutex<BamlRuntimeManager>> {
+    pub fn runtime(&self) -> Arc<RwLock<BamlRuntimeManager>> {
         self.runtime.clone()
     }
 
@@ -73,9 +123,61 @@ impl A2aAgent {
         self.provenance_writer.clone()
     }
 
+    /// Access recorded provenance, if the store supports reading it back.
+    pub fn provenance_reader(&self) -> Option<Arc<dyn ProvenanceReader>> {
+        self.provenance_reader.clone()
+    }
+
+    /// Subscribe to provenance events as they are recorded, if the store
+    /// supports a live feed.
+    pub fn subscribe_provenance(&self) -> Option<broadcast::Receiver<ProvEvent>> {
+        self.provenance_subscriber
+            .as_ref()
+            .map(|subscriber| subscriber.subscribe())
+    }
+
+    /// Access the event bus of this agent's runtime.
+    ///
+    /// LLM calls, tool calls, JS errors, task updates, and bridge failover
+    /// are all published on it.
+    pub fn events(&self) -> EventBus {
+        self.events.clone()
+    }
+
+    /// Subscribe to every runtime event of this agent instance.
+    pub fn subscribe_events(&self) -> broadcast::Receiver<RuntimeEvent> {
+        self.events.subscribe()
+    }
+
     /// Subscribe to task update events for this agent instance.
-    pub fn subscribe_task_updates(&self) -> broadcast::Receiver<TaskUpdateEvent> {
-        self.update_tx.subscribe()
+    pub fn subscribe_task_updates(&self) -> TaskUpdateReceiver {
+        TaskUpdateReceiver::new(self.events.subscribe())
+    }
+
+    /// Subscribe to JS bridge health and failover events.
+    pub fn subscribe_bridge_events(&self) -> broadcast::Receiver<BridgeEvent> {
+        self.bridge_supervisor.subscribe()
+    }
+
+    /// Access the supervisor that replaces the JS bridge after fatal errors.
+    pub fn bridge_supervisor(&self) -> Arc<BridgeSupervisor> {
+        self.bridge_supervisor.clone()
+    }
+
+    /// Access the outbox dispatcher, if the outbox is enabled.
+    pub fn outbox(&self) -> Option<Arc<OutboxDispatcher>> {
+        self.outbox.clone()
+    }
+
+    /// Access the custom JSON-RPC method handlers.
+    pub fn method_handlers(&self) -> Arc<MethodHandlers> {
+        self.method_handlers.clone()
+    }
+
+    /// Access the tenants this agent serves, e.g. to rotate a tenant's
+    /// credentials.
+    pub fn tenants(&self) -> Arc<TenantRegistry> {
+        self.tenants.clone()
     }
 
     /// Evaluate JavaScript in the agent runtime.
@@ -84,7 +186,19 @@ impl A2aAgent {
         bridge.evaluate(code).await
     }
 
+    /// Swap in the BAML schema at `schema_path` without restarting the agent.
+    ///
+    /// Requests in flight finish on the old schema; a schema that fails to
+    /// load leaves the old one in place.
+    pub async fn reload_schema(&self, schema_path: &str) -> Result<SchemaReload> {
+        let mut bridge = self.bridge.lock().await;
+        bridge.reload_schema(schema_path).await
+    }
+
     /// Register a JavaScript tool and expose it to BAML-native tool calls.
+    ///
+    /// Registering a name again replaces the tool's code, description, and
+    /// schema, so an agent can reload its JS without unregistering first.
     pub async fn register_js_tool(
         &self,
         name: impl Into<String>,
@@ -93,15 +207,24 @@ impl A2aAgent {
         js_function_code: impl AsRef<str>,
     ) -> Result<()> {
         let name = name.into();
+        let js_function_code = js_function_code.as_ref();
         {
             let mut bridge = self.bridge.lock().await;
-            bridge.register_js_tool(&name, js_function_code).await?;
+            if bridge.is_js_tool(&name) {
+                bridge.replace_js_tool(&name, js_function_code).await?;
+            } else {
+                bridge.register_js_tool(&name, js_function_code).await?;
+            }
         }
+        self.bridge_supervisor
+            .track_js_tool(name.clone(), js_function_code)
+            .await;
 
         let metadata = ToolMetadata {
             name: name.clone(),
             description: description.into(),
             input_schema,
+            requires_approval: false,
         };
 
         let executor: Arc<dyn ToolExecutor> = Arc::new(JsToolExecutor {
@@ -110,25 +233,111 @@ impl A2aAgent {
         });
 
         let registry = {
-            let runtime = self.runtime.lock().await;
+            let runtime = self.runtime.read().await;
             runtime.tool_registry()
         };
         let mut registry = registry.lock().await;
-        registry.register_dynamic(metadata, executor)?;
+        registry.replace_in(JS_NAMESPACE, metadata, executor)?;
 
         Ok(())
     }
+
+    /// Remove a tool from the registry, and from the JS runtime when it is
+    /// a JavaScript tool
+    ///
+    /// `name` can be qualified (`js:greet`) or bare.
+    pub async fn unregister_tool(&self, name: &str) -> Result<()> {
+        let registry = {
+            let runtime = self.runtime.read().await;
+            runtime.tool_registry()
+        };
+        let (qualified, metadata) = {
+            let mut registry = registry.lock().await;
+            let qualified = registry.resolve(name)?;
+            (qualified.clone(), registry.unregister(&qualified)?)
+        };
+        if qualified == qualified_tool_name(JS_NAMESPACE, &metadata.name) {
+            self.bridge
+                .lock()
+                .await
+                .unregister_js_tool(&metadata.name)
+                .await?;
+            self.bridge_supervisor.untrack_js_tool(&metadata.name).await;
+        }
+        Ok(())
+    }
+
+    /// Register a side-effectful tool whose effect runs through the outbox.
+    ///
+    /// Calling the tool records an intent and returns a link to the outbox
+    /// entry; `effect` runs later on the outbox dispatcher.
+    pub async fn register_outbox_tool(
+        &self,
+        name: impl Into<String>,
+        description: impl Into<String>,
+        input_schema: Value,
+        effect: Arc<dyn ToolExecutor>,
+    ) -> Result<()> {
+        let dispatcher = self.outbox.clone().ok_or_else(|| {
+            BamlRtError::Configuration(
+                "Outbox tools require A2aAgentBuilder::with_outbox".to_string(),
+            )
+        })?;
+        let name = name.into();
+        let metadata = ToolMetadata {
+            name: name.clone(),
+            description: description.into(),
+            input_schema,
+            requires_approval: false,
+        };
+        let executor: Arc<dyn ToolExecutor> =
+            Arc::new(OutboxToolExecutor::new(name.clone(), dispatcher.clone()));
+
+        let registry = {
+            let runtime = self.runtime.read().await;
+            runtime.tool_registry()
+        };
+        registry.lock().await.register_dynamic(metadata, executor)?;
+        dispatcher.register_effect(name, effect).await;
+        Ok(())
+    }
 }
 
 /// Builder for configuring an A2A agent and its subcomponents.
 pub struct A2aAgentBuilder {
-    runtime: Option<Arc<Mutex<BamlRuntimeManager>>>,
+    runtime: Option<Arc<RwLock<BamlRuntimeManager>>>,
     bridge: Option<Arc<Mutex<QuickJSBridge>>>,
     quickjs_config: QuickJSConfig,
     register_baml_functions: bool,
     init_js: Vec<String>,
     task_store: Option<Arc<dyn TaskStoreBackend>>,
     provenance_writer: Option<Arc<dyn ProvenanceWriter>>,
+    provenance_reader: Option<Arc<dyn ProvenanceReader>>,
+    provenance_subscriber: Option<Arc<dyn ProvenanceSubscriber>>,
+    provenance_redaction: RedactionPolicy,
+    bridge_failover: BridgeFailoverConfig,
+    verbosity_authorizer: Option<Arc<dyn VerbosityAuthorizer>>,
+    outbox: Option<OutboxConfig>,
+    outbox_store: Option<Arc<dyn OutboxStore>>,
+    memory_metrics: Option<(String, Duration)>,
+    context_history: bool,
+    artifact_store: Option<Arc<dyn ArtifactStore>>,
+    kv_store: Option<Arc<dyn KvStore>>,
+    part_resolver: PartResolver,
+    agent_caller: Option<Arc<dyn AgentCaller>>,
+    method_handlers: Vec<(String, Arc<dyn MethodHandler>)>,
+    health_probes: Vec<Arc<dyn ProviderProbe>>,
+    tool_schema: Option<ToolSchemaInjection>,
+    media_policy: Option<MediaPolicy>,
+    batch_execution: a2a::BatchExecution,
+    error_mapper: Option<Arc<dyn ErrorMapper>>,
+    background: Option<BackgroundConfig>,
+    deduplication: DeduplicationConfig,
+    stream_normalizer: Option<Arc<dyn StreamNormalizer>>,
+    tenants: TenantRegistry,
+    authenticator: Option<Arc<dyn Authenticator>>,
+    access_policy: Option<Arc<dyn AccessPolicy>>,
+    tool_approver: Option<Arc<dyn ToolApprover>>,
 }
 
 impl A2aAgentBuilder {
@@ -141,17 +350,43 @@ impl A2aAgentBuilder {
             init_js: Vec::new(),
             task_store: None,
             provenance_writer: None,
+            provenance_reader: None,
+            provenance_subscriber: None,
+            provenance_redaction: RedactionPolicy::default(),
+            bridge_failover: BridgeFailoverConfig::default(),
+            verbosity_authorizer: None,
+            outbox: None,
+            outbox_store: None,
+            memory_metrics: None,
+            context_history: true,
+            artifact_store: None,
+            kv_store: None,
+            part_resolver: PartResolver::default(),
+            agent_caller: None,
+            method_handlers: Vec::new(),
+            health_probes: Vec::new(),
+            tool_schema: None,
+            media_policy: None,
+            batch_execution: a2a::BatchExecution::default(),
+            error_mapper: None,
+            background: None,
+            deduplication: DeduplicationConfig::default(),
+            stream_normalizer: None,
+            tenants: TenantRegistry::new(),
+            authenticator: None,
+            access_policy: None,
+            tool_approver: None,
         }
     }
 
     /// Provide an existing runtime manager.
     pub fn with_runtime_manager(mut self, runtime: BamlRuntimeManager) -> Self {
-        self.runtime = Some(Arc::new(Mutex::new(runtime)));
+        self.runtime = Some(Arc::new(RwLock::new(runtime)));
         self
     }
 
     /// Provide a shared runtime manager.
-    pub fn with_runtime_handle(mut self, runtime: Arc<Mutex<BamlRuntimeManager>>) -> Self {
+    pub fn with_runtime_handle(mut self, runtime: Arc<RwLock<BamlRuntimeManager>>) -> Self {
         self.runtime = Some(runtime);
         self
     }
@@ -192,6 +427,190 @@ impl A2aAgentBuilder {
         self
     }
 
+    /// Provide read access to a custom provenance store.
+    pub fn with_provenance_reader(mut self, reader: Arc<dyn ProvenanceReader>) -> Self {
+        self.provenance_reader = Some(reader);
+        self
+    }
+
+    /// Provide a live feed of a custom provenance store's events.
+    pub fn with_provenance_subscriber(mut self, subscriber: Arc<dyn ProvenanceSubscriber>) -> Self {
+        self.provenance_subscriber = Some(subscriber);
+        self
+    }
+
+    /// Redact LLM and tool payloads before they are recorded as provenance.
+    pub fn with_provenance_redaction(mut self, policy: RedactionPolicy) -> Self {
+        self.provenance_redaction = policy;
+        self
+    }
+
+    /// Configure replacement of the JS bridge after fatal engine errors.
+    pub fn with_bridge_failover(mut self, config: BridgeFailoverConfig) -> Self {
+        self.bridge_failover = config;
+        self
+    }
+
+    /// Decide which requests may raise their own log verbosity.
+    pub fn with_verbosity_authorizer(mut self, authorizer: Arc<dyn VerbosityAuthorizer>) -> Self {
+        self.verbosity_authorizer = Some(authorizer);
+        self
+    }
+
+    /// Enable the transactional outbox for side-effectful tools.
+    pub fn with_outbox(mut self, config: OutboxConfig) -> Self {
+        self.outbox = Some(config);
+        self
+    }
+
+    /// Provide the outbox store (required with a custom task store backend).
+    pub fn with_outbox_store(mut self, store: Arc<dyn OutboxStore>) -> Self {
+        self.outbox_store = Some(store);
+        self
+    }
+
+    /// Record QuickJS memory metrics under `scope` every `interval`.
+    pub fn with_memory_metrics(mut self, scope: impl Into<String>, interval: Duration) -> Self {
+        self.memory_metrics = Some((scope.into(), interval));
+        self
+    }
+
+    /// Pass message handlers the earlier messages of their context as
+    /// `params.history` (on by default).
+    pub fn with_context_history(mut self, enabled: bool) -> Self {
+        self.context_history = enabled;
+        self
+    }
+
+    /// Persist task artifacts in `store`, exposed to JS as `artifacts` and to
+    /// clients through `artifacts.get`.
+    pub fn with_artifact_store(mut self, store: Arc<dyn ArtifactStore>) -> Self {
+        self.artifact_store = Some(store);
+        self
+    }
+
+    /// Keep the state JS handlers store with `kvSet` in `store`, namespaced
+    /// per agent.
+    pub fn with_kv_store(mut self, store: Arc<dyn KvStore>) -> Self {
+        self.kv_store = Some(store);
+        self
+    }
+
+    /// Limit the file parts accepted in messages, e.g. to allow downloading
+    /// files referenced by URI.
+    pub fn with_file_policy(mut self, policy: FilePolicy) -> Self {
+        self.part_resolver = PartResolver::new(policy);
+        self
+    }
+
+    /// Resolve message parts with a custom resolver.
+    pub fn with_part_resolver(mut self, resolver: PartResolver) -> Self {
+        self.part_resolver = resolver;
+        self
+    }
+
+    /// Let JS handlers call other agents with `callAgent`, e.g. through an
+    /// [`A2aClient`](crate::a2a_client::A2aClient).
+    pub fn with_agent_caller(mut self, caller: Arc<dyn AgentCaller>) -> Self {
+        self.agent_caller = Some(caller);
+        self
+    }
+
+    /// Offer the registered tools to the BAML functions selected by
+    /// `injection`, without listing them in the schema.
+    pub fn with_tool_schema_injection(mut self, injection: ToolSchemaInjection) -> Self {
+        self.tool_schema = Some(injection);
+        self
+    }
+
+    /// Let BAML media arguments be read from the files `policy` allows.
+    pub fn with_media_policy(mut self, policy: MediaPolicy) -> Self {
+        self.media_policy = Some(policy);
+        self
+    }
+
+    /// Run the members of JSON-RPC batch requests as `execution` says;
+    /// sequentially by default.
+    pub fn with_batch_execution(mut self, execution: a2a::BatchExecution) -> Self {
+        self.batch_execution = execution;
+        self
+    }
+
+    /// Answer errors with the JSON-RPC codes and data `mapper` picks; errors
+    /// it does not map keep the default codes.
+    pub fn with_error_mapper(mut self, mapper: Arc<dyn ErrorMapper>) -> Self {
+        self.error_mapper = Some(mapper);
+        self
+    }
+
+    /// Run `message.send` requests sent with `configuration.blocking: false`
+    /// on background workers, answering them with a submitted task.
+    pub fn with_background_tasks(mut self, config: BackgroundConfig) -> Self {
+        self.background = Some(config);
+        self
+    }
+
+    /// Decide which results count as duplicates and are not stored again.
+    ///
+    /// By default a result identical to any stored before is dropped.
+    pub fn with_result_deduplication(mut self, config: DeduplicationConfig) -> Self {
+        self.deduplication = config;
+        self
+    }
+
+    /// Turn the chunks JS handlers stream into A2A stream responses with
+    /// `normalizer`, e.g. the one
+    /// [`normalizer_for`](crate::stream_normalizer::normalizer_for) the
+    /// agent's manifest `stream_format` returns.
+    ///
+    /// By default chunks must be stream responses, messages, or tasks.
+    pub fn with_stream_normalizer(mut self, normalizer: Arc<dyn StreamNormalizer>) -> Self {
+        self.stream_normalizer = Some(normalizer);
+        self
+    }
+
+    /// Serve the tenants named by requests' `tenant` param with the
+    /// credentials and rate limits `tenants` configures.
+    pub fn with_tenants(mut self, tenants: TenantRegistry) -> Self {
+        self.tenants = tenants;
+        self
+    }
+
+    /// Authenticate requests with `authenticator` before routing them.
+    pub fn with_authenticator(mut self, authenticator: Arc<dyn Authenticator>) -> Self {
+        self.authenticator = Some(authenticator);
+        self
+    }
+
+    /// Restrict the agents, methods, functions, and tools callers may use.
+    pub fn with_access_policy(mut self, policy: Arc<dyn AccessPolicy>) -> Self {
+        self.access_policy = Some(policy);
+        self
+    }
+
+    /// Decide calls to tools that require approval with `approver` before
+    /// pausing their tasks for a person to decide.
+    pub fn with_tool_approver(mut self, approver: Arc<dyn ToolApprover>) -> Self {
+        self.tool_approver = Some(approver);
+        self
+    }
+
+    /// Serve the custom JSON-RPC `method` with `handler`.
+    pub fn with_method_handler(
+        mut self,
+        method: impl Into<String>,
+        handler: Arc<dyn MethodHandler>,
+    ) -> Self {
+        self.method_handlers.push((method.into(), handler));
+        self
+    }
+
+    /// Report the reachability of an LLM provider in `agent.health`.
+    pub fn with_health_probe(mut self, probe: Arc<dyn ProviderProbe>) -> Self {
+        self.health_probes.push(probe);
+        self
+    }
+
     /// Build the agent with the configured subcomponents.
     pub async fn build(self) -> Result<A2aAgent> {
         if self.bridge.is_some() && self.runtime.is_none() {
@@ -200,84 +619,216 @@ impl A2aAgentBuilder {
             ));
         }
 
+        let mut method_handlers = MethodHandlers::new();
+        let health = self
+            .health_probes
+            .into_iter()
+            .fold(HealthCheck::new(), HealthCheck::with_probe);
+        method_handlers.insert(HEALTH_METHOD, Arc::new(health))?;
+        method_handlers.insert(FUNCTIONS_METHOD, Arc::new(FunctionListing))?;
+        // A handler registered for a built-in method replaces the built-in one
+        for (method, handler) in self.method_handlers {
+            method_handlers.insert(method, handler)?;
+        }
+
         let runtime = match self.runtime {
             Some(runtime) => runtime,
-            None => Arc::new(Mutex::new(BamlRuntimeManager::new()?)),
+            None => Arc::new(RwLock::new(BamlRuntimeManager::new()?)),
+        };
+
+        let artifact_store = {
+            let mut runtime_guard = runtime.write().await;
+            if let Some(store) = self.artifact_store {
+                runtime_guard.set_artifact_store(store);
+            }
+            if let Some(store) = self.kv_store {
+                runtime_guard.set_kv_store(store);
+            }
+            if let Some(caller) = self.agent_caller {
+                runtime_guard.set_agent_caller(caller);
+            }
+            if let Some(injection) = self.tool_schema {
+                runtime_guard.set_tool_schema_injection(injection);
+            }
+            if let Some(policy) = self.media_policy {
+                runtime_guard.set_media_policy(policy);
+            }
+            runtime_guard.artifact_store()
         };
 
         let bridge = match self.bridge {
             Some(bridge) => bridge,
             None => {
                 let bridge =
-                    QuickJSBridge::new_with_config(runtime.clone(), self.quickjs_config).await?;
+                    QuickJSBridge::new_with_config(runtime.clone(), self.quickjs_config.clone())
+                        .await?;
                 Arc::new(Mutex::new(bridge))
             }
         };
 
-        if self.register_baml_functions || !self.init_js.is_empty() {
+        // Handler code may use the A2A helpers, so they are defined first
+        let init_js: Vec<String> = std::iter::once(REQUIRE_INPUT_JS.to_string())
+            .chain(self.init_js)
+            .collect();
+        {
             let mut bridge_guard = bridge.lock().await;
             if self.register_baml_functions {
                 bridge_guard.register_baml_functions().await?;
             }
-            for code in self.init_js {
-                bridge_guard.evaluate(&code).await?;
+            for code in &init_js {
+                bridge_guard.evaluate(code).await?;
             }
         }
 
-        let (update_tx, _update_rx) = broadcast::channel(256);
+        let events = runtime.read().await.events();
+        let bridge_supervisor = Arc::new(
+            BridgeSupervisor::new(
+                bridge.clone(),
+                runtime.clone(),
+                self.quickjs_config,
+                self.register_baml_functions,
+                init_js,
+                self.bridge_failover,
+            )
+            .with_event_bus(events.clone()),
+        );
+        bridge_supervisor.prepare_standby().await?;
+
+        if let Some((scope, interval)) = self.memory_metrics {
+            spawn_memory_reporter(&bridge, scope, interval);
+        }
 
+        let mut default_outbox_store: Option<Arc<dyn OutboxStore>> = None;
+        let mut provenance_reader = self.provenance_reader;
+        let mut provenance_subscriber = self.provenance_subscriber;
         let (task_store, provenance_writer) = match (self.task_store, self.provenance_writer) {
             (Some(task_store), provenance_writer) => (task_store, provenance_writer),
-            (None, None) => {
-                let writer: Arc<dyn ProvenanceWriter> = Arc::new(InMemoryProvenanceStore::new());
-                let store: Arc<dyn TaskStoreBackend> =
-                    Arc::new(ProvenanceTaskStore::new(Some(writer.clone())));
+            (None, provenance_writer) => {
+                let writer = provenance_writer.unwrap_or_else(|| {
+                    let store = Arc::new(InMemoryProvenanceStore::new());
+                    let reader: Arc<dyn ProvenanceReader> = store.clone();
+                    provenance_reader.get_or_insert(reader);
+                    let subscriber: Arc<dyn ProvenanceSubscriber> = store.clone();
+                    provenance_subscriber.get_or_insert(subscriber);
+                    let writer: Arc<dyn ProvenanceWriter> = store;
+                    writer
+                });
+                let store = Arc::new(ProvenanceTaskStore::new(Some(writer.clone())));
+                default_outbox_store = Some(store.clone());
+                let store: Arc<dyn TaskStoreBackend> = store;
                 (store, Some(writer))
             }
-            (None, Some(writer)) => {
-                let store: Arc<dyn TaskStoreBackend> =
-                    Arc::new(ProvenanceTaskStore::new(Some(writer.clone())));
-                (store, Some(writer))
+        };
+
+        let (outbox, outbox_loop) = match self.outbox {
+            Some(config) => {
+                let store = self.outbox_store.or(default_outbox_store).ok_or_else(|| {
+                    BamlRtError::Configuration(
+                        "with_outbox requires with_outbox_store when using a custom task store backend"
+                            .to_string(),
+                    )
+                })?;
+                let dispatcher = Arc::new(OutboxDispatcher::new(store, config));
+                let dispatch_loop = Arc::new(dispatcher.start());
+                (Some(dispatcher), Some(dispatch_loop))
             }
+            None => (None, None),
         };
 
-        let emitter: Arc<dyn EventEmitter> =
-            Arc::new(BroadcastEventEmitter::new(update_tx.clone()));
+        let emitter: Arc<dyn EventEmitter> = Arc::new(BusEventEmitter::new(events.clone()));
+        runtime
+            .write()
+            .await
+            .set_task_operations(Arc::new(StoreTaskOperations::new(
+                task_store.clone(),
+                emitter.clone(),
+            )));
         let result_pipeline: Arc<dyn ResultStoragePipeline> =
             Arc::new(A2aResultPipeline::new(task_store.clone(), emitter.clone()));
-        let deduplicator: Arc<dyn ResultDeduplicator> = Arc::new(HashResultDeduplicator::new());
-        let result_pipeline: Arc<dyn ResultStoragePipeline> =
-            Arc::new(DeduplicatingPipeline::new(result_pipeline, deduplicator));
-        let response_formatter: Arc<dyn ResponseFormatter> = Arc::new(JsonRpcResponseFormatter);
-        let stream_normalizer: Arc<dyn StreamNormalizer> = Arc::new(A2aStreamNormalizer);
+        let result_pipeline: Arc<dyn ResultStoragePipeline> = if self.deduplication.enabled {
+            let deduplicator: Arc<dyn ResultDeduplicator> =
+                Arc::new(HashResultDeduplicator::with_config(self.deduplication));
+            Arc::new(DeduplicatingPipeline::new(result_pipeline, deduplicator))
+        } else {
+            result_pipeline
+        };
+        let mut response_formatter: Arc<dyn ResponseFormatter> = Arc::new(JsonRpcResponseFormatter);
+        if let Some(mapper) = self.error_mapper {
+            response_formatter = Arc::new(ErrorMappingFormatter::new(response_formatter, mapper));
+        }
+        let stream_normalizer = self
+            .stream_normalizer
+            .unwrap_or_else(|| Arc::new(A2aStreamNormalizer));
         let repository: Arc<dyn TaskRepository> = task_store.clone();
         let recorder: Arc<dyn TaskEventRecorder> = task_store.clone();
         let update_queue: Arc<dyn TaskUpdateQueue> = task_store.clone();
-        let task_handler: Arc<dyn TaskHandler> = Arc::new(DefaultTaskHandler::new(
-            repository,
-            recorder,
-            update_queue,
-            bridge.clone(),
-            emitter.clone(),
-        ));
+        let cancellations = Arc::new(TaskCancellations::new());
+        let task_handler: Arc<dyn TaskHandler> = Arc::new(
+            DefaultTaskHandler::new(
+                repository,
+                recorder,
+                update_queue,
+                bridge.clone(),
+                emitter.clone(),
+            )
+            .with_cancellations(cancellations.clone()),
+        );
         let js_invoker: Arc<dyn crate::request_router::JsInvoker> = Arc::new(QuickJsInvoker::new(
             bridge.clone(),
             stream_normalizer.clone(),
         ));
-        let request_router: Arc<dyn RequestRouter> = Arc::new(MethodBasedRouter::new(
+        let mut approvals = ApprovalGate::new();
+        if let Some(approver) = self.tool_approver {
+            approvals = approvals.with_approver(approver);
+        }
+        if let Some(writer) = provenance_writer.clone() {
+            approvals = approvals.with_recorder(Arc::new(ProvenanceApprovals::new(writer)));
+        }
+        let approvals = Arc::new(approvals);
+        runtime.write().await.set_approval_gate(approvals.clone());
+        let mut method_router = MethodBasedRouter::new(
             task_handler.clone(),
-            js_invoker,
+            js_invoker.clone(),
             result_pipeline.clone(),
-        ));
+        )
+        .with_part_resolver(Arc::new(self.part_resolver))
+        .with_input_resume(task_store.clone())
+        .with_task_cancellations(cancellations.clone())
+        .with_tool_approvals(approvals);
+        if self.context_history {
+            method_router = method_router.with_context_history(task_store.clone());
+        }
+        if let Some(store) = artifact_store {
+            method_router = method_router.with_artifact_store(store);
+        }
+        if let Some(config) = self.background {
+            let executor = BackgroundExecutor::new(
+                config,
+                js_invoker.clone(),
+                result_pipeline.clone(),
+                task_store.clone(),
+            )
+            .with_task_cancellations(cancellations);
+            method_router = method_router.with_background_executor(Arc::new(executor));
+        }
+        let request_router: Arc<dyn RequestRouter> = Arc::new(method_router);
         let error_classifier: Arc<dyn ErrorClassifier> = Arc::new(A2aErrorClassifier);
+        let verbosity_authorizer = self
+            .verbosity_authorizer
+            .unwrap_or_else(|| Arc::new(DenyVerbosityOverrides));
 
         if let Some(writer) = provenance_writer.clone() {
-            let runtime_guard = runtime.lock().await;
+            let runtime_guard = runtime.read().await;
+            let redaction = self.provenance_redaction;
             runtime_guard
-                .register_llm_interceptor(ProvenanceInterceptor::new(writer.clone()))
+                .register_llm_interceptor(
+                    ProvenanceInterceptor::new(writer.clone()).with_redaction(redaction.clone()),
+                )
                 .await;
             runtime_guard
-                .register_tool_interceptor(ProvenanceInterceptor::new(writer))
+                .register_tool_interceptor(
+                    ProvenanceInterceptor::new(writer).with_redaction(redaction),
+                )
                 .await;
         }
         Ok(A2aAgent {
@@ -285,10 +836,21 @@ impl A2aAgentBuilder {
             bridge,
             task_store,
             provenance_writer,
+            provenance_reader,
+            provenance_subscriber,
             response_formatter,
             request_router,
             error_classifier,
-            update_tx,
+            events,
+            bridge_supervisor,
+            verbosity_authorizer,
+            outbox,
+            _outbox_loop: outbox_loop,
+            method_handlers: Arc::new(method_handlers),
+            batch_execution: self.batch_execution,
+            tenants: Arc::new(self.tenants),
+            authenticator: self.authenticator,
+            access_policy: self.access_policy,
         })
     }
 }
@@ -301,29 +863,150 @@ impl Default for A2aAgentBuilder {
 
 /// Trait for alternative, non-standard A2A transports.
 ///
-/// The transport receives raw JSON and returns JSON-RPC responses.
+/// The transport receives raw JSON and returns JSON-RPC responses. A batch
+/// (an array of requests) is answered with a single batch response array.
 #[async_trait(?Send)]
 pub trait A2aRequestHandler: Send + Sync {
     async fn handle_a2a(&self, request: Value) -> Result<Vec<Value>>;
+
+    /// Handle a request along with what the transport knows about it, such
+    /// as the headers it carried credentials in.
+    async fn handle_a2a_with_transport(
+        &self,
+        request: Value,
+        transport: &TransportMetadata,
+    ) -> Result<Vec<Value>> {
+        let _ = transport;
+        self.handle_a2a(request).await
+    }
+
+    /// Handle a request, passing each response to `responses` as soon as it
+    /// is ready, so a client can read the first chunks of a stream while
+    /// later ones are still being produced.
+    ///
+    /// By default the responses are passed on together once
+    /// [`handle_a2a_with_transport`](Self::handle_a2a_with_transport)
+    /// returns them.
+    async fn handle_a2a_streaming(
+        &self,
+        request: Value,
+        transport: &TransportMetadata,
+        responses: mpsc::UnboundedSender<Value>,
+    ) -> Result<()> {
+        for response in self.handle_a2a_with_transport(request, transport).await? {
+            // The client may have stopped reading
+            let _ = responses.send(response);
+        }
+        Ok(())
+    }
 }
 
 #[async_trait(?Send)]
 impl A2aRequestHandler for A2aAgent {
     async fn handle_a2a(&self, request: Value) -> Result<Vec<Value>> {
+        self.handle_a2a_with_transport(request, &TransportMetadata::default())
+            .await
+    }
+
+    async fn handle_a2a_with_transport(
+        &self,
+        request: Value,
+        transport: &TransportMetadata,
+    ) -> Result<Vec<Value>> {
+        let Value::Array(requests) = request else {
+            return self.handle_request(request, transport).await;
+        };
+        let batch = a2a::handle_batch(requests, self.batch_execution, |request| async move {
+            let request_id = a2a::extract_jsonrpc_id(&request);
+            self.handle_request(request, transport)
+                .await
+                .unwrap_or_else(|err| vec![self.response_formatter.format_error(request_id, &err)])
+        })
+        .await;
+        Ok(batch.into_iter().collect())
+    }
+}
+
+impl A2aAgent {
+    /// Serve a single JSON-RPC request, leaving notifications unanswered.
+    async fn handle_request(
+        &self,
+        request: Value,
+        transport: &TransportMetadata,
+    ) -> Result<Vec<Value>> {
+        if !a2a::is_notification(&request) {
+            return self.serve_request(request, transport).await;
+        }
+        let method = request["method"].as_str().unwrap_or_default().to_string();
+        let responses = self.serve_request(request, transport).await?;
+        Ok(a2a::discard_notification_responses(&method, responses))
+    }
+
+    async fn serve_request(
+        &self,
+        mut request: Value,
+        transport: &TransportMetadata,
+    ) -> Result<Vec<Value>> {
+        match self.authenticate(&mut request, transport).await {
+            Ok(None) => self.serve_authorized(request).await,
+            Ok(caller) => auth::with_caller(caller, self.serve_authorized(request)).await,
+            Err(err) => {
+                let request_id = a2a::extract_jsonrpc_id(&request);
+                let method = request["method"].as_str().unwrap_or_default().to_string();
+                let outcome = Err(err);
+                self.record_outcome(&method, false, Duration::ZERO, &outcome);
+                Ok(self.format_outcome(request_id, outcome))
+            }
+        }
+    }
+
+    /// Authenticate a request and check that its caller may make it.
+    ///
+    /// The request's `auth` param is removed and, when the agent
+    /// authenticates, `metadata.principal` is replaced with the principal
+    /// it established. The tenant the request names must be one the
+    /// principal holds, and defaults to its own. Returns `None` when the
+    /// agent neither authenticates nor restricts its callers.
+    async fn authenticate(
+        &self,
+        request: &mut Value,
+        transport: &TransportMetadata,
+    ) -> Result<Option<Caller>> {
+        // Only an authenticator may say who the caller is
+        stamp_principal(request, None);
+        if self.authenticator.is_none() && self.access_policy.is_none() {
+            return Ok(None);
+        }
+        let policy = self
+            .access_policy
+            .clone()
+            .unwrap_or_else(|| Arc::new(AllowAll));
+        authorize(request, transport, self.authenticator.as_deref(), policy)
+            .await
+            .map(Some)
+    }
+
+    async fn serve_authorized(&self, request: Value) -> Result<Vec<Value>> {
         let request_id = a2a::extract_jsonrpc_id(&request);
+        if let Some(handler) = request
+            .get("method")
+            .and_then(Value::as_str)
+            .and_then(|method| self.method_handlers.get(method))
+        {
+            return Ok(self.handle_custom_method(handler, request).await);
+        }
         let parsed_request = match a2a::A2aRequest::from_value(request) {
             Ok(parsed) => parsed,
-            Err(err) => {
-                let formatter = JsonRpcResponseFormatter;
-                return Ok(vec![formatter.format_error(request_id, &err)]);
-            }
+            Err(err) => return Ok(vec![self.response_formatter.format_error(request_id, &err)]),
         };
         use baml_rt_core::ids::CorrelationId;
-        let correlation_id = parsed_request
+        let mut correlation_id = parsed_request
             .correlation_id()
             .map(|s| CorrelationId::from(s))
             .unwrap_or_else(correlation::generate_correlation_id);
 
+        let _verbosity_guard = self.elevate_verbosity(&parsed_request, &mut correlation_id);
+
         let span = if parsed_request.is_stream {
             spans::a2a_stream(parsed_request.method.as_str(), correlation_id.as_str())
         } else {
@@ -338,32 +1021,126 @@ impl A2aRequestHandler for A2aAgent {
             .context_id
             .clone()
             .unwrap_or_else(context::generate_context_id);
-        let outcome = correlation::with_correlation_id(correlation_id, async move {
-            context::with_context_id(request_context_id, async move {
-                self.request_router.route(&parsed_request).await
+        let request_deadline = parsed_request.deadline();
+        let request_metadata = context::RequestMetadata::default();
+        if let Some(message_id) = parsed_request.message_id() {
+            request_metadata.set(context::MESSAGE_ID_KEY, Value::from(message_id));
+        }
+        let request_tenant = match self.tenants.resolve(parsed_request.tenant()) {
+            Ok(request_tenant) => request_tenant,
+            Err(err) => return Ok(vec![self.response_formatter.format_error(request_id, &err)]),
+        };
+        let admission = request_tenant
+            .as_ref()
+            .map_or(Ok(()), |request_tenant| self.tenants.admit(request_tenant));
+        let outcome = tenant::with_tenant(request_tenant.clone(), async move {
+            admission?;
+            correlation::with_correlation_id(correlation_id, async move {
+                context::with_context(request_context_id, request_metadata, async move {
+                    match request_deadline {
+                        Some(request_deadline) => {
+                            deadline::with_deadline(
+                                request_deadline,
+                                "A2A request",
+                                self.route_with_failover(&parsed_request),
+                            )
+                            .await
+                        }
+                        None => self.route_with_failover(&parsed_request).await,
+                    }
+                })
+                .await
             })
             .await
         })
         .await;
 
-        let duration = start.elapsed();
-        match &outcome {
+        tenant::with_tenant_sync(request_tenant, || {
+            self.record_outcome(method.as_str(), is_stream, start.elapsed(), &outcome)
+        });
+        Ok(self.format_outcome(request_id, outcome))
+    }
+
+    /// Serve a custom JSON-RPC method registered with
+    /// [`A2aAgentBuilder::with_method_handler`].
+    async fn handle_custom_method(
+        &self,
+        handler: Arc<dyn MethodHandler>,
+        request: Value,
+    ) -> Vec<Value> {
+        let request_id = a2a::extract_jsonrpc_id(&request);
+        let request: JSONRPCRequest = match serde_json::from_value(request) {
+            Ok(request) => request,
+            Err(err) => {
+                return vec![
+                    self.response_formatter
+                        .format_error(request_id, &BamlRtError::Json(err)),
+                ];
+            }
+        };
+        if request.jsonrpc != "2.0" {
+            let err = BamlRtError::InvalidArgument(format!(
+                "Unsupported jsonrpc version: {}",
+                request.jsonrpc
+            ));
+            return vec![self.response_formatter.format_error(request_id, &err)];
+        }
+
+        let correlation_id = request_id
+            .as_ref()
+            .map(|id| baml_rt_core::ids::CorrelationId::from(a2a::id_to_string(id)))
+            .unwrap_or_else(correlation::generate_correlation_id);
+        let span = spans::a2a_request(&request.method, correlation_id.as_str());
+        let _guard = span.enter();
+        let start = std::time::Instant::now();
+        let method = request.method.clone();
+        let method_request = MethodRequest {
+            id: request_id.clone(),
+            method: request.method,
+            params: request.params.unwrap_or(Value::Null),
+        };
+        let outcome = correlation::with_correlation_id(correlation_id, async move {
+            context::with_context_id(
+                context::generate_context_id(),
+                handler.handle(self, method_request),
+            )
+            .await
+        })
+        .await;
+
+        let is_stream = matches!(outcome, Ok(a2a::A2aOutcome::Stream(_)));
+        self.record_outcome(&method, is_stream, start.elapsed(), &outcome);
+        self.format_outcome(request_id, outcome)
+    }
+
+    /// Record request metrics for a finished request.
+    fn record_outcome(
+        &self,
+        method: &str,
+        is_stream: bool,
+        duration: Duration,
+        outcome: &Result<a2a::A2aOutcome>,
+    ) {
+        match outcome {
             Ok(a2a::A2aOutcome::Stream(chunks)) => {
-                metrics::record_a2a_request(method.as_str(), "success", is_stream, duration);
-                metrics::record_a2a_stream_chunks(method.as_str(), chunks.len());
+                metrics::record_a2a_request(method, "success", is_stream, duration);
+                metrics::record_a2a_stream_chunks(method, chunks.len());
             }
-            Ok(_) => metrics::record_a2a_request(method.as_str(), "success", is_stream, duration),
+            Ok(_) => metrics::record_a2a_request(method, "success", is_stream, duration),
             Err(err) => {
-                metrics::record_a2a_request(method.as_str(), "error", is_stream, duration);
-                metrics::record_a2a_error(
-                    method.as_str(),
-                    self.error_classifier.classify(err),
-                    is_stream,
-                );
+                metrics::record_a2a_request(method, "error", is_stream, duration);
+                metrics::record_a2a_error(method, self.error_classifier.classify(err), is_stream);
             }
         }
+    }
 
-        let responses = match outcome {
+    /// JSON-RPC responses for a finished request.
+    fn format_outcome(
+        &self,
+        request_id: Option<JSONRPCId>,
+        outcome: Result<a2a::A2aOutcome>,
+    ) -> Vec<Value> {
+        match outcome {
             Ok(a2a::A2aOutcome::Response(result)) => {
                 vec![self.response_formatter.format_success(request_id, result)]
             }
@@ -371,14 +1148,158 @@ impl A2aRequestHandler for A2aAgent {
                 self.response_formatter.format_stream(request_id, chunks)
             }
             Err(err) => vec![self.response_formatter.format_error(request_id, &err)],
+        }
+    }
+
+    // Result storage is handled by ResultStoragePipeline.
+
+    /// Handle a request resolved by a [`RoutingTable`](crate::method_routing::RoutingTable).
+    ///
+    /// A2A methods go through [`A2aRequestHandler::handle_a2a`]; function calls
+    /// invoke the BAML or JS function directly and are wrapped as JSON-RPC
+    /// responses.
+    pub async fn handle_routed(&self, routed: RoutedRequest) -> Result<Vec<Value>> {
+        self.handle_routed_with_transport(routed, &TransportMetadata::default())
+            .await
+    }
+
+    /// Handle a routed request, authenticating it with `transport` and
+    /// checking that its caller may use the agent it was routed to.
+    pub async fn handle_routed_with_transport(
+        &self,
+        mut routed: RoutedRequest,
+        transport: &TransportMetadata,
+    ) -> Result<Vec<Value>> {
+        let transport = transport.clone().with_agent(routed.agent.clone());
+        let (function_name, target) = match &routed.handler {
+            RouteHandler::A2a => {
+                return self
+                    .handle_a2a_with_transport(routed.request, &transport)
+                    .await;
+            }
+            RouteHandler::BamlFunction(name) if routed.is_stream => {
+                (format!("{}Stream", name), name.clone())
+            }
+            RouteHandler::BamlFunction(name) | RouteHandler::JsFunction(name) => {
+                (name.clone(), name.clone())
+            }
+        };
+        let notification = a2a::is_notification(&routed.request);
+        if let Some(params) = routed.params.as_object_mut() {
+            params.remove(AUTH_PARAM);
+        }
+        let caller = self
+            .authenticate(&mut routed.request, &transport)
+            .await
+            .and_then(|caller| {
+                if let Some(caller) = &caller {
+                    caller.check(Access::Function(&target))?;
+                }
+                Ok(caller)
+            });
+        // The function is called with the params, not the request, so they
+        // must carry the authenticated principal too
+        if let Ok(caller) = &caller {
+            stamp_params_principal(
+                &mut routed.params,
+                caller.as_ref().and_then(Caller::principal),
+            );
+        }
+
+        use baml_rt_core::ids::CorrelationId;
+        let correlation_id = routed
+            .id
+            .as_ref()
+            .map(|id| CorrelationId::from(a2a::id_to_string(id)))
+            .unwrap_or_else(correlation::generate_correlation_id);
+
+        let bridge = self.bridge.clone();
+        let params = routed.params;
+        let invoke = correlation::with_correlation_id(correlation_id, async move {
+            let mut bridge = bridge.lock().await;
+            bridge.invoke_js_function(&function_name, params).await
+        });
+        let outcome = match caller {
+            Ok(None) => invoke.await,
+            Ok(caller) => auth::with_caller(caller, invoke).await,
+            Err(err) => Err(err),
         };
 
+        let responses = match outcome {
+            Ok(Value::Array(chunks)) if routed.is_stream => {
+                self.response_formatter.format_stream(routed.id, chunks)
+            }
+            Ok(result) if routed.is_stream => self
+                .response_formatter
+                .format_stream(routed.id, vec![result]),
+            Ok(result) => vec![self.response_formatter.format_success(routed.id, result)],
+            Err(err) => vec![self.response_formatter.format_error(routed.id, &err)],
+        };
+        if notification {
+            let method = routed.request["method"].as_str().unwrap_or_default();
+            return Ok(a2a::discard_notification_responses(method, responses));
+        }
         Ok(responses)
     }
-}
 
-impl A2aAgent {
-    // Result storage is handled by ResultStoragePipeline.
+    /// Apply the request's verbosity override if it asked for one and is allowed to.
+    ///
+    /// Only authenticated callers may raise verbosity. The override is keyed
+    /// by a correlation ID generated for the request, which replaces the one
+    /// taken from its JSON-RPC ID, since other requests may reuse that ID.
+    fn elevate_verbosity(
+        &self,
+        request: &a2a::A2aRequest,
+        correlation_id: &mut baml_rt_core::ids::CorrelationId,
+    ) -> Option<verbosity::VerbosityGuard> {
+        let requested = requested_verbosity(request)?;
+        if auth::current_principal().is_none() || !self.verbosity_authorizer.authorize(request) {
+            tracing::warn!(
+                method = request.method.as_str(),
+                correlation_id = correlation_id.as_str(),
+                "Ignoring unauthorized verbosity override"
+            );
+            return None;
+        }
+        let unique = correlation::generate_correlation_id();
+        tracing::info!(
+            method = request.method.as_str(),
+            correlation_id = correlation_id.as_str(),
+            verbose_correlation_id = unique.as_str(),
+            verbosity = ?requested,
+            "Raising log verbosity for request"
+        );
+        *correlation_id = unique.clone();
+        Some(verbosity::elevate(unique, requested))
+    }
+
+    /// Route a request, replacing the JS bridge if it fails fatally.
+    ///
+    /// Requests that are safe to repeat are replayed once on the replacement
+    /// bridge; everything else surfaces the original error.
+    async fn route_with_failover(&self, request: &a2a::A2aRequest) -> Result<a2a::A2aOutcome> {
+        let generation = self.bridge_supervisor.generation();
+        let err = match self.request_router.route(request).await {
+            Err(err) if is_fatal_engine_error(&err) => err,
+            outcome => return outcome,
+        };
+
+        let recovered = self.bridge_supervisor.recover(generation, &err).await;
+        if !recovered
+            || !self.bridge_supervisor.config().replay_idempotent
+            || !request.is_replay_safe()
+        {
+            return Err(err);
+        }
+
+        tracing::info!(
+            method = request.method.as_str(),
+            "Replaying request on replacement QuickJS bridge"
+        );
+        self.bridge_supervisor
+            .record_replay(request.method.as_str());
+        self.request_router.route(request).await
+    }
 }
 
 struct JsToolExecutor {
@@ -396,16 +1317,17 @@ impl ToolExecutor for JsToolExecutor {
             handle.block_on(async move {
                 let mut bridge = bridge.lock().await;
                 let result = bridge.invoke_js_tool(&tool_name, args).await?;
-                if let Some(error) = result.get("error").and_then(Value::as_str) {
-                    return Err(BamlRtError::QuickJs(error.to_string()));
+                if result.get("error").and_then(Value::as_str).is_some()
+                    && let Some(exception) = JsException::from_error_object(&result)
+                {
+                    return Err(BamlRtError::JsException(exception));
                 }
                 Ok(result)
             })
         })
         .await
-        .map_err(|err| BamlRtError::QuickJsWithSource {
-            context: "js tool join error".to_string(),
-            source: Box::new(err),
+        .map_err(|err| BamlRtError::EngineFailure {
+            reason: format!("js tool task failed: {}", err),
         })?
     }
 }
@@ -438,7 +1360,7 @@ mod tests {
 
         let runtime = agent.runtime();
         let result = {
-            let runtime = runtime.lock().await;
+            let runtime = runtime.read().await;
             runtime
                 .execute_tool("add_js", json!({"a": 2, "b": 3}))
                 .await
@@ -447,4 +1369,47 @@ mod tests {
 
         assert_eq!(result.get("sum").and_then(|v| v.as_i64()), Some(5));
     }
+
+    #[tokio::test]
+    async fn js_tools_can_be_replaced_and_unregistered() {
+        let agent = A2aAgent::builder().build().await.expect("agent build");
+        for version in [1, 2] {
+            agent
+                .register_js_tool(
+                    "version_js",
+                    format!("Returns version {}", version),
+                    json!({ "type": "object" }),
+                    format!("(args) => ({{ version: {} }})", version),
+                )
+                .await
+                .expect("register js tool");
+        }
+
+        let runtime = agent.runtime();
+        let result = runtime
+            .read()
+            .await
+            .execute_tool("js:version_js", json!({}))
+            .await
+            .expect("execute tool");
+        assert_eq!(result, json!({ "version": 2 }));
+
+        agent
+            .unregister_tool("version_js")
+            .await
+            .expect("unregister tool");
+        assert!(
+            runtime
+                .read()
+                .await
+                .execute_tool("version_js", json!({}))
+                .await
+                .is_err()
+        );
+        let global = agent
+            .evaluate_js("typeof globalThis.version_js")
+            .await
+            .expect("evaluate");
+        assert_eq!(global, json!("undefined"));
+    }
 }
@@ -0,0 +1,174 @@
+//! Tasks that pause while a tool call waits for approval.
+//!
+//! A JS handler that calls a tool marked `requires_approval` gets an error
+//! back, and the [`ApprovalGate`](baml_rt_tools::ApprovalGate) keeps the call
+//! as pending. The router then pauses the task in
+//! `TASK_STATE_INPUT_REQUIRED`, with the proposed call as a data part of the
+//! status message, and keeps it, along with the params of the request that
+//! led to it, in the task's metadata under [`PENDING_APPROVAL_KEY`].
+//!
+//! The next message naming the task decides the call, with metadata
+//! `approved: true` or `false`, or the text `approve` or `deny`. Approving
+//! handles the original request again, and this time the call runs; denying
+//! cancels the task. Decisions are recorded in provenance as
+//! `ToolApprovalDecided` events.
+
+use crate::a2a;
+use crate::a2a_store::TaskState;
+use crate::a2a_types::{ROLE_AGENT, Task};
+use async_trait::async_trait;
+use baml_rt_core::ids::{ContextId, TaskId};
+use baml_rt_provenance::{ProvEvent, ProvenanceWriter};
+use baml_rt_tools::{ApprovalDecision, ApprovalRecorder, ProposedToolCall};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::sync::Arc;
+
+/// Task metadata key holding the [`PendingApproval`] of a paused task.
+pub const PENDING_APPROVAL_KEY: &str = "pendingApproval";
+
+/// A tool call a paused task waits on
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PendingApproval {
+    pub call: ProposedToolCall,
+    /// Params of the request whose handling proposed the call, without the
+    /// parts and history resolved for it
+    pub params: Value,
+}
+
+impl PendingApproval {
+    pub fn new(call: ProposedToolCall, request: &a2a::A2aRequest) -> Self {
+        let mut params = request.params.clone();
+        if let Value::Object(params) = &mut params {
+            params.remove("parts");
+            params.remove("history");
+        }
+        Self { call, params }
+    }
+
+    /// The call `task` waits on, if it was paused for one
+    pub fn of(task: &Task) -> Option<Self> {
+        let pending = task.metadata.as_ref()?.get(PENDING_APPROVAL_KEY)?;
+        serde_json::from_value(pending.clone()).ok()
+    }
+
+    /// The task a handler's result is replaced with while the call waits
+    pub fn paused_task(&self) -> Value {
+        let prompt = format!(
+            "Tool {} needs approval. Reply 'approve' or 'deny'.",
+            self.call.tool
+        );
+        json!({
+            "task": {
+                "status": {
+                    "state": TaskState::InputRequired.as_str(),
+                    "message": {
+                        "messageId": format!("{}-prompt", self.call.id),
+                        "role": ROLE_AGENT,
+                        "parts": [{ "text": prompt }, { "data": self.call }],
+                    },
+                },
+                "metadata": { PENDING_APPROVAL_KEY: self },
+            }
+        })
+    }
+
+    /// `request` with the original params in place of the reply's, its
+    /// message filed under `task_id` in `context_id`
+    pub fn replay_request(
+        &self,
+        request: &a2a::A2aRequest,
+        task_id: &TaskId,
+        context_id: &ContextId,
+    ) -> a2a::A2aRequest {
+        let mut request = request.clone();
+        request.params = self.params.clone();
+        request.params["message"]["taskId"] = json!(task_id);
+        request.params["message"]["contextId"] = json!(context_id);
+        request.context_id = Some(context_id.clone());
+        request
+    }
+}
+
+/// The decision a reply message states, if any
+pub fn decision_in(message: &Value) -> Option<ApprovalDecision> {
+    let approved = match message.pointer("/metadata/approved") {
+        Some(approved) => approved.as_bool()?,
+        None => {
+            let text = message["parts"]
+                .as_array()?
+                .iter()
+                .find_map(|part| part["text"].as_str())?;
+            match text.trim().to_ascii_lowercase().as_str() {
+                "approve" | "approved" | "yes" => true,
+                "deny" | "denied" | "no" => false,
+                _ => return None,
+            }
+        }
+    };
+    Some(if approved {
+        ApprovalDecision::Approve
+    } else {
+        ApprovalDecision::Deny
+    })
+}
+
+/// Records approval decisions as provenance events
+pub struct ProvenanceApprovals {
+    writer: Arc<dyn ProvenanceWriter>,
+}
+
+impl ProvenanceApprovals {
+    pub fn new(writer: Arc<dyn ProvenanceWriter>) -> Self {
+        Self { writer }
+    }
+}
+
+#[async_trait]
+impl ApprovalRecorder for ProvenanceApprovals {
+    async fn record_decision(
+        &self,
+        call: &ProposedToolCall,
+        decision: ApprovalDecision,
+        decided_by: &str,
+    ) {
+        let event = ProvEvent::tool_approval_decided(
+            call.context_id.clone(),
+            None,
+            call.id.clone(),
+            call.tool.clone(),
+            call.function.clone(),
+            call.args.clone(),
+            decision == ApprovalDecision::Approve,
+            decided_by.to_string(),
+        );
+        if let Err(e) = self.writer.add_event(event).await {
+            tracing::warn!(error = %e, "Failed to record tool approval decision");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn replies_state_decisions_in_metadata_or_text() {
+        let reply = |message: Value| decision_in(&message);
+        assert_eq!(
+            reply(json!({ "parts": [{ "text": " Approve " }] })),
+            Some(ApprovalDecision::Approve)
+        );
+        assert_eq!(
+            reply(json!({ "parts": [{ "data": {} }, { "text": "deny" }] })),
+            Some(ApprovalDecision::Deny)
+        );
+        assert_eq!(
+            reply(json!({ "parts": [{ "text": "approve" }], "metadata": { "approved": false } })),
+            Some(ApprovalDecision::Deny)
+        );
+        assert_eq!(reply(json!({ "parts": [{ "text": "maybe" }] })), None);
+        assert_eq!(reply(json!({ "metadata": { "approved": "yes" } })), None);
+    }
+}
@@ -0,0 +1,481 @@
+//! Authentication and access control for A2A requests.
+//!
+//! Before routing a request the agent asks its [`Authenticator`] who sent
+//! it, using the transport's metadata (e.g. an `Authorization` header) or
+//! the request's `auth` param. The resulting [`Caller`] is checked against
+//! the agent's [`AccessPolicy`] for the agent and method it asked for, and
+//! the request is served in the caller's scope so that BAML functions and
+//! tools are checked as they are invoked.
+//!
+//! Failures are answered with [`UNAUTHENTICATED_CODE`] or [`FORBIDDEN_CODE`].
+//! Both are implementation-defined server errors outside the range A2A
+//! reserves for its own errors (-32001 to -32007).
+//!
+//! [`Caller`]: baml_rt_core::auth::Caller
+
+use crate::tenancy::TENANT_PARAM;
+use crate::verbosity::PRINCIPAL_METADATA_KEY;
+use async_trait::async_trait;
+use baml_rt_core::auth::{Access, AccessPolicy, Caller, Principal};
+use baml_rt_core::ids::TenantId;
+use baml_rt_core::{BamlRtError, Result};
+use serde_json::{Map, Value};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+/// JSON-RPC error code for requests without valid credentials
+pub const UNAUTHENTICATED_CODE: i64 = -32010;
+
+/// JSON-RPC error code for requests the caller may not make
+pub const FORBIDDEN_CODE: i64 = -32011;
+
+/// Request param carrying credentials, for transports without headers
+pub const AUTH_PARAM: &str = "auth";
+
+/// What a transport knows about a request besides its JSON-RPC body
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TransportMetadata {
+    headers: HashMap<String, String>,
+    agent: Option<String>,
+}
+
+impl TransportMetadata {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a header, e.g. an HTTP header or a NATS message header.
+    /// Names are case-insensitive.
+    pub fn with_header(mut self, name: impl AsRef<str>, value: impl Into<String>) -> Self {
+        self.headers
+            .insert(name.as_ref().to_ascii_lowercase(), value.into());
+        self
+    }
+
+    /// Name the agent the request was addressed to
+    pub fn with_agent(mut self, agent: impl Into<String>) -> Self {
+        self.agent = Some(agent.into());
+        self
+    }
+
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .get(&name.to_ascii_lowercase())
+            .map(String::as_str)
+    }
+
+    pub fn agent(&self) -> Option<&str> {
+        self.agent.as_deref()
+    }
+}
+
+/// A request to authenticate
+#[derive(Debug, Clone, Copy)]
+pub struct AuthRequest<'a> {
+    /// JSON-RPC method, e.g. `message.send`
+    pub method: &'a str,
+    /// The request's `auth` param, which is removed before routing
+    pub credentials: Option<&'a Value>,
+    pub transport: &'a TransportMetadata,
+}
+
+impl AuthRequest<'_> {
+    /// Bearer token from the `Authorization` header, or else from the `auth`
+    /// param as either `"<token>"` or `{"token": "<token>"}`
+    pub fn bearer_token(&self) -> Option<&str> {
+        if let Some(header) = self.transport.header("authorization") {
+            return header
+                .split_once(' ')
+                .filter(|(scheme, _)| scheme.eq_ignore_ascii_case("bearer"))
+                .map(|(_, token)| token.trim());
+        }
+        match self.credentials? {
+            Value::String(token) => Some(token),
+            credentials => credentials.get("token").and_then(Value::as_str),
+        }
+    }
+}
+
+/// Establishes who sent a request
+#[async_trait]
+pub trait Authenticator: Send + Sync {
+    /// The request's principal, `None` for an anonymous request, or
+    /// [`BamlRtError::Unauthenticated`] when its credentials are invalid
+    async fn authenticate(&self, request: &AuthRequest<'_>) -> Result<Option<Principal>>;
+}
+
+/// Authenticates requests by a fixed set of bearer tokens
+#[derive(Debug, Clone, Default)]
+pub struct BearerTokens {
+    tokens: HashMap<String, Principal>,
+}
+
+impl BearerTokens {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Authenticate requests bearing `token` as `principal`
+    pub fn with_token(mut self, token: impl Into<String>, principal: Principal) -> Self {
+        self.tokens.insert(token.into(), principal);
+        self
+    }
+}
+
+#[async_trait]
+impl Authenticator for BearerTokens {
+    async fn authenticate(&self, request: &AuthRequest<'_>) -> Result<Option<Principal>> {
+        let Some(token) = request.bearer_token() else {
+            return Ok(None);
+        };
+        match self.tokens.get(token) {
+            Some(principal) => Ok(Some(principal.clone())),
+            None => Err(BamlRtError::Unauthenticated(
+                "Invalid bearer token".to_string(),
+            )),
+        }
+    }
+}
+
+/// What one caller may use
+///
+/// Each kind of access is unrestricted until it is limited to a list of
+/// names, so `Grants::all().with_tools(["search"])` allows every agent,
+/// method, and function but only the `search` tool.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Grants {
+    agents: Option<HashSet<String>>,
+    methods: Option<HashSet<String>>,
+    functions: Option<HashSet<String>>,
+    tools: Option<HashSet<String>>,
+}
+
+impl Grants {
+    /// Allow everything
+    pub fn all() -> Self {
+        Self::default()
+    }
+
+    /// Allow nothing
+    pub fn none() -> Self {
+        Self {
+            agents: Some(HashSet::new()),
+            methods: Some(HashSet::new()),
+            functions: Some(HashSet::new()),
+            tools: Some(HashSet::new()),
+        }
+    }
+
+    pub fn with_agents<I, S>(mut self, agents: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.agents = Some(agents.into_iter().map(Into::into).collect());
+        self
+    }
+
+    pub fn with_methods<I, S>(mut self, methods: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.methods = Some(methods.into_iter().map(Into::into).collect());
+        self
+    }
+
+    pub fn with_functions<I, S>(mut self, functions: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.functions = Some(functions.into_iter().map(Into::into).collect());
+        self
+    }
+
+    pub fn with_tools<I, S>(mut self, tools: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.tools = Some(tools.into_iter().map(Into::into).collect());
+        self
+    }
+
+    pub fn allows(&self, access: &Access<'_>) -> bool {
+        let (granted, name) = match access {
+            Access::Agent(name) => (&self.agents, name),
+            Access::Method(name) => (&self.methods, name),
+            Access::Function(name) => (&self.functions, name),
+            Access::Tool(name) => (&self.tools, name),
+        };
+        granted.as_ref().is_none_or(|names| names.contains(*name))
+    }
+}
+
+/// An [`AccessPolicy`] granting each principal what it is configured with
+///
+/// Principals without grants of their own get the grants for authenticated
+/// callers, which allow everything unless replaced. Anonymous callers are
+/// allowed nothing unless given grants.
+#[derive(Debug, Clone)]
+pub struct AccessRules {
+    principals: HashMap<String, Grants>,
+    authenticated: Grants,
+    anonymous: Grants,
+}
+
+impl AccessRules {
+    pub fn new() -> Self {
+        Self {
+            principals: HashMap::new(),
+            authenticated: Grants::all(),
+            anonymous: Grants::none(),
+        }
+    }
+
+    /// Grant the principal with id `principal` exactly `grants`
+    pub fn with_principal(mut self, principal: impl Into<String>, grants: Grants) -> Self {
+        self.principals.insert(principal.into(), grants);
+        self
+    }
+
+    /// Grant authenticated principals without grants of their own `grants`
+    pub fn with_authenticated(mut self, grants: Grants) -> Self {
+        self.authenticated = grants;
+        self
+    }
+
+    /// Grant anonymous callers `grants`
+    pub fn with_anonymous(mut self, grants: Grants) -> Self {
+        self.anonymous = grants;
+        self
+    }
+}
+
+impl Default for AccessRules {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AccessPolicy for AccessRules {
+    fn allows(&self, principal: Option<&Principal>, access: &Access<'_>) -> bool {
+        let grants = match principal {
+            Some(principal) => self
+                .principals
+                .get(principal.id())
+                .unwrap_or(&self.authenticated),
+            None => &self.anonymous,
+        };
+        grants.allows(access)
+    }
+}
+
+/// Authenticate a request and check that its caller may make it
+///
+/// The request's `auth` param is removed and `metadata.principal` is set to
+/// the principal `authenticator` established, if any. The caller must be
+/// allowed the request's method and, when the transport names one, its
+/// agent. The tenant the request names must be one the principal holds,
+/// and defaults to its own.
+pub async fn authorize(
+    request: &mut Value,
+    transport: &TransportMetadata,
+    authenticator: Option<&dyn Authenticator>,
+    policy: Arc<dyn AccessPolicy>,
+) -> Result<Caller> {
+    let method = request["method"].as_str().unwrap_or_default().to_string();
+    let credentials = request
+        .get_mut("params")
+        .and_then(Value::as_object_mut)
+        .and_then(|params| params.remove(AUTH_PARAM));
+    let principal = match authenticator {
+        Some(authenticator) => {
+            let auth_request = AuthRequest {
+                method: &method,
+                credentials: credentials.as_ref(),
+                transport,
+            };
+            authenticator.authenticate(&auth_request).await?
+        }
+        None => None,
+    };
+    stamp_principal(request, principal.as_ref());
+    let caller = Caller::new(principal, policy);
+    let agent = transport.agent().map(Access::Agent);
+    for access in agent.into_iter().chain([Access::Method(&method)]) {
+        if let Err(err) = caller.check(access) {
+            // Anonymous callers may be allowed more once they authenticate
+            return Err(match caller.principal() {
+                Some(_) => err,
+                None => {
+                    BamlRtError::Unauthenticated(format!("Authentication required to {}", access))
+                }
+            });
+        }
+    }
+    bind_tenant(request, caller.principal())?;
+    Ok(caller)
+}
+
+/// Set `metadata.principal` in a request's params to the principal its
+/// authenticator established, removing any the caller claimed for itself.
+pub(crate) fn stamp_principal(request: &mut Value, principal: Option<&Principal>) {
+    if let Some(params) = request.get_mut("params") {
+        stamp_params_principal(params, principal);
+    }
+}
+
+/// [`stamp_principal`] for params already taken out of their request, such
+/// as the arguments of a routed function call.
+pub(crate) fn stamp_params_principal(params: &mut Value, principal: Option<&Principal>) {
+    let Some(params) = params.as_object_mut() else {
+        return;
+    };
+    match principal {
+        Some(principal) => {
+            let metadata = params
+                .entry("metadata")
+                .or_insert_with(|| Value::Object(Map::new()));
+            if let Some(metadata) = metadata.as_object_mut() {
+                metadata.insert(
+                    PRINCIPAL_METADATA_KEY.to_string(),
+                    Value::String(principal.id().to_string()),
+                );
+            }
+        }
+        None => {
+            if let Some(metadata) = params.get_mut("metadata").and_then(Value::as_object_mut) {
+                metadata.remove(PRINCIPAL_METADATA_KEY);
+            }
+        }
+    }
+}
+
+/// Check the tenant a request names against those `principal` holds, naming
+/// the principal's own tenant when the request names none.
+///
+/// Anonymous callers may not name a tenant at all.
+pub(crate) fn bind_tenant(request: &mut Value, principal: Option<&Principal>) -> Result<()> {
+    let Some(params) = request.get_mut("params").and_then(Value::as_object_mut) else {
+        return Ok(());
+    };
+    let named = params
+        .get(TENANT_PARAM)
+        .and_then(Value::as_str)
+        .filter(|tenant| !tenant.is_empty())
+        .map(TenantId::from);
+    match (named, principal) {
+        (None, Some(principal)) => {
+            if let Some(own) = principal.own_tenant() {
+                params.insert(TENANT_PARAM.to_string(), Value::from(own.as_str()));
+            }
+            Ok(())
+        }
+        (None, None) => Ok(()),
+        (Some(tenant), Some(principal)) if principal.holds_tenant(&tenant) => Ok(()),
+        (Some(tenant), Some(principal)) => Err(BamlRtError::Forbidden {
+            principal: principal.id().to_string(),
+            action: format!("act for tenant '{}'", tenant),
+        }),
+        (Some(tenant), None) => Err(BamlRtError::Unauthenticated(format!(
+            "Authentication required to act for tenant '{}'",
+            tenant
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn bearer_tokens_come_from_the_header_before_the_auth_param() {
+        let credentials = json!({ "token": "from-param" });
+        let mut request = AuthRequest {
+            method: "message.send",
+            credentials: Some(&credentials),
+            transport: &TransportMetadata::new(),
+        };
+        assert_eq!(request.bearer_token(), Some("from-param"));
+
+        let transport = TransportMetadata::new().with_header("Authorization", "Bearer from-header");
+        request.transport = &transport;
+        assert_eq!(request.bearer_token(), Some("from-header"));
+
+        let transport = TransportMetadata::new().with_header("authorization", "Basic dXNlcg==");
+        request.transport = &transport;
+        assert_eq!(request.bearer_token(), None);
+    }
+
+    #[tokio::test]
+    async fn unknown_bearer_tokens_are_rejected() {
+        let tokens = BearerTokens::new().with_token("s3cret", Principal::new("ops"));
+        let authenticate = |token: &'static str| {
+            let credentials = Value::from(token);
+            let tokens = tokens.clone();
+            async move {
+                tokens
+                    .authenticate(&AuthRequest {
+                        method: "message.send",
+                        credentials: Some(&credentials),
+                        transport: &TransportMetadata::new(),
+                    })
+                    .await
+            }
+        };
+        assert_eq!(
+            authenticate("s3cret").await.unwrap(),
+            Some(Principal::new("ops"))
+        );
+        assert!(matches!(
+            authenticate("guess").await,
+            Err(BamlRtError::Unauthenticated(_))
+        ));
+    }
+
+    #[test]
+    fn principals_act_only_for_their_tenants() {
+        let principal = Principal::new("support")
+            .with_tenant("acme")
+            .with_tenant("acme-eu");
+        let request = |params: Value| json!({ "method": "tasks.get", "params": params });
+
+        let mut defaulted = request(json!({ "id": "t1" }));
+        bind_tenant(&mut defaulted, Some(&principal)).unwrap();
+        assert_eq!(defaulted["params"][TENANT_PARAM], "acme");
+
+        let mut held = request(json!({ "id": "t1", "tenant": "acme-eu" }));
+        bind_tenant(&mut held, Some(&principal)).unwrap();
+        assert_eq!(held["params"][TENANT_PARAM], "acme-eu");
+
+        let mut other = request(json!({ "id": "t1", "tenant": "globex" }));
+        assert!(matches!(
+            bind_tenant(&mut other, Some(&principal)),
+            Err(BamlRtError::Forbidden { action, .. }) if action == "act for tenant 'globex'"
+        ));
+        assert!(matches!(
+            bind_tenant(&mut other, None),
+            Err(BamlRtError::Unauthenticated(_))
+        ));
+        assert!(bind_tenant(&mut request(json!({ "id": "t1" })), None).is_ok());
+    }
+
+    #[test]
+    fn rules_grant_principals_their_own_grants() {
+        let rules = AccessRules::new()
+            .with_principal("support", Grants::all().with_tools(["lookup_order"]))
+            .with_anonymous(Grants::none().with_methods(["agent.health"]));
+        let support = Principal::new("support");
+        let ops = Principal::new("ops");
+
+        assert!(rules.allows(Some(&support), &Access::Tool("lookup_order")));
+        assert!(!rules.allows(Some(&support), &Access::Tool("refund")));
+        assert!(rules.allows(Some(&support), &Access::Function("Summarize")));
+        assert!(rules.allows(Some(&ops), &Access::Tool("refund")));
+        assert!(rules.allows(None, &Access::Method("agent.health")));
+        assert!(!rules.allows(None, &Access::Method("message.send")));
+    }
+}
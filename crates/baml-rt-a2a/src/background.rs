@@ -0,0 +1,207 @@
+//! Background execution of long-running `message.send` requests.
+//!
+//! A `message.send` with `configuration.blocking: false` is answered at once
+//! with a task in `TASK_STATE_SUBMITTED`; the JS handler then runs on a
+//! worker. The task moves to `TASK_STATE_WORKING` when the worker picks it
+//! up, and its updates reach clients through `tasks.subscribe` and
+//! [`A2aAgent::subscribe_task_updates`](crate::A2aAgent::subscribe_task_updates)
+//! like those of any other task.
+//!
+//! The handler sees the task's id as `params.message.taskId`, and the task a
+//! handler returns is filed under that id. A handler that answers with a
+//! message, or any other value, completes the task with that answer; one that
+//! fails fails it. Canceling the task aborts its work when the executor
+//! shares [`TaskCancellations`] with the task handler; without them the work
+//! runs on and its result is dropped when it finishes.
+
+use crate::a2a;
+use crate::a2a_store::{TaskRepository, TaskState, generate_task_id, task_state};
+use crate::a2a_types::{SendMessageRequest, Task, TaskStatus};
+use crate::parts::normalize_file_parts;
+use crate::request_router::JsInvoker;
+use crate::result_pipeline::{ResultStoragePipeline, TaskResultRecorder};
+use crate::task_cancellation::TaskCancellations;
+use baml_rt_core::ids::TaskId;
+use baml_rt_core::{BamlRtError, Result, auth, context, correlation, tenant};
+use serde_json::{Value, json};
+use std::sync::Arc;
+use tokio::sync::Semaphore;
+
+/// Limits of the background workers.
+#[derive(Debug, Clone)]
+pub struct BackgroundConfig {
+    /// Tasks run at the same time; further tasks wait in `submitted`.
+    pub max_workers: usize,
+}
+
+impl Default for BackgroundConfig {
+    fn default() -> Self {
+        Self { max_workers: 4 }
+    }
+}
+
+impl BackgroundConfig {
+    pub fn with_max_workers(mut self, max_workers: usize) -> Self {
+        self.max_workers = max_workers.max(1);
+        self
+    }
+}
+
+/// Runs non-blocking `message.send` requests as background tasks.
+pub struct BackgroundExecutor {
+    js_invoker: Arc<dyn JsInvoker>,
+    result_pipeline: Arc<dyn ResultStoragePipeline>,
+    repository: Arc<dyn TaskRepository>,
+    workers: Arc<Semaphore>,
+    cancellations: Option<Arc<TaskCancellations>>,
+}
+
+impl BackgroundExecutor {
+    pub fn new(
+        config: BackgroundConfig,
+        js_invoker: Arc<dyn JsInvoker>,
+        result_pipeline: Arc<dyn ResultStoragePipeline>,
+        repository: Arc<dyn TaskRepository>,
+    ) -> Self {
+        Self {
+            js_invoker,
+            result_pipeline,
+            repository,
+            workers: Arc::new(Semaphore::new(config.max_workers.max(1))),
+            cancellations: None,
+        }
+    }
+
+    /// Register running tasks with `cancellations`, so `tasks.cancel` aborts them
+    pub fn with_task_cancellations(mut self, cancellations: Arc<TaskCancellations>) -> Self {
+        self.cancellations = Some(cancellations);
+        self
+    }
+
+    /// Whether `request` asks not to wait for its result.
+    pub fn accepts(request: &a2a::A2aRequest) -> bool {
+        request.method == a2a::A2aMethod::MessageSend
+            && !request.is_stream
+            && request
+                .params
+                .pointer("/configuration/blocking")
+                .and_then(Value::as_bool)
+                == Some(false)
+    }
+
+    /// Record `request` as a submitted task and start it on a worker.
+    ///
+    /// Returns the `message.send` result naming the submitted task.
+    pub async fn submit(&self, request: &a2a::A2aRequest) -> Result<Value> {
+        let params: SendMessageRequest =
+            serde_json::from_value(request.params.clone()).map_err(BamlRtError::Json)?;
+        let task_id = params
+            .message
+            .task_id
+            .clone()
+            .unwrap_or_else(generate_task_id);
+        let context_id = request
+            .context_id
+            .clone()
+            .or_else(|| params.message.context_id.clone())
+            .unwrap_or_else(context::current_or_new);
+
+        let task = Task {
+            id: Some(task_id.clone()),
+            context_id: Some(context_id.clone()),
+            history: vec![params.message],
+            status: Some(TaskStatus {
+                state: Some(TaskState::Submitted.into()),
+                ..TaskStatus::default()
+            }),
+            ..Task::default()
+        };
+        let submitted = json!({ "task": task });
+        self.result_pipeline.store_result(&submitted).await?;
+
+        let mut request = request.clone();
+        request.params["message"]["taskId"] = json!(task_id);
+        let job = Job {
+            js_invoker: self.js_invoker.clone(),
+            repository: self.repository.clone(),
+            cancellations: self.cancellations.clone(),
+            recorder: TaskResultRecorder::new(
+                self.result_pipeline.clone(),
+                task_id.clone(),
+                context_id.clone(),
+            ),
+            request,
+            task_id,
+        };
+        let workers = self.workers.clone();
+        let correlation_id = correlation::current_or_new();
+        let request_tenant = tenant::current_tenant();
+        let caller = auth::current_caller();
+        // JS handlers are not Send, so drive each job on a blocking thread
+        let handle = tokio::runtime::Handle::current();
+        tokio::task::spawn_blocking(move || {
+            handle.block_on(async move {
+                let Ok(_permit) = workers.acquire_owned().await else {
+                    return;
+                };
+                let job = context::with_context_id(context_id, job.run());
+                correlation::with_correlation_id(
+                    correlation_id,
+                    tenant::with_tenant(request_tenant, auth::with_caller(caller, job)),
+                )
+                .await;
+            })
+        });
+
+        Ok(submitted)
+    }
+}
+
+/// One background task and what it needs to run.
+struct Job {
+    js_invoker: Arc<dyn JsInvoker>,
+    repository: Arc<dyn TaskRepository>,
+    cancellations: Option<Arc<TaskCancellations>>,
+    recorder: TaskResultRecorder,
+    request: a2a::A2aRequest,
+    task_id: TaskId,
+}
+
+impl Job {
+    async fn run(self) {
+        if let Err(err) = self.execute().await {
+            tracing::warn!(
+                task_id = %self.task_id,
+                error = %err,
+                "Background task could not record its result"
+            );
+        }
+    }
+
+    async fn execute(&self) -> Result<()> {
+        // A task canceled while waiting for a worker is not started
+        if self.state().await != Some(TaskState::Submitted) {
+            return Ok(());
+        }
+        self.recorder.record_state(TaskState::Working, None).await?;
+        let invocation = self.js_invoker.invoke_handler(&self.request);
+        let outcome = match &self.cancellations {
+            Some(cancellations) => cancellations.run(&self.task_id, invocation).await,
+            None => invocation.await,
+        };
+        match outcome {
+            Ok(mut result) => {
+                normalize_file_parts(&mut result);
+                self.recorder.record_result(&mut result).await
+            }
+            // `tasks.cancel` already recorded the task as canceled
+            Err(BamlRtError::Canceled { .. }) => Ok(()),
+            Err(err) => self.recorder.record_failure(err.to_string()).await,
+        }
+    }
+
+    async fn state(&self) -> Option<TaskState> {
+        let task = self.repository.get(self.task_id.as_str(), Some(0)).await?;
+        task_state(&task)
+    }
+}
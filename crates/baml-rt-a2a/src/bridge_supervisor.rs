@@ -0,0 +1,407 @@
+//! Bridge health supervision and failover.
+//!
+//! The QuickJS runtime can end up in a state it cannot recover from (a
+//! worker that panicked, a dead engine thread). When that happens every
+//! request routed through the bridge fails until the process restarts. The
+//! supervisor detects those fatal engine errors, swaps a freshly initialized
+//! bridge into the shared handle, and reports what happened on a broadcast
+//! channel.
+//!
+//! The bridge reports those failures as [`BamlRtError::EngineFailure`]:
+//! QuickJS exhausting its heap, or the task driving it panicking or being
+//! cancelled. Anything else a script raises leaves the engine usable.
+//!
+//! Replacement happens *inside* the shared `Arc<Mutex<QuickJSBridge>>`, so the
+//! task handler, JS invoker, and JS tool executors keep their handles and pick
+//! up the new bridge on their next lock.
+
+use baml_rt_core::events::{EventBus, RuntimeEvent};
+use baml_rt_core::{BamlRtError, Result};
+use baml_rt_observability::metrics;
+use baml_rt_quickjs::{BamlRuntimeManager, QuickJSBridge, QuickJSConfig};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use tokio::sync::{Mutex, RwLock, broadcast};
+
+/// Lifecycle events emitted by the bridge supervisor.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BridgeEvent {
+    /// A fatal engine error was observed and the bridge was marked unhealthy.
+    Unhealthy { generation: u64, error: String },
+    /// A replacement bridge was installed.
+    Replaced { generation: u64, from_standby: bool },
+    /// Building a replacement bridge failed; the bridge stays unhealthy.
+    ReplacementFailed { generation: u64, error: String },
+    /// A request that failed on the old bridge was replayed on the new one.
+    RequestReplayed { generation: u64, method: String },
+}
+
+/// Failover behaviour for the agent's JS bridge.
+#[derive(Debug, Clone)]
+pub struct BridgeFailoverConfig {
+    /// Replace the bridge automatically after a fatal engine error.
+    pub enabled: bool,
+    /// Keep a fully initialized spare bridge ready so failover skips startup.
+    pub warm_standby: bool,
+    /// Replay requests that are safe to repeat after a replacement.
+    pub replay_idempotent: bool,
+}
+
+impl Default for BridgeFailoverConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            warm_standby: false,
+            replay_idempotent: true,
+        }
+    }
+}
+
+impl BridgeFailoverConfig {
+    /// Disable automatic bridge replacement.
+    pub fn disabled() -> Self {
+        Self {
+            enabled: false,
+            ..Self::default()
+        }
+    }
+
+    /// Enable or disable the warm standby bridge.
+    pub fn with_warm_standby(mut self, enabled: bool) -> Self {
+        self.warm_standby = enabled;
+        self
+    }
+
+    /// Enable or disable replay of idempotent requests.
+    pub fn with_replay_idempotent(mut self, enabled: bool) -> Self {
+        self.replay_idempotent = enabled;
+        self
+    }
+}
+
+/// Returns true when an error means the JS engine itself is unusable.
+pub fn is_fatal_engine_error(error: &BamlRtError) -> bool {
+    matches!(error, BamlRtError::EngineFailure { .. })
+}
+
+/// Everything needed to rebuild a bridge identical to the one in service.
+struct BridgeRecipe {
+    runtime: Arc<RwLock<BamlRuntimeManager>>,
+    quickjs_config: QuickJSConfig,
+    register_baml_functions: bool,
+    init_js: Vec<String>,
+    js_tools: Mutex<Vec<(String, String)>>,
+}
+
+impl BridgeRecipe {
+    async fn build(&self) -> Result<QuickJSBridge> {
+        let mut bridge =
+            QuickJSBridge::new_with_config(self.runtime.clone(), self.quickjs_config.clone())
+                .await?;
+        if self.register_baml_functions {
+            bridge.register_baml_functions().await?;
+        }
+        for code in &self.init_js {
+            bridge.evaluate(code).await?;
+        }
+        let js_tools = self.js_tools.lock().await.clone();
+        for (name, code) in js_tools {
+            bridge.install_js_tool(name, code).await?;
+        }
+        Ok(bridge)
+    }
+}
+
+/// Watches the shared bridge and replaces it after fatal engine errors.
+pub struct BridgeSupervisor {
+    bridge: Arc<Mutex<QuickJSBridge>>,
+    recipe: BridgeRecipe,
+    config: BridgeFailoverConfig,
+    standby: Mutex<Option<QuickJSBridge>>,
+    healthy: AtomicBool,
+    generation: AtomicU64,
+    events: broadcast::Sender<BridgeEvent>,
+    runtime_events: Option<EventBus>,
+}
+
+impl BridgeSupervisor {
+    pub fn new(
+        bridge: Arc<Mutex<QuickJSBridge>>,
+        runtime: Arc<RwLock<BamlRuntimeManager>>,
+        quickjs_config: QuickJSConfig,
+        register_baml_functions: bool,
+        init_js: Vec<String>,
+        config: BridgeFailoverConfig,
+    ) -> Self {
+        let (events, _events_rx) = broadcast::channel(64);
+        Self {
+            bridge,
+            recipe: BridgeRecipe {
+                runtime,
+                quickjs_config,
+                register_baml_functions,
+                init_js,
+                js_tools: Mutex::new(Vec::new()),
+            },
+            config,
+            standby: Mutex::new(None),
+            healthy: AtomicBool::new(true),
+            generation: AtomicU64::new(0),
+            events,
+            runtime_events: None,
+        }
+    }
+
+    /// Also publish failover on the runtime's event bus.
+    pub fn with_event_bus(mut self, events: EventBus) -> Self {
+        self.runtime_events = Some(events);
+        self
+    }
+
+    fn publish(&self, build: impl FnOnce() -> RuntimeEvent) {
+        if let Some(events) = &self.runtime_events {
+            events.publish_with(build);
+        }
+    }
+
+    /// Failover configuration in effect.
+    pub fn config(&self) -> &BridgeFailoverConfig {
+        &self.config
+    }
+
+    /// Whether the bridge in service is believed to be usable.
+    pub fn is_healthy(&self) -> bool {
+        self.healthy.load(Ordering::SeqCst)
+    }
+
+    /// Number of replacements performed so far.
+    pub fn generation(&self) -> u64 {
+        self.generation.load(Ordering::SeqCst)
+    }
+
+    /// Subscribe to bridge lifecycle events.
+    pub fn subscribe(&self) -> broadcast::Receiver<BridgeEvent> {
+        self.events.subscribe()
+    }
+
+    /// Remember a JS tool so replacement bridges register it too.
+    ///
+    /// Tracking a name again replaces the code remembered for it.
+    pub async fn track_js_tool(&self, name: impl Into<String>, code: impl Into<String>) {
+        let (name, code) = (name.into(), code.into());
+        let mut standby = self.standby.lock().await;
+        if let Some(bridge) = standby.as_mut()
+            && let Err(err) = bridge.replace_js_tool(name.clone(), &code).await
+        {
+            tracing::warn!(error = %err, tool = name.as_str(), "Discarding standby QuickJS bridge");
+            *standby = None;
+        }
+        let mut js_tools = self.recipe.js_tools.lock().await;
+        match js_tools.iter_mut().find(|(tracked, _)| *tracked == name) {
+            Some(tool) => tool.1 = code,
+            None => js_tools.push((name, code)),
+        }
+    }
+
+    /// Forget a JS tool, so replacement bridges leave it out.
+    pub async fn untrack_js_tool(&self, name: &str) {
+        let mut standby = self.standby.lock().await;
+        if let Some(bridge) = standby.as_mut()
+            && let Err(err) = bridge.unregister_js_tool(name).await
+        {
+            tracing::warn!(error = %err, tool = name, "Discarding standby QuickJS bridge");
+            *standby = None;
+        }
+        self.recipe
+            .js_tools
+            .lock()
+            .await
+            .retain(|(tracked, _)| tracked != name);
+    }
+
+    /// Build the standby bridge if warm standby is enabled and none is ready.
+    pub async fn prepare_standby(&self) -> Result<()> {
+        if !self.config.enabled || !self.config.warm_standby {
+            return Ok(());
+        }
+        let mut standby = self.standby.lock().await;
+        if standby.is_none() {
+            *standby = Some(self.recipe.build().await?);
+        }
+        Ok(())
+    }
+
+    /// Replace the bridge after `error` was observed at `observed_generation`.
+    ///
+    /// Concurrent callers that saw the same failure share a single replacement:
+    /// only the first caller for a generation swaps the bridge. Returns `true`
+    /// when a healthy bridge is in service afterwards.
+    pub async fn recover(&self, observed_generation: u64, error: &BamlRtError) -> bool {
+        if !self.config.enabled {
+            return false;
+        }
+
+        let mut bridge = self.bridge.lock().await;
+        if self.generation() != observed_generation {
+            return self.is_healthy();
+        }
+
+        self.healthy.store(false, Ordering::SeqCst);
+        tracing::error!(
+            generation = observed_generation,
+            error = %error,
+            "QuickJS bridge hit a fatal engine error; replacing it"
+        );
+        let _ = self.events.send(BridgeEvent::Unhealthy {
+            generation: observed_generation,
+            error: error.to_string(),
+        });
+        self.publish(|| RuntimeEvent::BridgeUnhealthy {
+            generation: observed_generation,
+            error: error.to_string(),
+        });
+
+        let standby = self.standby.lock().await.take();
+        let from_standby = standby.is_some();
+        let replacement = match standby {
+            Some(standby) => Ok(standby),
+            None => self.recipe.build().await,
+        };
+
+        match replacement {
+            Ok(replacement) => {
+                *bridge = replacement;
+                let generation = self.generation.fetch_add(1, Ordering::SeqCst) + 1;
+                self.healthy.store(true, Ordering::SeqCst);
+                drop(bridge);
+                metrics::record_bridge_failover("replaced", from_standby);
+                tracing::info!(
+                    generation = generation,
+                    from_standby = from_standby,
+                    "QuickJS bridge replaced"
+                );
+                let _ = self.events.send(BridgeEvent::Replaced {
+                    generation,
+                    from_standby,
+                });
+                self.publish(|| RuntimeEvent::BridgeReplaced {
+                    generation,
+                    from_standby,
+                });
+                if let Err(err) = self.prepare_standby().await {
+                    tracing::warn!(error = %err, "Failed to prepare standby QuickJS bridge");
+                }
+                true
+            }
+            Err(err) => {
+                metrics::record_bridge_failover("failed", from_standby);
+                tracing::error!(error = %err, "Failed to build replacement QuickJS bridge");
+                let _ = self.events.send(BridgeEvent::ReplacementFailed {
+                    generation: observed_generation,
+                    error: err.to_string(),
+                });
+                self.publish(|| RuntimeEvent::BridgeReplacementFailed {
+                    generation: observed_generation,
+                    error: err.to_string(),
+                });
+                false
+            }
+        }
+    }
+
+    /// Record that a request was replayed on the current bridge.
+    pub fn record_replay(&self, method: &str) {
+        let _ = self.events.send(BridgeEvent::RequestReplayed {
+            generation: self.generation(),
+            method: method.to_string(),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use baml_rt_core::JsException;
+
+    #[test]
+    fn fatal_engine_errors_are_detected() {
+        assert!(is_fatal_engine_error(&BamlRtError::EngineFailure {
+            reason: "Failed to execute JavaScript: out of memory".to_string(),
+        }));
+        assert!(is_fatal_engine_error(&BamlRtError::EngineFailure {
+            reason: "js tool task failed: task panicked".to_string(),
+        }));
+
+        // Errors scripts raise leave the engine usable
+        assert!(!is_fatal_engine_error(&BamlRtError::QuickJsWithSource {
+            context: "Failed to execute JavaScript".to_string(),
+            source: "sending on a closed channel".into(),
+        }));
+        assert!(!is_fatal_engine_error(&BamlRtError::QuickJs(
+            "Promise did not resolve after 60000 attempts (60000ms)".to_string()
+        )));
+        assert!(!is_fatal_engine_error(&BamlRtError::JsException(
+            JsException::new("InternalError", "stack overflow")
+        )));
+    }
+
+    #[tokio::test]
+    async fn recover_replaces_bridge_and_reruns_init_js() {
+        let runtime = Arc::new(RwLock::new(BamlRuntimeManager::new().expect("runtime")));
+        let init_js =
+            vec!["globalThis.__initCount = (globalThis.__initCount || 0) + 1;".to_string()];
+        let mut bridge = QuickJSBridge::new(runtime.clone()).await.expect("bridge");
+        bridge.evaluate(&init_js[0]).await.expect("init js");
+        bridge
+            .evaluate("globalThis.__scratch = 'old';")
+            .await
+            .expect("scratch");
+        let bridge = Arc::new(Mutex::new(bridge));
+
+        let supervisor = BridgeSupervisor::new(
+            bridge.clone(),
+            runtime,
+            QuickJSConfig::default(),
+            false,
+            init_js,
+            BridgeFailoverConfig::default(),
+        );
+        supervisor
+            .track_js_tool("double_js", "(args) => ({ value: args.n * 2 })")
+            .await;
+        let mut events = supervisor.subscribe();
+
+        let error = BamlRtError::QuickJs("out of memory".to_string());
+        assert!(supervisor.recover(0, &error).await);
+        assert_eq!(supervisor.generation(), 1);
+        assert!(supervisor.is_healthy());
+
+        // A stale observer does not trigger a second replacement.
+        assert!(supervisor.recover(0, &error).await);
+        assert_eq!(supervisor.generation(), 1);
+
+        let state = bridge
+            .lock()
+            .await
+            .evaluate(
+                "(() => JSON.stringify({ init: globalThis.__initCount, scratch: typeof globalThis.__scratch }))()",
+            )
+            .await
+            .expect("inspect replacement");
+        assert_eq!(state["init"], 1);
+        assert_eq!(state["scratch"], "undefined");
+        assert!(bridge.lock().await.is_js_tool("double_js"));
+
+        assert!(matches!(
+            events.recv().await,
+            Ok(BridgeEvent::Unhealthy { generation: 0, .. })
+        ));
+        assert!(matches!(
+            events.recv().await,
+            Ok(BridgeEvent::Replaced {
+                generation: 1,
+                from_standby: false
+            })
+        ));
+    }
+}
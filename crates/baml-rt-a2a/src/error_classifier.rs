@@ -1,3 +1,4 @@
+use crate::bridge_supervisor::is_fatal_engine_error;
 use baml_rt_core::BamlRtError;
 
 pub trait ErrorClassifier: Send + Sync {
@@ -8,12 +9,25 @@ pub struct A2aErrorClassifier;
 
 impl ErrorClassifier for A2aErrorClassifier {
     fn classify(&self, error: &BamlRtError) -> &'static str {
+        if is_fatal_engine_error(error) {
+            return "quickjs_fatal";
+        }
         match error {
             BamlRtError::InvalidArgument(_) => "invalid_argument",
             BamlRtError::FunctionNotFound(_) => "function_not_found",
             BamlRtError::QuickJs(_) => "quickjs",
+            BamlRtError::JsException(_) => "js_exception",
             BamlRtError::Json(_) => "json",
             BamlRtError::ToolExecution(_) => "tool_execution",
+            BamlRtError::OutputRejected { .. } => "output_rejected",
+            BamlRtError::AgentCall { .. } => "agent_call",
+            BamlRtError::TaskTransition { .. } => "task_transition",
+            BamlRtError::DeadlineExceeded { .. } => "deadline_exceeded",
+            BamlRtError::Canceled { .. } => "canceled",
+            BamlRtError::RateLimited { .. } => "rate_limited",
+            BamlRtError::Unauthenticated(_) => "unauthenticated",
+            BamlRtError::Forbidden { .. } => "forbidden",
+            BamlRtError::ApprovalRequired { .. } => "approval_required",
             _ => "internal",
         }
     }
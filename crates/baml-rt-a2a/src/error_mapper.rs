@@ -0,0 +1,185 @@
+//! Deployment-specific JSON-RPC error codes.
+//!
+//! Errors are answered with the codes of [`JsonRpcResponseFormatter`] unless
+//! an [`ErrorMapper`] given to
+//! [`A2aAgentBuilder::with_error_mapper`](crate::A2aAgentBuilder::with_error_mapper)
+//! maps them first. [`ErrorCodeMap`] covers the common cases: picking errors
+//! by their kind or message and answering them with a fixed code, message
+//! and data payload.
+//!
+//! [`JsonRpcResponseFormatter`]: crate::response::JsonRpcResponseFormatter
+
+use crate::a2a;
+use crate::a2a_types::{JSONRPCError, JSONRPCId};
+use crate::error_classifier::{A2aErrorClassifier, ErrorClassifier};
+use crate::response::ResponseFormatter;
+use baml_rt_core::BamlRtError;
+use serde_json::{Value, json};
+use std::sync::Arc;
+
+/// Chooses the JSON-RPC error an error is answered with.
+pub trait ErrorMapper: Send + Sync {
+    /// The error to answer with, or `None` for the default mapping.
+    fn map_error(&self, error: &BamlRtError) -> Option<JSONRPCError>;
+}
+
+type Matcher = Box<dyn Fn(&BamlRtError) -> bool + Send + Sync>;
+
+struct Rule {
+    matches: Matcher,
+    code: i32,
+    message: String,
+    data: Option<Value>,
+}
+
+/// Maps errors to codes by rules; the first matching rule wins.
+///
+/// The `data` of a mapped error is the rule's payload with the error text
+/// added as `error`, or just `{ "error": ... }` for rules without one.
+#[derive(Default)]
+pub struct ErrorCodeMap {
+    rules: Vec<Rule>,
+}
+
+impl ErrorCodeMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Answer errors of `kind`, as named in error metrics (for example
+    /// `tool_execution` or `output_rejected`), with `code` and `message`.
+    pub fn with_kind(self, kind: &'static str, code: i32, message: impl Into<String>) -> Self {
+        self.with_rule(
+            move |error| A2aErrorClassifier.classify(error) == kind,
+            code,
+            message,
+        )
+    }
+
+    /// Answer errors whose text contains `text` with `code` and `message`.
+    pub fn with_message_containing(
+        self,
+        text: impl Into<String>,
+        code: i32,
+        message: impl Into<String>,
+    ) -> Self {
+        let text = text.into();
+        self.with_rule(
+            move |error| error.to_string().contains(&text),
+            code,
+            message,
+        )
+    }
+
+    /// Answer the errors `matches` accepts with `code` and `message`.
+    pub fn with_rule(
+        mut self,
+        matches: impl Fn(&BamlRtError) -> bool + Send + Sync + 'static,
+        code: i32,
+        message: impl Into<String>,
+    ) -> Self {
+        self.rules.push(Rule {
+            matches: Box::new(matches),
+            code,
+            message: message.into(),
+            data: None,
+        });
+        self
+    }
+
+    /// Send `data` with the errors matched by the last rule added.
+    pub fn with_data(mut self, data: Value) -> Self {
+        if let Some(rule) = self.rules.last_mut() {
+            rule.data = Some(data);
+        }
+        self
+    }
+}
+
+impl ErrorMapper for ErrorCodeMap {
+    fn map_error(&self, error: &BamlRtError) -> Option<JSONRPCError> {
+        let rule = self.rules.iter().find(|rule| (rule.matches)(error))?;
+        let mut data = match &rule.data {
+            Some(Value::Object(data)) => data.clone(),
+            Some(other) => {
+                let mut data = serde_json::Map::new();
+                data.insert("details".to_string(), other.clone());
+                data
+            }
+            None => serde_json::Map::new(),
+        };
+        data.insert("error".to_string(), json!(error.to_string()));
+        Some(JSONRPCError {
+            code: rule.code,
+            message: rule.message.clone(),
+            data: Some(Value::Object(data)),
+        })
+    }
+}
+
+/// Formats errors as an [`ErrorMapper`] says, falling back to `inner`.
+pub struct ErrorMappingFormatter {
+    inner: Arc<dyn ResponseFormatter>,
+    mapper: Arc<dyn ErrorMapper>,
+}
+
+impl ErrorMappingFormatter {
+    pub fn new(inner: Arc<dyn ResponseFormatter>, mapper: Arc<dyn ErrorMapper>) -> Self {
+        Self { inner, mapper }
+    }
+}
+
+impl ResponseFormatter for ErrorMappingFormatter {
+    fn format_success(&self, id: Option<JSONRPCId>, result: Value) -> Value {
+        self.inner.format_success(id, result)
+    }
+
+    fn format_stream(&self, id: Option<JSONRPCId>, chunks: Vec<Value>) -> Vec<Value> {
+        self.inner.format_stream(id, chunks)
+    }
+
+    fn format_error(&self, id: Option<JSONRPCId>, error: &BamlRtError) -> Value {
+        match self.mapper.map_error(error) {
+            Some(mapped) => {
+                a2a::error_response(id, mapped.code.into(), &mapped.message, mapped.data)
+            }
+            None => self.inner.format_error(id, error),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::response::JsonRpcResponseFormatter;
+
+    #[test]
+    fn mapped_errors_replace_the_default_codes() {
+        let mapper = ErrorCodeMap::new()
+            .with_message_containing("rate limit", -32029, "Rate limited")
+            .with_data(json!({ "retryAfterMs": 1000 }))
+            .with_kind("tool_execution", -32050, "Tool failed");
+        let formatter =
+            ErrorMappingFormatter::new(Arc::new(JsonRpcResponseFormatter), Arc::new(mapper));
+        let id = Some(JSONRPCId::Integer(7));
+
+        let limited = BamlRtError::BamlRuntime("provider rate limit exceeded".to_string());
+        let response = formatter.format_error(id.clone(), &limited);
+        assert_eq!(response["error"]["code"], -32029);
+        assert_eq!(response["error"]["message"], "Rate limited");
+        assert_eq!(response["error"]["data"]["retryAfterMs"], 1000);
+        assert_eq!(response["id"], 7);
+
+        let timeout = BamlRtError::ToolExecution("timed out after 30s".to_string());
+        let response = formatter.format_error(id.clone(), &timeout);
+        assert_eq!(response["error"]["code"], -32050);
+        assert_eq!(
+            response["error"]["data"]["error"],
+            "Tool execution error: timed out after 30s"
+        );
+
+        let unmapped = BamlRtError::FunctionNotFound("Missing".to_string());
+        let response = formatter.format_error(id, &unmapped);
+        assert_eq!(response["error"]["code"], -32601);
+    }
+}
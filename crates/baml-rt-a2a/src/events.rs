@@ -1,25 +1,98 @@
 use crate::a2a_store::TaskUpdateEvent;
+use crate::a2a_types::{TaskArtifactUpdateEvent, TaskStatusUpdateEvent};
 use async_trait::async_trait;
+use baml_rt_core::events::{EventBus, RuntimeEvent, TaskUpdateKind};
 use tokio::sync::broadcast;
+use tokio::sync::broadcast::error::RecvError;
 
 #[async_trait]
 pub trait EventEmitter: Send + Sync {
     async fn emit(&self, event: TaskUpdateEvent);
 }
 
-pub struct BroadcastEventEmitter {
-    tx: broadcast::Sender<TaskUpdateEvent>,
+/// Publishes task updates on the runtime's event bus
+pub struct BusEventEmitter {
+    events: EventBus,
 }
 
-impl BroadcastEventEmitter {
-    pub fn new(tx: broadcast::Sender<TaskUpdateEvent>) -> Self {
-        Self { tx }
+impl BusEventEmitter {
+    pub fn new(events: EventBus) -> Self {
+        Self { events }
     }
 }
 
 #[async_trait]
-impl EventEmitter for BroadcastEventEmitter {
+impl EventEmitter for BusEventEmitter {
     async fn emit(&self, event: TaskUpdateEvent) {
-        let _ = self.tx.send(event);
+        self.events.publish_with(|| event.to_runtime_event());
+    }
+}
+
+impl TaskUpdateEvent {
+    /// The update as a [`RuntimeEvent::TaskUpdated`]
+    pub fn to_runtime_event(&self) -> RuntimeEvent {
+        let (kind, task_id, context_id, update) = match self {
+            TaskUpdateEvent::Status(event) => (
+                TaskUpdateKind::Status,
+                event.task_id.clone(),
+                event.context_id.clone(),
+                serde_json::to_value(event),
+            ),
+            TaskUpdateEvent::Artifact(event) => (
+                TaskUpdateKind::Artifact,
+                event.task_id.clone(),
+                event.context_id.clone(),
+                serde_json::to_value(event),
+            ),
+        };
+        RuntimeEvent::TaskUpdated {
+            task_id,
+            context_id,
+            kind,
+            update: update.unwrap_or_default(),
+        }
+    }
+
+    /// The task update a runtime event carries, if it is one
+    pub fn from_runtime_event(event: &RuntimeEvent) -> Option<Self> {
+        let RuntimeEvent::TaskUpdated { kind, update, .. } = event else {
+            return None;
+        };
+        let update = update.clone();
+        let parsed = match kind {
+            TaskUpdateKind::Status => {
+                serde_json::from_value::<TaskStatusUpdateEvent>(update).map(Self::Status)
+            }
+            TaskUpdateKind::Artifact => {
+                serde_json::from_value::<TaskArtifactUpdateEvent>(update).map(Self::Artifact)
+            }
+        };
+        parsed
+            .inspect_err(|e| tracing::warn!(error = %e, "Dropping unreadable task update event"))
+            .ok()
+    }
+}
+
+/// Task updates read off a runtime event bus
+pub struct TaskUpdateReceiver {
+    events: broadcast::Receiver<RuntimeEvent>,
+}
+
+impl TaskUpdateReceiver {
+    pub fn new(events: broadcast::Receiver<RuntimeEvent>) -> Self {
+        Self { events }
+    }
+
+    /// Wait for the next task update, skipping other events
+    ///
+    /// Fails like [`broadcast::Receiver::recv`]; a lagging receiver is told
+    /// how many events, of any kind, it missed.
+    pub async fn recv(&mut self) -> Result<TaskUpdateEvent, RecvError> {
+        loop {
+            let event = self.events.recv().await?;
+            if let Some(update) = TaskUpdateEvent::from_runtime_event(&event) {
+                return Ok(update);
+            }
+        }
     }
 }
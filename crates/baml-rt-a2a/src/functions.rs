@@ -0,0 +1,37 @@
+//! Function listing for agent self-description.
+//!
+//! Every agent answers the `agent.functions` JSON-RPC method with
+//! `{ "functions": [...] }`, describing each BAML function of its schema by
+//! name, parameters, return type, streaming support, and docstring, so that
+//! UIs and other agents can discover what it offers. Functions the caller may
+//! not invoke are left out.
+
+use crate::a2a::A2aOutcome;
+use crate::a2a_transport::A2aAgent;
+use crate::method_handlers::{MethodHandler, MethodRequest};
+use async_trait::async_trait;
+use baml_rt_core::Result;
+use baml_rt_core::auth::{self, Access};
+use serde_json::json;
+
+/// JSON-RPC method answered with the agent's functions.
+pub const FUNCTIONS_METHOD: &str = "agent.functions";
+
+/// Serves `agent.functions`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FunctionListing;
+
+#[async_trait(?Send)]
+impl MethodHandler for FunctionListing {
+    async fn handle(&self, agent: &A2aAgent, _request: MethodRequest) -> Result<A2aOutcome> {
+        let functions: Vec<_> = agent
+            .runtime()
+            .read()
+            .await
+            .describe_functions()
+            .into_iter()
+            .filter(|function| auth::check(Access::Function(&function.name)).is_ok())
+            .collect();
+        Ok(A2aOutcome::Response(json!({ "functions": functions })))
+    }
+}
@@ -5,6 +5,7 @@ use crate::a2a_types::{
     SubscribeToTaskRequest, TaskStatusUpdateEvent,
 };
 use crate::events::EventEmitter;
+use crate::task_cancellation::TaskCancellations;
 use async_trait::async_trait;
 use baml_rt_core::{BamlRtError, Result};
 use baml_rt_quickjs::QuickJSBridge;
@@ -30,6 +31,7 @@ pub struct DefaultTaskHandler {
     update_queue: Arc<dyn TaskUpdateQueue>,
     bridge: Arc<Mutex<QuickJSBridge>>,
     emitter: Arc<dyn EventEmitter>,
+    cancellations: Arc<TaskCancellations>,
 }
 
 impl DefaultTaskHandler {
@@ -46,8 +48,15 @@ impl DefaultTaskHandler {
             update_queue,
             bridge,
             emitter,
+            cancellations: Arc::new(TaskCancellations::new()),
         }
     }
+
+    /// Abort the work `cancellations` tracks for a task when it is canceled
+    pub fn with_cancellations(mut self, cancellations: Arc<TaskCancellations>) -> Self {
+        self.cancellations = cancellations;
+        self
+    }
 }
 
 #[async_trait(?Send)]
@@ -74,19 +83,24 @@ impl TaskHandler for DefaultTaskHandler {
             let task = self
                 .repository
                 .cancel(request.id.as_str())
-                .await
+                .await?
                 .ok_or_else(|| BamlRtError::InvalidArgument("Task not found".to_string()))?;
             if let Some(status) = task.status.clone()
                 && let Some(event) = self
                     .recorder
                     .record_status_update(task.id.clone(), task.context_id.clone(), status)
-                    .await
+                    .await?
             {
                 self.emitter.emit(event).await;
             }
             task
         };
 
+        // Work in flight holds the bridge until it stops, so stop it first
+        if self.cancellations.cancel(request.id.as_str()) {
+            tracing::debug!(task_id = %request.id, "Aborted in-flight work of canceled task");
+        }
+
         {
             let mut bridge = self.bridge.lock().await;
             let _ = bridge
@@ -0,0 +1,210 @@
+//! Agent health and readiness reporting.
+//!
+//! Every agent answers the `agent.health` JSON-RPC method with a
+//! [`HealthReport`]: whether its BAML schema is loaded, how many functions and
+//! tools it exposes, the state and memory use of its JS bridge, and whether
+//! the LLM providers registered with [`HealthCheck::with_probe`] can be
+//! reached. Orchestrators can use `ready` as a readiness probe and
+//! `status != "unhealthy"` as a liveness probe. Pass
+//! `{ "probeProviders": false }` to skip the network checks.
+
+use crate::a2a::A2aOutcome;
+use crate::a2a_transport::A2aAgent;
+use crate::method_handlers::{MethodHandler, MethodRequest};
+use async_trait::async_trait;
+use baml_rt_core::Result;
+use baml_rt_quickjs::MemoryStats;
+use serde::Serialize;
+use serde_json::Value;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// JSON-RPC method answered with a [`HealthReport`].
+pub const HEALTH_METHOD: &str = "agent.health";
+
+/// How long a health check waits for a runtime that is loading a schema or
+/// a bridge that is serving a request before leaving their details out.
+const LOCK_WAIT: Duration = Duration::from_millis(250);
+
+/// Overall agent health.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HealthStatus {
+    Ok,
+    /// Serving, but an LLM provider is unreachable.
+    Degraded,
+    /// The JS bridge is unusable until it is replaced.
+    Unhealthy,
+}
+
+/// Reachability of one LLM provider.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderHealth {
+    pub name: String,
+    pub reachable: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub latency_ms: u64,
+}
+
+/// Checks whether an LLM provider can be reached.
+#[async_trait]
+pub trait ProviderProbe: Send + Sync {
+    async fn probe(&self) -> ProviderHealth;
+}
+
+/// Probes a provider by sending a GET request to its base URL.
+///
+/// Any HTTP response, including an authentication error, counts as
+/// reachable; only connection failures and timeouts do not.
+pub struct HttpProviderProbe {
+    name: String,
+    url: String,
+    timeout: Duration,
+    client: reqwest::Client,
+}
+
+impl HttpProviderProbe {
+    pub fn new(name: impl Into<String>, url: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            url: url.into(),
+            timeout: Duration::from_secs(5),
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// Give up on the provider after `timeout`.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+}
+
+#[async_trait]
+impl ProviderProbe for HttpProviderProbe {
+    async fn probe(&self) -> ProviderHealth {
+        let start = Instant::now();
+        let error = match self
+            .client
+            .get(&self.url)
+            .timeout(self.timeout)
+            .send()
+            .await
+        {
+            Ok(_) => None,
+            Err(err) => Some(err.to_string()),
+        };
+        ProviderHealth {
+            name: self.name.clone(),
+            reachable: error.is_none(),
+            error,
+            latency_ms: start.elapsed().as_millis() as u64,
+        }
+    }
+}
+
+/// Health of an agent instance.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HealthReport {
+    pub status: HealthStatus,
+    /// Whether the agent can take requests.
+    pub ready: bool,
+    /// Schema, function and tool details are missing while a schema is being
+    /// loaded.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub schema_loaded: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub functions: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tools: Option<usize>,
+    pub bridge_healthy: bool,
+    pub bridge_generation: u64,
+    /// Missing while the bridge is busy with another request.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quickjs_memory: Option<MemoryStats>,
+    pub llm_providers: Vec<ProviderHealth>,
+}
+
+/// Serves `agent.health`.
+#[derive(Default)]
+pub struct HealthCheck {
+    probes: Vec<Arc<dyn ProviderProbe>>,
+}
+
+impl HealthCheck {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Report the reachability of the provider checked by `probe`.
+    pub fn with_probe(mut self, probe: Arc<dyn ProviderProbe>) -> Self {
+        self.probes.push(probe);
+        self
+    }
+
+    /// Check `agent`, probing providers when `probe_providers` is set.
+    pub async fn report(&self, agent: &A2aAgent, probe_providers: bool) -> HealthReport {
+        let runtime = agent.runtime();
+        let (schema_loaded, functions, tools) =
+            match tokio::time::timeout(LOCK_WAIT, runtime.read()).await {
+                Ok(manager) => (
+                    Some(manager.is_schema_loaded()),
+                    Some(manager.list_functions().len()),
+                    Some(manager.list_tools().await.len()),
+                ),
+                Err(_) => (None, None, None),
+            };
+
+        let bridge = agent.bridge();
+        let quickjs_memory = tokio::time::timeout(LOCK_WAIT, bridge.lock())
+            .await
+            .ok()
+            .map(|bridge| bridge.memory_stats());
+
+        let supervisor = agent.bridge_supervisor();
+        let bridge_healthy = supervisor.is_healthy();
+
+        let mut llm_providers = Vec::new();
+        if probe_providers {
+            for probe in &self.probes {
+                llm_providers.push(probe.probe().await);
+            }
+        }
+
+        let status = if !bridge_healthy {
+            HealthStatus::Unhealthy
+        } else if llm_providers.iter().any(|provider| !provider.reachable) {
+            HealthStatus::Degraded
+        } else {
+            HealthStatus::Ok
+        };
+
+        HealthReport {
+            status,
+            ready: bridge_healthy,
+            schema_loaded,
+            functions,
+            tools,
+            bridge_healthy,
+            bridge_generation: supervisor.generation(),
+            quickjs_memory,
+            llm_providers,
+        }
+    }
+}
+
+#[async_trait(?Send)]
+impl MethodHandler for HealthCheck {
+    async fn handle(&self, agent: &A2aAgent, request: MethodRequest) -> Result<A2aOutcome> {
+        let probe_providers = request
+            .params
+            .get("probeProviders")
+            .and_then(Value::as_bool)
+            .unwrap_or(true);
+        let report = self.report(agent, probe_providers).await;
+        Ok(A2aOutcome::Response(serde_json::to_value(report)?))
+    }
+}
@@ -0,0 +1,112 @@
+//! Tasks that pause for user input and resume on the next message.
+//!
+//! A JS handler pauses its task by returning `requireInput(prompt, state)`:
+//! the task moves to `TASK_STATE_INPUT_REQUIRED` with `prompt` as its status
+//! message, and `state`, whatever the handler needs to pick up where it left
+//! off, is kept in the task's metadata under [`RESUME_STATE_KEY`].
+//!
+//! When a later `message.send` names the paused task in `message.taskId`, the
+//! task moves back to `TASK_STATE_WORKING` and the handler is called with the
+//! new message and `params.resume` set to the [`PendingInput`] it left behind:
+//!
+//! ```js
+//! globalThis.handle_a2a_request = async (request) => {
+//!     const resume = request.params.resume;
+//!     if (!resume) {
+//!         return requireInput("Which city?", { step: "city" });
+//!     }
+//!     const city = request.params.message.parts[0].text;
+//!     return { message: { messageId: "m1", role: "ROLE_AGENT", parts: [{ text: city }] } };
+//! };
+//! ```
+//!
+//! A resumed handler that answers with a message completes the task, one that
+//! fails fails it, and one that returns a task, possibly paused again, files
+//! it under the resumed task's id.
+
+use crate::a2a;
+use crate::a2a_store::{TaskState, task_state};
+use crate::a2a_types::{Message, Task};
+use crate::approval::PendingApproval;
+use baml_rt_core::ids::{ContextId, TaskId};
+use serde::Serialize;
+use serde_json::{Value, json};
+
+/// Task metadata key holding the state a paused handler resumes with.
+pub const RESUME_STATE_KEY: &str = "resumeState";
+
+/// Defines `requireInput(prompt, state)` for JS handlers.
+///
+/// `prompt` is a string, an array of parts or a full message.
+pub const REQUIRE_INPUT_JS: &str = r#"
+    globalThis.requireInput = function(prompt, state) {
+        const isMessage = prompt !== null && typeof prompt === 'object'
+            && !Array.isArray(prompt) && Array.isArray(prompt.parts);
+        const message = isMessage ? prompt : {
+            messageId: 'prompt-' + Date.now() + '-' + Math.random().toString(36).slice(2),
+            role: 'ROLE_AGENT',
+            parts: Array.isArray(prompt) ? prompt : [{ text: String(prompt) }],
+        };
+        const task = { status: { state: 'TASK_STATE_INPUT_REQUIRED', message } };
+        if (state !== undefined) {
+            task.metadata = { resumeState: state };
+        }
+        return { task };
+    };
+"#;
+
+/// What a paused task waits for, as passed to the handler in
+/// `params.resume`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PendingInput {
+    pub task_id: TaskId,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub context_id: Option<ContextId>,
+    /// The question the handler asked
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prompt: Option<Message>,
+    /// The state the handler paused with
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub state: Option<Value>,
+    /// The tool call the task waits on, when it was paused for approval
+    /// rather than by the handler
+    #[serde(skip)]
+    pub approval: Option<PendingApproval>,
+}
+
+impl PendingInput {
+    /// What `task` waits for, if it is paused in `input-required` or
+    /// `auth-required`.
+    pub fn of(task: &Task) -> Option<Self> {
+        if !matches!(
+            task_state(task)?,
+            TaskState::InputRequired | TaskState::AuthRequired
+        ) {
+            return None;
+        }
+        Some(Self {
+            task_id: task.id.clone()?,
+            context_id: task.context_id.clone(),
+            prompt: task.status.as_ref()?.message.clone(),
+            state: task
+                .metadata
+                .as_ref()
+                .and_then(|metadata| metadata.get(RESUME_STATE_KEY))
+                .cloned(),
+            approval: PendingApproval::of(task),
+        })
+    }
+
+    /// `request` as it reaches the handler resuming the task: in the task's
+    /// context, with this pending input as `params.resume`.
+    pub fn resume_request(&self, request: &a2a::A2aRequest) -> a2a::A2aRequest {
+        let mut request = request.clone();
+        if let Some(context_id) = &self.context_id {
+            request.context_id = Some(context_id.clone());
+            request.params["message"]["contextId"] = json!(context_id);
+        }
+        request.params["resume"] = json!(self);
+        request
+    }
+}
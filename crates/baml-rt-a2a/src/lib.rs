@@ -1,12 +1,24 @@
 //! A2A protocol support.
 
 pub mod a2a;
+pub mod a2a_client;
 pub mod a2a_store;
 pub mod a2a_transport;
-pub mod a2a_types;
+pub mod approval;
+pub mod auth;
+pub mod background;
+pub mod bridge_supervisor;
 pub mod error_classifier;
+pub mod error_mapper;
 pub mod events;
+pub mod functions;
 pub mod handlers;
+pub mod health;
+pub mod input_required;
+pub mod method_handlers;
+pub mod method_routing;
+pub mod outbox;
+pub mod parts;
 pub mod request_router;
 pub mod response;
 pub mod result_deduplicator;
@@ -14,6 +26,35 @@ pub mod result_extractor;
 pub mod result_pipeline;
 pub mod result_processor;
 pub mod stream_normalizer;
+pub mod task_cancellation;
+pub mod task_operations;
+pub mod tenancy;
+pub mod verbosity;
 
-pub use a2a::{A2aMethod, A2aOutcome, A2aRequest};
+pub use a2a::{A2aMethod, A2aOutcome, A2aRequest, BatchExecution};
+pub use a2a_client::{A2aClient, AgentTarget};
 pub use a2a_transport::{A2aAgent, A2aAgentBuilder, A2aRequestHandler};
+pub use approval::{PENDING_APPROVAL_KEY, PendingApproval, ProvenanceApprovals};
+pub use auth::{
+    AccessRules, AuthRequest, Authenticator, BearerTokens, FORBIDDEN_CODE, Grants,
+    TransportMetadata, UNAUTHENTICATED_CODE, authorize,
+};
+pub use background::{BackgroundConfig, BackgroundExecutor};
+pub use baml_rt_a2a_client::a2a_types;
+pub use bridge_supervisor::{BridgeEvent, BridgeFailoverConfig, BridgeSupervisor};
+pub use error_mapper::{ErrorCodeMap, ErrorMapper, ErrorMappingFormatter};
+pub use events::TaskUpdateReceiver;
+pub use functions::{FUNCTIONS_METHOD, FunctionListing};
+pub use health::{HealthCheck, HealthReport, HealthStatus, HttpProviderProbe, ProviderProbe};
+pub use input_required::PendingInput;
+pub use method_handlers::{MethodHandler, MethodHandlers, MethodRequest};
+pub use method_routing::{MethodResolver, RouteHandler, RouteTarget, RoutedRequest, RoutingTable};
+pub use outbox::{
+    DispatchLoop, OutboxConfig, OutboxDispatcher, OutboxEntry, OutboxStatus, OutboxStore,
+};
+pub use parts::{FilePolicy, PartResolver, UriFetcher};
+pub use result_deduplicator::DeduplicationConfig;
+pub use stream_normalizer::{StreamNormalizer, normalizer_for};
+pub use task_cancellation::TaskCancellations;
+pub use task_operations::StoreTaskOperations;
+pub use tenancy::TenantRegistry;
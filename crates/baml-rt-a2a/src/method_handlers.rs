@@ -0,0 +1,76 @@
+//! Custom JSON-RPC methods.
+//!
+//! The standard A2A methods are served by the agent's request router. Any
+//! other method an agent should answer, such as `myorg.customMethod`, is
+//! registered with
+//! [`A2aAgentBuilder::with_method_handler`](crate::A2aAgentBuilder::with_method_handler).
+//! Custom requests get the same correlation, tracing, metrics and error
+//! responses as standard ones, and their handler can reach the agent's
+//! runtime, bridge and task store.
+
+use crate::a2a::{A2aMethod, A2aOutcome};
+use crate::a2a_transport::A2aAgent;
+use crate::a2a_types::JSONRPCId;
+use async_trait::async_trait;
+use baml_rt_core::{BamlRtError, Result};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// A request for a custom method.
+#[derive(Debug, Clone)]
+pub struct MethodRequest {
+    pub id: Option<JSONRPCId>,
+    pub method: String,
+    /// The request params, `null` when absent.
+    pub params: Value,
+}
+
+/// Serves one custom JSON-RPC method.
+///
+/// Return [`A2aOutcome::Stream`] to answer with stream chunks.
+#[async_trait(?Send)]
+pub trait MethodHandler: Send + Sync {
+    async fn handle(&self, agent: &A2aAgent, request: MethodRequest) -> Result<A2aOutcome>;
+}
+
+/// Custom method handlers by method name.
+#[derive(Clone, Default)]
+pub struct MethodHandlers {
+    handlers: HashMap<String, Arc<dyn MethodHandler>>,
+}
+
+impl MethodHandlers {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register `handler` for `method`.
+    ///
+    /// Standard A2A methods cannot be replaced.
+    pub fn insert(
+        &mut self,
+        method: impl Into<String>,
+        handler: Arc<dyn MethodHandler>,
+    ) -> Result<()> {
+        let method = method.into();
+        if method.parse::<A2aMethod>().is_ok() {
+            return Err(BamlRtError::Configuration(format!(
+                "Cannot register a handler for standard A2A method '{}'",
+                method
+            )));
+        }
+        self.handlers.insert(method, handler);
+        Ok(())
+    }
+
+    /// The handler for `method`, if one is registered.
+    pub fn get(&self, method: &str) -> Option<Arc<dyn MethodHandler>> {
+        self.handlers.get(method).cloned()
+    }
+
+    /// Names of all custom methods.
+    pub fn methods(&self) -> impl Iterator<Item = &str> {
+        self.handlers.keys().map(String::as_str)
+    }
+}
@@ -0,0 +1,422 @@
+//! Method routing across agents.
+//!
+//! Hosts that serve several agents over a single JSON-RPC channel need to
+//! decide which agent handles a request and how. Standard A2A methods,
+//! `agent.health`, and `agent.functions` are forwarded to the agent
+//! untouched. Any other method is treated as a call: either to a BAML
+//! function (`"SimpleGreeting"`) or to a JS function the agent exposes.
+//! [`RoutingTable`] resolves requests in this order:
+//!
+//! 1. explicit method mappings registered with [`RoutingTable::with_mapping`]
+//! 2. custom [`MethodResolver`]s, in registration order
+//! 3. agent-prefixed methods (`agent::Fn`, `agent/Fn`, `agent.Fn`)
+//! 4. `params.agent`
+//! 5. the single agent exposing a BAML function with that name
+//! 6. the only loaded agent
+//!
+//! A `/stream`, `.stream`, or `:stream` method suffix (or `params.stream`)
+//! selects the streaming variant of the call.
+
+use crate::a2a::{self, A2aMethod};
+use crate::a2a_types::JSONRPCId;
+use crate::functions::FUNCTIONS_METHOD;
+use crate::health::HEALTH_METHOD;
+use baml_rt_core::{BamlRtError, Result};
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::sync::Arc;
+
+/// How a routed request is executed by its agent.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RouteHandler {
+    /// Forward the JSON-RPC request to the agent's A2A handler.
+    A2a,
+    /// Call a BAML function by name.
+    BamlFunction(String),
+    /// Call a global JS function exposed by the agent.
+    JsFunction(String),
+}
+
+/// Destination of a method: an optional agent and the handler to run.
+///
+/// Without an agent the table picks one the same way it does for
+/// unmapped methods.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RouteTarget {
+    pub agent: Option<String>,
+    pub handler: RouteHandler,
+}
+
+impl RouteTarget {
+    /// Route to a BAML function.
+    pub fn baml_function(function: impl Into<String>) -> Self {
+        Self {
+            agent: None,
+            handler: RouteHandler::BamlFunction(function.into()),
+        }
+    }
+
+    /// Route to a JS function.
+    pub fn js_function(function: impl Into<String>) -> Self {
+        Self {
+            agent: None,
+            handler: RouteHandler::JsFunction(function.into()),
+        }
+    }
+
+    /// Pin the target to a specific agent.
+    pub fn on_agent(mut self, agent: impl Into<String>) -> Self {
+        self.agent = Some(agent.into());
+        self
+    }
+}
+
+/// A request after routing.
+#[derive(Debug, Clone)]
+pub struct RoutedRequest {
+    pub agent: String,
+    pub handler: RouteHandler,
+    pub id: Option<JSONRPCId>,
+    /// Call arguments, with routing keys (`agent`, `stream`) removed.
+    pub params: Value,
+    pub is_stream: bool,
+    /// The original JSON-RPC request, forwarded as-is for [`RouteHandler::A2a`].
+    pub request: Value,
+}
+
+/// Pluggable method resolution.
+///
+/// Resolvers run after explicit mappings and before the built-in
+/// conventions. Returning `None` defers to the next resolver.
+pub trait MethodResolver: Send + Sync {
+    fn resolve(
+        &self,
+        method: &str,
+        params: &Map<String, Value>,
+        table: &RoutingTable,
+    ) -> Option<RouteTarget>;
+}
+
+/// Routes JSON-RPC requests to agents and handlers.
+#[derive(Clone, Default)]
+pub struct RoutingTable {
+    agents: BTreeMap<String, BTreeSet<String>>,
+    mappings: HashMap<String, RouteTarget>,
+    resolvers: Vec<Arc<dyn MethodResolver>>,
+}
+
+impl RoutingTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register an agent and the BAML functions it exposes.
+    pub fn register_agent<I, S>(&mut self, agent: impl Into<String>, functions: I)
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.agents.insert(
+            agent.into(),
+            functions.into_iter().map(Into::into).collect(),
+        );
+    }
+
+    /// Map a method name to a fixed target.
+    pub fn with_mapping(mut self, method: impl Into<String>, target: RouteTarget) -> Self {
+        self.mappings.insert(method.into(), target);
+        self
+    }
+
+    /// Add a custom method resolver.
+    pub fn with_resolver(mut self, resolver: Arc<dyn MethodResolver>) -> Self {
+        self.resolvers.push(resolver);
+        self
+    }
+
+    /// Names of all registered agents.
+    pub fn agent_names(&self) -> impl Iterator<Item = &str> {
+        self.agents.keys().map(String::as_str)
+    }
+
+    /// Whether `agent` exposes a BAML function named `function`.
+    pub fn agent_has_function(&self, agent: &str, function: &str) -> bool {
+        self.agents
+            .get(agent)
+            .is_some_and(|functions| functions.contains(function))
+    }
+
+    /// Resolve a raw JSON-RPC request to an agent and handler.
+    pub fn resolve(&self, request: Value) -> Result<RoutedRequest> {
+        let method = request
+            .get("method")
+            .and_then(Value::as_str)
+            .ok_or_else(|| BamlRtError::InvalidArgument("A2A request missing method".to_string()))?
+            .to_string();
+        let id = a2a::extract_jsonrpc_id(&request);
+
+        if is_a2a_method(&method) {
+            let agent = a2a::extract_agent_name(&request)
+                .or_else(|| {
+                    request
+                        .get("params")
+                        .and_then(|params| params.get("agent"))
+                        .and_then(Value::as_str)
+                        .map(str::to_string)
+                })
+                .or_else(|| self.sole_agent())
+                .ok_or_else(|| {
+                    BamlRtError::InvalidArgument(
+                        "A2A request missing agent (set message metadata agent or params.agent)"
+                            .to_string(),
+                    )
+                })?;
+            let params = request.get("params").cloned().unwrap_or(Value::Null);
+            return Ok(RoutedRequest {
+                agent,
+                handler: RouteHandler::A2a,
+                id,
+                params,
+                is_stream: false,
+                request,
+            });
+        }
+
+        let (method_base, had_stream_suffix) = strip_stream_suffix(&method);
+        let mut params = match request.get("params").cloned().unwrap_or(Value::Null) {
+            Value::Object(map) => map,
+            Value::Null => Map::new(),
+            other => {
+                let mut map = Map::new();
+                map.insert("value".to_string(), other);
+                map
+            }
+        };
+        let explicit_agent = params
+            .remove("agent")
+            .and_then(|agent| agent.as_str().map(str::to_string));
+        let stream_param = params
+            .remove("stream")
+            .and_then(|stream| stream.as_bool())
+            .unwrap_or(false);
+
+        let (agent, handler) = self.resolve_call(method_base, &params, explicit_agent)?;
+        Ok(RoutedRequest {
+            agent,
+            handler,
+            id,
+            params: Value::Object(params),
+            is_stream: had_stream_suffix || stream_param,
+            request,
+        })
+    }
+
+    fn resolve_call(
+        &self,
+        method: &str,
+        params: &Map<String, Value>,
+        explicit_agent: Option<String>,
+    ) -> Result<(String, RouteHandler)> {
+        let target = self.mappings.get(method).cloned().or_else(|| {
+            self.resolvers
+                .iter()
+                .find_map(|resolver| resolver.resolve(method, params, self))
+        });
+        if let Some(target) = target {
+            let function = match &target.handler {
+                RouteHandler::BamlFunction(name) | RouteHandler::JsFunction(name) => {
+                    Some(name.as_str())
+                }
+                RouteHandler::A2a => None,
+            };
+            let agent = match target.agent.or(explicit_agent) {
+                Some(agent) => agent,
+                None => self.default_agent(method, function)?,
+            };
+            return Ok((agent, target.handler));
+        }
+
+        if let Some((agent, function)) = self.split_agent_method(method) {
+            let handler = self.function_handler(&agent, function);
+            return Ok((agent, handler));
+        }
+
+        let agent = match explicit_agent {
+            Some(agent) => agent,
+            None => self.default_agent(method, Some(method))?,
+        };
+        let handler = self.function_handler(&agent, method);
+        Ok((agent, handler))
+    }
+
+    /// Pick an agent for a call that did not name one.
+    fn default_agent(&self, method: &str, function: Option<&str>) -> Result<String> {
+        if let Some(function) = function {
+            let mut exposing = self
+                .agents
+                .iter()
+                .filter(|(_, functions)| functions.contains(function))
+                .map(|(agent, _)| agent);
+            match (exposing.next(), exposing.next()) {
+                (Some(agent), None) => return Ok(agent.clone()),
+                (Some(_), Some(_)) => {
+                    return Err(BamlRtError::InvalidArgument(format!(
+                        "Method '{}' is exposed by multiple agents (set params.agent or prefix method with agent name)",
+                        method
+                    )));
+                }
+                _ => {}
+            }
+        }
+        self.sole_agent().ok_or_else(|| {
+            BamlRtError::InvalidArgument(
+                "A2A request missing agent (set params.agent or prefix method with agent name)"
+                    .to_string(),
+            )
+        })
+    }
+
+    fn function_handler(&self, agent: &str, function: &str) -> RouteHandler {
+        if self.agent_has_function(agent, function) {
+            RouteHandler::BamlFunction(function.to_string())
+        } else {
+            RouteHandler::JsFunction(function.to_string())
+        }
+    }
+
+    fn split_agent_method<'a>(&self, method: &'a str) -> Option<(String, &'a str)> {
+        for sep in ["::", "/", "."] {
+            if let Some((prefix, suffix)) = method.split_once(sep)
+                && self.agents.contains_key(prefix)
+            {
+                return Some((prefix.to_string(), suffix));
+            }
+        }
+        None
+    }
+
+    fn sole_agent(&self) -> Option<String> {
+        if self.agents.len() == 1 {
+            self.agents.keys().next().cloned()
+        } else {
+            None
+        }
+    }
+}
+
+fn strip_stream_suffix(method: &str) -> (&str, bool) {
+    for suffix in ["/stream", ".stream", ":stream"] {
+        if let Some(stripped) = method.strip_suffix(suffix) {
+            return (stripped, true);
+        }
+    }
+    (method, false)
+}
+
+fn is_a2a_method(method: &str) -> bool {
+    method.parse::<A2aMethod>().is_ok()
+        || method == HEALTH_METHOD
+        || method == FUNCTIONS_METHOD
+        || method.starts_with("message/")
+        || method.starts_with("tasks/")
+        || method.starts_with("agent/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn table() -> RoutingTable {
+        let mut table = RoutingTable::new();
+        table.register_agent("voidship", ["SimpleGreeting"]);
+        table.register_agent("tally", ["CountWords"]);
+        table
+    }
+
+    fn call(method: &str, params: Value) -> Value {
+        json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params })
+    }
+
+    #[test]
+    fn routes_function_names_to_the_agent_exposing_them() {
+        let routed = table()
+            .resolve(call("SimpleGreeting/stream", json!({ "name": "World" })))
+            .expect("route");
+        assert_eq!(routed.agent, "voidship");
+        assert_eq!(
+            routed.handler,
+            RouteHandler::BamlFunction("SimpleGreeting".to_string())
+        );
+        assert!(routed.is_stream);
+        assert_eq!(routed.params, json!({ "name": "World" }));
+    }
+
+    #[test]
+    fn agent_prefix_and_params_select_agent() {
+        let table = table();
+        let routed = table
+            .resolve(call("tally::summarize", json!({})))
+            .expect("prefixed route");
+        assert_eq!(routed.agent, "tally");
+        assert_eq!(
+            routed.handler,
+            RouteHandler::JsFunction("summarize".to_string())
+        );
+
+        let routed = table
+            .resolve(call("summarize", json!({ "agent": "voidship" })))
+            .expect("params.agent route");
+        assert_eq!(routed.agent, "voidship");
+        assert_eq!(routed.params, json!({}));
+
+        assert!(table.resolve(call("summarize", json!({}))).is_err());
+    }
+
+    #[test]
+    fn mappings_and_resolvers_take_precedence() {
+        struct Alias;
+        impl MethodResolver for Alias {
+            fn resolve(
+                &self,
+                method: &str,
+                _params: &Map<String, Value>,
+                _table: &RoutingTable,
+            ) -> Option<RouteTarget> {
+                (method == "count").then(|| RouteTarget::baml_function("CountWords"))
+            }
+        }
+
+        let table = table()
+            .with_mapping(
+                "greet",
+                RouteTarget::baml_function("SimpleGreeting").on_agent("voidship"),
+            )
+            .with_resolver(Arc::new(Alias));
+
+        let routed = table.resolve(call("greet", json!({}))).expect("mapped");
+        assert_eq!(routed.agent, "voidship");
+        assert_eq!(
+            routed.handler,
+            RouteHandler::BamlFunction("SimpleGreeting".to_string())
+        );
+
+        let routed = table.resolve(call("count", json!({}))).expect("resolved");
+        assert_eq!(routed.agent, "tally");
+        assert_eq!(
+            routed.handler,
+            RouteHandler::BamlFunction("CountWords".to_string())
+        );
+    }
+
+    #[test]
+    fn a2a_methods_are_forwarded() {
+        let mut table = RoutingTable::new();
+        table.register_agent("voidship", ["SimpleGreeting"]);
+        let request = call("tasks.list", json!({}));
+        let routed = table.resolve(request.clone()).expect("a2a route");
+        assert_eq!(routed.agent, "voidship");
+        assert_eq!(routed.handler, RouteHandler::A2a);
+        assert_eq!(routed.request, request);
+    }
+}
@@ -0,0 +1,654 @@
+//! Transactional outbox for side-effectful tools.
+//!
+//! Tools that send email, move money, or otherwise touch the outside world
+//! should not run their effect inline: a retried A2A request would repeat it.
+//! An outbox tool instead records an *intent* in the task store, keyed by an
+//! idempotency key, and returns a link to that entry. The [`OutboxDispatcher`]
+//! executes pending intents separately, retrying failures with backoff and
+//! marking each entry dispatched once its effect succeeds.
+//!
+//! Recording the same idempotency key twice returns the existing entry, so a
+//! replayed request links to the original intent instead of creating a new
+//! one. Keys are scoped to the tenant and principal making the call, so
+//! callers sharing the outbox never collide with each other's intents. The
+//! effect itself runs at most once per entry unless the process dies between
+//! executing it and marking it dispatched.
+
+use async_trait::async_trait;
+use baml_rt_core::auth::{self, Principal};
+use baml_rt_core::context;
+use baml_rt_core::ids::{ContextId, TenantId};
+use baml_rt_core::tenant;
+use baml_rt_core::{BamlRtError, Result};
+use baml_rt_observability::metrics;
+use baml_rt_tools::ToolExecutor;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::{Notify, RwLock};
+
+/// Argument key a caller can set to choose the idempotency key explicitly.
+pub const IDEMPOTENCY_KEY_ARG: &str = "idempotency_key";
+
+/// Lifecycle of an outbox entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OutboxStatus {
+    /// Waiting for (another) dispatch attempt.
+    Pending,
+    /// Claimed by the dispatcher; the effect is running.
+    InFlight,
+    /// The effect succeeded.
+    Dispatched,
+    /// The effect failed on every allowed attempt.
+    Failed,
+}
+
+/// A recorded intent to run a tool's side effect.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OutboxEntry {
+    pub id: String,
+    pub idempotency_key: String,
+    pub tool_name: String,
+    pub args: Value,
+    pub context_id: Option<ContextId>,
+    pub status: OutboxStatus,
+    pub attempts: u32,
+    /// Earliest time (ms since the Unix epoch) of the next dispatch attempt.
+    pub next_attempt_at_ms: u64,
+    pub result: Option<Value>,
+    pub last_error: Option<String>,
+}
+
+impl OutboxEntry {
+    pub fn new(idempotency_key: String, tool_name: String, args: Value) -> Self {
+        Self {
+            id: format!("outbox-{:x}", Sha256::digest(&idempotency_key)),
+            idempotency_key,
+            tool_name,
+            args,
+            context_id: None,
+            status: OutboxStatus::Pending,
+            attempts: 0,
+            next_attempt_at_ms: 0,
+            result: None,
+            last_error: None,
+        }
+    }
+
+    /// Tool result linking back to this entry.
+    pub fn to_tool_result(&self) -> Value {
+        json!({
+            "outbox_id": self.id,
+            "idempotency_key": self.idempotency_key,
+            "status": self.status,
+            "result": self.result,
+        })
+    }
+}
+
+/// Storage for outbox entries.
+#[async_trait]
+pub trait OutboxStore: Send + Sync {
+    /// Record an intent, or return the existing entry with the same idempotency key.
+    async fn record_intent(&self, entry: OutboxEntry) -> OutboxEntry;
+    async fn get_entry(&self, id: &str) -> Option<OutboxEntry>;
+    /// Claim pending entries whose next attempt is due, marking them in flight.
+    async fn claim_due(&self, now_ms: u64) -> Vec<OutboxEntry>;
+    async fn mark_dispatched(&self, id: &str, result: Value) -> Option<OutboxEntry>;
+    /// Record a failed attempt. `retry_at_ms` of `None` fails the entry for good.
+    async fn mark_attempt_failed(
+        &self,
+        id: &str,
+        error: String,
+        retry_at_ms: Option<u64>,
+    ) -> Option<OutboxEntry>;
+}
+
+/// In-memory outbox entries, embedded in the in-memory task store.
+#[derive(Debug, Default)]
+pub struct OutboxLog {
+    entries: HashMap<String, OutboxEntry>,
+    by_key: HashMap<String, String>,
+    order: Vec<String>,
+}
+
+impl OutboxLog {
+    pub fn record_intent(&mut self, entry: OutboxEntry) -> OutboxEntry {
+        if let Some(existing) = self
+            .by_key
+            .get(&entry.idempotency_key)
+            .and_then(|id| self.entries.get(id))
+        {
+            return existing.clone();
+        }
+        self.by_key
+            .insert(entry.idempotency_key.clone(), entry.id.clone());
+        self.order.push(entry.id.clone());
+        self.entries.insert(entry.id.clone(), entry.clone());
+        entry
+    }
+
+    pub fn get(&self, id: &str) -> Option<OutboxEntry> {
+        self.entries.get(id).cloned()
+    }
+
+    pub fn claim_due(&mut self, now_ms: u64) -> Vec<OutboxEntry> {
+        let mut claimed = Vec::new();
+        for id in &self.order {
+            if let Some(entry) = self.entries.get_mut(id)
+                && entry.status == OutboxStatus::Pending
+                && entry.next_attempt_at_ms <= now_ms
+            {
+                entry.status = OutboxStatus::InFlight;
+                entry.attempts += 1;
+                claimed.push(entry.clone());
+            }
+        }
+        claimed
+    }
+
+    pub fn mark_dispatched(&mut self, id: &str, result: Value) -> Option<OutboxEntry> {
+        let entry = self.entries.get_mut(id)?;
+        entry.status = OutboxStatus::Dispatched;
+        entry.result = Some(result);
+        entry.last_error = None;
+        Some(entry.clone())
+    }
+
+    pub fn mark_attempt_failed(
+        &mut self,
+        id: &str,
+        error: String,
+        retry_at_ms: Option<u64>,
+    ) -> Option<OutboxEntry> {
+        let entry = self.entries.get_mut(id)?;
+        entry.last_error = Some(error);
+        match retry_at_ms {
+            Some(retry_at_ms) => {
+                entry.status = OutboxStatus::Pending;
+                entry.next_attempt_at_ms = retry_at_ms;
+            }
+            None => entry.status = OutboxStatus::Failed,
+        }
+        Some(entry.clone())
+    }
+}
+
+/// Retry behaviour of the outbox dispatcher.
+#[derive(Debug, Clone)]
+pub struct OutboxConfig {
+    /// Attempts per entry before it is marked failed.
+    pub max_attempts: u32,
+    /// Delay before the first retry; doubled after each failure.
+    pub initial_backoff: Duration,
+    /// Upper bound on the retry delay.
+    pub max_backoff: Duration,
+    /// How often the dispatcher looks for due entries when not woken.
+    pub poll_interval: Duration,
+}
+
+impl Default for OutboxConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(30),
+            poll_interval: Duration::from_secs(1),
+        }
+    }
+}
+
+impl OutboxConfig {
+    pub fn with_max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    pub fn with_backoff(mut self, initial: Duration, max: Duration) -> Self {
+        self.initial_backoff = initial;
+        self.max_backoff = max;
+        self
+    }
+
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    fn backoff_for(&self, attempts: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempts.saturating_sub(1));
+        self.initial_backoff
+            .saturating_mul(factor)
+            .min(self.max_backoff)
+    }
+}
+
+/// Executes recorded intents with retries.
+pub struct OutboxDispatcher {
+    store: Arc<dyn OutboxStore>,
+    effects: RwLock<HashMap<String, Arc<dyn ToolExecutor>>>,
+    config: OutboxConfig,
+    wake: Notify,
+    stopped: AtomicBool,
+}
+
+impl OutboxDispatcher {
+    pub fn new(store: Arc<dyn OutboxStore>, config: OutboxConfig) -> Self {
+        Self {
+            store,
+            effects: RwLock::new(HashMap::new()),
+            config,
+            wake: Notify::new(),
+            stopped: AtomicBool::new(false),
+        }
+    }
+
+    pub fn store(&self) -> Arc<dyn OutboxStore> {
+        self.store.clone()
+    }
+
+    /// Register the effect that runs for `tool_name` intents.
+    pub async fn register_effect(
+        &self,
+        tool_name: impl Into<String>,
+        effect: Arc<dyn ToolExecutor>,
+    ) {
+        self.effects.write().await.insert(tool_name.into(), effect);
+    }
+
+    /// Look up an entry by id.
+    pub async fn entry(&self, id: &str) -> Option<OutboxEntry> {
+        self.store.get_entry(id).await
+    }
+
+    /// Wake the dispatch loop so new intents are handled promptly.
+    pub fn notify(&self) {
+        self.wake.notify_one();
+    }
+
+    /// Stop the dispatch loop after its current pass.
+    pub fn shutdown(&self) {
+        self.stopped.store(true, Ordering::SeqCst);
+        self.wake.notify_one();
+    }
+
+    /// Run one dispatch pass over the due entries. Returns how many were attempted.
+    pub async fn dispatch_due(&self) -> usize {
+        let due = self.store.claim_due(now_ms()).await;
+        let attempted = due.len();
+        for entry in due {
+            self.dispatch_entry(entry).await;
+        }
+        attempted
+    }
+
+    async fn dispatch_entry(&self, entry: OutboxEntry) {
+        let effect = self.effects.read().await.get(&entry.tool_name).cloned();
+        let outcome = match effect {
+            Some(effect) => effect.execute(entry.args.clone()).await,
+            None => Err(BamlRtError::ToolExecution(format!(
+                "No outbox effect registered for tool '{}'",
+                entry.tool_name
+            ))),
+        };
+
+        match outcome {
+            Ok(result) => {
+                metrics::record_outbox_dispatch(&entry.tool_name, "dispatched");
+                tracing::info!(
+                    outbox_id = entry.id.as_str(),
+                    tool = entry.tool_name.as_str(),
+                    attempts = entry.attempts,
+                    "Outbox entry dispatched"
+                );
+                self.store.mark_dispatched(&entry.id, result).await;
+            }
+            Err(err) => {
+                let retry_at_ms = (entry.attempts < self.config.max_attempts)
+                    .then(|| now_ms() + self.config.backoff_for(entry.attempts).as_millis() as u64);
+                let outcome = if retry_at_ms.is_some() {
+                    "retry"
+                } else {
+                    "failed"
+                };
+                metrics::record_outbox_dispatch(&entry.tool_name, outcome);
+                tracing::warn!(
+                    outbox_id = entry.id.as_str(),
+                    tool = entry.tool_name.as_str(),
+                    attempts = entry.attempts,
+                    error = %err,
+                    will_retry = retry_at_ms.is_some(),
+                    "Outbox dispatch attempt failed"
+                );
+                self.store
+                    .mark_attempt_failed(&entry.id, err.to_string(), retry_at_ms)
+                    .await;
+            }
+        }
+    }
+
+    /// Dispatch entries until [`shutdown`](Self::shutdown) is called.
+    pub async fn run(self: Arc<Self>) {
+        while !self.stopped.load(Ordering::SeqCst) {
+            self.dispatch_due().await;
+            let _ = tokio::time::timeout(self.config.poll_interval, self.wake.notified()).await;
+        }
+    }
+
+    /// Spawn the dispatch loop on the current Tokio runtime.
+    pub fn spawn(self: &Arc<Self>) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(self.clone().run())
+    }
+
+    /// Spawn the dispatch loop, stopping it when the returned handle drops.
+    pub fn start(self: &Arc<Self>) -> DispatchLoop {
+        DispatchLoop {
+            dispatcher: self.clone(),
+            task: self.spawn(),
+        }
+    }
+}
+
+/// A running dispatch loop, shut down once dropped.
+pub struct DispatchLoop {
+    dispatcher: Arc<OutboxDispatcher>,
+    task: tokio::task::JoinHandle<()>,
+}
+
+impl DispatchLoop {
+    /// Whether the loop has exited.
+    pub fn is_finished(&self) -> bool {
+        self.task.is_finished()
+    }
+}
+
+impl Drop for DispatchLoop {
+    fn drop(&mut self) {
+        // Let the current pass finish so no claimed entry is left in flight
+        self.dispatcher.shutdown();
+    }
+}
+
+/// Tool executor that records an intent instead of running the effect.
+pub struct OutboxToolExecutor {
+    tool_name: String,
+    dispatcher: Arc<OutboxDispatcher>,
+}
+
+impl OutboxToolExecutor {
+    pub fn new(tool_name: impl Into<String>, dispatcher: Arc<OutboxDispatcher>) -> Self {
+        Self {
+            tool_name: tool_name.into(),
+            dispatcher,
+        }
+    }
+}
+
+#[async_trait]
+impl ToolExecutor for OutboxToolExecutor {
+    async fn execute(&self, args: Value) -> Result<Value> {
+        let context_id = context::current_context_id();
+        let message_id = context::current_metadata()
+            .and_then(|metadata| metadata.get(context::MESSAGE_ID_KEY))
+            .and_then(|message_id| message_id.as_str().map(str::to_string));
+        let key = idempotency_key(
+            &self.tool_name,
+            tenant::current_tenant_id().as_ref(),
+            auth::current_principal().as_ref(),
+            message_id.as_deref(),
+            &args,
+        )?;
+        let mut entry = OutboxEntry::new(key, self.tool_name.clone(), args);
+        entry.context_id = context_id;
+
+        let entry = self.dispatcher.store.record_intent(entry).await;
+        if entry.status == OutboxStatus::Pending {
+            self.dispatcher.notify();
+        }
+        Ok(entry.to_tool_result())
+    }
+}
+
+/// Idempotency key for a tool call, scoped to the tenant and principal
+/// making it.
+///
+/// The caller's explicit key is used when it gives one. Otherwise the key
+/// hashes the arguments with the ID of the message being served, which a
+/// retried request sends again; outside a message request there is nothing
+/// stable to derive a key from, so the call is rejected.
+pub fn idempotency_key(
+    tool_name: &str,
+    tenant_id: Option<&TenantId>,
+    principal: Option<&Principal>,
+    message_id: Option<&str>,
+    args: &Value,
+) -> Result<String> {
+    let tenant_id = tenant_id.map(TenantId::as_str).unwrap_or("");
+    let principal = principal.map(Principal::id).unwrap_or("");
+    let serialized;
+    let request = match (
+        args.get(IDEMPOTENCY_KEY_ARG).and_then(Value::as_str),
+        message_id,
+    ) {
+        (Some(key), _) => ["key", key, ""],
+        (None, Some(message_id)) => {
+            serialized = serde_json::to_string(args)?;
+            ["message", message_id, serialized.as_str()]
+        }
+        (None, None) => {
+            return Err(BamlRtError::InvalidArgument(format!(
+                "Outbox tool '{}' needs an '{}' argument outside a message request",
+                tool_name, IDEMPOTENCY_KEY_ARG
+            )));
+        }
+    };
+    // Length-prefixed, so no choice of parts can collide with another's
+    let mut digest = Sha256::new();
+    for part in [tenant_id, principal].iter().chain(&request) {
+        digest.update((part.len() as u64).to_be_bytes());
+        digest.update(part.as_bytes());
+    }
+    Ok(format!("{}:{:x}", tool_name, digest.finalize()))
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::a2a_store::TaskStore;
+    use baml_rt_core::auth::{AllowAll, Caller};
+    use baml_rt_core::tenant::Tenant;
+    use std::sync::atomic::AtomicU32;
+    use tokio::sync::Mutex;
+
+    struct FlakyEffect {
+        failures_left: AtomicU32,
+        calls: AtomicU32,
+    }
+
+    #[async_trait]
+    impl ToolExecutor for FlakyEffect {
+        async fn execute(&self, args: Value) -> Result<Value> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            if self
+                .failures_left
+                .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1))
+                .is_ok()
+            {
+                return Err(BamlRtError::ToolExecution("smtp unavailable".to_string()));
+            }
+            Ok(json!({ "sent_to": args["to"] }))
+        }
+    }
+
+    fn dispatcher(config: OutboxConfig) -> Arc<OutboxDispatcher> {
+        let store: Arc<dyn OutboxStore> = Arc::new(Mutex::new(TaskStore::new()));
+        Arc::new(OutboxDispatcher::new(store, config))
+    }
+
+    /// Run `fut` as part of serving message `message_id`, in a fresh context
+    async fn in_message<T>(message_id: &str, fut: impl Future<Output = T>) -> T {
+        let metadata = context::RequestMetadata::default();
+        metadata.set(context::MESSAGE_ID_KEY, json!(message_id));
+        context::with_context(context::generate_context_id(), metadata, fut).await
+    }
+
+    /// Run `fut` on behalf of `principal` acting for `tenant_id`
+    async fn as_caller<T>(tenant_id: &str, principal: &str, fut: impl Future<Output = T>) -> T {
+        let tenant = Tenant::new(TenantId::from(tenant_id), Arc::default());
+        let caller = Caller::new(Some(Principal::new(principal)), Arc::new(AllowAll));
+        tenant::with_tenant(Some(tenant), auth::with_caller(Some(caller), fut)).await
+    }
+
+    #[tokio::test]
+    async fn repeated_intents_share_one_entry_and_effect() {
+        let dispatcher = dispatcher(OutboxConfig::default());
+        let effect = Arc::new(FlakyEffect {
+            failures_left: AtomicU32::new(0),
+            calls: AtomicU32::new(0),
+        });
+        dispatcher
+            .register_effect("send_email", effect.clone())
+            .await;
+        let tool = OutboxToolExecutor::new("send_email", dispatcher.clone());
+
+        let args = json!({ "to": "ops@example.com", "idempotency_key": "welcome-1" });
+        let first = tool.execute(args.clone()).await.expect("record intent");
+        assert_eq!(first["status"], "pending");
+
+        assert_eq!(dispatcher.dispatch_due().await, 1);
+        let replayed = tool.execute(args).await.expect("replayed intent");
+        assert_eq!(replayed["outbox_id"], first["outbox_id"]);
+        assert_eq!(replayed["status"], "dispatched");
+        assert_eq!(replayed["result"]["sent_to"], "ops@example.com");
+
+        assert_eq!(dispatcher.dispatch_due().await, 0);
+        assert_eq!(effect.calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn failed_effects_retry_until_max_attempts() {
+        let config = OutboxConfig::default()
+            .with_max_attempts(2)
+            .with_backoff(Duration::ZERO, Duration::ZERO);
+        let dispatcher = dispatcher(config);
+        let effect = Arc::new(FlakyEffect {
+            failures_left: AtomicU32::new(5),
+            calls: AtomicU32::new(0),
+        });
+        dispatcher.register_effect("charge", effect.clone()).await;
+        let tool = OutboxToolExecutor::new("charge", dispatcher.clone());
+
+        let result = in_message("msg-1", tool.execute(json!({ "amount": 10 })))
+            .await
+            .expect("record intent");
+        let id = result["outbox_id"].as_str().expect("outbox id").to_string();
+
+        dispatcher.dispatch_due().await;
+        let entry = dispatcher.entry(&id).await.expect("entry");
+        assert_eq!(entry.status, OutboxStatus::Pending);
+        assert_eq!(entry.attempts, 1);
+
+        dispatcher.dispatch_due().await;
+        let entry = dispatcher.entry(&id).await.expect("entry");
+        assert_eq!(entry.status, OutboxStatus::Failed);
+        assert_eq!(
+            entry.last_error.as_deref(),
+            Some("Tool execution error: smtp unavailable")
+        );
+        assert_eq!(effect.calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn retried_messages_reuse_their_intent() {
+        let dispatcher = dispatcher(OutboxConfig::default());
+        let tool = OutboxToolExecutor::new("charge", dispatcher.clone());
+        let args = json!({ "amount": 10 });
+
+        // Each retry arrives without a context ID and is given a new one
+        let first = in_message("msg-1", tool.execute(args.clone()))
+            .await
+            .expect("record intent");
+        let retried = in_message("msg-1", tool.execute(args.clone()))
+            .await
+            .expect("retried intent");
+        assert_eq!(retried["outbox_id"], first["outbox_id"]);
+
+        let next = in_message("msg-2", tool.execute(args.clone()))
+            .await
+            .expect("next intent");
+        assert_ne!(next["outbox_id"], first["outbox_id"]);
+
+        assert!(matches!(
+            tool.execute(args).await,
+            Err(BamlRtError::InvalidArgument(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn explicit_keys_are_scoped_to_the_caller() {
+        let dispatcher = dispatcher(OutboxConfig::default());
+        let tool = OutboxToolExecutor::new("send_email", dispatcher.clone());
+        let args = json!({ "to": "ops@example.com", "idempotency_key": "welcome-1" });
+        let record = |tenant_id: &'static str, principal: &'static str| {
+            as_caller(tenant_id, principal, tool.execute(args.clone()))
+        };
+
+        let acme = record("acme", "support").await.expect("acme intent");
+        let again = record("acme", "support").await.expect("acme intent");
+        let globex = record("globex", "support").await.expect("globex intent");
+        let billing = record("acme", "billing").await.expect("billing intent");
+        assert_eq!(again["outbox_id"], acme["outbox_id"]);
+        assert_ne!(globex["outbox_id"], acme["outbox_id"]);
+        assert_ne!(billing["outbox_id"], acme["outbox_id"]);
+    }
+
+    #[test]
+    fn keys_and_ids_are_stable_digests() {
+        let key = idempotency_key(
+            "charge",
+            Some(&TenantId::from("acme")),
+            None,
+            Some("msg-1"),
+            &json!({ "amount": 10 }),
+        )
+        .expect("key");
+        assert_eq!(
+            key,
+            "charge:edfb71c72fde778ae0f07b67a4a90dcb7c205609c09f3ad8b82dee12976e3fa0"
+        );
+        let entry = OutboxEntry::new(key, "charge".to_string(), json!({ "amount": 10 }));
+        assert_eq!(
+            entry.id,
+            "outbox-4ddc27c197dc91cc55e4f9de402b9cf9caee2735549f0dd9f96b4bfab927b70d"
+        );
+    }
+
+    #[tokio::test]
+    async fn dropping_the_loop_stops_dispatch() {
+        let config = OutboxConfig::default().with_poll_interval(Duration::from_secs(60));
+        let dispatch_loop = dispatcher(config).start();
+        assert!(!dispatch_loop.is_finished());
+
+        let dispatcher = dispatch_loop.dispatcher.clone();
+        drop(dispatch_loop);
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while Arc::strong_count(&dispatcher) > 1 {
+                tokio::task::yield_now().await;
+            }
+        })
+        .await
+        .expect("dispatch loop exits");
+    }
+}
@@ -0,0 +1,434 @@
+//! File and data parts of A2A messages
+//!
+//! Incoming message parts are resolved into a uniform list handed to the JS
+//! handler as `params.parts`:
+//!
+//! - `{ kind: "text", text }`
+//! - `{ kind: "data", data }`
+//! - `{ kind: "file", name, mediaType, uri, bytes, size, text }`, where
+//!   `bytes` is base64 and `text` is set for textual media types
+//!
+//! Inline bytes come from `raw` (or the older `file.bytes`). A file given
+//! only by `url` (or `file.uri`) is downloaded when the [`FilePolicy`] allows
+//! it, and passed through with just its `uri` otherwise.
+//!
+//! Agents may answer with file parts in either shape; [`normalize_file_parts`]
+//! rewrites the older `{ file: { bytes, uri, mimeType, name } }` form to
+//! `raw`/`url`/`mediaType`/`filename`.
+
+use crate::a2a_types::Part;
+use async_trait::async_trait;
+use baml_rt_core::{BamlRtError, Result};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use serde_json::{Map, Value, json};
+use std::sync::Arc;
+
+/// Limits on the file parts an agent accepts
+#[derive(Debug, Clone)]
+pub struct FilePolicy {
+    /// Download files referenced by URI
+    pub fetch_uris: bool,
+    /// URI schemes that may be downloaded
+    pub allowed_schemes: Vec<String>,
+    /// Hosts that may be downloaded from; any host when empty
+    pub allowed_hosts: Vec<String>,
+    /// Largest file accepted, inline or downloaded
+    pub max_bytes: usize,
+}
+
+impl Default for FilePolicy {
+    fn default() -> Self {
+        Self {
+            fetch_uris: false,
+            allowed_schemes: vec!["https".to_string()],
+            allowed_hosts: Vec::new(),
+            max_bytes: 10 * 1024 * 1024,
+        }
+    }
+}
+
+impl FilePolicy {
+    /// Allow downloading files referenced by URI
+    pub fn with_uri_fetching(mut self, enabled: bool) -> Self {
+        self.fetch_uris = enabled;
+        self
+    }
+
+    /// Only download from `host`; call repeatedly to allow several hosts
+    pub fn with_allowed_host(mut self, host: impl Into<String>) -> Self {
+        self.allowed_hosts.push(host.into());
+        self
+    }
+
+    pub fn with_allowed_schemes(mut self, schemes: Vec<String>) -> Self {
+        self.allowed_schemes = schemes;
+        self
+    }
+
+    pub fn with_max_bytes(mut self, max_bytes: usize) -> Self {
+        self.max_bytes = max_bytes;
+        self
+    }
+
+    /// Reject `uri` unless its scheme and host are allowed
+    fn check_uri(&self, uri: &str) -> Result<()> {
+        let url = reqwest::Url::parse(uri).map_err(|e| {
+            BamlRtError::InvalidArgument(format!("Invalid file URI '{}': {}", uri, e))
+        })?;
+        if !self
+            .allowed_schemes
+            .iter()
+            .any(|scheme| scheme == url.scheme())
+        {
+            return Err(BamlRtError::InvalidArgument(format!(
+                "File URI scheme '{}' is not allowed",
+                url.scheme()
+            )));
+        }
+        let host = url.host_str().unwrap_or_default();
+        if !self.allowed_hosts.is_empty() && !self.allowed_hosts.iter().any(|h| h == host) {
+            return Err(BamlRtError::InvalidArgument(format!(
+                "File host '{}' is not allowed",
+                host
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// A downloaded file
+#[derive(Debug, Clone)]
+pub struct FetchedFile {
+    pub bytes: Vec<u8>,
+    pub media_type: Option<String>,
+}
+
+/// Downloads files referenced by URI
+#[async_trait]
+pub trait UriFetcher: Send + Sync {
+    /// Download `uri`, failing once more than `max_bytes` have been read
+    async fn fetch(&self, uri: &str, max_bytes: usize) -> Result<FetchedFile>;
+}
+
+/// [`UriFetcher`] over HTTP(S)
+#[derive(Default)]
+pub struct HttpUriFetcher {
+    client: reqwest::Client,
+}
+
+impl HttpUriFetcher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl UriFetcher for HttpUriFetcher {
+    async fn fetch(&self, uri: &str, max_bytes: usize) -> Result<FetchedFile> {
+        let fetch_error = |e: reqwest::Error| {
+            BamlRtError::InvalidArgument(format!("Failed to fetch '{}': {}", uri, e))
+        };
+        let mut response = self
+            .client
+            .get(uri)
+            .send()
+            .await
+            .and_then(reqwest::Response::error_for_status)
+            .map_err(fetch_error)?;
+        let media_type = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string);
+        let mut bytes = Vec::new();
+        while let Some(chunk) = response.chunk().await.map_err(fetch_error)? {
+            if bytes.len() + chunk.len() > max_bytes {
+                return Err(too_large(uri, max_bytes));
+            }
+            bytes.extend_from_slice(&chunk);
+        }
+        Ok(FetchedFile { bytes, media_type })
+    }
+}
+
+fn too_large(name: &str, max_bytes: usize) -> BamlRtError {
+    BamlRtError::InvalidArgument(format!(
+        "File '{}' is larger than {} bytes",
+        name, max_bytes
+    ))
+}
+
+/// Resolves message parts for the JS handler under a [`FilePolicy`]
+pub struct PartResolver {
+    policy: FilePolicy,
+    fetcher: Arc<dyn UriFetcher>,
+}
+
+impl Default for PartResolver {
+    fn default() -> Self {
+        Self::new(FilePolicy::default())
+    }
+}
+
+impl PartResolver {
+    pub fn new(policy: FilePolicy) -> Self {
+        Self {
+            policy,
+            fetcher: Arc::new(HttpUriFetcher::new()),
+        }
+    }
+
+    /// Download files with `fetcher` instead of over HTTP
+    pub fn with_fetcher(mut self, fetcher: Arc<dyn UriFetcher>) -> Self {
+        self.fetcher = fetcher;
+        self
+    }
+
+    pub fn policy(&self) -> &FilePolicy {
+        &self.policy
+    }
+
+    /// The resolved form of each part, in order
+    pub async fn resolve(&self, parts: &[Part]) -> Result<Vec<Value>> {
+        let mut resolved = Vec::with_capacity(parts.len());
+        for part in parts {
+            resolved.push(self.resolve_part(part).await?);
+        }
+        Ok(resolved)
+    }
+
+    async fn resolve_part(&self, part: &Part) -> Result<Value> {
+        let file = FileFields::of(part);
+        if file.is_empty() {
+            if let Some(text) = &part.text {
+                return Ok(json!({ "kind": "text", "text": text }));
+            }
+            let data = part.data.clone().unwrap_or(Value::Null);
+            return Ok(json!({ "kind": "data", "data": data }));
+        }
+
+        let name = file.name.clone().unwrap_or_else(|| "file".to_string());
+        let mut media_type = file.media_type.clone();
+        let bytes = match (&file.bytes, &file.uri) {
+            (Some(encoded), _) => {
+                let bytes = STANDARD.decode(encoded).map_err(|e| {
+                    BamlRtError::InvalidArgument(format!(
+                        "Invalid base64 in file part '{}': {}",
+                        name, e
+                    ))
+                })?;
+                if bytes.len() > self.policy.max_bytes {
+                    return Err(too_large(&name, self.policy.max_bytes));
+                }
+                Some(bytes)
+            }
+            (None, Some(uri)) if self.policy.fetch_uris => {
+                self.policy.check_uri(uri)?;
+                let fetched = self.fetcher.fetch(uri, self.policy.max_bytes).await?;
+                media_type = media_type.or(fetched.media_type);
+                Some(fetched.bytes)
+            }
+            _ => None,
+        };
+
+        let mut resolved = Map::new();
+        resolved.insert("kind".to_string(), json!("file"));
+        for (key, value) in [
+            ("name", file.name),
+            ("mediaType", media_type.clone()),
+            ("uri", file.uri),
+        ] {
+            if let Some(value) = value {
+                resolved.insert(key.to_string(), Value::String(value));
+            }
+        }
+        if let Some(bytes) = bytes {
+            resolved.insert("size".to_string(), json!(bytes.len()));
+            if is_textual(media_type.as_deref())
+                && let Ok(text) = std::str::from_utf8(&bytes)
+            {
+                resolved.insert("text".to_string(), json!(text));
+            }
+            resolved.insert("bytes".to_string(), json!(STANDARD.encode(&bytes)));
+        }
+        Ok(Value::Object(resolved))
+    }
+}
+
+/// File fields of a part, from the current or the older `file` shape
+#[derive(Default)]
+struct FileFields {
+    bytes: Option<String>,
+    uri: Option<String>,
+    name: Option<String>,
+    media_type: Option<String>,
+}
+
+impl FileFields {
+    fn of(part: &Part) -> Self {
+        let legacy = part.extra.get("file");
+        let legacy_field = |key: &str| {
+            legacy
+                .and_then(|file| file.get(key))
+                .and_then(Value::as_str)
+                .map(str::to_string)
+        };
+        Self {
+            bytes: part.raw.clone().or_else(|| legacy_field("bytes")),
+            uri: part.url.clone().or_else(|| legacy_field("uri")),
+            name: part.filename.clone().or_else(|| legacy_field("name")),
+            media_type: part
+                .media_type
+                .clone()
+                .or_else(|| legacy_field("mimeType"))
+                .or_else(|| legacy_field("mediaType")),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.bytes.is_none() && self.uri.is_none()
+    }
+}
+
+fn is_textual(media_type: Option<&str>) -> bool {
+    media_type.is_some_and(|media_type| {
+        media_type.starts_with("text/")
+            || media_type.starts_with("application/json")
+            || media_type.ends_with("+json")
+    })
+}
+
+/// Rewrite `{ file: { bytes, uri, mimeType, name } }` parts anywhere in an
+/// agent response to `raw`/`url`/`mediaType`/`filename`
+pub fn normalize_file_parts(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            if let Some(Value::Array(parts)) = map.get_mut("parts") {
+                for part in parts.iter_mut() {
+                    normalize_file_part(part);
+                }
+            }
+            for child in map.values_mut() {
+                normalize_file_parts(child);
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(normalize_file_parts),
+        _ => {}
+    }
+}
+
+fn normalize_file_part(part: &mut Value) {
+    let Some(map) = part.as_object_mut() else {
+        return;
+    };
+    let Some(Value::Object(file)) = map.remove("file") else {
+        return;
+    };
+    for (from, to) in [
+        ("bytes", "raw"),
+        ("uri", "url"),
+        ("mimeType", "mediaType"),
+        ("mediaType", "mediaType"),
+        ("name", "filename"),
+    ] {
+        if let Some(value) = file.get(from) {
+            map.entry(to.to_string()).or_insert_with(|| value.clone());
+        }
+    }
+    if map.get("kind").and_then(Value::as_str) == Some("file") {
+        map.remove("kind");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct StaticFetcher;
+
+    #[async_trait]
+    impl UriFetcher for StaticFetcher {
+        async fn fetch(&self, _uri: &str, max_bytes: usize) -> Result<FetchedFile> {
+            let bytes = b"{\"ok\":true}".to_vec();
+            if bytes.len() > max_bytes {
+                return Err(too_large("static", max_bytes));
+            }
+            Ok(FetchedFile {
+                bytes,
+                media_type: Some("application/json".to_string()),
+            })
+        }
+    }
+
+    fn part(value: Value) -> Part {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[tokio::test]
+    async fn resolves_inline_legacy_and_uri_parts() {
+        let parts = vec![
+            part(json!({ "text": "summarize these" })),
+            part(json!({ "raw": "aGVsbG8=", "mediaType": "text/plain", "filename": "a.txt" })),
+            part(json!({ "kind": "file", "file": { "bytes": "AAE=", "mimeType": "image/png" } })),
+            part(json!({ "url": "https://files.example.com/r.json" })),
+            part(json!({ "data": { "rows": 2 } })),
+        ];
+
+        let passthrough = PartResolver::default().resolve(&parts).await.unwrap();
+        assert_eq!(
+            passthrough[0],
+            json!({ "kind": "text", "text": "summarize these" })
+        );
+        assert_eq!(passthrough[1]["text"], json!("hello"));
+        assert_eq!(passthrough[1]["size"], json!(5));
+        assert_eq!(passthrough[2]["mediaType"], json!("image/png"));
+        assert_eq!(passthrough[2].get("text"), None);
+        assert_eq!(
+            passthrough[3],
+            json!({ "kind": "file", "uri": "https://files.example.com/r.json" })
+        );
+        assert_eq!(passthrough[4]["data"], json!({ "rows": 2 }));
+
+        let fetching = PartResolver::new(
+            FilePolicy::default()
+                .with_uri_fetching(true)
+                .with_allowed_host("files.example.com"),
+        )
+        .with_fetcher(Arc::new(StaticFetcher));
+        let resolved = fetching.resolve(&parts).await.unwrap();
+        assert_eq!(resolved[3]["text"], json!("{\"ok\":true}"));
+        assert_eq!(resolved[3]["mediaType"], json!("application/json"));
+
+        let other_host = [part(json!({ "url": "https://evil.example.net/x" }))];
+        assert!(fetching.resolve(&other_host).await.is_err());
+        let plain_http = [part(json!({ "url": "http://files.example.com/x" }))];
+        assert!(fetching.resolve(&plain_http).await.is_err());
+        let small = PartResolver::new(FilePolicy::default().with_max_bytes(2));
+        assert!(small.resolve(&parts[1..2]).await.is_err());
+    }
+
+    #[test]
+    fn normalizes_legacy_file_parts_in_responses() {
+        let mut response = json!({
+            "message": {
+                "parts": [
+                    { "text": "see attached" },
+                    {
+                        "kind": "file",
+                        "file": { "bytes": "AAE=", "mimeType": "image/png", "name": "x.png" }
+                    }
+                ]
+            }
+        });
+        normalize_file_parts(&mut response);
+        assert_eq!(
+            response["message"]["parts"][1],
+            json!({ "raw": "AAE=", "mediaType": "image/png", "filename": "x.png" })
+        );
+        assert_eq!(
+            response["message"]["parts"][0],
+            json!({ "text": "see attached" })
+        );
+    }
+}
@@ -1,11 +1,20 @@
 use crate::a2a;
+use crate::a2a_store::{TaskRepository, TaskState, generate_task_id};
+use crate::a2a_types::{Artifact, GetArtifactRequest, NumberOrString, Part, SendMessageRequest};
+use crate::approval::{self, PendingApproval};
+use crate::background::BackgroundExecutor;
 use crate::handlers::TaskHandler;
-use crate::result_pipeline::ResultStoragePipeline;
+use crate::input_required::PendingInput;
+use crate::parts::{PartResolver, normalize_file_parts};
+use crate::result_pipeline::{ResultStoragePipeline, TaskResultRecorder};
 use crate::stream_normalizer::StreamNormalizer;
+use crate::task_cancellation::TaskCancellations;
 use async_trait::async_trait;
-use baml_rt_core::{BamlRtError, Result};
-use baml_rt_quickjs::QuickJSBridge;
-use serde_json::Value;
+use baml_rt_core::ids::TaskId;
+use baml_rt_core::{BamlRtError, JsException, Result, context};
+use baml_rt_quickjs::{ArtifactStore, QuickJSBridge};
+use baml_rt_tools::{ApprovalDecision, ApprovalGate, ProposedToolCall};
+use serde_json::{Value, json};
 use std::sync::Arc;
 use tokio::sync::Mutex;
 
@@ -45,16 +54,13 @@ impl JsInvoker for QuickJsInvoker {
     async fn invoke_stream(&self, request: &a2a::A2aRequest) -> Result<Vec<Value>> {
         let result = self.invoke_handler(request).await?;
         match result {
-            Value::Array(values) => values
-                .into_iter()
-                .map(|value| self.stream_normalizer.normalize_chunk(value))
-                .collect::<Result<Vec<Value>>>(),
-            Value::Object(map) if map.get("error").is_some() => Err(BamlRtError::QuickJs(
-                map.get("error")
-                    .and_then(Value::as_str)
-                    .unwrap_or("unknown")
-                    .to_string(),
-            )),
+            Value::Array(values) => self.stream_normalizer.normalize_stream(values),
+            error @ Value::Object(_) if error.get("error").is_some() => {
+                Err(match JsException::from_error_object(&error) {
+                    Some(exception) => BamlRtError::JsException(exception),
+                    None => BamlRtError::QuickJs(error["error"].to_string()),
+                })
+            }
             other => Ok(vec![self.stream_normalizer.normalize_chunk(other)?]),
         }
     }
@@ -69,6 +75,13 @@ pub struct MethodBasedRouter {
     task_handler: Arc<dyn TaskHandler>,
     js_invoker: Arc<dyn JsInvoker>,
     result_pipeline: Arc<dyn ResultStoragePipeline>,
+    history: Option<Arc<dyn TaskRepository>>,
+    parts: Option<Arc<PartResolver>>,
+    artifacts: Option<Arc<dyn ArtifactStore>>,
+    background: Option<Arc<BackgroundExecutor>>,
+    paused_tasks: Option<Arc<dyn TaskRepository>>,
+    cancellations: Option<Arc<TaskCancellations>>,
+    approvals: Option<Arc<ApprovalGate>>,
 }
 
 impl MethodBasedRouter {
@@ -81,7 +94,329 @@ impl MethodBasedRouter {
             task_handler,
             js_invoker,
             result_pipeline,
+            history: None,
+            parts: None,
+            artifacts: None,
+            background: None,
+            paused_tasks: None,
+            cancellations: None,
+            approvals: None,
+        }
+    }
+
+    /// Pause the task of a message request whose handler proposed a tool
+    /// call that `gate` holds for approval, and decide the call with the
+    /// next message naming the task
+    pub fn with_tool_approvals(mut self, gate: Arc<ApprovalGate>) -> Self {
+        self.approvals = Some(gate);
+        self
+    }
+
+    /// Register message requests naming a task in `message.taskId` with
+    /// `cancellations` while they run, so `tasks.cancel` can abort them
+    pub fn with_task_cancellations(mut self, cancellations: Arc<TaskCancellations>) -> Self {
+        self.cancellations = Some(cancellations);
+        self
+    }
+
+    /// Run `fut` as the work of `task_id`, when cancellations are tracked
+    async fn cancelable<F, T>(&self, task_id: Option<TaskId>, fut: F) -> Result<T>
+    where
+        F: std::future::Future<Output = Result<T>>,
+    {
+        match (&self.cancellations, task_id) {
+            (Some(cancellations), Some(task_id)) => cancellations.run(&task_id, fut).await,
+            _ => fut.await,
+        }
+    }
+
+    /// Serve `artifacts.get` from `store`
+    pub fn with_artifact_store(mut self, store: Arc<dyn ArtifactStore>) -> Self {
+        self.artifacts = Some(store);
+        self
+    }
+
+    /// Run `message.send` requests sent with `configuration.blocking: false`
+    /// on `executor`, answering them with the submitted task
+    pub fn with_background_executor(mut self, executor: Arc<BackgroundExecutor>) -> Self {
+        self.background = Some(executor);
+        self
+    }
+
+    /// Resume tasks paused for input in `repository` when a message names
+    /// them in `message.taskId`
+    pub fn with_input_resume(mut self, repository: Arc<dyn TaskRepository>) -> Self {
+        self.paused_tasks = Some(repository);
+        self
+    }
+
+    /// What the task a message request names waits for, if it is paused
+    async fn pending_input(&self, request: &a2a::A2aRequest) -> Option<PendingInput> {
+        if !matches!(
+            request.method,
+            a2a::A2aMethod::MessageSend | a2a::A2aMethod::MessageSendStream
+        ) {
+            return None;
         }
+        let repository = self.paused_tasks.as_ref()?;
+        let task_id = request.params.pointer("/message/taskId")?.as_str()?;
+        let task = repository.get(task_id, Some(0)).await?;
+        PendingInput::of(&task)
+    }
+
+    /// Hand a paused task the input it waited for
+    async fn resume(
+        &self,
+        request: &a2a::A2aRequest,
+        pending: PendingInput,
+    ) -> Result<a2a::A2aOutcome> {
+        let context_id = pending
+            .context_id
+            .clone()
+            .or_else(|| request.context_id.clone())
+            .unwrap_or_else(context::current_or_new);
+        let recorder = TaskResultRecorder::new(
+            self.result_pipeline.clone(),
+            pending.task_id.clone(),
+            context_id,
+        );
+        recorder.record_state(TaskState::Working, None).await?;
+        let request = pending.resume_request(request);
+        let task_id = Some(pending.task_id.clone());
+        if request.is_stream {
+            let outcome = self.cancelable(task_id, self.invoke(&request)).await;
+            return self.pause_for_approval(&request, outcome).await;
+        }
+        let outcome = self
+            .cancelable(task_id, self.js_invoker.invoke_handler(&request))
+            .await;
+        match self.held_call(&request) {
+            Some(call) => self.pause(&request, call).await,
+            None => record_outcome(&recorder, outcome).await,
+        }
+    }
+
+    /// Decide the tool call a paused task waits on with the reply in
+    /// `request`
+    ///
+    /// A denied call cancels the task. An approved one handles the message
+    /// that proposed it again, in the task, and this time the call runs.
+    async fn decide_approval(
+        &self,
+        request: &a2a::A2aRequest,
+        pending: PendingInput,
+        approval: PendingApproval,
+    ) -> Result<a2a::A2aOutcome> {
+        let gate = self.approvals.as_ref().ok_or_else(|| {
+            BamlRtError::Configuration("Tool approvals are not configured".to_string())
+        })?;
+        let decision = approval::decision_in(&request.params["message"]).ok_or_else(|| {
+            BamlRtError::InvalidArgument(format!(
+                "Task {} waits for approval of tool {}; reply 'approve' or 'deny'",
+                pending.task_id, approval.call.tool
+            ))
+        })?;
+        gate.decide(&approval.call, decision, "message").await;
+
+        let task_id = pending.task_id.clone();
+        let context_id = pending
+            .context_id
+            .clone()
+            .unwrap_or_else(|| approval.call.context_id.clone());
+        let recorder = TaskResultRecorder::new(
+            self.result_pipeline.clone(),
+            task_id.clone(),
+            context_id.clone(),
+        );
+        if decision == ApprovalDecision::Deny {
+            recorder.record_state(TaskState::Canceled, None).await?;
+            let task = match &self.paused_tasks {
+                Some(repository) => repository.get(task_id.as_str(), Some(0)).await,
+                None => None,
+            };
+            return Ok(a2a::A2aOutcome::Response(json!({ "task": task })));
+        }
+
+        recorder.record_state(TaskState::Working, None).await?;
+        let replay = approval.replay_request(request, &task_id, &context_id);
+        let prepared = self.prepare_message_request(&replay).await?;
+        let replay = prepared.unwrap_or(replay);
+        // The call was proposed, and is approved, in the context it ran in
+        context::with_context_id(approval.call.context_id.clone(), async {
+            if replay.is_stream {
+                let outcome = self.cancelable(Some(task_id), self.invoke(&replay)).await;
+                return self.pause_for_approval(&replay, outcome).await;
+            }
+            let outcome = self
+                .cancelable(Some(task_id), self.js_invoker.invoke_handler(&replay))
+                .await;
+            match self.held_call(&replay) {
+                Some(call) => self.pause(&replay, call).await,
+                None => record_outcome(&recorder, outcome).await,
+            }
+        })
+        .await
+    }
+
+    /// Answer with the paused task instead of `outcome` when the handler
+    /// of `request` proposed a tool call that waits for approval
+    ///
+    /// The handler usually fails when its call is held, but a handler that
+    /// catches the error is paused all the same.
+    async fn pause_for_approval(
+        &self,
+        request: &a2a::A2aRequest,
+        outcome: Result<a2a::A2aOutcome>,
+    ) -> Result<a2a::A2aOutcome> {
+        match self.held_call(request) {
+            Some(call) => self.pause(request, call).await,
+            None => outcome,
+        }
+    }
+
+    /// The tool call the handler of `request` proposed, if it waits for
+    /// approval
+    fn held_call(&self, request: &a2a::A2aRequest) -> Option<ProposedToolCall> {
+        let gate = self.approvals.as_ref()?;
+        // Tools see the context the handler runs in, which a resumed
+        // request's own context may differ from
+        let context_id = context::current_context_id().or_else(|| request.context_id.clone())?;
+        gate.take_pending(&context_id)
+    }
+
+    /// Store and answer with the task of `request`, paused until `call` is
+    /// decided
+    async fn pause(
+        &self,
+        request: &a2a::A2aRequest,
+        call: ProposedToolCall,
+    ) -> Result<a2a::A2aOutcome> {
+        let mut paused = PendingApproval::new(call, request).paused_task();
+        name_new_task(&mut paused, request);
+        self.result_pipeline.store_result(&paused).await?;
+        if request.is_stream {
+            Ok(a2a::A2aOutcome::Stream(vec![paused]))
+        } else {
+            Ok(a2a::A2aOutcome::Response(paused))
+        }
+    }
+
+    /// Run the JS handler and store what it answers
+    async fn invoke(&self, request: &a2a::A2aRequest) -> Result<a2a::A2aOutcome> {
+        if request.is_stream {
+            let mut chunks = self.js_invoker.invoke_stream(request).await?;
+            for chunk in &mut chunks {
+                normalize_file_parts(chunk);
+                self.result_pipeline.store_result(chunk).await?;
+            }
+            Ok(a2a::A2aOutcome::Stream(chunks))
+        } else {
+            let mut result = self.js_invoker.invoke_handler(request).await?;
+            normalize_file_parts(&mut result);
+            name_new_task(&mut result, request);
+            self.result_pipeline.store_result(&result).await?;
+            Ok(a2a::A2aOutcome::Response(result))
+        }
+    }
+
+    /// An artifact as an A2A [`Artifact`] with one base64 `raw` part
+    async fn get_artifact(&self, request: GetArtifactRequest) -> Result<a2a::A2aOutcome> {
+        let store = self.artifacts.as_ref().ok_or_else(|| {
+            BamlRtError::InvalidArgument("Artifact storage is not configured".to_string())
+        })?;
+        let stored = store
+            .get(&request.id)
+            .await?
+            .ok_or_else(|| BamlRtError::InvalidArgument("Artifact not found".to_string()))?;
+        let artifact = Artifact {
+            artifact_id: Some(stored.info.artifact_id.clone()),
+            name: stored.info.name.clone(),
+            parts: vec![Part {
+                raw: Some(stored.content_base64()),
+                filename: stored.info.name.clone(),
+                media_type: stored.info.media_type.clone(),
+                ..Part::default()
+            }],
+            ..Artifact::default()
+        };
+        let value = serde_json::to_value(artifact).map_err(BamlRtError::Json)?;
+        Ok(a2a::A2aOutcome::Response(value))
+    }
+
+    /// Record incoming messages in `repository` and pass each message
+    /// request the earlier messages of its context as `params.history`
+    pub fn with_context_history(mut self, repository: Arc<dyn TaskRepository>) -> Self {
+        self.history = Some(repository);
+        self
+    }
+
+    /// Pass message requests their parts, with files decoded or downloaded,
+    /// as `params.parts`
+    pub fn with_part_resolver(mut self, resolver: Arc<PartResolver>) -> Self {
+        self.parts = Some(resolver);
+        self
+    }
+
+    /// `request` with its history and resolved parts attached, or `None`
+    /// when it is not a message request or there is nothing to attach
+    async fn prepare_message_request(
+        &self,
+        request: &a2a::A2aRequest,
+    ) -> Result<Option<a2a::A2aRequest>> {
+        if !matches!(
+            request.method,
+            a2a::A2aMethod::MessageSend | a2a::A2aMethod::MessageSendStream
+        ) || (self.history.is_none() && self.parts.is_none())
+        {
+            return Ok(None);
+        }
+        let Ok(params) = serde_json::from_value::<SendMessageRequest>(request.params.clone())
+        else {
+            return Ok(None);
+        };
+
+        let mut request = request.clone();
+        let history = self.history(&request, &params).await;
+        let parts = match &self.parts {
+            Some(resolver) => Some(resolver.resolve(&params.message.parts).await?),
+            None => None,
+        };
+        if let Value::Object(map) = &mut request.params {
+            if let Some(history) = history {
+                map.entry("history").or_insert_with(|| json!(history));
+            }
+            if let Some(parts) = parts {
+                map.insert("parts".to_string(), Value::Array(parts));
+            }
+        }
+        Ok(Some(request))
+    }
+
+    /// Prior messages of the request's context
+    ///
+    /// The incoming message is recorded after the history is read, so it is
+    /// part of the history of the next request. `configuration.historyLength`
+    /// limits how many messages are returned.
+    async fn history(
+        &self,
+        request: &a2a::A2aRequest,
+        params: &SendMessageRequest,
+    ) -> Option<Vec<Message>> {
+        let repository = self.history.as_ref()?;
+        let context_id = request.context_id.as_ref()?;
+        let history_length = params
+            .configuration
+            .as_ref()
+            .and_then(|configuration| configuration.history_length.as_ref())
+            .and_then(NumberOrString::as_usize);
+
+        // A replayed request already has its message recorded
+        let mut history = repository
+            .context_history(context_id.as_str(), history_length)
+            .await;
+        history.retain(|message| message.message_id != params.message.message_id);
+        repository.insert_message(&params.message).await;
+        Some(history)
     }
 }
 
@@ -111,19 +446,75 @@ impl RequestRouter for MethodBasedRouter {
                     .handle_subscribe(req, request.is_stream)
                     .await
             }
+            a2a::A2aMethod::ArtifactsGet => {
+                let req =
+                    serde_json::from_value(request.params.clone()).map_err(BamlRtError::Json)?;
+                self.get_artifact(req).await
+            }
             _ => {
-                if request.is_stream {
-                    let chunks = self.js_invoker.invoke_stream(request).await?;
-                    for chunk in &chunks {
-                        self.result_pipeline.store_result(chunk).await?;
+                let prepared = self.prepare_message_request(request).await?;
+                let request = prepared.as_ref().unwrap_or(request);
+                if let Some(pending) = self.pending_input(request).await {
+                    if let Some(approval) = pending.approval.clone() {
+                        return self.decide_approval(request, pending, approval).await;
                     }
-                    Ok(a2a::A2aOutcome::Stream(chunks))
-                } else {
-                    let result = self.js_invoker.invoke_handler(request).await?;
-                    self.result_pipeline.store_result(&result).await?;
-                    Ok(a2a::A2aOutcome::Response(result))
+                    return self.resume(request, pending).await;
+                }
+                if let Some(executor) = &self.background
+                    && BackgroundExecutor::accepts(request)
+                {
+                    let submitted = executor.submit(request).await?;
+                    return Ok(a2a::A2aOutcome::Response(submitted));
                 }
+                let task_id = request
+                    .params
+                    .pointer("/message/taskId")
+                    .and_then(Value::as_str)
+                    .map(TaskId::from);
+                let outcome = self.cancelable(task_id, self.invoke(request)).await;
+                self.pause_for_approval(request, outcome).await
             }
         }
     }
 }
+
+/// File what a handler answered for a task the runtime owns under it
+async fn record_outcome(
+    recorder: &TaskResultRecorder,
+    outcome: Result<Value>,
+) -> Result<a2a::A2aOutcome> {
+    match outcome {
+        Ok(mut result) => {
+            normalize_file_parts(&mut result);
+            recorder.record_result(&mut result).await?;
+            Ok(a2a::A2aOutcome::Response(result))
+        }
+        // `tasks.cancel` already recorded the task as canceled
+        Err(err @ BamlRtError::Canceled { .. }) => Err(err),
+        Err(err) => {
+            recorder.record_failure(err.to_string()).await?;
+            Err(err)
+        }
+    }
+}
+
+/// Give a task the handler returned without an id the one the request's
+/// message names, or a new one, and the request's context
+fn name_new_task(result: &mut Value, request: &a2a::A2aRequest) {
+    let Some(task) = result.get_mut("task").and_then(Value::as_object_mut) else {
+        return;
+    };
+    if !task.contains_key("id") {
+        let task_id = request
+            .params
+            .pointer("/message/taskId")
+            .cloned()
+            .unwrap_or_else(|| json!(generate_task_id()));
+        task.insert("id".to_string(), task_id);
+    }
+    if !task.contains_key("contextId")
+        && let Some(context_id) = &request.context_id
+    {
+        task.insert("contextId".to_string(), json!(context_id));
+    }
+}
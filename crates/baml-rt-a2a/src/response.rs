@@ -1,5 +1,6 @@
 use crate::a2a;
 use crate::a2a_types::JSONRPCId;
+use crate::auth::{FORBIDDEN_CODE, UNAUTHENTICATED_CODE};
 use baml_rt_core::BamlRtError;
 use serde_json::Value;
 
@@ -62,6 +63,74 @@ fn map_jsonrpc_error(error: &BamlRtError) -> (i64, &'static str, Option<Value>)
                 "details": json_err.to_string(),
             })),
         ),
+        BamlRtError::JsException(exception) => (
+            -32000,
+            "JS exception",
+            Some(serde_json::json!({
+                "error": error.to_string(),
+                "exception": exception,
+            })),
+        ),
+        BamlRtError::OutputRejected { guard, reason } => (
+            -32000,
+            "Output rejected",
+            Some(serde_json::json!({
+                "error": error.to_string(),
+                "guard": guard,
+                "details": reason,
+            })),
+        ),
+        BamlRtError::TaskTransition { task_id, from, to } => (
+            -32002,
+            "Invalid task state transition",
+            Some(serde_json::json!({
+                "error": error.to_string(),
+                "taskId": task_id,
+                "from": from,
+                "to": to,
+            })),
+        ),
+        BamlRtError::DeadlineExceeded { operation } => (
+            -32000,
+            "Deadline exceeded",
+            Some(serde_json::json!({
+                "error": error.to_string(),
+                "operation": operation,
+            })),
+        ),
+        BamlRtError::Canceled { operation } => (
+            -32000,
+            "Canceled",
+            Some(serde_json::json!({
+                "error": error.to_string(),
+                "operation": operation,
+            })),
+        ),
+        BamlRtError::RateLimited { tenant } => (
+            -32000,
+            "Rate limit exceeded",
+            Some(serde_json::json!({
+                "error": error.to_string(),
+                "tenant": tenant,
+            })),
+        ),
+        BamlRtError::Unauthenticated(message) => (
+            UNAUTHENTICATED_CODE,
+            "Unauthenticated",
+            Some(serde_json::json!({
+                "error": error.to_string(),
+                "details": message,
+            })),
+        ),
+        BamlRtError::Forbidden { principal, action } => (
+            FORBIDDEN_CODE,
+            "Forbidden",
+            Some(serde_json::json!({
+                "error": error.to_string(),
+                "principal": principal,
+                "action": action,
+            })),
+        ),
         BamlRtError::QuickJsWithSource { context, .. } => (
             -32603,
             "Internal error",
@@ -1,11 +1,21 @@
+//! Deduplication of stored results.
+//!
+//! Results pass through a [`ResultDeduplicator`] before they are stored, so
+//! a result delivered twice, e.g. by a retried request, is stored once. What
+//! counts as a duplicate is set by a [`DeduplicationConfig`]: the whole
+//! result or only some of its fields, remembered forever or for a time
+//! window. Agents that legitimately emit identical consecutive results can
+//! narrow the window or turn deduplication off.
+
 use crate::result_pipeline::ResultStoragePipeline;
 use async_trait::async_trait;
-use baml_rt_core::Result;
+use baml_rt_core::{Result, tenant};
 use serde_json::Value;
-use std::collections::HashSet;
+use std::collections::HashMap;
 use std::collections::hash_map::DefaultHasher;
 use std::hash::{Hash, Hasher};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::sync::Mutex;
 
 #[async_trait]
@@ -14,37 +24,129 @@ pub trait ResultDeduplicator: Send + Sync {
     async fn mark_processed(&self, value: &Value);
 }
 
+/// What the agent treats as a duplicate result.
+#[derive(Debug, Clone)]
+pub struct DeduplicationConfig {
+    /// Drop duplicate results; when `false`, every result is stored.
+    pub enabled: bool,
+    /// JSON pointers of the fields that identify a result, e.g. `/id`; the
+    /// whole result when empty. A missing field counts as `null`.
+    pub fields: Vec<String>,
+    /// How long a stored result suppresses its duplicates; forever when
+    /// `None`.
+    pub window: Option<Duration>,
+}
+
+impl Default for DeduplicationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            fields: Vec::new(),
+            window: None,
+        }
+    }
+}
+
+impl DeduplicationConfig {
+    /// Store every result, duplicates included.
+    pub fn disabled() -> Self {
+        Self {
+            enabled: false,
+            ..Self::default()
+        }
+    }
+
+    /// Compare results by the fields at these JSON pointers only.
+    pub fn with_fields<I, S>(mut self, fields: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.fields = fields.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Only drop duplicates of results stored within `window`.
+    pub fn with_window(mut self, window: Duration) -> Self {
+        self.window = Some(window);
+        self
+    }
+}
+
 pub struct HashResultDeduplicator {
-    seen: Mutex<HashSet<u64>>,
+    config: DeduplicationConfig,
+    seen: Mutex<HashMap<u64, Instant>>,
 }
 
 impl HashResultDeduplicator {
     pub fn new() -> Self {
+        Self::with_config(DeduplicationConfig::default())
+    }
+
+    pub fn with_config(config: DeduplicationConfig) -> Self {
         Self {
-            seen: Mutex::new(HashSet::new()),
+            config,
+            seen: Mutex::new(HashMap::new()),
         }
     }
 
-    fn hash_value(value: &Value) -> u64 {
+    /// Hash of `value` as a result of the tenant in scope, so tenants
+    /// storing identical results do not suppress each other's
+    fn hash_value(&self, value: &Value) -> u64 {
         let mut hasher = DefaultHasher::new();
-        let serialized = serde_json::to_string(value).unwrap_or_default();
-        serialized.hash(&mut hasher);
+        tenant::current_tenant_id().hash(&mut hasher);
+        if self.config.fields.is_empty() {
+            serde_json::to_string(value)
+                .unwrap_or_default()
+                .hash(&mut hasher);
+        } else {
+            for field in &self.config.fields {
+                let selected = value.pointer(field).unwrap_or(&Value::Null);
+                field.hash(&mut hasher);
+                serde_json::to_string(selected)
+                    .unwrap_or_default()
+                    .hash(&mut hasher);
+            }
+        }
         hasher.finish()
     }
+
+    fn is_fresh(&self, stored_at: Instant) -> bool {
+        self.config
+            .window
+            .is_none_or(|window| stored_at.elapsed() < window)
+    }
+}
+
+impl Default for HashResultDeduplicator {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 #[async_trait]
 impl ResultDeduplicator for HashResultDeduplicator {
     async fn should_process(&self, value: &Value) -> bool {
-        let hash = Self::hash_value(value);
+        if !self.config.enabled {
+            return true;
+        }
+        let hash = self.hash_value(value);
         let seen = self.seen.lock().await;
-        !seen.contains(&hash)
+        !seen
+            .get(&hash)
+            .is_some_and(|stored_at| self.is_fresh(*stored_at))
     }
 
     async fn mark_processed(&self, value: &Value) {
-        let hash = Self::hash_value(value);
+        if !self.config.enabled {
+            return;
+        }
+        let hash = self.hash_value(value);
         let mut seen = self.seen.lock().await;
-        seen.insert(hash);
+        if self.config.window.is_some() {
+            seen.retain(|_, stored_at| self.is_fresh(*stored_at));
+        }
+        seen.insert(hash, Instant::now());
     }
 }
 
@@ -75,3 +177,45 @@ impl ResultStoragePipeline for DeduplicatingPipeline {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn policy_controls_what_counts_as_a_duplicate() {
+        let chunk = json!({ "id": "a", "text": "tick" });
+
+        let whole = HashResultDeduplicator::new();
+        whole.mark_processed(&chunk).await;
+        assert!(!whole.should_process(&chunk).await);
+        assert!(
+            whole
+                .should_process(&json!({ "id": "a", "text": "tock" }))
+                .await
+        );
+
+        let by_id = HashResultDeduplicator::with_config(
+            DeduplicationConfig::default().with_fields(["/id"]),
+        );
+        by_id.mark_processed(&chunk).await;
+        assert!(
+            !by_id
+                .should_process(&json!({ "id": "a", "text": "tock" }))
+                .await
+        );
+
+        let disabled = HashResultDeduplicator::with_config(DeduplicationConfig::disabled());
+        disabled.mark_processed(&chunk).await;
+        assert!(disabled.should_process(&chunk).await);
+
+        let windowed = HashResultDeduplicator::with_config(
+            DeduplicationConfig::default().with_window(Duration::from_millis(20)),
+        );
+        windowed.mark_processed(&chunk).await;
+        assert!(!windowed.should_process(&chunk).await);
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        assert!(windowed.should_process(&chunk).await);
+    }
+}
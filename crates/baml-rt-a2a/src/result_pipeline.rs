@@ -1,9 +1,12 @@
-use crate::a2a_store::TaskStoreBackend;
+use crate::a2a_store::{TaskState, TaskStoreBackend};
+use crate::a2a_types::{Message, MessageRole, Part, ROLE_AGENT, TaskStatus, TaskStatusUpdateEvent};
 use crate::events::EventEmitter;
 use crate::result_extractor::{A2aResultExtractor, ResultExtractor};
 use crate::result_processor::TaskProcessor;
 use baml_rt_core::Result;
-use serde_json::Value;
+use baml_rt_core::ids::{ContextId, MessageId, TaskId};
+use serde_json::{Value, json};
+use std::collections::HashMap;
 use std::sync::Arc;
 
 #[async_trait::async_trait]
@@ -51,3 +54,97 @@ impl ResultStoragePipeline for A2aResultPipeline {
         Ok(())
     }
 }
+
+/// Stores the results of a handler working on one known task.
+///
+/// Used where the runtime, not the handler, owns the task: background tasks
+/// and tasks resumed after `input-required`.
+pub struct TaskResultRecorder {
+    pipeline: Arc<dyn ResultStoragePipeline>,
+    task_id: TaskId,
+    context_id: ContextId,
+}
+
+impl TaskResultRecorder {
+    pub fn new(
+        pipeline: Arc<dyn ResultStoragePipeline>,
+        task_id: TaskId,
+        context_id: ContextId,
+    ) -> Self {
+        Self {
+            pipeline,
+            task_id,
+            context_id,
+        }
+    }
+
+    pub async fn record_state(&self, state: TaskState, message: Option<Message>) -> Result<()> {
+        let update = TaskStatusUpdateEvent {
+            task_id: Some(self.task_id.clone()),
+            context_id: Some(self.context_id.clone()),
+            status: Some(TaskStatus {
+                state: Some(state.into()),
+                message,
+                ..TaskStatus::default()
+            }),
+            ..TaskStatusUpdateEvent::default()
+        };
+        self.pipeline
+            .store_result(&json!({ "statusUpdate": update }))
+            .await
+    }
+
+    /// Fail the task, telling the client why
+    pub async fn record_failure(&self, error: String) -> Result<()> {
+        let message = self.agent_message(Part {
+            text: Some(error),
+            ..Part::default()
+        });
+        self.record_state(TaskState::Failed, Some(message)).await
+    }
+
+    /// File the handler's answer under the task
+    ///
+    /// A returned task takes the task's id and context. A message, or any
+    /// other value, completes the task with that answer, and an `error`
+    /// object fails it. `result` is updated to name the task.
+    pub async fn record_result(&self, result: &mut Value) -> Result<()> {
+        if let Some(task) = result.get_mut("task").and_then(Value::as_object_mut) {
+            task.insert("id".to_string(), json!(self.task_id));
+            task.insert("contextId".to_string(), json!(self.context_id));
+            return self.pipeline.store_result(result).await;
+        }
+        if result.get("message").is_none()
+            && let Some(error) = result.get("error")
+        {
+            let error = error
+                .as_str()
+                .map_or_else(|| error.to_string(), str::to_string);
+            return self.record_failure(error).await;
+        }
+        let message = result
+            .get("message")
+            .and_then(|message| serde_json::from_value::<Message>(message.clone()).ok());
+        let message = message.unwrap_or_else(|| {
+            self.agent_message(Part {
+                data: Some(result.clone()),
+                ..Part::default()
+            })
+        });
+        self.record_state(TaskState::Completed, Some(message)).await
+    }
+
+    fn agent_message(&self, part: Part) -> Message {
+        Message {
+            message_id: MessageId::from(format!("{}-result", self.task_id)),
+            role: MessageRole::String(ROLE_AGENT.to_string()),
+            parts: vec![part],
+            context_id: Some(self.context_id.clone()),
+            task_id: Some(self.task_id.clone()),
+            reference_task_ids: Vec::new(),
+            extensions: Vec::new(),
+            metadata: None,
+            extra: HashMap::new(),
+        }
+    }
+}
@@ -5,6 +5,7 @@ use crate::a2a_types::{
 };
 use crate::events::EventEmitter;
 use baml_rt_core::Result;
+use baml_rt_core::context;
 use std::sync::Arc;
 
 pub struct TaskProcessor {
@@ -51,12 +52,12 @@ impl TaskProcessor {
             let context_id = task.context_id.clone();
             let task_id = task.id.clone();
             let artifacts = task.artifacts.clone();
-            self.task_store.upsert(task).await;
+            self.task_store.upsert(task).await?;
             if let Some(status) = status {
                 if let Some(event) = self
                     .task_store
                     .record_status_update(task_id.clone(), context_id.clone(), status)
-                    .await
+                    .await?
                 {
                     self.emitter.emit(event).await;
                 }
@@ -79,7 +80,11 @@ impl TaskProcessor {
                 }
             }
         }
-        if let Some(message) = message {
+        if let Some(mut message) = message {
+            // Replies usually leave out the context; file them under the request's
+            if message.context_id.is_none() {
+                message.context_id = context::current_context_id();
+            }
             self.task_store.insert_message(&message).await;
         }
         if let Some(update) = status_update {
@@ -87,7 +92,7 @@ impl TaskProcessor {
                 if let Some(event) = self
                     .task_store
                     .record_status_update(update.task_id.clone(), update.context_id.clone(), status)
-                    .await
+                    .await?
                 {
                     self.emitter.emit(event).await;
                 }
@@ -1,11 +1,69 @@
-use crate::a2a_types::{Message, StreamResponse, Task};
+//! Normalization of the chunks JS handlers stream.
+//!
+//! A `message.stream` handler returns an array of chunks, which a
+//! [`StreamNormalizer`] turns into A2A stream responses. Agents declare the
+//! shape they emit with the manifest's `stream_format`, picking one of the
+//! built-in normalizers with [`normalizer_for`]; embedders with other shapes
+//! register their own with
+//! [`A2aAgentBuilder::with_stream_normalizer`](crate::A2aAgentBuilder::with_stream_normalizer).
+
+use crate::a2a_types::{Message, MessageRole, Part, ROLE_AGENT, StreamResponse, Task};
+use baml_rt_core::ids::MessageId;
+use baml_rt_core::manifest::StreamFormat;
 use baml_rt_core::{BamlRtError, Result};
 use serde_json::Value;
 use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
 
 pub trait StreamNormalizer: Send + Sync {
     fn normalize_chunk(&self, value: Value) -> Result<Value>;
     fn is_stream_response(&self, value: &Value) -> bool;
+
+    /// Normalize a whole stream; normalizers may drop chunks that carry
+    /// nothing for the client
+    fn normalize_stream(&self, values: Vec<Value>) -> Result<Vec<Value>> {
+        values
+            .into_iter()
+            .map(|value| self.normalize_chunk(value))
+            .collect()
+    }
+}
+
+/// The built-in normalizer for chunks of `format`
+pub fn normalizer_for(format: StreamFormat) -> Arc<dyn StreamNormalizer> {
+    match format {
+        StreamFormat::A2a => Arc::new(A2aStreamNormalizer),
+        StreamFormat::OpenAi => Arc::new(OpenAiDeltaNormalizer),
+        StreamFormat::Text => Arc::new(TextStreamNormalizer),
+    }
+}
+
+static MESSAGE_COUNTER: AtomicU64 = AtomicU64::new(1);
+
+/// A stream response holding an agent message with one text part
+fn text_response(text: &str) -> Result<Value> {
+    let millis = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0);
+    let counter = MESSAGE_COUNTER.fetch_add(1, Ordering::Relaxed);
+    let message = Message {
+        message_id: MessageId::new(format!("msg-{}-{}", millis, counter)),
+        role: MessageRole::String(ROLE_AGENT.to_string()),
+        parts: vec![Part {
+            text: Some(text.to_string()),
+            ..Part::default()
+        }],
+        context_id: None,
+        task_id: None,
+        reference_task_ids: Vec::new(),
+        extensions: Vec::new(),
+        metadata: None,
+        extra: HashMap::new(),
+    };
+    A2aStreamNormalizer.normalize_chunk(serde_json::to_value(message).map_err(BamlRtError::Json)?)
 }
 
 pub struct A2aStreamNormalizer;
@@ -48,3 +106,106 @@ impl StreamNormalizer for A2aStreamNormalizer {
             || map.contains_key("artifactUpdate")
     }
 }
+
+/// Normalizes OpenAI chat completion chunks
+///
+/// A chunk's `choices[0].delta.content` (or `message.content`, for a
+/// complete response) becomes an agent message; chunks without content, such
+/// as the role and finish deltas, are dropped from streams. Chunks of other
+/// shapes are normalized as A2A.
+pub struct OpenAiDeltaNormalizer;
+
+impl OpenAiDeltaNormalizer {
+    /// `Some` content for an OpenAI chunk, `None` for any other value
+    fn content(value: &Value) -> Option<Option<&str>> {
+        let choice = value.get("choices")?.as_array()?.first();
+        let Some(choice) = choice else {
+            return Some(None);
+        };
+        let content = ["delta", "message"]
+            .iter()
+            .find_map(|key| choice.get(key)?.get("content")?.as_str());
+        Some(content.filter(|text| !text.is_empty()))
+    }
+}
+
+impl StreamNormalizer for OpenAiDeltaNormalizer {
+    fn normalize_chunk(&self, value: Value) -> Result<Value> {
+        match Self::content(&value) {
+            Some(content) => text_response(content.unwrap_or_default()),
+            None => A2aStreamNormalizer.normalize_chunk(value),
+        }
+    }
+
+    fn is_stream_response(&self, value: &Value) -> bool {
+        A2aStreamNormalizer.is_stream_response(value)
+    }
+
+    fn normalize_stream(&self, values: Vec<Value>) -> Result<Vec<Value>> {
+        values
+            .into_iter()
+            .filter(|value| !matches!(Self::content(value), Some(None)))
+            .map(|value| self.normalize_chunk(value))
+            .collect()
+    }
+}
+
+/// Normalizes raw strings, each becoming an agent message; other values are
+/// normalized as A2A
+pub struct TextStreamNormalizer;
+
+impl StreamNormalizer for TextStreamNormalizer {
+    fn normalize_chunk(&self, value: Value) -> Result<Value> {
+        match value {
+            Value::String(text) => text_response(&text),
+            other => A2aStreamNormalizer.normalize_chunk(other),
+        }
+    }
+
+    fn is_stream_response(&self, value: &Value) -> bool {
+        A2aStreamNormalizer.is_stream_response(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn openai_deltas_and_raw_strings_become_agent_messages() {
+        let deltas = vec![
+            json!({ "choices": [{ "index": 0, "delta": { "role": "assistant" } }] }),
+            json!({ "choices": [{ "index": 0, "delta": { "content": "Hel" } }] }),
+            json!({ "choices": [{ "index": 0, "delta": { "content": "lo" } }] }),
+            json!({ "choices": [{ "index": 0, "delta": {}, "finish_reason": "stop" }] }),
+        ];
+        let responses = normalizer_for(StreamFormat::OpenAi)
+            .normalize_stream(deltas)
+            .unwrap();
+        let texts: Vec<_> = responses
+            .iter()
+            .map(|response| {
+                assert_eq!(response["message"]["role"], ROLE_AGENT);
+                response["message"]["parts"][0]["text"].clone()
+            })
+            .collect();
+        assert_eq!(texts, [json!("Hel"), json!("lo")]);
+        assert_ne!(
+            responses[0]["message"]["messageId"],
+            responses[1]["message"]["messageId"]
+        );
+
+        let text = normalizer_for(StreamFormat::Text);
+        let responses = text
+            .normalize_stream(vec![json!("tick"), json!("tick")])
+            .unwrap();
+        assert_eq!(responses.len(), 2);
+        assert_eq!(responses[1]["message"]["parts"][0]["text"], "tick");
+        let task_update = json!({ "statusUpdate": { "taskId": "t1" } });
+        assert_eq!(
+            text.normalize_chunk(task_update.clone()).unwrap(),
+            task_update
+        );
+    }
+}
@@ -0,0 +1,105 @@
+//! Cancellation of in-flight task work.
+//!
+//! Work run for a task, such as a background `message.send` or a resumed
+//! task, is registered under the task's id while it runs. `tasks.cancel`
+//! cancels the registered token after marking the task canceled, which trips
+//! the BAML calls, tool executions, and JS invocation beneath it through
+//! [`baml_rt_core::cancellation`], so the LLM request is aborted instead of
+//! running to completion.
+//!
+//! Tokens are registered under the tenant in scope as well, so a tenant can
+//! only stop its own work.
+
+use baml_rt_core::ids::{TaskId, TenantId};
+use baml_rt_core::{Result, cancellation, tenant};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+use tokio_util::sync::CancellationToken;
+
+/// A task of a tenant, or of no tenant
+type TaskKey = (Option<TenantId>, TaskId);
+
+/// Cancellation tokens of the tasks with work in flight
+#[derive(Debug, Default)]
+pub struct TaskCancellations {
+    /// Token of each running task, with the run that registered it
+    tokens: Mutex<HashMap<TaskKey, (u64, CancellationToken)>>,
+    runs: AtomicU64,
+}
+
+impl TaskCancellations {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Run `fut` as the work of `task_id`, failing with
+    /// [`BamlRtError::Canceled`](baml_rt_core::BamlRtError::Canceled) if the
+    /// task is canceled first
+    pub async fn run<F, T>(&self, task_id: &TaskId, fut: F) -> Result<T>
+    where
+        F: std::future::Future<Output = Result<T>>,
+    {
+        let run = self.runs.fetch_add(1, Ordering::Relaxed);
+        let token = CancellationToken::new();
+        let key = (tenant::current_tenant_id(), task_id.clone());
+        self.lock().insert(key.clone(), (run, token.clone()));
+        let outcome = cancellation::with_cancellation(token, "task", fut).await;
+        let mut tokens = self.lock();
+        // A later run of the same task may have registered its own token
+        if tokens
+            .get(&key)
+            .is_some_and(|(registered, _)| *registered == run)
+        {
+            tokens.remove(&key);
+        }
+        outcome
+    }
+
+    /// Cancel the work in flight for `task_id`, returning whether there was any
+    pub fn cancel(&self, task_id: &str) -> bool {
+        let key = (tenant::current_tenant_id(), TaskId::from(task_id));
+        match self.lock().remove(&key) {
+            Some((_, token)) => {
+                token.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<TaskKey, (u64, CancellationToken)>> {
+        self.tokens
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use baml_rt_core::BamlRtError;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn canceling_a_task_stops_its_work() {
+        let cancellations = TaskCancellations::new();
+        let task_id = TaskId::from("task-1");
+        let work = cancellations.run(&task_id, async {
+            cancellation::enforce("tool 'slow'", async {
+                tokio::time::sleep(Duration::from_secs(5)).await;
+                Ok(())
+            })
+            .await
+        });
+        let cancel = async {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            cancellations.cancel("task-1")
+        };
+
+        let (outcome, canceled) = tokio::join!(work, cancel);
+        assert!(canceled);
+        assert!(matches!(outcome, Err(BamlRtError::Canceled { .. })));
+        assert!(!cancellations.cancel("task-1"));
+    }
+}
@@ -0,0 +1,150 @@
+//! Task operations for agent JavaScript, over the agent's task store.
+//!
+//! [`StoreTaskOperations`] backs `taskCreate`, `taskUpdateStatus`, and
+//! `taskAddArtifact`. Changes go through the same [`TaskProcessor`] as
+//! handler results, so state transitions are checked and subscribers of the
+//! task see each status and artifact update as it happens.
+
+use crate::a2a_store::{TaskState, TaskStoreBackend, generate_task_id};
+use crate::a2a_types::{
+    self, Artifact, Message, MessageRole, Part, ROLE_AGENT, StreamResponse, Task,
+    TaskArtifactUpdateEvent, TaskStatus, TaskStatusUpdateEvent,
+};
+use crate::events::EventEmitter;
+use crate::result_processor::TaskProcessor;
+use async_trait::async_trait;
+use baml_rt_core::context;
+use baml_rt_core::ids::{ArtifactId, MessageId, TaskId};
+use baml_rt_core::{BamlRtError, Result};
+use baml_rt_quickjs::TaskOperations;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// [`TaskOperations`] over a task store
+pub struct StoreTaskOperations {
+    task_store: Arc<dyn TaskStoreBackend>,
+    processor: TaskProcessor,
+}
+
+impl StoreTaskOperations {
+    pub fn new(task_store: Arc<dyn TaskStoreBackend>, emitter: Arc<dyn EventEmitter>) -> Self {
+        Self {
+            processor: TaskProcessor::new(task_store.clone(), emitter),
+            task_store,
+        }
+    }
+
+    /// The stored task `task_id`, failing if there is none
+    async fn task(&self, task_id: &str) -> Result<Task> {
+        self.task_store
+            .get(task_id, None)
+            .await
+            .ok_or_else(|| BamlRtError::InvalidArgument(format!("Unknown task: {}", task_id)))
+    }
+
+    async fn task_value(&self, task_id: &str) -> Result<Value> {
+        Ok(serde_json::to_value(self.task(task_id).await?)?)
+    }
+}
+
+#[async_trait]
+impl TaskOperations for StoreTaskOperations {
+    async fn create(&self, task: Value) -> Result<Value> {
+        let mut task: Task = serde_json::from_value(task)
+            .map_err(|e| BamlRtError::InvalidArgument(format!("Invalid task: {}", e)))?;
+        let task_id = task.id.get_or_insert_with(generate_task_id).clone();
+        task.context_id.get_or_insert_with(context::current_or_new);
+        task.status.get_or_insert_with(|| TaskStatus {
+            state: Some(TaskState::Submitted.into()),
+            ..TaskStatus::default()
+        });
+        self.processor.process_task(task).await?;
+        self.task_value(task_id.as_str()).await
+    }
+
+    async fn update_status(
+        &self,
+        task_id: &str,
+        state: &str,
+        message: Option<Value>,
+    ) -> Result<Value> {
+        let task = self.task(task_id).await?;
+        let state = TaskState::from_wire(&a2a_types::TaskState::String(state.to_string()))?;
+        let message = message
+            .map(|message| status_message(&task, state, message))
+            .transpose()?;
+        let update = TaskStatusUpdateEvent {
+            context_id: task.context_id.clone(),
+            task_id: task.id.clone(),
+            status: Some(TaskStatus {
+                state: Some(state.into()),
+                message,
+                ..TaskStatus::default()
+            }),
+            ..TaskStatusUpdateEvent::default()
+        };
+        self.processor
+            .process_stream_response(StreamResponse {
+                status_update: Some(update),
+                ..StreamResponse::default()
+            })
+            .await?;
+        self.task_value(task_id).await
+    }
+
+    async fn add_artifact(&self, task_id: &str, artifact: Value) -> Result<Value> {
+        let mut task = self.task(task_id).await?;
+        let mut artifact: Artifact = serde_json::from_value(artifact)
+            .map_err(|e| BamlRtError::InvalidArgument(format!("Invalid artifact: {}", e)))?;
+        let number = task.artifacts.len() + 1;
+        artifact
+            .artifact_id
+            .get_or_insert_with(|| ArtifactId::from(format!("{}-artifact-{}", task_id, number)));
+        // Update events are not kept on the task, so it gets the artifact itself
+        task.artifacts.push(artifact.clone());
+        let update = TaskArtifactUpdateEvent {
+            context_id: task.context_id.clone(),
+            task_id: task.id.clone(),
+            append: Some(false),
+            last_chunk: Some(true),
+            artifact: Some(artifact),
+            ..TaskArtifactUpdateEvent::default()
+        };
+        self.task_store.upsert(task).await?;
+        self.processor
+            .process_stream_response(StreamResponse {
+                artifact_update: Some(update),
+                ..StreamResponse::default()
+            })
+            .await?;
+        self.task_value(task_id).await
+    }
+}
+
+/// A status message given as a message, or as the text of an agent message
+fn status_message(task: &Task, state: TaskState, message: Value) -> Result<Message> {
+    let text = match message {
+        Value::String(text) => text,
+        message => {
+            return serde_json::from_value(message).map_err(|e| {
+                BamlRtError::InvalidArgument(format!("Invalid status message: {}", e))
+            });
+        }
+    };
+    let task_id = task.id.as_ref().map_or("", TaskId::as_str);
+    Ok(Message {
+        message_id: MessageId::from(format!("{}-{}", task_id, state.as_str().to_lowercase())),
+        role: MessageRole::String(ROLE_AGENT.to_string()),
+        parts: vec![Part {
+            text: Some(text),
+            ..Part::default()
+        }],
+        context_id: task.context_id.clone(),
+        task_id: task.id.clone(),
+        reference_task_ids: Vec::new(),
+        extensions: Vec::new(),
+        metadata: None,
+        extra: HashMap::new(),
+    })
+}
@@ -0,0 +1,186 @@
+//! Tenants an agent serves.
+//!
+//! Requests name their tenant in the `tenant` param. When the agent
+//! authenticates its callers, that tenant must be one the caller's
+//! [`Principal`] holds, and requests naming none are made for the
+//! principal's own tenant. The agent looks it up
+//! in its [`TenantRegistry`], admits the request against the tenant's rate
+//! limit, and serves it in the tenant's scope
+//! ([`baml_rt_core::tenant::with_tenant`]): tasks, provenance, and metrics
+//! are kept per tenant, and BAML calls use the tenant's credentials.
+//!
+//! [`Principal`]: baml_rt_core::auth::Principal
+
+use baml_rt_core::ids::TenantId;
+use baml_rt_core::tenant::{Tenant, TenantConfig};
+use baml_rt_core::{BamlRtError, Result};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+
+/// Request param naming the tenant a request is made for
+pub(crate) const TENANT_PARAM: &str = "tenant";
+
+/// Span over which `requests_per_minute` is counted
+const RATE_WINDOW: Duration = Duration::from_secs(60);
+
+/// Requests a tenant has made in its current rate window
+#[derive(Debug, Clone, Copy)]
+struct RateWindow {
+    started: Instant,
+    requests: u32,
+}
+
+/// Settings of the tenants an agent serves
+///
+/// Tenants that are not registered get the default config, unless the
+/// registry only accepts registered tenants. Configs may be replaced while
+/// the agent runs, e.g. to rotate credentials.
+#[derive(Debug, Default)]
+pub struct TenantRegistry {
+    tenants: RwLock<HashMap<TenantId, Arc<TenantConfig>>>,
+    default_config: Arc<TenantConfig>,
+    registered_only: bool,
+    windows: Mutex<HashMap<TenantId, RateWindow>>,
+}
+
+impl TenantRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Serve tenant `id` with `config`
+    pub fn with_tenant(self, id: impl Into<TenantId>, config: TenantConfig) -> Self {
+        self.set_tenant(id, config);
+        self
+    }
+
+    /// Serve tenants that are not registered with `config`
+    pub fn with_default_config(mut self, config: TenantConfig) -> Self {
+        self.default_config = Arc::new(config);
+        self
+    }
+
+    /// Reject requests that name no tenant or one that is not registered
+    pub fn with_registered_tenants_only(mut self) -> Self {
+        self.registered_only = true;
+        self
+    }
+
+    /// Register tenant `id`, replacing its config if it has one
+    pub fn set_tenant(&self, id: impl Into<TenantId>, config: TenantConfig) {
+        self.write_tenants().insert(id.into(), Arc::new(config));
+    }
+
+    /// Stop serving tenant `id`, returning whether it was registered
+    pub fn remove_tenant(&self, id: &TenantId) -> bool {
+        self.write_tenants().remove(id).is_some()
+    }
+
+    /// The tenant a request naming `id` is served for
+    pub fn resolve(&self, id: Option<TenantId>) -> Result<Option<Tenant>> {
+        let Some(id) = id else {
+            if self.registered_only {
+                return Err(BamlRtError::InvalidArgument(
+                    "Requests must name a tenant".to_string(),
+                ));
+            }
+            return Ok(None);
+        };
+        let registered = self
+            .tenants
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .get(&id)
+            .cloned();
+        let config = match registered {
+            Some(config) => config,
+            None if self.registered_only => {
+                return Err(BamlRtError::InvalidArgument(format!(
+                    "Unknown tenant '{}'",
+                    id
+                )));
+            }
+            None => self.default_config.clone(),
+        };
+        Ok(Some(Tenant::new(id, config)))
+    }
+
+    /// Count a request from `tenant`, failing with
+    /// [`BamlRtError::RateLimited`] once it is over its limit
+    pub fn admit(&self, tenant: &Tenant) -> Result<()> {
+        self.admit_at(tenant, Instant::now())
+    }
+
+    fn admit_at(&self, tenant: &Tenant, now: Instant) -> Result<()> {
+        let Some(limit) = tenant.config().requests_per_minute() else {
+            return Ok(());
+        };
+        let mut windows = self
+            .windows
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let window = windows.entry(tenant.id().clone()).or_insert(RateWindow {
+            started: now,
+            requests: 0,
+        });
+        if now.duration_since(window.started) >= RATE_WINDOW {
+            *window = RateWindow {
+                started: now,
+                requests: 0,
+            };
+        }
+        if window.requests >= limit {
+            return Err(BamlRtError::RateLimited {
+                tenant: tenant.id().to_string(),
+            });
+        }
+        window.requests += 1;
+        Ok(())
+    }
+
+    fn write_tenants(
+        &self,
+    ) -> std::sync::RwLockWriteGuard<'_, HashMap<TenantId, Arc<TenantConfig>>> {
+        self.tenants
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tenants_are_limited_per_window() {
+        let registry = TenantRegistry::new()
+            .with_tenant("acme", TenantConfig::new().with_requests_per_minute(2));
+        let acme = registry.resolve(Some("acme".into())).unwrap().unwrap();
+        let other = registry.resolve(Some("globex".into())).unwrap().unwrap();
+        let start = Instant::now();
+
+        assert!(registry.admit_at(&acme, start).is_ok());
+        assert!(registry.admit_at(&acme, start).is_ok());
+        assert!(matches!(
+            registry.admit_at(&acme, start + Duration::from_secs(59)),
+            Err(BamlRtError::RateLimited { tenant }) if tenant == "acme"
+        ));
+        // Unregistered tenants get the default config, which has no limit
+        assert!(registry.admit_at(&other, start).is_ok());
+        assert!(registry.admit_at(&acme, start + RATE_WINDOW).is_ok());
+    }
+
+    #[test]
+    fn only_registered_tenants_are_served_when_required() {
+        let registry = TenantRegistry::new()
+            .with_tenant("acme", TenantConfig::new())
+            .with_registered_tenants_only();
+        assert!(registry.resolve(Some("acme".into())).is_ok());
+        assert!(registry.resolve(Some("globex".into())).is_err());
+        assert!(registry.resolve(None).is_err());
+
+        assert!(registry.remove_tenant(&"acme".into()));
+        assert!(registry.resolve(Some("acme".into())).is_err());
+    }
+}
@@ -0,0 +1,108 @@
+//! Per-request verbosity overrides for A2A requests.
+//!
+//! A request can set `metadata.log_level` to `"debug"` or `"trace"` to raise
+//! logging for itself only. Because the extra output includes prompts, stream
+//! chunks, and JS console output, overrides are only honoured for requests
+//! from an authenticated caller that a [`VerbosityAuthorizer`] approves.
+
+use crate::a2a::A2aRequest;
+use baml_rt_core::verbosity::Verbosity;
+use serde_json::Value;
+use std::collections::HashSet;
+
+/// Metadata key carrying the requested verbosity.
+pub const LOG_LEVEL_METADATA_KEY: &str = "log_level";
+
+/// Metadata key carrying the authenticated principal, stamped by the transport,
+/// which removes any value the caller set.
+pub const PRINCIPAL_METADATA_KEY: &str = "principal";
+
+/// Decides whether a request may raise its own log verbosity.
+pub trait VerbosityAuthorizer: Send + Sync {
+    fn authorize(&self, request: &A2aRequest) -> bool;
+}
+
+/// Rejects every verbosity override. This is the default.
+pub struct DenyVerbosityOverrides;
+
+impl VerbosityAuthorizer for DenyVerbosityOverrides {
+    fn authorize(&self, _request: &A2aRequest) -> bool {
+        false
+    }
+}
+
+/// Allows overrides for requests whose `metadata.principal` is in the list.
+///
+/// The principal must be set by a transport that has authenticated the
+/// caller; it is not a credential on its own.
+pub struct PrincipalAllowList {
+    principals: HashSet<String>,
+}
+
+impl PrincipalAllowList {
+    pub fn new<I, S>(principals: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            principals: principals.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl VerbosityAuthorizer for PrincipalAllowList {
+    fn authorize(&self, request: &A2aRequest) -> bool {
+        metadata_str(request, PRINCIPAL_METADATA_KEY)
+            .is_some_and(|principal| self.principals.contains(principal))
+    }
+}
+
+/// Verbosity requested through request metadata, if any.
+pub fn requested_verbosity(request: &A2aRequest) -> Option<Verbosity> {
+    metadata_str(request, LOG_LEVEL_METADATA_KEY).and_then(Verbosity::parse)
+}
+
+fn metadata_str<'a>(request: &'a A2aRequest, key: &str) -> Option<&'a str> {
+    request
+        .params
+        .get("metadata")
+        .and_then(|metadata| metadata.get(key))
+        .and_then(Value::as_str)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::a2a::A2aMethod;
+    use serde_json::json;
+
+    fn request_with_metadata(metadata: Value) -> A2aRequest {
+        A2aRequest {
+            id: None,
+            method: A2aMethod::TasksList,
+            params: json!({ "metadata": metadata }),
+            is_stream: false,
+            context_id: None,
+            is_notification: false,
+        }
+    }
+
+    #[test]
+    fn allow_list_checks_principal() {
+        let authorizer = PrincipalAllowList::new(["ops@example.com"]);
+        let allowed = request_with_metadata(json!({
+            "log_level": "trace",
+            "principal": "ops@example.com"
+        }));
+        let denied = request_with_metadata(json!({
+            "log_level": "trace",
+            "principal": "guest"
+        }));
+
+        assert!(authorizer.authorize(&allowed));
+        assert!(!authorizer.authorize(&denied));
+        assert!(!DenyVerbosityOverrides.authorize(&allowed));
+        assert_eq!(requested_verbosity(&allowed), Some(Verbosity::Trace));
+    }
+}
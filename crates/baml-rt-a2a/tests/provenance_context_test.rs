@@ -134,7 +134,7 @@ async fn test_context_id_is_task_local_under_concurrency() {
         };
     "#;
 
-    let mut runtime = BamlRuntimeManager::new().expect("runtime");
+    let runtime = BamlRuntimeManager::new().expect("runtime");
     runtime
         .register_tool(EchoTool)
         .await
@@ -5,11 +5,14 @@ use baml_rt::a2a_types::{
 use baml_rt::baml::BamlRuntimeManager;
 use baml_rt::tools::BamlTool;
 use baml_rt::{A2aAgent, A2aRequestHandler};
+use baml_rt_a2a::BackgroundConfig;
+use baml_rt_a2a::a2a_store::TaskUpdateQueue;
 use serde_json::{Value, json};
 use std::collections::HashMap;
 use std::fs;
+use std::time::Duration;
 use test_support::common;
-use test_support::common::CalculatorTool;
+use test_support::common::{CalculatorTool, use_mock_llm};
 
 fn fixture_agent_dir() -> std::path::PathBuf {
     common::agent_fixture("voidship-rites")
@@ -47,6 +50,7 @@ async fn setup_agent() -> A2aAgent {
     let mut manager = BamlRuntimeManager::new().unwrap();
     let agent_dir = fixture_agent_dir();
     manager.load_schema(agent_dir.to_str().unwrap()).unwrap();
+    use_mock_llm(&mut manager);
     manager.map_baml_variant_to_tool("CalculatorTool", "calculate");
     A2aAgent::builder()
         .with_runtime_manager(manager)
@@ -240,7 +244,7 @@ async fn test_message_send_tool_calling() {
     let agent = setup_agent().await;
     {
         let runtime = agent.runtime();
-        let mut manager = runtime.lock().await;
+        let manager = runtime.read().await;
         manager.register_tool(AddNumbersTool).await.unwrap();
     }
 
@@ -283,7 +287,7 @@ async fn test_message_send_baml_tool_calling() {
     let agent = setup_agent().await;
     {
         let runtime = agent.runtime();
-        let mut manager = runtime.lock().await;
+        let manager = runtime.read().await;
         manager.register_tool(CalculatorTool).await.unwrap();
         manager.map_baml_variant_to_tool("RiteCalcTool", "calculate");
     }
@@ -321,3 +325,448 @@ async fn test_message_send_baml_tool_calling() {
         text
     );
 }
+
+/// Never finishes on its own
+struct StallTool;
+
+#[async_trait]
+impl BamlTool for StallTool {
+    const NAME: &'static str = "stall";
+
+    fn description(&self) -> &'static str {
+        "Waits forever"
+    }
+
+    fn input_schema(&self) -> serde_json::Value {
+        json!({ "type": "object", "properties": {} })
+    }
+
+    async fn execute(&self, _args: serde_json::Value) -> baml_rt::Result<serde_json::Value> {
+        std::future::pending().await
+    }
+}
+
+async fn task_state(agent: &A2aAgent, task_id: &str) -> Option<String> {
+    let request = json!({
+        "jsonrpc": "2.0",
+        "method": "tasks.get",
+        "params": { "id": task_id },
+        "id": "get"
+    });
+    let responses = agent.handle_a2a(request).await.unwrap();
+    responses[0]
+        .pointer("/result/status/state")
+        .and_then(Value::as_str)
+        .map(str::to_string)
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_tasks_cancel_aborts_in_flight_background_work() {
+    let manager = BamlRuntimeManager::new().unwrap();
+    manager.register_tool(StallTool).await.unwrap();
+    let agent = A2aAgent::builder()
+        .with_runtime_manager(manager)
+        .with_init_js(
+            r#"
+            globalThis.handle_a2a_request = async function(request) {
+                await invokeTool("stall", {});
+                return { message: { role: "ROLE_AGENT", parts: [{ text: "done" }] } };
+            };
+            "#,
+        )
+        .with_background_tasks(BackgroundConfig::default())
+        .build()
+        .await
+        .unwrap();
+
+    let mut message = serde_json::to_value(user_message("stall-1", "stall")).unwrap();
+    message["taskId"] = json!("stalled-task");
+    let send = json!({
+        "jsonrpc": "2.0",
+        "method": "message.send",
+        "params": { "message": message, "configuration": { "blocking": false } },
+        "id": "send"
+    });
+    agent.handle_a2a(send).await.unwrap();
+    for _ in 0..100 {
+        if task_state(&agent, "stalled-task").await.as_deref() == Some("TASK_STATE_WORKING") {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    // Let the handler reach the tool
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    // The stalled handler holds the JS bridge, so the cancel only returns
+    // once it has been aborted
+    let cancel = json!({
+        "jsonrpc": "2.0",
+        "method": "tasks.cancel",
+        "params": { "id": "stalled-task" },
+        "id": "cancel"
+    });
+    let responses = tokio::time::timeout(Duration::from_secs(5), agent.handle_a2a(cancel))
+        .await
+        .expect("tasks.cancel should abort the running handler")
+        .unwrap();
+    assert!(responses[0].get("error").is_none(), "{:?}", responses);
+    assert_eq!(
+        task_state(&agent, "stalled-task").await.as_deref(),
+        Some("TASK_STATE_CANCELED")
+    );
+}
+
+#[tokio::test]
+async fn test_tenants_cannot_read_each_others_tasks() {
+    use baml_rt_a2a::TenantRegistry;
+    use baml_rt_core::tenant::TenantConfig;
+
+    let mut manager = BamlRuntimeManager::new().unwrap();
+    manager
+        .load_schema(fixture_agent_dir().to_str().unwrap())
+        .unwrap();
+    let agent = A2aAgent::builder()
+        .with_runtime_manager(manager)
+        .with_init_js(fixture_js_code())
+        .with_tenants(
+            TenantRegistry::new()
+                .with_tenant("acme", TenantConfig::new().with_requests_per_minute(2)),
+        )
+        .build()
+        .await
+        .unwrap();
+
+    let params = SendMessageRequest {
+        message: user_message("vox-9", "long-rite: tenant wards"),
+        configuration: None,
+        metadata: None,
+        tenant: Some("acme".to_string()),
+        extra: HashMap::new(),
+    };
+    let send = json!({
+        "jsonrpc": "2.0",
+        "method": "message.send",
+        "params": params,
+        "id": "send"
+    });
+    agent.handle_a2a(send).await.unwrap();
+
+    let get = |tenant: &str| {
+        json!({
+            "jsonrpc": "2.0",
+            "method": "tasks.get",
+            "params": { "id": "rite-task-vox-9", "tenant": tenant },
+            "id": "get"
+        })
+    };
+    let responses = agent.handle_a2a(get("globex")).await.unwrap();
+    assert_eq!(
+        responses[0]
+            .pointer("/error/data/details")
+            .and_then(Value::as_str),
+        Some("Task not found"),
+        "{:?}",
+        responses
+    );
+    assert_eq!(task_state(&agent, "rite-task-vox-9").await, None);
+
+    let responses = agent.handle_a2a(get("acme")).await.unwrap();
+    assert!(responses[0].get("result").is_some(), "{:?}", responses);
+
+    // The send and the first get used up acme's two requests a minute
+    let responses = agent.handle_a2a(get("acme")).await.unwrap();
+    assert_eq!(
+        responses[0]
+            .pointer("/error/message")
+            .and_then(Value::as_str),
+        Some("Rate limit exceeded")
+    );
+}
+
+#[tokio::test]
+async fn test_callers_are_authenticated_and_limited_to_their_grants() {
+    use baml_rt_a2a::{
+        AccessRules, BearerTokens, FORBIDDEN_CODE, Grants, TransportMetadata, UNAUTHENTICATED_CODE,
+    };
+    use baml_rt_core::auth::Principal;
+    use std::sync::Arc;
+
+    let mut manager = BamlRuntimeManager::new().unwrap();
+    manager
+        .load_schema(fixture_agent_dir().to_str().unwrap())
+        .unwrap();
+    let agent = A2aAgent::builder()
+        .with_runtime_manager(manager)
+        .with_init_js(fixture_js_code())
+        .with_authenticator(Arc::new(
+            BearerTokens::new()
+                .with_token("ops-token", Principal::new("ops"))
+                .with_token("reader-token", Principal::new("reader")),
+        ))
+        .with_access_policy(Arc::new(
+            AccessRules::new().with_principal("reader", Grants::all().with_methods(["tasks.get"])),
+        ))
+        .build()
+        .await
+        .unwrap();
+
+    let send = |auth: Option<&str>| {
+        let mut params = json!({ "message": user_message("vox-10", "long-rite: seals") });
+        if let Some(token) = auth {
+            params["auth"] = json!({ "token": token });
+        }
+        json!({ "jsonrpc": "2.0", "method": "message.send", "params": params, "id": "send" })
+    };
+    let error_code = |responses: Vec<Value>| responses[0].pointer("/error/code").cloned();
+
+    let responses = agent.handle_a2a(send(None)).await.unwrap();
+    assert_eq!(error_code(responses), Some(json!(UNAUTHENTICATED_CODE)));
+    let responses = agent.handle_a2a(send(Some("forged"))).await.unwrap();
+    assert_eq!(error_code(responses), Some(json!(UNAUTHENTICATED_CODE)));
+    let responses = agent.handle_a2a(send(Some("reader-token"))).await.unwrap();
+    assert_eq!(error_code(responses), Some(json!(FORBIDDEN_CODE)));
+
+    let transport = TransportMetadata::new().with_header("Authorization", "Bearer ops-token");
+    let responses = agent
+        .handle_a2a_with_transport(send(None), &transport)
+        .await
+        .unwrap();
+    assert!(responses[0].get("result").is_some(), "{:?}", responses);
+}
+
+#[tokio::test]
+async fn test_callers_cannot_claim_a_principal() {
+    use baml_rt_a2a::{A2aOutcome, MethodHandler, MethodRequest};
+    use std::sync::Arc;
+
+    struct EchoParams;
+
+    #[async_trait(?Send)]
+    impl MethodHandler for EchoParams {
+        async fn handle(
+            &self,
+            _agent: &A2aAgent,
+            request: MethodRequest,
+        ) -> baml_rt_core::Result<A2aOutcome> {
+            Ok(A2aOutcome::Response(request.params))
+        }
+    }
+
+    // Without an authenticator, nothing says who the caller is
+    let agent = A2aAgent::builder()
+        .with_init_js(fixture_js_code())
+        .with_method_handler("test.echo", Arc::new(EchoParams))
+        .build()
+        .await
+        .unwrap();
+    let responses = agent
+        .handle_a2a(json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "test.echo",
+            "params": { "metadata": { "principal": "ops", "log_level": "trace" } }
+        }))
+        .await
+        .unwrap();
+    assert_eq!(
+        responses[0].pointer("/result/metadata"),
+        Some(&json!({ "log_level": "trace" })),
+        "{:?}",
+        responses
+    );
+}
+
+#[tokio::test]
+async fn test_routed_calls_cannot_claim_a_principal() {
+    use baml_rt_a2a::{BearerTokens, RoutingTable, TransportMetadata};
+    use baml_rt_core::auth::Principal;
+    use std::sync::Arc;
+
+    let who_called = "globalThis.whoCalled = (params) => params.metadata || {};";
+    let mut routing = RoutingTable::new();
+    routing.register_agent("concierge", Vec::<String>::new());
+    let call = || {
+        routing
+            .resolve(json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "whoCalled",
+                "params": { "metadata": { "principal": "ops", "log_level": "trace" } }
+            }))
+            .unwrap()
+    };
+
+    // Without an authenticator, nothing says who the caller is
+    let agent = A2aAgent::builder()
+        .with_init_js(who_called)
+        .build()
+        .await
+        .unwrap();
+    let responses = agent
+        .handle_routed_with_transport(call(), &TransportMetadata::new())
+        .await
+        .unwrap();
+    assert_eq!(
+        responses[0].get("result"),
+        Some(&json!({ "log_level": "trace" })),
+        "{:?}",
+        responses
+    );
+
+    let agent = A2aAgent::builder()
+        .with_init_js(who_called)
+        .with_authenticator(Arc::new(
+            BearerTokens::new().with_token("support-token", Principal::new("support")),
+        ))
+        .build()
+        .await
+        .unwrap();
+    let transport = TransportMetadata::new().with_header("Authorization", "Bearer support-token");
+    let responses = agent
+        .handle_routed_with_transport(call(), &transport)
+        .await
+        .unwrap();
+    assert_eq!(
+        responses[0].pointer("/result/principal"),
+        Some(&json!("support")),
+        "{:?}",
+        responses
+    );
+}
+
+#[tokio::test]
+async fn test_callers_act_only_for_their_tenants() {
+    use baml_rt_a2a::{
+        A2aOutcome, BearerTokens, FORBIDDEN_CODE, MethodHandler, MethodRequest, TransportMetadata,
+    };
+    use baml_rt_core::auth::Principal;
+    use std::sync::Arc;
+
+    struct EchoParams;
+
+    #[async_trait(?Send)]
+    impl MethodHandler for EchoParams {
+        async fn handle(
+            &self,
+            _agent: &A2aAgent,
+            request: MethodRequest,
+        ) -> baml_rt_core::Result<A2aOutcome> {
+            Ok(A2aOutcome::Response(request.params))
+        }
+    }
+
+    let agent = A2aAgent::builder()
+        .with_init_js(fixture_js_code())
+        .with_authenticator(Arc::new(
+            BearerTokens::new()
+                .with_token("acme-token", Principal::new("support").with_tenant("acme")),
+        ))
+        .with_method_handler("test.echo", Arc::new(EchoParams))
+        .build()
+        .await
+        .unwrap();
+    let transport = TransportMetadata::new().with_header("Authorization", "Bearer acme-token");
+    let echo = |params: Value| {
+        json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "test.echo",
+            "params": params
+        })
+    };
+
+    let responses = agent
+        .handle_a2a_with_transport(echo(json!({ "tenant": "globex" })), &transport)
+        .await
+        .unwrap();
+    assert_eq!(
+        responses[0].pointer("/error/code"),
+        Some(&json!(FORBIDDEN_CODE)),
+        "{:?}",
+        responses
+    );
+
+    let responses = agent
+        .handle_a2a_with_transport(echo(json!({})), &transport)
+        .await
+        .unwrap();
+    assert_eq!(
+        responses[0].pointer("/result/tenant"),
+        Some(&json!("acme")),
+        "{:?}",
+        responses
+    );
+}
+
+#[tokio::test]
+async fn test_agent_functions_describes_the_schema_to_js_and_a2a() {
+    let agent = setup_agent().await;
+
+    let responses = agent
+        .handle_a2a(json!({ "jsonrpc": "2.0", "id": "fns", "method": "agent.functions" }))
+        .await
+        .unwrap();
+    let functions = responses[0]["result"]["functions"].clone();
+    assert_eq!(
+        functions[1],
+        json!({
+            "name": "VoidshipGreeting",
+            "params": [{ "name": "name", "type": "string" }],
+            "returnType": "string",
+            "streaming": true,
+        })
+    );
+    assert_eq!(functions[0]["name"], json!("ChooseRiteTool"));
+    assert_eq!(functions[0]["returnType"], json!("RiteToolChoice"));
+
+    let listed = agent
+        .bridge()
+        .lock()
+        .await
+        .evaluate("__baml_list_functions()")
+        .await
+        .unwrap();
+    assert_eq!(listed, functions);
+}
+
+#[tokio::test]
+async fn test_js_tasks_are_kept_in_the_agent_task_store() {
+    let agent = setup_agent().await;
+
+    let task = agent
+        .bridge()
+        .lock()
+        .await
+        .evaluate(
+            r#"(async () => {
+                const task = await taskCreate({ id: "js-task-1", metadata: { source: "js" } });
+                await taskUpdateStatus(task.id, "TASK_STATE_WORKING", "Charting the course");
+                await taskAddArtifact(task.id, { name: "course", parts: [{ text: "Sol" }] });
+                return await taskUpdateStatus(task.id, "completed");
+            })()"#,
+        )
+        .await
+        .unwrap();
+    assert_eq!(task["id"], json!("js-task-1"));
+    assert_eq!(task["status"]["state"], json!("TASK_STATE_COMPLETED"));
+    assert_eq!(
+        task["artifacts"][0]["artifactId"],
+        json!("js-task-1-artifact-1")
+    );
+
+    assert_eq!(
+        task_state(&agent, "js-task-1").await.as_deref(),
+        Some("TASK_STATE_COMPLETED")
+    );
+    let updates = agent.task_store().drain_updates("js-task-1").await;
+    assert_eq!(updates.len(), 4);
+
+    let error = agent
+        .bridge()
+        .lock()
+        .await
+        .evaluate(r#"taskUpdateStatus("js-task-1", "TASK_STATE_WORKING")"#)
+        .await
+        .unwrap_err();
+    assert!(error.to_string().contains("js-task-1"), "{}", error);
+}
@@ -1,23 +1,32 @@
 //! BAML Agent Builder
 //!
-//! This binary compiles, lints, and packages BAML + TypeScript agent applications
-//! into distributable tar.gz packages, and runs agents with stdin/stdout connectivity.
+//! This binary checks, compiles, lints, and packages BAML + TypeScript agent applications
+//! into distributable tar.gz packages, pushes them to OCI registries, runs agents with
+//! stdin/stdout connectivity, and provides an interactive REPL over an agent's source directory.
 //!
 //! Uses OXC for high-performance TypeScript compilation and linting.
 
 use baml_rt_builder::builder::{
-    AgentDir, BuildDir, BuilderService, FileSystem, FunctionName, Linter, OxcLinter,
-    OxcTypeScriptCompiler, PackagePath, RuntimeTypeGenerator, StdFileSystem, StdPackager,
+    AgentDir, AgentScaffold, AgentTestRunner, BuildDir, BuilderService, DevAgent, DevWatcher,
+    EvalDataset, EvalTarget, Evaluator, FileSystem, FunctionName, Linter, OxcLinter,
+    OxcTypeScriptCompiler, PackagePath, RuntimeTypeGenerator, SchemaChecker, Severity,
+    StdFileSystem, StdPackager, eval, push_package, watch_sources,
 };
+use baml_rt_core::manifest::{AgentManifest, MANIFEST_FILE, RUNTIME_VERSION};
 use baml_rt_core::{BamlRtError, Result};
 use baml_rt_observability::{spans, tracing_setup};
 use baml_rt_quickjs::{BamlRuntimeManager, QuickJSBridge};
-use clap::{Parser, Subcommand};
+use clap::{Parser, Subcommand, ValueEnum};
+use rustyline::completion::{Completer, Pair};
+use rustyline::error::ReadlineError;
+use rustyline::history::DefaultHistory;
+use rustyline::{Context, Editor, Helper, Highlighter, Hinter, Validator};
 use serde_json::Value;
 use std::fs;
 use std::io::{self, BufRead, Write};
 use std::path::PathBuf;
-use std::sync::Arc;
+use std::sync::{Arc, PoisonError, RwLock};
+use std::time::{Duration, Instant};
 use tokio::sync::Mutex;
 
 #[derive(Parser)]
@@ -29,13 +38,43 @@ struct Cli {
     command: Commands,
 }
 
+/// How a subcommand prints its results
+#[derive(Clone, Copy, ValueEnum)]
+enum OutputFormat {
+    /// Readable text
+    Human,
+    /// One JSON document, for editors and CI
+    Json,
+}
+
 #[derive(Subcommand)]
 enum Commands {
+    /// Scaffold a new agent project
+    New {
+        /// Agent name; also the name of the created directory
+        name: String,
+
+        /// Directory to create the project in (default: current directory)
+        #[arg(short, long, default_value = ".")]
+        dir: PathBuf,
+    },
+
     /// Lint TypeScript/JavaScript source code
     Lint {
         /// Agent directory (default: current directory)
         #[arg(short, long, default_value = ".")]
         agent_dir: PathBuf,
+
+        /// How to print the results
+        #[arg(long, value_enum, default_value_t = OutputFormat::Human)]
+        format: OutputFormat,
+    },
+
+    /// Check the BAML schema and the tool variants the agent maps
+    Check {
+        /// Agent directory (default: current directory)
+        #[arg(short, long, default_value = ".")]
+        agent_dir: PathBuf,
     },
 
     /// Package an agent into a tar.gz file
@@ -53,6 +92,18 @@ enum Commands {
         skip_lint: bool,
     },
 
+    /// Publish a package to an OCI registry, for runners to load with an
+    /// oci:// reference
+    Push {
+        /// Agent package file path
+        #[arg(short, long)]
+        package: PathBuf,
+
+        /// Where to push it, e.g. ghcr.io/acme/agent:1.0.0
+        #[arg(short, long)]
+        reference: String,
+    },
+
     /// Run an agent package with stdin/stdout connectivity
     Run {
         /// Agent package file path
@@ -66,6 +117,103 @@ enum Commands {
         /// JSON arguments (if not provided and function specified, reads from stdin)
         #[arg(short, long)]
         args: Option<String>,
+
+        /// Run the agent even if it targets an incompatible runtime version
+        #[arg(long)]
+        ignore_version: bool,
+
+        /// Extract the package here instead of the system temp directory
+        #[arg(long)]
+        workdir: Option<PathBuf>,
+    },
+
+    /// Run the agent's test suites (tests/*.test.ts) with BAML calls mocked
+    Test {
+        /// Agent directory (default: current directory)
+        #[arg(short, long, default_value = ".")]
+        agent_dir: PathBuf,
+
+        /// Only run test files whose path contains this string
+        #[arg(long)]
+        filter: Option<String>,
+
+        /// Write a JUnit XML report to this path
+        #[arg(long)]
+        junit: Option<PathBuf>,
+    },
+
+    /// Score a function or the agent's A2A handler over a dataset of cases
+    Eval {
+        /// Agent directory (default: current directory)
+        #[arg(short, long, default_value = ".")]
+        agent_dir: PathBuf,
+
+        /// Dataset of cases: a JSON array or JSONL of {name?, input, expected?}
+        #[arg(short, long)]
+        dataset: PathBuf,
+
+        /// Function to evaluate, called with each case input as its arguments
+        #[arg(short, long, conflicts_with = "a2a", required_unless_present = "a2a")]
+        function: Option<String>,
+
+        /// Evaluate handle_a2a_request; string inputs are sent as message.send text
+        #[arg(long)]
+        a2a: bool,
+
+        /// Scorer: exact, json-fields[:<pointer>,...], or judge:<function>[:<threshold>]
+        /// (repeatable; default: exact)
+        #[arg(short, long = "scorer")]
+        scorers: Vec<String>,
+
+        /// Write the JSON report to this path
+        #[arg(long)]
+        report: Option<PathBuf>,
+
+        /// How to print the results
+        #[arg(long, value_enum, default_value_t = OutputFormat::Human)]
+        format: OutputFormat,
+    },
+
+    /// Rebuild the agent whenever its sources change
+    Dev {
+        /// Agent directory (default: current directory)
+        #[arg(short, long, default_value = ".")]
+        agent_dir: PathBuf,
+
+        /// Where to write dist/ (default: the agent directory)
+        #[arg(short, long)]
+        out_dir: Option<PathBuf>,
+
+        /// Restart a local run of the agent after every rebuild
+        #[arg(long)]
+        run: bool,
+
+        /// Function to call on every restart (requires --run)
+        #[arg(short, long, requires = "run")]
+        function: Option<String>,
+
+        /// JSON arguments for --function (default: {})
+        #[arg(short = 'j', long, requires = "function")]
+        args: Option<String>,
+
+        /// How often to look for changes, in milliseconds
+        #[arg(long, default_value_t = 500)]
+        poll_ms: u64,
+    },
+
+    /// Load an agent directory (without packaging) into an interactive REPL
+    Repl {
+        /// Agent directory (default: current directory)
+        #[arg(short, long, default_value = ".")]
+        agent_dir: PathBuf,
+
+        /// Do not reload the agent when its sources change
+        #[arg(long)]
+        no_reload: bool,
+
+        /// How often to look for changes, in milliseconds
+        #[arg(long, default_value_t = 500)]
+        poll_ms: u64,
     },
 }
 
@@ -76,9 +224,20 @@ async fn main() -> Result<()> {
     let cli = Cli::parse();
 
     match cli.command {
-        Commands::Lint { agent_dir } => {
+        Commands::New { name, dir } => {
+            new_agent(&name, &dir)?;
+        }
+        Commands::Lint { agent_dir, format } => {
             let agent_dir = AgentDir::new(agent_dir)?;
-            lint_agent(&agent_dir).await?;
+            if !lint_agent(&agent_dir, format).await? {
+                std::process::exit(1);
+            }
+        }
+        Commands::Check { agent_dir } => {
+            let agent_dir = AgentDir::new(agent_dir)?;
+            if !check_agent(&agent_dir)? {
+                std::process::exit(1);
+            }
         }
         Commands::Package {
             agent_dir,
@@ -88,27 +247,259 @@ async fn main() -> Result<()> {
             let agent_dir = AgentDir::new(agent_dir)?;
             package_agent(&agent_dir, &output, !skip_lint).await?;
         }
+        Commands::Push { package, reference } => {
+            let package_path = PackagePath::new(package)?;
+            let pushed = push_package(&package_path, &reference).await?;
+            println!(
+                "✅ Pushed {} {} to {}",
+                pushed.manifest.name, pushed.manifest.version, pushed.manifest_url
+            );
+            println!("   Layer digest: {}", pushed.layer_digest);
+        }
         Commands::Run {
             package,
             function,
             args,
+            ignore_version,
+            workdir,
         } => {
             let package_path = PackagePath::new(package)?;
             let function_name = function.map(FunctionName::new).transpose()?;
-            run_agent(&package_path, function_name.as_ref(), args.as_deref()).await?;
+            run_agent(
+                &package_path,
+                function_name.as_ref(),
+                args.as_deref(),
+                ignore_version,
+                workdir.as_deref(),
+            )
+            .await?;
+        }
+        Commands::Test {
+            agent_dir,
+            filter,
+            junit,
+        } => {
+            let agent_dir = AgentDir::new(agent_dir)?;
+            if !test_agent(agent_dir, filter, junit.as_deref()).await? {
+                std::process::exit(1);
+            }
+        }
+        Commands::Eval {
+            agent_dir,
+            dataset,
+            function,
+            a2a,
+            scorers,
+            report,
+            format,
+        } => {
+            let agent_dir = AgentDir::new(agent_dir)?;
+            let target = match function {
+                Some(function) if !a2a => EvalTarget::Function(function),
+                _ => EvalTarget::A2a,
+            };
+            let options = EvalOptions {
+                target,
+                scorers,
+                report,
+                format,
+            };
+            if !eval_agent(&agent_dir, &dataset, options).await? {
+                std::process::exit(1);
+            }
+        }
+        Commands::Dev {
+            agent_dir,
+            out_dir,
+            run,
+            function,
+            args,
+            poll_ms,
+        } => {
+            let agent_dir = AgentDir::new(agent_dir)?;
+            let build_dir =
+                BuildDir::at(out_dir.unwrap_or_else(|| agent_dir.as_path().to_path_buf()))?;
+            let call = match function {
+                Some(function) => {
+                    let args = args.as_deref().unwrap_or("{}");
+                    let args = serde_json::from_str(args).map_err(|e| {
+                        BamlRtError::InvalidArgumentWithSource {
+                            message: format!("Invalid JSON arguments: {}", e),
+                            source: Box::new(e),
+                        }
+                    })?;
+                    Some((FunctionName::new(function)?, args))
+                }
+                None => None,
+            };
+            let options = DevOptions {
+                run,
+                call,
+                poll: Duration::from_millis(poll_ms.max(50)),
+            };
+            dev(agent_dir, build_dir, options).await?;
+        }
+        Commands::Repl {
+            agent_dir,
+            no_reload,
+            poll_ms,
+        } => {
+            let agent_dir = AgentDir::new(agent_dir)?;
+            let poll = (!no_reload).then(|| Duration::from_millis(poll_ms.max(50)));
+            repl(&agent_dir, poll).await?;
         }
     }
 
     Ok(())
 }
 
-async fn lint_agent(agent_dir: &AgentDir) -> Result<()> {
+fn new_agent(name: &str, dir: &std::path::Path) -> Result<()> {
+    let scaffold = AgentScaffold::new(name)?;
+    let root = scaffold.write(&StdFileSystem, dir)?;
+
+    println!(
+        "✅ Created agent '{}' in {}",
+        scaffold.name(),
+        root.display()
+    );
+    println!(
+        "
+Next steps:"
+    );
+    println!("   cd {}", root.display());
+    println!("   baml-agent-builder test");
+    println!(
+        "   baml-agent-builder package --output {}.tar.gz",
+        scaffold.name()
+    );
+    Ok(())
+}
+
+/// Print the agent's lint results; true when there are no errors
+async fn lint_agent(agent_dir: &AgentDir, format: OutputFormat) -> Result<bool> {
     let span = spans::lint_agent(agent_dir.as_path());
     let _guard = span.enter();
 
     let filesystem = StdFileSystem;
     let linter = OxcLinter::new(filesystem);
-    linter.lint(agent_dir).await
+    let report = linter.lint(agent_dir).await?;
+    match format {
+        OutputFormat::Human => print!("{}", report),
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&report)?),
+    }
+    Ok(report.error_count() == 0)
+}
+
+/// Print the agent's schema diagnostics; true when there are no errors
+fn check_agent(agent_dir: &AgentDir) -> Result<bool> {
+    let span = spans::check_agent(agent_dir.as_path());
+    let _guard = span.enter();
+
+    println!("🔍 Checking BAML schema in {}", agent_dir);
+    let diagnostics = SchemaChecker::new(StdFileSystem).check(agent_dir)?;
+    for diagnostic in &diagnostics {
+        println!("  {}", diagnostic);
+    }
+    let errors = diagnostics
+        .iter()
+        .filter(|diagnostic| diagnostic.severity == Severity::Error)
+        .count();
+    if errors > 0 {
+        println!("\n❌ Check failed with {} error(s)", errors);
+        return Ok(false);
+    }
+    println!(
+        "\n✓ Schema is valid ({} warning(s))",
+        diagnostics.len() - errors
+    );
+    Ok(true)
+}
+
+/// How `dev` runs the agent it rebuilds
+struct DevOptions {
+    /// Restart a local run of the agent after every rebuild
+    run: bool,
+    /// Function called on every restart, with its arguments
+    call: Option<(FunctionName, Value)>,
+    poll: Duration,
+}
+
+async fn dev(agent_dir: AgentDir, build_dir: BuildDir, options: DevOptions) -> Result<()> {
+    let span = spans::watch_agent(agent_dir.as_path());
+    let _guard = span.enter();
+
+    println!("👀 Watching {} (Ctrl+C to stop)", agent_dir);
+    println!("   Output: {}", build_dir.join("dist").display());
+    let mut watcher = DevWatcher::new(
+        agent_dir.clone(),
+        build_dir,
+        OxcTypeScriptCompiler::new(StdFileSystem),
+        RuntimeTypeGenerator::new(),
+    );
+    let mut running: Option<DevAgent> = None;
+    loop {
+        let started = Instant::now();
+        match watcher.rebuild().await {
+            Ok(rebuild) if rebuild.any() => {
+                let mut steps = Vec::new();
+                if rebuild.types {
+                    steps.push("types");
+                }
+                if rebuild.typescript {
+                    steps.push("typescript");
+                }
+                if rebuild.manifest {
+                    steps.push("manifest");
+                }
+                println!(
+                    "✅ Rebuilt {} in {} ms",
+                    steps.join(", "),
+                    started.elapsed().as_millis()
+                );
+                if options.run {
+                    // Stop the previous run before starting the next
+                    drop(running.take());
+                    running = restart_dev_agent(&agent_dir, options.call.as_ref()).await;
+                }
+            }
+            Ok(_) => {}
+            Err(e) => eprintln!("❌ Rebuild failed: {}", e),
+        }
+
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => break,
+            _ = tokio::time::sleep(options.poll) => {}
+        }
+    }
+    println!("\n👋 Stopped watching");
+    Ok(())
+}
+
+/// Load the agent afresh and make the configured call, if any
+async fn restart_dev_agent(
+    agent_dir: &AgentDir,
+    call: Option<&(FunctionName, Value)>,
+) -> Option<DevAgent> {
+    let agent = match DevAgent::load(agent_dir).await {
+        Ok(agent) => agent,
+        Err(e) => {
+            eprintln!("❌ Agent failed to start: {}", e);
+            return None;
+        }
+    };
+    println!("🚀 Agent running: {}", agent.name());
+    if let Some((function, args)) = call {
+        let invoke_span = spans::invoke_function(agent.name(), function.as_str());
+        let _invoke_guard = invoke_span.enter();
+        match agent.invoke(function.as_str(), args.clone()).await {
+            Ok(result) => match serde_json::to_string_pretty(&result) {
+                Ok(result) => println!("{}", result),
+                Err(e) => eprintln!("Error: {}", e),
+            },
+            Err(e) => eprintln!("Error: {}() failed: {}", function, e),
+        }
+    }
+    Some(agent)
 }
 
 async fn package_agent(agent_dir: &AgentDir, output: &std::path::Path, lint: bool) -> Result<()> {
@@ -150,13 +541,15 @@ async fn run_agent(
     package_path: &PackagePath,
     function: Option<&FunctionName>,
     args_json: Option<&str>,
+    ignore_version: bool,
+    workdir: Option<&std::path::Path>,
 ) -> Result<()> {
     let span = spans::load_agent_package(package_path.as_path());
     let _guard = span.enter();
 
     // Load the agent package
     println!("📦 Loading agent package: {}", package_path);
-    let agent = load_agent_package(package_path.as_path()).await?;
+    let agent = load_agent_package(package_path.as_path(), ignore_version, workdir).await?;
     println!("✅ Agent loaded: {}", agent.name());
 
     // If function is specified, call it once
@@ -248,10 +641,414 @@ async fn run_agent(
     Ok(())
 }
 
+/// Run the agent's tests and print a summary. Returns whether every test passed.
+async fn test_agent(
+    agent_dir: AgentDir,
+    filter: Option<String>,
+    junit: Option<&std::path::Path>,
+) -> Result<bool> {
+    println!("🧪 Running agent tests in {}", agent_dir);
+    let mut runner = AgentTestRunner::new(agent_dir);
+    if let Some(filter) = filter {
+        runner = runner.with_filter(filter);
+    }
+    let report = runner.run().await?;
+
+    for suite in &report.suites {
+        println!("\n{}", suite.name);
+        if let Some(error) = &suite.error {
+            println!("  ✗ failed to run: {}", error);
+        }
+        for case in &suite.cases {
+            match &case.failure {
+                None => println!("  ✓ {} ({} ms)", case.name, case.duration_ms),
+                Some(failure) => println!("  ✗ {}\n      {}", case.name, failure),
+            }
+        }
+    }
+
+    let total = report.total();
+    let failures = report.failures();
+    println!(
+        "\n{} passed, {} failed, {} file error(s)",
+        total - failures,
+        failures,
+        report.errors()
+    );
+
+    if let Some(path) = junit {
+        StdFileSystem.write_string(path, &report.to_junit_xml())?;
+        println!("📝 JUnit report written to {}", path.display());
+    }
+
+    Ok(report.success())
+}
+
+/// What `eval` runs and where its results go
+struct EvalOptions {
+    target: EvalTarget,
+    /// Scorer specs, parsed by `eval::scorer_from_spec`
+    scorers: Vec<String>,
+    report: Option<PathBuf>,
+    format: OutputFormat,
+}
+
+/// Evaluate the agent over a dataset and print the report. Returns whether
+/// every case passed.
+async fn eval_agent(
+    agent_dir: &AgentDir,
+    dataset: &std::path::Path,
+    options: EvalOptions,
+) -> Result<bool> {
+    let dataset = EvalDataset::load(dataset)?;
+    let mut evaluator = Evaluator::new(options.target);
+    let specs = if options.scorers.is_empty() {
+        vec!["exact".to_string()]
+    } else {
+        options.scorers
+    };
+    for spec in &specs {
+        evaluator = evaluator.with_boxed_scorer(eval::scorer_from_spec(spec)?);
+    }
+
+    let agent = DevAgent::load(agent_dir).await?;
+    let report = evaluator.run(&agent, &dataset).await;
+    match options.format {
+        OutputFormat::Human => print!("{}", report),
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&report)?),
+    }
+    if let Some(path) = &options.report {
+        StdFileSystem.write_string(path, &serde_json::to_string_pretty(&report)?)?;
+        if matches!(options.format, OutputFormat::Human) {
+            println!("📝 Evaluation report written to {}", path.display());
+        }
+    }
+    Ok(report.success())
+}
+
+const REPL_COMMANDS: &[&str] = &[
+    ":help",
+    ":functions",
+    ":tools",
+    ":stream",
+    ":tool",
+    ":reload",
+    ":quit",
+];
+
+/// Names offered by tab completion, refreshed when the agent reloads
+#[derive(Default)]
+struct Completions {
+    functions: Vec<String>,
+    tools: Vec<String>,
+}
+
+impl Completions {
+    async fn of(agent: &DevAgent) -> Self {
+        Self {
+            functions: agent.functions().await,
+            tools: agent.tools().await,
+        }
+    }
+}
+
+/// Tab completion over REPL commands, BAML functions, and tools
+#[derive(Helper, Hinter, Highlighter, Validator)]
+struct ReplHelper {
+    completions: Arc<RwLock<Completions>>,
+}
+
+impl Completer for ReplHelper {
+    type Candidate = Pair;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<Pair>)> {
+        let line = &line[..pos];
+        let start = line.rfind(char::is_whitespace).map_or(0, |idx| idx + 1);
+        let word = &line[start..];
+        let previous = line[..start].split_whitespace().collect::<Vec<_>>();
+        let completions = self
+            .completions
+            .read()
+            .unwrap_or_else(PoisonError::into_inner);
+
+        let candidates: Vec<&String> = match previous.as_slice() {
+            [] if word.starts_with(':') => {
+                return Ok((
+                    start,
+                    REPL_COMMANDS
+                        .iter()
+                        .filter(|command| command.starts_with(word))
+                        .map(|command| Pair {
+                            display: command.to_string(),
+                            replacement: command.to_string(),
+                        })
+                        .collect(),
+                ));
+            }
+            [] | [":stream"] => completions.functions.iter().collect(),
+            [":tool"] => completions.tools.iter().collect(),
+            _ => return Ok((pos, Vec::new())),
+        };
+
+        Ok((
+            start,
+            candidates
+                .into_iter()
+                .filter(|name| name.starts_with(word))
+                .map(|name| Pair {
+                    display: name.clone(),
+                    replacement: format!("{} ", name),
+                })
+                .collect(),
+        ))
+    }
+}
+
+fn repl_history_path() -> Option<PathBuf> {
+    std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".baml_agent_repl_history"))
+}
+
+/// Lines typed at the REPL prompt
+///
+/// The editor runs on a thread of its own, so the agent can reload while the
+/// prompt waits. Each call to `prompt` reads one line.
+struct LineReader {
+    prompts: std::sync::mpsc::Sender<()>,
+    lines: tokio::sync::mpsc::UnboundedReceiver<rustyline::Result<String>>,
+    thread: std::thread::JoinHandle<()>,
+}
+
+impl LineReader {
+    fn spawn(helper: ReplHelper) -> Self {
+        let (prompts, prompted) = std::sync::mpsc::channel::<()>();
+        let (sender, lines) = tokio::sync::mpsc::unbounded_channel();
+        let thread = std::thread::spawn(move || {
+            let mut editor: Editor<ReplHelper, DefaultHistory> = match Editor::new() {
+                Ok(editor) => editor,
+                Err(e) => {
+                    let _ = sender.send(Err(e));
+                    return;
+                }
+            };
+            editor.set_helper(Some(helper));
+            let history_path = repl_history_path();
+            if let Some(path) = &history_path {
+                let _ = editor.load_history(path);
+            }
+            while prompted.recv().is_ok() {
+                let line = editor.readline("> ");
+                if let Ok(line) = &line
+                    && !line.trim().is_empty()
+                {
+                    let _ = editor.add_history_entry(line.trim());
+                }
+                let eof = matches!(line, Err(ReadlineError::Eof));
+                if sender.send(line).is_err() || eof {
+                    break;
+                }
+            }
+            if let Some(path) = &history_path {
+                let _ = editor.save_history(path);
+            }
+        });
+        Self {
+            prompts,
+            lines,
+            thread,
+        }
+    }
+
+    /// Prompt for a line
+    fn prompt(&self) {
+        // A stopped editor has nothing more to read, which `line` reports
+        let _ = self.prompts.send(());
+    }
+
+    /// The line prompted for; `None` once the editor has stopped
+    async fn line(&mut self) -> Option<rustyline::Result<String>> {
+        self.lines.recv().await
+    }
+
+    /// Stop the editor, saving its history
+    fn close(self) {
+        drop(self.prompts);
+        let _ = self.thread.join();
+    }
+}
+
+/// The next change to the agent's sources, or never when not watching
+async fn source_change(changes: &mut Option<tokio::sync::mpsc::Receiver<()>>) -> Option<()> {
+    match changes {
+        Some(changes) => changes.recv().await,
+        None => std::future::pending().await,
+    }
+}
+
+/// Replace `agent` with a fresh load of `agent_dir`, keeping it if that fails
+async fn reload_agent(
+    agent_dir: &AgentDir,
+    agent: &mut DevAgent,
+    completions: &RwLock<Completions>,
+) {
+    match DevAgent::load(agent_dir).await {
+        Ok(reloaded) => {
+            *agent = reloaded;
+            let refreshed = Completions::of(agent).await;
+            *completions.write().unwrap_or_else(PoisonError::into_inner) = refreshed;
+            println!("✅ Agent reloaded: {}", agent.name());
+        }
+        Err(e) => eprintln!("Error: Reload failed, keeping previous agent: {}", e),
+    }
+}
+
+/// Run the REPL over `agent_dir`, reloading the agent when its sources
+/// change if `poll` is set
+async fn repl(agent_dir: &AgentDir, poll: Option<Duration>) -> Result<()> {
+    println!("📂 Loading agent from {}", agent_dir);
+    let mut agent = DevAgent::load(agent_dir).await?;
+    let mut changes = poll.map(|poll| watch_sources(agent_dir.clone(), poll));
+    println!("✅ Agent loaded: {}", agent.name());
+    println!("   Type :help for commands, Tab to complete, Ctrl+D to exit\n");
+
+    let completions = Arc::new(RwLock::new(Completions::of(&agent).await));
+    let mut reader = LineReader::spawn(ReplHelper {
+        completions: completions.clone(),
+    });
+
+    loop {
+        reader.prompt();
+        let line = loop {
+            tokio::select! {
+                line = reader.line() => break line,
+                Some(()) = source_change(&mut changes) => {
+                    println!("\n🔄 Sources changed, reloading agent...");
+                    reload_agent(agent_dir, &mut agent, &completions).await;
+                }
+            }
+        };
+        let line = match line {
+            Some(Ok(line)) => line,
+            Some(Err(ReadlineError::Interrupted)) => continue,
+            None | Some(Err(ReadlineError::Eof)) => break,
+            Some(Err(e)) => return Err(BamlRtError::Io(io::Error::other(e))),
+        };
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            continue;
+        }
+
+        let (command, rest) = trimmed
+            .split_once(char::is_whitespace)
+            .map(|(command, rest)| (command, rest.trim()))
+            .unwrap_or((trimmed, ""));
+
+        match command {
+            ":quit" | ":exit" => break,
+            ":reload" => reload_agent(agent_dir, &mut agent, &completions).await,
+            ":help" => {
+                println!("  <function> <json_args>        Call a BAML or JS function");
+                println!("  :stream <function> <json>     Stream partial results");
+                println!("  :tool <tool> <json>           Execute a registered tool");
+                println!("  :functions                    List BAML functions");
+                println!("  :tools                        List registered tools");
+                println!("  :reload                       Reload the agent");
+                println!("  :quit                         Exit");
+            }
+            ":functions" => {
+                for function in agent.functions().await {
+                    println!("  {}", function);
+                }
+            }
+            ":tools" => {
+                for tool in agent.tools().await {
+                    println!("  {}", tool);
+                }
+            }
+            ":stream" | ":tool" => {
+                let (name, args) = match parse_call(rest) {
+                    Ok(call) => call,
+                    Err(e) => {
+                        eprintln!("Error: {}", e);
+                        continue;
+                    }
+                };
+                if command == ":tool" {
+                    match agent.execute_tool(name, args).await {
+                        Ok(result) => println!("{}", serde_json::to_string_pretty(&result)?),
+                        Err(e) => eprintln!("Error: {}", e),
+                    }
+                    continue;
+                }
+                let invoke_span = spans::invoke_function(agent.name(), name);
+                let _invoke_guard = invoke_span.enter();
+                let mut idx = 0;
+                let streamed = agent.invoke_stream(name, args, |chunk| {
+                    println!("[{}] {}", idx, chunk);
+                    idx += 1;
+                });
+                if let Err(e) = streamed.await {
+                    eprintln!("Error: {}", e);
+                }
+            }
+            other if other.starts_with(':') => {
+                eprintln!("Error: Unknown command {} (try :help)", other);
+            }
+            _ => {
+                let (name, args) = match parse_call(trimmed) {
+                    Ok(call) => call,
+                    Err(e) => {
+                        eprintln!("Error: {}", e);
+                        continue;
+                    }
+                };
+                let invoke_span = spans::invoke_function(agent.name(), name);
+                let _invoke_guard = invoke_span.enter();
+                match agent.invoke(name, args).await {
+                    Ok(result) => println!("{}", serde_json::to_string_pretty(&result)?),
+                    Err(e) => eprintln!("Error: {}", e),
+                }
+            }
+        }
+    }
+
+    reader.close();
+    println!("\n👋 Exiting");
+    Ok(())
+}
+
+/// Split `<name> [json_args]` into a name and arguments (default `{}`)
+fn parse_call(input: &str) -> Result<(&str, Value)> {
+    let (name, args) = input
+        .split_once(char::is_whitespace)
+        .map(|(name, args)| (name, args.trim()))
+        .unwrap_or((input, ""));
+    if name.is_empty() {
+        return Err(BamlRtError::InvalidArgument(
+            "Expected format: <name> <json_args>".to_string(),
+        ));
+    }
+    let args = if args.is_empty() {
+        Value::Object(Default::default())
+    } else {
+        serde_json::from_str(args).map_err(|e| BamlRtError::InvalidArgumentWithSource {
+            message: "Invalid JSON args".to_string(),
+            source: Box::new(e),
+        })?
+    };
+    Ok((name, args))
+}
+
 // Agent package loader (reusing logic from baml-agent-runner)
 struct LoadedAgent {
     name: String,
     js_bridge: Arc<Mutex<QuickJSBridge>>,
+    /// Removed along with the agent
+    _extract_dir: tempfile::TempDir,
 }
 
 impl LoadedAgent {
@@ -266,61 +1063,54 @@ impl LoadedAgent {
     }
 }
 
-async fn load_agent_package(package_path: &std::path::Path) -> Result<LoadedAgent> {
+async fn load_agent_package(
+    package_path: &std::path::Path,
+    ignore_version: bool,
+    workdir: Option<&std::path::Path>,
+) -> Result<LoadedAgent> {
     use std::sync::Arc;
-    use tokio::sync::Mutex;
+    use tokio::sync::{Mutex, RwLock};
 
-    // Extract package
-    let timestamp = std::time::SystemTime::now()
-        .duration_since(std::time::UNIX_EPOCH)
-        .map_err(BamlRtError::SystemTime)?;
-
-    let extract_dir = std::env::temp_dir().join(format!("baml-agent-{}", timestamp.as_secs()));
-    fs::create_dir_all(&extract_dir).map_err(BamlRtError::Io)?;
+    // Extract package to a directory of its own
+    let mut temp_dir = tempfile::Builder::new();
+    temp_dir.prefix("baml-agent-");
+    let temp_dir = match workdir {
+        Some(workdir) => {
+            fs::create_dir_all(workdir).map_err(BamlRtError::Io)?;
+            temp_dir.tempdir_in(workdir)
+        }
+        None => temp_dir.tempdir(),
+    }
+    .map_err(BamlRtError::Io)?;
+    let extract_dir = temp_dir.path();
 
     let tar_gz = fs::File::open(package_path).map_err(BamlRtError::Io)?;
     let tar = flate2::read::GzDecoder::new(tar_gz);
     let mut archive = tar::Archive::new(tar);
-    archive.unpack(&extract_dir).map_err(BamlRtError::Io)?;
-
-    // Load manifest
-    let manifest_path = extract_dir.join("manifest.json");
-    let manifest_content = fs::read_to_string(&manifest_path).map_err(BamlRtError::Io)?;
-    let manifest_json: Value =
-        serde_json::from_str(&manifest_content).map_err(BamlRtError::Json)?;
-
-    let name = manifest_json
-        .get("name")
-        .and_then(|v| v.as_str())
-        .ok_or_else(|| {
-            BamlRtError::InvalidArgument("manifest.json missing 'name' field".to_string())
-        })?
-        .to_string();
+    archive.unpack(extract_dir).map_err(BamlRtError::Io)?;
 
-    let entry_point = manifest_json
-        .get("entry_point")
-        .and_then(|v| v.as_str())
-        .map(String::from)
-        .unwrap_or_else(|| "dist/index.js".to_string());
+    let manifest = AgentManifest::load(&extract_dir.join(MANIFEST_FILE))?;
+    match manifest.check_runtime_version(RUNTIME_VERSION) {
+        Err(err) if ignore_version => {
+            tracing::warn!(error = %err, "Running agent despite runtime version mismatch");
+        }
+        result => result?,
+    }
+    manifest.check_environment()?;
+    manifest.check_capabilities()?;
 
     // Load BAML schema
     let baml_src = extract_dir.join("baml_src");
     let runtime_manager = {
         let schema_span = spans::load_baml_schema(&baml_src);
         let _schema_guard = schema_span.enter();
-        let baml_src_str = baml_src.to_str().ok_or_else(|| {
-            BamlRtError::InvalidArgument(format!(
-                "BAML source path contains invalid UTF-8: {}",
-                baml_src.display()
-            ))
-        })?;
         let mut rm = BamlRuntimeManager::new()?;
-        rm.load_schema(baml_src_str)?;
+        rm.load_schema(&baml_src)?;
         rm
     };
 
     // Create QuickJS bridge
-    let runtime_manager_arc = Arc::new(Mutex::new(runtime_manager));
+    let runtime_manager_arc = Arc::new(RwLock::new(runtime_manager));
     let mut js_bridge = {
         let bridge_span = spans::create_js_bridge();
         let _bridge_guard = bridge_span.enter();
@@ -330,22 +1120,31 @@ async fn load_agent_package(package_path: &std::path::Path) -> Result<LoadedAgen
     };
 
     // Load agent JavaScript code
-    let entry_point_path = extract_dir.join(&entry_point);
+    let entry_point_path = extract_dir.join(&manifest.entry_point);
     if entry_point_path.exists() {
-        let eval_span = spans::evaluate_agent_code(&entry_point);
+        let eval_span = spans::evaluate_agent_code(&manifest.entry_point);
         let _eval_guard = eval_span.enter();
         let agent_code = fs::read_to_string(&entry_point_path).map_err(BamlRtError::Io)?;
+        let mut source_map_path = entry_point_path.clone().into_os_string();
+        source_map_path.push(".map");
+        let source_map = fs::read_to_string(source_map_path).ok();
         // Execute agent code - this should set up functions on globalThis
-        if let Err(e) = js_bridge.evaluate(&agent_code).await {
+        if let Err(e) = js_bridge
+            .evaluate_script(&manifest.entry_point, &agent_code, source_map.as_deref())
+            .await
+        {
             tracing::warn!(error = ?e, "Agent init script evaluation failed");
         }
     } else {
         tracing::warn!(entry_point = %entry_point_path.display(), "Agent entry point not found");
     }
 
+    manifest.check_tools(&runtime_manager_arc.read().await.list_tools().await)?;
+
     Ok(LoadedAgent {
-        name,
+        name: manifest.name,
         js_bridge: Arc::new(Mutex::new(js_bridge)),
+        _extract_dir: temp_dir,
     })
 }
 
@@ -356,7 +1155,7 @@ mod tests {
     use baml_rt_quickjs::QuickJSBridge;
     use serde_json::json;
     use std::sync::Arc;
-    use tokio::sync::Mutex;
+    use tokio::sync::{Mutex, RwLock};
 
     async fn create_test_agent() -> LoadedAgent {
         let agent_dir = test_support::common::agent_fixture("voidship-rites");
@@ -366,7 +1165,7 @@ mod tests {
             .load_schema(agent_dir.to_str().unwrap())
             .unwrap();
 
-        let runtime_manager_arc = Arc::new(Mutex::new(runtime_manager));
+        let runtime_manager_arc = Arc::new(RwLock::new(runtime_manager));
         let mut js_bridge = QuickJSBridge::new(runtime_manager_arc.clone())
             .await
             .unwrap();
@@ -384,6 +1183,7 @@ mod tests {
         LoadedAgent {
             name: "test-agent".to_string(),
             js_bridge: Arc::new(Mutex::new(js_bridge)),
+            _extract_dir: tempfile::tempdir().unwrap(),
         }
     }
 
@@ -0,0 +1,1351 @@
+//! Multi-file TypeScript bundling
+//!
+//! Follows relative imports from an entry file and emits a single script that
+//! QuickJS can evaluate without a module loader. The entry module runs at the
+//! top level, so its declarations stay global exactly as they did for
+//! single-file agents. Every other module is wrapped in a factory that runs on
+//! first `__bundle_require`; its exports become getters on an exports object,
+//! and importers read their bindings from that object when they run.
+//!
+//! Dependencies are tree-shaken per statement: a top-level function, class,
+//! enum, or side-effect-free variable is dropped when no kept code refers to
+//! it by name. Statements that may have side effects are always kept.
+//!
+//! The concatenated source is transpiled in one pass, and the resulting
+//! source map is rewritten to point back at the original files.
+//!
+//! Bare imports are bundled from `node_modules` as ordinary modules when the
+//! [`NpmPolicy`] allows the package; see [`crate::builder::npm`].
+
+use baml_rt_core::{BamlRtError, Result};
+use baml_rt_quickjs::source_map::decode_mappings;
+use oxc_allocator::Allocator;
+use oxc_ast::ast::{
+    BindingIdentifier, Declaration, ExportDefaultDeclarationKind, Expression, IdentifierReference,
+    ImportDeclarationSpecifier, ModuleDeclaration, Statement,
+};
+use oxc_ast_visit::Visit;
+use oxc_codegen::{Codegen, CodegenOptions};
+use oxc_parser::Parser;
+use oxc_semantic::SemanticBuilder;
+use oxc_span::{GetSpan, SourceType};
+use oxc_transformer::{HelperLoaderMode, TransformOptions, Transformer};
+use serde_json::{Value, json};
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
+use std::path::{Component, Path, PathBuf};
+
+use crate::builder::npm::{self, NpmPolicy};
+use crate::builder::traits::FileSystem;
+
+/// Extensions tried, in order, when an import omits one
+const RESOLVE_EXTENSIONS: &[&str] = &["ts", "tsx", "js", "jsx"];
+
+const PRELUDE: &str = r#"var __bundle_factories = Object.create(null);
+var __bundle_cache = Object.create(null);
+function __bundle_define(id, factory) { __bundle_factories[id] = factory; }
+function __bundle_require(id) {
+  var cached = __bundle_cache[id];
+  if (cached) return cached.exports;
+  var module = { exports: {} };
+  __bundle_cache[id] = module;
+  __bundle_factories[id](module.exports);
+  return module.exports;
+}
+function __bundle_export(exports, name, get) { Object.defineProperty(exports, name, { enumerable: true, get: get }); }
+function __bundle_export_star(exports, source) {
+  Object.keys(source).forEach(function (name) {
+    if (name !== "default" && !Object.prototype.hasOwnProperty.call(exports, name)) {
+      __bundle_export(exports, name, function () { return source[name]; });
+    }
+  });
+}
+"#;
+
+/// A bundled script and its source map
+#[derive(Debug, Clone)]
+pub struct Bundle {
+    /// JavaScript ending in a `sourceMappingURL` comment
+    pub code: String,
+    /// Source map (v3 JSON) pointing at the original modules
+    pub source_map: String,
+}
+
+/// Bundles an entry file and the modules it imports
+pub struct Bundler<'a, FS> {
+    filesystem: &'a FS,
+    root: &'a Path,
+    npm: NpmPolicy,
+}
+
+impl<'a, FS: FileSystem> Bundler<'a, FS> {
+    /// Create a bundler; module IDs and source map paths are relative to `root`
+    ///
+    /// Bare imports are refused until [`Bundler::with_npm_policy`] allows them.
+    pub fn new(filesystem: &'a FS, root: &'a Path) -> Self {
+        Self {
+            filesystem,
+            root,
+            npm: NpmPolicy::default(),
+        }
+    }
+
+    /// Bundle the npm packages `policy` allows
+    pub fn with_npm_policy(mut self, policy: NpmPolicy) -> Self {
+        self.npm = policy;
+        self
+    }
+
+    /// Bundle `entry` into a script that will be written as `output_file_name`
+    pub fn bundle(&self, entry: &Path, output_file_name: &str) -> Result<Bundle> {
+        let graph = self.load_graph(entry)?;
+        let shaken = graph.shake();
+
+        let mut writer = BundleWriter::default();
+        let mut counter = 0;
+        if graph.modules.len() > 1 {
+            writer.push_generated(PRELUDE);
+        }
+        for index in 1..graph.modules.len() {
+            let (body, getters) = graph.render(index, &shaken, &mut counter);
+            writer.push_generated(&format!(
+                "__bundle_define({}, function (__exports) {{ {}\n",
+                json!(graph.modules[index].id),
+                getters
+            ));
+            writer.push_module(index, &body);
+            writer.push_generated("});\n");
+        }
+        let (body, _) = graph.render(0, &shaken, &mut counter);
+        writer.push_module(0, &body);
+
+        let jsx = graph.modules.iter().any(|module| module.jsx);
+        let (code, map) = transpile(entry, &writer.text, jsx, output_file_name)?;
+        let source_map = remap_source_map(&map, &writer.segments, &graph, output_file_name)?;
+
+        Ok(Bundle {
+            code: format!(
+                "{}\n//# sourceMappingURL={}.map\n",
+                code.trim_end(),
+                output_file_name
+            ),
+            source_map,
+        })
+    }
+
+    fn load_graph(&self, entry: &Path) -> Result<ModuleGraph> {
+        let mut graph = ModuleGraph::default();
+        let mut by_path: HashMap<PathBuf, usize> = HashMap::new();
+        let mut queue = VecDeque::new();
+        let mut dependency_bytes = 0u64;
+
+        let entry = normalize(entry);
+        by_path.insert(entry.clone(), 0);
+        graph.modules.push(self.load_module(&entry)?);
+        queue.push_back(0);
+
+        while let Some(index) = queue.pop_front() {
+            let path = graph.modules[index].path.clone();
+            let specifiers: Vec<String> = graph.modules[index]
+                .statements
+                .iter()
+                .filter_map(|statement| statement.kind.source())
+                .map(str::to_string)
+                .collect();
+            for specifier in specifiers {
+                let resolved = if is_relative(&specifier) {
+                    resolve_import(&path, &specifier)?
+                } else {
+                    self.npm.resolve(self.filesystem, &path, &specifier)?
+                };
+                let dependency = match by_path.get(&resolved) {
+                    Some(&dependency) => dependency,
+                    None => {
+                        let dependency = graph.modules.len();
+                        by_path.insert(resolved.clone(), dependency);
+                        let module = self.load_module(&resolved)?;
+                        if npm::is_dependency(&resolved) {
+                            dependency_bytes += module.source.len() as u64;
+                            if dependency_bytes > self.npm.max_bytes() {
+                                return Err(BamlRtError::InvalidArgument(format!(
+                                    "npm dependencies of {} exceed {} bytes of source at {}; \
+                                     raise bundle.max_dependency_bytes or import less",
+                                    entry.display(),
+                                    self.npm.max_bytes(),
+                                    resolved.display()
+                                )));
+                            }
+                        }
+                        graph.modules.push(module);
+                        queue.push_back(dependency);
+                        dependency
+                    }
+                };
+                if dependency == 0 {
+                    return Err(BamlRtError::InvalidArgument(format!(
+                        "{} imports the entry module {}; move the shared code into its own module",
+                        path.display(),
+                        entry.display()
+                    )));
+                }
+                graph.modules[index]
+                    .dependencies
+                    .insert(specifier, dependency);
+            }
+        }
+
+        Ok(graph)
+    }
+
+    fn load_module(&self, path: &Path) -> Result<Module> {
+        let source = self.filesystem.read_to_string(path)?;
+        let (statements, jsx) = analyze(path, &source)?;
+        let relative = match path.strip_prefix(self.root) {
+            Ok(relative) => relative.to_path_buf(),
+            Err(_) => npm::dependency_id(path).unwrap_or_else(|| path.to_path_buf()),
+        };
+        let id = relative
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        Ok(Module {
+            id,
+            path: path.to_path_buf(),
+            source,
+            jsx,
+            statements,
+            dependencies: HashMap::new(),
+        })
+    }
+}
+
+struct Module {
+    id: String,
+    path: PathBuf,
+    source: String,
+    jsx: bool,
+    statements: Vec<TopLevel>,
+    /// Import specifier -> module index
+    dependencies: HashMap<String, usize>,
+}
+
+impl Module {
+    /// Names this module exports itself, as opposed to through `export *`
+    fn explicit_exports(&self) -> HashSet<&str> {
+        let mut names = HashSet::new();
+        for statement in &self.statements {
+            match &statement.kind {
+                Kind::ExportDeclaration { .. } => {
+                    names.extend(statement.declares.iter().map(String::as_str))
+                }
+                Kind::ExportDefault { .. } => {
+                    names.insert("default");
+                }
+                Kind::ExportList { specifiers, .. } => {
+                    names.extend(specifiers.iter().map(|(_, exported)| exported.as_str()))
+                }
+                Kind::ExportAll {
+                    alias: Some(alias), ..
+                } => {
+                    names.insert(alias.as_str());
+                }
+                _ => {}
+            }
+        }
+        names
+    }
+}
+
+/// Exports of a module that importers use
+#[derive(Debug, Clone, Default, PartialEq)]
+struct Demand {
+    all: bool,
+    names: BTreeSet<String>,
+}
+
+impl Demand {
+    fn all() -> Self {
+        Self {
+            all: true,
+            names: BTreeSet::new(),
+        }
+    }
+
+    fn wants(&self, name: &str) -> bool {
+        self.all || self.names.contains(name)
+    }
+
+    /// Merge `other` into this demand, returning whether it grew
+    fn merge(&mut self, other: &Demand) -> bool {
+        let before = (self.all, self.names.len());
+        self.all |= other.all;
+        self.names.extend(other.names.iter().cloned());
+        before != (self.all, self.names.len())
+    }
+}
+
+struct Shaken {
+    kept: Vec<Vec<bool>>,
+    demands: Vec<Demand>,
+}
+
+#[derive(Default)]
+struct ModuleGraph {
+    /// The entry module is always at index 0
+    modules: Vec<Module>,
+}
+
+impl ModuleGraph {
+    /// Decide which top-level statements of every module survive tree-shaking,
+    /// along with the exports importers need from each module
+    fn shake(&self) -> Shaken {
+        let mut demands = vec![Demand::default(); self.modules.len()];
+        demands[0] = Demand::all();
+
+        loop {
+            let kept: Vec<Vec<bool>> = (0..self.modules.len())
+                .map(|index| self.keep_statements(index, &demands[index]))
+                .collect();
+
+            let mut changed = false;
+            for (index, module) in self.modules.iter().enumerate() {
+                let used = self.used_names(index, &kept[index]);
+                for (statement, keep) in module.statements.iter().zip(&kept[index]) {
+                    if !keep {
+                        continue;
+                    }
+                    let Some(source) = statement.kind.source() else {
+                        continue;
+                    };
+                    let dependency = module.dependencies[source];
+                    let demand = statement.demand_on_source(&used, &demands[index], module);
+                    changed |= demands[dependency].merge(&demand);
+                }
+            }
+            if !changed {
+                return Shaken { kept, demands };
+            }
+        }
+    }
+
+    fn keep_statements(&self, index: usize, demand: &Demand) -> Vec<bool> {
+        let module = &self.modules[index];
+        if index == 0 {
+            // The entry's top-level declarations are the agent's global API
+            return module
+                .statements
+                .iter()
+                .map(|statement| !matches!(statement.kind, Kind::TypeOnly))
+                .collect();
+        }
+
+        let explicit = module.explicit_exports();
+        let mut referenced: HashSet<String> = HashSet::new();
+        let mut keep: Vec<bool> = module
+            .statements
+            .iter()
+            .map(|statement| match &statement.kind {
+                Kind::Plain => !statement.removable,
+                Kind::ExportDeclaration { .. } => {
+                    !statement.removable || statement.declares.iter().any(|name| demand.wants(name))
+                }
+                Kind::ExportDefault { .. } => !statement.removable || demand.wants("default"),
+                Kind::ExportList {
+                    specifiers,
+                    source: None,
+                } => {
+                    let wanted: Vec<String> = specifiers
+                        .iter()
+                        .filter(|(_, exported)| demand.wants(exported))
+                        .map(|(local, _)| local.clone())
+                        .collect();
+                    let keep = !wanted.is_empty();
+                    referenced.extend(wanted);
+                    keep
+                }
+                Kind::ExportList { specifiers, .. } => specifiers
+                    .iter()
+                    .any(|(_, exported)| demand.wants(exported)),
+                Kind::ExportAll {
+                    alias: Some(alias), ..
+                } => demand.wants(alias),
+                Kind::ExportAll { alias: None, .. } => {
+                    demand.all
+                        || demand
+                            .names
+                            .iter()
+                            .any(|name| !explicit.contains(name.as_str()))
+                }
+                Kind::Import { bindings, .. } => bindings.is_empty(),
+                Kind::TypeOnly => false,
+            })
+            .collect();
+
+        let mut visited = vec![false; keep.len()];
+        loop {
+            for (position, statement) in module.statements.iter().enumerate() {
+                if keep[position] && !visited[position] {
+                    visited[position] = true;
+                    referenced.extend(statement.references.iter().cloned());
+                }
+            }
+            let mut changed = false;
+            for (position, statement) in module.statements.iter().enumerate() {
+                let declares_used = statement
+                    .declares
+                    .iter()
+                    .any(|name| referenced.contains(name));
+                let is_declaration = matches!(
+                    statement.kind,
+                    Kind::Plain | Kind::ExportDeclaration { .. } | Kind::ExportDefault { .. }
+                );
+                if !keep[position] && is_declaration && declares_used {
+                    keep[position] = true;
+                    changed = true;
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+
+        for (position, statement) in module.statements.iter().enumerate() {
+            if let Kind::Import { bindings, .. } = &statement.kind
+                && bindings
+                    .iter()
+                    .any(|binding| referenced.contains(binding.local()))
+            {
+                keep[position] = true;
+            }
+        }
+        keep
+    }
+
+    /// Names referenced by the kept statements of a module
+    fn used_names(&self, index: usize, kept: &[bool]) -> HashSet<String> {
+        let module = &self.modules[index];
+        let mut used = HashSet::new();
+        for (statement, keep) in module.statements.iter().zip(kept) {
+            if *keep {
+                used.extend(statement.references.iter().cloned());
+            }
+            if let Kind::ExportList {
+                specifiers,
+                source: None,
+            } = &statement.kind
+            {
+                used.extend(specifiers.iter().map(|(local, _)| local.clone()));
+            }
+        }
+        if index == 0 {
+            // Entry imports are global bindings, so keep every one of them
+            for statement in &module.statements {
+                if let Kind::Import { bindings, .. } = &statement.kind {
+                    used.extend(bindings.iter().map(|binding| binding.local().to_string()));
+                }
+            }
+        }
+        used
+    }
+
+    /// Rewrite a module's source: module syntax becomes `__bundle_*` calls and
+    /// shaken statements are blanked. Returns the body and its export getters.
+    fn render(&self, index: usize, shaken: &Shaken, counter: &mut usize) -> (String, String) {
+        let module = &self.modules[index];
+        let is_entry = index == 0;
+        let used = self.used_names(index, &shaken.kept[index]);
+        let demand = &shaken.demands[index];
+        let mut edits: Vec<(u32, u32, String)> = Vec::new();
+        let mut getters: Vec<(String, String)> = Vec::new();
+
+        for (statement, keep) in module.statements.iter().zip(&shaken.kept[index]) {
+            let (start, end) = (statement.start, statement.end);
+            if !keep {
+                edits.push((start, end, String::new()));
+                continue;
+            }
+            let mut require = || {
+                let source = statement.kind.source().unwrap_or_default();
+                let id = &self.modules[module.dependencies[source]].id;
+                *counter += 1;
+                (format!("__bundle_m{}", counter), json!(id).to_string())
+            };
+            match &statement.kind {
+                Kind::Plain | Kind::TypeOnly => {}
+                Kind::Import { bindings, .. } => {
+                    let (var, id) = require();
+                    let mut text = if bindings.is_empty() {
+                        format!("__bundle_require({});", id)
+                    } else {
+                        format!("var {} = __bundle_require({});", var, id)
+                    };
+                    let locals: Vec<String> = bindings
+                        .iter()
+                        .filter(|binding| used.contains(binding.local()))
+                        .map(|binding| match binding {
+                            ImportBinding::Named { imported, local } => {
+                                format!("{} = {}[{}]", local, var, json!(imported))
+                            }
+                            ImportBinding::Default(local) => format!("{} = {}.default", local, var),
+                            ImportBinding::Namespace(local) => format!("{} = {}", local, var),
+                        })
+                        .collect();
+                    if !locals.is_empty() {
+                        text.push_str(&format!(" var {};", locals.join(", ")));
+                    }
+                    edits.push((start, end, text));
+                }
+                Kind::ExportDeclaration { body_start } => {
+                    edits.push((start, *body_start, String::new()));
+                    if !is_entry {
+                        getters.extend(
+                            statement
+                                .declares
+                                .iter()
+                                .map(|name| (name.clone(), name.clone())),
+                        );
+                    }
+                }
+                Kind::ExportDefault {
+                    body_start,
+                    body_end,
+                    local,
+                    runtime,
+                } => match local {
+                    Some(local) => {
+                        edits.push((start, *body_start, String::new()));
+                        if !is_entry {
+                            getters.push(("default".to_string(), local.clone()));
+                        }
+                    }
+                    None if *runtime => {
+                        edits.push((start, *body_start, "var __bundle_default = ".to_string()));
+                        edits.push((*body_end, end, ";".to_string()));
+                        if !is_entry {
+                            getters.push(("default".to_string(), "__bundle_default".to_string()));
+                        }
+                    }
+                    None => edits.push((start, *body_start, String::new())),
+                },
+                Kind::ExportList {
+                    specifiers,
+                    source: None,
+                } => {
+                    edits.push((start, end, String::new()));
+                    if !is_entry {
+                        getters.extend(
+                            specifiers
+                                .iter()
+                                .filter(|(_, exported)| demand.wants(exported))
+                                .map(|(local, exported)| (exported.clone(), local.clone())),
+                        );
+                    }
+                }
+                Kind::ExportList { specifiers, .. } => {
+                    let (var, id) = require();
+                    edits.push((
+                        start,
+                        end,
+                        format!("var {} = __bundle_require({});", var, id),
+                    ));
+                    if !is_entry {
+                        getters.extend(
+                            specifiers
+                                .iter()
+                                .filter(|(_, exported)| demand.wants(exported))
+                                .map(|(local, exported)| {
+                                    (exported.clone(), format!("{}[{}]", var, json!(local)))
+                                }),
+                        );
+                    }
+                }
+                Kind::ExportAll { alias, .. } => {
+                    let (var, id) = require();
+                    let text = match alias {
+                        Some(alias) if !is_entry => {
+                            getters.push((alias.clone(), var.clone()));
+                            format!("var {} = __bundle_require({});", var, id)
+                        }
+                        None if !is_entry => {
+                            format!("__bundle_export_star(__exports, __bundle_require({}));", id)
+                        }
+                        _ => format!("__bundle_require({});", id),
+                    };
+                    edits.push((start, end, text));
+                }
+            }
+        }
+
+        let getters = getters
+            .iter()
+            .map(|(name, expression)| {
+                format!(
+                    "__bundle_export(__exports, {}, function () {{ return {}; }});",
+                    json!(name),
+                    expression
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(" ");
+        (apply_edits(&module.source, edits), getters)
+    }
+}
+
+/// A top-level statement, reduced to what bundling needs
+struct TopLevel {
+    start: u32,
+    end: u32,
+    kind: Kind,
+    /// Runtime bindings this statement declares
+    declares: Vec<String>,
+    /// Identifiers this statement refers to
+    references: HashSet<String>,
+    /// Whether the statement can be dropped when nothing uses what it declares
+    removable: bool,
+}
+
+enum Kind {
+    Plain,
+    Import {
+        source: String,
+        bindings: Vec<ImportBinding>,
+    },
+    /// `export <declaration>`, with the declaration starting at `body_start`
+    ExportDeclaration {
+        body_start: u32,
+    },
+    ExportDefault {
+        body_start: u32,
+        body_end: u32,
+        /// Name of a default-exported function or class declaration
+        local: Option<String>,
+        /// False for `export default interface`
+        runtime: bool,
+    },
+    /// `export { local as exported }`, optionally re-exported from `source`
+    ExportList {
+        specifiers: Vec<(String, String)>,
+        source: Option<String>,
+    },
+    ExportAll {
+        source: String,
+        alias: Option<String>,
+    },
+    /// `import type` / `export type`, which emit no code
+    TypeOnly,
+}
+
+impl Kind {
+    fn source(&self) -> Option<&str> {
+        match self {
+            Kind::Import { source, .. }
+            | Kind::ExportList {
+                source: Some(source),
+                ..
+            }
+            | Kind::ExportAll { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+impl TopLevel {
+    /// What a kept import or re-export needs from the module it names
+    fn demand_on_source(&self, used: &HashSet<String>, own: &Demand, module: &Module) -> Demand {
+        let mut demand = Demand::default();
+        match &self.kind {
+            Kind::Import { bindings, .. } => {
+                for binding in bindings.iter().filter(|b| used.contains(b.local())) {
+                    match binding {
+                        ImportBinding::Named { imported, .. } => {
+                            demand.names.insert(imported.clone());
+                        }
+                        ImportBinding::Default(_) => {
+                            demand.names.insert("default".to_string());
+                        }
+                        ImportBinding::Namespace(_) => demand.all = true,
+                    }
+                }
+            }
+            Kind::ExportList { specifiers, .. } => {
+                demand.names.extend(
+                    specifiers
+                        .iter()
+                        .filter(|(_, exported)| own.wants(exported))
+                        .map(|(local, _)| local.clone()),
+                );
+            }
+            Kind::ExportAll { alias: Some(_), .. } => demand.all = true,
+            Kind::ExportAll { alias: None, .. } => {
+                let explicit = module.explicit_exports();
+                demand.all = own.all;
+                demand.names.extend(
+                    own.names
+                        .iter()
+                        .filter(|name| !explicit.contains(name.as_str()))
+                        .cloned(),
+                );
+            }
+            _ => {}
+        }
+        demand
+    }
+}
+
+enum ImportBinding {
+    Named { imported: String, local: String },
+    Default(String),
+    Namespace(String),
+}
+
+impl ImportBinding {
+    fn local(&self) -> &str {
+        match self {
+            ImportBinding::Named { local, .. }
+            | ImportBinding::Default(local)
+            | ImportBinding::Namespace(local) => local,
+        }
+    }
+}
+
+/// Collects the names of identifier references
+#[derive(Default)]
+struct References(HashSet<String>);
+
+impl<'a> Visit<'a> for References {
+    fn visit_identifier_reference(&mut self, it: &IdentifierReference<'a>) {
+        self.0.insert(it.name.to_string());
+    }
+}
+
+/// Collects the names of binding identifiers
+#[derive(Default)]
+pub(crate) struct Bindings(pub(crate) Vec<String>);
+
+impl<'a> Visit<'a> for Bindings {
+    fn visit_binding_identifier(&mut self, it: &BindingIdentifier<'a>) {
+        self.0.push(it.name.to_string());
+    }
+}
+
+/// Parse a module and summarise its top-level statements
+fn analyze(path: &Path, source: &str) -> Result<(Vec<TopLevel>, bool)> {
+    let allocator = Allocator::default();
+    let source_type = SourceType::from_path(path).unwrap_or_default();
+    let parse_result = Parser::new(&allocator, source, source_type).parse();
+    if !parse_result.errors.is_empty() {
+        return Err(diagnostics_error("Parse", path, &parse_result.errors));
+    }
+    let semantic_result = SemanticBuilder::new().build(&parse_result.program);
+    if !semantic_result.errors.is_empty() {
+        return Err(diagnostics_error("Semantic", path, &semantic_result.errors));
+    }
+
+    let statements = parse_result
+        .program
+        .body
+        .iter()
+        .map(|statement| summarize(path, statement))
+        .collect::<Result<Vec<_>>>()?;
+    Ok((statements, source_type.is_jsx()))
+}
+
+fn summarize(path: &Path, statement: &Statement) -> Result<TopLevel> {
+    let span = statement.span();
+    let mut top = TopLevel {
+        start: span.start,
+        end: span.end,
+        kind: Kind::Plain,
+        declares: Vec::new(),
+        references: HashSet::new(),
+        removable: false,
+    };
+
+    let Some(module_declaration) = statement.as_module_declaration() else {
+        if let Some(declaration) = statement.as_declaration() {
+            top.declares = runtime_names(declaration);
+            top.removable = is_removable(declaration);
+        }
+        let mut references = References::default();
+        references.visit_statement(statement);
+        top.references = references.0;
+        return Ok(top);
+    };
+
+    match module_declaration {
+        ModuleDeclaration::ImportDeclaration(import) => {
+            if import.import_kind.is_type() {
+                top.kind = Kind::TypeOnly;
+                return Ok(top);
+            }
+            let mut bindings = Vec::new();
+            for specifier in import.specifiers.iter().flatten() {
+                match specifier {
+                    ImportDeclarationSpecifier::ImportSpecifier(specifier) => {
+                        if !specifier.import_kind.is_type() {
+                            bindings.push(ImportBinding::Named {
+                                imported: specifier.imported.name().to_string(),
+                                local: specifier.local.name.to_string(),
+                            });
+                        }
+                    }
+                    ImportDeclarationSpecifier::ImportDefaultSpecifier(specifier) => {
+                        bindings.push(ImportBinding::Default(specifier.local.name.to_string()));
+                    }
+                    ImportDeclarationSpecifier::ImportNamespaceSpecifier(specifier) => {
+                        bindings.push(ImportBinding::Namespace(specifier.local.name.to_string()));
+                    }
+                }
+            }
+            let only_types = bindings.is_empty()
+                && import
+                    .specifiers
+                    .as_ref()
+                    .is_some_and(|specifiers| !specifiers.is_empty());
+            top.kind = if only_types {
+                Kind::TypeOnly
+            } else {
+                Kind::Import {
+                    source: import.source.value.to_string(),
+                    bindings,
+                }
+            };
+        }
+        ModuleDeclaration::ExportNamedDeclaration(export) => {
+            if export.export_kind.is_type() {
+                top.kind = Kind::TypeOnly;
+                return Ok(top);
+            }
+            if let Some(declaration) = &export.declaration {
+                top.declares = runtime_names(declaration);
+                top.removable = is_removable(declaration);
+                let mut references = References::default();
+                references.visit_declaration(declaration);
+                top.references = references.0;
+                top.kind = Kind::ExportDeclaration {
+                    body_start: declaration.span().start,
+                };
+            } else {
+                let specifiers = export
+                    .specifiers
+                    .iter()
+                    .filter(|specifier| !specifier.export_kind.is_type())
+                    .map(|specifier| {
+                        (
+                            specifier.local.name().to_string(),
+                            specifier.exported.name().to_string(),
+                        )
+                    })
+                    .collect();
+                top.kind = Kind::ExportList {
+                    specifiers,
+                    source: export
+                        .source
+                        .as_ref()
+                        .map(|source| source.value.to_string()),
+                };
+            }
+        }
+        ModuleDeclaration::ExportDefaultDeclaration(export) => {
+            let body = export.declaration.span();
+            let (local, runtime, removable) = match &export.declaration {
+                ExportDefaultDeclarationKind::FunctionDeclaration(function) => (
+                    function.id.as_ref().map(|id| id.name.to_string()),
+                    true,
+                    true,
+                ),
+                ExportDefaultDeclarationKind::ClassDeclaration(class) => {
+                    (class.id.as_ref().map(|id| id.name.to_string()), true, true)
+                }
+                ExportDefaultDeclarationKind::TSInterfaceDeclaration(_) => (None, false, true),
+                other => (
+                    None,
+                    true,
+                    other.as_expression().is_some_and(is_pure_expression),
+                ),
+            };
+            let mut references = References::default();
+            references.visit_export_default_declaration_kind(&export.declaration);
+            top.references = references.0;
+            top.declares = local.iter().cloned().collect();
+            top.removable = removable;
+            top.kind = Kind::ExportDefault {
+                body_start: body.start,
+                body_end: body.end,
+                local,
+                runtime,
+            };
+        }
+        ModuleDeclaration::ExportAllDeclaration(export) => {
+            top.kind = if export.export_kind.is_type() {
+                Kind::TypeOnly
+            } else {
+                Kind::ExportAll {
+                    source: export.source.value.to_string(),
+                    alias: export.exported.as_ref().map(|name| name.name().to_string()),
+                }
+            };
+        }
+        _ => {
+            return Err(BamlRtError::InvalidArgument(format!(
+                "Unsupported module syntax in {}: use ES `import`/`export` statements",
+                path.display()
+            )));
+        }
+    }
+    Ok(top)
+}
+
+/// Runtime bindings introduced by a declaration; type-only declarations have none
+fn runtime_names(declaration: &Declaration) -> Vec<String> {
+    match declaration {
+        Declaration::VariableDeclaration(variable) => {
+            let mut bindings = Bindings::default();
+            for declarator in &variable.declarations {
+                bindings.visit_binding_pattern(&declarator.id);
+            }
+            bindings.0
+        }
+        Declaration::FunctionDeclaration(function) => {
+            function.id.iter().map(|id| id.name.to_string()).collect()
+        }
+        Declaration::ClassDeclaration(class) => {
+            class.id.iter().map(|id| id.name.to_string()).collect()
+        }
+        Declaration::TSEnumDeclaration(enumeration) => vec![enumeration.id.name.to_string()],
+        _ => Vec::new(),
+    }
+}
+
+fn is_removable(declaration: &Declaration) -> bool {
+    match declaration {
+        Declaration::FunctionDeclaration(_)
+        | Declaration::ClassDeclaration(_)
+        | Declaration::TSEnumDeclaration(_)
+        | Declaration::TSTypeAliasDeclaration(_)
+        | Declaration::TSInterfaceDeclaration(_) => true,
+        Declaration::VariableDeclaration(variable) => variable
+            .declarations
+            .iter()
+            .all(|declarator| declarator.init.as_ref().is_none_or(is_pure_expression)),
+        _ => false,
+    }
+}
+
+fn is_pure_expression(expression: &Expression) -> bool {
+    matches!(
+        expression.get_inner_expression(),
+        Expression::BooleanLiteral(_)
+            | Expression::NullLiteral(_)
+            | Expression::NumericLiteral(_)
+            | Expression::BigIntLiteral(_)
+            | Expression::StringLiteral(_)
+            | Expression::Identifier(_)
+            | Expression::FunctionExpression(_)
+            | Expression::ArrowFunctionExpression(_)
+    )
+}
+
+fn diagnostics_error<E: std::fmt::Debug>(stage: &str, path: &Path, errors: &[E]) -> BamlRtError {
+    let errors: Vec<String> = errors.iter().map(|e| format!("{:?}", e)).collect();
+    BamlRtError::InvalidArgument(format!(
+        "{} error in {}: {}",
+        stage,
+        path.display(),
+        errors.join(", ")
+    ))
+}
+
+/// Apply non-overlapping `(start, end, replacement)` edits, keeping the line
+/// count of every replaced range so source map lines still line up
+fn apply_edits(source: &str, mut edits: Vec<(u32, u32, String)>) -> String {
+    edits.sort_by_key(|(start, _, _)| *start);
+    let mut output = String::with_capacity(source.len());
+    let mut cursor = 0;
+    for (start, end, replacement) in edits {
+        let (start, end) = (start as usize, end as usize);
+        output.push_str(&source[cursor..start]);
+        output.push_str(&replacement);
+        output.extend(std::iter::repeat_n(
+            '\n',
+            source[start..end].matches('\n').count(),
+        ));
+        cursor = end;
+    }
+    output.push_str(&source[cursor..]);
+    if !output.ends_with('\n') {
+        output.push('\n');
+    }
+    output
+}
+
+/// Resolve a relative import against the importing file
+fn resolve_import(importer: &Path, specifier: &str) -> Result<PathBuf> {
+    if !is_relative(specifier) {
+        return Err(BamlRtError::InvalidArgument(format!(
+            "Cannot bundle import '{}' in {}: only relative imports are supported",
+            specifier,
+            importer.display()
+        )));
+    }
+    let base = normalize(&importer.parent().unwrap_or(Path::new("")).join(specifier));
+    resolve_file(&base).ok_or_else(|| {
+        BamlRtError::InvalidArgument(format!(
+            "Cannot resolve import '{}' in {}",
+            specifier,
+            importer.display()
+        ))
+    })
+}
+
+pub(crate) fn is_relative(specifier: &str) -> bool {
+    specifier.starts_with("./") || specifier.starts_with("../")
+}
+
+/// The file `base` names, trying TypeScript's extension and index rules
+pub(crate) fn resolve_file(base: &Path) -> Option<PathBuf> {
+    let mut candidates = vec![base.to_path_buf()];
+    // `./util.js` may name `util.ts`, as TypeScript's own resolution allows
+    if matches!(
+        base.extension().and_then(|ext| ext.to_str()),
+        Some("js" | "jsx")
+    ) {
+        candidates.extend(["ts", "tsx"].iter().map(|ext| base.with_extension(ext)));
+    }
+    for ext in RESOLVE_EXTENSIONS {
+        candidates.push(PathBuf::from(format!("{}.{}", base.display(), ext)));
+    }
+    for ext in RESOLVE_EXTENSIONS {
+        candidates.push(base.join(format!("index.{}", ext)));
+    }
+
+    candidates.into_iter().find(|candidate| candidate.is_file())
+}
+
+/// Lexically normalise `.` and `..` components
+pub(crate) fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if !normalized.pop() {
+                    normalized.push("..");
+                }
+            }
+            other => normalized.push(other.as_os_str()),
+        }
+    }
+    normalized
+}
+
+/// Lines of the concatenated source that come from a module body
+struct Segment {
+    first_line: u32,
+    line_count: u32,
+    module: usize,
+}
+
+#[derive(Default)]
+struct BundleWriter {
+    text: String,
+    lines: u32,
+    segments: Vec<Segment>,
+}
+
+impl BundleWriter {
+    fn push_generated(&mut self, text: &str) {
+        self.text.push_str(text);
+        self.lines += text.matches('\n').count() as u32;
+    }
+
+    fn push_module(&mut self, module: usize, body: &str) {
+        let line_count = body.matches('\n').count() as u32;
+        self.segments.push(Segment {
+            first_line: self.lines,
+            line_count,
+            module,
+        });
+        self.text.push_str(body);
+        self.lines += line_count;
+    }
+}
+
+/// Strip types and emit JavaScript with a source map over `source`
+fn transpile(
+    entry: &Path,
+    source: &str,
+    jsx: bool,
+    output_file_name: &str,
+) -> Result<(String, String)> {
+    let allocator = Allocator::default();
+    let source_type = if jsx {
+        SourceType::tsx()
+    } else {
+        SourceType::ts()
+    };
+    let parse_result = Parser::new(&allocator, source, source_type).parse();
+    if !parse_result.errors.is_empty() {
+        return Err(diagnostics_error("Parse", entry, &parse_result.errors));
+    }
+
+    let mut program = parse_result.program;
+    let semantic_result = SemanticBuilder::new()
+        .with_excess_capacity(2.0)
+        .build(&program);
+    if !semantic_result.errors.is_empty() {
+        return Err(diagnostics_error(
+            "Semantic",
+            entry,
+            &semantic_result.errors,
+        ));
+    }
+
+    let scoping = semantic_result.semantic.into_scoping();
+    let mut transform_options = TransformOptions::default();
+    transform_options.helper_loader.mode = HelperLoaderMode::External;
+    let transform_result = Transformer::new(&allocator, entry, &transform_options)
+        .build_with_scoping(scoping, &mut program);
+    if !transform_result.errors.is_empty() {
+        return Err(diagnostics_error(
+            "Transform",
+            entry,
+            &transform_result.errors,
+        ));
+    }
+
+    let generated = Codegen::new()
+        .with_options(CodegenOptions {
+            source_map_path: Some(PathBuf::from(output_file_name)),
+            ..CodegenOptions::default()
+        })
+        .build(&program);
+    let map = generated
+        .map
+        .map(|map| map.to_json_string())
+        .ok_or_else(|| {
+            BamlRtError::InvalidArgument(format!("No source map generated for {}", entry.display()))
+        })?;
+    Ok((generated.code, map))
+}
+
+/// Point a source map over the concatenated source back at the original modules
+fn remap_source_map(
+    map: &str,
+    segments: &[Segment],
+    graph: &ModuleGraph,
+    output_file_name: &str,
+) -> Result<String> {
+    let map: Value = serde_json::from_str(map)?;
+    let mappings = map.get("mappings").and_then(Value::as_str).unwrap_or("");
+
+    let remapped: Vec<Vec<[i64; 4]>> = decode_mappings(mappings)
+        .into_iter()
+        .map(|line| {
+            line.into_iter()
+                .filter_map(|[column, _, source_line, source_column]| {
+                    let segment = segments.iter().find(|segment| {
+                        (segment.first_line..segment.first_line + segment.line_count)
+                            .contains(&(source_line as u32))
+                    })?;
+                    Some([
+                        column,
+                        segment.module as i64,
+                        source_line - segment.first_line as i64,
+                        source_column,
+                    ])
+                })
+                .collect()
+        })
+        .collect();
+
+    let source_map = json!({
+        "version": 3,
+        "file": output_file_name,
+        "sources": graph.modules.iter().map(|module| module.id.clone()).collect::<Vec<_>>(),
+        "sourcesContent": graph.modules.iter().map(|module| module.source.clone()).collect::<Vec<_>>(),
+        "names": [],
+        "mappings": encode_mappings(&remapped),
+    });
+    Ok(serde_json::to_string(&source_map)?)
+}
+
+const BASE64: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+fn encode_mappings(lines: &[Vec<[i64; 4]>]) -> String {
+    let mut previous = [0i64; 4];
+    lines
+        .iter()
+        .map(|line| {
+            previous[0] = 0;
+            line.iter()
+                .map(|segment| {
+                    let mut encoded = String::new();
+                    for (slot, value) in previous.iter_mut().zip(segment) {
+                        encode_vlq(value - *slot, &mut encoded);
+                        *slot = *value;
+                    }
+                    encoded
+                })
+                .collect::<Vec<_>>()
+                .join(",")
+        })
+        .collect::<Vec<_>>()
+        .join(";")
+}
+
+fn encode_vlq(value: i64, out: &mut String) {
+    let mut vlq = if value < 0 {
+        ((-value) << 1) | 1
+    } else {
+        value << 1
+    };
+    loop {
+        let mut digit = vlq & 31;
+        vlq >>= 5;
+        if vlq > 0 {
+            digit |= 32;
+        }
+        out.push(BASE64[digit as usize] as char);
+        if vlq == 0 {
+            break;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::filesystem::StdFileSystem;
+
+    fn write(dir: &Path, relative: &str, contents: &str) {
+        let path = dir.join(relative);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, contents).unwrap();
+    }
+
+    #[test]
+    fn bundles_relative_imports_and_drops_unused_exports() {
+        let dir = tempfile::TempDir::new().unwrap();
+        write(
+            dir.path(),
+            "index.ts",
+            "import { add } from \"./math\";\nimport label from \"./lib/label.ts\";\n\nfunction total(values: number[]): string {\n  return label(values.reduce(add, 0));\n}\n\nglobalThis.total = total;\n",
+        );
+        write(
+            dir.path(),
+            "math.ts",
+            "export function add(a: number, b: number): number {\n  return a + b;\n}\n\nexport function unusedHelper(): number {\n  return 42;\n}\n",
+        );
+        write(
+            dir.path(),
+            "lib/label.ts",
+            "export type Label = string;\nexport default function label(value: number): Label {\n  return `total: ${value}`;\n}\n",
+        );
+
+        let bundle = Bundler::new(&StdFileSystem, dir.path())
+            .bundle(&dir.path().join("index.ts"), "index.js")
+            .unwrap();
+
+        assert!(bundle.code.contains("__bundle_define(\"math.ts\""));
+        assert!(bundle.code.contains("__bundle_define(\"lib/label.ts\""));
+        assert!(bundle.code.contains("function total(values)"));
+        assert!(bundle.code.contains("function add("));
+        assert!(!bundle.code.contains("unusedHelper"));
+        assert!(!bundle.code.contains("import "));
+        assert!(bundle.code.ends_with("//# sourceMappingURL=index.js.map\n"));
+
+        let map: Value = serde_json::from_str(&bundle.source_map).unwrap();
+        assert_eq!(
+            map["sources"],
+            json!(["index.ts", "math.ts", "lib/label.ts"])
+        );
+        assert!(!map["mappings"].as_str().unwrap().is_empty());
+    }
+
+    #[test]
+    fn allowed_npm_packages_are_bundled_from_node_modules() {
+        let dir = tempfile::TempDir::new().unwrap();
+        write(
+            dir.path(),
+            "index.ts",
+            "import { shout } from \"tiny-esm\";\nglobalThis.greet = (name: string) => shout(name);\n",
+        );
+        write(
+            dir.path(),
+            "node_modules/tiny-esm/package.json",
+            r#"{ "exports": { ".": { "require": "./index.cjs", "import": "./index.mjs" } } }"#,
+        );
+        write(
+            dir.path(),
+            "node_modules/tiny-esm/index.mjs",
+            "export function shout(s) { return s.toUpperCase(); }\n",
+        );
+        let policy = |bytes| {
+            NpmPolicy::from_manifest(&baml_rt_core::manifest::ManifestBundle {
+                dependencies: vec!["tiny-esm".to_string()],
+                max_dependency_bytes: bytes,
+            })
+        };
+
+        let error = Bundler::new(&StdFileSystem, dir.path())
+            .bundle(&dir.path().join("index.ts"), "index.js")
+            .unwrap_err();
+        assert!(error.to_string().contains("bundle.dependencies"));
+
+        let bundle = Bundler::new(&StdFileSystem, dir.path())
+            .with_npm_policy(policy(1024))
+            .bundle(&dir.path().join("index.ts"), "index.js")
+            .unwrap();
+        assert!(
+            bundle
+                .code
+                .contains("__bundle_define(\"node_modules/tiny-esm/index.mjs\"")
+        );
+        assert!(bundle.code.contains("toUpperCase"));
+
+        let error = Bundler::new(&StdFileSystem, dir.path())
+            .with_npm_policy(policy(8))
+            .bundle(&dir.path().join("index.ts"), "index.js")
+            .unwrap_err();
+        assert!(error.to_string().contains("max_dependency_bytes"));
+    }
+
+    #[test]
+    fn single_file_entry_is_not_wrapped() {
+        let dir = tempfile::TempDir::new().unwrap();
+        write(
+            dir.path(),
+            "index.ts",
+            "function greet(name: string): string {\n  return `hi ${name}`;\n}\nglobalThis.greet = greet;\n",
+        );
+
+        let bundle = Bundler::new(&StdFileSystem, dir.path())
+            .bundle(&dir.path().join("index.ts"), "index.js")
+            .unwrap();
+
+        assert!(!bundle.code.contains("__bundle_"));
+        assert!(bundle.code.contains("function greet(name)"));
+    }
+
+    #[test]
+    fn rejects_package_imports() {
+        let dir = tempfile::TempDir::new().unwrap();
+        write(dir.path(), "index.ts", "import fs from \"fs\";\n");
+
+        let error = Bundler::new(&StdFileSystem, dir.path())
+            .bundle(&dir.path().join("index.ts"), "index.js")
+            .unwrap_err();
+        assert!(error.to_string().contains("only relative imports"));
+    }
+
+    #[test]
+    fn vlq_round_trips() {
+        let lines = vec![
+            vec![[0, 0, 0, 0], [4, 1, 12, 3]],
+            vec![],
+            vec![[2, 0, 3, 1]],
+        ];
+        assert_eq!(decode_mappings(&encode_mappings(&lines)), lines);
+    }
+}
@@ -0,0 +1,282 @@
+//! Schema checks that run without packaging
+//!
+//! Validates an agent's `baml_src` with the BAML compiler and reports every
+//! diagnostic with its file, line, and column. Once the schema compiles, calls
+//! to `map_baml_variant_to_tool` in the agent's sources are checked against
+//! the schema: each variant they name must be a BAML class, or the tool call
+//! could never be routed. Used by the `check` subcommand.
+
+use crate::builder::traits::FileSystem;
+use crate::builder::types::AgentDir;
+use baml_rt_core::{BamlRtError, Result};
+use internal_baml_core::feature_flags::FeatureFlags;
+use internal_baml_core::internal_baml_diagnostics::{SourceFile, Span};
+use regex::Regex;
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+/// Calls naming a tool union variant by string literal
+static VARIANT_MAPPING: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"map_baml_variant_to_tool\s*\(\s*"([^"]+)""#).expect("valid variant pattern")
+});
+
+/// Directories holding generated or third-party code, never scanned
+const SKIPPED_DIRS: [&str; 5] = ["node_modules", "dist", "target", "baml_src", ".git"];
+
+/// How serious a diagnostic is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Error => f.write_str("error"),
+            Severity::Warning => f.write_str("warning"),
+        }
+    }
+}
+
+/// A problem found in an agent's sources, at a 1-based line and column
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaDiagnostic {
+    pub severity: Severity,
+    /// Path relative to the agent directory
+    pub file: PathBuf,
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl fmt::Display for SchemaDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}:{}: {}: {}",
+            self.file.display(),
+            self.line,
+            self.column,
+            self.severity,
+            self.message
+        )
+    }
+}
+
+/// Checks an agent's BAML schema and the tool variants its code maps
+pub struct SchemaChecker<FS> {
+    filesystem: FS,
+}
+
+impl<FS: FileSystem> SchemaChecker<FS> {
+    pub fn new(filesystem: FS) -> Self {
+        Self { filesystem }
+    }
+
+    /// Every diagnostic for the agent in `agent_dir`, in file order
+    pub fn check(&self, agent_dir: &AgentDir) -> Result<Vec<SchemaDiagnostic>> {
+        let baml_src = agent_dir.baml_src();
+        if !baml_src.is_dir() {
+            return Err(BamlRtError::InvalidArgument(format!(
+                "No baml_src directory in {}",
+                agent_dir
+            )));
+        }
+
+        let mut baml_files = Vec::new();
+        collect_files(&baml_src, &["baml"], &[], &mut baml_files)?;
+        let mut sources = Vec::with_capacity(baml_files.len());
+        for path in baml_files {
+            let content = self.filesystem.read_to_string(&path)?;
+            sources.push(SourceFile::from((path, content)));
+        }
+
+        let schema = internal_baml_core::validate(&baml_src, sources, FeatureFlags::default());
+        let mut diagnostics: Vec<SchemaDiagnostic> = schema
+            .diagnostics
+            .errors()
+            .iter()
+            .map(|error| baml_diagnostic(agent_dir, Severity::Error, error.span(), error.message()))
+            .chain(schema.diagnostics.warnings().iter().map(|warning| {
+                baml_diagnostic(
+                    agent_dir,
+                    Severity::Warning,
+                    warning.span(),
+                    warning.message(),
+                )
+            }))
+            .collect();
+        if diagnostics.iter().any(|d| d.severity == Severity::Error) {
+            return Ok(diagnostics);
+        }
+
+        let runtime = baml_runtime::BamlRuntime::from_directory(
+            &baml_src,
+            HashMap::new(),
+            FeatureFlags::default(),
+        )
+        .map_err(|e| BamlRtError::RuntimeLoadFailed { source: e })?;
+        let classes: BTreeSet<String> = runtime
+            .ir()
+            .walk_classes()
+            .map(|class| class.name().to_string())
+            .collect();
+        diagnostics.extend(self.check_variant_mappings(agent_dir, &classes)?);
+        Ok(diagnostics)
+    }
+
+    /// Diagnostics for `map_baml_variant_to_tool` calls naming no class
+    fn check_variant_mappings(
+        &self,
+        agent_dir: &AgentDir,
+        classes: &BTreeSet<String>,
+    ) -> Result<Vec<SchemaDiagnostic>> {
+        let mut files = Vec::new();
+        collect_files(
+            agent_dir.as_path(),
+            &["rs", "ts", "tsx", "js", "jsx"],
+            &SKIPPED_DIRS,
+            &mut files,
+        )?;
+
+        let mut diagnostics = Vec::new();
+        for path in files {
+            let content = self.filesystem.read_to_string(&path)?;
+            for captures in VARIANT_MAPPING.captures_iter(&content) {
+                let variant = &captures[1];
+                if classes.contains(variant) {
+                    continue;
+                }
+                let offset = captures.get(1).map_or(0, |m| m.start());
+                let (line, column) = line_and_column(&content, offset);
+                diagnostics.push(SchemaDiagnostic {
+                    severity: Severity::Error,
+                    file: relative_to(agent_dir, &path),
+                    line,
+                    column,
+                    message: format!(
+                        "tool variant '{}' is not a class in baml_src (classes: {})",
+                        variant,
+                        classes.iter().cloned().collect::<Vec<_>>().join(", ")
+                    ),
+                });
+            }
+        }
+        Ok(diagnostics)
+    }
+}
+
+fn baml_diagnostic(
+    agent_dir: &AgentDir,
+    severity: Severity,
+    span: &Span,
+    message: &str,
+) -> SchemaDiagnostic {
+    let ((line, column), _) = span.line_and_column();
+    SchemaDiagnostic {
+        severity,
+        file: relative_to(agent_dir, &span.file.path_buf()),
+        line: line + 1,
+        column: column + 1,
+        message: message.to_string(),
+    }
+}
+
+fn relative_to(agent_dir: &AgentDir, path: &Path) -> PathBuf {
+    path.strip_prefix(agent_dir.as_path())
+        .unwrap_or(path)
+        .to_path_buf()
+}
+
+/// 1-based line and column of the byte at `offset`
+pub(crate) fn line_and_column(content: &str, offset: usize) -> (usize, usize) {
+    let before = &content[..offset];
+    let line = before.matches('\n').count() + 1;
+    let column = before
+        .rfind('\n')
+        .map_or(offset, |newline| offset - newline - 1)
+        + 1;
+    (line, column)
+}
+
+/// Files under `dir` with one of `extensions`, skipping `skipped` directories,
+/// sorted by path
+fn collect_files(
+    dir: &Path,
+    extensions: &[&str],
+    skipped: &[&str],
+    files: &mut Vec<PathBuf>,
+) -> Result<()> {
+    let mut entries = std::fs::read_dir(dir)
+        .map_err(BamlRtError::Io)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<std::io::Result<Vec<_>>>()
+        .map_err(BamlRtError::Io)?;
+    entries.sort();
+    for path in entries {
+        if path.is_dir() {
+            let skip = path
+                .file_name()
+                .is_some_and(|name| skipped.iter().any(|skipped| name == *skipped));
+            if !skip {
+                collect_files(&path, extensions, skipped, files)?;
+            }
+        } else if path
+            .extension()
+            .is_some_and(|ext| extensions.iter().any(|wanted| ext == *wanted))
+        {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn offsets_map_to_one_based_lines_and_columns() {
+        let content = "first\nsecond line\n  third";
+        assert_eq!(line_and_column(content, 0), (1, 1));
+        assert_eq!(line_and_column(content, 6), (2, 1));
+        assert_eq!(line_and_column(content, 13), (2, 8));
+        assert_eq!(line_and_column(content, 20), (3, 3));
+    }
+
+    #[test]
+    fn variant_mappings_are_found_in_rust_and_typescript() {
+        let source = r#"
+            manager.map_baml_variant_to_tool("WeatherTool", "get_weather");
+            manager.map_baml_variant_to_tool(
+                "CalculatorTool",
+                "calculate",
+            );
+        "#;
+        let variants: Vec<&str> = VARIANT_MAPPING
+            .captures_iter(source)
+            .map(|captures| captures.get(1).unwrap().as_str())
+            .collect();
+        assert_eq!(variants, ["WeatherTool", "CalculatorTool"]);
+    }
+
+    #[test]
+    fn diagnostics_render_as_file_line_column() {
+        let diagnostic = SchemaDiagnostic {
+            severity: Severity::Error,
+            file: PathBuf::from("baml_src/main.baml"),
+            line: 3,
+            column: 7,
+            message: "Type `Strin` does not exist".to_string(),
+        };
+        assert_eq!(
+            diagnostic.to_string(),
+            "baml_src/main.baml:3:7: error: Type `Strin` does not exist"
+        );
+    }
+}
@@ -1,12 +1,21 @@
 //! Compiler implementations for BAML and TypeScript
 
+use crate::builder::bundler::Bundler;
+use crate::builder::declarations::BamlDeclarations;
+use crate::builder::npm::NpmPolicy;
 use crate::builder::traits::{FileSystem, TypeGenerator, TypeScriptCompiler};
 use crate::builder::types::BuildDir;
 use baml_rt_core::{BamlRtError, Result};
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 /// TypeScript compiler using OXC
+///
+/// When the source directory has an `index.ts` (or `index.tsx`), it is bundled
+/// with everything it imports into `dist/index.js`. Otherwise every file is
+/// bundled as its own entry, which is how test suites are compiled. Each
+/// output gets a `.js.map` source map next to it. npm packages are bundled
+/// when the manifest beside the source directory allows them.
 pub struct OxcTypeScriptCompiler<FS> {
     filesystem: FS,
 }
@@ -15,6 +24,22 @@ impl<FS: FileSystem> OxcTypeScriptCompiler<FS> {
     pub fn new(filesystem: FS) -> Self {
         Self { filesystem }
     }
+
+    fn entry_points(&self, src_dir: &Path) -> Result<Vec<PathBuf>> {
+        let index = ["index.ts", "index.tsx"]
+            .iter()
+            .map(|name| src_dir.join(name))
+            .find(|path| path.is_file());
+        if let Some(index) = index {
+            return Ok(vec![index]);
+        }
+
+        let mut files = Vec::new();
+        self.filesystem.collect_ts_files(src_dir, &mut files)?;
+        files.retain(|path| !path.to_string_lossy().ends_with(".d.ts"));
+        files.sort();
+        Ok(files)
+    }
 }
 
 #[async_trait::async_trait]
@@ -22,86 +47,29 @@ impl<FS: FileSystem> TypeScriptCompiler for OxcTypeScriptCompiler<FS> {
     async fn compile(&self, src_dir: &Path, dist_dir: &Path) -> Result<()> {
         self.filesystem.create_dir_all(dist_dir)?;
 
-        let mut files = Vec::new();
-        self.filesystem.collect_ts_files(src_dir, &mut files)?;
-
-        use oxc_allocator::Allocator;
-        use oxc_codegen::Codegen;
-        use oxc_parser::Parser;
-        use oxc_semantic::SemanticBuilder;
-        use oxc_transformer::{HelperLoaderMode, TransformOptions, Transformer};
-
-        for file_path in files {
-            let content = self.filesystem.read_to_string(&file_path)?;
-
-            let allocator = Allocator::default();
-            let source_type = oxc_span::SourceType::from_path(&file_path)
-                .unwrap_or_else(|_| oxc_span::SourceType::default());
-            let parser = Parser::new(&allocator, &content, source_type);
-            let parse_result = parser.parse();
-
-            if !parse_result.errors.is_empty() {
-                let errors: Vec<String> = parse_result
-                    .errors
-                    .iter()
-                    .map(|e| format!("{:?}", e))
-                    .collect();
-                return Err(BamlRtError::InvalidArgument(format!(
-                    "Parse error in {}: {}",
-                    file_path.display(),
-                    errors.join(", ")
-                )));
-            }
-
-            let mut program = parse_result.program;
-            let semantic_result = SemanticBuilder::new()
-                .with_excess_capacity(2.0)
-                .build(&program);
-            if !semantic_result.errors.is_empty() {
-                let errors: Vec<String> = semantic_result
-                    .errors
-                    .iter()
-                    .map(|e| format!("{:?}", e))
-                    .collect();
-                return Err(BamlRtError::InvalidArgument(format!(
-                    "Semantic error in {}: {}",
-                    file_path.display(),
-                    errors.join(", ")
-                )));
-            }
-
-            let scoping = semantic_result.semantic.into_scoping();
-            let mut transform_options = TransformOptions::default();
-            transform_options.helper_loader.mode = HelperLoaderMode::External;
-            let transform_result = Transformer::new(&allocator, &file_path, &transform_options)
-                .build_with_scoping(scoping, &mut program);
-            if !transform_result.errors.is_empty() {
-                let errors: Vec<String> = transform_result
-                    .errors
-                    .iter()
-                    .map(|e| format!("{:?}", e))
-                    .collect();
-                return Err(BamlRtError::InvalidArgument(format!(
-                    "Transform error in {}: {}",
-                    file_path.display(),
-                    errors.join(", ")
-                )));
-            }
-
-            let js_code = Codegen::new().build(&program).code;
-            let relative_path = file_path.strip_prefix(src_dir).map_err(|_| {
+        let bundler = Bundler::new(&self.filesystem, src_dir)
+            .with_npm_policy(NpmPolicy::for_sources(src_dir)?);
+        for entry in self.entry_points(src_dir)? {
+            let relative_path = entry.strip_prefix(src_dir).map_err(|_| {
                 BamlRtError::InvalidArgument(format!(
                     "File {} is not under src directory",
-                    file_path.display()
+                    entry.display()
                 ))
             })?;
 
             let output_path = dist_dir.join(relative_path).with_extension("js");
+            let output_name = output_path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            let bundle = bundler.bundle(&entry, &output_name)?;
+
             if let Some(parent) = output_path.parent() {
                 self.filesystem.create_dir_all(parent)?;
             }
-
-            self.filesystem.write_string(&output_path, &js_code)?;
+            self.filesystem.write_string(&output_path, &bundle.code)?;
+            self.filesystem
+                .write_string(&output_path.with_extension("js.map"), &bundle.source_map)?;
         }
 
         Ok(())
@@ -109,6 +77,9 @@ impl<FS: FileSystem> TypeScriptCompiler for OxcTypeScriptCompiler<FS> {
 }
 
 /// Type generator for runtime declarations
+///
+/// Emits `dist/baml.d.ts` with typed signatures for every BAML function,
+/// its `<Name>Stream` variant, and the classes and enums they reference.
 pub struct RuntimeTypeGenerator;
 
 impl RuntimeTypeGenerator {
@@ -129,39 +100,17 @@ impl TypeGenerator for RuntimeTypeGenerator {
         use baml_runtime::BamlRuntime;
         use std::collections::HashMap;
 
-        // Load BAML runtime to discover functions
+        // Load BAML runtime to access the schema IR
         let env_vars: HashMap<String, String> = HashMap::new();
         let feature_flags = internal_baml_core::feature_flags::FeatureFlags::default();
 
         let runtime = BamlRuntime::from_directory(baml_src, env_vars, feature_flags)
             .map_err(|e| BamlRtError::RuntimeLoadFailed { source: e })?;
 
-        // Get function names from runtime
-        let function_names: Vec<String> = runtime.function_names().map(|s| s.to_string()).collect();
-
-        // Generate type declarations
-        let mut declarations =
-            String::from("// TypeScript declarations for BAML runtime host functions\n");
-        declarations.push_str("// This file is auto-generated - do not edit manually\n");
-        declarations.push_str("// Generated from BAML runtime\n\n");
-
-        // Generate function declarations - using any for now since we don't have full type info
-        // TypeScript will infer types at runtime through our QuickJS bridge
-        for function_name in &function_names {
-            declarations.push_str(&format!(
-                "/**\n * {} BAML function\n */\ndeclare function {}(args?: Record<string, any>): Promise<any>;\n\n",
-                function_name, function_name
-            ));
-        }
-
-        // Add invokeTool declaration
-        declarations.push_str("/**\n");
-        declarations.push_str(" * Dynamically invoke a tool by name.\n");
-        declarations
-            .push_str(" * Works for both Rust-registered tools and JavaScript-registered tools.\n");
-        declarations.push_str(" */\ndeclare function invokeTool(toolName: string, args: Record<string, any>): Promise<any>;\n\n");
+        // Lower the IR into declarations matching what the QuickJS bridge registers
+        let declarations = BamlDeclarations::from_ir(runtime.ir()).render();
 
-        let output_path = build_dir.join("dist").join("baml-runtime.d.ts");
+        let output_path = build_dir.join("dist").join("baml.d.ts");
         if let Some(parent) = output_path.parent() {
             fs::create_dir_all(parent).map_err(BamlRtError::Io)?;
         }
@@ -0,0 +1,423 @@
+//! TypeScript declaration model for BAML schemas
+//!
+//! Lowers the BAML intermediate representation into a small TypeScript type
+//! model and renders it as a `baml.d.ts` file. The rendered declarations mirror
+//! the host functions registered by the QuickJS bridge: every BAML function is
+//! exposed as a global async function taking either a single argument object
+//! or its parameters by position, and a `<Name>Stream` variant resolving to
+//! the list of partial results.
+
+use internal_baml_core::ir::FieldType;
+use internal_baml_core::ir::repr::IntermediateRepr;
+use std::fmt::Write as _;
+
+/// A TypeScript type expression
+#[derive(Debug, Clone, PartialEq)]
+pub enum TsType {
+    String,
+    Number,
+    Boolean,
+    Null,
+    /// Fallback for BAML types without a TypeScript equivalent
+    Unknown,
+    /// Reference to a generated class interface or enum alias
+    Named(String),
+    /// Literal type, already rendered (e.g. `"ok"`, `42`, `true`)
+    Literal(String),
+    Array(Box<TsType>),
+    Record(Box<TsType>),
+    Tuple(Vec<TsType>),
+    Union(Vec<TsType>),
+}
+
+impl TsType {
+    fn render(&self) -> String {
+        match self {
+            TsType::String => "string".to_string(),
+            TsType::Number => "number".to_string(),
+            TsType::Boolean => "boolean".to_string(),
+            TsType::Null => "null".to_string(),
+            TsType::Unknown => "unknown".to_string(),
+            TsType::Named(name) => name.clone(),
+            TsType::Literal(literal) => literal.clone(),
+            TsType::Array(inner) => match inner.as_ref() {
+                TsType::Union(_) => format!("({})[]", inner.render()),
+                _ => format!("{}[]", inner.render()),
+            },
+            TsType::Record(value) => format!("Record<string, {}>", value.render()),
+            TsType::Tuple(items) => format!(
+                "[{}]",
+                items
+                    .iter()
+                    .map(TsType::render)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            TsType::Union(items) => items
+                .iter()
+                .map(TsType::render)
+                .collect::<Vec<_>>()
+                .join(" | "),
+        }
+    }
+
+    fn is_nullable(&self) -> bool {
+        match self {
+            TsType::Null => true,
+            TsType::Union(items) => items.iter().any(TsType::is_nullable),
+            _ => false,
+        }
+    }
+}
+
+/// A named, typed field of a class or function argument object
+#[derive(Debug, Clone, PartialEq)]
+pub struct TsField {
+    pub name: String,
+    pub ty: TsType,
+}
+
+impl TsField {
+    fn render(&self) -> String {
+        let optional = if self.ty.is_nullable() { "?" } else { "" };
+        format!(
+            "{}{}: {};",
+            quote_property(&self.name),
+            optional,
+            self.ty.render()
+        )
+    }
+}
+
+/// Declaration of a BAML function exposed to JavaScript
+#[derive(Debug, Clone, PartialEq)]
+pub struct TsFunction {
+    pub name: String,
+    pub params: Vec<TsField>,
+    pub output: TsType,
+}
+
+impl TsFunction {
+    /// The parameter list of the positional form
+    ///
+    /// A nullable parameter may be omitted only when every parameter after it
+    /// may be omitted too. Names that are not identifiers become `argN`.
+    fn render_positional_params(&self) -> String {
+        let mut omittable = true;
+        let mut rendered: Vec<String> = self
+            .params
+            .iter()
+            .enumerate()
+            .rev()
+            .map(|(idx, param)| {
+                omittable &= param.ty.is_nullable();
+                let name = if is_identifier(&param.name) {
+                    param.name.clone()
+                } else {
+                    format!("arg{}", idx)
+                };
+                let optional = if omittable { "?" } else { "" };
+                format!("{}{}: {}", name, optional, param.ty.render())
+            })
+            .collect();
+        rendered.reverse();
+        rendered.join(", ")
+    }
+}
+
+/// Declaration of a BAML class
+#[derive(Debug, Clone, PartialEq)]
+pub struct TsClass {
+    pub name: String,
+    pub fields: Vec<TsField>,
+}
+
+/// Declaration of a BAML enum
+#[derive(Debug, Clone, PartialEq)]
+pub struct TsEnum {
+    pub name: String,
+    pub values: Vec<String>,
+}
+
+/// Full set of declarations for a BAML schema
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BamlDeclarations {
+    pub functions: Vec<TsFunction>,
+    pub classes: Vec<TsClass>,
+    pub enums: Vec<TsEnum>,
+}
+
+impl BamlDeclarations {
+    /// Lower a BAML intermediate representation into declarations
+    pub fn from_ir(ir: &IntermediateRepr) -> Self {
+        let mut enums: Vec<TsEnum> = ir
+            .walk_enums()
+            .map(|e| TsEnum {
+                name: e.name().to_string(),
+                values: e.walk_values().map(|v| v.name().to_string()).collect(),
+            })
+            .collect();
+        enums.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let mut classes: Vec<TsClass> = ir
+            .walk_classes()
+            .map(|c| TsClass {
+                name: c.name().to_string(),
+                fields: c
+                    .walk_fields()
+                    .map(|f| TsField {
+                        name: f.name().to_string(),
+                        ty: field_type_to_ts(f.r#type()),
+                    })
+                    .collect(),
+            })
+            .collect();
+        classes.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let mut functions: Vec<TsFunction> = ir
+            .walk_functions()
+            .map(|f| TsFunction {
+                name: f.name().to_string(),
+                params: f
+                    .inputs()
+                    .iter()
+                    .map(|(name, ty)| TsField {
+                        name: name.clone(),
+                        ty: field_type_to_ts(ty),
+                    })
+                    .collect(),
+                output: field_type_to_ts(f.output()),
+            })
+            .collect();
+        functions.sort_by(|a, b| a.name.cmp(&b.name));
+
+        Self {
+            functions,
+            classes,
+            enums,
+        }
+    }
+
+    /// Render the declarations as the contents of `baml.d.ts`
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        out.push_str("// TypeScript declarations for BAML runtime host functions\n");
+        out.push_str("// This file is auto-generated - do not edit manually\n");
+        out.push_str("// Generated from BAML IR\n\n");
+
+        out.push_str("/**\n * Partial result emitted by streaming BAML functions.\n */\n");
+        out.push_str(
+            "type BamlPartial<T> = T extends (infer U)[]\n  ? BamlPartial<U>[]\n  : T extends object\n    ? { [K in keyof T]?: BamlPartial<T[K]> | null }\n    : T;\n\n",
+        );
+
+        for baml_enum in &self.enums {
+            let variants = if baml_enum.values.is_empty() {
+                "never".to_string()
+            } else {
+                baml_enum
+                    .values
+                    .iter()
+                    .map(|v| format!("{:?}", v))
+                    .collect::<Vec<_>>()
+                    .join(" | ")
+            };
+            let _ = writeln!(out, "type {} = {};\n", baml_enum.name, variants);
+        }
+
+        for class in &self.classes {
+            let _ = writeln!(out, "interface {} {{", class.name);
+            for field in &class.fields {
+                let _ = writeln!(out, "  {}", field.render());
+            }
+            out.push_str("}\n\n");
+        }
+
+        for function in &self.functions {
+            let args_type = format!("{}Args", function.name);
+            let _ = writeln!(out, "interface {} {{", args_type);
+            for param in &function.params {
+                let _ = writeln!(out, "  {}", param.render());
+            }
+            out.push_str("}\n\n");
+
+            let output = function.output.render();
+            let positional = function.render_positional_params();
+            let _ = writeln!(
+                out,
+                "/**\n * {} BAML function\n */\ndeclare function {}(args: {}): Promise<{}>;\ndeclare function {}({}): Promise<{}>;\n",
+                function.name, function.name, args_type, output, function.name, positional, output
+            );
+            let _ = writeln!(
+                out,
+                "/**\n * Streaming variant of {}. Resolves to every partial result in order.\n */\ndeclare function {}Stream(args: {}): Promise<BamlPartial<{}>[]>;\ndeclare function {}Stream({}): Promise<BamlPartial<{}>[]>;\n",
+                function.name, function.name, args_type, output, function.name, positional, output
+            );
+        }
+
+        out.push_str("/**\n");
+        out.push_str(" * Dynamically invoke a tool by name.\n");
+        out.push_str(" * Works for both Rust-registered tools and JavaScript-registered tools.\n");
+        out.push_str(
+            " * Pass `function` to hold the call to the tools that BAML function may use.\n",
+        );
+        out.push_str(" */\ndeclare function invokeTool(toolName: string, args: Record<string, any>, options?: { function?: string }): Promise<any>;\n");
+
+        out
+    }
+}
+
+fn field_type_to_ts(field_type: &FieldType) -> TsType {
+    use internal_baml_core::ir::{LiteralValue, TypeValue};
+
+    match field_type {
+        FieldType::Primitive(primitive, ..) => match primitive {
+            TypeValue::String => TsType::String,
+            TypeValue::Int | TypeValue::Float => TsType::Number,
+            TypeValue::Bool => TsType::Boolean,
+            TypeValue::Null => TsType::Null,
+            _ => TsType::Unknown,
+        },
+        FieldType::Enum { name, .. } | FieldType::Class { name, .. } => TsType::Named(name.clone()),
+        FieldType::RecursiveTypeAlias { name, .. } => TsType::Named(name.clone()),
+        FieldType::Literal(literal, ..) => match literal {
+            LiteralValue::String(s) => TsType::Literal(format!("{:?}", s)),
+            LiteralValue::Int(i) => TsType::Literal(i.to_string()),
+            LiteralValue::Bool(b) => TsType::Literal(b.to_string()),
+        },
+        FieldType::List(inner, ..) => TsType::Array(Box::new(field_type_to_ts(inner))),
+        FieldType::Map(_, value, ..) => TsType::Record(Box::new(field_type_to_ts(value))),
+        FieldType::Tuple(items, ..) => TsType::Tuple(items.iter().map(field_type_to_ts).collect()),
+        FieldType::Union(union, ..) => {
+            let mut variants: Vec<TsType> = union
+                .iter_skip_null()
+                .into_iter()
+                .map(field_type_to_ts)
+                .collect();
+            if union.is_optional() {
+                variants.push(TsType::Null);
+            }
+            match variants.len() {
+                0 => TsType::Null,
+                1 => variants.remove(0),
+                _ => TsType::Union(variants),
+            }
+        }
+        _ => TsType::Unknown,
+    }
+}
+
+fn is_identifier(name: &str) -> bool {
+    name.chars()
+        .next()
+        .map(|c| c.is_ascii_alphabetic() || c == '_' || c == '$')
+        .unwrap_or(false)
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+}
+
+fn quote_property(name: &str) -> String {
+    if is_identifier(name) {
+        name.to_string()
+    } else {
+        format!("{:?}", name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_functions_with_args_and_stream_variants() {
+        let declarations = BamlDeclarations {
+            functions: vec![TsFunction {
+                name: "Greet".to_string(),
+                params: vec![
+                    TsField {
+                        name: "name".to_string(),
+                        ty: TsType::String,
+                    },
+                    TsField {
+                        name: "mood".to_string(),
+                        ty: TsType::Union(vec![TsType::Named("Mood".to_string()), TsType::Null]),
+                    },
+                ],
+                output: TsType::Named("Greeting".to_string()),
+            }],
+            classes: vec![TsClass {
+                name: "Greeting".to_string(),
+                fields: vec![TsField {
+                    name: "lines".to_string(),
+                    ty: TsType::Array(Box::new(TsType::String)),
+                }],
+            }],
+            enums: vec![TsEnum {
+                name: "Mood".to_string(),
+                values: vec!["Happy".to_string(), "Grim".to_string()],
+            }],
+        };
+
+        let rendered = declarations.render();
+        assert!(rendered.contains("type Mood = \"Happy\" | \"Grim\";"));
+        assert!(rendered.contains("interface Greeting {\n  lines: string[];\n}"));
+        assert!(
+            rendered.contains("interface GreetArgs {\n  name: string;\n  mood?: Mood | null;\n}")
+        );
+        assert!(rendered.contains("declare function Greet(args: GreetArgs): Promise<Greeting>;"));
+        assert!(rendered.contains(
+            "declare function GreetStream(args: GreetArgs): Promise<BamlPartial<Greeting>[]>;"
+        ));
+        assert!(rendered.contains(
+            "declare function Greet(name: string, mood?: Mood | null): Promise<Greeting>;"
+        ));
+        assert!(rendered.contains(
+            "declare function GreetStream(name: string, mood?: Mood | null): Promise<BamlPartial<Greeting>[]>;"
+        ));
+        assert!(rendered.contains("declare function invokeTool("));
+    }
+
+    #[test]
+    fn positional_params_are_optional_only_when_trailing() {
+        let function = TsFunction {
+            name: "Plan".to_string(),
+            params: vec![
+                TsField {
+                    name: "note".to_string(),
+                    ty: TsType::Union(vec![TsType::String, TsType::Null]),
+                },
+                TsField {
+                    name: "step-count".to_string(),
+                    ty: TsType::Number,
+                },
+                TsField {
+                    name: "hint".to_string(),
+                    ty: TsType::Null,
+                },
+            ],
+            output: TsType::String,
+        };
+        assert_eq!(
+            function.render_positional_params(),
+            "note: string | null, arg1: number, hint?: null"
+        );
+    }
+
+    #[test]
+    fn quotes_non_identifier_properties() {
+        let field = TsField {
+            name: "first-name".to_string(),
+            ty: TsType::String,
+        };
+        assert_eq!(field.render(), "\"first-name\": string;");
+    }
+
+    #[test]
+    fn parenthesizes_union_arrays() {
+        let ty = TsType::Array(Box::new(TsType::Union(vec![
+            TsType::String,
+            TsType::Number,
+        ])));
+        assert_eq!(ty.render(), "(string | number)[]");
+    }
+}
@@ -0,0 +1,277 @@
+//! Unpackaged agents for local development
+//!
+//! Loads an agent straight from its source directory: TypeScript is compiled
+//! into a scratch build directory and the BAML schema is read from
+//! `baml_src`, so no tar.gz package is needed. Used by the `repl` subcommand,
+//! which reloads the agent when [`watch_sources`] reports an edit.
+
+use crate::builder::compiler::OxcTypeScriptCompiler;
+use crate::builder::filesystem::StdFileSystem;
+use crate::builder::traits::{FileSystem, TypeScriptCompiler};
+use crate::builder::types::{AgentDir, BuildDir};
+use baml_rt_core::manifest::{AgentManifest, DEFAULT_ENTRY_POINT, MANIFEST_FILE};
+use baml_rt_core::{BamlRtError, Result};
+use baml_rt_quickjs::{BamlRuntimeManager, QuickJSBridge};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use tokio::sync::{Mutex, RwLock, mpsc};
+
+/// An agent loaded from its source directory
+pub struct DevAgent {
+    name: String,
+    runtime: Arc<RwLock<BamlRuntimeManager>>,
+    bridge: Arc<Mutex<QuickJSBridge>>,
+}
+
+impl DevAgent {
+    /// Compile and load the agent in `agent_dir`
+    pub async fn load(agent_dir: &AgentDir) -> Result<Self> {
+        let manifest = read_manifest(agent_dir)?;
+        let name = manifest
+            .as_ref()
+            .map(|manifest| manifest.name.clone())
+            .or_else(|| {
+                agent_dir
+                    .as_path()
+                    .file_name()
+                    .map(|name| name.to_string_lossy().into_owned())
+            })
+            .unwrap_or_else(|| "agent".to_string());
+        let entry_point = manifest
+            .as_ref()
+            .map_or(DEFAULT_ENTRY_POINT, |manifest| {
+                manifest.entry_point.as_str()
+            })
+            .to_string();
+
+        let build_dir = BuildDir::new()?;
+        let compiler = OxcTypeScriptCompiler::new(StdFileSystem);
+        compiler
+            .compile(&agent_dir.src(), &build_dir.join("dist"))
+            .await?;
+
+        let baml_src = agent_dir.baml_src();
+        let baml_src_str = baml_src.to_str().ok_or_else(|| {
+            BamlRtError::InvalidArgument(format!(
+                "BAML source path contains invalid UTF-8: {}",
+                baml_src.display()
+            ))
+        })?;
+        let mut runtime = BamlRuntimeManager::new()?;
+        runtime.load_schema(baml_src_str)?;
+        let runtime = Arc::new(RwLock::new(runtime));
+
+        let mut bridge = QuickJSBridge::new(runtime.clone()).await?;
+        bridge.register_baml_functions().await?;
+
+        let entry_point_path = build_dir.join(&entry_point);
+        if entry_point_path.exists() {
+            let agent_code = StdFileSystem.read_to_string(&entry_point_path)?;
+            let source_map = StdFileSystem
+                .read_to_string(Path::new(&format!("{}.map", entry_point_path.display())))
+                .ok();
+            if let Err(e) = bridge
+                .evaluate_script(&entry_point, &agent_code, source_map.as_deref())
+                .await
+            {
+                tracing::warn!(error = ?e, "Agent init script evaluation failed");
+            }
+        } else {
+            tracing::warn!(entry_point = %entry_point_path.display(), "Agent entry point not found");
+        }
+
+        Ok(Self {
+            name,
+            runtime,
+            bridge: Arc::new(Mutex::new(bridge)),
+        })
+    }
+
+    /// Agent name from manifest.json, or the directory name
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// BAML functions exposed by the agent, sorted by name
+    pub async fn functions(&self) -> Vec<String> {
+        let mut functions = self.runtime.read().await.list_functions();
+        functions.sort();
+        functions
+    }
+
+    /// Registered tools, sorted by name
+    pub async fn tools(&self) -> Vec<String> {
+        let mut tools = self.runtime.read().await.list_tools().await;
+        tools.sort();
+        tools
+    }
+
+    /// Call a JS or BAML function exposed on the agent's global scope
+    pub async fn invoke(&self, function_name: &str, args: Value) -> Result<Value> {
+        let mut bridge = self.bridge.lock().await;
+        bridge.invoke_js_function(function_name, args).await
+    }
+
+    /// Call the streaming variant of a function, passing each partial result
+    /// to `on_chunk` as it arrives
+    ///
+    /// BAML functions stream straight from the LLM. A JS `<function>Stream`
+    /// resolves to all of its chunks at once, so those arrive together.
+    pub async fn invoke_stream<F>(
+        &self,
+        function_name: &str,
+        args: Value,
+        mut on_chunk: F,
+    ) -> Result<()>
+    where
+        F: FnMut(Value) + Send,
+    {
+        let is_baml = self
+            .runtime
+            .read()
+            .await
+            .list_functions()
+            .iter()
+            .any(|name| name == function_name);
+        if !is_baml || self.has_js_stream(function_name).await? {
+            let mut bridge = self.bridge.lock().await;
+            for chunk in bridge.invoke_function_stream(function_name, args).await? {
+                on_chunk(chunk);
+            }
+            return Ok(());
+        }
+
+        let mut stream = {
+            let runtime = self.runtime.read().await;
+            let type_builder = runtime.tool_type_builder(function_name).await;
+            runtime
+                .invoke_function_stream_with_cancel(
+                    function_name,
+                    args,
+                    Default::default(),
+                    type_builder,
+                )
+                .await?
+        };
+        // The stream owns what it needs, so the runtime is free while it runs
+        stream
+            .run(
+                |result| {
+                    if let Some(Ok(parsed)) = result.parsed()
+                        && let Ok(value) = serde_json::to_value(parsed.serialize_partial())
+                    {
+                        on_chunk(value);
+                    }
+                },
+                HashMap::new(),
+            )
+            .await
+            .map_err(|e| BamlRtError::BamlRuntime(format!("Stream failed: {}", e)))?;
+        Ok(())
+    }
+
+    /// Whether the agent's JS defines `<function>Stream`
+    async fn has_js_stream(&self, function_name: &str) -> Result<bool> {
+        let name = serde_json::to_string(&format!("{}Stream", function_name))?;
+        let code = format!(
+            "(() => JSON.stringify(typeof globalThis[{}] === 'function'))()",
+            name
+        );
+        let defined = self.bridge.lock().await.evaluate(&code).await?;
+        Ok(defined == Value::Bool(true))
+    }
+
+    /// Execute a registered tool
+    pub async fn execute_tool(&self, tool_name: &str, args: Value) -> Result<Value> {
+        let runtime = self.runtime.read().await;
+        runtime.execute_tool(tool_name, args).await
+    }
+}
+
+/// Modification times of an agent's sources, used to detect edits
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceSnapshot(BTreeMap<PathBuf, SystemTime>);
+
+impl SourceSnapshot {
+    /// Record the sources under `baml_src`, `src`, and `manifest.json`
+    pub fn capture(agent_dir: &AgentDir) -> Self {
+        let mut entries = BTreeMap::new();
+        collect_mtimes(&agent_dir.baml_src(), &mut entries);
+        collect_mtimes(&agent_dir.src(), &mut entries);
+        collect_mtimes(&agent_dir.as_path().join(MANIFEST_FILE), &mut entries);
+        Self(entries)
+    }
+
+    /// Record the files under `path` alone
+    pub fn capture_path(path: &Path) -> Self {
+        let mut entries = BTreeMap::new();
+        collect_mtimes(path, &mut entries);
+        Self(entries)
+    }
+}
+
+/// Watch an agent's sources, checking every `poll`; the receiver gets a
+/// message after each change
+///
+/// Changes made while an earlier one is still unread are reported once. The
+/// watch stops when the receiver is dropped.
+pub fn watch_sources(agent_dir: AgentDir, poll: Duration) -> mpsc::Receiver<()> {
+    let (changes, receiver) = mpsc::channel(1);
+    tokio::spawn(async move {
+        let mut snapshot = SourceSnapshot::capture(&agent_dir);
+        while !changes.is_closed() {
+            tokio::time::sleep(poll).await;
+            let current = SourceSnapshot::capture(&agent_dir);
+            if current != snapshot {
+                snapshot = current;
+                let _ = changes.try_send(());
+            }
+        }
+    });
+    receiver
+}
+
+fn collect_mtimes(path: &Path, entries: &mut BTreeMap<PathBuf, SystemTime>) {
+    if path.is_dir() {
+        let Ok(dir) = std::fs::read_dir(path) else {
+            return;
+        };
+        for entry in dir.flatten() {
+            collect_mtimes(&entry.path(), entries);
+        }
+    } else if let Ok(modified) = path.metadata().and_then(|m| m.modified()) {
+        entries.insert(path.to_path_buf(), modified);
+    }
+}
+
+fn read_manifest(agent_dir: &AgentDir) -> Result<Option<AgentManifest>> {
+    let manifest_path = agent_dir.as_path().join(MANIFEST_FILE);
+    if !manifest_path.exists() {
+        return Ok(None);
+    }
+    AgentManifest::from_json(&StdFileSystem.read_to_string(&manifest_path)?).map(Some)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn edits_are_reported_without_being_asked_for() {
+        let root = tempfile::TempDir::new().unwrap();
+        std::fs::create_dir_all(root.path().join("baml_src")).unwrap();
+        std::fs::write(root.path().join("baml_src/main.baml"), "").unwrap();
+        let agent_dir = AgentDir::new(root.path().to_path_buf()).unwrap();
+
+        let mut changes = watch_sources(agent_dir, Duration::from_millis(20));
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(changes.try_recv().is_err());
+
+        std::fs::write(root.path().join("baml_src/other.baml"), "").unwrap();
+        let change = tokio::time::timeout(Duration::from_secs(5), changes.recv()).await;
+        assert_eq!(change.expect("change reported"), Some(()));
+    }
+}
@@ -0,0 +1,791 @@
+//! Evaluation runs over datasets
+//!
+//! An [`Evaluator`] calls a target once per case of an [`EvalDataset`]: a
+//! BAML (or JS) function given the case input as its arguments, or the
+//! agent's `handle_a2a_request` given an A2A request. Every output is graded
+//! by the evaluator's [`Scorer`]s, and the results are collected in an
+//! [`EvalReport`] with per-case scores and aggregate metrics.
+//!
+//! Datasets are JSON arrays or JSONL files of cases shaped like
+//! `{ "name": "greets", "input": {...}, "expected": ... }`, where `name` and
+//! `expected` are optional. For A2A targets a string input is sent as the
+//! text of a `message.send` request, and an object input as the request
+//! itself.
+//!
+//! Scorers are exact match ([`ExactMatch`]), field checks at JSON pointers
+//! ([`JsonFields`]), and LLM-as-judge ([`LlmJudge`]), which asks another BAML
+//! function to grade the output.
+
+use crate::builder::dev_agent::DevAgent;
+use crate::builder::filesystem::StdFileSystem;
+use crate::builder::traits::FileSystem;
+use baml_rt_core::{BamlRtError, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::Path;
+use std::time::Instant;
+
+/// Function receiving A2A requests on an agent's global scope
+pub const A2A_HANDLER: &str = "handle_a2a_request";
+
+/// Score at or above which a judge's verdict passes, unless it says otherwise
+pub const DEFAULT_JUDGE_THRESHOLD: f64 = 0.5;
+
+/// One input of a dataset and what it should produce
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EvalCase {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    pub input: Value,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expected: Option<Value>,
+}
+
+/// The cases an evaluation runs
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EvalDataset {
+    pub cases: Vec<EvalCase>,
+}
+
+impl EvalDataset {
+    /// Parse a JSON array of cases, or one case per line (JSONL)
+    pub fn parse(content: &str) -> Result<Self> {
+        if content.trim_start().starts_with('[') {
+            let cases = serde_json::from_str(content).map_err(|e| {
+                BamlRtError::InvalidArgument(format!("Invalid evaluation dataset: {}", e))
+            })?;
+            return Ok(Self { cases });
+        }
+        let mut cases = Vec::new();
+        for (index, line) in content.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let case = serde_json::from_str(line).map_err(|e| {
+                BamlRtError::InvalidArgument(format!(
+                    "Invalid evaluation case on line {}: {}",
+                    index + 1,
+                    e
+                ))
+            })?;
+            cases.push(case);
+        }
+        Ok(Self { cases })
+    }
+
+    /// Read a dataset file
+    pub fn load(path: &Path) -> Result<Self> {
+        Self::parse(&StdFileSystem.read_to_string(path)?)
+    }
+}
+
+/// What an evaluation calls
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EvalTarget {
+    /// A BAML or JS function, called with the case input as its arguments
+    Function(String),
+    /// The agent's `handle_a2a_request`
+    A2a,
+}
+
+impl EvalTarget {
+    /// Name of the function called for each case
+    pub fn function(&self) -> &str {
+        match self {
+            Self::Function(name) => name,
+            Self::A2a => A2A_HANDLER,
+        }
+    }
+
+    /// Arguments of the call for the case at `index`
+    fn arguments(&self, index: usize, input: &Value) -> Result<Value> {
+        match (self, input) {
+            (Self::Function(_), Value::Object(_)) => Ok(input.clone()),
+            (Self::Function(name), _) => Err(BamlRtError::InvalidArgument(format!(
+                "input of a {} case must be an object of arguments",
+                name
+            ))),
+            (Self::A2a, Value::String(text)) => Ok(json!({
+                "method": "message.send",
+                "params": {
+                    "message": {
+                        "messageId": format!("eval-{}", index + 1),
+                        "role": "ROLE_USER",
+                        "parts": [{ "text": text }]
+                    }
+                }
+            })),
+            (Self::A2a, Value::Object(_)) => Ok(input.clone()),
+            (Self::A2a, _) => Err(BamlRtError::InvalidArgument(
+                "input of an A2A case must be a message text or a request object".to_string(),
+            )),
+        }
+    }
+}
+
+impl fmt::Display for EvalTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Function(name) => write!(f, "{}", name),
+            Self::A2a => write!(f, "{} (A2A)", A2A_HANDLER),
+        }
+    }
+}
+
+/// Calls the functions an evaluation targets, and the ones judges use
+///
+/// Calls into an agent's QuickJS bridge are not `Send`, so neither are the
+/// futures of this trait or of [`Scorer`].
+#[async_trait::async_trait(?Send)]
+pub trait EvalCaller {
+    async fn call(&self, function: &str, args: Value) -> Result<Value>;
+}
+
+#[async_trait::async_trait(?Send)]
+impl EvalCaller for DevAgent {
+    async fn call(&self, function: &str, args: Value) -> Result<Value> {
+        self.invoke(function, args).await
+    }
+}
+
+/// How one scorer graded an output
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Score {
+    /// Between 0 and 1
+    pub score: f64,
+    pub passed: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+impl Score {
+    pub fn pass() -> Self {
+        Self {
+            score: 1.0,
+            passed: true,
+            detail: None,
+        }
+    }
+
+    pub fn fail(detail: impl Into<String>) -> Self {
+        Self {
+            score: 0.0,
+            passed: false,
+            detail: Some(detail.into()),
+        }
+    }
+}
+
+/// Grades the output of a case
+#[async_trait::async_trait(?Send)]
+pub trait Scorer {
+    /// Name the scorer's results are reported under
+    fn name(&self) -> String;
+
+    async fn score(
+        &self,
+        case: &EvalCase,
+        output: &Value,
+        caller: &dyn EvalCaller,
+    ) -> Result<Score>;
+}
+
+/// Passes outputs equal to the expected value
+///
+/// A string expectation is compared with the output's text: the output
+/// itself when it is a string, or the text parts of an A2A response's
+/// message.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ExactMatch;
+
+#[async_trait::async_trait(?Send)]
+impl Scorer for ExactMatch {
+    fn name(&self) -> String {
+        "exact".to_string()
+    }
+
+    async fn score(
+        &self,
+        case: &EvalCase,
+        output: &Value,
+        _caller: &dyn EvalCaller,
+    ) -> Result<Score> {
+        let Some(expected) = &case.expected else {
+            return Ok(Score::fail("case has no expected output"));
+        };
+        let matches = match (expected, output_text(output)) {
+            (Value::String(expected), Some(text)) => *expected == text,
+            _ => expected == output,
+        };
+        Ok(if matches {
+            Score::pass()
+        } else {
+            Score::fail(format!("expected {}, got {}", expected, output))
+        })
+    }
+}
+
+/// Text of an output: a string, or the text parts of an A2A response
+fn output_text(output: &Value) -> Option<String> {
+    if let Value::String(text) = output {
+        return Some(text.clone());
+    }
+    let parts = output.pointer("/message/parts")?.as_array()?;
+    let text: Vec<&str> = parts
+        .iter()
+        .filter_map(|part| part["text"].as_str())
+        .collect();
+    (!text.is_empty()).then(|| text.concat())
+}
+
+/// Compares fields of the output with the same fields of the expected value
+///
+/// Fields are JSON pointers such as `/message/role`; without any, every
+/// top-level field of the expected object is compared. The score is the
+/// fraction of fields that match, and the output passes when all do.
+#[derive(Debug, Clone, Default)]
+pub struct JsonFields {
+    pointers: Vec<String>,
+}
+
+impl JsonFields {
+    pub fn new<I, S>(pointers: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            pointers: pointers.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+#[async_trait::async_trait(?Send)]
+impl Scorer for JsonFields {
+    fn name(&self) -> String {
+        "json-fields".to_string()
+    }
+
+    async fn score(
+        &self,
+        case: &EvalCase,
+        output: &Value,
+        _caller: &dyn EvalCaller,
+    ) -> Result<Score> {
+        let Some(expected) = &case.expected else {
+            return Ok(Score::fail("case has no expected output"));
+        };
+        let pointers = if self.pointers.is_empty() {
+            match expected {
+                Value::Object(fields) => fields
+                    .keys()
+                    .map(|key| format!("/{}", key.replace('~', "~0").replace('/', "~1")))
+                    .collect(),
+                _ => return Ok(Score::fail("expected output is not an object")),
+            }
+        } else {
+            self.pointers.clone()
+        };
+        if pointers.is_empty() {
+            return Ok(Score::pass());
+        }
+
+        let mismatches: Vec<String> = pointers
+            .iter()
+            .filter_map(|pointer| {
+                let want = expected.pointer(pointer);
+                let got = output.pointer(pointer);
+                match (want, got) {
+                    (Some(want), Some(got)) if want == got => None,
+                    (None, _) => Some(format!("{}: missing from expected output", pointer)),
+                    (Some(want), None) => Some(format!("{}: expected {}, missing", pointer, want)),
+                    (Some(want), Some(got)) => {
+                        Some(format!("{}: expected {}, got {}", pointer, want, got))
+                    }
+                }
+            })
+            .collect();
+        let matched = pointers.len() - mismatches.len();
+        Ok(Score {
+            score: matched as f64 / pointers.len() as f64,
+            passed: mismatches.is_empty(),
+            detail: (!mismatches.is_empty()).then(|| mismatches.join("; ")),
+        })
+    }
+}
+
+/// Asks a BAML function to grade the output
+///
+/// The judge is called with `input`, `expected`, and `output` string
+/// arguments holding the JSON text of each (`expected` is `null` when the
+/// case has none). It may return a score between 0 and 1, a boolean, or an
+/// object with a `score` and optional `passed` and `reason` fields. Scores
+/// pass at or above the threshold unless the judge says otherwise.
+#[derive(Debug, Clone)]
+pub struct LlmJudge {
+    function: String,
+    threshold: f64,
+}
+
+impl LlmJudge {
+    pub fn new(function: impl Into<String>) -> Self {
+        Self {
+            function: function.into(),
+            threshold: DEFAULT_JUDGE_THRESHOLD,
+        }
+    }
+
+    /// Pass scores at or above `threshold`
+    pub fn with_threshold(mut self, threshold: f64) -> Self {
+        self.threshold = threshold.clamp(0.0, 1.0);
+        self
+    }
+
+    fn verdict(&self, verdict: &Value) -> Result<Score> {
+        let from_score = |score: f64, passed: Option<bool>, detail: Option<String>| {
+            let score = score.clamp(0.0, 1.0);
+            Score {
+                score,
+                passed: passed.unwrap_or(score >= self.threshold),
+                detail,
+            }
+        };
+        match verdict {
+            Value::Number(score) => Ok(from_score(score.as_f64().unwrap_or(0.0), None, None)),
+            Value::Bool(passed) => Ok(from_score(f64::from(u8::from(*passed)), None, None)),
+            Value::Object(fields) => {
+                let passed = fields.get("passed").and_then(Value::as_bool);
+                let score = match fields.get("score").and_then(Value::as_f64) {
+                    Some(score) => score,
+                    None => match passed {
+                        Some(passed) => f64::from(u8::from(passed)),
+                        None => return Err(self.unreadable(verdict)),
+                    },
+                };
+                let reason = fields
+                    .get("reason")
+                    .and_then(Value::as_str)
+                    .map(str::to_string);
+                Ok(from_score(score, passed, reason))
+            }
+            _ => Err(self.unreadable(verdict)),
+        }
+    }
+
+    fn unreadable(&self, verdict: &Value) -> BamlRtError {
+        BamlRtError::InvalidArgument(format!(
+            "judge {} returned {}, expected a score, a boolean, or {{ score, passed, reason }}",
+            self.function, verdict
+        ))
+    }
+}
+
+#[async_trait::async_trait(?Send)]
+impl Scorer for LlmJudge {
+    fn name(&self) -> String {
+        format!("judge:{}", self.function)
+    }
+
+    async fn score(
+        &self,
+        case: &EvalCase,
+        output: &Value,
+        caller: &dyn EvalCaller,
+    ) -> Result<Score> {
+        let args = json!({
+            "input": case.input.to_string(),
+            "expected": case.expected.as_ref().unwrap_or(&Value::Null).to_string(),
+            "output": output.to_string(),
+        });
+        let verdict = caller.call(&self.function, args).await?;
+        self.verdict(&verdict)
+    }
+}
+
+/// A scorer from its command-line spec
+///
+/// Specs are `exact`, `json-fields` or `json-fields:/a,/b/c` for the given
+/// pointers, and `judge:Function` or `judge:Function:0.8` for a threshold.
+pub fn scorer_from_spec(spec: &str) -> Result<Box<dyn Scorer>> {
+    let (kind, rest) = spec
+        .split_once(':')
+        .map_or((spec, None), |(kind, rest)| (kind, Some(rest)));
+    match (kind, rest) {
+        ("exact", None) => Ok(Box::new(ExactMatch)),
+        ("json-fields", None) => Ok(Box::new(JsonFields::default())),
+        ("json-fields", Some(pointers)) => Ok(Box::new(JsonFields::new(
+            pointers.split(',').map(str::trim).filter(|p| !p.is_empty()),
+        ))),
+        ("judge", Some(rest)) if !rest.is_empty() => {
+            let (function, threshold) = match rest.split_once(':') {
+                Some((function, threshold)) => {
+                    let threshold = threshold.parse::<f64>().map_err(|_| {
+                        BamlRtError::InvalidArgument(format!(
+                            "Invalid judge threshold in scorer '{}'",
+                            spec
+                        ))
+                    })?;
+                    (function, threshold)
+                }
+                None => (rest, DEFAULT_JUDGE_THRESHOLD),
+            };
+            Ok(Box::new(LlmJudge::new(function).with_threshold(threshold)))
+        }
+        _ => Err(BamlRtError::InvalidArgument(format!(
+            "Unknown scorer '{}': expected exact, json-fields[:<pointers>], or judge:<function>",
+            spec
+        ))),
+    }
+}
+
+/// A scorer's grade of one case
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CaseScore {
+    pub scorer: String,
+    #[serde(flatten)]
+    pub score: Score,
+}
+
+/// Outcome of one case
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EvalCaseResult {
+    pub name: String,
+    pub input: Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expected: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output: Option<Value>,
+    /// Set when the target call failed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub duration_ms: u64,
+    pub scores: Vec<CaseScore>,
+}
+
+impl EvalCaseResult {
+    /// True when the call succeeded and every scorer passed the output
+    pub fn passed(&self) -> bool {
+        self.error.is_none() && self.scores.iter().all(|score| score.score.passed)
+    }
+}
+
+/// Aggregate results of one scorer
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ScorerMetrics {
+    pub mean_score: f64,
+    pub pass_rate: f64,
+}
+
+/// Aggregate results of an evaluation
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct EvalMetrics {
+    pub total: usize,
+    pub passed: usize,
+    pub failed: usize,
+    /// Cases whose target call failed; also counted as failed
+    pub errors: usize,
+    pub pass_rate: f64,
+    pub mean_duration_ms: f64,
+    /// Over the cases whose call succeeded, by scorer name
+    pub scorers: BTreeMap<String, ScorerMetrics>,
+}
+
+impl EvalMetrics {
+    fn from_cases(cases: &[EvalCaseResult]) -> Self {
+        let total = cases.len();
+        let passed = cases.iter().filter(|case| case.passed()).count();
+        let mean = |sum: f64, count: usize| if count == 0 { 0.0 } else { sum / count as f64 };
+
+        let mut by_scorer: BTreeMap<String, (f64, usize, usize)> = BTreeMap::new();
+        for score in cases.iter().flat_map(|case| &case.scores) {
+            let entry = by_scorer.entry(score.scorer.clone()).or_default();
+            entry.0 += score.score.score;
+            entry.1 += usize::from(score.score.passed);
+            entry.2 += 1;
+        }
+        Self {
+            total,
+            passed,
+            failed: total - passed,
+            errors: cases.iter().filter(|case| case.error.is_some()).count(),
+            pass_rate: mean(passed as f64, total),
+            mean_duration_ms: mean(
+                cases.iter().map(|case| case.duration_ms as f64).sum(),
+                total,
+            ),
+            scorers: by_scorer
+                .into_iter()
+                .map(|(name, (sum, passes, count))| {
+                    let metrics = ScorerMetrics {
+                        mean_score: mean(sum, count),
+                        pass_rate: mean(passes as f64, count),
+                    };
+                    (name, metrics)
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Per-case results and aggregate metrics of an evaluation
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EvalReport {
+    /// The function evaluated
+    pub target: String,
+    pub cases: Vec<EvalCaseResult>,
+    pub metrics: EvalMetrics,
+}
+
+impl EvalReport {
+    pub fn new(target: impl Into<String>, cases: Vec<EvalCaseResult>) -> Self {
+        let metrics = EvalMetrics::from_cases(&cases);
+        Self {
+            target: target.into(),
+            cases,
+            metrics,
+        }
+    }
+
+    pub fn success(&self) -> bool {
+        self.metrics.failed == 0
+    }
+}
+
+impl fmt::Display for EvalReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "📊 Evaluated {} ({} case(s))",
+            self.target, self.metrics.total
+        )?;
+        for case in &self.cases {
+            let mark = if case.passed() { "✓" } else { "✗" };
+            writeln!(f, "  {} {} ({} ms)", mark, case.name, case.duration_ms)?;
+            if let Some(error) = &case.error {
+                writeln!(f, "      error: {}", error)?;
+            }
+            for score in &case.scores {
+                write!(f, "      {}: {:.2}", score.scorer, score.score.score)?;
+                match &score.score.detail {
+                    Some(detail) if !score.score.passed => writeln!(f, " ({})", detail)?,
+                    _ => writeln!(f)?,
+                }
+            }
+        }
+        let metrics = &self.metrics;
+        writeln!(
+            f,
+            "\n{} passed, {} failed ({} error(s)), pass rate {:.1}%, mean {:.0} ms",
+            metrics.passed,
+            metrics.failed,
+            metrics.errors,
+            metrics.pass_rate * 100.0,
+            metrics.mean_duration_ms
+        )?;
+        for (name, scorer) in &metrics.scorers {
+            writeln!(
+                f,
+                "  {}: mean score {:.2}, pass rate {:.1}%",
+                name,
+                scorer.mean_score,
+                scorer.pass_rate * 100.0
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Runs a dataset against a target and scores the outputs
+pub struct Evaluator {
+    target: EvalTarget,
+    scorers: Vec<Box<dyn Scorer>>,
+}
+
+impl Evaluator {
+    /// An evaluator without scorers, passing every case whose call succeeds
+    pub fn new(target: EvalTarget) -> Self {
+        Self {
+            target,
+            scorers: Vec::new(),
+        }
+    }
+
+    pub fn with_scorer(mut self, scorer: impl Scorer + 'static) -> Self {
+        self.scorers.push(Box::new(scorer));
+        self
+    }
+
+    pub fn with_boxed_scorer(mut self, scorer: Box<dyn Scorer>) -> Self {
+        self.scorers.push(scorer);
+        self
+    }
+
+    /// Run the cases one after another
+    ///
+    /// Failed calls are recorded in their case's result rather than ending
+    /// the run, and so are scorers that fail to grade an output.
+    pub async fn run(&self, caller: &dyn EvalCaller, dataset: &EvalDataset) -> EvalReport {
+        let mut results = Vec::with_capacity(dataset.cases.len());
+        for (index, case) in dataset.cases.iter().enumerate() {
+            let name = case
+                .name
+                .clone()
+                .unwrap_or_else(|| format!("case {}", index + 1));
+            let started = Instant::now();
+            let outcome = match self.target.arguments(index, &case.input) {
+                Ok(args) => caller.call(self.target.function(), args).await,
+                Err(e) => Err(e),
+            };
+            let duration_ms = started.elapsed().as_millis() as u64;
+
+            let mut result = EvalCaseResult {
+                name,
+                input: case.input.clone(),
+                expected: case.expected.clone(),
+                output: None,
+                error: None,
+                duration_ms,
+                scores: Vec::new(),
+            };
+            match outcome {
+                Ok(output) => {
+                    for scorer in &self.scorers {
+                        let score = scorer
+                            .score(case, &output, caller)
+                            .await
+                            .unwrap_or_else(|e| Score::fail(format!("scorer failed: {}", e)));
+                        result.scores.push(CaseScore {
+                            scorer: scorer.name(),
+                            score,
+                        });
+                    }
+                    result.output = Some(output);
+                }
+                Err(e) => {
+                    tracing::debug!(case = %result.name, error = %e, "Evaluation case failed");
+                    result.error = Some(e.to_string());
+                }
+            }
+            results.push(result);
+        }
+        EvalReport::new(self.target.to_string(), results)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Echoes `Echo` arguments, answers A2A requests with their text
+    /// reversed, and grades judge calls by output length
+    struct FakeAgent;
+
+    #[async_trait::async_trait(?Send)]
+    impl EvalCaller for FakeAgent {
+        async fn call(&self, function: &str, args: Value) -> Result<Value> {
+            match function {
+                "Echo" => Ok(args),
+                A2A_HANDLER => {
+                    let text = args
+                        .pointer("/params/message/parts/0/text")
+                        .and_then(Value::as_str)
+                        .unwrap_or_default();
+                    Ok(json!({
+                        "message": {
+                            "role": "ROLE_AGENT",
+                            "parts": [{ "text": text.chars().rev().collect::<String>() }]
+                        }
+                    }))
+                }
+                "Judge" => {
+                    let output: Value = serde_json::from_str(args["output"].as_str().unwrap())?;
+                    let long = output.to_string().len() > 20;
+                    Ok(json!({ "score": if long { 0.9 } else { 0.2 }, "reason": "length" }))
+                }
+                other => Err(BamlRtError::FunctionNotFound(other.to_string())),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn scores_cases_and_aggregates_metrics() {
+        let dataset = EvalDataset::parse(
+            r#"
+{"name": "same", "input": {"name": "Ada", "age": 36}, "expected": {"name": "Ada", "age": 36}}
+{"input": {"name": "Bob", "age": 40}, "expected": {"name": "Bob", "age": 41}}
+{"name": "not args", "input": "Ada"}
+"#,
+        )
+        .unwrap();
+        let report = Evaluator::new(EvalTarget::Function("Echo".to_string()))
+            .with_scorer(ExactMatch)
+            .with_boxed_scorer(scorer_from_spec("json-fields").unwrap())
+            .with_boxed_scorer(scorer_from_spec("judge:Judge:0.5").unwrap())
+            .run(&FakeAgent, &dataset)
+            .await;
+
+        assert_eq!(report.cases[0].name, "same");
+        assert!(report.cases[0].passed());
+        let second = &report.cases[1];
+        assert_eq!(second.name, "case 2");
+        assert!(!second.passed());
+        assert_eq!(second.scores[1].score.score, 0.5);
+        assert_eq!(
+            second.scores[1].score.detail.as_deref(),
+            Some("/age: expected 41, got 40")
+        );
+        assert!(second.scores[2].score.passed, "{:?}", second.scores[2]);
+        assert!(
+            report.cases[2]
+                .error
+                .as_deref()
+                .unwrap()
+                .contains("object of arguments")
+        );
+
+        let metrics = &report.metrics;
+        assert_eq!((metrics.total, metrics.passed, metrics.errors), (3, 1, 1));
+        assert!((metrics.pass_rate - 1.0 / 3.0).abs() < 1e-9);
+        assert_eq!(metrics.scorers["exact"].pass_rate, 0.5);
+        assert_eq!(metrics.scorers["json-fields"].mean_score, 0.75);
+        assert!(!report.success());
+        assert!(
+            report
+                .to_string()
+                .contains("1 passed, 2 failed (1 error(s))")
+        );
+    }
+
+    #[tokio::test]
+    async fn a2a_cases_compare_reply_text() {
+        let dataset = EvalDataset::parse(
+            r#"[
+                {"input": "olleh", "expected": "hello"},
+                {"input": "abc", "expected": {"message": {"role": "ROLE_AGENT"}}}
+            ]"#,
+        )
+        .unwrap();
+        let report = Evaluator::new(EvalTarget::A2a)
+            .with_boxed_scorer(scorer_from_spec("exact").unwrap())
+            .run(&FakeAgent, &dataset)
+            .await;
+        assert!(report.cases[0].passed(), "{:?}", report.cases[0]);
+        assert!(!report.cases[1].passed());
+
+        let report = Evaluator::new(EvalTarget::A2a)
+            .with_boxed_scorer(scorer_from_spec("json-fields:/message/role").unwrap())
+            .run(
+                &FakeAgent,
+                &EvalDataset {
+                    cases: dataset.cases[1..].to_vec(),
+                },
+            )
+            .await;
+        assert!(report.success(), "{}", report);
+
+        assert!(scorer_from_spec("judge:").is_err());
+        assert!(scorer_from_spec("fuzzy").is_err());
+    }
+}
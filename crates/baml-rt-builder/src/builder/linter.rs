@@ -1,9 +1,212 @@
 //! Linter implementation using OXC parser
+//!
+//! Linting produces a [`LintReport`] rather than printing: its `Display`
+//! renders the human-readable summary and it serializes as the JSON read by
+//! editors and CI (`lint --format json`).
+//!
+//! Besides syntax errors, the linter knows what the QuickJS sandbox offers and
+//! flags code that would parse but fail once the agent is loaded: Node globals
+//! that do not exist, `await` outside any function, an entry point that never
+//! installs `handle_a2a_request`, and calls to BAML functions missing from
+//! `baml_src`. Like the bundler, the rules resolve names per file rather than
+//! per scope.
 
+use crate::builder::bundler::Bindings;
+use crate::builder::checker::{Severity, line_and_column};
 use crate::builder::traits::{FileSystem, Linter};
 use crate::builder::types::AgentDir;
 use baml_rt_core::{BamlRtError, Result};
+use internal_baml_core::feature_flags::FeatureFlags;
+use oxc_ast::AstKind;
+use oxc_ast::ast::{
+    AssignmentExpression, AssignmentTarget, AwaitExpression, CallExpression, Expression,
+    ForOfStatement, IdentifierReference, Program,
+};
+use oxc_ast_visit::{Visit, walk};
+use oxc_span::Span;
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fmt;
 use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Rule reported for source that does not parse
+pub const SYNTAX_RULE: &str = "syntax";
+
+/// Rule reported for Node or browser globals the sandbox does not provide
+pub const UNAVAILABLE_GLOBAL_RULE: &str = "no-unavailable-globals";
+
+/// Rule reported for `await` outside any function
+pub const TOP_LEVEL_AWAIT_RULE: &str = "no-top-level-await";
+
+/// Rule reported when the entry point never sets `handle_a2a_request`
+pub const MISSING_A2A_HANDLER_RULE: &str = "missing-a2a-handler";
+
+/// Rule reported for calls to BAML functions that `baml_src` does not define
+pub const UNKNOWN_BAML_FUNCTION_RULE: &str = "unknown-baml-function";
+
+/// Globals agents commonly reach for that QuickJS does not have, and what to
+/// use instead
+const UNAVAILABLE_GLOBALS: [(&str, &str); 3] = [
+    (
+        "require",
+        "use an `import` statement; relative imports are bundled into dist/index.js",
+    ),
+    (
+        "fetch",
+        "the sandbox has no network access; call a host tool to reach external services",
+    ),
+    (
+        "process",
+        "there is no Node `process` object; pass configuration in as function arguments",
+    ),
+];
+
+/// Capitalized JavaScript builtins that may be called without `new`, so are
+/// never mistaken for BAML functions
+const CALLABLE_BUILTINS: [&str; 17] = [
+    "AggregateError",
+    "Array",
+    "BigInt",
+    "Boolean",
+    "Date",
+    "Error",
+    "EvalError",
+    "Function",
+    "Number",
+    "Object",
+    "RangeError",
+    "ReferenceError",
+    "RegExp",
+    "String",
+    "Symbol",
+    "SyntaxError",
+    "TypeError",
+];
+
+/// Entry points the compiler bundles, relative to the agent directory
+const ENTRY_FILES: [&str; 2] = ["src/index.ts", "src/index.tsx"];
+
+/// The global the runner calls with every A2A request
+const A2A_HANDLER: &str = "handle_a2a_request";
+
+/// Where a diagnostic points: byte offsets, and the 1-based line and column
+/// of `start`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct LintSpan {
+    pub start: usize,
+    pub end: usize,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl LintSpan {
+    /// The span of `start..end` in `content`
+    pub fn new(content: &str, start: usize, end: usize) -> Self {
+        let start = start.min(content.len());
+        let (line, column) = line_and_column(content, start);
+        Self {
+            start,
+            end: end.clamp(start, content.len()),
+            line,
+            column,
+        }
+    }
+}
+
+/// One problem found by the linter
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LintDiagnostic {
+    pub severity: Severity,
+    pub rule: String,
+    /// Path relative to the agent directory
+    pub file: PathBuf,
+    pub span: LintSpan,
+    pub message: String,
+    /// How to fix the problem, when the rule knows
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub suggestion: Option<String>,
+}
+
+impl fmt::Display for LintDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}:{}: {}[{}]: {}",
+            self.file.display(),
+            self.span.line,
+            self.span.column,
+            self.severity,
+            self.rule,
+            self.message
+        )?;
+        if let Some(suggestion) = &self.suggestion {
+            write!(f, "\n    help: {}", suggestion)?;
+        }
+        Ok(())
+    }
+}
+
+/// The files a lint run covered and what it found in them
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct LintReport {
+    /// Linted files, relative to the agent directory
+    pub files: Vec<PathBuf>,
+    pub diagnostics: Vec<LintDiagnostic>,
+}
+
+impl LintReport {
+    pub fn error_count(&self) -> usize {
+        self.count(Severity::Error)
+    }
+
+    pub fn warning_count(&self) -> usize {
+        self.count(Severity::Warning)
+    }
+
+    fn count(&self, severity: Severity) -> usize {
+        self.diagnostics
+            .iter()
+            .filter(|diagnostic| diagnostic.severity == severity)
+            .count()
+    }
+
+    /// Fail when the report has errors
+    pub fn into_result(self) -> Result<Self> {
+        match self.error_count() {
+            0 => Ok(self),
+            errors => Err(BamlRtError::InvalidArgument(format!(
+                "Linting failed with {} error(s)",
+                errors
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for LintReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.files.is_empty() {
+            return writeln!(f, "✓ No TypeScript/JavaScript files found to lint");
+        }
+        writeln!(f, "🔍 Linted {} file(s)", self.files.len())?;
+        for file in &self.files {
+            if !self.diagnostics.iter().any(|d| &d.file == file) {
+                writeln!(f, "  ✓ {}", file.display())?;
+            }
+        }
+        for diagnostic in &self.diagnostics {
+            writeln!(f, "  {}", diagnostic)?;
+        }
+        match self.error_count() {
+            0 => writeln!(
+                f,
+                "\n✓ All files passed linting ({} warning(s))",
+                self.warning_count()
+            ),
+            errors => writeln!(f, "\n❌ Linting failed with {} error(s)", errors),
+        }
+    }
+}
 
 /// OXC-based linter implementation
 pub struct OxcLinter<FS> {
@@ -18,35 +221,23 @@ impl<FS: FileSystem> OxcLinter<FS> {
 
 #[async_trait::async_trait]
 impl<FS: FileSystem> Linter for OxcLinter<FS> {
-    async fn lint(&self, agent_dir: &AgentDir) -> Result<()> {
-        let src_dir = agent_dir.src();
-
-        if !src_dir.exists() {
-            println!("✓ No src directory found, nothing to lint");
-            return Ok(());
-        }
-
+    async fn lint(&self, agent_dir: &AgentDir) -> Result<LintReport> {
         let mut files = Vec::new();
-        self.filesystem.collect_ts_js_files(&src_dir, &mut files)?;
-
-        if files.is_empty() {
-            println!("✓ No TypeScript/JavaScript files found to lint");
-            return Ok(());
-        }
-
-        println!("🔍 Linting {} file(s)...", files.len());
+        self.filesystem
+            .collect_ts_js_files(&agent_dir.src(), &mut files)?;
+        files.sort();
 
         use oxc_allocator::Allocator;
         use oxc_parser::Parser;
 
-        let mut errors = Vec::new();
+        let baml_functions = baml_function_names(agent_dir);
+        let mut report = LintReport::default();
         for file_path in files {
             let content = fs::read_to_string(&file_path).map_err(BamlRtError::Io)?;
-            let file_name = file_path
+            let file = file_path
                 .strip_prefix(agent_dir.as_path())
                 .unwrap_or(&file_path)
-                .display()
-                .to_string();
+                .to_path_buf();
 
             let allocator = Allocator::default();
             let source_type = oxc_span::SourceType::from_path(&file_path)
@@ -54,27 +245,332 @@ impl<FS: FileSystem> Linter for OxcLinter<FS> {
             let parser = Parser::new(&allocator, &content, source_type);
             let parse_result = parser.parse();
 
-            if !parse_result.errors.is_empty() {
-                for error in parse_result.errors {
-                    errors.push(format!("{} - Syntax error: {:?}", file_name, error));
-                }
-            } else {
-                println!("  ✓ {}", file_name);
+            for error in parse_result.errors {
+                let (start, end) = error
+                    .labels
+                    .as_ref()
+                    .and_then(|labels| labels.first())
+                    .map_or((0, 0), |label| {
+                        (label.offset(), label.offset() + label.len())
+                    });
+                report.diagnostics.push(LintDiagnostic {
+                    severity: Severity::Error,
+                    rule: SYNTAX_RULE.to_string(),
+                    file: file.clone(),
+                    span: LintSpan::new(&content, start, end),
+                    message: error.message.to_string(),
+                    suggestion: error.help.as_ref().map(ToString::to_string),
+                });
             }
+            if !parse_result.panicked {
+                report.diagnostics.extend(check_agent_rules(
+                    &file,
+                    &content,
+                    &parse_result.program,
+                    baml_functions.as_ref(),
+                ));
+            }
+            report.files.push(file);
         }
 
-        if !errors.is_empty() {
-            println!("\n❌ Linting failed with {} error(s):", errors.len());
-            for error in &errors {
-                println!("  {}", error);
-            }
-            return Err(BamlRtError::InvalidArgument(format!(
-                "Linting failed with {} error(s)",
-                errors.len()
-            )));
+        Ok(report)
+    }
+}
+
+/// Names of the functions defined in `baml_src`, or `None` when the schema
+/// does not load (the `check` subcommand reports why)
+fn baml_function_names(agent_dir: &AgentDir) -> Option<BTreeSet<String>> {
+    let runtime = baml_runtime::BamlRuntime::from_directory(
+        &agent_dir.baml_src(),
+        HashMap::new(),
+        FeatureFlags::default(),
+    )
+    .ok()?;
+    Some(
+        runtime
+            .ir()
+            .walk_functions()
+            .map(|function| function.name().to_string())
+            .collect(),
+    )
+}
+
+/// Diagnostics from the agent-specific rules for one parsed file
+fn check_agent_rules(
+    file: &Path,
+    content: &str,
+    program: &Program<'_>,
+    baml_functions: Option<&BTreeSet<String>>,
+) -> Vec<LintDiagnostic> {
+    let mut bindings = Bindings::default();
+    bindings.visit_program(program);
+
+    let mut rules = AgentRules {
+        file,
+        content,
+        bindings: bindings.0.into_iter().collect(),
+        baml_functions,
+        function_depth: 0,
+        sets_a2a_handler: false,
+        diagnostics: Vec::new(),
+    };
+    rules.visit_program(program);
+
+    let is_entry = ENTRY_FILES.iter().any(|entry| file == Path::new(entry));
+    if is_entry && !rules.sets_a2a_handler {
+        rules.report(
+            Severity::Warning,
+            MISSING_A2A_HANDLER_RULE,
+            Span::default(),
+            format!(
+                "entry point never sets globalThis.{}, so the agent cannot serve A2A requests",
+                A2A_HANDLER
+            ),
+            Some(format!(
+                "define `{0}` and add `globalThis.{0} = {0};`",
+                A2A_HANDLER
+            )),
+        );
+    }
+    rules.diagnostics
+}
+
+/// Walks one file applying the agent-specific rules
+struct AgentRules<'r> {
+    file: &'r Path,
+    content: &'r str,
+    /// Every name the file binds, in any scope
+    bindings: HashSet<String>,
+    baml_functions: Option<&'r BTreeSet<String>>,
+    /// How many functions enclose the node being visited
+    function_depth: usize,
+    sets_a2a_handler: bool,
+    diagnostics: Vec<LintDiagnostic>,
+}
+
+impl AgentRules<'_> {
+    fn report(
+        &mut self,
+        severity: Severity,
+        rule: &str,
+        span: Span,
+        message: String,
+        suggestion: Option<String>,
+    ) {
+        self.diagnostics.push(LintDiagnostic {
+            severity,
+            rule: rule.to_string(),
+            file: self.file.to_path_buf(),
+            span: LintSpan::new(self.content, span.start as usize, span.end as usize),
+            message,
+            suggestion,
+        });
+    }
+
+    fn report_top_level_await(&mut self, span: Span) {
+        self.report(
+            Severity::Error,
+            TOP_LEVEL_AWAIT_RULE,
+            span,
+            "`await` outside a function; agent scripts are not evaluated as modules".to_string(),
+            Some(
+                "move it into an async function, or wrap it in `(async () => { ... })();`"
+                    .to_string(),
+            ),
+        );
+    }
+
+    /// Whether a capitalized, unbound callee names a BAML function
+    fn is_known_function(&self, functions: &BTreeSet<String>, name: &str) -> bool {
+        functions.contains(name)
+            || name
+                .strip_suffix("Stream")
+                .is_some_and(|base| functions.contains(base))
+    }
+}
+
+impl<'a> Visit<'a> for AgentRules<'_> {
+    fn enter_node(&mut self, kind: AstKind<'a>) {
+        if matches!(
+            kind,
+            AstKind::Function(_) | AstKind::ArrowFunctionExpression(_)
+        ) {
+            self.function_depth += 1;
         }
+    }
 
-        println!("\n✓ All files passed linting");
-        Ok(())
+    fn leave_node(&mut self, kind: AstKind<'a>) {
+        if matches!(
+            kind,
+            AstKind::Function(_) | AstKind::ArrowFunctionExpression(_)
+        ) {
+            self.function_depth -= 1;
+        }
+    }
+
+    fn visit_identifier_reference(&mut self, it: &IdentifierReference<'a>) {
+        let unavailable = UNAVAILABLE_GLOBALS
+            .iter()
+            .find(|(name, _)| it.name == *name);
+        if let Some((name, suggestion)) = unavailable
+            && !self.bindings.contains(*name)
+        {
+            self.report(
+                Severity::Error,
+                UNAVAILABLE_GLOBAL_RULE,
+                it.span,
+                format!("`{}` is not available in the agent sandbox", name),
+                Some(suggestion.to_string()),
+            );
+        }
+    }
+
+    fn visit_await_expression(&mut self, it: &AwaitExpression<'a>) {
+        if self.function_depth == 0 {
+            self.report_top_level_await(it.span);
+        }
+        walk::walk_await_expression(self, it);
+    }
+
+    fn visit_for_of_statement(&mut self, it: &ForOfStatement<'a>) {
+        if it.r#await && self.function_depth == 0 {
+            self.report_top_level_await(it.span);
+        }
+        walk::walk_for_of_statement(self, it);
+    }
+
+    fn visit_assignment_expression(&mut self, it: &AssignmentExpression<'a>) {
+        if let AssignmentTarget::StaticMemberExpression(member) = &it.left
+            && let Expression::Identifier(object) = &member.object
+            && object.name == "globalThis"
+            && member.property.name == A2A_HANDLER
+        {
+            self.sets_a2a_handler = true;
+        }
+        walk::walk_assignment_expression(self, it);
+    }
+
+    fn visit_call_expression(&mut self, it: &CallExpression<'a>) {
+        if let Expression::Identifier(callee) = &it.callee
+            && let Some(functions) = self.baml_functions
+            && callee.name.starts_with(|c: char| c.is_ascii_uppercase())
+            && !CALLABLE_BUILTINS.contains(&callee.name.as_str())
+            && !self.bindings.contains(callee.name.as_str())
+            && !self.is_known_function(functions, &callee.name)
+        {
+            let known = functions.iter().cloned().collect::<Vec<_>>().join(", ");
+            self.report(
+                Severity::Error,
+                UNKNOWN_BAML_FUNCTION_RULE,
+                callee.span,
+                format!("`{}` is not a function in baml_src", callee.name),
+                Some(format!("functions in baml_src: {}", known)),
+            );
+        }
+        walk::walk_call_expression(self, it);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::filesystem::StdFileSystem;
+
+    #[tokio::test]
+    async fn syntax_errors_are_reported_with_their_span() {
+        let root = tempfile::TempDir::new().unwrap();
+        let src = root.path().join("src");
+        fs::create_dir_all(&src).unwrap();
+        fs::create_dir_all(root.path().join("baml_src")).unwrap();
+        fs::write(src.join("good.ts"), "export const ok = 1;\n").unwrap();
+        fs::write(src.join("bad.ts"), "const a = 1;\nconst = 2;\n").unwrap();
+        let agent_dir = AgentDir::new(root.path().to_path_buf()).unwrap();
+
+        let report = OxcLinter::new(StdFileSystem)
+            .lint(&agent_dir)
+            .await
+            .unwrap();
+        assert_eq!(report.files.len(), 2);
+        assert!(report.error_count() >= 1);
+        let diagnostic = &report.diagnostics[0];
+        assert_eq!(diagnostic.rule, SYNTAX_RULE);
+        assert_eq!(diagnostic.file, PathBuf::from("src/bad.ts"));
+        assert_eq!(diagnostic.span.line, 2);
+
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["diagnostics"][0]["severity"], "error");
+        assert_eq!(json["diagnostics"][0]["span"]["line"], 2);
+        assert!(report.into_result().is_err());
+    }
+
+    fn rules_for(file: &str, source: &str, functions: &[&str]) -> Vec<LintDiagnostic> {
+        use oxc_allocator::Allocator;
+        use oxc_parser::Parser;
+
+        let allocator = Allocator::default();
+        let source_type = oxc_span::SourceType::from_path(file).unwrap();
+        let parsed = Parser::new(&allocator, source, source_type).parse();
+        assert!(parsed.errors.is_empty(), "{:?}", parsed.errors);
+        let functions: BTreeSet<String> = functions.iter().map(|f| f.to_string()).collect();
+        check_agent_rules(Path::new(file), source, &parsed.program, Some(&functions))
+    }
+
+    fn rules(diagnostics: &[LintDiagnostic]) -> Vec<(&str, usize)> {
+        diagnostics
+            .iter()
+            .map(|d| (d.rule.as_str(), d.span.line))
+            .collect()
+    }
+
+    #[test]
+    fn sandbox_pitfalls_are_flagged() {
+        let source = r#"const fs = require("fs");
+const config = await loadConfig();
+async function loadConfig() {
+  const res = await fetch(process.env.URL);
+  return Greet({ name: "a" });
+}
+Greeet({ name: "b" });
+"#;
+        let diagnostics = rules_for("src/tools.ts", source, &["Greet"]);
+        assert_eq!(
+            rules(&diagnostics),
+            [
+                (UNAVAILABLE_GLOBAL_RULE, 1),
+                (TOP_LEVEL_AWAIT_RULE, 2),
+                (UNAVAILABLE_GLOBAL_RULE, 4),
+                (UNAVAILABLE_GLOBAL_RULE, 4),
+                (UNKNOWN_BAML_FUNCTION_RULE, 7),
+            ]
+        );
+        assert_eq!(
+            diagnostics[4].suggestion.as_deref(),
+            Some("functions in baml_src: Greet")
+        );
+    }
+
+    #[test]
+    fn supported_patterns_pass() {
+        let source = r#"import { fetch } from "./http";
+(async () => {
+  for await (const chunk of GreetStream({ name: "a" })) {
+    console.log(String(chunk), Helper());
+  }
+})();
+function Helper() { return fetch("x"); }
+async function handle_a2a_request(request: any) {
+  return await Greet(request);
+}
+globalThis.handle_a2a_request = handle_a2a_request;
+"#;
+        assert!(rules_for("src/index.ts", source, &["Greet"]).is_empty());
+    }
+
+    #[test]
+    fn entry_without_a2a_handler_is_warned_about() {
+        let diagnostics = rules_for("src/index.ts", "globalThis.greet = () => 1;\n", &[]);
+        assert_eq!(rules(&diagnostics), [(MISSING_A2A_HANDLER_RULE, 1)]);
+        assert_eq!(diagnostics[0].severity, Severity::Warning);
+        assert!(rules_for("src/helpers.ts", "export const a = 1;\n", &[]).is_empty());
     }
 }
@@ -3,18 +3,41 @@
 //! Provides production-grade abstractions for building, linting, and packaging
 //! BAML agent applications.
 
+pub mod bundler;
+pub mod checker;
 pub mod compiler;
+pub mod declarations;
+pub mod dev_agent;
+pub mod eval;
 pub mod filesystem;
 pub mod linter;
+pub mod npm;
 pub mod packager;
+pub mod registry;
+pub mod scaffold;
 pub mod service;
+pub mod test_runner;
 pub mod traits;
 pub mod types;
+pub mod watch;
 
+pub use bundler::{Bundle, Bundler};
+pub use checker::{SchemaChecker, SchemaDiagnostic, Severity};
 pub use compiler::{OxcTypeScriptCompiler, RuntimeTypeGenerator};
+pub use declarations::BamlDeclarations;
+pub use dev_agent::{DevAgent, SourceSnapshot, watch_sources};
+pub use eval::{
+    EvalCaller, EvalCase, EvalCaseResult, EvalDataset, EvalMetrics, EvalReport, EvalTarget,
+    Evaluator, ExactMatch, JsonFields, LlmJudge, Score, Scorer,
+};
 pub use filesystem::StdFileSystem;
-pub use linter::OxcLinter;
+pub use linter::{LintDiagnostic, LintReport, LintSpan, OxcLinter};
+pub use npm::NpmPolicy;
 pub use packager::StdPackager;
+pub use registry::{PushedPackage, push_package};
+pub use scaffold::AgentScaffold;
 pub use service::BuilderService;
+pub use test_runner::{AgentTestRunner, TestCaseReport, TestReport, TestSuiteReport};
 pub use traits::{FileSystem, Linter, Packager, TypeGenerator, TypeScriptCompiler};
 pub use types::{AgentDir, BuildDir, FunctionName, PackagePath};
+pub use watch::{DevWatcher, Rebuild};
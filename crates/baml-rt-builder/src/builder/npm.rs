@@ -0,0 +1,309 @@
+//! npm dependencies for the bundler
+//!
+//! Bare imports such as `zod` or `date-fns/format` resolve into the nearest
+//! `node_modules`, the way Node looks them up, but only for packages listed
+//! under `bundle.dependencies` in the agent's manifest. QuickJS has no module
+//! loader or native addons, so a package must ship ES modules and plain
+//! JavaScript: its entry is taken from the `import` or `module` condition of
+//! `exports`, the `module` field, or `main` when the package declares
+//! `"type": "module"`. The total package source read is capped by
+//! `bundle.max_dependency_bytes` so a stray import cannot balloon the agent.
+
+use crate::builder::bundler::{normalize, resolve_file};
+use crate::builder::traits::FileSystem;
+use baml_rt_core::manifest::{
+    AgentManifest, DEFAULT_MAX_DEPENDENCY_BYTES, MANIFEST_FILE, ManifestBundle,
+};
+use baml_rt_core::{BamlRtError, Result};
+use serde_json::Value;
+use std::collections::BTreeSet;
+use std::path::{Component, Path, PathBuf};
+
+const NODE_MODULES: &str = "node_modules";
+
+/// `exports` conditions that select an ES module build, in preference order
+const ESM_CONDITIONS: [&str; 2] = ["import", "module"];
+
+/// Which npm packages a bundle may include, and how much of them
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NpmPolicy {
+    allowed: BTreeSet<String>,
+    max_bytes: u64,
+}
+
+impl Default for NpmPolicy {
+    fn default() -> Self {
+        Self {
+            allowed: BTreeSet::new(),
+            max_bytes: DEFAULT_MAX_DEPENDENCY_BYTES,
+        }
+    }
+}
+
+impl NpmPolicy {
+    pub fn from_manifest(bundle: &ManifestBundle) -> Self {
+        Self {
+            allowed: bundle.dependencies.iter().cloned().collect(),
+            max_bytes: bundle.max_dependency_bytes,
+        }
+    }
+
+    /// The policy in the manifest beside `src_dir`; none is allowed when the
+    /// agent has no manifest
+    pub fn for_sources(src_dir: &Path) -> Result<Self> {
+        let manifest = src_dir
+            .parent()
+            .map(|agent_dir| agent_dir.join(MANIFEST_FILE))
+            .filter(|path| path.is_file());
+        match manifest {
+            Some(path) => Ok(Self::from_manifest(&AgentManifest::load(&path)?.bundle)),
+            None => Ok(Self::default()),
+        }
+    }
+
+    pub fn max_bytes(&self) -> u64 {
+        self.max_bytes
+    }
+
+    /// Resolve the bare `specifier` imported by `importer` to a file
+    pub fn resolve<FS: FileSystem>(
+        &self,
+        filesystem: &FS,
+        importer: &Path,
+        specifier: &str,
+    ) -> Result<PathBuf> {
+        let cannot = |reason: String| {
+            BamlRtError::InvalidArgument(format!(
+                "Cannot bundle import '{}' in {}: {}",
+                specifier,
+                importer.display(),
+                reason
+            ))
+        };
+        let (name, subpath) = split_specifier(specifier)
+            .ok_or_else(|| cannot("not a relative import or an npm package".to_string()))?;
+        if !self.allowed.contains(name) {
+            return Err(cannot(format!(
+                "add '{}' to bundle.dependencies in {} to bundle it from node_modules",
+                name, MANIFEST_FILE
+            )));
+        }
+
+        let package_dir = importer
+            .ancestors()
+            .skip(1)
+            .map(|dir| dir.join(NODE_MODULES).join(name))
+            .find(|dir| dir.join("package.json").is_file())
+            .ok_or_else(|| cannot(format!("'{}' is not installed in node_modules", name)))?;
+        let package_json = filesystem.read_to_string(&package_dir.join("package.json"))?;
+        let package: Value = serde_json::from_str(&package_json)
+            .map_err(|err| cannot(format!("{}/package.json is invalid: {}", name, err)))?;
+        if package.get("gypfile").and_then(Value::as_bool) == Some(true)
+            || package.get("binary").is_some()
+        {
+            return Err(cannot(format!(
+                "'{}' is a native addon; only pure-JavaScript packages can be bundled",
+                name
+            )));
+        }
+
+        let is_module_package = package.get("type").and_then(Value::as_str) == Some("module");
+        let (target, esm) = match package.get("exports") {
+            Some(exports) => export_target(exports, &subpath)
+                .ok_or_else(|| cannot(format!("'{}' does not export '{}'", name, subpath)))?,
+            None if subpath != "." => (subpath.clone(), false),
+            None => ["module", "main"]
+                .iter()
+                .find_map(|field| package.get(*field).and_then(Value::as_str))
+                .map(|entry| (entry.to_string(), package.get("module").is_some()))
+                .unwrap_or_else(|| ("index.js".to_string(), false)),
+        };
+
+        let path = resolve_file(&normalize(&package_dir.join(&target)))
+            .ok_or_else(|| cannot(format!("'{}' has no file {}", name, target)))?;
+        let extension = path.extension().and_then(|ext| ext.to_str());
+        let is_esm = match extension {
+            Some("mjs") => true,
+            Some("js") => esm || is_module_package,
+            _ => false,
+        };
+        if !is_esm {
+            return Err(cannot(format!(
+                "'{}' resolves to {}, which is not an ES module; only ESM packages can be bundled",
+                name,
+                path.display()
+            )));
+        }
+        Ok(path)
+    }
+}
+
+/// Whether `path` lies inside a `node_modules` directory
+pub(crate) fn is_dependency(path: &Path) -> bool {
+    path.components()
+        .any(|component| component.as_os_str() == NODE_MODULES)
+}
+
+/// `node_modules/<package>/<file>` for a path inside `node_modules`
+pub(crate) fn dependency_id(path: &Path) -> Option<PathBuf> {
+    let components: Vec<Component<'_>> = path.components().collect();
+    let last = components
+        .iter()
+        .rposition(|component| component.as_os_str() == NODE_MODULES)?;
+    Some(components[last..].iter().collect())
+}
+
+/// Split `@scope/name/sub/path` into the package name and a `./sub/path`
+/// export key
+fn split_specifier(specifier: &str) -> Option<(&str, String)> {
+    let name_len = if specifier.starts_with('@') {
+        let scope = specifier.find('/')?;
+        specifier[scope + 1..]
+            .find('/')
+            .map_or(specifier.len(), |end| scope + 1 + end)
+    } else {
+        specifier.find('/').unwrap_or(specifier.len())
+    };
+    let (name, rest) = specifier.split_at(name_len);
+    if name.is_empty() || name.ends_with('/') || name.starts_with('.') {
+        return None;
+    }
+    Some((name, format!(".{}", rest)))
+}
+
+/// The target `exports` maps `subpath` to, and whether an ESM condition
+/// chose it
+fn export_target(exports: &Value, subpath: &str) -> Option<(String, bool)> {
+    let map = match exports {
+        Value::Object(map) if map.keys().any(|key| key.starts_with('.')) => map,
+        // Sugar for `{ ".": exports }`
+        _ if subpath == "." => return conditional_target(exports, false),
+        _ => return None,
+    };
+    if let Some(entry) = map.get(subpath) {
+        return conditional_target(entry, false);
+    }
+    // Subpath patterns: "./*": "./dist/*.mjs"
+    map.iter().find_map(|(key, entry)| {
+        let (prefix, suffix) = key.split_once('*')?;
+        let matched = subpath.strip_prefix(prefix)?.strip_suffix(suffix)?;
+        let (target, esm) = conditional_target(entry, false)?;
+        Some((target.replace('*', matched), esm))
+    })
+}
+
+fn conditional_target(entry: &Value, esm: bool) -> Option<(String, bool)> {
+    match entry {
+        Value::String(target) => Some((target.clone(), esm)),
+        Value::Array(targets) => targets
+            .iter()
+            .find_map(|target| conditional_target(target, esm)),
+        Value::Object(conditions) => ESM_CONDITIONS
+            .iter()
+            .find_map(|condition| conditions.get(*condition))
+            .and_then(|target| conditional_target(target, true))
+            .or_else(|| {
+                conditions
+                    .get("default")
+                    .and_then(|target| conditional_target(target, esm))
+            }),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::filesystem::StdFileSystem;
+    use serde_json::json;
+
+    fn write(dir: &Path, relative: &str, contents: &str) {
+        let path = dir.join(relative);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, contents).unwrap();
+    }
+
+    fn allowing(packages: &[&str]) -> NpmPolicy {
+        NpmPolicy::from_manifest(&ManifestBundle {
+            dependencies: packages.iter().map(|p| p.to_string()).collect(),
+            ..ManifestBundle::default()
+        })
+    }
+
+    #[test]
+    fn specifiers_split_into_package_and_subpath() {
+        assert_eq!(split_specifier("zod"), Some(("zod", ".".to_string())));
+        assert_eq!(
+            split_specifier("date-fns/format"),
+            Some(("date-fns", "./format".to_string()))
+        );
+        assert_eq!(
+            split_specifier("@scope/pkg/a/b"),
+            Some(("@scope/pkg", "./a/b".to_string()))
+        );
+        assert_eq!(split_specifier("@scope"), None);
+    }
+
+    #[test]
+    fn exports_prefer_esm_conditions_and_expand_patterns() {
+        let exports = json!({
+            ".": { "require": "./index.cjs", "import": "./index.mjs" },
+            "./locale/*": { "default": "./locale/*.js" }
+        });
+        assert_eq!(
+            export_target(&exports, "."),
+            Some(("./index.mjs".to_string(), true))
+        );
+        assert_eq!(
+            export_target(&exports, "./locale/fr"),
+            Some(("./locale/fr.js".to_string(), false))
+        );
+        assert_eq!(export_target(&exports, "./missing"), None);
+    }
+
+    #[test]
+    fn only_allowed_esm_packages_resolve() {
+        let dir = tempfile::TempDir::new().unwrap();
+        write(
+            dir.path(),
+            "node_modules/esm-lib/package.json",
+            r#"{ "type": "module", "main": "lib/index.js" }"#,
+        );
+        write(
+            dir.path(),
+            "node_modules/esm-lib/lib/index.js",
+            "export {};",
+        );
+        write(
+            dir.path(),
+            "node_modules/cjs-lib/package.json",
+            r#"{ "main": "index.js" }"#,
+        );
+        write(
+            dir.path(),
+            "node_modules/cjs-lib/index.js",
+            "module.exports = {};",
+        );
+        let importer = dir.path().join("src/index.ts");
+        let policy = allowing(&["esm-lib", "cjs-lib"]);
+
+        let resolved = policy
+            .resolve(&StdFileSystem, &importer, "esm-lib")
+            .unwrap();
+        assert!(resolved.ends_with("node_modules/esm-lib/lib/index.js"));
+        assert_eq!(
+            dependency_id(&resolved),
+            Some(PathBuf::from("node_modules/esm-lib/lib/index.js"))
+        );
+
+        let error = |policy: &NpmPolicy, specifier: &str| {
+            policy
+                .resolve(&StdFileSystem, &importer, specifier)
+                .unwrap_err()
+                .to_string()
+        };
+        assert!(error(&policy, "cjs-lib").contains("not an ES module"));
+        assert!(error(&allowing(&[]), "esm-lib").contains("bundle.dependencies"));
+        assert!(error(&allowing(&["absent"]), "absent").contains("not installed"));
+    }
+}
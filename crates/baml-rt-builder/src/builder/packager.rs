@@ -1,13 +1,30 @@
 //! Packager implementation for creating tar.gz agent packages
+//!
+//! Packages are reproducible: the same inputs give a byte-identical archive.
+//! Entries are written in path order with fixed modes, owners, and mtimes,
+//! the gzip header carries no timestamp or file name, and the packaged
+//! `manifest.json` records a digest of every other entry in `content_digest`,
+//! so signatures and attestations can cover the archive or its contents.
 
 use crate::builder::traits::{FileSystem, Packager};
 use crate::builder::types::{AgentDir, BuildDir};
+use baml_rt_core::manifest::{AgentManifest, MANIFEST_FILE};
 use baml_rt_core::{BamlRtError, Result};
-use flate2::Compression;
-use flate2::write::GzEncoder;
+use flate2::{Compression, GzBuilder};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::Path;
-use tar::{Builder, Header};
+use tar::{Builder, EntryType, Header};
+
+/// Modification time of every entry: the Unix epoch
+const ENTRY_MTIME: u64 = 0;
+
+/// Gzip level, pinned so a change of library default cannot change output
+const GZIP_LEVEL: u32 = 6;
+
+/// Prefix of `content_digest` values
+pub const DIGEST_PREFIX: &str = "sha256:";
 
 /// Standard packager implementation
 pub struct StdPackager<FS> {
@@ -32,60 +49,91 @@ impl<FS: FileSystem> Packager for StdPackager<FS> {
             fs::create_dir_all(parent).map_err(BamlRtError::Io)?;
         }
 
-        let tar_gz = fs::File::create(output).map_err(BamlRtError::Io)?;
-        let enc = GzEncoder::new(tar_gz, Compression::default());
-        let mut tar = Builder::new(enc);
-
-        // Add manifest.json
-        let manifest_path = agent_dir.as_path().join("manifest.json");
-        if manifest_path.exists() {
-            let content = fs::read_to_string(&manifest_path).map_err(BamlRtError::Io)?;
-            let mut header = Header::new_gnu();
-            header
-                .set_path("manifest.json")
-                .map_err(BamlRtError::TarHeaderPath)?;
-            header.set_size(content.len() as u64);
-            header.set_cksum();
-            tar.append(&header, content.as_bytes())
-                .map_err(BamlRtError::Io)?;
-        }
+        // Archive path -> content, so entries are written in a stable order
+        let mut entries: BTreeMap<String, Vec<u8>> = BTreeMap::new();
 
         // Add package.json if it exists
         let package_json_path = agent_dir.as_path().join("package.json");
         if package_json_path.exists() {
-            let content = fs::read_to_string(&package_json_path).map_err(BamlRtError::Io)?;
-            let mut header = Header::new_gnu();
-            header
-                .set_path("package.json")
-                .map_err(BamlRtError::TarHeaderPath)?;
-            header.set_size(content.len() as u64);
-            header.set_cksum();
-            tar.append(&header, content.as_bytes())
-                .map_err(BamlRtError::Io)?;
+            let content = fs::read(&package_json_path).map_err(BamlRtError::Io)?;
+            entries.insert("package.json".to_string(), content);
         }
 
         // Add baml_src (required - runtime loads from this)
         let baml_src_build = build_dir.join("baml_src");
         if baml_src_build.exists() {
-            add_directory_to_tar(&mut tar, &baml_src_build, "baml_src", &self.filesystem)?;
+            collect_directory(&mut entries, &baml_src_build, "baml_src")?;
         }
 
         // Add dist
         let dist_build = build_dir.join("dist");
         if dist_build.exists() {
-            add_directory_to_tar(&mut tar, &dist_build, "dist", &self.filesystem)?;
+            collect_directory(&mut entries, &dist_build, "dist")?;
         }
 
-        tar.finish().map_err(BamlRtError::Io)?;
+        // Add manifest.json, refusing to package one the runner would reject
+        let manifest_path = agent_dir.as_path().join(MANIFEST_FILE);
+        if manifest_path.exists() {
+            let content = self.filesystem.read_to_string(&manifest_path)?;
+            let mut manifest = AgentManifest::from_json(&content)?;
+            manifest.content_digest = Some(content_digest(&entries));
+            let mut content = serde_json::to_string_pretty(&manifest)?;
+            content.push('\n');
+            entries.insert(MANIFEST_FILE.to_string(), content.into_bytes());
+        }
+
+        let tar_gz = fs::File::create(output).map_err(BamlRtError::Io)?;
+        let encoder = GzBuilder::new()
+            .mtime(0)
+            .write(tar_gz, Compression::new(GZIP_LEVEL));
+        let mut tar = Builder::new(encoder);
+        for (path, content) in &entries {
+            tar.append(&entry_header(path, content.len())?, content.as_slice())
+                .map_err(BamlRtError::Io)?;
+        }
+        tar.into_inner()
+            .and_then(|encoder| encoder.finish())
+            .map_err(BamlRtError::Io)?;
         Ok(())
     }
 }
 
-fn add_directory_to_tar<FS: FileSystem>(
-    tar: &mut Builder<GzEncoder<fs::File>>,
+/// `sha256:<hex>` over every entry but the manifest, in path order, each
+/// hashed as its path, a NUL byte, its length as a little-endian `u64`, and
+/// its content
+pub fn content_digest(entries: &BTreeMap<String, Vec<u8>>) -> String {
+    let mut hasher = Sha256::new();
+    for (path, content) in entries {
+        if path == MANIFEST_FILE {
+            continue;
+        }
+        hasher.update(path.as_bytes());
+        hasher.update([0]);
+        hasher.update((content.len() as u64).to_le_bytes());
+        hasher.update(content);
+    }
+    format!("{}{:x}", DIGEST_PREFIX, hasher.finalize())
+}
+
+/// A regular-file header that does not depend on who built the package, or when
+fn entry_header(path: &str, size: usize) -> Result<Header> {
+    let mut header = Header::new_gnu();
+    header.set_path(path).map_err(BamlRtError::TarHeaderPath)?;
+    header.set_entry_type(EntryType::Regular);
+    header.set_size(size as u64);
+    header.set_mode(0o644);
+    header.set_uid(0);
+    header.set_gid(0);
+    header.set_mtime(ENTRY_MTIME);
+    header.set_cksum();
+    Ok(header)
+}
+
+/// Add every file under `dir` to `entries`, below `prefix`
+fn collect_directory(
+    entries: &mut BTreeMap<String, Vec<u8>>,
     dir: &Path,
     prefix: &str,
-    _filesystem: &FS,
 ) -> Result<()> {
     // Recursively collect all files in the directory
     fn collect_all_files(
@@ -108,7 +156,7 @@ fn add_directory_to_tar<FS: FileSystem>(
     collect_all_files(dir, &mut files).map_err(BamlRtError::Io)?;
 
     for file_path in files {
-        let content = fs::read_to_string(&file_path).map_err(BamlRtError::Io)?;
+        let content = fs::read(&file_path).map_err(BamlRtError::Io)?;
         let relative_path = file_path.strip_prefix(dir).map_err(|_| {
             BamlRtError::InvalidArgument(format!(
                 "File {} is not under directory {}",
@@ -117,16 +165,81 @@ fn add_directory_to_tar<FS: FileSystem>(
             ))
         })?;
 
-        let tar_path = format!("{}/{}", prefix, relative_path.display());
-        let mut header = Header::new_gnu();
-        header
-            .set_path(&tar_path)
-            .map_err(BamlRtError::TarHeaderPath)?;
-        header.set_size(content.len() as u64);
-        header.set_cksum();
-        tar.append(&header, content.as_bytes())
-            .map_err(BamlRtError::Io)?;
+        // Archive paths use `/` whatever the host separator
+        let relative = relative_path
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        entries.insert(format!("{}/{}", prefix, relative), content);
     }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::filesystem::StdFileSystem;
+
+    fn write(dir: &Path, relative: &str, contents: &str) {
+        let path = dir.join(relative);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, contents).unwrap();
+    }
+
+    #[tokio::test]
+    async fn identical_inputs_give_identical_archives() {
+        let root = tempfile::TempDir::new().unwrap();
+        let agent = root.path().join("agent");
+        write(&agent, "baml_src/main.baml", "");
+        write(
+            &agent,
+            MANIFEST_FILE,
+            r#"{ "name": "agent", "version": "1.0.0" }"#,
+        );
+        let agent_dir = AgentDir::new(agent.clone()).unwrap();
+
+        let mut archives = Vec::new();
+        for build in ["build-a", "build-b"] {
+            let build_dir = BuildDir::at(root.path().join(build)).unwrap();
+            write(build_dir.as_path(), "baml_src/main.baml", "");
+            write(build_dir.as_path(), "dist/index.js", "globalThis.a = 1;\n");
+            write(build_dir.as_path(), "dist/lib/b.js", "export {};\n");
+            let output = root.path().join(format!("{}.tar.gz", build));
+            StdPackager::new(StdFileSystem)
+                .package(&agent_dir, &build_dir, &output)
+                .await
+                .unwrap();
+            archives.push(fs::read(output).unwrap());
+        }
+        assert_eq!(archives[0], archives[1]);
+
+        let mut archive = tar::Archive::new(flate2::read::GzDecoder::new(&archives[0][..]));
+        let mut paths = Vec::new();
+        let mut manifest = None;
+        for entry in archive.entries().unwrap() {
+            let mut entry = entry.unwrap();
+            assert_eq!(entry.header().mtime().unwrap(), ENTRY_MTIME);
+            let path = entry.path().unwrap().to_string_lossy().into_owned();
+            if path == MANIFEST_FILE {
+                let mut content = String::new();
+                std::io::Read::read_to_string(&mut entry, &mut content).unwrap();
+                manifest = Some(AgentManifest::from_json(&content).unwrap());
+            }
+            paths.push(path);
+        }
+        assert_eq!(
+            paths,
+            [
+                "baml_src/main.baml",
+                "dist/index.js",
+                "dist/lib/b.js",
+                MANIFEST_FILE
+            ]
+        );
+        let digest = manifest.unwrap().content_digest.unwrap();
+        assert!(digest.starts_with(DIGEST_PREFIX));
+        assert_eq!(digest.len(), DIGEST_PREFIX.len() + 64);
+    }
+}
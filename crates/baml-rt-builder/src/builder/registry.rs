@@ -0,0 +1,126 @@
+//! Publishing agent packages to OCI registries
+//!
+//! A package is pushed as an OCI artifact with an empty config and a single
+//! layer, the tar.gz itself, typed [`PACKAGE_MEDIA_TYPE`]. The artifact's
+//! manifest is annotated with the agent's name and version from its
+//! `manifest.json`. Credentials come from [`registry_auth`]; runners load the artifact back with an
+//! `oci://` reference.
+
+use crate::builder::types::PackagePath;
+use baml_rt_core::manifest::{AgentManifest, MANIFEST_FILE, PACKAGE_MEDIA_TYPE};
+use baml_rt_core::registry::{parse_reference, registry_auth, registry_error};
+use baml_rt_core::{BamlRtError, Result};
+use oci_client::Client;
+use oci_client::client::{ClientConfig, Config, ImageLayer};
+use oci_client::manifest::OciImageManifest;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::io::Read;
+use std::path::Path;
+
+/// Media type of the empty config of a package artifact
+const EMPTY_CONFIG_MEDIA_TYPE: &str = "application/vnd.oci.empty.v1+json";
+
+/// Where a package was pushed
+#[derive(Debug, Clone)]
+pub struct PushedPackage {
+    pub manifest: AgentManifest,
+    /// Digest of the package layer
+    pub layer_digest: String,
+    pub manifest_url: String,
+}
+
+/// Push the package at `package` to the registry `reference` names, e.g.
+/// `ghcr.io/acme/support-agent:1.2.0`
+pub async fn push_package(package: &PackagePath, reference: &str) -> Result<PushedPackage> {
+    let reference = parse_reference(reference)?;
+    let archive = std::fs::read(package.as_path())?;
+    let manifest = packaged_manifest(&archive)?;
+    let layer_digest = format!("sha256:{:x}", Sha256::digest(&archive));
+
+    let layers = vec![ImageLayer::new(
+        archive,
+        PACKAGE_MEDIA_TYPE.to_string(),
+        None,
+    )];
+    let config = Config::new(b"{}".to_vec(), EMPTY_CONFIG_MEDIA_TYPE.to_string(), None);
+    let annotations = BTreeMap::from([
+        (
+            "org.opencontainers.image.title".to_string(),
+            manifest.name.clone(),
+        ),
+        (
+            "org.opencontainers.image.version".to_string(),
+            manifest.version.clone(),
+        ),
+    ]);
+    let image_manifest = OciImageManifest::build(&layers, &config, Some(annotations));
+
+    let client = Client::new(ClientConfig::default());
+    let response = client
+        .push(
+            &reference,
+            &layers,
+            config,
+            &registry_auth(),
+            Some(image_manifest),
+        )
+        .await
+        .map_err(registry_error)?;
+    tracing::info!(%reference, layer_digest = layer_digest.as_str(), "Pushed agent package");
+
+    Ok(PushedPackage {
+        manifest,
+        layer_digest,
+        manifest_url: response.manifest_url,
+    })
+}
+
+/// The `manifest.json` of a tar.gz package
+fn packaged_manifest(archive: &[u8]) -> Result<AgentManifest> {
+    let mut tar = tar::Archive::new(flate2::read::GzDecoder::new(archive));
+    for entry in tar.entries()? {
+        let mut entry = entry?;
+        if entry.path()?.as_ref() == Path::new(MANIFEST_FILE) {
+            let mut content = String::new();
+            entry.read_to_string(&mut content)?;
+            return AgentManifest::from_json(&content);
+        }
+    }
+    Err(BamlRtError::InvalidArgument(format!(
+        "Package has no {}",
+        MANIFEST_FILE
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tar_gz(entries: &[(&str, &str)]) -> Vec<u8> {
+        let gz = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        let mut tar = tar::Builder::new(gz);
+        for (name, content) in entries {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(content.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            tar.append_data(&mut header, name, content.as_bytes())
+                .unwrap();
+        }
+        tar.into_inner().unwrap().finish().unwrap()
+    }
+
+    #[test]
+    fn packages_are_annotated_from_their_manifest() {
+        let manifest = r#"{"name": "support", "version": "1.2.0"}"#;
+        let archive = tar_gz(&[("baml_src/main.baml", ""), (MANIFEST_FILE, manifest)]);
+        let manifest = packaged_manifest(&archive).unwrap();
+        assert_eq!(
+            (manifest.name.as_str(), manifest.version.as_str()),
+            ("support", "1.2.0")
+        );
+
+        assert!(packaged_manifest(&tar_gz(&[("baml_src/main.baml", "")])).is_err());
+    }
+}
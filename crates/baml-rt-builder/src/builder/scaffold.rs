@@ -0,0 +1,168 @@
+//! Agent project scaffolding
+//!
+//! Writes a minimal, buildable agent project: a BAML function in `baml_src`,
+//! an A2A entry point in `src/index.ts`, `manifest.json`, `tsconfig.json`, and
+//! a test suite that runs under `baml-agent-builder test`.
+
+use crate::builder::traits::FileSystem;
+use baml_rt_core::manifest::AgentManifest;
+use baml_rt_core::{BamlRtError, Result};
+use std::path::{Path, PathBuf};
+
+const BAML_SRC: &str = r##"// Sample BAML function called from src/index.ts
+function Reply(message: string) -> string {
+  client DefaultClient
+  prompt #"
+    You are a helpful agent. Reply briefly to the following message.
+
+    {{ _.role('user') }}
+    {{ message }}
+  "#
+}
+
+client DefaultClient {
+  provider openai-generic
+  options {
+    model "openai/gpt-4o-mini"
+    base_url "https://openrouter.ai/api/v1"
+    api_key env.OPENROUTER_API_KEY
+    // Set per call by the runtime so provider logs can be joined with traces
+    headers {
+      "x-correlation-id" env.BAML_CORRELATION_ID
+    }
+  }
+}
+"##;
+
+const INDEX_TS: &str = r#"// Agent entry point. Runs in the QuickJS sandbox; BAML functions such as
+// Reply are available as globals.
+
+function extractText(params: any): string {
+  const parts = params && params.message && params.message.parts;
+  if (Array.isArray(parts)) {
+    for (const part of parts) {
+      if (part && typeof part.text === "string") {
+        return part.text;
+      }
+    }
+  }
+  return typeof params?.text === "string" ? params.text : "";
+}
+
+function agentMessage(messageId: string, text: string) {
+  return {
+    messageId,
+    role: "ROLE_AGENT",
+    parts: [{ text }],
+  };
+}
+
+async function handle_a2a_request(request: any) {
+  const method = request && request.method;
+  const params = (request && request.params) || {};
+  const messageId = params.message?.messageId ?? "msg-1";
+
+  if (method === "message.send" || method === "message.sendStream") {
+    const reply = await Reply({ message: extractText(params) });
+    return { message: agentMessage(`resp-${messageId}`, reply) };
+  }
+
+  return {
+    message: agentMessage(`resp-${messageId}`, `Unsupported method: ${method}`),
+  };
+}
+
+globalThis.handle_a2a_request = handle_a2a_request;
+"#;
+
+const TEST_TS: &str = r#"// Run with `baml-agent-builder test`. BAML calls are answered by mocks.
+
+test("replies to message.send", async () => {
+  mockBaml("Reply", (args: { message: string }) => `You said: ${args.message}`);
+  const response = await handle_a2a_request({
+    method: "message.send",
+    params: { message: { messageId: "m1", role: "ROLE_USER", parts: [{ text: "hi" }] } },
+  });
+  expect(response.message.parts[0].text).toBe("You said: hi");
+});
+"#;
+
+const TSCONFIG: &str = r#"{
+  "compilerOptions": {
+    "target": "ES2020",
+    "module": "ES2020",
+    "lib": ["ES2020"],
+    "outDir": "./dist",
+    "rootDir": "./src",
+    "strict": false,
+    "skipLibCheck": true,
+    "moduleResolution": "node"
+  },
+  "include": ["src/**/*"],
+  "exclude": ["node_modules", "dist"]
+}
+"#;
+
+const GITIGNORE: &str = "dist/\nnode_modules/\n*.tar.gz\n";
+
+/// A new agent project, written by [`AgentScaffold::write`]
+#[derive(Debug, Clone)]
+pub struct AgentScaffold {
+    name: String,
+}
+
+impl AgentScaffold {
+    /// Create a scaffold, validating that `name` is usable as a package name
+    pub fn new(name: impl Into<String>) -> Result<Self> {
+        let name = name.into();
+        let valid = name.chars().next().is_some_and(|c| c.is_ascii_lowercase())
+            && name
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_');
+        if !valid {
+            return Err(BamlRtError::InvalidArgument(format!(
+                "Invalid agent name '{}': use lowercase letters, digits, '-' or '_', starting with a letter",
+                name
+            )));
+        }
+        Ok(Self { name })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Write the project into `parent/<name>`, which must not already exist
+    pub fn write<F: FileSystem>(&self, filesystem: &F, parent: &Path) -> Result<PathBuf> {
+        let root = parent.join(&self.name);
+        if root.exists() {
+            return Err(BamlRtError::InvalidArgument(format!(
+                "Directory already exists: {}",
+                root.display()
+            )));
+        }
+
+        let mut manifest = AgentManifest::new(&self.name, "0.1.0");
+        manifest.description = Some(format!("{} agent", self.name));
+        manifest.runtime_version = Some(env!("CARGO_PKG_VERSION").to_string());
+        let manifest = format!("{}\n", serde_json::to_string_pretty(&manifest)?);
+
+        let files: [(&str, &str); 6] = [
+            ("baml_src/main.baml", BAML_SRC),
+            ("src/index.ts", INDEX_TS),
+            ("tests/agent.test.ts", TEST_TS),
+            ("manifest.json", &manifest),
+            ("tsconfig.json", TSCONFIG),
+            (".gitignore", GITIGNORE),
+        ];
+        for (relative, contents) in files {
+            let path = root.join(relative);
+            if let Some(dir) = path.parent() {
+                filesystem.create_dir_all(dir)?;
+            }
+            filesystem.write_string(&path, contents)?;
+        }
+
+        Ok(root)
+    }
+}
@@ -39,7 +39,9 @@ where
         // Stage 1: Lint (if enabled)
         if lint {
             println!("\n🔍 Linting source code...");
-            self.linter.lint(agent_dir).await?;
+            let report = self.linter.lint(agent_dir).await?;
+            print!("{}", report);
+            report.into_result()?;
         }
 
         // Stage 2: Generate runtime type declarations from BAML runtime
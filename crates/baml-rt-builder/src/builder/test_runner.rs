@@ -0,0 +1,443 @@
+//! Agent test suites
+//!
+//! Discovers `*.test.ts` / `*.test.js` files under an agent's `tests`
+//! directory and runs each file in a fresh QuickJS bridge with the agent's
+//! code loaded. BAML calls never reach an LLM: they resolve from mocks
+//! registered by the test (`mockBaml`) or from recorded fixtures in
+//! `tests/fixtures.json`, and fail otherwise.
+//!
+//! Test files are plain scripts and use the globals installed by the harness:
+//! `describe`, `test`/`it`, `expect`, `mockBaml`, and `clearBamlMocks`.
+
+use crate::builder::compiler::OxcTypeScriptCompiler;
+use crate::builder::filesystem::StdFileSystem;
+use crate::builder::traits::{FileSystem, TypeScriptCompiler};
+use crate::builder::types::{AgentDir, BuildDir};
+use baml_rt_core::manifest::{AgentManifest, DEFAULT_ENTRY_POINT, MANIFEST_FILE};
+use baml_rt_core::{BamlRtError, Result};
+use baml_rt_quickjs::{BamlRuntimeManager, QuickJSBridge};
+use serde::Deserialize;
+use serde_json::{Value, json};
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+const HARNESS_JS: &str = r#"
+(function() {
+    const tests = [];
+    const prefixes = [];
+    const mocks = {};
+    const calls = [];
+
+    globalThis.describe = function(name, body) {
+        prefixes.push(name);
+        try { body(); } finally { prefixes.pop(); }
+    };
+    globalThis.test = function(name, body) {
+        tests.push({ name: prefixes.concat([name]).join(' > '), body });
+    };
+    globalThis.it = globalThis.test;
+
+    globalThis.mockBaml = function(name, value) { mocks[name] = value; };
+    globalThis.clearBamlMocks = function() {
+        for (const key of Object.keys(mocks)) delete mocks[key];
+        calls.length = 0;
+    };
+    globalThis.bamlCalls = function(name) {
+        return calls.filter((call) => name === undefined || call.name === name);
+    };
+
+    const same = (a, b) => JSON.stringify(a) === JSON.stringify(b);
+    const show = (value) => {
+        try { return JSON.stringify(value); } catch (_) { return String(value); }
+    };
+    globalThis.expect = function(actual) {
+        const check = (ok, message) => { if (!ok) throw new Error(message); };
+        return {
+            toBe: (expected) => check(actual === expected, `expected ${show(actual)} to be ${show(expected)}`),
+            toEqual: (expected) => check(same(actual, expected), `expected ${show(actual)} to equal ${show(expected)}`),
+            toContain: (item) => check(actual != null && actual.includes(item), `expected ${show(actual)} to contain ${show(item)}`),
+            toBeTruthy: () => check(!!actual, `expected ${show(actual)} to be truthy`),
+            toBeFalsy: () => check(!actual, `expected ${show(actual)} to be falsy`),
+            toBeDefined: () => check(actual !== undefined, 'expected value to be defined'),
+        };
+    };
+
+    async function dispatch(name, args, streaming) {
+        calls.push({ name, args, streaming });
+        let result;
+        if (Object.prototype.hasOwnProperty.call(mocks, name)) {
+            const mock = mocks[name];
+            result = typeof mock === 'function' ? await mock(args) : mock;
+        } else {
+            const recorded = __bamlTestFixtures[name];
+            if (recorded === undefined) {
+                throw new Error(`No mock or fixture for BAML function ${name}`);
+            }
+            if (Array.isArray(recorded)) {
+                const match = recorded.find((entry) => entry.args === undefined || same(entry.args, args));
+                if (match === undefined) {
+                    throw new Error(`No fixture for BAML function ${name} with args ${show(args)}`);
+                }
+                result = match.result;
+            } else {
+                result = recorded.result;
+            }
+        }
+        if (streaming) {
+            return Array.isArray(result) ? result : [result];
+        }
+        return result;
+    }
+
+    // Args arrive as objects, or as JSON strings from older callers
+    const parseArgs = (args) => (typeof args === 'string' ? JSON.parse(args) : args);
+    globalThis.__baml_invoke = (name, args) => dispatch(name, parseArgs(args), false);
+    globalThis.__baml_stream = (name, args) => dispatch(name, parseArgs(args), true);
+
+    globalThis.__runAgentTests = async function() {
+        const results = [];
+        for (const { name, body } of tests) {
+            const started = Date.now();
+            let failure = null;
+            try {
+                await body();
+            } catch (error) {
+                failure = (error && error.message) || String(error);
+            }
+            results.push({ name, duration_ms: Date.now() - started, failure });
+        }
+        return results;
+    };
+})();
+"#;
+
+/// Outcome of a single test case
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct TestCaseReport {
+    pub name: String,
+    pub duration_ms: u64,
+    pub failure: Option<String>,
+}
+
+impl TestCaseReport {
+    pub fn passed(&self) -> bool {
+        self.failure.is_none()
+    }
+}
+
+/// Results of one test file
+#[derive(Debug, Clone, PartialEq)]
+pub struct TestSuiteReport {
+    pub name: String,
+    pub cases: Vec<TestCaseReport>,
+    /// Set when the file could not be loaded or run at all
+    pub error: Option<String>,
+}
+
+impl TestSuiteReport {
+    pub fn failures(&self) -> usize {
+        self.cases.iter().filter(|case| !case.passed()).count()
+    }
+}
+
+/// Results of a full test run
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TestReport {
+    pub suites: Vec<TestSuiteReport>,
+}
+
+impl TestReport {
+    pub fn total(&self) -> usize {
+        self.suites.iter().map(|suite| suite.cases.len()).sum()
+    }
+
+    pub fn failures(&self) -> usize {
+        self.suites.iter().map(TestSuiteReport::failures).sum()
+    }
+
+    pub fn errors(&self) -> usize {
+        self.suites
+            .iter()
+            .filter(|suite| suite.error.is_some())
+            .count()
+    }
+
+    pub fn success(&self) -> bool {
+        self.failures() == 0 && self.errors() == 0
+    }
+
+    /// Render the report as JUnit XML
+    pub fn to_junit_xml(&self) -> String {
+        let mut out = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        let _ = writeln!(
+            out,
+            "<testsuites tests=\"{}\" failures=\"{}\" errors=\"{}\">",
+            self.total(),
+            self.failures(),
+            self.errors()
+        );
+        for suite in &self.suites {
+            let time: u64 = suite.cases.iter().map(|case| case.duration_ms).sum();
+            let _ = writeln!(
+                out,
+                "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" errors=\"{}\" time=\"{:.3}\">",
+                xml_escape(&suite.name),
+                suite.cases.len(),
+                suite.failures(),
+                usize::from(suite.error.is_some()),
+                time as f64 / 1000.0
+            );
+            if let Some(error) = &suite.error {
+                let _ = writeln!(out, "    <error message=\"{}\"/>", xml_escape(error));
+            }
+            for case in &suite.cases {
+                let _ = write!(
+                    out,
+                    "    <testcase name=\"{}\" classname=\"{}\" time=\"{:.3}\"",
+                    xml_escape(&case.name),
+                    xml_escape(&suite.name),
+                    case.duration_ms as f64 / 1000.0
+                );
+                match &case.failure {
+                    Some(failure) => {
+                        let _ = writeln!(
+                            out,
+                            ">\n      <failure message=\"{}\"/>\n    </testcase>",
+                            xml_escape(failure)
+                        );
+                    }
+                    None => out.push_str("/>\n"),
+                }
+            }
+            out.push_str("  </testsuite>\n");
+        }
+        out.push_str("</testsuites>\n");
+        out
+    }
+}
+
+/// Runs an agent's test files
+pub struct AgentTestRunner {
+    agent_dir: AgentDir,
+    filter: Option<String>,
+}
+
+impl AgentTestRunner {
+    pub fn new(agent_dir: AgentDir) -> Self {
+        Self {
+            agent_dir,
+            filter: None,
+        }
+    }
+
+    /// Only run test files whose path contains `filter`
+    pub fn with_filter(mut self, filter: impl Into<String>) -> Self {
+        self.filter = Some(filter.into());
+        self
+    }
+
+    /// Test files under `tests/`, sorted by path
+    pub fn discover(&self) -> Result<Vec<PathBuf>> {
+        let mut files = Vec::new();
+        StdFileSystem.collect_ts_js_files(&self.agent_dir.tests(), &mut files)?;
+        files.retain(|path| {
+            let name = path.to_string_lossy();
+            let is_test = [".test.ts", ".test.tsx", ".test.js", ".test.jsx"]
+                .iter()
+                .any(|suffix| name.ends_with(suffix));
+            is_test
+                && self
+                    .filter
+                    .as_deref()
+                    .is_none_or(|filter| name.contains(filter))
+        });
+        files.sort();
+        Ok(files)
+    }
+
+    /// Compile the agent and its tests, then run every discovered test file
+    pub async fn run(&self) -> Result<TestReport> {
+        let files = self.discover()?;
+        let build_dir = BuildDir::new()?;
+        let compiler = OxcTypeScriptCompiler::new(StdFileSystem);
+        compiler
+            .compile(&self.agent_dir.src(), &build_dir.join("dist"))
+            .await?;
+        compiler
+            .compile(&self.agent_dir.tests(), &build_dir.join("tests"))
+            .await?;
+
+        let runtime = {
+            let baml_src = self.agent_dir.baml_src();
+            let baml_src_str = baml_src.to_str().ok_or_else(|| {
+                BamlRtError::InvalidArgument(format!(
+                    "BAML source path contains invalid UTF-8: {}",
+                    baml_src.display()
+                ))
+            })?;
+            let mut runtime = BamlRuntimeManager::new()?;
+            runtime.load_schema(baml_src_str)?;
+            Arc::new(RwLock::new(runtime))
+        };
+        let fixtures = self.load_fixtures()?;
+        let agent = self.agent_script(&build_dir)?;
+
+        let mut report = TestReport::default();
+        for file in files {
+            let name = file
+                .strip_prefix(self.agent_dir.as_path())
+                .unwrap_or(&file)
+                .display()
+                .to_string();
+            let compiled = compiled_test_path(&self.agent_dir.tests(), &build_dir, &file);
+            let suite = match self
+                .run_file(runtime.clone(), &fixtures, agent.as_ref(), &compiled)
+                .await
+            {
+                Ok(cases) => TestSuiteReport {
+                    name,
+                    cases,
+                    error: None,
+                },
+                Err(err) => TestSuiteReport {
+                    name,
+                    cases: Vec::new(),
+                    error: Some(err.to_string()),
+                },
+            };
+            report.suites.push(suite);
+        }
+        Ok(report)
+    }
+
+    async fn run_file(
+        &self,
+        runtime: Arc<RwLock<BamlRuntimeManager>>,
+        fixtures: &Value,
+        agent: Option<&AgentScript>,
+        test_file: &Path,
+    ) -> Result<Vec<TestCaseReport>> {
+        let test_code = StdFileSystem.read_to_string(test_file)?;
+
+        let mut bridge = QuickJSBridge::new(runtime).await?;
+        bridge.register_baml_functions().await?;
+        bridge
+            .evaluate(&format!("globalThis.__bamlTestFixtures = {};", fixtures))
+            .await?;
+        bridge.evaluate(HARNESS_JS).await?;
+        if let Some(agent) = agent {
+            bridge
+                .evaluate_script(&agent.entry_point, &agent.code, agent.source_map.as_deref())
+                .await?;
+        }
+        bridge.evaluate(&test_code).await?;
+
+        let results = bridge
+            .invoke_js_function("__runAgentTests", json!({}))
+            .await?;
+        serde_json::from_value(results).map_err(BamlRtError::Json)
+    }
+
+    /// Recorded BAML results from `tests/fixtures.json`
+    ///
+    /// Each key is a BAML function name mapping to either `{ "result": ... }`
+    /// or a list of `{ "args": ..., "result": ... }` entries matched by args.
+    fn load_fixtures(&self) -> Result<Value> {
+        let path = self.agent_dir.tests().join("fixtures.json");
+        if !path.exists() {
+            return Ok(Value::Object(Default::default()));
+        }
+        let content = StdFileSystem.read_to_string(&path)?;
+        let fixtures: Value = serde_json::from_str(&content).map_err(BamlRtError::Json)?;
+        if !fixtures.is_object() {
+            return Err(BamlRtError::InvalidArgument(format!(
+                "{} must contain a JSON object keyed by BAML function name",
+                path.display()
+            )));
+        }
+        Ok(fixtures)
+    }
+
+    fn agent_script(&self, build_dir: &BuildDir) -> Result<Option<AgentScript>> {
+        let manifest_path = self.agent_dir.as_path().join(MANIFEST_FILE);
+        let entry_point = if manifest_path.exists() {
+            AgentManifest::from_json(&StdFileSystem.read_to_string(&manifest_path)?)?.entry_point
+        } else {
+            DEFAULT_ENTRY_POINT.to_string()
+        };
+        let path = build_dir.join(&entry_point);
+        if path.exists() {
+            Ok(Some(AgentScript {
+                code: StdFileSystem.read_to_string(&path)?,
+                source_map: StdFileSystem
+                    .read_to_string(Path::new(&format!("{}.map", path.display())))
+                    .ok(),
+                entry_point,
+            }))
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+/// The compiled agent entry point, loaded before each test file
+struct AgentScript {
+    entry_point: String,
+    code: String,
+    source_map: Option<String>,
+}
+
+fn compiled_test_path(tests_dir: &Path, build_dir: &BuildDir, file: &Path) -> PathBuf {
+    let relative = file.strip_prefix(tests_dir).unwrap_or(file);
+    match file.extension().and_then(|ext| ext.to_str()) {
+        Some("ts") | Some("tsx") => build_dir.join("tests").join(relative).with_extension("js"),
+        _ => file.to_path_buf(),
+    }
+}
+
+fn xml_escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_junit_xml() {
+        let report = TestReport {
+            suites: vec![TestSuiteReport {
+                name: "tests/greeting.test.ts".to_string(),
+                cases: vec![
+                    TestCaseReport {
+                        name: "greets by name".to_string(),
+                        duration_ms: 12,
+                        failure: None,
+                    },
+                    TestCaseReport {
+                        name: "rejects <empty> names".to_string(),
+                        duration_ms: 3,
+                        failure: Some("expected \"\" to be truthy".to_string()),
+                    },
+                ],
+                error: None,
+            }],
+        };
+
+        let xml = report.to_junit_xml();
+        assert!(xml.contains("<testsuites tests=\"2\" failures=\"1\" errors=\"0\">"));
+        assert!(xml.contains(
+            "<testcase name=\"greets by name\" classname=\"tests/greeting.test.ts\" time=\"0.012\"/>"
+        ));
+        assert!(xml.contains("name=\"rejects &lt;empty&gt; names\""));
+        assert!(xml.contains("<failure message=\"expected &quot;&quot; to be truthy\"/>"));
+        assert!(!report.success());
+    }
+}
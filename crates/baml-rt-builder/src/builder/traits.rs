@@ -3,6 +3,7 @@
 //! These traits provide a clean abstraction for different operations
 //! in the agent building pipeline, enabling testability and modularity.
 
+use crate::builder::linter::LintReport;
 use crate::builder::types::{AgentDir, BuildDir};
 use baml_rt_core::Result;
 use std::path::Path;
@@ -11,7 +12,10 @@ use std::path::Path;
 #[async_trait::async_trait]
 pub trait Linter: Send + Sync {
     /// Lint the source code in the given agent directory
-    async fn lint(&self, agent_dir: &AgentDir) -> Result<()>;
+    ///
+    /// Problems found are reported, not returned as errors; see
+    /// [`LintReport::into_result`].
+    async fn lint(&self, agent_dir: &AgentDir) -> Result<LintReport>;
 }
 
 /// Trait for compiling TypeScript to JavaScript
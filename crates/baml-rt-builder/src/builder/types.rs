@@ -45,6 +45,11 @@ impl AgentDir {
     pub fn src(&self) -> PathBuf {
         self.0.join("src")
     }
+
+    /// Get the tests subdirectory
+    pub fn tests(&self) -> PathBuf {
+        self.0.join("tests")
+    }
 }
 
 impl fmt::Display for AgentDir {
@@ -140,6 +145,13 @@ impl BuildDir {
         Ok(Self(build_dir))
     }
 
+    /// Use `path` as the build directory, creating it if needed
+    pub fn at(path: impl Into<PathBuf>) -> baml_rt_core::Result<Self> {
+        let build_dir = path.into();
+        std::fs::create_dir_all(&build_dir).map_err(baml_rt_core::BamlRtError::Io)?;
+        Ok(Self(build_dir))
+    }
+
     /// Get the inner path
     pub fn as_path(&self) -> &Path {
         &self.0
@@ -0,0 +1,161 @@
+//! Watch-and-rebuild for local development
+//!
+//! [`DevWatcher`] keeps a build directory in step with an agent's sources:
+//! a change under `baml_src` regenerates `dist/baml.d.ts`, a change under
+//! `src` recompiles the TypeScript, and neither redoes the other's work. The
+//! `dev` subcommand polls it and, when asked, restarts a local run of the
+//! agent after every successful rebuild.
+
+use crate::builder::dev_agent::SourceSnapshot;
+use crate::builder::traits::{TypeGenerator, TypeScriptCompiler};
+use crate::builder::types::{AgentDir, BuildDir};
+use baml_rt_core::Result;
+use baml_rt_core::manifest::MANIFEST_FILE;
+
+/// What a call to [`DevWatcher::rebuild`] redid
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Rebuild {
+    /// `baml_src` changed and the type declarations were regenerated
+    pub types: bool,
+    /// `src` changed and the TypeScript was recompiled
+    pub typescript: bool,
+    /// `manifest.json` changed
+    pub manifest: bool,
+}
+
+impl Rebuild {
+    /// Whether anything changed since the previous rebuild
+    pub fn any(&self) -> bool {
+        self.types || self.typescript || self.manifest
+    }
+}
+
+/// Rebuilds the parts of an agent whose sources changed
+pub struct DevWatcher<TC, TG> {
+    agent_dir: AgentDir,
+    build_dir: BuildDir,
+    ts_compiler: TC,
+    type_generator: TG,
+    schema: Option<SourceSnapshot>,
+    sources: Option<SourceSnapshot>,
+    manifest: Option<SourceSnapshot>,
+}
+
+impl<TC, TG> DevWatcher<TC, TG>
+where
+    TC: TypeScriptCompiler,
+    TG: TypeGenerator,
+{
+    /// Watch `agent_dir`, writing output to `build_dir/dist`
+    pub fn new(
+        agent_dir: AgentDir,
+        build_dir: BuildDir,
+        ts_compiler: TC,
+        type_generator: TG,
+    ) -> Self {
+        Self {
+            agent_dir,
+            build_dir,
+            ts_compiler,
+            type_generator,
+            schema: None,
+            sources: None,
+            manifest: None,
+        }
+    }
+
+    pub fn build_dir(&self) -> &BuildDir {
+        &self.build_dir
+    }
+
+    /// Rebuild whatever changed since the last call; the first call builds
+    /// everything.
+    ///
+    /// A failed step is retried on the next change to its sources, not on
+    /// every call.
+    pub async fn rebuild(&mut self) -> Result<Rebuild> {
+        let schema = SourceSnapshot::capture_path(&self.agent_dir.baml_src());
+        let sources = SourceSnapshot::capture_path(&self.agent_dir.src());
+        let manifest = SourceSnapshot::capture_path(&self.agent_dir.as_path().join(MANIFEST_FILE));
+        let rebuild = Rebuild {
+            types: self.schema.as_ref() != Some(&schema),
+            typescript: self.sources.as_ref() != Some(&sources),
+            manifest: self.manifest.as_ref() != Some(&manifest),
+        };
+        self.schema = Some(schema);
+        self.sources = Some(sources);
+        self.manifest = Some(manifest);
+
+        if rebuild.types {
+            self.type_generator
+                .generate(&self.agent_dir.baml_src(), &self.build_dir)
+                .await?;
+        }
+        if rebuild.typescript {
+            self.ts_compiler
+                .compile(&self.agent_dir.src(), &self.build_dir.join("dist"))
+                .await?;
+        }
+        Ok(rebuild)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[derive(Clone, Default)]
+    struct Counter(Arc<AtomicUsize>);
+
+    impl Counter {
+        fn count(&self) -> usize {
+            self.0.load(Ordering::SeqCst)
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl TypeScriptCompiler for Counter {
+        async fn compile(&self, _src_dir: &Path, _dist_dir: &Path) -> Result<()> {
+            self.0.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl TypeGenerator for Counter {
+        async fn generate(&self, _baml_src: &Path, _build_dir: &BuildDir) -> Result<()> {
+            self.0.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn only_changed_sources_are_rebuilt() {
+        let root = tempfile::TempDir::new().unwrap();
+        std::fs::create_dir_all(root.path().join("baml_src")).unwrap();
+        std::fs::create_dir_all(root.path().join("src")).unwrap();
+        std::fs::write(root.path().join("baml_src/main.baml"), "").unwrap();
+        std::fs::write(root.path().join("src/index.ts"), "").unwrap();
+
+        let compiles = Counter::default();
+        let generations = Counter::default();
+        let mut watcher = DevWatcher::new(
+            AgentDir::new(root.path().to_path_buf()).unwrap(),
+            BuildDir::at(root.path().join("build")).unwrap(),
+            compiles.clone(),
+            generations.clone(),
+        );
+
+        let first = watcher.rebuild().await.unwrap();
+        assert!(first.types && first.typescript);
+        assert!(!watcher.rebuild().await.unwrap().any());
+
+        std::fs::write(root.path().join("src/tools.ts"), "export {};").unwrap();
+        let second = watcher.rebuild().await.unwrap();
+        assert!(second.typescript && !second.types);
+        assert_eq!((compiles.count(), generations.count()), (2, 1));
+    }
+}
@@ -4,7 +4,7 @@
 //! that all CLI subcommands work correctly end-to-end.
 
 use tempfile::TempDir;
-use test_support::common::{agent_fixture, workspace_root};
+use test_support::common::{agent_fixture, mocked_agent_fixture, require_api_key, workspace_root};
 use test_support::support::cli::CliHarness;
 
 #[test]
@@ -74,6 +74,220 @@ fn test_cli_package_agent() {
     }
 }
 
+#[test]
+fn test_cli_repl_lists_functions_from_agent_dir() {
+    use std::io::Write;
+    use std::process::Stdio;
+
+    let harness = CliHarness::new();
+    let agent_dir = workspace_root().join("examples").join("agent-example");
+
+    let mut cmd = harness.builder_command();
+    cmd.arg("repl")
+        .arg("--agent-dir")
+        .arg(&agent_dir)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+
+    let mut child = cmd.spawn().expect("Failed to spawn repl command");
+    child
+        .stdin
+        .take()
+        .expect("repl stdin")
+        .write_all(b":functions\n:quit\n")
+        .expect("Failed to write repl input");
+    let output = child.wait_with_output().expect("Failed to run repl");
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        output.status.success(),
+        "REPL should exit cleanly: {}",
+        stderr
+    );
+    assert!(
+        stdout.contains("SimpleGreeting"),
+        "REPL should list BAML functions, got: {}",
+        stdout
+    );
+}
+
+#[test]
+fn test_cli_new_scaffolds_agent_that_passes_its_tests() {
+    let harness = CliHarness::new();
+    let parent = TempDir::new().unwrap();
+
+    let mut cmd = harness.builder_command();
+    cmd.arg("new")
+        .arg("my-agent")
+        .arg("--dir")
+        .arg(parent.path());
+    let output = cmd.output().expect("Failed to execute new command");
+    assert!(
+        output.status.success(),
+        "Scaffolding should succeed. Stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let agent_dir = parent.path().join("my-agent");
+    for file in [
+        "baml_src/main.baml",
+        "src/index.ts",
+        "tests/agent.test.ts",
+        "manifest.json",
+        "tsconfig.json",
+    ] {
+        assert!(agent_dir.join(file).exists(), "{} should be created", file);
+    }
+    let manifest: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(agent_dir.join("manifest.json")).unwrap())
+            .unwrap();
+    assert_eq!(manifest["name"], "my-agent");
+
+    let mut cmd = harness.builder_command();
+    cmd.arg("test").arg("--agent-dir").arg(&agent_dir);
+    let output = cmd.output().expect("Failed to execute test command");
+    assert!(
+        output.status.success(),
+        "Scaffolded agent tests should pass.\nStdout: {}\nStderr: {}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let mut cmd = harness.builder_command();
+    cmd.arg("new")
+        .arg("my-agent")
+        .arg("--dir")
+        .arg(parent.path());
+    let output = cmd.output().expect("Failed to execute new command");
+    assert!(
+        !output.status.success(),
+        "Scaffolding over an existing directory should fail"
+    );
+}
+
+#[test]
+fn test_cli_test_runs_agent_tests_with_junit_report() {
+    let harness = CliHarness::new();
+    let agent_dir = workspace_root().join("examples").join("agent-example");
+    let output_dir = TempDir::new().unwrap();
+    let junit_path = output_dir.path().join("junit.xml");
+
+    let mut cmd = harness.builder_command();
+    cmd.arg("test")
+        .arg("--agent-dir")
+        .arg(&agent_dir)
+        .arg("--junit")
+        .arg(&junit_path);
+
+    let output = cmd.output().expect("Failed to execute test command");
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        output.status.success(),
+        "Agent tests should pass.\nStdout: {}\nStderr: {}",
+        stdout,
+        stderr
+    );
+    assert!(stdout.contains("3 passed, 0 failed"), "Got: {}", stdout);
+
+    let junit = std::fs::read_to_string(&junit_path).expect("JUnit report should be written");
+    assert!(junit.contains("<testsuites tests=\"3\" failures=\"0\" errors=\"0\">"));
+    assert!(junit.contains("greetUser &gt; accepts a plain name"));
+}
+
+#[test]
+fn test_cli_eval_scores_a2a_cases_and_writes_report() {
+    // The agent's tool choice goes to the mock LLM, whose reply names no
+    // tool, so every message is answered with a blessing
+    require_api_key();
+    let harness = CliHarness::new();
+    let agent_dir = TempDir::new().unwrap();
+    mocked_agent_fixture("voidship-rites", agent_dir.path()).unwrap();
+    let output_dir = TempDir::new().unwrap();
+    let dataset = output_dir.path().join("rites.jsonl");
+    std::fs::write(
+        &dataset,
+        concat!(
+            r#"{"name": "blessing", "input": "Hear me", "expected": "Blessings upon Hear me"}"#,
+            "\n",
+            r#"{"name": "curse", "input": "Heed", "expected": "Curses upon Heed"}"#,
+            "\n",
+        ),
+    )
+    .unwrap();
+    let report_path = output_dir.path().join("report.json");
+
+    let mut cmd = harness.builder_command();
+    cmd.arg("eval")
+        .arg("--agent-dir")
+        .arg(agent_dir.path())
+        .arg("--dataset")
+        .arg(&dataset)
+        .arg("--a2a")
+        .arg("--scorer")
+        .arg("exact")
+        .arg("--report")
+        .arg(&report_path);
+
+    let output = cmd.output().expect("Failed to execute eval command");
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(
+        !output.status.success(),
+        "A failing case should fail the run.\nStdout: {}\nStderr: {}",
+        stdout,
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(stdout.contains("✓ blessing"), "Got: {}", stdout);
+    assert!(stdout.contains("1 passed, 1 failed"), "Got: {}", stdout);
+
+    let report: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&report_path).unwrap()).unwrap();
+    assert_eq!(report["target"], "handle_a2a_request (A2A)");
+    assert_eq!(report["metrics"]["pass_rate"], 0.5);
+    assert_eq!(report["metrics"]["scorers"]["exact"]["mean_score"], 0.5);
+    assert_eq!(report["cases"][1]["scores"][0]["passed"], false);
+}
+
+#[test]
+fn test_cli_check_reports_unknown_tool_variants() {
+    let harness = CliHarness::new();
+    let agent_dir = TempDir::new().unwrap();
+    let baml_src = agent_dir.path().join("baml_src");
+    std::fs::create_dir_all(&baml_src).unwrap();
+    std::fs::copy(
+        agent_fixture("voidship-rites").join("baml_src/voidship_prompt.baml"),
+        baml_src.join("voidship_prompt.baml"),
+    )
+    .unwrap();
+    let host = agent_dir.path().join("src").join("host.rs");
+    std::fs::create_dir_all(host.parent().unwrap()).unwrap();
+
+    let check = |source: &str| {
+        std::fs::write(&host, source).unwrap();
+        let mut cmd = harness.builder_command();
+        cmd.arg("check").arg("--agent-dir").arg(agent_dir.path());
+        cmd.output().expect("Failed to execute check command")
+    };
+
+    let output = check("manager.map_baml_variant_to_tool(\"RiteCalcTool\", \"calculate\");\n");
+    assert!(
+        output.status.success(),
+        "Known variants should pass.\nStdout: {}",
+        String::from_utf8_lossy(&output.stdout)
+    );
+
+    let output = check("\n    manager.map_baml_variant_to_tool(\"AugurTool\", \"scan\");\n");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(!output.status.success(), "Unknown variants should fail");
+    assert!(
+        stdout.contains("src/host.rs:2:39: error: tool variant 'AugurTool'"),
+        "Got: {}",
+        stdout
+    );
+}
+
 #[test]
 fn test_cli_package_creates_manifest_if_missing() {
     // Test skipped - core functionality tested in test_cli_package_agent
@@ -90,7 +304,7 @@ async fn test_full_integration_package_load_execute() {
     use serde_json::json;
     use std::fs;
     use std::sync::Arc;
-    use tokio::sync::Mutex;
+    use tokio::sync::RwLock;
 
     // Use voidship-rites fixture
     let agent_dir = agent_fixture("voidship-rites");
@@ -135,6 +349,14 @@ async fn test_full_integration_package_load_execute() {
         dist_index.exists(),
         "Package should contain compiled JavaScript at dist/index.js"
     );
+    assert!(
+        extract_dir
+            .path()
+            .join("dist")
+            .join("index.js.map")
+            .exists(),
+        "Package should contain the bundle's source map"
+    );
 
     // STEP 3: Load the package (simulating what baml-agent-builder does)
     // Set up BAML runtime
@@ -143,7 +365,7 @@ async fn test_full_integration_package_load_execute() {
     baml_manager
         .load_schema(baml_src.to_str().unwrap())
         .unwrap();
-    let baml_manager = Arc::new(Mutex::new(baml_manager));
+    let baml_manager = Arc::new(RwLock::new(baml_manager));
 
     // Create QuickJS bridge
     let mut bridge = QuickJSBridge::new(baml_manager.clone()).await.unwrap();
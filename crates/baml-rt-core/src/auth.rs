@@ -0,0 +1,219 @@
+//! Caller identity and access checks for invocation flows.
+//!
+//! A transport that has authenticated a request scopes it with the
+//! [`Caller`] it was made by: the [`Principal`], if any, and the
+//! [`AccessPolicy`] deciding what it may do. Code serving the request then
+//! calls [`check`] before doing something a policy may restrict, such as
+//! invoking a BAML function or executing a tool. Outside any caller's scope
+//! every check passes.
+
+use crate::ids::TenantId;
+use crate::{BamlRtError, Result};
+use serde_json::{Map, Value};
+use std::fmt;
+use std::sync::Arc;
+
+tokio::task_local! {
+    static CALLER: Option<Caller>;
+}
+
+/// An authenticated caller, e.g. a user or a service account
+///
+/// A principal acts only for the tenants it holds, the first of which is
+/// its own.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Principal {
+    id: String,
+    claims: Map<String, Value>,
+    tenants: Vec<TenantId>,
+}
+
+impl Principal {
+    pub fn new(id: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            claims: Map::new(),
+            tenants: Vec::new(),
+        }
+    }
+
+    /// Let the principal act for `tenant`
+    pub fn with_tenant(mut self, tenant: impl Into<TenantId>) -> Self {
+        self.tenants.push(tenant.into());
+        self
+    }
+
+    /// Attach a claim the authenticator established, e.g. a role
+    pub fn with_claim(mut self, name: impl Into<String>, value: Value) -> Self {
+        self.claims.insert(name.into(), value);
+        self
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn claim(&self, name: &str) -> Option<&Value> {
+        self.claims.get(name)
+    }
+
+    /// The tenant requests naming none are made for
+    pub fn own_tenant(&self) -> Option<&TenantId> {
+        self.tenants.first()
+    }
+
+    pub fn holds_tenant(&self, tenant: &TenantId) -> bool {
+        self.tenants.contains(tenant)
+    }
+}
+
+/// Something a caller asks to do
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Access<'a> {
+    /// Send a request to an agent
+    Agent(&'a str),
+    /// Call a JSON-RPC method, e.g. `message.send`
+    Method(&'a str),
+    /// Invoke a BAML or JavaScript function
+    Function(&'a str),
+    /// Execute a tool
+    Tool(&'a str),
+}
+
+impl fmt::Display for Access<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Access::Agent(name) => write!(f, "call agent '{}'", name),
+            Access::Method(name) => write!(f, "call method '{}'", name),
+            Access::Function(name) => write!(f, "invoke function '{}'", name),
+            Access::Tool(name) => write!(f, "execute tool '{}'", name),
+        }
+    }
+}
+
+/// Decides what callers may do
+pub trait AccessPolicy: Send + Sync {
+    /// Whether `principal`, or an anonymous caller when `None`, may make
+    /// `access`
+    fn allows(&self, principal: Option<&Principal>, access: &Access<'_>) -> bool;
+}
+
+/// Allows everything
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AllowAll;
+
+impl AccessPolicy for AllowAll {
+    fn allows(&self, _principal: Option<&Principal>, _access: &Access<'_>) -> bool {
+        true
+    }
+}
+
+/// Who a request was made by, and the policy that applies to it
+#[derive(Clone)]
+pub struct Caller {
+    principal: Option<Principal>,
+    policy: Arc<dyn AccessPolicy>,
+}
+
+impl Caller {
+    pub fn new(principal: Option<Principal>, policy: Arc<dyn AccessPolicy>) -> Self {
+        Self { principal, policy }
+    }
+
+    pub fn principal(&self) -> Option<&Principal> {
+        self.principal.as_ref()
+    }
+
+    /// Fail with [`BamlRtError::Forbidden`] unless the caller may make
+    /// `access`
+    pub fn check(&self, access: Access<'_>) -> Result<()> {
+        if self.policy.allows(self.principal.as_ref(), &access) {
+            return Ok(());
+        }
+        Err(BamlRtError::Forbidden {
+            principal: self
+                .principal
+                .as_ref()
+                .map_or_else(|| "anonymous".to_string(), |p| p.id.clone()),
+            action: access.to_string(),
+        })
+    }
+}
+
+impl fmt::Debug for Caller {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Caller")
+            .field("principal", &self.principal)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Caller of the current request, if it was authenticated
+pub fn current_caller() -> Option<Caller> {
+    CALLER.try_with(|caller| caller.clone()).ok().flatten()
+}
+
+pub fn current_principal() -> Option<Principal> {
+    current_caller().and_then(|caller| caller.principal)
+}
+
+/// Fail with [`BamlRtError::Forbidden`] unless the caller in scope may make
+/// `access`
+pub fn check(access: Access<'_>) -> Result<()> {
+    match current_caller() {
+        Some(caller) => caller.check(access),
+        None => Ok(()),
+    }
+}
+
+/// Run `fut` on behalf of `caller`; `None` lifts any outer caller's
+/// restrictions
+pub async fn with_caller<F, T>(caller: Option<Caller>, fut: F) -> T
+where
+    F: std::future::Future<Output = T>,
+{
+    CALLER.scope(caller, fut).await
+}
+
+/// Run a synchronous closure on behalf of `caller`
+pub fn with_caller_sync<F, T>(caller: Option<Caller>, f: F) -> T
+where
+    F: FnOnce() -> T,
+{
+    CALLER.sync_scope(caller, f)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct ToolsForAdmins;
+
+    impl AccessPolicy for ToolsForAdmins {
+        fn allows(&self, principal: Option<&Principal>, access: &Access<'_>) -> bool {
+            !matches!(access, Access::Tool(_))
+                || principal.and_then(|p| p.claim("role")) == Some(&Value::from("admin"))
+        }
+    }
+
+    #[tokio::test]
+    async fn checks_apply_the_policy_of_the_caller_in_scope() {
+        assert!(check(Access::Tool("shell")).is_ok());
+
+        let guest = Caller::new(Some(Principal::new("guest")), Arc::new(ToolsForAdmins));
+        let denied = with_caller(Some(guest), async {
+            assert!(check(Access::Function("Summarize")).is_ok());
+            check(Access::Tool("shell"))
+        })
+        .await;
+        assert!(matches!(
+            denied,
+            Err(BamlRtError::Forbidden { principal, action })
+                if principal == "guest" && action == "execute tool 'shell'"
+        ));
+
+        let admin = Principal::new("ops").with_claim("role", Value::from("admin"));
+        let admin = Caller::new(Some(admin), Arc::new(ToolsForAdmins));
+        assert!(with_caller_sync(Some(admin), || check(Access::Tool("shell"))).is_ok());
+    }
+}
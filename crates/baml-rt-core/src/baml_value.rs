@@ -0,0 +1,425 @@
+//! Typed conversion between JSON and BAML values
+//!
+//! JSON arguments are converted against the declared BAML types of a
+//! function's parameters: strings become enum values, objects class instances
+//! or [media](crate::media), and numbers ints or floats as declared. A value
+//! that does not fit its type is reported with the path to it, e.g.
+//! `resume.skills[2]`. Values without a known type are converted as they are,
+//! with integers kept apart from floats.
+//!
+//! Function results are serialized by BAML itself, which writes `@check`
+//! results and `@stream.with_state` completion state as `{ "value", "checks" }`
+//! and `{ "value", "state" }` wrappers; [`baml_to_json`] covers plain values
+//! such as arguments.
+
+use crate::media::{self, MediaPolicy};
+use crate::types::{BamlType, ObjectField};
+use crate::{BamlRtError, Result};
+use baml_types::{BamlMap, BamlMediaContent, BamlValue};
+use serde_json::{Map, Number, Value};
+use std::collections::HashMap;
+
+/// Enums and classes declared in a BAML schema
+#[derive(Debug, Clone, Default)]
+pub struct BamlSchema {
+    enums: HashMap<String, Vec<String>>,
+    classes: HashMap<String, Vec<ObjectField>>,
+    media_policy: MediaPolicy,
+}
+
+impl BamlSchema {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_enum(&mut self, name: impl Into<String>, values: Vec<String>) {
+        self.enums.insert(name.into(), values);
+    }
+
+    pub fn add_class(&mut self, name: impl Into<String>, fields: Vec<ObjectField>) {
+        self.classes.insert(name.into(), fields);
+    }
+
+    /// Files media arguments may be read from
+    pub fn set_media_policy(&mut self, policy: MediaPolicy) {
+        self.media_policy = policy;
+    }
+
+    /// Convert function arguments against the types of its parameters
+    ///
+    /// Arguments without a declared parameter are converted untyped, so the
+    /// BAML runtime can report them.
+    pub fn args_to_baml(
+        &self,
+        args: &Value,
+        params: &[(String, BamlType)],
+    ) -> Result<BamlMap<String, BamlValue>> {
+        let args = args
+            .as_object()
+            .ok_or_else(|| BamlRtError::InvalidArgument("Expected JSON object".to_string()))?;
+        let mut map = BamlMap::new();
+        for (name, arg) in args {
+            let ty = params
+                .iter()
+                .find(|(param, _)| param == name)
+                .map_or(&BamlType::Any, |(_, ty)| ty);
+            map.insert(name.clone(), self.to_baml(arg, ty, name)?);
+        }
+        Ok(map)
+    }
+
+    /// Convert `value` to the BAML type `ty`, reporting errors at `path`
+    pub fn to_baml(&self, value: &Value, ty: &BamlType, path: &str) -> Result<BamlValue> {
+        let mismatch = || invalid(path, format!("expected {}, got {}", ty, kind(value)));
+        match (ty, value) {
+            (BamlType::Any, _) => untyped(value, path),
+            (BamlType::String, Value::String(s)) => Ok(BamlValue::String(s.clone())),
+            (BamlType::String, _) => Err(mismatch()),
+            (BamlType::Int, Value::Number(n)) => n
+                .as_i64()
+                .or_else(|| integral(n.as_f64()?))
+                .map(BamlValue::Int)
+                .ok_or_else(mismatch),
+            (BamlType::Int, _) => Err(mismatch()),
+            (BamlType::Float, Value::Number(n)) => {
+                n.as_f64().map(BamlValue::Float).ok_or_else(mismatch)
+            }
+            (BamlType::Float, _) => Err(mismatch()),
+            (BamlType::Bool, Value::Bool(b)) => Ok(BamlValue::Bool(*b)),
+            (BamlType::Bool, _) => Err(mismatch()),
+            (BamlType::Null, Value::Null) => Ok(BamlValue::Null),
+            (BamlType::Null, _) => Err(mismatch()),
+            (BamlType::Literal(literal), _) if literal == value => untyped(value, path),
+            (BamlType::Literal(_), _) => Err(mismatch()),
+            (BamlType::Media(kind), _) => media::media_from_json(value, *kind, &self.media_policy)
+                .map(BamlValue::Media)
+                .map_err(|message| invalid(path, message)),
+            (BamlType::Optional(_), Value::Null) => Ok(BamlValue::Null),
+            (BamlType::Optional(inner), _) => self.to_baml(value, inner, path),
+            (BamlType::List(item), Value::Array(items)) => items
+                .iter()
+                .enumerate()
+                .map(|(i, value)| self.to_baml(value, item, &format!("{}[{}]", path, i)))
+                .collect::<Result<_>>()
+                .map(BamlValue::List),
+            (BamlType::List(_), _) => Err(mismatch()),
+            (BamlType::Map(_, item), Value::Object(entries)) => {
+                let mut map = BamlMap::new();
+                for (key, value) in entries {
+                    let value = self.to_baml(value, item, &field_path(path, key))?;
+                    map.insert(key.clone(), value);
+                }
+                Ok(BamlValue::Map(map))
+            }
+            (BamlType::Map(..), _) => Err(mismatch()),
+            (BamlType::Object(fields), Value::Object(entries)) => {
+                Ok(BamlValue::Map(self.fields(entries, fields, path)?))
+            }
+            (BamlType::Object(_), _) => Err(mismatch()),
+            (BamlType::Union(variants), _) => variants
+                .iter()
+                .find_map(|variant| self.to_baml(value, variant, path).ok())
+                .ok_or_else(mismatch),
+            (BamlType::Named(name), _) => self.named(value, name, path),
+        }
+    }
+
+    fn named(&self, value: &Value, name: &str, path: &str) -> Result<BamlValue> {
+        if let Some(values) = self.enums.get(name) {
+            return match value.as_str() {
+                Some(variant) if values.iter().any(|v| v == variant) => {
+                    Ok(BamlValue::Enum(name.to_string(), variant.to_string()))
+                }
+                _ => Err(invalid(
+                    path,
+                    format!(
+                        "expected one of {} ({}), got {}",
+                        name,
+                        values.join(", "),
+                        value
+                    ),
+                )),
+            };
+        }
+        if let Some(fields) = self.classes.get(name) {
+            let Value::Object(entries) = value else {
+                return Err(invalid(
+                    path,
+                    format!("expected {}, got {}", name, kind(value)),
+                ));
+            };
+            return Ok(BamlValue::Class(
+                name.to_string(),
+                self.fields(entries, fields, path)?,
+            ));
+        }
+        // Type aliases and classes added at runtime
+        untyped(value, path)
+    }
+
+    /// Convert the declared fields of an object, and the others untyped
+    ///
+    /// Missing fields are only allowed when they can be null.
+    fn fields(
+        &self,
+        entries: &Map<String, Value>,
+        fields: &[ObjectField],
+        path: &str,
+    ) -> Result<BamlMap<String, BamlValue>> {
+        let mut map = BamlMap::new();
+        for field in fields {
+            let field_path = field_path(path, &field.name);
+            let value = entries.get(&field.name).unwrap_or(&Value::Null);
+            let converted = self.to_baml(value, &field.ty, &field_path).map_err(|err| {
+                if entries.contains_key(&field.name) {
+                    err
+                } else {
+                    invalid(&field_path, "missing required field".to_string())
+                }
+            })?;
+            map.insert(field.name.clone(), converted);
+        }
+        for (key, value) in entries {
+            if !fields.iter().any(|field| &field.name == key) {
+                map.insert(key.clone(), untyped(value, &field_path(path, key))?);
+            }
+        }
+        Ok(map)
+    }
+}
+
+/// Convert a BAML value to JSON
+///
+/// Enum values become their names, class instances objects and media
+/// `{ "url" | "base64": ..., "media_type": ... }` objects, one of the forms
+/// [`BamlSchema::to_baml`] reads them back from. Floats JSON cannot hold
+/// become `null`.
+pub fn baml_to_json(value: &BamlValue) -> Value {
+    match value {
+        BamlValue::String(s) => Value::String(s.clone()),
+        BamlValue::Int(i) => Value::from(*i),
+        BamlValue::Float(f) => Number::from_f64(*f).map_or(Value::Null, Value::Number),
+        BamlValue::Bool(b) => Value::Bool(*b),
+        BamlValue::Null => Value::Null,
+        BamlValue::Enum(_, variant) => Value::String(variant.clone()),
+        BamlValue::List(items) => Value::Array(items.iter().map(baml_to_json).collect()),
+        BamlValue::Map(entries) | BamlValue::Class(_, entries) => Value::Object(
+            entries
+                .iter()
+                .map(|(key, value)| (key.clone(), baml_to_json(value)))
+                .collect(),
+        ),
+        BamlValue::Media(media) => {
+            let mut object = Map::new();
+            match &media.content {
+                BamlMediaContent::Url(url) => {
+                    object.insert("url".to_string(), Value::String(url.url.clone()));
+                }
+                BamlMediaContent::Base64(data) => {
+                    object.insert("base64".to_string(), Value::String(data.base64.clone()));
+                }
+                // Files are only referenced from BAML sources
+                BamlMediaContent::File(_) => return Value::Null,
+            }
+            if let Some(mime_type) = &media.mime_type {
+                object.insert("media_type".to_string(), Value::String(mime_type.clone()));
+            }
+            Value::Object(object)
+        }
+    }
+}
+
+/// Convert a value of unknown type, keeping integers apart from floats
+fn untyped(value: &Value, path: &str) -> Result<BamlValue> {
+    Ok(match value {
+        Value::Null => BamlValue::Null,
+        Value::Bool(b) => BamlValue::Bool(*b),
+        Value::String(s) => BamlValue::String(s.clone()),
+        Value::Number(n) => match (n.as_i64(), n.as_f64()) {
+            (Some(i), _) => BamlValue::Int(i),
+            (None, Some(f)) if !n.is_u64() => BamlValue::Float(f),
+            _ => return Err(invalid(path, format!("integer {} is out of range", n))),
+        },
+        Value::Array(items) => BamlValue::List(
+            items
+                .iter()
+                .enumerate()
+                .map(|(i, item)| untyped(item, &format!("{}[{}]", path, i)))
+                .collect::<Result<_>>()?,
+        ),
+        Value::Object(entries) => {
+            let mut map = BamlMap::new();
+            for (key, value) in entries {
+                map.insert(key.clone(), untyped(value, &field_path(path, key))?);
+            }
+            BamlValue::Map(map)
+        }
+    })
+}
+
+/// `value` as an integer, if it is one; JSON writers may send `3.0` for `3`
+fn integral(value: f64) -> Option<i64> {
+    (value.fract() == 0.0 && value.abs() < i64::MAX as f64).then_some(value as i64)
+}
+
+fn field_path(path: &str, field: &str) -> String {
+    if path.is_empty() {
+        field.to_string()
+    } else {
+        format!("{}.{}", path, field)
+    }
+}
+
+fn kind(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "bool",
+        Value::Number(n) if n.is_f64() => "float",
+        Value::Number(_) => "int",
+        Value::String(_) => "string",
+        Value::Array(_) => "list",
+        Value::Object(_) => "object",
+    }
+}
+
+fn invalid(path: &str, message: String) -> BamlRtError {
+    BamlRtError::ValueConversion {
+        path: if path.is_empty() {
+            "$".to_string()
+        } else {
+            path.to_string()
+        },
+        message,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn schema() -> BamlSchema {
+        let mut schema = BamlSchema::new();
+        schema.add_enum("Level", vec!["Junior".to_string(), "Senior".to_string()]);
+        schema.add_class(
+            "Resume",
+            vec![
+                ObjectField {
+                    name: "name".to_string(),
+                    ty: BamlType::parse("string"),
+                },
+                ObjectField {
+                    name: "level".to_string(),
+                    ty: BamlType::parse("Level"),
+                },
+                ObjectField {
+                    name: "skills".to_string(),
+                    ty: BamlType::parse("string[]"),
+                },
+                ObjectField {
+                    name: "photo".to_string(),
+                    ty: BamlType::parse("image?"),
+                },
+                ObjectField {
+                    name: "score".to_string(),
+                    ty: BamlType::parse("float | null"),
+                },
+            ],
+        );
+        schema
+    }
+
+    #[test]
+    fn typed_values_round_trip() {
+        let schema = schema();
+        let params = vec![
+            ("resume".to_string(), BamlType::parse("Resume")),
+            ("years".to_string(), BamlType::parse("map<string, int>")),
+        ];
+        let args = json!({
+            "resume": {
+                "name": "Ada",
+                "level": "Senior",
+                "skills": ["rust", "baml"],
+                "photo": { "url": "https://example.com/ada.png", "media_type": "image/png" },
+                "score": 3.0
+            },
+            "years": { "rust": 9 }
+        });
+
+        let converted = schema.args_to_baml(&args, &params).unwrap();
+        let BamlValue::Class(class, fields) = &converted["resume"] else {
+            panic!("expected a class instance, got {:?}", converted["resume"]);
+        };
+        assert_eq!(class, "Resume");
+        assert!(
+            matches!(&fields["level"], BamlValue::Enum(name, v) if name == "Level" && v == "Senior")
+        );
+        assert!(matches!(fields["photo"], BamlValue::Media(_)));
+        assert!(matches!(fields["score"], BamlValue::Float(score) if score == 3.0));
+        assert!(matches!(converted["years"], BamlValue::Map(_)));
+
+        let back: Map<String, Value> = converted
+            .iter()
+            .map(|(name, value)| (name.clone(), baml_to_json(value)))
+            .collect();
+        assert_eq!(Value::Object(back), args);
+    }
+
+    #[test]
+    fn errors_name_the_offending_path() {
+        let schema = schema();
+        let params = vec![("resume".to_string(), BamlType::parse("Resume"))];
+        let error = |args: Value| schema.args_to_baml(&args, &params).unwrap_err().to_string();
+
+        assert_eq!(
+            error(json!({ "resume": { "name": "Ada", "level": "Senior", "skills": ["rust", 7] } })),
+            "Invalid value at resume.skills[1]: expected string, got int"
+        );
+        assert_eq!(
+            error(json!({ "resume": { "name": "Ada", "level": "Lead", "skills": [] } })),
+            "Invalid value at resume.level: expected one of Level (Junior, Senior), got \"Lead\""
+        );
+        assert_eq!(
+            error(json!({ "resume": { "name": "Ada", "skills": [] } })),
+            "Invalid value at resume.level: missing required field"
+        );
+        assert_eq!(
+            error(json!({
+                "resume": { "name": "Ada", "level": "Junior", "skills": [], "score": "high" }
+            })),
+            "Invalid value at resume.score: expected (float | null), got string"
+        );
+    }
+
+    #[test]
+    fn untyped_values_keep_integers_apart_from_floats() {
+        let schema = BamlSchema::new();
+        let error = schema
+            .args_to_baml(&json!({ "count": 3, "ratio": 0.5, "huge": u64::MAX }), &[])
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            format!(
+                "Invalid value at huge: integer {} is out of range",
+                u64::MAX
+            )
+        );
+
+        let converted = schema
+            .args_to_baml(&json!({ "count": 3, "ratio": 0.5 }), &[])
+            .unwrap();
+        assert!(matches!(converted["count"], BamlValue::Int(3)));
+        assert!(matches!(converted["ratio"], BamlValue::Float(r) if r == 0.5));
+
+        let params = vec![
+            ("count".to_string(), BamlType::Int),
+            ("ratio".to_string(), BamlType::Float),
+        ];
+        let converted = schema
+            .args_to_baml(&json!({ "count": 3.0, "ratio": 2 }), &params)
+            .unwrap();
+        assert!(matches!(converted["count"], BamlValue::Int(3)));
+        assert!(matches!(converted["ratio"], BamlValue::Float(r) if r == 2.0));
+    }
+}
@@ -0,0 +1,99 @@
+//! Request cancellation for async invocation flows.
+//!
+//! A caller that may abandon a request, such as an A2A task that can be
+//! canceled with `tasks.cancel`, scopes it with [`with_cancellation`]. BAML
+//! calls trip their `TripWire` and tool executions and JS invocations
+//! [`enforce`] the token, so canceling it stops the work instead of letting
+//! it run to completion unobserved.
+
+use crate::{BamlRtError, Result};
+use tokio_util::sync::CancellationToken;
+
+tokio::task_local! {
+    static CANCELLATION: CancellationToken;
+}
+
+/// Cancellation token of the current request, if it can be canceled
+pub fn current_cancellation() -> Option<CancellationToken> {
+    CANCELLATION.try_with(CancellationToken::clone).ok()
+}
+
+/// Run `fut` with `token` in scope, failing with [`BamlRtError::Canceled`]
+/// if it is canceled first
+///
+/// Inside another cancellation scope, canceling either token cancels `fut`.
+pub async fn with_cancellation<F, T>(token: CancellationToken, operation: &str, fut: F) -> Result<T>
+where
+    F: std::future::Future<Output = Result<T>>,
+{
+    let Some(outer) = current_cancellation() else {
+        return CANCELLATION.scope(token, enforce(operation, fut)).await;
+    };
+    // The guard stops the link once `fut` is done
+    let scoped = outer.child_token();
+    let _link_guard = scoped.clone().drop_guard();
+    let link = scoped.clone();
+    tokio::spawn(async move {
+        tokio::select! {
+            _ = token.cancelled() => link.cancel(),
+            _ = link.cancelled() => {}
+        }
+    });
+    CANCELLATION.scope(scoped, enforce(operation, fut)).await
+}
+
+/// Run `fut` under the current cancellation token, failing with
+/// [`BamlRtError::Canceled`] if it is canceled first
+///
+/// Without a token in scope, `fut` runs to completion.
+pub async fn enforce<F, T>(operation: &str, fut: F) -> Result<T>
+where
+    F: std::future::Future<Output = Result<T>>,
+{
+    let Some(token) = current_cancellation() else {
+        return fut.await;
+    };
+    // Polling `fut` first reports the innermost operation that was stopped
+    tokio::select! {
+        biased;
+        result = fut => result,
+        _ = token.cancelled() => Err(BamlRtError::Canceled {
+            operation: operation.to_string(),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn canceling_the_outer_token_stops_nested_work() {
+        let outer = CancellationToken::new();
+        let canceler = outer.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            canceler.cancel();
+        });
+
+        let outcome = with_cancellation(outer, "request", async {
+            with_cancellation(CancellationToken::new(), "inner", async {
+                enforce("tool 'slow'", async {
+                    tokio::time::sleep(Duration::from_secs(5)).await;
+                    Ok(())
+                })
+                .await
+            })
+            .await
+        })
+        .await;
+
+        assert!(matches!(
+            outcome,
+            Err(BamlRtError::Canceled { operation }) if operation == "tool 'slow'"
+        ));
+        assert!(current_cancellation().is_none());
+        assert_eq!(enforce("uncancelable", async { Ok(1) }).await.unwrap(), 1);
+    }
+}
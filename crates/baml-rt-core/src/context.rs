@@ -2,13 +2,57 @@
 //!
 //! This module provides task-local context IDs so async boundaries
 //! can retain request context without requiring JS changes.
+//!
+//! Each context scope also carries [`RequestMetadata`]: key/value pairs any
+//! code handling the request can read or add to, including agent JS through
+//! `bamlContext()`.
 
 use crate::ids::ContextId;
+use serde_json::{Map, Value};
 use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
 use std::time::{SystemTime, UNIX_EPOCH};
 
 tokio::task_local! {
     static CONTEXT_ID: ContextId;
+    static METADATA: RequestMetadata;
+}
+
+/// Metadata key holding the ID of the message a request delivers, which a
+/// client sends again unchanged when it retries the request
+pub const MESSAGE_ID_KEY: &str = "message_id";
+
+/// Metadata shared by everything running in one context scope
+///
+/// Clones share the same map, so a value set by one holder is seen by all.
+#[derive(Debug, Clone, Default)]
+pub struct RequestMetadata(Arc<Mutex<Map<String, Value>>>);
+
+impl RequestMetadata {
+    pub fn new(values: Map<String, Value>) -> Self {
+        Self(Arc::new(Mutex::new(values)))
+    }
+
+    pub fn get(&self, key: &str) -> Option<Value> {
+        self.0
+            .lock()
+            .ok()
+            .and_then(|values| values.get(key).cloned())
+    }
+
+    pub fn set(&self, key: impl Into<String>, value: Value) {
+        if let Ok(mut values) = self.0.lock() {
+            values.insert(key.into(), value);
+        }
+    }
+
+    /// A copy of every entry
+    pub fn snapshot(&self) -> Map<String, Value> {
+        self.0
+            .lock()
+            .map(|values| values.clone())
+            .unwrap_or_default()
+    }
 }
 
 static CONTEXT_COUNTER: AtomicU64 = AtomicU64::new(1);
@@ -30,9 +74,46 @@ pub fn current_or_new() -> ContextId {
     current_context_id().unwrap_or_else(generate_context_id)
 }
 
+/// Metadata of the current context scope
+pub fn current_metadata() -> Option<RequestMetadata> {
+    METADATA.try_with(|metadata| metadata.clone()).ok()
+}
+
+/// Run `fut` in a context scope with `id` and empty metadata
 pub async fn with_context_id<F, T>(id: ContextId, fut: F) -> T
 where
     F: std::future::Future<Output = T>,
 {
-    CONTEXT_ID.scope(id, fut).await
+    with_context(id, RequestMetadata::default(), fut).await
+}
+
+/// Run `fut` in a context scope with `id` and `metadata`
+pub async fn with_context<F, T>(id: ContextId, metadata: RequestMetadata, fut: F) -> T
+where
+    F: std::future::Future<Output = T>,
+{
+    CONTEXT_ID.scope(id, METADATA.scope(metadata, fut)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn metadata_is_scoped_with_the_context_id() {
+        assert!(current_metadata().is_none());
+        let metadata = RequestMetadata::default();
+        let seen = with_context(ContextId::from("ctx-1"), metadata.clone(), async {
+            current_metadata()
+                .unwrap()
+                .set("tenant", Value::from("acme"));
+            with_context_id(ContextId::from("ctx-2"), async {
+                current_metadata().unwrap().get("tenant")
+            })
+            .await
+        })
+        .await;
+        assert_eq!(seen, None);
+        assert_eq!(metadata.get("tenant"), Some(Value::from("acme")));
+    }
 }
@@ -36,3 +36,14 @@ where
 {
     CORRELATION_ID.scope(id, fut).await
 }
+
+/// Run a synchronous closure with `id` as the current correlation ID.
+///
+/// Useful on threads that are not driven by a tokio task, such as host
+/// function callbacks invoked by the JS engine.
+pub fn with_correlation_id_sync<F, T>(id: CorrelationId, f: F) -> T
+where
+    F: FnOnce() -> T,
+{
+    CORRELATION_ID.sync_scope(id, f)
+}
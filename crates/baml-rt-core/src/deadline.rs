@@ -0,0 +1,117 @@
+//! End-to-end deadline propagation for async invocation flows.
+//!
+//! A transport that knows how long its caller will wait scopes the request
+//! with [`with_deadline`]; BAML calls, tool executions, and JS invocations
+//! made anywhere beneath it then [`enforce`] the same deadline, so the whole
+//! chain gives up together instead of each step running to its own timeout.
+
+use crate::{BamlRtError, Result};
+use std::time::Duration;
+use tokio::time::Instant;
+
+tokio::task_local! {
+    static DEADLINE: Deadline;
+}
+
+/// The instant by which a request must finish
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Deadline(Instant);
+
+impl Deadline {
+    /// A deadline `budget` from now
+    pub fn after(budget: Duration) -> Self {
+        Self(Instant::now() + budget)
+    }
+
+    pub fn at(instant: Instant) -> Self {
+        Self(instant)
+    }
+
+    pub fn instant(&self) -> Instant {
+        self.0
+    }
+
+    /// Time left, zero once the deadline has passed
+    pub fn remaining(&self) -> Duration {
+        self.0.saturating_duration_since(Instant::now())
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.remaining().is_zero()
+    }
+}
+
+/// Deadline of the current request, if it has one
+pub fn current_deadline() -> Option<Deadline> {
+    DEADLINE.try_with(|deadline| *deadline).ok()
+}
+
+/// Run `fut` with `deadline` in scope, failing with
+/// [`BamlRtError::DeadlineExceeded`] if it passes first
+///
+/// Inside another deadline's scope, the earlier of the two applies.
+pub async fn with_deadline<F, T>(deadline: Deadline, operation: &str, fut: F) -> Result<T>
+where
+    F: std::future::Future<Output = Result<T>>,
+{
+    let deadline = current_deadline().map_or(deadline, |outer| outer.min(deadline));
+    DEADLINE.scope(deadline, enforce(operation, fut)).await
+}
+
+/// Run `fut` under the current deadline, failing with
+/// [`BamlRtError::DeadlineExceeded`] if it passes first
+///
+/// Without a deadline in scope, `fut` runs unbounded.
+pub async fn enforce<F, T>(operation: &str, fut: F) -> Result<T>
+where
+    F: std::future::Future<Output = Result<T>>,
+{
+    let Some(deadline) = current_deadline() else {
+        return fut.await;
+    };
+    if deadline.is_expired() {
+        return Err(exceeded(operation));
+    }
+    tokio::time::timeout_at(deadline.instant(), fut)
+        .await
+        .unwrap_or_else(|_| Err(exceeded(operation)))
+}
+
+fn exceeded(operation: &str) -> BamlRtError {
+    BamlRtError::DeadlineExceeded {
+        operation: operation.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn nested_work_gives_up_at_the_outer_deadline() {
+        let outcome = with_deadline(
+            Deadline::after(Duration::from_millis(20)),
+            "request",
+            async {
+                with_deadline(Deadline::after(Duration::from_secs(60)), "inner", async {
+                    assert!(current_deadline().unwrap().remaining() <= Duration::from_millis(20));
+                    enforce("tool 'slow'", async {
+                        tokio::time::sleep(Duration::from_secs(5)).await;
+                        Ok(())
+                    })
+                    .await
+                })
+                .await
+            },
+        )
+        .await;
+
+        let error = outcome.unwrap_err();
+        assert!(matches!(
+            &error,
+            BamlRtError::DeadlineExceeded { operation } if operation == "tool 'slow'"
+        ));
+        assert!(current_deadline().is_none());
+        assert_eq!(enforce("unbounded", async { Ok(1) }).await.unwrap(), 1);
+    }
+}
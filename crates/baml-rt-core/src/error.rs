@@ -4,6 +4,9 @@
 //! and error chaining throughout the codebase.
 
 use anyhow::Error as AnyhowError;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fmt;
 use std::time::SystemTimeError;
 use thiserror::Error;
 
@@ -18,6 +21,10 @@ pub enum BamlRtError {
     #[error("QuickJS error: {0}")]
     QuickJs(String),
 
+    /// Exception thrown by JavaScript code running in QuickJS
+    #[error("JS exception: {0}")]
+    JsException(JsException),
+
     /// QuickJS error with source
     #[error("QuickJS error: {context}")]
     QuickJsWithSource {
@@ -26,10 +33,19 @@ pub enum BamlRtError {
         source: Box<dyn std::error::Error + Send + Sync>,
     },
 
+    /// The JS engine can no longer run code: its heap is exhausted, or the
+    /// task driving it died
+    #[error("JS engine failure: {reason}")]
+    EngineFailure { reason: String },
+
     /// Type conversion error between Rust and JavaScript types
     #[error("Type conversion error: {0}")]
     TypeConversion(String),
 
+    /// A value does not match the BAML type expected at `path`
+    #[error("Invalid value at {path}: {message}")]
+    ValueConversion { path: String, message: String },
+
     /// Function not found in registry
     #[error("Function not found: {0}")]
     FunctionNotFound(String),
@@ -54,6 +70,59 @@ pub enum BamlRtError {
     #[error("JSON error: {0}")]
     Json(#[from] serde_json::Error),
 
+    /// Function output rejected by an output guard
+    #[error("Output rejected by guard '{guard}': {reason}")]
+    OutputRejected { guard: String, reason: String },
+
+    /// Call to another agent failed, in transport or with an error response
+    #[error("Agent call to '{target}' failed: {message}")]
+    AgentCall { target: String, message: String },
+
+    /// A task was asked to move to a state it cannot reach from its current one
+    #[error("Task '{task_id}' cannot move from {from} to {to}")]
+    TaskTransition {
+        task_id: String,
+        from: String,
+        to: String,
+    },
+
+    /// An agent package targets a runtime version this runtime cannot run
+    #[error("Agent '{agent}' targets runtime {required} but this is runtime {runtime}; {hint}")]
+    IncompatibleRuntime {
+        agent: String,
+        required: String,
+        runtime: String,
+        hint: String,
+    },
+
+    /// The request's deadline passed before `operation` finished
+    #[error("Deadline exceeded during {operation}")]
+    DeadlineExceeded { operation: String },
+
+    /// The request was canceled before `operation` finished
+    #[error("Canceled during {operation}")]
+    Canceled { operation: String },
+
+    /// The caller of a request could not be authenticated
+    #[error("Unauthenticated: {0}")]
+    Unauthenticated(String),
+
+    /// The caller of a request may not do what it asked
+    #[error("'{principal}' may not {action}")]
+    Forbidden { principal: String, action: String },
+
+    /// A tool call is waiting for a human to approve or deny it
+    #[error("Tool '{tool}' is waiting for approval ({approval_id})")]
+    ApprovalRequired { tool: String, approval_id: String },
+
+    /// A tenant sent more requests than its rate limit allows
+    #[error("Rate limit exceeded for tenant '{tenant}'")]
+    RateLimited { tenant: String },
+
+    /// A secret provider failed to read a secret
+    #[error("Failed to read secret '{name}': {message}")]
+    Secret { name: String, message: String },
+
     /// Tool execution error
     #[error("Tool execution error: {0}")]
     ToolExecution(String),
@@ -112,5 +181,127 @@ pub enum BamlRtError {
     TarHeaderPath(#[source] std::io::Error),
 }
 
+impl BamlRtError {
+    /// The JavaScript exception behind this error, if any
+    pub fn js_exception(&self) -> Option<&JsException> {
+        match self {
+            BamlRtError::JsException(exception) => Some(exception),
+            _ => None,
+        }
+    }
+}
+
+/// Maximum depth of `cause` chains kept from a JS exception
+const MAX_CAUSE_DEPTH: usize = 8;
+
+/// A JavaScript exception, as thrown inside QuickJS
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JsException {
+    /// Error class name, e.g. `TypeError`
+    pub name: String,
+    pub message: String,
+    /// Stack trace, with frames mapped to original sources when possible
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stack: Option<String>,
+    /// The exception's `cause`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cause: Option<Box<JsException>>,
+}
+
+impl JsException {
+    pub fn new(name: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            message: message.into(),
+            stack: None,
+            cause: None,
+        }
+    }
+
+    /// Read an error object produced by the bridge's JS wrappers:
+    /// `{ error, name?, message?, stack?, cause? }`
+    ///
+    /// Returns `None` when `value` is not an error object.
+    pub fn from_error_object(value: &Value) -> Option<Self> {
+        Self::from_error_object_at(value, 0)
+    }
+
+    fn from_error_object_at(value: &Value, depth: usize) -> Option<Self> {
+        let object = value.as_object()?;
+        let message = object
+            .get("message")
+            .or_else(|| object.get("error"))
+            .and_then(Value::as_str)?;
+        let name = object
+            .get("name")
+            .and_then(Value::as_str)
+            .filter(|name| !name.is_empty())
+            .unwrap_or("Error");
+        let stack = object
+            .get("stack")
+            .and_then(Value::as_str)
+            .map(str::trim_end)
+            .filter(|stack| !stack.is_empty())
+            .map(str::to_string);
+        let cause = object
+            .get("cause")
+            .filter(|_| depth < MAX_CAUSE_DEPTH)
+            .and_then(|cause| Self::from_error_object_at(cause, depth + 1))
+            .map(Box::new);
+        Some(Self {
+            name: name.to_string(),
+            message: message.to_string(),
+            stack,
+            cause,
+        })
+    }
+}
+
+impl fmt::Display for JsException {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.name, self.message)?;
+        if let Some(stack) = &self.stack {
+            write!(f, "\n{}", stack)?;
+        }
+        if let Some(cause) = &self.cause {
+            write!(f, "\ncaused by: {}", cause)?;
+        }
+        Ok(())
+    }
+}
+
 /// Result type alias for convenience
 pub type Result<T> = std::result::Result<T, BamlRtError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn reads_nested_js_error_objects() {
+        let exception = JsException::from_error_object(&json!({
+            "error": "lookup failed",
+            "name": "TypeError",
+            "message": "lookup failed",
+            "stack": "    at find (src/index.ts:4:3)\n",
+            "cause": { "error": "timeout" },
+        }))
+        .unwrap();
+
+        assert_eq!(exception.name, "TypeError");
+        assert_eq!(
+            exception.stack.as_deref(),
+            Some("    at find (src/index.ts:4:3)")
+        );
+        assert_eq!(
+            exception.cause.as_deref(),
+            Some(&JsException::new("Error", "timeout"))
+        );
+        assert_eq!(
+            exception.to_string(),
+            "TypeError: lookup failed\n    at find (src/index.ts:4:3)\ncaused by: Error: timeout"
+        );
+        assert!(JsException::from_error_object(&json!({ "result": 1 })).is_none());
+    }
+}
@@ -0,0 +1,220 @@
+//! Typed runtime events, published on one bus per runtime.
+//!
+//! Every crate that does observable work reports it here: the interceptor
+//! registry reports LLM and tool calls, the JS bridge reports uncaught
+//! exceptions, and the A2A layer reports task updates and bridge failover.
+//! An embedder subscribes once with [`EventBus::subscribe`] instead of wiring
+//! up an interceptor, a provenance store, and a task channel.
+//!
+//! The bus is a broadcast channel: a subscriber that falls more than the
+//! bus's capacity behind misses the oldest events and is told how many with
+//! `RecvError::Lagged`. Events are only built when someone is subscribed.
+
+use crate::JsException;
+use crate::correlation::current_correlation_id;
+use crate::ids::{ContextId, CorrelationId, TaskId};
+use serde::Serialize;
+use serde_json::Value;
+use tokio::sync::broadcast;
+
+/// Events a bus holds for subscribers that have not read them yet
+pub const DEFAULT_EVENT_CAPACITY: usize = 1024;
+
+/// Kind of A2A task update
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskUpdateKind {
+    Status,
+    Artifact,
+}
+
+/// Something that happened in the runtime
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RuntimeEvent {
+    /// An LLM call was allowed by the interceptors and is about to run
+    LlmCallStarted {
+        function_name: String,
+        client: String,
+        model: String,
+        context_id: ContextId,
+        correlation_id: Option<CorrelationId>,
+    },
+    /// An LLM call was blocked by an interceptor
+    LlmCallBlocked {
+        function_name: String,
+        client: String,
+        context_id: ContextId,
+        correlation_id: Option<CorrelationId>,
+        reason: String,
+    },
+    /// An LLM call finished; `error` is set when it failed
+    LlmCallCompleted {
+        function_name: String,
+        client: String,
+        context_id: ContextId,
+        correlation_id: Option<CorrelationId>,
+        duration_ms: u64,
+        error: Option<String>,
+    },
+    /// A tool call was allowed by the interceptors and is about to run
+    ToolCallStarted {
+        tool_name: String,
+        function_name: Option<String>,
+        context_id: ContextId,
+        correlation_id: Option<CorrelationId>,
+    },
+    /// A tool call was blocked by an interceptor
+    ToolCallBlocked {
+        tool_name: String,
+        context_id: ContextId,
+        correlation_id: Option<CorrelationId>,
+        reason: String,
+    },
+    /// A tool call finished; `error` is set when it failed
+    ToolCompleted {
+        tool_name: String,
+        context_id: ContextId,
+        correlation_id: Option<CorrelationId>,
+        duration_ms: u64,
+        error: Option<String>,
+    },
+    /// An A2A task changed status or produced an artifact
+    ///
+    /// `update` is the A2A wire form of the update, e.g. a
+    /// `TaskStatusUpdateEvent` for [`TaskUpdateKind::Status`].
+    TaskUpdated {
+        task_id: Option<TaskId>,
+        context_id: Option<ContextId>,
+        kind: TaskUpdateKind,
+        update: Value,
+    },
+    /// JavaScript threw an exception that reached the host
+    JsError {
+        /// What the host was doing, e.g. the function it invoked
+        context: String,
+        exception: JsException,
+        correlation_id: Option<CorrelationId>,
+    },
+    /// The JS bridge hit a fatal engine error and was marked unhealthy
+    BridgeUnhealthy { generation: u64, error: String },
+    /// A replacement JS bridge was installed
+    BridgeReplaced { generation: u64, from_standby: bool },
+    /// Building a replacement JS bridge failed
+    BridgeReplacementFailed { generation: u64, error: String },
+}
+
+impl RuntimeEvent {
+    /// Name of the event, as in its serialized `type` field
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::LlmCallStarted { .. } => "llm_call_started",
+            Self::LlmCallBlocked { .. } => "llm_call_blocked",
+            Self::LlmCallCompleted { .. } => "llm_call_completed",
+            Self::ToolCallStarted { .. } => "tool_call_started",
+            Self::ToolCallBlocked { .. } => "tool_call_blocked",
+            Self::ToolCompleted { .. } => "tool_completed",
+            Self::TaskUpdated { .. } => "task_updated",
+            Self::JsError { .. } => "js_error",
+            Self::BridgeUnhealthy { .. } => "bridge_unhealthy",
+            Self::BridgeReplaced { .. } => "bridge_replaced",
+            Self::BridgeReplacementFailed { .. } => "bridge_replacement_failed",
+        }
+    }
+
+    /// A JS exception thrown under the current correlation ID
+    pub fn js_error(context: impl Into<String>, exception: JsException) -> Self {
+        Self::JsError {
+            context: context.into(),
+            exception,
+            correlation_id: current_correlation_id(),
+        }
+    }
+}
+
+/// Broadcast bus of [`RuntimeEvent`]s
+///
+/// Clones publish to and subscribe on the same bus.
+#[derive(Debug, Clone)]
+pub struct EventBus {
+    tx: broadcast::Sender<RuntimeEvent>,
+}
+
+impl EventBus {
+    /// A bus holding up to [`DEFAULT_EVENT_CAPACITY`] unread events
+    pub fn new() -> Self {
+        Self::with_capacity(DEFAULT_EVENT_CAPACITY)
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        let (tx, _rx) = broadcast::channel(capacity.max(1));
+        Self { tx }
+    }
+
+    /// Receive every event published from now on
+    pub fn subscribe(&self) -> broadcast::Receiver<RuntimeEvent> {
+        self.tx.subscribe()
+    }
+
+    /// Whether anyone would receive a published event
+    pub fn has_subscribers(&self) -> bool {
+        self.tx.receiver_count() > 0
+    }
+
+    /// Publish `event` to the current subscribers
+    pub fn publish(&self, event: RuntimeEvent) {
+        let _ = self.tx.send(event);
+    }
+
+    /// Publish the event `build` returns, only building it when someone is
+    /// subscribed
+    pub fn publish_with(&self, build: impl FnOnce() -> RuntimeEvent) {
+        if self.has_subscribers() {
+            self.publish(build());
+        }
+    }
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn subscribers_receive_events_published_after_they_subscribe() {
+        let bus = EventBus::new();
+        let mut built = false;
+        bus.publish_with(|| {
+            built = true;
+            RuntimeEvent::BridgeReplaced {
+                generation: 1,
+                from_standby: false,
+            }
+        });
+        assert!(!built);
+
+        let mut events = bus.clone().subscribe();
+        bus.publish(RuntimeEvent::js_error(
+            "invoking handler",
+            JsException::new("TypeError", "x is undefined"),
+        ));
+
+        let event = events.recv().await.unwrap();
+        assert_eq!(event.name(), "js_error");
+        assert_eq!(
+            serde_json::to_value(&event).unwrap(),
+            json!({
+                "type": "js_error",
+                "context": "invoking handler",
+                "exception": { "name": "TypeError", "message": "x is undefined" },
+                "correlation_id": null
+            })
+        );
+    }
+}
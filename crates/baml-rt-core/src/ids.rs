@@ -83,3 +83,8 @@ define_id_type!(
     /// Event identifier for provenance events
     EventId
 );
+
+define_id_type!(
+    /// Tenant identifier for requests served on behalf of one customer
+    TenantId
+);
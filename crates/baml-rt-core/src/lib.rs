@@ -1,10 +1,23 @@
 //! BAML runtime core types and shared utilities.
 
+pub mod auth;
+pub mod baml_value;
+pub mod cancellation;
 pub mod context;
 pub mod correlation;
+pub mod deadline;
 pub mod error;
+pub mod events;
 pub mod ids;
+pub mod manifest;
+pub mod media;
+#[cfg(feature = "registry")]
+pub mod registry;
+pub mod tenant;
 pub mod types;
+pub mod verbosity;
 
-pub use error::{BamlRtError, Result};
-pub use ids::{ArtifactId, ContextId, CorrelationId, EventId, MessageId, TaskId};
+pub use error::{BamlRtError, JsException, Result};
+pub use events::{EventBus, RuntimeEvent};
+pub use ids::{ArtifactId, ContextId, CorrelationId, EventId, MessageId, TaskId, TenantId};
+pub use manifest::AgentManifest;
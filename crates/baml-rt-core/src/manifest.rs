@@ -0,0 +1,535 @@
+//! Agent package manifests
+//!
+//! Every agent package carries a `manifest.json` describing the agent: its
+//! name and version, the script to evaluate, and what it expects of the
+//! runtime it is loaded into. The builder checks the manifest before
+//! packaging and the runner checks it again at load time, so a package that
+//! cannot run fails with an error naming the field to fix.
+//!
+//! ```json
+//! {
+//!   "name": "support-agent",
+//!   "version": "1.2.0",
+//!   "entry_point": "dist/index.js",
+//!   "runtime_version": "0.1.0",
+//!   "required_env": ["OPENAI_API_KEY"],
+//!   "tools": ["lookup_order"],
+//!   "capabilities": { "streaming": true }
+//! }
+//! ```
+//!
+//! Fields the runtime does not know about are kept in
+//! [`AgentManifest::extra`] so tooling can add its own.
+
+use crate::{BamlRtError, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::path::{Component, Path};
+
+/// File name of the manifest inside an agent directory or package
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+/// Version of this runtime, checked against a manifest's `runtime_version`
+pub const RUNTIME_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Script evaluated when a manifest does not name one
+pub const DEFAULT_ENTRY_POINT: &str = "dist/index.js";
+
+/// Default cap on the npm source bundled into an agent
+pub const DEFAULT_MAX_DEPENDENCY_BYTES: u64 = 1024 * 1024;
+
+/// Media type of the layer holding a tar.gz package in an OCI artifact
+pub const PACKAGE_MEDIA_TYPE: &str = "application/vnd.baml.agent.package.v1.tar+gzip";
+
+/// Media type of the layer holding a zip package in an OCI artifact
+pub const ZIP_PACKAGE_MEDIA_TYPE: &str = "application/vnd.baml.agent.package.v1+zip";
+
+/// Variables holding the username and password packages are pushed to and
+/// pulled from OCI registries with
+pub const REGISTRY_USERNAME_ENV: &str = "BAML_REGISTRY_USERNAME";
+pub const REGISTRY_PASSWORD_ENV: &str = "BAML_REGISTRY_PASSWORD";
+
+/// What an agent package declares about itself
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AgentManifest {
+    pub name: String,
+    pub version: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Script evaluated to initialize the agent, relative to the package root
+    #[serde(default = "default_entry_point")]
+    pub entry_point: String,
+    /// Runtime version the agent was built for; see
+    /// [`AgentManifest::check_runtime_version`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub runtime_version: Option<String>,
+    /// Environment variables that must be set for the agent to load
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub required_env: Vec<String>,
+    /// Tools the agent expects to be registered once its code has run
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tools: Vec<String>,
+    /// BAML functions offered the registered tools
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tool_functions: Vec<String>,
+    /// Agents this agent may call
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub calls: Vec<String>,
+    /// Function calls run on a schedule
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub schedules: Vec<ManifestSchedule>,
+    #[serde(default)]
+    pub capabilities: ManifestCapabilities,
+    /// Shape of the chunks the agent's handler streams; A2A stream
+    /// responses when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stream_format: Option<StreamFormat>,
+    /// npm packages the builder may bundle from `node_modules`
+    #[serde(default)]
+    pub bundle: ManifestBundle,
+    /// Digest of the package's other files, recorded by the builder
+    /// (`sha256:<hex>`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_digest: Option<String>,
+    /// Fields the runtime does not interpret
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// A function call listed under `schedules`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ManifestSchedule {
+    /// Label used in logs and task metadata; defaults to the function name
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Five-field cron expression or an `@` alias, in UTC
+    pub cron: String,
+    /// JS function to call
+    pub function: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub args: Option<Value>,
+}
+
+/// The A2A features an agent relies on
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ManifestCapabilities {
+    /// Answers `message.stream` and `tasks.subscribe`
+    pub streaming: bool,
+    /// Delivers task updates to push notification endpoints
+    pub push_notifications: bool,
+}
+
+impl Default for ManifestCapabilities {
+    fn default() -> Self {
+        Self {
+            streaming: true,
+            push_notifications: false,
+        }
+    }
+}
+
+/// Shape of the chunks an agent's handler returns for `message.stream`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StreamFormat {
+    /// A2A stream responses, or messages and tasks to wrap in them
+    #[default]
+    A2a,
+    /// OpenAI chat completion chunks, whose `delta.content` becomes an
+    /// agent message
+    #[serde(rename = "openai")]
+    OpenAi,
+    /// Raw strings, each becoming an agent message
+    Text,
+}
+
+/// What the builder may pull from `node_modules` into the bundle
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ManifestBundle {
+    /// Packages that bare imports may resolve to; anything else is refused
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub dependencies: Vec<String>,
+    /// Total bytes of package source the bundler will read
+    pub max_dependency_bytes: u64,
+}
+
+impl Default for ManifestBundle {
+    fn default() -> Self {
+        Self {
+            dependencies: Vec::new(),
+            max_dependency_bytes: DEFAULT_MAX_DEPENDENCY_BYTES,
+        }
+    }
+}
+
+fn default_entry_point() -> String {
+    DEFAULT_ENTRY_POINT.to_string()
+}
+
+impl AgentManifest {
+    /// A manifest with the given name and version and defaults elsewhere
+    pub fn new(name: impl Into<String>, version: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            version: version.into(),
+            description: None,
+            entry_point: default_entry_point(),
+            runtime_version: None,
+            required_env: Vec::new(),
+            tools: Vec::new(),
+            tool_functions: Vec::new(),
+            calls: Vec::new(),
+            schedules: Vec::new(),
+            capabilities: ManifestCapabilities::default(),
+            stream_format: None,
+            bundle: ManifestBundle::default(),
+            content_digest: None,
+            extra: Map::new(),
+        }
+    }
+
+    /// Parse and validate manifest JSON
+    pub fn from_json(content: &str) -> Result<Self> {
+        let manifest: Self = serde_json::from_str(content).map_err(|err| {
+            BamlRtError::InvalidArgumentWithSource {
+                message: format!("{} is not a valid agent manifest: {}", MANIFEST_FILE, err),
+                source: Box::new(err),
+            }
+        })?;
+        manifest.validate()?;
+        Ok(manifest)
+    }
+
+    /// Read and validate the manifest at `path`
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path).map_err(|err| {
+            BamlRtError::InvalidArgumentWithSource {
+                message: format!("Cannot read agent manifest {}: {}", path.display(), err),
+                source: Box::new(err),
+            }
+        })?;
+        Self::from_json(&content)
+    }
+
+    /// Check the fields serde cannot: names, versions, and paths
+    pub fn validate(&self) -> Result<()> {
+        if self.name.trim().is_empty() {
+            return Err(invalid("'name' must not be empty"));
+        }
+        if parse_version(&self.version).is_none() {
+            return Err(invalid(format!(
+                "'version' must look like 1.2.3, got '{}'",
+                self.version
+            )));
+        }
+        if let Some(runtime_version) = &self.runtime_version
+            && parse_version(runtime_version).is_none()
+        {
+            return Err(invalid(format!(
+                "'runtime_version' must look like 0.1.0, got '{}'",
+                runtime_version
+            )));
+        }
+        let entry_point = Path::new(&self.entry_point);
+        if self.entry_point.is_empty()
+            || !entry_point
+                .components()
+                .all(|component| matches!(component, Component::Normal(_)))
+        {
+            return Err(invalid(format!(
+                "'entry_point' must be a path inside the package, got '{}'",
+                self.entry_point
+            )));
+        }
+        for variable in &self.required_env {
+            if !is_env_name(variable) {
+                return Err(invalid(format!(
+                    "'required_env' entry '{}' is not an environment variable name",
+                    variable
+                )));
+            }
+        }
+        for (field, names) in [
+            ("tools", &self.tools),
+            ("tool_functions", &self.tool_functions),
+            ("calls", &self.calls),
+        ] {
+            if names.iter().any(|name| name.trim().is_empty()) {
+                return Err(invalid(format!("'{}' must not contain empty names", field)));
+            }
+        }
+        for (index, schedule) in self.schedules.iter().enumerate() {
+            if schedule.function.trim().is_empty() || schedule.cron.trim().is_empty() {
+                return Err(invalid(format!(
+                    "'schedules' entry {} needs a 'function' and a 'cron'",
+                    index
+                )));
+            }
+        }
+        if let Some(package) = self
+            .bundle
+            .dependencies
+            .iter()
+            .find(|package| !is_package_name(package))
+        {
+            return Err(invalid(format!(
+                "'bundle.dependencies' entry '{}' is not an npm package name",
+                package
+            )));
+        }
+        if self.bundle.max_dependency_bytes == 0 {
+            return Err(invalid("'bundle.max_dependency_bytes' must be positive"));
+        }
+        Ok(())
+    }
+
+    /// Check that the environment provides every variable in `required_env`
+    pub fn check_environment(&self) -> Result<()> {
+        let missing: Vec<&str> = self
+            .required_env
+            .iter()
+            .filter(|variable| std::env::var_os(variable.as_str()).is_none())
+            .map(String::as_str)
+            .collect();
+        if missing.is_empty() {
+            return Ok(());
+        }
+        Err(BamlRtError::InvalidArgument(format!(
+            "Agent '{}' needs environment variables that are not set: {}",
+            self.name,
+            missing.join(", ")
+        )))
+    }
+
+    /// Check that `registered` includes every tool in `tools`
+    pub fn check_tools(&self, registered: &[String]) -> Result<()> {
+        let missing: Vec<&str> = self
+            .tools
+            .iter()
+            .filter(|tool| !registered.contains(tool))
+            .map(String::as_str)
+            .collect();
+        if missing.is_empty() {
+            return Ok(());
+        }
+        Err(BamlRtError::InvalidArgument(format!(
+            "Agent '{}' declares tools its code does not register: {} (registered: {})",
+            self.name,
+            missing.join(", "),
+            if registered.is_empty() {
+                "none".to_string()
+            } else {
+                registered.join(", ")
+            }
+        )))
+    }
+
+    /// Check that `runtime` can run an agent built for `runtime_version`
+    ///
+    /// Compatibility follows semver: the runtime must be no older than the
+    /// target and share its major version, or its minor version while the
+    /// major version is 0. Manifests without `runtime_version` run anywhere.
+    pub fn check_runtime_version(&self, runtime: &str) -> Result<()> {
+        let Some(required) = &self.runtime_version else {
+            return Ok(());
+        };
+        let (Some(target), Some(running)) = (parse_version(required), parse_version(runtime))
+        else {
+            return Err(invalid(format!(
+                "'runtime_version' '{}' cannot be compared with runtime {}",
+                required, runtime
+            )));
+        };
+        let hint = if running < target {
+            "upgrade the runtime"
+        } else if target.0 != running.0 || (target.0 == 0 && target.1 != running.1) {
+            "rebuild the agent for this runtime"
+        } else {
+            return Ok(());
+        };
+        Err(BamlRtError::IncompatibleRuntime {
+            agent: self.name.clone(),
+            required: required.clone(),
+            runtime: runtime.to_string(),
+            hint: format!("{}, or pass --ignore-version to load it anyway", hint),
+        })
+    }
+
+    /// Check that the runtime offers every capability the agent relies on
+    pub fn check_capabilities(&self) -> Result<()> {
+        if self.capabilities.push_notifications {
+            return Err(BamlRtError::InvalidArgument(format!(
+                "Agent '{}' requires push notifications, which this runtime does not support; \
+                 set capabilities.push_notifications to false",
+                self.name
+            )));
+        }
+        Ok(())
+    }
+}
+
+fn invalid(reason: impl std::fmt::Display) -> BamlRtError {
+    BamlRtError::InvalidArgument(format!("{} {}", MANIFEST_FILE, reason))
+}
+
+fn is_env_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|first| first.is_ascii_alphabetic() || first == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// `name` or `@scope/name`, as npm allows them
+fn is_package_name(name: &str) -> bool {
+    let is_part = |part: &str| {
+        !part.is_empty()
+            && !part.starts_with(['.', '_'])
+            && part.chars().all(|c| {
+                c.is_ascii_lowercase() || c.is_ascii_digit() || matches!(c, '-' | '.' | '_' | '~')
+            })
+    };
+    match name.strip_prefix('@') {
+        Some(scoped) => scoped
+            .split_once('/')
+            .is_some_and(|(scope, name)| is_part(scope) && is_part(name)),
+        None => is_part(name),
+    }
+}
+
+/// `major.minor.patch`, allowing a `-pre` or `+build` suffix
+fn parse_version(version: &str) -> Option<(u64, u64, u64)> {
+    let core = version.split(['-', '+']).next()?;
+    let mut parts = core.split('.').map(|part| part.parse::<u64>().ok());
+    let version = (parts.next()??, parts.next()??, parts.next()??);
+    parts.next().is_none().then_some(version)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn manifests_parse_with_defaults_and_keep_extra_fields() {
+        let manifest = AgentManifest::from_json(
+            r#"{
+                "name": "example-agent",
+                "version": "1.0.0",
+                "runtime_version": "0.1.0",
+                "metadata": { "tags": ["example"] }
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(manifest.entry_point, DEFAULT_ENTRY_POINT);
+        assert_eq!(manifest.runtime_version.as_deref(), Some("0.1.0"));
+        assert!(manifest.capabilities.streaming);
+        assert_eq!(manifest.extra["metadata"]["tags"][0], "example");
+
+        let round_trip: AgentManifest =
+            serde_json::from_value(serde_json::to_value(&manifest).unwrap()).unwrap();
+        assert_eq!(round_trip, manifest);
+    }
+
+    #[test]
+    fn invalid_manifests_name_the_field_to_fix() {
+        let error = |json: &str| AgentManifest::from_json(json).unwrap_err().to_string();
+
+        assert!(error(r#"{ "version": "1.0.0" }"#).contains("missing field `name`"));
+        assert!(error(r#"{ "name": "a", "version": "one" }"#).contains("'version'"));
+        assert!(
+            error(r#"{ "name": "a", "version": "1.0.0", "entry_point": "../x.js" }"#)
+                .contains("'entry_point'")
+        );
+        assert!(
+            error(r#"{ "name": "a", "version": "1.0.0", "required_env": ["API KEY"] }"#)
+                .contains("'required_env'")
+        );
+        assert!(
+            error(r#"{ "name": "a", "version": "1.0.0", "capabilities": { "stream": true } }"#)
+                .contains("unknown field `stream`")
+        );
+        assert!(
+            error(r#"{ "name": "a", "version": "1.0.0", "schedules": [{ "function": "f" }] }"#)
+                .contains("missing field `cron`")
+        );
+        assert!(
+            error(r#"{ "name": "a", "version": "1.0.0", "bundle": { "dependencies": ["../x"] } }"#)
+                .contains("'bundle.dependencies'")
+        );
+    }
+
+    #[test]
+    fn bundle_dependencies_accept_scoped_packages() {
+        let manifest = AgentManifest::from_json(
+            r#"{
+                "name": "a",
+                "version": "1.0.0",
+                "bundle": { "dependencies": ["zod", "@scope/date-fns.tz"] }
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(manifest.bundle.dependencies.len(), 2);
+        assert_eq!(
+            manifest.bundle.max_dependency_bytes,
+            DEFAULT_MAX_DEPENDENCY_BYTES
+        );
+    }
+
+    #[test]
+    fn runtime_checks_report_what_is_missing() {
+        let mut manifest = AgentManifest::new("agent", "1.0.0");
+        manifest.required_env = vec!["BAML_RT_MANIFEST_TEST_UNSET".to_string()];
+        manifest.tools = vec!["search".to_string(), "fetch".to_string()];
+
+        let env = manifest.check_environment().unwrap_err().to_string();
+        assert!(env.contains("BAML_RT_MANIFEST_TEST_UNSET"));
+
+        let tools = manifest
+            .check_tools(&["search".to_string()])
+            .unwrap_err()
+            .to_string();
+        assert!(tools.contains("fetch") && tools.contains("registered: search"));
+        assert!(
+            manifest
+                .check_tools(&["fetch".to_string(), "search".to_string()])
+                .is_ok()
+        );
+
+        assert!(manifest.check_capabilities().is_ok());
+        manifest.capabilities.push_notifications = true;
+        assert!(manifest.check_capabilities().is_err());
+    }
+
+    #[test]
+    fn runtime_versions_are_checked_for_semver_compatibility() {
+        let targeting = |version: &str| {
+            let mut manifest = AgentManifest::new("agent", "1.0.0");
+            manifest.runtime_version = Some(version.to_string());
+            manifest
+        };
+
+        assert!(
+            AgentManifest::new("agent", "1.0.0")
+                .check_runtime_version("0.1.0")
+                .is_ok()
+        );
+        assert!(targeting("0.1.0").check_runtime_version("0.1.4").is_ok());
+        assert!(targeting("1.2.0").check_runtime_version("1.5.0").is_ok());
+
+        let newer = targeting("0.3.0")
+            .check_runtime_version("0.2.1")
+            .unwrap_err();
+        assert!(matches!(newer, BamlRtError::IncompatibleRuntime { .. }));
+        assert!(newer.to_string().contains("upgrade the runtime"));
+        assert!(newer.to_string().contains("--ignore-version"));
+
+        let older_minor = targeting("0.1.0")
+            .check_runtime_version("0.2.0")
+            .unwrap_err();
+        assert!(older_minor.to_string().contains("rebuild the agent"));
+        assert!(targeting("1.0.0").check_runtime_version("2.0.0").is_err());
+    }
+}
@@ -0,0 +1,248 @@
+//! Media arguments of BAML functions
+//!
+//! Parameters typed `image`, `audio`, `pdf` or `video` accept these JSON
+//! values:
+//!
+//! - a URL string, which the model provider fetches, or a data URI
+//!   (`data:image/png;base64,...`)
+//! - `{ "url": ... }`, or `{ "uri": ... }`
+//! - `{ "base64": ..., "media_type": "image/png" }`; `bytes` and `mediaType`
+//!   are read as well, so the file parts of A2A messages can be passed as is
+//! - a [`Bytes`] value, which is what a `Uint8Array` becomes when JS passes
+//!   it, alone or as `{ "bytes": <Uint8Array>, "media_type": ... }`
+//! - `{ "file": "photos/cat.png" }`, read from disk when the [`MediaPolicy`]
+//!   allows the directory; no directory is allowed by default
+//!
+//! A missing media type is guessed from the file extension or data URI where
+//! there is one.
+
+use crate::types::{Bytes, MediaKind};
+use baml_types::{BamlMedia, BamlMediaType};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use serde_json::{Map, Value};
+use std::path::{Path, PathBuf};
+
+/// Which local files media arguments may be read from
+#[derive(Debug, Clone)]
+pub struct MediaPolicy {
+    /// Directories files may be read from, including their subdirectories
+    pub allowed_dirs: Vec<PathBuf>,
+    /// Largest file read
+    pub max_bytes: u64,
+}
+
+impl Default for MediaPolicy {
+    fn default() -> Self {
+        Self {
+            allowed_dirs: Vec::new(),
+            max_bytes: 20 * 1024 * 1024,
+        }
+    }
+}
+
+impl MediaPolicy {
+    /// Allow reading files under `dir`; call repeatedly to allow several
+    pub fn with_allowed_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.allowed_dirs.push(dir.into());
+        self
+    }
+
+    pub fn with_max_bytes(mut self, max_bytes: u64) -> Self {
+        self.max_bytes = max_bytes;
+        self
+    }
+
+    /// Read `path` if it lies in an allowed directory
+    fn read(&self, path: &str) -> Result<Vec<u8>, String> {
+        let denied = || format!("reading '{}' is not allowed by the media policy", path);
+        let resolved = Path::new(path)
+            .canonicalize()
+            .map_err(|e| format!("cannot read '{}': {}", path, e))?;
+        let allowed = self.allowed_dirs.iter().any(|dir| {
+            dir.canonicalize()
+                .is_ok_and(|dir| resolved.starts_with(dir))
+        });
+        if !allowed {
+            return Err(denied());
+        }
+        let size = std::fs::metadata(&resolved)
+            .map_err(|e| format!("cannot read '{}': {}", path, e))?
+            .len();
+        if size > self.max_bytes {
+            return Err(format!(
+                "'{}' is {} bytes, more than the {} allowed",
+                path, size, self.max_bytes
+            ));
+        }
+        std::fs::read(&resolved).map_err(|e| format!("cannot read '{}': {}", path, e))
+    }
+}
+
+/// The media `value` encodes, or why it encodes none
+pub fn media_from_json(
+    value: &Value,
+    kind: MediaKind,
+    policy: &MediaPolicy,
+) -> Result<BamlMedia, String> {
+    let media_type = match kind {
+        MediaKind::Image => BamlMediaType::Image,
+        MediaKind::Audio => BamlMediaType::Audio,
+        MediaKind::Pdf => BamlMediaType::Pdf,
+        MediaKind::Video => BamlMediaType::Video,
+    };
+    if let Some(bytes) = Bytes::from_value(value) {
+        return Ok(BamlMedia::base64(
+            media_type,
+            STANDARD.encode(&*bytes),
+            None,
+        ));
+    }
+    match value {
+        Value::String(source) => Ok(from_string(media_type, source, None)),
+        Value::Object(object) => from_object(media_type, object, policy),
+        _ => Err(
+            "expected a URL, a data URI, bytes or an object with \"url\", \"base64\" or \"file\""
+                .to_string(),
+        ),
+    }
+}
+
+fn from_string(media_type: BamlMediaType, source: &str, mime_type: Option<String>) -> BamlMedia {
+    match parse_data_uri(source) {
+        Some((data_mime_type, data)) => BamlMedia::base64(
+            media_type,
+            data.to_string(),
+            mime_type.or(data_mime_type.map(str::to_string)),
+        ),
+        None => BamlMedia::url(media_type, source.to_string(), mime_type),
+    }
+}
+
+fn from_object(
+    media_type: BamlMediaType,
+    object: &Map<String, Value>,
+    policy: &MediaPolicy,
+) -> Result<BamlMedia, String> {
+    let field = |names: &[&str]| names.iter().find_map(|name| object.get(*name));
+    let mime_type = field(&["media_type", "mediaType", "mime_type"])
+        .and_then(Value::as_str)
+        .map(str::to_string);
+
+    if let Some(url) = field(&["url", "uri"]).and_then(Value::as_str) {
+        return Ok(from_string(media_type, url, mime_type));
+    }
+    if let Some(data) = field(&["base64", "bytes"]) {
+        if let Some(bytes) = Bytes::from_value(data) {
+            return Ok(BamlMedia::base64(
+                media_type,
+                STANDARD.encode(&*bytes),
+                mime_type,
+            ));
+        }
+        if let Some(data) = data.as_str() {
+            return Ok(BamlMedia::base64(media_type, data.to_string(), mime_type));
+        }
+    }
+    if let Some(path) = field(&["file", "path"]).and_then(Value::as_str) {
+        let data = policy.read(path)?;
+        let mime_type = mime_type.or_else(|| guess_mime_type(path).map(str::to_string));
+        return Ok(BamlMedia::base64(
+            media_type,
+            STANDARD.encode(data),
+            mime_type,
+        ));
+    }
+    Err("expected an object with \"url\", \"base64\" or \"file\"".to_string())
+}
+
+/// Media type and base64 data of a `data:<type>;base64,<data>` URI
+fn parse_data_uri(source: &str) -> Option<(Option<&str>, &str)> {
+    let (header, data) = source.strip_prefix("data:")?.split_once(',')?;
+    let mime_type = header.strip_suffix(";base64")?;
+    Some(((!mime_type.is_empty()).then_some(mime_type), data))
+}
+
+fn guess_mime_type(path: &str) -> Option<&'static str> {
+    let extension = Path::new(path).extension()?.to_str()?.to_ascii_lowercase();
+    Some(match extension.as_str() {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "mp3" => "audio/mpeg",
+        "wav" => "audio/wav",
+        "ogg" => "audio/ogg",
+        "flac" => "audio/flac",
+        "m4a" => "audio/mp4",
+        "pdf" => "application/pdf",
+        "mp4" => "video/mp4",
+        "webm" => "video/webm",
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use baml_types::BamlMediaContent;
+    use serde_json::json;
+
+    fn base64_of(media: &BamlMedia) -> (&str, Option<&str>) {
+        match &media.content {
+            BamlMediaContent::Base64(data) => (data.base64.as_str(), media.mime_type.as_deref()),
+            other => panic!("expected base64 media, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn reads_every_documented_encoding() {
+        let policy = MediaPolicy::default();
+        let image = |value: Value| media_from_json(&value, MediaKind::Image, &policy).unwrap();
+
+        let media = image(json!("https://example.com/cat.png"));
+        assert!(
+            matches!(&media.content, BamlMediaContent::Url(url) if url.url.ends_with("cat.png"))
+        );
+
+        let media = image(json!("data:image/png;base64,iVBORw0K"));
+        assert_eq!(base64_of(&media), ("iVBORw0K", Some("image/png")));
+
+        let media = image(json!({ "bytes": "iVBORw0K", "mediaType": "image/png", "kind": "file" }));
+        assert_eq!(base64_of(&media), ("iVBORw0K", Some("image/png")));
+
+        let media = image(json!({ "bytes": Bytes::new([1, 2, 3]), "media_type": "image/gif" }));
+        assert_eq!(base64_of(&media), ("AQID", Some("image/gif")));
+
+        let media = image(Bytes::new([1, 2, 3]).to_value());
+        assert_eq!(base64_of(&media), ("AQID", None));
+
+        assert!(media_from_json(&json!(42), MediaKind::Image, &policy).is_err());
+    }
+
+    #[test]
+    fn files_are_read_only_from_allowed_dirs() {
+        let dir = std::env::temp_dir().join(format!("baml-rt-media-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("clip.wav");
+        std::fs::write(&path, [1u8, 2, 3]).unwrap();
+        let value = json!({ "file": path.to_str().unwrap() });
+
+        let denied = media_from_json(&value, MediaKind::Audio, &MediaPolicy::default());
+        assert!(
+            denied
+                .unwrap_err()
+                .contains("not allowed by the media policy")
+        );
+
+        let policy = MediaPolicy::default().with_allowed_dir(&dir);
+        let media = media_from_json(&value, MediaKind::Audio, &policy).unwrap();
+        assert_eq!(base64_of(&media), ("AQID", Some("audio/wav")));
+
+        let too_small = policy.with_max_bytes(2);
+        let error = media_from_json(&value, MediaKind::Audio, &too_small).unwrap_err();
+        assert!(error.contains("more than the 2 allowed"));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
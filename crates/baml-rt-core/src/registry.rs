@@ -0,0 +1,35 @@
+//! OCI registry access shared by the tools that push and pull packages
+//!
+//! Both sides authenticate with the credentials in [`REGISTRY_USERNAME_ENV`]
+//! and [`REGISTRY_PASSWORD_ENV`] when both are set, anonymously otherwise.
+
+use crate::manifest::{REGISTRY_PASSWORD_ENV, REGISTRY_USERNAME_ENV};
+use crate::{BamlRtError, Result};
+use oci_client::Reference;
+use oci_client::errors::OciDistributionError;
+use oci_client::secrets::RegistryAuth;
+
+/// Basic credentials from the environment, if both are set
+pub fn registry_auth() -> RegistryAuth {
+    match (
+        std::env::var(REGISTRY_USERNAME_ENV),
+        std::env::var(REGISTRY_PASSWORD_ENV),
+    ) {
+        (Ok(username), Ok(password)) => RegistryAuth::Basic(username, password),
+        _ => RegistryAuth::Anonymous,
+    }
+}
+
+/// Parse a reference such as `ghcr.io/acme/support-agent:1.2.0`
+pub fn parse_reference(reference: &str) -> Result<Reference> {
+    reference
+        .parse()
+        .map_err(|err| BamlRtError::InvalidArgumentWithSource {
+            message: format!("Invalid OCI reference: {}", reference),
+            source: Box::new(err),
+        })
+}
+
+pub fn registry_error(err: OciDistributionError) -> BamlRtError {
+    BamlRtError::Io(std::io::Error::other(err))
+}
@@ -0,0 +1,129 @@
+//! Tenant propagation for async invocation flows.
+//!
+//! A runner serving several customers scopes each request with the tenant
+//! it was made for. Task stores, provenance, and metrics read
+//! [`current_tenant_id`] to keep one tenant's data apart from another's,
+//! and BAML calls take provider credentials from the tenant's
+//! [`TenantConfig`] before falling back to the process environment.
+
+use crate::ids::TenantId;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+tokio::task_local! {
+    static TENANT: Option<Tenant>;
+}
+
+/// Settings for one tenant
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TenantConfig {
+    env: HashMap<String, String>,
+    requests_per_minute: Option<u32>,
+}
+
+impl TenantConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set `name` in the environment BAML calls see for this tenant, e.g.
+    /// `OPENAI_API_KEY`
+    pub fn with_env(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.env.insert(name.into(), value.into());
+        self
+    }
+
+    /// Admit at most `limit` requests per minute from this tenant
+    pub fn with_requests_per_minute(mut self, limit: u32) -> Self {
+        self.requests_per_minute = Some(limit);
+        self
+    }
+
+    pub fn env(&self) -> &HashMap<String, String> {
+        &self.env
+    }
+
+    pub fn requests_per_minute(&self) -> Option<u32> {
+        self.requests_per_minute
+    }
+}
+
+/// A tenant and its settings
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tenant {
+    id: TenantId,
+    config: Arc<TenantConfig>,
+}
+
+impl Tenant {
+    pub fn new(id: TenantId, config: Arc<TenantConfig>) -> Self {
+        Self { id, config }
+    }
+
+    pub fn id(&self) -> &TenantId {
+        &self.id
+    }
+
+    pub fn config(&self) -> &TenantConfig {
+        &self.config
+    }
+}
+
+/// Tenant of the current request, if it names one
+pub fn current_tenant() -> Option<Tenant> {
+    TENANT.try_with(|tenant| tenant.clone()).ok().flatten()
+}
+
+pub fn current_tenant_id() -> Option<TenantId> {
+    current_tenant().map(|tenant| tenant.id)
+}
+
+/// Run `fut` on behalf of `tenant`; `None` runs it on behalf of no tenant,
+/// even inside another tenant's scope
+pub async fn with_tenant<F, T>(tenant: Option<Tenant>, fut: F) -> T
+where
+    F: std::future::Future<Output = T>,
+{
+    TENANT.scope(tenant, fut).await
+}
+
+/// Run a synchronous closure on behalf of `tenant`
+pub fn with_tenant_sync<F, T>(tenant: Option<Tenant>, f: F) -> T
+where
+    F: FnOnce() -> T,
+{
+    TENANT.sync_scope(tenant, f)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn an_inner_scope_without_a_tenant_clears_the_outer_one() {
+        let acme = Tenant::new(
+            TenantId::from("acme"),
+            Arc::new(TenantConfig::new().with_env("OPENAI_API_KEY", "sk-acme")),
+        );
+        let (outer, inner) = with_tenant(Some(acme), async {
+            let outer = current_tenant();
+            (
+                outer,
+                with_tenant(None, async { current_tenant_id() }).await,
+            )
+        })
+        .await;
+        let outer = outer.unwrap();
+        assert_eq!(outer.id().as_str(), "acme");
+        assert_eq!(
+            outer
+                .config()
+                .env()
+                .get("OPENAI_API_KEY")
+                .map(String::as_str),
+            Some("sk-acme")
+        );
+        assert_eq!(inner, None);
+        assert_eq!(current_tenant_id(), None);
+    }
+}
@@ -1,31 +1,401 @@
 //! Type definitions for BAML runtime integration
 
-use serde::{Deserialize, Serialize};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+use std::fmt;
+use std::ops::Deref;
 
 /// Represents a BAML function signature
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FunctionSignature {
     pub name: String,
+    /// Parameter names in declaration order
+    #[serde(default)]
+    pub param_names: Vec<String>,
     pub input_types: Vec<BamlType>,
     pub output_type: BamlType,
+    /// The `///` comment above the function's declaration
+    #[serde(default)]
+    pub docstring: Option<String>,
+}
+
+/// A function as described to callers, e.g. for building a form for it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FunctionDescription {
+    pub name: String,
+    pub params: Vec<ParamDescription>,
+    /// Return type in BAML syntax
+    pub return_type: String,
+    /// Whether the function can stream partial results
+    pub streaming: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub docstring: Option<String>,
+}
+
+/// A function parameter as described to callers
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ParamDescription {
+    pub name: String,
+    /// Type in BAML syntax
+    #[serde(rename = "type")]
+    pub ty: String,
+}
+
+impl From<&FunctionSignature> for FunctionDescription {
+    /// Describe a BAML function; every BAML function can stream
+    fn from(signature: &FunctionSignature) -> Self {
+        Self {
+            name: signature.name.clone(),
+            params: signature
+                .param_names
+                .iter()
+                .zip(&signature.input_types)
+                .map(|(name, ty)| ParamDescription {
+                    name: name.clone(),
+                    ty: ty.to_string(),
+                })
+                .collect(),
+            return_type: signature.output_type.to_string(),
+            streaming: true,
+            docstring: signature.docstring.clone(),
+        }
+    }
 }
 
 /// Represents a BAML type
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum BamlType {
     String,
     Int,
     Float,
     Bool,
+    Null,
+    Media(MediaKind),
+    /// A string, int or bool literal
+    Literal(Value),
     List(Box<BamlType>),
     Map(Box<BamlType>, Box<BamlType>), // key type, value type
     Object(Vec<ObjectField>),
     Optional(Box<BamlType>),
-    // TODO: Add more types as needed
+    Union(Vec<BamlType>),
+    /// A class, enum or type alias declared in the schema
+    Named(String),
+    /// Any value; used where the type is unknown
+    Any,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Kinds of BAML media values
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MediaKind {
+    Image,
+    Audio,
+    Pdf,
+    Video,
+}
+
+impl BamlType {
+    /// Parse a type written in BAML syntax, e.g. `map<string, Resume[]>?`
+    ///
+    /// Types that are not understood, such as tuples or types carrying
+    /// attributes, parse as [`BamlType::Any`].
+    pub fn parse(source: &str) -> Self {
+        let mut parser = TypeParser {
+            source: source.as_bytes(),
+            pos: 0,
+        };
+        parser
+            .union()
+            .filter(|_| parser.at_end())
+            .unwrap_or(BamlType::Any)
+    }
+}
+
+/// Writes the type in BAML syntax
+impl fmt::Display for BamlType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BamlType::String => f.write_str("string"),
+            BamlType::Int => f.write_str("int"),
+            BamlType::Float => f.write_str("float"),
+            BamlType::Bool => f.write_str("bool"),
+            BamlType::Null => f.write_str("null"),
+            BamlType::Media(MediaKind::Image) => f.write_str("image"),
+            BamlType::Media(MediaKind::Audio) => f.write_str("audio"),
+            BamlType::Media(MediaKind::Pdf) => f.write_str("pdf"),
+            BamlType::Media(MediaKind::Video) => f.write_str("video"),
+            BamlType::Literal(literal) => write!(f, "{}", literal),
+            BamlType::List(item) => write!(f, "{}[]", item),
+            BamlType::Map(key, value) => write!(f, "map<{}, {}>", key, value),
+            BamlType::Object(_) => f.write_str("object"),
+            BamlType::Optional(inner) => write!(f, "{}?", inner),
+            BamlType::Union(variants) => {
+                let variants: Vec<String> = variants.iter().map(ToString::to_string).collect();
+                write!(f, "({})", variants.join(" | "))
+            }
+            BamlType::Named(name) => f.write_str(name),
+            BamlType::Any => f.write_str("any"),
+        }
+    }
+}
+
+/// Recursive descent parser behind [`BamlType::parse`]
+struct TypeParser<'a> {
+    source: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> TypeParser<'a> {
+    fn union(&mut self) -> Option<BamlType> {
+        let mut variants = vec![self.postfix()?];
+        while self.eat(b'|') {
+            variants.push(self.postfix()?);
+        }
+        Some(if variants.len() == 1 {
+            variants.remove(0)
+        } else {
+            BamlType::Union(variants)
+        })
+    }
+
+    fn postfix(&mut self) -> Option<BamlType> {
+        let mut ty = self.atom()?;
+        loop {
+            if self.eat(b'[') {
+                self.expect(b']')?;
+                ty = BamlType::List(Box::new(ty));
+            } else if self.eat(b'?') {
+                ty = BamlType::Optional(Box::new(ty));
+            } else {
+                return Some(ty);
+            }
+        }
+    }
+
+    fn atom(&mut self) -> Option<BamlType> {
+        self.skip_whitespace();
+        match *self.source.get(self.pos)? {
+            b'(' => {
+                self.pos += 1;
+                let ty = self.union()?;
+                self.expect(b')')?;
+                Some(ty)
+            }
+            b'"' => {
+                self.pos += 1;
+                let start = self.pos;
+                while *self.source.get(self.pos)? != b'"' {
+                    self.pos += 1;
+                }
+                let literal = std::str::from_utf8(&self.source[start..self.pos]).ok()?;
+                self.pos += 1;
+                Some(BamlType::Literal(Value::String(literal.to_string())))
+            }
+            b'-' | b'0'..=b'9' => {
+                let start = self.pos;
+                self.pos += 1;
+                while self.source.get(self.pos).is_some_and(u8::is_ascii_digit) {
+                    self.pos += 1;
+                }
+                let literal = std::str::from_utf8(&self.source[start..self.pos]).ok()?;
+                Some(BamlType::Literal(Value::from(literal.parse::<i64>().ok()?)))
+            }
+            _ => {
+                let name = self.identifier()?;
+                Some(match name {
+                    "string" => BamlType::String,
+                    "int" => BamlType::Int,
+                    "float" => BamlType::Float,
+                    "bool" => BamlType::Bool,
+                    "null" => BamlType::Null,
+                    "true" | "false" => BamlType::Literal(Value::Bool(name == "true")),
+                    "image" => BamlType::Media(MediaKind::Image),
+                    "audio" => BamlType::Media(MediaKind::Audio),
+                    "pdf" => BamlType::Media(MediaKind::Pdf),
+                    "video" => BamlType::Media(MediaKind::Video),
+                    "map" => {
+                        self.expect(b'<')?;
+                        let key = self.union()?;
+                        self.expect(b',')?;
+                        let value = self.union()?;
+                        self.expect(b'>')?;
+                        BamlType::Map(Box::new(key), Box::new(value))
+                    }
+                    _ => BamlType::Named(name.to_string()),
+                })
+            }
+        }
+    }
+
+    fn identifier(&mut self) -> Option<&'a str> {
+        let source = self.source;
+        let start = self.pos;
+        while self
+            .source
+            .get(self.pos)
+            .is_some_and(|c| c.is_ascii_alphanumeric() || *c == b'_' || *c == b'.')
+        {
+            self.pos += 1;
+        }
+        if self.pos == start {
+            return None;
+        }
+        std::str::from_utf8(&source[start..self.pos]).ok()
+    }
+
+    /// Consume `token`, after any whitespace, if it comes next
+    fn eat(&mut self, token: u8) -> bool {
+        self.skip_whitespace();
+        let found = self.source.get(self.pos) == Some(&token);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn expect(&mut self, token: u8) -> Option<()> {
+        self.eat(token).then_some(())
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self
+            .source
+            .get(self.pos)
+            .is_some_and(u8::is_ascii_whitespace)
+        {
+            self.pos += 1;
+        }
+    }
+
+    fn at_end(&mut self) -> bool {
+        self.skip_whitespace();
+        self.pos == self.source.len()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ObjectField {
     pub name: String,
     pub ty: BamlType,
 }
+
+/// Key of the JSON object that carries [`Bytes`]
+pub const BYTES_KEY: &str = "$bytes";
+
+/// Binary data inside a JSON value
+///
+/// Serializes as `{ "$bytes": "<base64>" }`. The JS bridge hands such values
+/// to JS as `Uint8Array`s and turns `Uint8Array` and `ArrayBuffer` arguments
+/// back into them, so tools can take and return binary data without JS
+/// code encoding it.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Bytes(pub Vec<u8>);
+
+impl Bytes {
+    pub fn new(data: impl Into<Vec<u8>>) -> Self {
+        Self(data.into())
+    }
+
+    /// The bytes `value` carries, if it is a `{ "$bytes": ... }` object
+    pub fn from_value(value: &Value) -> Option<Self> {
+        let map = value.as_object().filter(|map| map.len() == 1)?;
+        let encoded = map.get(BYTES_KEY)?.as_str()?;
+        STANDARD.decode(encoded).ok().map(Self)
+    }
+
+    /// The `{ "$bytes": ... }` object carrying these bytes
+    pub fn to_value(&self) -> Value {
+        serde_json::json!({ BYTES_KEY: STANDARD.encode(&self.0) })
+    }
+
+    pub fn into_inner(self) -> Vec<u8> {
+        self.0
+    }
+}
+
+impl Deref for Bytes {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl From<Vec<u8>> for Bytes {
+    fn from(data: Vec<u8>) -> Self {
+        Self(data)
+    }
+}
+
+impl From<Bytes> for Value {
+    fn from(bytes: Bytes) -> Self {
+        bytes.to_value()
+    }
+}
+
+impl Serialize for Bytes {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        self.to_value().serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Bytes {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let value = Value::deserialize(deserializer)?;
+        Self::from_value(&value)
+            .ok_or_else(|| D::Error::custom(format!("expected {{ \"{}\": <base64> }}", BYTES_KEY)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn parses_baml_type_syntax() {
+        assert_eq!(
+            BamlType::parse("map<string, Resume[]>?"),
+            BamlType::Optional(Box::new(BamlType::Map(
+                Box::new(BamlType::String),
+                Box::new(BamlType::List(Box::new(BamlType::Named(
+                    "Resume".to_string()
+                )))),
+            )))
+        );
+        assert_eq!(
+            BamlType::parse("(\"high\" | 3 | image | null)[]"),
+            BamlType::List(Box::new(BamlType::Union(vec![
+                BamlType::Literal(json!("high")),
+                BamlType::Literal(json!(3)),
+                BamlType::Media(MediaKind::Image),
+                BamlType::Null,
+            ])))
+        );
+        assert_eq!(
+            BamlType::parse("int @check(positive, {{ this > 0 }})"),
+            BamlType::Any
+        );
+        assert_eq!(BamlType::parse("(int, string)"), BamlType::Any);
+
+        let source = "map<string, (\"high\" | 3 | Resume)[]>?";
+        assert_eq!(BamlType::parse(source).to_string(), source);
+    }
+
+    #[test]
+    fn bytes_round_trip_through_json() {
+        let bytes = Bytes::new(vec![0u8, 1, 254, 255]);
+        let value = serde_json::to_value(&bytes).unwrap();
+        assert_eq!(value, json!({ "$bytes": "AAH+/w==" }));
+        assert_eq!(serde_json::from_value::<Bytes>(value).unwrap(), bytes);
+
+        assert_eq!(Bytes::from_value(&json!({ "$bytes": "not base64!" })), None);
+        assert_eq!(
+            Bytes::from_value(&json!({ "$bytes": "", "other": 1 })),
+            None
+        );
+    }
+}
@@ -0,0 +1,125 @@
+//! Per-request log verbosity overrides.
+//!
+//! A request may ask for more detailed logging than the global filter allows.
+//! Overrides are keyed by correlation ID, so only events emitted while that
+//! correlation ID is current are affected. Tracing filters consult
+//! [`allows`] to let those events through.
+
+use crate::correlation;
+use crate::ids::CorrelationId;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{OnceLock, RwLock};
+use tracing::Level;
+
+static OVERRIDES: OnceLock<RwLock<HashMap<CorrelationId, Verbosity>>> = OnceLock::new();
+static ACTIVE_OVERRIDES: AtomicUsize = AtomicUsize::new(0);
+
+fn overrides() -> &'static RwLock<HashMap<CorrelationId, Verbosity>> {
+    OVERRIDES.get_or_init(|| RwLock::new(HashMap::new()))
+}
+
+/// Elevated verbosity level for a single request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Verbosity {
+    Debug,
+    Trace,
+}
+
+impl Verbosity {
+    /// Parse a verbosity name (`"debug"` or `"trace"`), case-insensitively.
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "debug" => Some(Verbosity::Debug),
+            "trace" => Some(Verbosity::Trace),
+            _ => None,
+        }
+    }
+
+    /// Whether events at `level` should be emitted under this verbosity.
+    pub fn allows(self, level: &Level) -> bool {
+        match self {
+            Verbosity::Debug => *level <= Level::DEBUG,
+            Verbosity::Trace => true,
+        }
+    }
+}
+
+/// Removes a verbosity override when dropped.
+#[must_use = "the override is removed as soon as the guard is dropped"]
+pub struct VerbosityGuard {
+    id: CorrelationId,
+}
+
+impl Drop for VerbosityGuard {
+    fn drop(&mut self) {
+        if let Ok(mut map) = overrides().write()
+            && map.remove(&self.id).is_some()
+        {
+            ACTIVE_OVERRIDES.fetch_sub(1, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Raise verbosity for everything logged under `id` until the guard drops.
+pub fn elevate(id: CorrelationId, verbosity: Verbosity) -> VerbosityGuard {
+    if let Ok(mut map) = overrides().write()
+        && map.insert(id.clone(), verbosity).is_none()
+    {
+        ACTIVE_OVERRIDES.fetch_add(1, Ordering::Relaxed);
+    }
+    VerbosityGuard { id }
+}
+
+/// Verbosity override for a correlation ID, if any.
+pub fn verbosity_for(id: &CorrelationId) -> Option<Verbosity> {
+    if ACTIVE_OVERRIDES.load(Ordering::Relaxed) == 0 {
+        return None;
+    }
+    overrides().read().ok()?.get(id).copied()
+}
+
+/// Verbosity override for the current correlation ID, if any.
+pub fn current_verbosity() -> Option<Verbosity> {
+    if ACTIVE_OVERRIDES.load(Ordering::Relaxed) == 0 {
+        return None;
+    }
+    verbosity_for(&correlation::current_correlation_id()?)
+}
+
+/// Whether an event at `level` is enabled by the current request's override.
+pub fn allows(level: &Level) -> bool {
+    current_verbosity().is_some_and(|verbosity| verbosity.allows(level))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn override_applies_only_to_its_correlation_id() {
+        let elevated = CorrelationId::from("corr-verbose");
+        let other = CorrelationId::from("corr-quiet");
+        let guard = elevate(elevated.clone(), Verbosity::Debug);
+
+        let elevated_allows = correlation::with_correlation_id(elevated.clone(), async {
+            (allows(&Level::DEBUG), allows(&Level::TRACE))
+        })
+        .await;
+        assert_eq!(elevated_allows, (true, false));
+
+        let other_allows =
+            correlation::with_correlation_id(other, async { allows(&Level::DEBUG) }).await;
+        assert!(!other_allows);
+
+        drop(guard);
+        assert_eq!(verbosity_for(&elevated), None);
+    }
+
+    #[test]
+    fn parses_verbosity_names() {
+        assert_eq!(Verbosity::parse("TRACE"), Some(Verbosity::Trace));
+        assert_eq!(Verbosity::parse("debug"), Some(Verbosity::Debug));
+        assert_eq!(Verbosity::parse("info"), None);
+    }
+}
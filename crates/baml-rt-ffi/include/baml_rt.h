@@ -0,0 +1,68 @@
+/*
+ * C interface to the BAML runtime (baml-rt-ffi).
+ *
+ * Values cross the boundary as NUL-terminated UTF-8 JSON strings. Every
+ * function returns a baml_rt_status; on failure a message is written to
+ * `error_out` when it is not NULL. Strings written to out-pointers belong to
+ * the caller and are released with baml_rt_string_free.
+ */
+#ifndef BAML_RT_H
+#define BAML_RT_H
+
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#define BAML_RT_ABI_VERSION 1
+
+typedef enum baml_rt_status {
+  BAML_RT_OK = 0,
+  BAML_RT_INVALID_ARGUMENT = 1,
+  BAML_RT_ERROR = 2,
+  BAML_RT_PANIC = 3,
+} baml_rt_status;
+
+typedef struct BamlRtRuntime baml_rt_runtime;
+typedef struct BamlRtToolOutput baml_rt_tool_output;
+
+/* Runs on runtime worker threads, possibly several at once */
+typedef baml_rt_status (*baml_rt_tool_callback)(void *user_data, const char *args_json,
+                                                baml_rt_tool_output *output);
+typedef void (*baml_rt_free_user_data)(void *user_data);
+
+uint32_t baml_rt_abi_version(void);
+
+baml_rt_status baml_rt_runtime_new(baml_rt_runtime **runtime_out, char **error_out);
+void baml_rt_runtime_free(baml_rt_runtime *runtime);
+
+baml_rt_status baml_rt_load_schema(const baml_rt_runtime *runtime, const char *schema_path,
+                                   char **error_out);
+
+/* `args_json` may be NULL for no arguments */
+baml_rt_status baml_rt_invoke_function(const baml_rt_runtime *runtime, const char *function_name,
+                                       const char *args_json, char **result_out,
+                                       char **error_out);
+
+/* `free_user_data` may be NULL; it is called when the runtime is freed or
+ * registration fails */
+baml_rt_status baml_rt_register_tool(const baml_rt_runtime *runtime, const char *name,
+                                     const char *description, const char *input_schema_json,
+                                     baml_rt_tool_callback callback, void *user_data,
+                                     baml_rt_free_user_data free_user_data, char **error_out);
+
+baml_rt_status baml_rt_execute_tool(const baml_rt_runtime *runtime, const char *name,
+                                    const char *args_json, char **result_out, char **error_out);
+
+/* For tool callbacks; both copy their argument */
+baml_rt_status baml_rt_tool_output_set_result(baml_rt_tool_output *output, const char *json);
+baml_rt_status baml_rt_tool_output_set_error(baml_rt_tool_output *output, const char *message);
+
+void baml_rt_string_free(char *value);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* BAML_RT_H */
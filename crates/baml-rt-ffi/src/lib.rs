@@ -0,0 +1,462 @@
+//! C interface for embedding the runtime
+//!
+//! Exposes the BAML runtime manager to hosts that link a C library, such as
+//! Go (cgo), Java (JNI/FFM), and .NET (P/Invoke); the declarations are in
+//! `include/baml_rt.h`. Values cross the boundary as NUL-terminated UTF-8
+//! JSON strings.
+//!
+//! Conventions shared by every function:
+//!
+//! - Functions return a [`BamlRtStatus`]; outputs go through out-pointers.
+//! - On failure, a message is written to `error_out` when it is not NULL.
+//! - Strings written to out-pointers belong to the caller, who releases them
+//!   with [`baml_rt_string_free`]. Input strings are only borrowed.
+//! - Panics never cross the boundary; they are reported as
+//!   [`BamlRtStatus::Panic`].
+//!
+//! Each runtime owns a Tokio runtime and blocks the calling thread until a
+//! call completes, so a runtime can be shared by host threads but must not
+//! be called from inside a tool callback.
+
+use async_trait::async_trait;
+use baml_rt_core::{BamlRtError, Result};
+use baml_rt_quickjs::BamlRuntimeManager;
+use baml_rt_tools::{ToolExecutor, ToolMetadata};
+use serde_json::Value;
+use std::ffi::{CStr, CString, c_char, c_void};
+use std::panic::{AssertUnwindSafe, catch_unwind};
+use std::ptr;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+/// Version of this interface, raised on every incompatible change
+pub const BAML_RT_ABI_VERSION: u32 = 1;
+
+/// Outcome of a call
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BamlRtStatus {
+    Ok = 0,
+    /// A pointer was NULL, a string was not UTF-8 or JSON, or a name was
+    /// unknown or already taken
+    InvalidArgument = 1,
+    /// The runtime failed, e.g. loading a schema or running a function
+    Error = 2,
+    /// The runtime panicked; it should not be used further
+    Panic = 3,
+}
+
+/// A tool implemented by the host
+///
+/// Called with the `user_data` given at registration and the arguments as
+/// JSON. It reports its result through `output` with
+/// [`baml_rt_tool_output_set_result`] or [`baml_rt_tool_output_set_error`]
+/// and returns [`BamlRtStatus::Ok`] on success. Callbacks run on runtime
+/// worker threads, possibly several at once.
+pub type BamlRtToolCallback = extern "C" fn(
+    user_data: *mut c_void,
+    args_json: *const c_char,
+    output: *mut BamlRtToolOutput,
+) -> BamlRtStatus;
+
+/// Releases a tool's `user_data` once the tool is dropped with its runtime
+pub type BamlRtFreeUserData = extern "C" fn(user_data: *mut c_void);
+
+/// A runtime with its schema and tools
+pub struct BamlRtRuntime {
+    tokio: tokio::runtime::Runtime,
+    manager: Arc<RwLock<BamlRuntimeManager>>,
+}
+
+/// Where a tool callback puts its result
+#[derive(Default)]
+pub struct BamlRtToolOutput {
+    result: Option<std::result::Result<String, String>>,
+}
+
+/// The host's `user_data`, freed with the tool
+struct UserData {
+    ptr: *mut c_void,
+    free: Option<BamlRtFreeUserData>,
+}
+
+// The host promises at registration that its callback and `user_data` may
+// be used from any thread
+unsafe impl Send for UserData {}
+unsafe impl Sync for UserData {}
+
+impl Drop for UserData {
+    fn drop(&mut self) {
+        if let Some(free) = self.free {
+            free(self.ptr);
+        }
+    }
+}
+
+/// A tool implemented by a host callback
+struct CallbackTool {
+    callback: BamlRtToolCallback,
+    user_data: Arc<UserData>,
+}
+
+#[async_trait]
+impl ToolExecutor for CallbackTool {
+    async fn execute(&self, args: Value) -> Result<Value> {
+        let callback = self.callback;
+        let user_data = self.user_data.clone();
+        let args = CString::new(args.to_string())
+            .map_err(|err| BamlRtError::ToolExecution(err.to_string()))?;
+        let (status, output) = tokio::task::spawn_blocking(move || {
+            let mut output = BamlRtToolOutput::default();
+            let status = callback(user_data.ptr, args.as_ptr(), &mut output);
+            (status, output)
+        })
+        .await
+        .map_err(|err| BamlRtError::ToolExecution(format!("Tool callback panicked: {}", err)))?;
+
+        match (status, output.result) {
+            (BamlRtStatus::Ok, Some(Ok(json))) => serde_json::from_str(&json).map_err(|err| {
+                BamlRtError::ToolExecution(format!("Tool result is not JSON: {}", err))
+            }),
+            (BamlRtStatus::Ok, None) => Ok(Value::Null),
+            (_, Some(Err(message))) => Err(BamlRtError::ToolExecution(message)),
+            (status, _) => Err(BamlRtError::ToolExecution(format!(
+                "Tool callback failed with status {:?}",
+                status
+            ))),
+        }
+    }
+}
+
+fn status_of(err: &BamlRtError) -> BamlRtStatus {
+    match err {
+        BamlRtError::InvalidArgument(_)
+        | BamlRtError::InvalidArgumentWithSource { .. }
+        | BamlRtError::FunctionNotFound(_)
+        | BamlRtError::Json(_) => BamlRtStatus::InvalidArgument,
+        _ => BamlRtStatus::Error,
+    }
+}
+
+/// `value` as a caller-owned C string; interior NULs are dropped
+fn into_c_string(value: String) -> *mut c_char {
+    CString::new(value.replace('\0', ""))
+        .unwrap_or_default()
+        .into_raw()
+}
+
+/// Store `value` in `out` unless `out` is NULL
+///
+/// # Safety
+///
+/// `out` must be NULL or valid for writes.
+unsafe fn write_string(out: *mut *mut c_char, value: String) {
+    if !out.is_null() {
+        unsafe { *out = into_c_string(value) };
+    }
+}
+
+/// Borrow the C string `value`, naming it `what` in errors
+///
+/// # Safety
+///
+/// `value` must be NULL or a NUL-terminated string that outlives the call.
+unsafe fn read_str<'a>(value: *const c_char, what: &str) -> Result<&'a str> {
+    if value.is_null() {
+        return Err(BamlRtError::InvalidArgument(format!("{} is NULL", what)));
+    }
+    unsafe { CStr::from_ptr(value) }
+        .to_str()
+        .map_err(|_| BamlRtError::InvalidArgument(format!("{} is not UTF-8", what)))
+}
+
+/// Parse the JSON string `value`; NULL is an empty object
+///
+/// # Safety
+///
+/// As for [`read_str`].
+unsafe fn read_json(value: *const c_char, what: &str) -> Result<Value> {
+    if value.is_null() {
+        return Ok(Value::Object(Default::default()));
+    }
+    let json = unsafe { read_str(value, what) }?;
+    serde_json::from_str(json)
+        .map_err(|err| BamlRtError::InvalidArgument(format!("{} is not JSON: {}", what, err)))
+}
+
+/// Borrow the runtime behind `runtime`
+///
+/// # Safety
+///
+/// `runtime` must be NULL or a pointer from [`baml_rt_runtime_new`] that has
+/// not been freed.
+unsafe fn runtime_ref<'a>(runtime: *const BamlRtRuntime) -> Result<&'a BamlRtRuntime> {
+    unsafe { runtime.as_ref() }
+        .ok_or_else(|| BamlRtError::InvalidArgument("runtime is NULL".to_string()))
+}
+
+/// Run `body`, reporting its error or panic through `error_out`
+///
+/// # Safety
+///
+/// `error_out` must be NULL or valid for writes.
+unsafe fn ffi_call(error_out: *mut *mut c_char, body: impl FnOnce() -> Result<()>) -> BamlRtStatus {
+    match catch_unwind(AssertUnwindSafe(body)) {
+        Ok(Ok(())) => BamlRtStatus::Ok,
+        Ok(Err(err)) => {
+            unsafe { write_string(error_out, err.to_string()) };
+            status_of(&err)
+        }
+        Err(_) => {
+            unsafe { write_string(error_out, "baml-rt panicked".to_string()) };
+            BamlRtStatus::Panic
+        }
+    }
+}
+
+/// The [`BAML_RT_ABI_VERSION`] this library implements
+#[unsafe(no_mangle)]
+pub extern "C" fn baml_rt_abi_version() -> u32 {
+    BAML_RT_ABI_VERSION
+}
+
+/// Create a runtime with no schema loaded, storing it in `runtime_out`
+///
+/// # Safety
+///
+/// `runtime_out` must be valid for writes; `error_out` must be NULL or
+/// valid for writes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn baml_rt_runtime_new(
+    runtime_out: *mut *mut BamlRtRuntime,
+    error_out: *mut *mut c_char,
+) -> BamlRtStatus {
+    unsafe {
+        ffi_call(error_out, || {
+            if runtime_out.is_null() {
+                return Err(BamlRtError::InvalidArgument(
+                    "runtime_out is NULL".to_string(),
+                ));
+            }
+            let tokio = tokio::runtime::Builder::new_multi_thread()
+                .enable_all()
+                .build()?;
+            let manager = BamlRuntimeManager::new()?;
+            let runtime = Box::new(BamlRtRuntime {
+                tokio,
+                manager: Arc::new(RwLock::new(manager)),
+            });
+            *runtime_out = Box::into_raw(runtime);
+            Ok(())
+        })
+    }
+}
+
+/// Free a runtime and its tools; NULL is ignored
+///
+/// # Safety
+///
+/// `runtime` must be NULL or a pointer from [`baml_rt_runtime_new`] that has
+/// not been freed, and no call may be using it.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn baml_rt_runtime_free(runtime: *mut BamlRtRuntime) {
+    if !runtime.is_null() {
+        let runtime = unsafe { Box::from_raw(runtime) };
+        let _ = catch_unwind(AssertUnwindSafe(|| drop(runtime)));
+    }
+}
+
+/// Load the BAML schema in the directory `schema_path`
+///
+/// # Safety
+///
+/// `runtime` must be a live runtime, `schema_path` a NUL-terminated string,
+/// and `error_out` NULL or valid for writes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn baml_rt_load_schema(
+    runtime: *const BamlRtRuntime,
+    schema_path: *const c_char,
+    error_out: *mut *mut c_char,
+) -> BamlRtStatus {
+    unsafe {
+        ffi_call(error_out, || {
+            let runtime = runtime_ref(runtime)?;
+            let schema_path = read_str(schema_path, "schema_path")?;
+            runtime.tokio.block_on(async {
+                let mut manager = runtime.manager.write().await;
+                manager.load_schema(schema_path)
+            })
+        })
+    }
+}
+
+/// Call the BAML function `function_name` with the JSON object `args_json`
+/// (NULL for none), storing its JSON result in `result_out`
+///
+/// # Safety
+///
+/// `runtime` must be a live runtime, the strings NUL-terminated (or NULL
+/// where allowed), and the out-pointers valid for writes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn baml_rt_invoke_function(
+    runtime: *const BamlRtRuntime,
+    function_name: *const c_char,
+    args_json: *const c_char,
+    result_out: *mut *mut c_char,
+    error_out: *mut *mut c_char,
+) -> BamlRtStatus {
+    unsafe {
+        ffi_call(error_out, || {
+            let runtime = runtime_ref(runtime)?;
+            let function_name = read_str(function_name, "function_name")?;
+            let args = read_json(args_json, "args_json")?;
+            let result = runtime.tokio.block_on(async {
+                let manager = runtime.manager.read().await;
+                manager.invoke_function(function_name, args).await
+            })?;
+            write_string(result_out, result.to_string());
+            Ok(())
+        })
+    }
+}
+
+/// Register `callback` as the tool `name`, described to the model by
+/// `description` and the JSON schema `input_schema_json`
+///
+/// `free_user_data`, when not NULL, is called with `user_data` when the
+/// runtime is freed, or right away if registration fails.
+///
+/// # Safety
+///
+/// `runtime` must be a live runtime and the strings NUL-terminated.
+/// `callback` and `user_data` must be safe to use from any thread for as
+/// long as the runtime lives.
+#[unsafe(no_mangle)]
+#[allow(clippy::too_many_arguments)]
+pub unsafe extern "C" fn baml_rt_register_tool(
+    runtime: *const BamlRtRuntime,
+    name: *const c_char,
+    description: *const c_char,
+    input_schema_json: *const c_char,
+    callback: Option<BamlRtToolCallback>,
+    user_data: *mut c_void,
+    free_user_data: Option<BamlRtFreeUserData>,
+    error_out: *mut *mut c_char,
+) -> BamlRtStatus {
+    // Owned from here on, so every failure below releases it
+    let user_data = Arc::new(UserData {
+        ptr: user_data,
+        free: free_user_data,
+    });
+    unsafe {
+        ffi_call(error_out, || {
+            let runtime = runtime_ref(runtime)?;
+            let callback = callback
+                .ok_or_else(|| BamlRtError::InvalidArgument("callback is NULL".to_string()))?;
+            let metadata = ToolMetadata {
+                name: read_str(name, "name")?.to_string(),
+                description: read_str(description, "description")?.to_string(),
+                input_schema: read_json(input_schema_json, "input_schema_json")?,
+                requires_approval: false,
+            };
+            let executor: Arc<dyn ToolExecutor> = Arc::new(CallbackTool {
+                callback,
+                user_data,
+            });
+            runtime.tokio.block_on(async {
+                let registry = runtime.manager.read().await.tool_registry();
+                let mut registry = registry.lock().await;
+                registry.register_dynamic(metadata, executor)
+            })
+        })
+    }
+}
+
+/// Run the registered tool `name` with the JSON object `args_json` (NULL for
+/// none), storing its JSON result in `result_out`
+///
+/// # Safety
+///
+/// As for [`baml_rt_invoke_function`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn baml_rt_execute_tool(
+    runtime: *const BamlRtRuntime,
+    name: *const c_char,
+    args_json: *const c_char,
+    result_out: *mut *mut c_char,
+    error_out: *mut *mut c_char,
+) -> BamlRtStatus {
+    unsafe {
+        ffi_call(error_out, || {
+            let runtime = runtime_ref(runtime)?;
+            let name = read_str(name, "name")?;
+            let args = read_json(args_json, "args_json")?;
+            let result = runtime.tokio.block_on(async {
+                let manager = runtime.manager.read().await;
+                manager.execute_tool(name, args).await
+            })?;
+            write_string(result_out, result.to_string());
+            Ok(())
+        })
+    }
+}
+
+/// Report a tool's JSON result; the string is copied
+///
+/// # Safety
+///
+/// `output` must be the pointer passed to the running callback and `json` a
+/// NUL-terminated string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn baml_rt_tool_output_set_result(
+    output: *mut BamlRtToolOutput,
+    json: *const c_char,
+) -> BamlRtStatus {
+    unsafe { set_output(output, json, "json", Ok) }
+}
+
+/// Report that a tool failed with `message`; the string is copied
+///
+/// # Safety
+///
+/// As for [`baml_rt_tool_output_set_result`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn baml_rt_tool_output_set_error(
+    output: *mut BamlRtToolOutput,
+    message: *const c_char,
+) -> BamlRtStatus {
+    unsafe { set_output(output, message, "message", Err) }
+}
+
+/// # Safety
+///
+/// As for [`baml_rt_tool_output_set_result`].
+unsafe fn set_output(
+    output: *mut BamlRtToolOutput,
+    value: *const c_char,
+    what: &str,
+    wrap: fn(String) -> std::result::Result<String, String>,
+) -> BamlRtStatus {
+    unsafe {
+        ffi_call(ptr::null_mut(), || {
+            let output = output
+                .as_mut()
+                .ok_or_else(|| BamlRtError::InvalidArgument("output is NULL".to_string()))?;
+            output.result = Some(wrap(read_str(value, what)?.to_string()));
+            Ok(())
+        })
+    }
+}
+
+/// Free a string returned through an out-pointer; NULL is ignored
+///
+/// # Safety
+///
+/// `value` must be NULL or a string from this library that has not been
+/// freed.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn baml_rt_string_free(value: *mut c_char) {
+    if !value.is_null() {
+        drop(unsafe { CString::from_raw(value) });
+    }
+}
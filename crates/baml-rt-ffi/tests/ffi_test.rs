@@ -0,0 +1,126 @@
+use baml_rt_ffi::*;
+use serde_json::{Value, json};
+use std::ffi::{CStr, CString, c_char, c_void};
+use std::ptr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static FREED: AtomicUsize = AtomicUsize::new(0);
+
+extern "C" fn echo_tool(
+    user_data: *mut c_void,
+    args_json: *const c_char,
+    output: *mut BamlRtToolOutput,
+) -> BamlRtStatus {
+    let prefix = unsafe { CStr::from_ptr(user_data as *const c_char) }.to_string_lossy();
+    let args: Value = serde_json::from_str(&unsafe { CStr::from_ptr(args_json) }.to_string_lossy())
+        .expect("args are JSON");
+    if args.get("fail").is_some() {
+        let message = CString::new("asked to fail").unwrap();
+        return unsafe { baml_rt_tool_output_set_error(output, message.as_ptr()) };
+    }
+    let result = json!({ "echo": format!("{}{}", prefix, args["text"].as_str().unwrap()) });
+    let result = CString::new(result.to_string()).unwrap();
+    unsafe { baml_rt_tool_output_set_result(output, result.as_ptr()) }
+}
+
+extern "C" fn free_prefix(user_data: *mut c_void) {
+    drop(unsafe { CString::from_raw(user_data as *mut c_char) });
+    FREED.fetch_add(1, Ordering::SeqCst);
+}
+
+fn take_string(value: *mut c_char) -> String {
+    assert!(!value.is_null());
+    let owned = unsafe { CStr::from_ptr(value) }
+        .to_string_lossy()
+        .into_owned();
+    unsafe { baml_rt_string_free(value) };
+    owned
+}
+
+fn execute(runtime: *mut BamlRtRuntime, args: &str) -> (BamlRtStatus, String) {
+    let name = CString::new("echo").unwrap();
+    let args = CString::new(args).unwrap();
+    let (mut result, mut error) = (ptr::null_mut(), ptr::null_mut());
+    let status = unsafe {
+        baml_rt_execute_tool(
+            runtime,
+            name.as_ptr(),
+            args.as_ptr(),
+            &mut result,
+            &mut error,
+        )
+    };
+    let out = if status == BamlRtStatus::Ok {
+        result
+    } else {
+        error
+    };
+    (status, take_string(out))
+}
+
+#[test]
+fn test_callback_tools_round_trip_through_the_c_interface() {
+    assert_eq!(baml_rt_abi_version(), BAML_RT_ABI_VERSION);
+
+    let mut runtime = ptr::null_mut();
+    let status = unsafe { baml_rt_runtime_new(&mut runtime, ptr::null_mut()) };
+    assert_eq!(status, BamlRtStatus::Ok);
+
+    let register = |prefix: &str| {
+        let (name, description) = (CString::new("echo").unwrap(), CString::new("Echo").unwrap());
+        let schema = CString::new(r#"{"type":"object"}"#).unwrap();
+        let user_data = CString::new(prefix).unwrap().into_raw() as *mut c_void;
+        let mut error = ptr::null_mut();
+        let status = unsafe {
+            baml_rt_register_tool(
+                runtime,
+                name.as_ptr(),
+                description.as_ptr(),
+                schema.as_ptr(),
+                Some(echo_tool),
+                user_data,
+                Some(free_prefix),
+                &mut error,
+            )
+        };
+        (status, error)
+    };
+    let (status, _) = register("> ");
+    assert_eq!(status, BamlRtStatus::Ok);
+
+    let (status, result) = execute(runtime, r#"{"text":"hi"}"#);
+    assert_eq!(status, BamlRtStatus::Ok);
+    assert_eq!(
+        serde_json::from_str::<Value>(&result).unwrap(),
+        json!({ "echo": "> hi" })
+    );
+
+    let (status, error) = execute(runtime, r#"{"fail":true}"#);
+    assert_eq!(status, BamlRtStatus::Error);
+    assert!(error.contains("asked to fail"), "{}", error);
+
+    // A duplicate is rejected, and its user data released right away
+    let (status, error) = register("again ");
+    assert_eq!(status, BamlRtStatus::InvalidArgument);
+    assert!(take_string(error).contains("already registered"));
+    assert_eq!(FREED.load(Ordering::SeqCst), 1);
+
+    unsafe { baml_rt_runtime_free(runtime) };
+    assert_eq!(FREED.load(Ordering::SeqCst), 2);
+}
+
+#[test]
+fn test_invalid_arguments_are_reported_not_dereferenced() {
+    let mut error = ptr::null_mut();
+    let status = unsafe {
+        baml_rt_invoke_function(
+            ptr::null(),
+            ptr::null(),
+            ptr::null(),
+            ptr::null_mut(),
+            &mut error,
+        )
+    };
+    assert_eq!(status, BamlRtStatus::InvalidArgument);
+    assert_eq!(take_string(error), "Invalid argument: runtime is NULL");
+}
@@ -4,6 +4,8 @@
 //! LLM calls and tool executions for governance, tracing, and security purposes.
 
 use async_trait::async_trait;
+use baml_rt_core::correlation::current_correlation_id;
+use baml_rt_core::events::{EventBus, RuntimeEvent};
 use baml_rt_core::ids::ContextId;
 use baml_rt_core::{BamlRtError, Result};
 use serde_json::Value;
@@ -20,6 +22,76 @@ pub enum InterceptorDecision {
     Block(String),
 }
 
+/// How the decisions of several interceptors combine into one
+///
+/// Applies to pre-call and stream chunk hooks. An interceptor that fails
+/// abstains: the error is logged and its vote is ignored under every policy.
+/// Result transforms are not votes; they always compose in pipeline order.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DecisionPolicy {
+    /// The first interceptor to block decides; later ones are not called
+    #[default]
+    FirstBlockWins,
+
+    /// Every interceptor is called, and the call is blocked if any of them
+    /// blocked, with all of their reasons
+    AllMustAllow,
+
+    /// The call is allowed as soon as one interceptor allows it, and blocked
+    /// only if every interceptor that voted blocked
+    AnyAllows,
+}
+
+/// Combines decisions one interceptor at a time under a [`DecisionPolicy`]
+pub(crate) struct DecisionFold {
+    policy: DecisionPolicy,
+    allowed: bool,
+    blocks: Vec<String>,
+}
+
+impl DecisionFold {
+    pub(crate) fn new(policy: DecisionPolicy) -> Self {
+        Self {
+            policy,
+            allowed: false,
+            blocks: Vec::new(),
+        }
+    }
+
+    /// Record one interceptor's decision, returning true when the outcome is
+    /// settled and the remaining interceptors can be skipped
+    pub(crate) fn record(&mut self, decision: Result<InterceptorDecision>, hook: &str) -> bool {
+        match decision {
+            Ok(InterceptorDecision::Allow) => {
+                self.allowed = true;
+                self.policy == DecisionPolicy::AnyAllows
+            }
+            Ok(InterceptorDecision::Block(msg)) => {
+                self.blocks.push(msg);
+                self.policy == DecisionPolicy::FirstBlockWins
+            }
+            Err(e) => {
+                tracing::warn!(error = ?e, hook, "Interceptor failed");
+                false
+            }
+        }
+    }
+
+    pub(crate) fn finish(self) -> InterceptorDecision {
+        let blocked = match self.policy {
+            DecisionPolicy::FirstBlockWins | DecisionPolicy::AllMustAllow => {
+                !self.blocks.is_empty()
+            }
+            DecisionPolicy::AnyAllows => !self.allowed && !self.blocks.is_empty(),
+        };
+        if blocked {
+            InterceptorDecision::Block(self.blocks.join("; "))
+        } else {
+            InterceptorDecision::Allow
+        }
+    }
+}
+
 /// Context information about an LLM call
 #[derive(Debug, Clone)]
 pub struct LLMCallContext {
@@ -85,6 +157,45 @@ pub trait LLMInterceptor: Send + Sync + 'static {
         result: &Result<Value>,
         duration_ms: u64,
     );
+
+    /// Called for each partial output of a streaming LLM call
+    ///
+    /// # Arguments
+    /// * `context` - Information about the LLM call
+    /// * `chunk` - The output parsed so far; each chunk supersedes the last
+    /// * `index` - Position of the chunk in the stream, starting at 0
+    ///
+    /// # Returns
+    /// A decision on whether to keep streaming. Blocking cancels the call.
+    /// The default allows every chunk.
+    async fn on_llm_stream_chunk(
+        &self,
+        _context: &LLMCallContext,
+        _chunk: &Value,
+        _index: usize,
+    ) -> Result<InterceptorDecision> {
+        Ok(InterceptorDecision::Allow)
+    }
+
+    /// Rewrite the parsed result of a call before it is returned
+    ///
+    /// Runs for calls that are not streamed, in pipeline order like
+    /// [`ToolInterceptor::on_tool_result_transform`], e.g. to redact or
+    /// normalize what a model said.
+    ///
+    /// # Arguments
+    /// * `context` - Information about the LLM call
+    /// * `result` - The parsed result, as rewritten by earlier interceptors
+    ///
+    /// # Returns
+    /// The result to pass on. The default returns it unchanged.
+    async fn on_llm_result_transform(
+        &self,
+        _context: &LLMCallContext,
+        result: Value,
+    ) -> Result<Value> {
+        Ok(result)
+    }
 }
 
 /// Trait for intercepting tool calls
@@ -111,15 +222,47 @@ pub trait ToolInterceptor: Send + Sync + 'static {
         result: &Result<Value>,
         duration_ms: u64,
     );
+
+    /// Rewrite a successful tool result before it is returned to the caller
+    ///
+    /// Transforms run in pipeline order, each receiving the previous one's
+    /// output, and before `on_tool_call_complete`, which sees the final value.
+    /// Use this to truncate oversized payloads or annotate results.
+    ///
+    /// # Arguments
+    /// * `context` - The original call context
+    /// * `result` - The tool's result, as rewritten by earlier interceptors
+    ///
+    /// # Returns
+    /// The result to pass on. The default returns it unchanged.
+    async fn on_tool_result_transform(
+        &self,
+        _context: &ToolCallContext,
+        result: Value,
+    ) -> Result<Value> {
+        Ok(result)
+    }
+}
+
+/// Priority of interceptors registered without one
+pub const DEFAULT_PRIORITY: i32 = 0;
+
+/// Name and priority of a pipeline entry
+#[derive(Debug, Clone)]
+struct Slot {
+    name: Option<String>,
+    priority: i32,
 }
 
 /// Pipeline for composing multiple interceptors
 ///
 /// This allows interceptors to be chained together in a pipeline pattern.
-/// Interceptors are executed in order, and if any interceptor blocks,
-/// subsequent interceptors are not called.
+/// Interceptors run from highest to lowest priority, and in insertion order
+/// among equal priorities. How their decisions combine is up to the
+/// [`DecisionPolicy`] of whatever runs the pipeline.
 pub struct InterceptorPipeline<I: ?Sized> {
-    pub(crate) interceptors: Vec<Arc<I>>,
+    interceptors: Vec<Arc<I>>,
+    slots: Vec<Slot>,
 }
 
 impl<I: ?Sized> InterceptorPipeline<I> {
@@ -127,24 +270,76 @@ impl<I: ?Sized> InterceptorPipeline<I> {
     pub fn new() -> Self {
         Self {
             interceptors: Vec::new(),
+            slots: Vec::new(),
         }
     }
 
     /// Add an interceptor to the pipeline
     ///
-    /// Interceptors are executed in the order they are added.
+    /// Interceptors added this way have [`DEFAULT_PRIORITY`] and run in the
+    /// order they are added.
     pub fn with_interceptor(mut self, interceptor: Arc<I>) -> Self {
-        self.interceptors.push(interceptor);
+        self.insert(None, DEFAULT_PRIORITY, interceptor);
+        self
+    }
+
+    /// Add an interceptor under `name`, running before those of lower priority
+    pub fn with_named_interceptor(
+        mut self,
+        name: impl Into<String>,
+        priority: i32,
+        interceptor: Arc<I>,
+    ) -> Self {
+        self.insert(Some(name.into()), priority, interceptor);
         self
     }
 
     /// Add multiple interceptors to the pipeline
     pub fn add_all(mut self, interceptors: Vec<Arc<I>>) -> Self {
-        self.interceptors.extend(interceptors);
+        for interceptor in interceptors {
+            self.insert(None, DEFAULT_PRIORITY, interceptor);
+        }
         self
     }
 
-    /// Get all interceptors in the pipeline
+    /// Move every interceptor of `other` into this pipeline, keeping priorities
+    pub fn extend(&mut self, other: Self) {
+        for (interceptor, slot) in other.interceptors.into_iter().zip(other.slots) {
+            self.insert(slot.name, slot.priority, interceptor);
+        }
+    }
+
+    /// Remove the interceptor registered as `name`
+    pub fn remove(&mut self, name: &str) -> Option<Arc<I>> {
+        let index = self.position(name)?;
+        self.slots.remove(index);
+        Some(self.interceptors.remove(index))
+    }
+
+    /// Swap the interceptor registered as `name`, keeping its place in the
+    /// order; returns the previous interceptor
+    pub fn replace(&mut self, name: &str, interceptor: Arc<I>) -> Option<Arc<I>> {
+        let index = self.position(name)?;
+        Some(std::mem::replace(
+            &mut self.interceptors[index],
+            interceptor,
+        ))
+    }
+
+    /// Whether an interceptor is registered as `name`
+    pub fn contains(&self, name: &str) -> bool {
+        self.position(name).is_some()
+    }
+
+    /// Names of the named interceptors, in invocation order
+    pub fn names(&self) -> Vec<&str> {
+        self.slots
+            .iter()
+            .filter_map(|slot| slot.name.as_deref())
+            .collect()
+    }
+
+    /// Get all interceptors in the pipeline, in invocation order
     pub fn interceptors(&self) -> &[Arc<I>] {
         &self.interceptors
     }
@@ -158,6 +353,23 @@ impl<I: ?Sized> InterceptorPipeline<I> {
     pub fn is_empty(&self) -> bool {
         self.interceptors.is_empty()
     }
+
+    fn position(&self, name: &str) -> Option<usize> {
+        self.slots
+            .iter()
+            .position(|slot| slot.name.as_deref() == Some(name))
+    }
+
+    /// Insert after every entry of equal or higher priority
+    fn insert(&mut self, name: Option<String>, priority: i32, interceptor: Arc<I>) {
+        let index = self
+            .slots
+            .iter()
+            .position(|slot| slot.priority < priority)
+            .unwrap_or(self.slots.len());
+        self.slots.insert(index, Slot { name, priority });
+        self.interceptors.insert(index, interceptor);
+    }
 }
 
 impl<I: ?Sized> Default for InterceptorPipeline<I> {
@@ -169,9 +381,24 @@ impl<I: ?Sized> Default for InterceptorPipeline<I> {
 /// Registry for managing interceptors
 ///
 /// This registry manages pipelines of interceptors for both LLM and tool calls.
+///
+/// # Invocation order
+///
+/// The LLM and tool pipelines are independent: LLM interceptors only see LLM
+/// calls and tool interceptors only see tool calls. Within a pipeline, every
+/// hook (pre-call, stream chunk, result transform, and completion) visits
+/// interceptors in the same order: highest priority first, then registration
+/// order. Whether pre-call and stream chunk hooks stop early depends on the
+/// registry's [`DecisionPolicy`]; under the default, they stop at the first
+/// interceptor that blocks. The other hooks always reach every interceptor.
+/// When an LLM response
+/// triggers a tool, the LLM completion hooks run before the tool's pre-call
+/// hooks.
 pub struct InterceptorRegistry {
     pub(crate) llm_pipeline: InterceptorPipeline<dyn LLMInterceptor>,
     pub(crate) tool_pipeline: InterceptorPipeline<dyn ToolInterceptor>,
+    policy: DecisionPolicy,
+    events: Option<EventBus>,
 }
 
 impl InterceptorRegistry {
@@ -180,6 +407,8 @@ impl InterceptorRegistry {
         Self {
             llm_pipeline: InterceptorPipeline::new(),
             tool_pipeline: InterceptorPipeline::new(),
+            policy: DecisionPolicy::default(),
+            events: None,
         }
     }
 
@@ -191,48 +420,142 @@ impl InterceptorRegistry {
         Self {
             llm_pipeline,
             tool_pipeline,
+            policy: DecisionPolicy::default(),
+            events: None,
+        }
+    }
+
+    /// Set how conflicting interceptor decisions are resolved
+    pub fn with_decision_policy(mut self, policy: DecisionPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Publish the start, outcome, and blocking of every call on `events`
+    pub fn with_event_bus(mut self, events: EventBus) -> Self {
+        self.events = Some(events);
+        self
+    }
+
+    /// Change the bus calls are published on
+    pub fn set_event_bus(&mut self, events: EventBus) {
+        self.events = Some(events);
+    }
+
+    /// The bus calls are published on, if any
+    pub fn event_bus(&self) -> Option<&EventBus> {
+        self.events.as_ref()
+    }
+
+    fn publish(&self, build: impl FnOnce() -> RuntimeEvent) {
+        if let Some(events) = &self.events {
+            events.publish_with(build);
         }
     }
 
+    /// Change how conflicting interceptor decisions are resolved
+    pub fn set_decision_policy(&mut self, policy: DecisionPolicy) {
+        self.policy = policy;
+    }
+
+    /// The policy resolving conflicting interceptor decisions
+    pub fn decision_policy(&self) -> DecisionPolicy {
+        self.policy
+    }
+
     /// Register an LLM interceptor
     ///
-    /// Interceptors are called in registration order. If any interceptor
-    /// blocks the call, subsequent interceptors are not called.
+    /// The interceptor gets [`DEFAULT_PRIORITY`] and runs after interceptors
+    /// already registered at that priority.
     pub fn register_llm_interceptor<I: LLMInterceptor>(&mut self, interceptor: I) {
-        let pipeline = std::mem::take(&mut self.llm_pipeline);
-        self.llm_pipeline =
-            pipeline.with_interceptor(Arc::new(interceptor) as Arc<dyn LLMInterceptor>);
+        self.llm_pipeline
+            .insert(None, DEFAULT_PRIORITY, Arc::new(interceptor));
     }
 
     /// Register a tool interceptor
     ///
-    /// Interceptors are called in registration order. If any interceptor
-    /// blocks the call, subsequent interceptors are not called.
+    /// The interceptor gets [`DEFAULT_PRIORITY`] and runs after interceptors
+    /// already registered at that priority.
     pub fn register_tool_interceptor<I: ToolInterceptor>(&mut self, interceptor: I) {
-        let pipeline = std::mem::take(&mut self.tool_pipeline);
-        self.tool_pipeline =
-            pipeline.with_interceptor(Arc::new(interceptor) as Arc<dyn ToolInterceptor>);
+        self.tool_pipeline
+            .insert(None, DEFAULT_PRIORITY, Arc::new(interceptor));
     }
 
-    /// Add an LLM interceptor pipeline
+    /// Register an LLM interceptor under `name` with an explicit priority
     ///
-    /// This allows composing multiple interceptors into a pipeline.
-    pub fn with_llm_pipeline(mut self, pipeline: InterceptorPipeline<dyn LLMInterceptor>) -> Self {
-        // Merge the new pipeline with existing interceptors
-        let existing = std::mem::take(&mut self.llm_pipeline);
-        let mut merged = InterceptorPipeline::new();
-
-        // Add existing interceptors
-        for interceptor in existing.interceptors() {
-            merged.interceptors.push(interceptor.clone());
+    /// Higher priorities run first. Fails if `name` is already registered.
+    pub fn register_named_llm_interceptor<I: LLMInterceptor>(
+        &mut self,
+        name: &str,
+        priority: i32,
+        interceptor: I,
+    ) -> Result<()> {
+        if self.llm_pipeline.contains(name) {
+            return Err(duplicate_name("LLM", name));
         }
+        self.llm_pipeline
+            .insert(Some(name.to_string()), priority, Arc::new(interceptor));
+        Ok(())
+    }
 
-        // Add new pipeline interceptors
-        for interceptor in pipeline.interceptors() {
-            merged.interceptors.push(interceptor.clone());
+    /// Register a tool interceptor under `name` with an explicit priority
+    ///
+    /// Higher priorities run first. Fails if `name` is already registered.
+    pub fn register_named_tool_interceptor<I: ToolInterceptor>(
+        &mut self,
+        name: &str,
+        priority: i32,
+        interceptor: I,
+    ) -> Result<()> {
+        if self.tool_pipeline.contains(name) {
+            return Err(duplicate_name("tool", name));
         }
+        self.tool_pipeline
+            .insert(Some(name.to_string()), priority, Arc::new(interceptor));
+        Ok(())
+    }
 
-        self.llm_pipeline = merged;
+    /// Remove the LLM interceptor registered as `name`, returning whether it existed
+    pub fn unregister_llm_interceptor(&mut self, name: &str) -> bool {
+        self.llm_pipeline.remove(name).is_some()
+    }
+
+    /// Remove the tool interceptor registered as `name`, returning whether it existed
+    pub fn unregister_tool_interceptor(&mut self, name: &str) -> bool {
+        self.tool_pipeline.remove(name).is_some()
+    }
+
+    /// Swap the LLM interceptor registered as `name`, keeping its priority
+    /// and position
+    pub fn replace_llm_interceptor<I: LLMInterceptor>(
+        &mut self,
+        name: &str,
+        interceptor: I,
+    ) -> Result<()> {
+        self.llm_pipeline
+            .replace(name, Arc::new(interceptor))
+            .map(|_| ())
+            .ok_or_else(|| unknown_name("LLM", name))
+    }
+
+    /// Swap the tool interceptor registered as `name`, keeping its priority
+    /// and position
+    pub fn replace_tool_interceptor<I: ToolInterceptor>(
+        &mut self,
+        name: &str,
+        interceptor: I,
+    ) -> Result<()> {
+        self.tool_pipeline
+            .replace(name, Arc::new(interceptor))
+            .map(|_| ())
+            .ok_or_else(|| unknown_name("tool", name))
+    }
+
+    /// Add an LLM interceptor pipeline
+    ///
+    /// This allows composing multiple interceptors into a pipeline.
+    pub fn with_llm_pipeline(mut self, pipeline: InterceptorPipeline<dyn LLMInterceptor>) -> Self {
+        self.merge_llm_pipeline(pipeline);
         self
     }
 
@@ -243,100 +566,185 @@ impl InterceptorRegistry {
         mut self,
         pipeline: InterceptorPipeline<dyn ToolInterceptor>,
     ) -> Self {
-        // Merge the new pipeline with existing interceptors
-        let existing = std::mem::take(&mut self.tool_pipeline);
-        let mut merged = InterceptorPipeline::new();
-
-        // Add existing interceptors
-        for interceptor in existing.interceptors() {
-            merged.interceptors.push(interceptor.clone());
-        }
-
-        // Add new pipeline interceptors
-        for interceptor in pipeline.interceptors() {
-            merged.interceptors.push(interceptor.clone());
-        }
-
-        self.tool_pipeline = merged;
+        self.merge_tool_pipeline(pipeline);
         self
     }
 
     /// Merge an LLM interceptor pipeline into the registry.
     ///
-    /// This preserves existing interceptors and appends the provided pipeline.
+    /// This preserves existing interceptors and adds the provided pipeline's
+    /// after existing ones of the same priority.
     pub fn merge_llm_pipeline(&mut self, pipeline: InterceptorPipeline<dyn LLMInterceptor>) {
-        let existing = std::mem::take(&mut self.llm_pipeline);
-        let mut merged = InterceptorPipeline::new();
-        merged.interceptors.extend(existing.interceptors);
-        merged.interceptors.extend(pipeline.interceptors);
-        self.llm_pipeline = merged;
+        self.llm_pipeline.extend(pipeline);
     }
 
     /// Merge a tool interceptor pipeline into the registry.
     ///
-    /// This preserves existing interceptors and appends the provided pipeline.
+    /// This preserves existing interceptors and adds the provided pipeline's
+    /// after existing ones of the same priority.
     pub fn merge_tool_pipeline(&mut self, pipeline: InterceptorPipeline<dyn ToolInterceptor>) {
-        let existing = std::mem::take(&mut self.tool_pipeline);
-        let mut merged = InterceptorPipeline::new();
-        merged.interceptors.extend(existing.interceptors);
-        merged.interceptors.extend(pipeline.interceptors);
-        self.tool_pipeline = merged;
+        self.tool_pipeline.extend(pipeline);
     }
 
     /// Execute LLM interceptors and return the final decision
     ///
-    /// Returns Ok(Allow) if all interceptors allow, or Err if any block
+    /// Returns Ok(Allow) if the interceptors allow the call under the
+    /// decision policy, or Err if they block it
     pub async fn intercept_llm_call(
         &self,
         context: &LLMCallContext,
     ) -> Result<InterceptorDecision> {
+        let mut fold = DecisionFold::new(self.policy);
         for interceptor in self.llm_pipeline.interceptors() {
-            match interceptor.intercept_llm_call(context).await {
-                Ok(InterceptorDecision::Allow) => {
-                    // Continue to next interceptor
-                }
-                Ok(InterceptorDecision::Block(msg)) => {
-                    return Err(BamlRtError::BamlRuntime(format!(
-                        "LLM call blocked by interceptor: {}",
-                        msg
-                    )));
-                }
-                Err(e) => {
-                    // Interceptor itself failed - log but continue?
-                    tracing::warn!(error = ?e, "LLM interceptor failed");
-                }
+            if fold.record(
+                interceptor.intercept_llm_call(context).await,
+                "intercept_llm_call",
+            ) {
+                break;
+            }
+        }
+        match fold.finish() {
+            InterceptorDecision::Allow => {
+                self.publish(|| RuntimeEvent::LlmCallStarted {
+                    function_name: context.function_name.clone(),
+                    client: context.client.clone(),
+                    model: context.model.clone(),
+                    context_id: context.context_id.clone(),
+                    correlation_id: current_correlation_id(),
+                });
+                Ok(InterceptorDecision::Allow)
+            }
+            InterceptorDecision::Block(msg) => {
+                self.publish(|| RuntimeEvent::LlmCallBlocked {
+                    function_name: context.function_name.clone(),
+                    client: context.client.clone(),
+                    context_id: context.context_id.clone(),
+                    correlation_id: current_correlation_id(),
+                    reason: msg.clone(),
+                });
+                Err(BamlRtError::BamlRuntime(format!(
+                    "LLM call blocked by interceptor: {}",
+                    msg
+                )))
             }
         }
-
-        Ok(InterceptorDecision::Allow)
     }
 
     /// Execute tool interceptors and return the final decision
     ///
-    /// Returns Ok(Allow) if all interceptors allow, or Err if any block
+    /// Returns Ok(Allow) if the interceptors allow the call under the
+    /// decision policy, or Err if they block it
     pub async fn intercept_tool_call(
         &self,
         context: &ToolCallContext,
     ) -> Result<InterceptorDecision> {
+        let mut fold = DecisionFold::new(self.policy);
         for interceptor in self.tool_pipeline.interceptors() {
-            match interceptor.intercept_tool_call(context).await {
-                Ok(InterceptorDecision::Allow) => {
-                    // Continue to next interceptor
-                }
-                Ok(InterceptorDecision::Block(msg)) => {
-                    return Err(BamlRtError::ToolExecution(format!(
-                        "Tool call blocked by interceptor: {}",
-                        msg
-                    )));
-                }
+            if fold.record(
+                interceptor.intercept_tool_call(context).await,
+                "intercept_tool_call",
+            ) {
+                break;
+            }
+        }
+        match fold.finish() {
+            InterceptorDecision::Allow => {
+                self.publish(|| RuntimeEvent::ToolCallStarted {
+                    tool_name: context.tool_name.clone(),
+                    function_name: context.function_name.clone(),
+                    context_id: context.context_id.clone(),
+                    correlation_id: current_correlation_id(),
+                });
+                Ok(InterceptorDecision::Allow)
+            }
+            InterceptorDecision::Block(msg) => {
+                self.publish(|| RuntimeEvent::ToolCallBlocked {
+                    tool_name: context.tool_name.clone(),
+                    context_id: context.context_id.clone(),
+                    correlation_id: current_correlation_id(),
+                    reason: msg.clone(),
+                });
+                Err(BamlRtError::ToolExecution(format!(
+                    "Tool call blocked by interceptor: {}",
+                    msg
+                )))
+            }
+        }
+    }
+
+    /// Execute LLM interceptors on one chunk of a streaming call
+    ///
+    /// Returns Ok(Allow) if the interceptors allow the chunk under the
+    /// decision policy, or Err if they block it
+    pub async fn intercept_llm_stream_chunk(
+        &self,
+        context: &LLMCallContext,
+        chunk: &Value,
+        index: usize,
+    ) -> Result<InterceptorDecision> {
+        let mut fold = DecisionFold::new(self.policy);
+        for interceptor in self.llm_pipeline.interceptors() {
+            if fold.record(
+                interceptor.on_llm_stream_chunk(context, chunk, index).await,
+                "on_llm_stream_chunk",
+            ) {
+                break;
+            }
+        }
+        match fold.finish() {
+            InterceptorDecision::Allow => Ok(InterceptorDecision::Allow),
+            InterceptorDecision::Block(msg) => Err(BamlRtError::BamlRuntime(format!(
+                "LLM stream blocked by interceptor: {}",
+                msg
+            ))),
+        }
+    }
+
+    /// Pass the parsed result of an LLM call through every LLM interceptor's
+    /// transform
+    ///
+    /// A transform that fails is skipped, leaving the value it was given.
+    pub async fn transform_llm_result(&self, context: &LLMCallContext, result: Value) -> Value {
+        let mut result = result;
+        for interceptor in self.llm_pipeline.interceptors() {
+            match interceptor
+                .on_llm_result_transform(context, result.clone())
+                .await
+            {
+                Ok(transformed) => result = transformed,
                 Err(e) => {
-                    // Interceptor itself failed - log but continue?
-                    tracing::warn!("Tool interceptor failed: {}", e);
+                    tracing::warn!(
+                        function = context.function_name.as_str(),
+                        error = ?e,
+                        "LLM result transform failed"
+                    );
                 }
             }
         }
+        result
+    }
 
-        Ok(InterceptorDecision::Allow)
+    /// Pass a successful tool result through every tool interceptor's transform
+    ///
+    /// A transform that fails is skipped, leaving the value it was given.
+    pub async fn transform_tool_result(&self, context: &ToolCallContext, result: Value) -> Value {
+        let mut result = result;
+        for interceptor in self.tool_pipeline.interceptors() {
+            match interceptor
+                .on_tool_result_transform(context, result.clone())
+                .await
+            {
+                Ok(transformed) => result = transformed,
+                Err(e) => {
+                    tracing::warn!(
+                        tool = context.tool_name.as_str(),
+                        error = ?e,
+                        "Tool result transform failed"
+                    );
+                }
+            }
+        }
+        result
     }
 
     /// Notify all LLM interceptors of a completed call
@@ -351,6 +759,14 @@ impl InterceptorRegistry {
                 .on_llm_call_complete(context, result, duration_ms)
                 .await;
         }
+        self.publish(|| RuntimeEvent::LlmCallCompleted {
+            function_name: context.function_name.clone(),
+            client: context.client.clone(),
+            context_id: context.context_id.clone(),
+            correlation_id: current_correlation_id(),
+            duration_ms,
+            error: result.as_ref().err().map(ToString::to_string),
+        });
     }
 
     /// Notify all tool interceptors of a completed call
@@ -365,6 +781,13 @@ impl InterceptorRegistry {
                 .on_tool_call_complete(context, result, duration_ms)
                 .await;
         }
+        self.publish(|| RuntimeEvent::ToolCompleted {
+            tool_name: context.tool_name.clone(),
+            context_id: context.context_id.clone(),
+            correlation_id: current_correlation_id(),
+            duration_ms,
+            error: result.as_ref().err().map(ToString::to_string),
+        });
     }
 
     /// Get the LLM interceptor pipeline (for inspection)
@@ -393,3 +816,14 @@ impl Default for InterceptorRegistry {
         Self::new()
     }
 }
+
+fn duplicate_name(kind: &str, name: &str) -> BamlRtError {
+    BamlRtError::InvalidArgument(format!(
+        "{} interceptor '{}' is already registered",
+        kind, name
+    ))
+}
+
+fn unknown_name(kind: &str, name: &str) -> BamlRtError {
+    BamlRtError::InvalidArgument(format!("No {} interceptor named '{}'", kind, name))
+}
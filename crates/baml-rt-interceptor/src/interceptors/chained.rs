@@ -0,0 +1,159 @@
+//! Chained interceptor
+//!
+//! Groups several interceptors into one that can be registered, prioritized,
+//! and replaced as a unit. The chain resolves its members' decisions with its
+//! own [`DecisionPolicy`], independent of the registry's, so a stack such as
+//! "block if any PII check objects, but let either allow-list through" can be
+//! expressed as nested chains.
+
+use crate::interceptor::{
+    DecisionFold, DecisionPolicy, InterceptorDecision, InterceptorPipeline, LLMCallContext,
+    LLMInterceptor, ToolCallContext, ToolInterceptor,
+};
+use async_trait::async_trait;
+use baml_rt_core::Result;
+use serde_json::Value;
+use std::sync::Arc;
+
+/// Interceptors run as one, combining their decisions under a policy
+///
+/// Members run in pipeline order. Completion hooks reach every member, and
+/// tool result transforms compose in order. A chain that blocks reports the
+/// members' reasons joined with `"; "`.
+pub struct ChainedInterceptor<I: ?Sized> {
+    pipeline: InterceptorPipeline<I>,
+    policy: DecisionPolicy,
+}
+
+impl<I: ?Sized> ChainedInterceptor<I> {
+    /// Create an empty chain resolving decisions with `policy`
+    pub fn new(policy: DecisionPolicy) -> Self {
+        Self {
+            pipeline: InterceptorPipeline::new(),
+            policy,
+        }
+    }
+
+    /// Create a chain over an existing pipeline
+    pub fn from_pipeline(pipeline: InterceptorPipeline<I>, policy: DecisionPolicy) -> Self {
+        Self { pipeline, policy }
+    }
+
+    /// Append a member to the chain
+    pub fn with_interceptor(mut self, interceptor: Arc<I>) -> Self {
+        self.pipeline = self.pipeline.with_interceptor(interceptor);
+        self
+    }
+
+    /// The chain's decision policy
+    pub fn policy(&self) -> DecisionPolicy {
+        self.policy
+    }
+
+    /// The chain's members, in invocation order
+    pub fn interceptors(&self) -> &[Arc<I>] {
+        self.pipeline.interceptors()
+    }
+}
+
+#[async_trait]
+impl LLMInterceptor for ChainedInterceptor<dyn LLMInterceptor> {
+    async fn intercept_llm_call(&self, context: &LLMCallContext) -> Result<InterceptorDecision> {
+        let mut fold = DecisionFold::new(self.policy);
+        for interceptor in self.pipeline.interceptors() {
+            if fold.record(
+                interceptor.intercept_llm_call(context).await,
+                "intercept_llm_call",
+            ) {
+                break;
+            }
+        }
+        Ok(fold.finish())
+    }
+
+    async fn on_llm_call_complete(
+        &self,
+        context: &LLMCallContext,
+        result: &Result<Value>,
+        duration_ms: u64,
+    ) {
+        for interceptor in self.pipeline.interceptors() {
+            interceptor
+                .on_llm_call_complete(context, result, duration_ms)
+                .await;
+        }
+    }
+
+    async fn on_llm_stream_chunk(
+        &self,
+        context: &LLMCallContext,
+        chunk: &Value,
+        index: usize,
+    ) -> Result<InterceptorDecision> {
+        let mut fold = DecisionFold::new(self.policy);
+        for interceptor in self.pipeline.interceptors() {
+            if fold.record(
+                interceptor.on_llm_stream_chunk(context, chunk, index).await,
+                "on_llm_stream_chunk",
+            ) {
+                break;
+            }
+        }
+        Ok(fold.finish())
+    }
+
+    async fn on_llm_result_transform(
+        &self,
+        context: &LLMCallContext,
+        result: Value,
+    ) -> Result<Value> {
+        let mut result = result;
+        for interceptor in self.pipeline.interceptors() {
+            result = interceptor.on_llm_result_transform(context, result).await?;
+        }
+        Ok(result)
+    }
+}
+
+#[async_trait]
+impl ToolInterceptor for ChainedInterceptor<dyn ToolInterceptor> {
+    async fn intercept_tool_call(&self, context: &ToolCallContext) -> Result<InterceptorDecision> {
+        let mut fold = DecisionFold::new(self.policy);
+        for interceptor in self.pipeline.interceptors() {
+            if fold.record(
+                interceptor.intercept_tool_call(context).await,
+                "intercept_tool_call",
+            ) {
+                break;
+            }
+        }
+        Ok(fold.finish())
+    }
+
+    async fn on_tool_call_complete(
+        &self,
+        context: &ToolCallContext,
+        result: &Result<Value>,
+        duration_ms: u64,
+    ) {
+        for interceptor in self.pipeline.interceptors() {
+            interceptor
+                .on_tool_call_complete(context, result, duration_ms)
+                .await;
+        }
+    }
+
+    async fn on_tool_result_transform(
+        &self,
+        context: &ToolCallContext,
+        result: Value,
+    ) -> Result<Value> {
+        let mut result = result;
+        for interceptor in self.pipeline.interceptors() {
+            result = interceptor
+                .on_tool_result_transform(context, result)
+                .await?;
+        }
+        Ok(result)
+    }
+}
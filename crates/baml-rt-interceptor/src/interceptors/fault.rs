@@ -0,0 +1,342 @@
+//! Fault injection interceptor
+//!
+//! Makes LLM and tool calls misbehave on purpose, so retry, timeout and A2A
+//! error handling can be exercised without a flaky provider. Each call is
+//! independently delayed, failed, or truncated with the configured
+//! probabilities:
+//!
+//! - a delay sleeps before the call runs, within the configured range;
+//! - a failure blocks the call with one of the configured [`FaultError`]s,
+//!   which surfaces as the usual "blocked by interceptor" error;
+//! - a truncation cuts the successful result short. Streamed LLM calls are
+//!   not truncated.
+//!
+//! Draws come from a seeded generator, so a run with the same seed and the
+//! same sequence of calls injects the same faults.
+
+use crate::interceptor::{
+    InterceptorDecision, LLMCallContext, LLMInterceptor, ToolCallContext, ToolInterceptor,
+};
+use async_trait::async_trait;
+use baml_rt_core::Result;
+use serde_json::Value;
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Which calls faults are injected into
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FaultTarget {
+    /// LLM and tool calls
+    #[default]
+    All,
+    /// LLM calls only
+    Llm,
+    /// Tool calls only
+    Tools,
+}
+
+/// The shape of an injected failure
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FaultError {
+    /// The provider refused the call for exceeding its rate limit
+    RateLimited,
+    /// The provider failed with a server error
+    ServerError,
+    /// The call timed out
+    Timeout,
+    /// The connection dropped mid-call
+    ConnectionReset,
+    /// A failure with this message
+    Custom(String),
+}
+
+impl FaultError {
+    /// Message the call is blocked with
+    pub fn message(&self) -> String {
+        let message = match self {
+            Self::RateLimited => "429 Too Many Requests: rate limit exceeded",
+            Self::ServerError => "500 Internal Server Error",
+            Self::Timeout => "request timed out",
+            Self::ConnectionReset => "connection reset by peer",
+            Self::Custom(message) => message,
+        };
+        format!("{} (injected fault)", message)
+    }
+}
+
+/// Counts of the faults injected so far
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FaultStats {
+    pub delays: u64,
+    pub failures: u64,
+    pub truncations: u64,
+}
+
+#[derive(Default)]
+struct Counters {
+    delays: AtomicU64,
+    failures: AtomicU64,
+    truncations: AtomicU64,
+}
+
+/// Interceptor injecting delays, failures and truncated results
+///
+/// Clones share their generator and counters, so one instance can be
+/// registered as both an LLM and a tool interceptor.
+#[derive(Clone)]
+pub struct FaultInjectionInterceptor {
+    target: FaultTarget,
+    names: Option<Arc<HashSet<String>>>,
+    delay_probability: f64,
+    delay_range: (Duration, Duration),
+    failure_probability: f64,
+    errors: Arc<Vec<FaultError>>,
+    truncate_probability: f64,
+    truncate_keep: f64,
+    rng: Arc<AtomicU64>,
+    counters: Arc<Counters>,
+}
+
+impl FaultInjectionInterceptor {
+    /// An interceptor injecting nothing yet, seeded from the clock
+    pub fn new() -> Self {
+        let seed = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_nanos() as u64)
+            .unwrap_or_default();
+        Self {
+            target: FaultTarget::All,
+            names: None,
+            delay_probability: 0.0,
+            delay_range: (Duration::ZERO, Duration::ZERO),
+            failure_probability: 0.0,
+            errors: Arc::new(vec![FaultError::ServerError]),
+            truncate_probability: 0.0,
+            truncate_keep: 0.5,
+            rng: Arc::new(AtomicU64::new(seed)),
+            counters: Arc::new(Counters::default()),
+        }
+    }
+
+    /// Draw faults from a generator seeded with `seed`, for reproducible runs
+    pub fn with_seed(self, seed: u64) -> Self {
+        self.rng.store(seed, Ordering::Relaxed);
+        self
+    }
+
+    /// Only inject faults into `target` calls
+    pub fn with_target(mut self, target: FaultTarget) -> Self {
+        self.target = target;
+        self
+    }
+
+    /// Only inject faults into calls of these functions or tools
+    pub fn for_names<I, S>(mut self, names: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.names = Some(Arc::new(names.into_iter().map(Into::into).collect()));
+        self
+    }
+
+    /// Delay calls with `probability` by a duration between `min` and `max`
+    pub fn with_delay(mut self, probability: f64, min: Duration, max: Duration) -> Self {
+        self.delay_probability = probability.clamp(0.0, 1.0);
+        self.delay_range = (min, max.max(min));
+        self
+    }
+
+    /// Fail calls with `probability`, with one of `errors` picked at random
+    ///
+    /// An empty list fails calls with [`FaultError::ServerError`].
+    pub fn with_failure(mut self, probability: f64, errors: Vec<FaultError>) -> Self {
+        self.failure_probability = probability.clamp(0.0, 1.0);
+        if !errors.is_empty() {
+            self.errors = Arc::new(errors);
+        }
+        self
+    }
+
+    /// Truncate results with `probability`, keeping the `keep` fraction of
+    /// them
+    ///
+    /// Strings keep their leading characters and arrays their leading
+    /// elements; other values are replaced by the start of their JSON text.
+    pub fn with_truncation(mut self, probability: f64, keep: f64) -> Self {
+        self.truncate_probability = probability.clamp(0.0, 1.0);
+        self.truncate_keep = keep.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Faults injected so far, across clones
+    pub fn stats(&self) -> FaultStats {
+        FaultStats {
+            delays: self.counters.delays.load(Ordering::Relaxed),
+            failures: self.counters.failures.load(Ordering::Relaxed),
+            truncations: self.counters.truncations.load(Ordering::Relaxed),
+        }
+    }
+
+    fn applies(&self, target: FaultTarget, name: &str) -> bool {
+        (self.target == FaultTarget::All || self.target == target)
+            && self.names.as_ref().is_none_or(|names| names.contains(name))
+    }
+
+    /// Next draw of the splitmix64 generator, in `[0, 1)`
+    fn draw(&self) -> f64 {
+        let state = self
+            .rng
+            .fetch_add(0x9E37_79B9_7F4A_7C15, Ordering::Relaxed)
+            .wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^= z >> 31;
+        (z >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    fn hit(&self, probability: f64) -> bool {
+        probability > 0.0 && self.draw() < probability
+    }
+
+    /// Delay and maybe fail a call about to run
+    async fn before_call(&self, kind: &str, name: &str) -> InterceptorDecision {
+        if self.hit(self.delay_probability) {
+            let (min, max) = self.delay_range;
+            let delay = min + (max - min).mul_f64(self.draw());
+            self.counters.delays.fetch_add(1, Ordering::Relaxed);
+            tracing::info!(
+                kind,
+                name,
+                delay_ms = delay.as_millis() as u64,
+                "Injecting delay"
+            );
+            tokio::time::sleep(delay).await;
+        }
+        if self.hit(self.failure_probability) {
+            let index = (self.draw() * self.errors.len() as f64) as usize;
+            let error = &self.errors[index.min(self.errors.len() - 1)];
+            self.counters.failures.fetch_add(1, Ordering::Relaxed);
+            tracing::info!(kind, name, error = ?error, "Injecting failure");
+            return InterceptorDecision::Block(error.message());
+        }
+        InterceptorDecision::Allow
+    }
+
+    /// Maybe truncate a successful result
+    fn after_call(&self, kind: &str, name: &str, result: Value) -> Value {
+        if !self.hit(self.truncate_probability) {
+            return result;
+        }
+        self.counters.truncations.fetch_add(1, Ordering::Relaxed);
+        tracing::info!(
+            kind,
+            name,
+            keep = self.truncate_keep,
+            "Injecting truncation"
+        );
+        truncate(result, self.truncate_keep)
+    }
+}
+
+impl Default for FaultInjectionInterceptor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Debug for FaultInjectionInterceptor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FaultInjectionInterceptor")
+            .field("target", &self.target)
+            .field("names", &self.names)
+            .field("delay_probability", &self.delay_probability)
+            .field("delay_range", &self.delay_range)
+            .field("failure_probability", &self.failure_probability)
+            .field("errors", &self.errors)
+            .field("truncate_probability", &self.truncate_probability)
+            .field("truncate_keep", &self.truncate_keep)
+            .finish()
+    }
+}
+
+/// Keep the leading `keep` fraction of a value
+fn truncate(value: Value, keep: f64) -> Value {
+    let kept = |len: usize| (len as f64 * keep).floor() as usize;
+    match value {
+        Value::String(text) => {
+            let count = kept(text.chars().count());
+            Value::String(text.chars().take(count).collect())
+        }
+        Value::Array(mut items) => {
+            items.truncate(kept(items.len()));
+            Value::Array(items)
+        }
+        other => {
+            let text = other.to_string();
+            let count = kept(text.chars().count());
+            Value::String(text.chars().take(count).collect())
+        }
+    }
+}
+
+#[async_trait]
+impl LLMInterceptor for FaultInjectionInterceptor {
+    async fn intercept_llm_call(&self, context: &LLMCallContext) -> Result<InterceptorDecision> {
+        if !self.applies(FaultTarget::Llm, &context.function_name) {
+            return Ok(InterceptorDecision::Allow);
+        }
+        Ok(self.before_call("llm", &context.function_name).await)
+    }
+
+    async fn on_llm_call_complete(
+        &self,
+        _context: &LLMCallContext,
+        _result: &Result<Value>,
+        _duration_ms: u64,
+    ) {
+    }
+
+    async fn on_llm_result_transform(
+        &self,
+        context: &LLMCallContext,
+        result: Value,
+    ) -> Result<Value> {
+        if !self.applies(FaultTarget::Llm, &context.function_name) {
+            return Ok(result);
+        }
+        Ok(self.after_call("llm", &context.function_name, result))
+    }
+}
+
+#[async_trait]
+impl ToolInterceptor for FaultInjectionInterceptor {
+    async fn intercept_tool_call(&self, context: &ToolCallContext) -> Result<InterceptorDecision> {
+        if !self.applies(FaultTarget::Tools, &context.tool_name) {
+            return Ok(InterceptorDecision::Allow);
+        }
+        Ok(self.before_call("tool", &context.tool_name).await)
+    }
+
+    async fn on_tool_call_complete(
+        &self,
+        _context: &ToolCallContext,
+        _result: &Result<Value>,
+        _duration_ms: u64,
+    ) {
+    }
+
+    async fn on_tool_result_transform(
+        &self,
+        context: &ToolCallContext,
+        result: Value,
+    ) -> Result<Value> {
+        if !self.applies(FaultTarget::Tools, &context.tool_name) {
+            return Ok(result);
+        }
+        Ok(self.after_call("tool", &context.tool_name, result))
+    }
+}
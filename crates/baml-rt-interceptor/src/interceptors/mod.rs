@@ -2,6 +2,10 @@
 //!
 //! This module provides pre-built interceptors for common use cases.
 
+pub mod chained;
+pub mod fault;
 pub mod tracing;
 
+pub use chained::ChainedInterceptor;
+pub use fault::{FaultError, FaultInjectionInterceptor, FaultStats, FaultTarget};
 pub use tracing::{TracingInterceptor, TracingLLMInterceptor, TracingToolInterceptor};
@@ -208,6 +208,23 @@ impl LLMInterceptor for TracingInterceptor {
             .on_llm_call_complete(context, result, duration_ms)
             .await;
     }
+
+    async fn on_llm_stream_chunk(
+        &self,
+        context: &LLMCallContext,
+        chunk: &Value,
+        index: usize,
+    ) -> Result<InterceptorDecision> {
+        self.llm.on_llm_stream_chunk(context, chunk, index).await
+    }
+
+    async fn on_llm_result_transform(
+        &self,
+        context: &LLMCallContext,
+        result: Value,
+    ) -> Result<Value> {
+        self.llm.on_llm_result_transform(context, result).await
+    }
 }
 
 // Delegate ToolInterceptor implementation
@@ -227,4 +244,12 @@ impl ToolInterceptor for TracingInterceptor {
             .on_tool_call_complete(context, result, duration_ms)
             .await;
     }
+
+    async fn on_tool_result_transform(
+        &self,
+        context: &ToolCallContext,
+        result: Value,
+    ) -> Result<Value> {
+        self.tool.on_tool_result_transform(context, result).await
+    }
 }
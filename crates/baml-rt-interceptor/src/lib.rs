@@ -4,7 +4,10 @@ pub mod interceptor;
 pub mod interceptors;
 
 pub use interceptor::{
-    InterceptorDecision, InterceptorPipeline, InterceptorRegistry, LLMCallContext, LLMInterceptor,
-    ToolCallContext, ToolInterceptor,
+    DEFAULT_PRIORITY, DecisionPolicy, InterceptorDecision, InterceptorPipeline,
+    InterceptorRegistry, LLMCallContext, LLMInterceptor, ToolCallContext, ToolInterceptor,
+};
+pub use interceptors::{
+    ChainedInterceptor, FaultError, FaultInjectionInterceptor, FaultStats, FaultTarget,
+    TracingInterceptor, TracingLLMInterceptor, TracingToolInterceptor,
 };
-pub use interceptors::{TracingInterceptor, TracingLLMInterceptor, TracingToolInterceptor};
@@ -1,15 +1,20 @@
 //! Integration and end-to-end tests for LLM interception.
 
 use baml_rt::{
+    baml::BamlRuntimeManager,
     error::Result,
-    interceptor::{InterceptorDecision, LLMCallContext, LLMInterceptor},
+    interceptor::{
+        DecisionPolicy, InterceptorDecision, InterceptorPipeline, InterceptorRegistry,
+        LLMCallContext, LLMInterceptor, ToolCallContext, ToolInterceptor,
+    },
+    interceptors::{ChainedInterceptor, FaultError, FaultInjectionInterceptor, FaultTarget},
 };
 use serde_json::Value;
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::sync::Mutex;
 
-use test_support::common::{require_api_key, setup_baml_runtime_manager_default};
+use test_support::common::{CalculatorTool, require_api_key, setup_baml_runtime_manager_default};
 /// Test interceptor that tracks pre-execution calls
 struct PreExecutionTracker {
     pre_execution_calls: Arc<Mutex<Vec<LLMCallContext>>>,
@@ -615,3 +620,291 @@ async fn test_e2e_llm_interceptor_with_baml_execution() {
 
     tracing::info!("🎉 E2E LLM interceptor test completed successfully!");
 }
+
+/// Tool interceptor that drops the verbose `formatted` field and cites its source
+struct CitingToolInterceptor {
+    completed: Arc<Mutex<Vec<Value>>>,
+}
+
+#[async_trait::async_trait]
+impl ToolInterceptor for CitingToolInterceptor {
+    async fn intercept_tool_call(&self, _context: &ToolCallContext) -> Result<InterceptorDecision> {
+        Ok(InterceptorDecision::Allow)
+    }
+
+    async fn on_tool_call_complete(
+        &self,
+        _context: &ToolCallContext,
+        result: &Result<Value>,
+        _duration_ms: u64,
+    ) {
+        if let Ok(value) = result {
+            self.completed.lock().await.push(value.clone());
+        }
+    }
+
+    async fn on_tool_result_transform(
+        &self,
+        context: &ToolCallContext,
+        mut result: Value,
+    ) -> Result<Value> {
+        if let Some(object) = result.as_object_mut() {
+            object.remove("formatted");
+            object.insert("source".to_string(), Value::from(context.tool_name.clone()));
+        }
+        Ok(result)
+    }
+}
+
+#[tokio::test]
+async fn test_tool_result_transform_rewrites_returned_value() {
+    let manager = BamlRuntimeManager::new().unwrap();
+    manager.register_tool(CalculatorTool).await.unwrap();
+    let completed = Arc::new(Mutex::new(Vec::new()));
+    manager
+        .register_tool_interceptor(CitingToolInterceptor {
+            completed: completed.clone(),
+        })
+        .await;
+
+    let result = manager
+        .execute_tool(
+            "calculate",
+            serde_json::json!({"expression": {"left": 2, "operation": "Add", "right": 3}}),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(result["result"], 5.0);
+    assert_eq!(result["source"], "calculate");
+    assert!(result.get("formatted").is_none());
+    // Completion hooks observe the value actually returned
+    assert_eq!(completed.lock().await.as_slice(), &[result]);
+}
+
+/// Tool interceptor that records its label when it sees a call
+struct LabelledToolInterceptor {
+    label: &'static str,
+    seen: Arc<Mutex<Vec<&'static str>>>,
+}
+
+#[async_trait::async_trait]
+impl ToolInterceptor for LabelledToolInterceptor {
+    async fn intercept_tool_call(&self, _context: &ToolCallContext) -> Result<InterceptorDecision> {
+        self.seen.lock().await.push(self.label);
+        Ok(InterceptorDecision::Allow)
+    }
+
+    async fn on_tool_call_complete(
+        &self,
+        _context: &ToolCallContext,
+        _result: &Result<Value>,
+        _duration_ms: u64,
+    ) {
+    }
+}
+
+#[tokio::test]
+async fn test_named_interceptors_follow_priority_and_can_be_replaced() {
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let labelled = |label| LabelledToolInterceptor {
+        label,
+        seen: seen.clone(),
+    };
+
+    let mut registry = InterceptorRegistry::new();
+    registry.register_tool_interceptor(labelled("unnamed"));
+    registry
+        .register_named_tool_interceptor("audit", -10, labelled("audit"))
+        .unwrap();
+    registry
+        .register_named_tool_interceptor("policy", 100, labelled("policy"))
+        .unwrap();
+    registry
+        .register_named_tool_interceptor("quota", 100, labelled("quota"))
+        .unwrap();
+    assert!(
+        registry
+            .register_named_tool_interceptor("quota", 0, labelled("again"))
+            .is_err()
+    );
+    assert_eq!(
+        registry.tool_pipeline().names(),
+        vec!["policy", "quota", "audit"]
+    );
+
+    registry
+        .replace_tool_interceptor("policy", labelled("policy v2"))
+        .unwrap();
+    assert!(registry.unregister_tool_interceptor("quota"));
+    assert!(!registry.unregister_tool_interceptor("quota"));
+    assert!(
+        registry
+            .replace_tool_interceptor("quota", labelled("quota"))
+            .is_err()
+    );
+
+    let context = ToolCallContext {
+        tool_name: "calculate".to_string(),
+        function_name: None,
+        args: Value::Null,
+        context_id: baml_rt_core::ids::ContextId::new("ctx-order".to_string()),
+        metadata: Value::Null,
+    };
+    registry.intercept_tool_call(&context).await.unwrap();
+    assert_eq!(
+        seen.lock().await.as_slice(),
+        &["policy v2", "unnamed", "audit"]
+    );
+}
+
+/// Tool interceptor with a fixed vote
+struct VotingToolInterceptor(Option<&'static str>);
+
+#[async_trait::async_trait]
+impl ToolInterceptor for VotingToolInterceptor {
+    async fn intercept_tool_call(&self, _context: &ToolCallContext) -> Result<InterceptorDecision> {
+        Ok(match self.0 {
+            Some(reason) => InterceptorDecision::Block(reason.to_string()),
+            None => InterceptorDecision::Allow,
+        })
+    }
+
+    async fn on_tool_call_complete(
+        &self,
+        _context: &ToolCallContext,
+        _result: &Result<Value>,
+        _duration_ms: u64,
+    ) {
+    }
+}
+
+fn votes(votes: &[Option<&'static str>]) -> InterceptorPipeline<dyn ToolInterceptor> {
+    votes.iter().fold(InterceptorPipeline::new(), |pipeline, vote| {
+        pipeline.with_interceptor(Arc::new(VotingToolInterceptor(*vote)) as Arc<dyn ToolInterceptor>)
+    })
+}
+
+#[tokio::test]
+async fn test_decision_policies_resolve_conflicting_votes() {
+    let context = ToolCallContext {
+        tool_name: "calculate".to_string(),
+        function_name: None,
+        args: Value::Null,
+        context_id: baml_rt_core::ids::ContextId::new("ctx-policy".to_string()),
+        metadata: Value::Null,
+    };
+    let mixed = [Some("pii"), None, Some("quota")];
+
+    let first = InterceptorRegistry::new().with_tool_pipeline(votes(&mixed));
+    let error = first.intercept_tool_call(&context).await.unwrap_err();
+    assert!(error.to_string().contains("pii") && !error.to_string().contains("quota"));
+
+    let all = InterceptorRegistry::new()
+        .with_tool_pipeline(votes(&mixed))
+        .with_decision_policy(DecisionPolicy::AllMustAllow);
+    let error = all.intercept_tool_call(&context).await.unwrap_err();
+    assert!(error.to_string().contains("pii; quota"));
+
+    let any = InterceptorRegistry::new()
+        .with_tool_pipeline(votes(&mixed))
+        .with_decision_policy(DecisionPolicy::AnyAllows);
+    assert!(any.intercept_tool_call(&context).await.is_ok());
+
+    // A chain votes as one member under its own policy
+    let chain = ChainedInterceptor::from_pipeline(
+        votes(&[Some("denylist"), None]),
+        DecisionPolicy::AnyAllows,
+    );
+    assert!(matches!(
+        chain.intercept_tool_call(&context).await.unwrap(),
+        InterceptorDecision::Allow
+    ));
+    let strict = ChainedInterceptor::from_pipeline(votes(&mixed), DecisionPolicy::AllMustAllow);
+    let mut registry = InterceptorRegistry::new();
+    registry.register_tool_interceptor(chain);
+    registry.register_tool_interceptor(strict);
+    let error = registry.intercept_tool_call(&context).await.unwrap_err();
+    assert!(error.to_string().contains("pii; quota"));
+}
+
+#[tokio::test]
+async fn test_fault_injection_delays_fails_and_truncates_calls() {
+    let tool_context = ToolCallContext {
+        tool_name: "calculate".to_string(),
+        function_name: None,
+        args: Value::Null,
+        context_id: baml_rt_core::ids::ContextId::new("ctx-fault".to_string()),
+        metadata: Value::Null,
+    };
+    let llm_context = LLMCallContext {
+        client: "Fast".to_string(),
+        model: "gpt-4o-mini".to_string(),
+        function_name: "Summarize".to_string(),
+        context_id: baml_rt_core::ids::ContextId::new("ctx-fault".to_string()),
+        prompt: Value::Null,
+        metadata: Value::Null,
+    };
+
+    let faults = FaultInjectionInterceptor::new()
+        .with_seed(7)
+        .with_target(FaultTarget::Tools)
+        .with_delay(1.0, Duration::from_millis(20), Duration::from_millis(20))
+        .with_failure(1.0, vec![FaultError::RateLimited]);
+    let mut registry = InterceptorRegistry::new();
+    registry.register_tool_interceptor(faults.clone());
+    registry.register_llm_interceptor(faults.clone());
+
+    let start = std::time::Instant::now();
+    let error = registry
+        .intercept_tool_call(&tool_context)
+        .await
+        .unwrap_err();
+    assert!(start.elapsed() >= Duration::from_millis(20));
+    assert!(error.to_string().contains("429"), "{}", error);
+    // LLM calls are not targeted
+    assert!(registry.intercept_llm_call(&llm_context).await.is_ok());
+    let stats = faults.stats();
+    assert_eq!((stats.delays, stats.failures, stats.truncations), (1, 1, 0));
+
+    let truncating = FaultInjectionInterceptor::new()
+        .with_seed(7)
+        .for_names(["Summarize"])
+        .with_truncation(1.0, 0.5);
+    let mut registry = InterceptorRegistry::new();
+    registry.register_llm_interceptor(truncating.clone());
+    registry.register_tool_interceptor(truncating);
+    let text = Value::String("abcdefgh".to_string());
+    assert_eq!(
+        registry
+            .transform_llm_result(&llm_context, text.clone())
+            .await,
+        Value::String("abcd".to_string())
+    );
+    // Only the named function is targeted
+    assert_eq!(
+        registry
+            .transform_tool_result(&tool_context, text.clone())
+            .await,
+        text
+    );
+
+    // The same seed injects the same faults
+    let draws = |seed| {
+        let faults = FaultInjectionInterceptor::new()
+            .with_seed(seed)
+            .with_failure(0.5, Vec::new());
+        let context = tool_context.clone();
+        async move {
+            let mut failed = Vec::new();
+            for _ in 0..16 {
+                let decision = faults.intercept_tool_call(&context).await.unwrap();
+                failed.push(matches!(decision, InterceptorDecision::Block(_)));
+            }
+            failed
+        }
+    };
+    let first = draws(42).await;
+    assert_eq!(first, draws(42).await);
+    assert!(first.contains(&true) && first.contains(&false));
+}
@@ -0,0 +1,26 @@
+export type Json = null | boolean | number | string | Json[] | { [key: string]: Json };
+
+export type ToolHandler = (args: any) => Json | Promise<Json>;
+
+export declare class BamlRuntime {
+  constructor();
+  /** A runtime with the BAML schema at `schemaPath` loaded */
+  static load(schemaPath: string): Promise<BamlRuntime>;
+  loadSchema(schemaPath: string): Promise<void>;
+  /** Names of the BAML functions in the loaded schema */
+  functions(): Promise<string[]>;
+  /** Names of the registered tools */
+  tools(): Promise<string[]>;
+  invokeFunction<T = any>(functionName: string, args?: Record<string, Json>): Promise<T>;
+  registerTool(
+    name: string,
+    description: string,
+    inputSchema: Json,
+    handler: ToolHandler,
+  ): Promise<void>;
+  /** Partial results as they arrive, then the final result */
+  streamFunction<T = any>(
+    functionName: string,
+    args?: Record<string, Json>,
+  ): AsyncGenerator<T, void, undefined>;
+}
@@ -0,0 +1,55 @@
+// The native module, wrapped so callers get plain async functions and
+// async iterators
+const binding = require('./binding.js');
+
+class BamlRuntime {
+  constructor() {
+    this.native = new binding.BamlRuntime();
+  }
+
+  static async load(schemaPath) {
+    const runtime = new BamlRuntime();
+    await runtime.loadSchema(schemaPath);
+    return runtime;
+  }
+
+  loadSchema(schemaPath) {
+    return this.native.loadSchema(schemaPath);
+  }
+
+  functions() {
+    return this.native.functions();
+  }
+
+  tools() {
+    return this.native.tools();
+  }
+
+  invokeFunction(functionName, args = {}) {
+    return this.native.invokeFunction(functionName, args);
+  }
+
+  // `handler` may be sync or async; the native side awaits a promise
+  registerTool(name, description, inputSchema, handler) {
+    return this.native.registerTool(name, description, inputSchema, async (args) =>
+      handler(args),
+    );
+  }
+
+  async *streamFunction(functionName, args = {}) {
+    const stream = this.native.streamFunction(functionName, args);
+    try {
+      for (;;) {
+        const chunk = await stream.next();
+        if (chunk === null) {
+          return;
+        }
+        yield chunk.value;
+      }
+    } finally {
+      stream.cancel();
+    }
+  }
+}
+
+module.exports = { BamlRuntime };
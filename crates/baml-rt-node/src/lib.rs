@@ -0,0 +1,243 @@
+//! Node.js bindings for the BAML runtime
+//!
+//! Built with napi-rs into the `baml-rt` package, whose `index.js` wraps
+//! this module:
+//!
+//! ```js
+//! const { BamlRuntime } = require('baml-rt');
+//!
+//! const runtime = await BamlRuntime.load('baml_src');
+//! await runtime.registerTool('lookup_order', 'Finds an order by id', schema, async ({ id }) =>
+//!   orders.get(id),
+//! );
+//! const greeting = await runtime.invokeFunction('SimpleGreeting', { name: 'Ada' });
+//! for await (const partial of runtime.streamFunction('WriteStory', { topic: 'otters' })) {
+//!   render(partial);
+//! }
+//! ```
+//!
+//! BAML functions run directly on the Rust executor, without a QuickJS
+//! sandbox, on napi's Tokio runtime. Values cross the boundary as JSON, and
+//! tools registered from JavaScript are called back on the main thread.
+
+use async_trait::async_trait;
+use baml_rt_core::{BamlRtError, Result};
+use baml_rt_quickjs::BamlRuntimeManager;
+use baml_rt_tools::{ToolExecutor, ToolMetadata};
+use napi::bindgen_prelude::Promise;
+use napi::threadsafe_function::{ErrorStrategy, ThreadSafeCallContext, ThreadsafeFunction};
+use napi::{Env, JsFunction, JsObject};
+use napi_derive::napi;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::{Mutex, RwLock, mpsc};
+use tokio_util::sync::CancellationToken;
+
+fn to_napi_err(err: BamlRtError) -> napi::Error {
+    napi::Error::from_reason(err.to_string())
+}
+
+/// A JavaScript function registered as a tool
+///
+/// `index.js` wraps handlers so they always return a promise.
+struct JsTool {
+    handler: ThreadsafeFunction<Value, ErrorStrategy::Fatal>,
+}
+
+#[async_trait]
+impl ToolExecutor for JsTool {
+    async fn execute(&self, args: Value) -> Result<Value> {
+        let to_tool_err = |err: napi::Error| BamlRtError::ToolExecution(err.reason);
+        let result = self
+            .handler
+            .call_async::<Promise<Value>>(args)
+            .await
+            .map_err(to_tool_err)?;
+        result.await.map_err(to_tool_err)
+    }
+}
+
+/// A BAML runtime with its schema and tools
+#[napi]
+pub struct BamlRuntime {
+    manager: Arc<RwLock<BamlRuntimeManager>>,
+}
+
+#[napi]
+impl BamlRuntime {
+    #[napi(constructor)]
+    pub fn new() -> napi::Result<Self> {
+        let manager = BamlRuntimeManager::new().map_err(to_napi_err)?;
+        Ok(Self {
+            manager: Arc::new(RwLock::new(manager)),
+        })
+    }
+
+    /// Load the BAML schema at `schema_path`, replacing any loaded before
+    #[napi]
+    pub async fn load_schema(&self, schema_path: String) -> napi::Result<()> {
+        let mut manager = self.manager.write().await;
+        manager.load_schema(&schema_path).map_err(to_napi_err)
+    }
+
+    /// Names of the BAML functions in the loaded schema
+    #[napi]
+    pub async fn functions(&self) -> Vec<String> {
+        self.manager.read().await.list_functions()
+    }
+
+    /// Names of the registered tools
+    #[napi]
+    pub async fn tools(&self) -> Vec<String> {
+        self.manager.read().await.list_tools().await
+    }
+
+    #[napi]
+    pub async fn invoke_function(&self, function_name: String, args: Value) -> napi::Result<Value> {
+        let manager = self.manager.read().await;
+        manager
+            .invoke_function(&function_name, args)
+            .await
+            .map_err(to_napi_err)
+    }
+
+    /// Register `handler` as the tool `name`; it is called with the
+    /// arguments object and resolves to the tool result
+    #[napi(ts_return_type = "Promise<void>")]
+    pub fn register_tool(
+        &self,
+        env: Env,
+        name: String,
+        description: String,
+        input_schema: Value,
+        handler: JsFunction,
+    ) -> napi::Result<JsObject> {
+        let mut handler: ThreadsafeFunction<Value, ErrorStrategy::Fatal> = handler
+            .create_threadsafe_function(0, |ctx: ThreadSafeCallContext<Value>| {
+                Ok(vec![ctx.value])
+            })?;
+        // A registered tool alone does not keep the process alive
+        handler.unref(&env)?;
+
+        let metadata = ToolMetadata {
+            name,
+            description,
+            input_schema,
+            requires_approval: false,
+        };
+        let executor: Arc<dyn ToolExecutor> = Arc::new(JsTool { handler });
+        let manager = self.manager.clone();
+        env.spawn_future(async move {
+            let registry = manager.read().await.tool_registry();
+            let mut registry = registry.lock().await;
+            registry
+                .register_dynamic(metadata, executor)
+                .map_err(to_napi_err)
+        })
+    }
+
+    /// Start streaming `function_name`; `index.js` turns the result into an
+    /// async iterator
+    #[napi]
+    pub fn stream_function(&self, function_name: String, args: Value) -> FunctionStream {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let cancel = CancellationToken::new();
+        napi::bindgen_prelude::spawn(run_stream(
+            self.manager.clone(),
+            function_name,
+            args,
+            cancel.clone(),
+            sender,
+        ));
+        FunctionStream {
+            receiver: Arc::new(Mutex::new(receiver)),
+            cancel,
+        }
+    }
+}
+
+/// One value of a [`FunctionStream`]
+#[napi(object)]
+pub struct StreamChunk {
+    pub value: Value,
+}
+
+/// The results of a streaming call: partial results as they arrive, then
+/// the final one
+#[napi]
+pub struct FunctionStream {
+    receiver: Arc<Mutex<mpsc::UnboundedReceiver<std::result::Result<Value, String>>>>,
+    cancel: CancellationToken,
+}
+
+#[napi]
+impl FunctionStream {
+    /// The next result, or `null` once the call has finished
+    #[napi]
+    pub async fn next(&self) -> napi::Result<Option<StreamChunk>> {
+        match self.receiver.lock().await.recv().await {
+            Some(Ok(value)) => Ok(Some(StreamChunk { value })),
+            Some(Err(reason)) => Err(napi::Error::from_reason(reason)),
+            None => Ok(None),
+        }
+    }
+
+    /// Abort the call; results already received stay readable
+    #[napi]
+    pub fn cancel(&self) {
+        self.cancel.cancel();
+    }
+}
+
+/// Run a streaming call, sending each partial result and then the final
+/// result or the error that ended it
+async fn run_stream(
+    manager: Arc<RwLock<BamlRuntimeManager>>,
+    function_name: String,
+    args: Value,
+    cancel: CancellationToken,
+    sender: mpsc::UnboundedSender<std::result::Result<Value, String>>,
+) {
+    let stream = {
+        let manager = manager.read().await;
+        let type_builder = manager.tool_type_builder(&function_name).await;
+        manager
+            .invoke_function_stream_with_cancel(&function_name, args, cancel, type_builder)
+            .await
+    };
+    // The stream owns what it needs, so the manager is free while it runs
+    let mut stream = match stream {
+        Ok(stream) => stream,
+        Err(err) => {
+            let _ = sender.send(Err(err.to_string()));
+            return;
+        }
+    };
+
+    let partials = sender.clone();
+    let result = stream
+        .run(
+            move |result| {
+                if let Some(Ok(parsed)) = result.parsed()
+                    && let Ok(value) = serde_json::to_value(parsed.serialize_partial())
+                {
+                    // A closed receiver means the caller stopped reading
+                    let _ = partials.send(Ok(value));
+                }
+            },
+            HashMap::new(),
+        )
+        .await;
+
+    let last = match result {
+        Ok(result) => match result.parsed() {
+            Some(Ok(parsed)) => {
+                serde_json::to_value(parsed.serialize_partial()).map_err(|err| err.to_string())
+            }
+            _ => Err(format!("{} returned no parsed result", function_name)),
+        },
+        Err(err) => Err(err.to_string()),
+    };
+    let _ = sender.send(last);
+}
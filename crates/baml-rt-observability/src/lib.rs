@@ -1,9 +1,11 @@
-//! Observability helpers (metrics, spans, tracing setup).
+//! Observability helpers (metrics, spans, tracing setup, recent activity).
 
 pub mod metrics;
+pub mod recent;
 pub mod spans;
 pub mod tracing_setup;
 
 pub use metrics::*;
+pub use recent::*;
 pub use spans::*;
 pub use tracing_setup::*;
@@ -1,8 +1,12 @@
 //! OpenTelemetry metrics helpers.
 //!
 //! Metrics are defined here to keep instrumentation orthogonal to business logic.
+//!
+//! Request, BAML function, and tool metrics carry a `tenant` label with the
+//! tenant in scope when they are recorded, `none` outside any tenant.
 
-use opentelemetry::metrics::{Counter, Histogram};
+use baml_rt_core::tenant;
+use opentelemetry::metrics::{Counter, Gauge, Histogram};
 use opentelemetry::{KeyValue, global};
 use std::sync::OnceLock;
 use std::time::Duration;
@@ -14,8 +18,25 @@ static A2A_REQUEST_HISTOGRAM: OnceLock<Histogram<f64>> = OnceLock::new();
 static A2A_ERROR_COUNTER: OnceLock<Counter<u64>> = OnceLock::new();
 static A2A_STREAM_CHUNK_COUNTER: OnceLock<Counter<u64>> = OnceLock::new();
 static A2A_STREAM_CHUNK_HISTOGRAM: OnceLock<Histogram<f64>> = OnceLock::new();
+static BAML_FUNCTION_COUNTER: OnceLock<Counter<u64>> = OnceLock::new();
+static BAML_FUNCTION_HISTOGRAM: OnceLock<Histogram<f64>> = OnceLock::new();
+static BAML_REPAIR_COUNTER: OnceLock<Counter<u64>> = OnceLock::new();
 static TOOL_INVOCATION_COUNTER: OnceLock<Counter<u64>> = OnceLock::new();
 static TOOL_INVOCATION_HISTOGRAM: OnceLock<Histogram<f64>> = OnceLock::new();
+static BRIDGE_FAILOVER_COUNTER: OnceLock<Counter<u64>> = OnceLock::new();
+static OUTBOX_DISPATCH_COUNTER: OnceLock<Counter<u64>> = OnceLock::new();
+static STREAM_TRUNCATION_COUNTER: OnceLock<Counter<u64>> = OnceLock::new();
+static QUICKJS_MALLOC_GAUGE: OnceLock<Gauge<u64>> = OnceLock::new();
+static QUICKJS_HEAP_GAUGE: OnceLock<Gauge<u64>> = OnceLock::new();
+static QUICKJS_OBJECT_GAUGE: OnceLock<Gauge<u64>> = OnceLock::new();
+static QUICKJS_GC_GAUGE: OnceLock<Gauge<u64>> = OnceLock::new();
+
+/// Label for the tenant of the current request
+fn tenant_attribute() -> KeyValue {
+    let tenant =
+        tenant::current_tenant_id().map_or_else(|| "none".to_string(), |id| id.into_string());
+    KeyValue::new("tenant", tenant)
+}
 
 fn a2a_request_counter() -> &'static Counter<u64> {
     A2A_REQUEST_COUNTER.get_or_init(|| {
@@ -57,6 +78,30 @@ fn a2a_stream_chunk_histogram() -> &'static Histogram<f64> {
     })
 }
 
+fn baml_function_counter() -> &'static Counter<u64> {
+    BAML_FUNCTION_COUNTER.get_or_init(|| {
+        global::meter(METER_NAME)
+            .u64_counter("baml_rt.baml.function_total")
+            .init()
+    })
+}
+
+fn baml_function_histogram() -> &'static Histogram<f64> {
+    BAML_FUNCTION_HISTOGRAM.get_or_init(|| {
+        global::meter(METER_NAME)
+            .f64_histogram("baml_rt.baml.function_duration_ms")
+            .init()
+    })
+}
+
+fn baml_repair_counter() -> &'static Counter<u64> {
+    BAML_REPAIR_COUNTER.get_or_init(|| {
+        global::meter(METER_NAME)
+            .u64_counter("baml_rt.baml.repair_attempt_total")
+            .init()
+    })
+}
+
 fn tool_invocation_counter() -> &'static Counter<u64> {
     TOOL_INVOCATION_COUNTER.get_or_init(|| {
         global::meter(METER_NAME)
@@ -73,12 +118,69 @@ fn tool_invocation_histogram() -> &'static Histogram<f64> {
     })
 }
 
+fn bridge_failover_counter() -> &'static Counter<u64> {
+    BRIDGE_FAILOVER_COUNTER.get_or_init(|| {
+        global::meter(METER_NAME)
+            .u64_counter("baml_rt.bridge.failover_total")
+            .init()
+    })
+}
+
+fn outbox_dispatch_counter() -> &'static Counter<u64> {
+    OUTBOX_DISPATCH_COUNTER.get_or_init(|| {
+        global::meter(METER_NAME)
+            .u64_counter("baml_rt.outbox.dispatch_total")
+            .init()
+    })
+}
+
+fn stream_truncation_counter() -> &'static Counter<u64> {
+    STREAM_TRUNCATION_COUNTER.get_or_init(|| {
+        global::meter(METER_NAME)
+            .u64_counter("baml_rt.stream.truncated_total")
+            .init()
+    })
+}
+
+fn quickjs_malloc_gauge() -> &'static Gauge<u64> {
+    QUICKJS_MALLOC_GAUGE.get_or_init(|| {
+        global::meter(METER_NAME)
+            .u64_gauge("baml_rt.quickjs.malloc_bytes")
+            .init()
+    })
+}
+
+fn quickjs_heap_gauge() -> &'static Gauge<u64> {
+    QUICKJS_HEAP_GAUGE.get_or_init(|| {
+        global::meter(METER_NAME)
+            .u64_gauge("baml_rt.quickjs.heap_used_bytes")
+            .init()
+    })
+}
+
+fn quickjs_object_gauge() -> &'static Gauge<u64> {
+    QUICKJS_OBJECT_GAUGE.get_or_init(|| {
+        global::meter(METER_NAME)
+            .u64_gauge("baml_rt.quickjs.object_count")
+            .init()
+    })
+}
+
+fn quickjs_gc_gauge() -> &'static Gauge<u64> {
+    QUICKJS_GC_GAUGE.get_or_init(|| {
+        global::meter(METER_NAME)
+            .u64_gauge("baml_rt.quickjs.gc_runs")
+            .init()
+    })
+}
+
 /// Record completion of an A2A request.
 pub fn record_a2a_request(method: &str, result: &str, is_stream: bool, duration: Duration) {
     let attributes = &[
         KeyValue::new("method", method.to_string()),
         KeyValue::new("result", result.to_string()),
         KeyValue::new("stream", is_stream.to_string()),
+        tenant_attribute(),
     ];
 
     a2a_request_counter().add(1, attributes);
@@ -91,23 +193,97 @@ pub fn record_a2a_error(method: &str, error_type: &str, is_stream: bool) {
         KeyValue::new("method", method.to_string()),
         KeyValue::new("error_type", error_type.to_string()),
         KeyValue::new("stream", is_stream.to_string()),
+        tenant_attribute(),
     ];
     a2a_error_counter().add(1, attributes);
 }
 
 /// Record the number of chunks produced by a stream.
 pub fn record_a2a_stream_chunks(method: &str, chunk_count: usize) {
-    let attributes = &[KeyValue::new("method", method.to_string())];
+    let attributes = &[
+        KeyValue::new("method", method.to_string()),
+        tenant_attribute(),
+    ];
     a2a_stream_chunk_counter().add(chunk_count as u64, attributes);
     a2a_stream_chunk_histogram().record(chunk_count as f64, attributes);
 }
 
+/// Record a BAML function invocation, labeled with the client that served it.
+pub fn record_baml_function(
+    function_name: &str,
+    model: &str,
+    result: &str,
+    is_stream: bool,
+    duration: Duration,
+) {
+    let attributes = &[
+        KeyValue::new("function", function_name.to_string()),
+        KeyValue::new("model", model.to_string()),
+        KeyValue::new("result", result.to_string()),
+        KeyValue::new("stream", is_stream.to_string()),
+        tenant_attribute(),
+    ];
+    baml_function_counter().add(1, attributes);
+    baml_function_histogram().record(duration.as_millis() as f64, attributes);
+}
+
+/// Record a repair attempt: a BAML function called again because its output
+/// was invalid, with `result` `repaired`, `invalid`, or `error`.
+pub fn record_baml_repair(function_name: &str, result: &str) {
+    let attributes = &[
+        KeyValue::new("function", function_name.to_string()),
+        KeyValue::new("result", result.to_string()),
+        tenant_attribute(),
+    ];
+    baml_repair_counter().add(1, attributes);
+}
+
 /// Record tool invocation metrics.
 pub fn record_tool_invocation(tool_name: &str, result: &str, duration: Duration) {
     let attributes = &[
         KeyValue::new("tool", tool_name.to_string()),
         KeyValue::new("result", result.to_string()),
+        tenant_attribute(),
     ];
     tool_invocation_counter().add(1, attributes);
     tool_invocation_histogram().record(duration.as_millis() as f64, attributes);
 }
+
+/// Record a JS bridge failover attempt.
+pub fn record_bridge_failover(result: &str, from_standby: bool) {
+    let attributes = &[
+        KeyValue::new("result", result.to_string()),
+        KeyValue::new("standby", from_standby.to_string()),
+    ];
+    bridge_failover_counter().add(1, attributes);
+}
+
+/// Record an outbox dispatch attempt.
+pub fn record_outbox_dispatch(tool_name: &str, result: &str) {
+    let attributes = &[
+        KeyValue::new("tool", tool_name.to_string()),
+        KeyValue::new("result", result.to_string()),
+    ];
+    outbox_dispatch_counter().add(1, attributes);
+}
+
+/// Record a stream cut off by the output token limiter.
+pub fn record_stream_truncation(function_name: &str) {
+    let attributes = &[KeyValue::new("function", function_name.to_string())];
+    stream_truncation_counter().add(1, attributes);
+}
+
+/// Record a QuickJS memory sample.
+pub fn record_quickjs_memory(
+    scope: &str,
+    malloc_bytes: u64,
+    heap_used_bytes: u64,
+    object_count: u64,
+    gc_runs: u64,
+) {
+    let attributes = &[KeyValue::new("scope", scope.to_string())];
+    quickjs_malloc_gauge().record(malloc_bytes, attributes);
+    quickjs_heap_gauge().record(heap_used_bytes, attributes);
+    quickjs_object_gauge().record(object_count, attributes);
+    quickjs_gc_gauge().record(gc_runs, attributes);
+}
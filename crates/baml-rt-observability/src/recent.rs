@@ -0,0 +1,241 @@
+//! In-memory buffers of recent log events and closed spans.
+//!
+//! [`RecentActivityLayer`] keeps the latest events and span snapshots in a
+//! bounded [`RecentActivity`] so they can be exported for bug reports without
+//! a log file on disk. [`init_tracing`](crate::init_tracing) installs a layer
+//! feeding the process-wide buffer returned by [`recent_activity`].
+
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Subscriber};
+use tracing_subscriber::Layer;
+use tracing_subscriber::layer::Context;
+use tracing_subscriber::registry::LookupSpan;
+
+/// Log events kept by the process-wide buffer
+pub const DEFAULT_LOG_CAPACITY: usize = 1000;
+/// Closed spans kept by the process-wide buffer
+pub const DEFAULT_SPAN_CAPACITY: usize = 500;
+
+static RECENT_ACTIVITY: OnceLock<Arc<RecentActivity>> = OnceLock::new();
+
+/// The process-wide buffer fed by [`init_tracing`](crate::init_tracing)
+pub fn recent_activity() -> Arc<RecentActivity> {
+    RECENT_ACTIVITY
+        .get_or_init(|| {
+            Arc::new(RecentActivity::new(
+                DEFAULT_LOG_CAPACITY,
+                DEFAULT_SPAN_CAPACITY,
+            ))
+        })
+        .clone()
+}
+
+/// A log event captured by [`RecentActivityLayer`]
+#[derive(Debug, Clone, Serialize)]
+pub struct LogRecord {
+    pub timestamp_ms: u64,
+    pub level: String,
+    pub target: String,
+    pub message: String,
+    pub fields: Map<String, Value>,
+}
+
+/// A closed span captured by [`RecentActivityLayer`]
+#[derive(Debug, Clone, Serialize)]
+pub struct SpanRecord {
+    pub name: String,
+    pub target: String,
+    pub level: String,
+    pub parent: Option<String>,
+    pub started_at_ms: u64,
+    pub duration_ms: f64,
+    pub fields: Map<String, Value>,
+}
+
+/// Bounded buffers of recent log events and spans; oldest entries are dropped first
+pub struct RecentActivity {
+    logs: Mutex<VecDeque<LogRecord>>,
+    spans: Mutex<VecDeque<SpanRecord>>,
+    log_capacity: usize,
+    span_capacity: usize,
+}
+
+impl RecentActivity {
+    pub fn new(log_capacity: usize, span_capacity: usize) -> Self {
+        Self {
+            logs: Mutex::new(VecDeque::with_capacity(log_capacity)),
+            spans: Mutex::new(VecDeque::with_capacity(span_capacity)),
+            log_capacity,
+            span_capacity,
+        }
+    }
+
+    /// Buffered log events, oldest first
+    pub fn logs(&self) -> Vec<LogRecord> {
+        self.logs
+            .lock()
+            .map(|logs| logs.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Buffered spans in the order they closed
+    pub fn spans(&self) -> Vec<SpanRecord> {
+        self.spans
+            .lock()
+            .map(|spans| spans.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    fn push_log(&self, record: LogRecord) {
+        if let Ok(mut logs) = self.logs.lock() {
+            push_bounded(&mut logs, record, self.log_capacity);
+        }
+    }
+
+    fn push_span(&self, record: SpanRecord) {
+        if let Ok(mut spans) = self.spans.lock() {
+            push_bounded(&mut spans, record, self.span_capacity);
+        }
+    }
+}
+
+fn push_bounded<T>(buffer: &mut VecDeque<T>, item: T, capacity: usize) {
+    if capacity == 0 {
+        return;
+    }
+    while buffer.len() >= capacity {
+        buffer.pop_front();
+    }
+    buffer.push_back(item);
+}
+
+/// Tracing layer that records into a [`RecentActivity`]
+pub struct RecentActivityLayer {
+    activity: Arc<RecentActivity>,
+}
+
+impl RecentActivityLayer {
+    pub fn new(activity: Arc<RecentActivity>) -> Self {
+        Self { activity }
+    }
+}
+
+/// Per-span state kept in the registry's extensions until the span closes
+struct OpenSpan {
+    started: Instant,
+    started_at_ms: u64,
+    fields: Map<String, Value>,
+}
+
+impl<S> Layer<S> for RecentActivityLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        let mut fields = JsonFields::default();
+        attrs.record(&mut fields);
+        span.extensions_mut().insert(OpenSpan {
+            started: Instant::now(),
+            started_at_ms: now_ms(),
+            fields: fields.0,
+        });
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        if let Some(open) = span.extensions_mut().get_mut::<OpenSpan>() {
+            let mut fields = JsonFields(std::mem::take(&mut open.fields));
+            values.record(&mut fields);
+            open.fields = fields.0;
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let mut fields = JsonFields::default();
+        event.record(&mut fields);
+        let message = match fields.0.remove("message") {
+            Some(Value::String(message)) => message,
+            Some(other) => other.to_string(),
+            None => String::new(),
+        };
+        let metadata = event.metadata();
+        self.activity.push_log(LogRecord {
+            timestamp_ms: now_ms(),
+            level: metadata.level().to_string(),
+            target: metadata.target().to_string(),
+            message,
+            fields: fields.0,
+        });
+    }
+
+    fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(&id) else {
+            return;
+        };
+        let Some(open) = span.extensions_mut().remove::<OpenSpan>() else {
+            return;
+        };
+        let metadata = span.metadata();
+        self.activity.push_span(SpanRecord {
+            name: metadata.name().to_string(),
+            target: metadata.target().to_string(),
+            level: metadata.level().to_string(),
+            parent: span.parent().map(|parent| parent.name().to_string()),
+            started_at_ms: open.started_at_ms,
+            duration_ms: open.started.elapsed().as_secs_f64() * 1000.0,
+            fields: open.fields,
+        });
+    }
+}
+
+#[derive(Default)]
+struct JsonFields(Map<String, Value>);
+
+impl Visit for JsonFields {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.0.insert(
+            field.name().to_string(),
+            Value::String(format!("{:?}", value)),
+        );
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0
+            .insert(field.name().to_string(), Value::String(value.to_string()));
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.0.insert(field.name().to_string(), Value::from(value));
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.0.insert(field.name().to_string(), Value::from(value));
+    }
+
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.0.insert(field.name().to_string(), Value::from(value));
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.0.insert(field.name().to_string(), Value::from(value));
+    }
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis() as u64)
+        .unwrap_or(0)
+}
@@ -20,6 +20,28 @@ pub fn lint_agent(agent_dir: &Path) -> Span {
     )
 }
 
+/// Create span for checking an agent's BAML schema.
+///
+/// Parent: CLI command span
+#[inline]
+pub fn check_agent(agent_dir: &Path) -> Span {
+    tracing::debug_span!(
+        "baml_rt.check_agent",
+        agent_dir = %agent_dir.display(),
+    )
+}
+
+/// Create span for watching an agent's sources and rebuilding on change.
+///
+/// Parent: CLI command span
+#[inline]
+pub fn watch_agent(agent_dir: &Path) -> Span {
+    tracing::info_span!(
+        "baml_rt.watch_agent",
+        agent_dir = %agent_dir.display(),
+    )
+}
+
 /// Create span for agent packaging operation.
 ///
 /// Parent: CLI command span
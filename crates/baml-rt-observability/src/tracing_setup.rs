@@ -1,13 +1,36 @@
 //! Standard tracing subscriber setup for CLI binaries.
 
+use crate::recent::{RecentActivityLayer, recent_activity};
+use baml_rt_core::verbosity;
+use tracing_subscriber::filter::{FilterExt, filter_fn};
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{EnvFilter, Layer};
+
 /// Initialize a tracing subscriber with env-based filtering.
 ///
 /// Default directives:
 /// - `baml_rt=info`
 /// - `quickjs_runtime::quickjsrealmadapter=warn`
 /// - `quickjs_runtime::typescript=warn`
+///
+/// Events that the directives would drop are still emitted while a request
+/// with an elevated [`verbosity`] override is being handled. The same events
+/// are kept in the [`recent_activity`] buffer for diagnostics bundles.
 pub fn init_tracing() {
-    let filter = tracing_subscriber::EnvFilter::from_default_env()
+    let elevated = || filter_fn(|metadata| verbosity::allows(metadata.level()));
+
+    tracing_subscriber::registry()
+        .with(tracing_subscriber::fmt::layer().with_filter(default_filter().or(elevated())))
+        .with(
+            RecentActivityLayer::new(recent_activity())
+                .with_filter(default_filter().or(elevated())),
+        )
+        .init();
+}
+
+fn default_filter() -> EnvFilter {
+    EnvFilter::from_default_env()
         .add_directive("baml_rt=info".parse().unwrap_or_default())
         .add_directive(
             "quickjs_runtime::quickjsrealmadapter=warn"
@@ -18,7 +41,5 @@ pub fn init_tracing() {
             "quickjs_runtime::typescript=warn"
                 .parse()
                 .unwrap_or_default(),
-        );
-
-    tracing_subscriber::fmt().with_env_filter(filter).init();
+        )
 }
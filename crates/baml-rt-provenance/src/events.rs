@@ -1,4 +1,7 @@
-use baml_rt_core::ids::{ArtifactId, ContextId, EventId, MessageId, TaskId};
+use baml_rt_core::ids::{
+    ArtifactId, ContextId, CorrelationId, EventId, MessageId, TaskId, TenantId,
+};
+use baml_rt_core::{correlation, tenant};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::HashMap;
@@ -23,8 +26,10 @@ fn next_event_id() -> EventId {
 pub enum ProvEventType {
     LlmCallStarted,
     LlmCallCompleted,
+    LlmStreamChunk,
     ToolCallStarted,
     ToolCallCompleted,
+    ToolApprovalDecided,
     TaskCreated,
     TaskStatusChanged,
     TaskArtifactGenerated,
@@ -42,6 +47,20 @@ pub enum ProvEventData {
         metadata: Value,
         duration_ms: Option<u64>,
         success: Option<bool>,
+        /// Result of a successful call, on its completion event
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        output: Option<Value>,
+        /// Error of a failed call, on its completion event
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        error: Option<String>,
+    },
+    /// A partial result of a streamed LLM call
+    LlmStreamChunk {
+        client: String,
+        function_name: String,
+        /// Position of the chunk in its stream, from 0
+        index: usize,
+        chunk: Value,
     },
     ToolCall {
         tool_name: String,
@@ -50,6 +69,22 @@ pub enum ProvEventData {
         metadata: Value,
         duration_ms: Option<u64>,
         success: Option<bool>,
+        /// Result of a successful call, on its completion event
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        output: Option<Value>,
+        /// Error of a failed call, on its completion event
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        error: Option<String>,
+    },
+    /// A decision about a tool call that required approval
+    ToolApproval {
+        approval_id: String,
+        tool_name: String,
+        function_name: Option<String>,
+        args: Value,
+        approved: bool,
+        /// Where the decision came from, e.g. `approver` or `message`
+        decided_by: String,
     },
     TaskCreated {
         task_id: TaskId,
@@ -79,6 +114,12 @@ pub struct ProvEvent {
     pub event_type: ProvEventType,
     pub context_id: ContextId,
     pub task_id: Option<TaskId>,
+    /// Correlation ID of the request the event was recorded for
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub correlation_id: Option<CorrelationId>,
+    /// Tenant the request was made for
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tenant_id: Option<TenantId>,
     pub timestamp_ms: u64,
     pub data: ProvEventData,
 }
@@ -98,6 +139,8 @@ impl ProvEvent {
             event_type: ProvEventType::LlmCallStarted,
             context_id,
             task_id,
+            correlation_id: correlation::current_correlation_id(),
+            tenant_id: tenant::current_tenant_id(),
             timestamp_ms: now_millis(),
             data: ProvEventData::LlmCall {
                 client,
@@ -107,6 +150,8 @@ impl ProvEvent {
                 metadata,
                 duration_ms: None,
                 success: None,
+                output: None,
+                error: None,
             },
         }
     }
@@ -127,6 +172,8 @@ impl ProvEvent {
             event_type: ProvEventType::LlmCallCompleted,
             context_id,
             task_id,
+            correlation_id: correlation::current_correlation_id(),
+            tenant_id: tenant::current_tenant_id(),
             timestamp_ms: now_millis(),
             data: ProvEventData::LlmCall {
                 client,
@@ -136,6 +183,8 @@ impl ProvEvent {
                 metadata,
                 duration_ms: Some(duration_ms),
                 success: Some(success),
+                output: None,
+                error: None,
             },
         }
     }
@@ -153,6 +202,8 @@ impl ProvEvent {
             event_type: ProvEventType::ToolCallStarted,
             context_id,
             task_id,
+            correlation_id: correlation::current_correlation_id(),
+            tenant_id: tenant::current_tenant_id(),
             timestamp_ms: now_millis(),
             data: ProvEventData::ToolCall {
                 tool_name,
@@ -161,6 +212,8 @@ impl ProvEvent {
                 metadata,
                 duration_ms: None,
                 success: None,
+                output: None,
+                error: None,
             },
         }
     }
@@ -180,6 +233,8 @@ impl ProvEvent {
             event_type: ProvEventType::ToolCallCompleted,
             context_id,
             task_id,
+            correlation_id: correlation::current_correlation_id(),
+            tenant_id: tenant::current_tenant_id(),
             timestamp_ms: now_millis(),
             data: ProvEventData::ToolCall {
                 tool_name,
@@ -188,6 +243,77 @@ impl ProvEvent {
                 metadata,
                 duration_ms: Some(duration_ms),
                 success: Some(success),
+                output: None,
+                error: None,
+            },
+        }
+    }
+
+    pub fn llm_stream_chunk(
+        context_id: ContextId,
+        task_id: Option<TaskId>,
+        client: String,
+        function_name: String,
+        index: usize,
+        chunk: Value,
+    ) -> Self {
+        Self {
+            id: next_event_id(),
+            event_type: ProvEventType::LlmStreamChunk,
+            context_id,
+            task_id,
+            correlation_id: correlation::current_correlation_id(),
+            tenant_id: tenant::current_tenant_id(),
+            timestamp_ms: now_millis(),
+            data: ProvEventData::LlmStreamChunk {
+                client,
+                function_name,
+                index,
+                chunk,
+            },
+        }
+    }
+
+    /// This completion event with the call's result, or its error
+    ///
+    /// Events of other kinds are returned unchanged.
+    pub fn with_outcome(mut self, outcome: std::result::Result<Value, String>) -> Self {
+        if let ProvEventData::LlmCall { output, error, .. }
+        | ProvEventData::ToolCall { output, error, .. } = &mut self.data
+        {
+            match outcome {
+                Ok(value) => *output = Some(value),
+                Err(message) => *error = Some(message),
+            }
+        }
+        self
+    }
+
+    pub fn tool_approval_decided(
+        context_id: ContextId,
+        task_id: Option<TaskId>,
+        approval_id: String,
+        tool_name: String,
+        function_name: Option<String>,
+        args: Value,
+        approved: bool,
+        decided_by: String,
+    ) -> Self {
+        Self {
+            id: next_event_id(),
+            event_type: ProvEventType::ToolApprovalDecided,
+            context_id,
+            task_id,
+            correlation_id: correlation::current_correlation_id(),
+            tenant_id: tenant::current_tenant_id(),
+            timestamp_ms: now_millis(),
+            data: ProvEventData::ToolApproval {
+                approval_id,
+                tool_name,
+                function_name,
+                args,
+                approved,
+                decided_by,
             },
         }
     }
@@ -202,6 +328,8 @@ impl ProvEvent {
             event_type: ProvEventType::TaskCreated,
             context_id,
             task_id: Some(task_id.clone()),
+            correlation_id: correlation::current_correlation_id(),
+            tenant_id: tenant::current_tenant_id(),
             timestamp_ms: now_millis(),
             data: ProvEventData::TaskCreated {
                 task_id,
@@ -221,6 +349,8 @@ impl ProvEvent {
             event_type: ProvEventType::TaskStatusChanged,
             context_id,
             task_id: Some(task_id.clone()),
+            correlation_id: correlation::current_correlation_id(),
+            tenant_id: tenant::current_tenant_id(),
             timestamp_ms: now_millis(),
             data: ProvEventData::TaskStatusChanged {
                 task_id,
@@ -241,6 +371,8 @@ impl ProvEvent {
             event_type: ProvEventType::TaskArtifactGenerated,
             context_id,
             task_id: Some(task_id.clone()),
+            correlation_id: correlation::current_correlation_id(),
+            tenant_id: tenant::current_tenant_id(),
             timestamp_ms: now_millis(),
             data: ProvEventData::TaskArtifactGenerated {
                 task_id,
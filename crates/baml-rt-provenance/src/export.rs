@@ -0,0 +1,723 @@
+//! Export of captured provenance in standard formats
+//!
+//! Events recorded while serving one request share its correlation ID.
+//! [`prov_document`] assembles them into a [`ProvDocument`], which
+//! [`ProvDocument::to_prov_json`] renders as W3C PROV-JSON: the request and
+//! each LLM and tool call are activities, the clients and tools that ran them
+//! are agents, and prompts, tool arguments, messages, task states, and
+//! artifacts are entities. [`openlineage_run_events`] describes the same
+//! request as the `START` and `COMPLETE` (or `FAIL`) events of an OpenLineage
+//! run, for lineage systems rather than audit stores.
+
+use crate::builders::{ActivityBuilder, ProvDocumentBuilder};
+use crate::document::ProvDocument;
+use crate::events::{ProvEvent, ProvEventData, ProvEventType};
+use baml_rt_core::ids::CorrelationId;
+use serde_json::{Map, Value, json};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+
+/// Namespaces of the qualified names in exported documents
+const PREFIXES: [(&str, &str); 3] = [
+    ("prov", "http://www.w3.org/ns/prov#"),
+    ("baml", "urn:baml-rt:"),
+    ("a2a", "urn:a2a:"),
+];
+
+/// Identifier of the runtime agent every request is associated with
+const RUNTIME_AGENT: &str = "baml:runtime";
+
+/// Schema of the OpenLineage run events produced
+pub const OPENLINEAGE_SCHEMA_URL: &str =
+    "https://openlineage.io/spec/2-0-2/OpenLineage.json#/$defs/RunEvent";
+
+/// Schema of the `bamlRuntime` run facet
+const RUN_FACET_SCHEMA_URL: &str =
+    "https://openlineage.io/spec/2-0-2/OpenLineage.json#/$defs/RunFacet";
+
+/// The job OpenLineage run events are reported for
+#[derive(Debug, Clone)]
+pub struct OpenLineageJob {
+    pub namespace: String,
+    pub name: String,
+    /// URI of the software emitting the events
+    pub producer: String,
+}
+
+impl OpenLineageJob {
+    pub fn new(namespace: impl Into<String>, name: impl Into<String>) -> Self {
+        Self {
+            namespace: namespace.into(),
+            name: name.into(),
+            producer: format!(
+                "https://github.com/suchapalaver/baml-ts-sandbox/tree/v{}",
+                env!("CARGO_PKG_VERSION")
+            ),
+        }
+    }
+
+    pub fn with_producer(mut self, producer: impl Into<String>) -> Self {
+        self.producer = producer.into();
+        self
+    }
+}
+
+/// The PROV document of the request with `correlation_id`
+///
+/// Events of other requests are ignored, so `events` can be everything a
+/// store holds. A completed LLM or tool call is matched to the earliest
+/// unmatched start of the same call.
+pub fn prov_document(correlation_id: &CorrelationId, events: &[ProvEvent]) -> ProvDocument {
+    let events = correlated(correlation_id, events);
+    let request = format!("baml:request/{}", correlation_id);
+    let (first, last) = time_span(&events);
+
+    let mut builder = ProvDocumentBuilder::new()
+        .agent(RUNTIME_AGENT, |agent| {
+            agent
+                .type_("prov:SoftwareAgent")
+                .attr("baml:name", "baml-rt")
+                .build()
+        })
+        .activity(&request, |activity| {
+            activity
+                .type_("baml:Request")
+                .start_time_ms(first)
+                .end_time_ms(last)
+                .attr("baml:correlationId", correlation_id.as_str())
+                .build()
+        })
+        .was_associated_with(&request, RUNTIME_AGENT)
+        .build();
+
+    for call in pair_calls(&events) {
+        builder = add_call(builder, &request, &call);
+    }
+
+    for event in &events {
+        let id = event.id.as_str();
+        builder = match &event.data {
+            ProvEventData::TaskCreated {
+                task_id,
+                agent_type,
+            } => {
+                let entity = format!("a2a:task/{}", task_id);
+                let builder = builder.entity(&entity, |entity| {
+                    let entity = entity
+                        .type_("a2a:Task")
+                        .attr("a2a:contextId", event.context_id.as_str());
+                    let entity = match agent_type {
+                        Some(agent_type) => entity.attr("a2a:agentType", agent_type.as_str()),
+                        None => entity,
+                    };
+                    entity.build()
+                });
+                builder
+                    .was_generated_by(&entity, &request)
+                    .time_ms(event.timestamp_ms)
+                    .build()
+            }
+            ProvEventData::TaskStatusChanged {
+                task_id,
+                old_status,
+                new_status,
+            } => {
+                let entity = format!("a2a:taskStatus/{}", id);
+                let builder = builder.entity(&entity, |entity| {
+                    let mut entity = entity
+                        .type_("a2a:TaskStatus")
+                        .attr("a2a:task", format!("a2a:task/{}", task_id));
+                    if let Some(old_status) = old_status {
+                        entity = entity.attr("a2a:oldStatus", old_status.as_str());
+                    }
+                    if let Some(new_status) = new_status {
+                        entity = entity.attr("a2a:status", new_status.as_str());
+                    }
+                    entity.build()
+                });
+                builder
+                    .was_generated_by(&entity, &request)
+                    .time_ms(event.timestamp_ms)
+                    .build()
+            }
+            ProvEventData::TaskArtifactGenerated {
+                task_id,
+                artifact_id,
+                artifact_type,
+            } => {
+                let entity = match artifact_id {
+                    Some(artifact_id) => format!("a2a:artifact/{}", artifact_id),
+                    None => format!("a2a:artifact/{}", id),
+                };
+                let builder = builder.entity(&entity, |entity| {
+                    let entity = entity
+                        .type_("a2a:Artifact")
+                        .attr("a2a:task", format!("a2a:task/{}", task_id));
+                    let entity = match artifact_type {
+                        Some(artifact_type) => {
+                            entity.attr("a2a:artifactType", artifact_type.as_str())
+                        }
+                        None => entity,
+                    };
+                    entity.build()
+                });
+                builder
+                    .was_generated_by(&entity, &request)
+                    .time_ms(event.timestamp_ms)
+                    .build()
+            }
+            ProvEventData::Message {
+                id: message_id,
+                role,
+                content,
+                ..
+            } => {
+                let entity = format!("a2a:message/{}", message_id);
+                let builder = builder.entity(&entity, |entity| {
+                    entity
+                        .type_("a2a:Message")
+                        .attr("a2a:role", role.as_str())
+                        .attr("prov:value", content.join("\n"))
+                        .build()
+                });
+                if event.event_type == ProvEventType::MessageSent {
+                    builder
+                        .was_generated_by(&entity, &request)
+                        .time_ms(event.timestamp_ms)
+                        .build()
+                } else {
+                    builder.used(&request, &entity).role("a2a:input").build()
+                }
+            }
+            ProvEventData::ToolApproval {
+                approval_id,
+                tool_name,
+                approved,
+                decided_by,
+                ..
+            } => {
+                let entity = format!("a2a:approval/{}/{}", approval_id, id);
+                let builder = builder.entity(&entity, |entity| {
+                    entity
+                        .type_("a2a:ToolApproval")
+                        .attr("a2a:tool", tool_name.as_str())
+                        .attr("a2a:approved", *approved)
+                        .attr("a2a:decidedBy", decided_by.as_str())
+                        .build()
+                });
+                builder
+                    .was_generated_by(&entity, &request)
+                    .time_ms(event.timestamp_ms)
+                    .build()
+            }
+            ProvEventData::LlmCall { .. }
+            | ProvEventData::LlmStreamChunk { .. }
+            | ProvEventData::ToolCall { .. } => builder,
+        };
+    }
+
+    builder.build()
+}
+
+impl ProvDocument {
+    /// The document as W3C PROV-JSON
+    ///
+    /// Times become `xsd:dateTime` strings, relation IDs become blank nodes,
+    /// and structured attribute values are written as JSON strings, since
+    /// PROV-JSON attributes hold literals.
+    pub fn to_prov_json(&self) -> Value {
+        let prefix: Map<String, Value> = PREFIXES
+            .iter()
+            .map(|(name, uri)| (name.to_string(), json!(uri)))
+            .collect();
+
+        let mut document = Map::new();
+        document.insert("prefix".to_string(), Value::Object(prefix));
+        insert_section(&mut document, "entity", &self.entity, |entity| {
+            let mut record = literal_attributes(&entity.attributes);
+            if let Some(prov_type) = &entity.prov_type {
+                record.insert("prov:type".to_string(), json!(prov_type));
+            }
+            record
+        });
+        insert_section(&mut document, "activity", &self.activity, |activity| {
+            let mut record = literal_attributes(&activity.attributes);
+            if let Some(prov_type) = &activity.prov_type {
+                record.insert("prov:type".to_string(), json!(prov_type));
+            }
+            if let Some(start) = activity.start_time_ms {
+                record.insert("prov:startTime".to_string(), json!(date_time(start)));
+            }
+            if let Some(end) = activity.end_time_ms {
+                record.insert("prov:endTime".to_string(), json!(date_time(end)));
+            }
+            record
+        });
+        insert_section(&mut document, "agent", &self.agent, |agent| {
+            let mut record = literal_attributes(&agent.attributes);
+            if let Some(prov_type) = &agent.prov_type {
+                record.insert("prov:type".to_string(), json!(prov_type));
+            }
+            record
+        });
+        insert_blank_section(&mut document, "used", &self.used, |used| {
+            let mut record = Map::new();
+            record.insert("prov:activity".to_string(), json!(used.activity));
+            record.insert("prov:entity".to_string(), json!(used.entity));
+            if let Some(role) = &used.role {
+                record.insert("prov:role".to_string(), json!(role));
+            }
+            record
+        });
+        insert_blank_section(
+            &mut document,
+            "wasGeneratedBy",
+            &self.was_generated_by,
+            |generation| {
+                let mut record = Map::new();
+                record.insert("prov:entity".to_string(), json!(generation.entity));
+                record.insert("prov:activity".to_string(), json!(generation.activity));
+                if let Some(time) = generation.time_ms {
+                    record.insert("prov:time".to_string(), json!(date_time(time)));
+                }
+                record
+            },
+        );
+        insert_blank_section(
+            &mut document,
+            "wasAssociatedWith",
+            &self.was_associated_with,
+            |association| {
+                let mut record = Map::new();
+                record.insert("prov:activity".to_string(), json!(association.activity));
+                record.insert("prov:agent".to_string(), json!(association.agent));
+                if let Some(role) = &association.role {
+                    record.insert("prov:role".to_string(), json!(role));
+                }
+                record
+            },
+        );
+        Value::Object(document)
+    }
+}
+
+/// The OpenLineage run events of the request with `correlation_id`
+///
+/// The run starts at the request's first event and completes at its last;
+/// it fails instead when an LLM or tool call failed or a task ended failed.
+/// Messages the request received are its inputs, and messages it sent and
+/// artifacts it generated are its outputs. Empty when no event belongs to
+/// the request.
+pub fn openlineage_run_events(
+    correlation_id: &CorrelationId,
+    events: &[ProvEvent],
+    job: &OpenLineageJob,
+) -> Vec<Value> {
+    let events = correlated(correlation_id, events);
+    if events.is_empty() {
+        return Vec::new();
+    }
+    let (first, last) = time_span(&events);
+    let calls = pair_calls(&events);
+    let failed = calls.iter().any(|call| call.success == Some(false))
+        || events.iter().any(|event| {
+            matches!(
+                &event.data,
+                ProvEventData::TaskStatusChanged { new_status: Some(status), .. }
+                    if status.to_ascii_uppercase().contains("FAILED")
+            )
+        });
+
+    let dataset = |name: String| json!({ "namespace": job.namespace, "name": name });
+    let mut inputs = Vec::new();
+    let mut outputs = Vec::new();
+    for event in &events {
+        match &event.data {
+            ProvEventData::Message { id, .. } if event.event_type == ProvEventType::MessageSent => {
+                outputs.push(dataset(format!("message/{}", id)));
+            }
+            ProvEventData::Message { id, .. } => inputs.push(dataset(format!("message/{}", id))),
+            ProvEventData::TaskArtifactGenerated {
+                artifact_id: Some(artifact_id),
+                ..
+            } => outputs.push(dataset(format!("artifact/{}", artifact_id))),
+            _ => {}
+        }
+    }
+
+    let run = json!({
+        "runId": run_id(correlation_id),
+        "facets": {
+            "bamlRuntime": {
+                "_producer": job.producer,
+                "_schemaURL": RUN_FACET_SCHEMA_URL,
+                "correlationId": correlation_id.as_str(),
+                "contextId": events[0].context_id.as_str(),
+                "llmCalls": calls.iter().filter(|call| call.kind == CallKind::Llm).count(),
+                "toolCalls": calls.iter().filter(|call| call.kind == CallKind::Tool).count(),
+            }
+        }
+    });
+    let job_value = json!({ "namespace": job.namespace, "name": job.name });
+    let run_event = |event_type: &str, time_ms: u64, inputs: &[Value], outputs: &[Value]| {
+        json!({
+            "eventType": event_type,
+            "eventTime": date_time(time_ms),
+            "run": run,
+            "job": job_value,
+            "inputs": inputs,
+            "outputs": outputs,
+            "producer": job.producer,
+            "schemaURL": OPENLINEAGE_SCHEMA_URL,
+        })
+    };
+
+    vec![
+        run_event("START", first, &inputs, &[]),
+        run_event(
+            if failed { "FAIL" } else { "COMPLETE" },
+            last,
+            &inputs,
+            &outputs,
+        ),
+    ]
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum CallKind {
+    Llm,
+    Tool,
+}
+
+/// An LLM or tool call, from its start event to its completion if recorded
+pub(crate) struct Call<'a> {
+    pub(crate) kind: CallKind,
+    pub(crate) start: &'a ProvEvent,
+    pub(crate) completion: Option<&'a ProvEvent>,
+    pub(crate) end_time_ms: Option<u64>,
+    pub(crate) success: Option<bool>,
+    pub(crate) duration_ms: Option<u64>,
+}
+
+/// Events of the request with `correlation_id`, in the order they happened
+pub(crate) fn correlated<'a>(
+    correlation_id: &CorrelationId,
+    events: &'a [ProvEvent],
+) -> Vec<&'a ProvEvent> {
+    let mut events: Vec<&ProvEvent> = events
+        .iter()
+        .filter(|event| event.correlation_id.as_ref() == Some(correlation_id))
+        .collect();
+    events.sort_by_key(|event| (event.timestamp_ms, sequence(event)));
+    events
+}
+
+/// Recording order of `event`, which a millisecond timestamp cannot settle
+/// and its ID only gives as a string
+fn sequence(event: &ProvEvent) -> u64 {
+    event
+        .id
+        .as_str()
+        .rsplit('-')
+        .next()
+        .and_then(|n| n.parse().ok())
+        .unwrap_or(0)
+}
+
+pub(crate) fn time_span(events: &[&ProvEvent]) -> (u64, u64) {
+    let first = events.first().map_or(0, |event| event.timestamp_ms);
+    let last = events.last().map_or(first, |event| event.timestamp_ms);
+    (first, last)
+}
+
+/// What identifies a call across its start and completion events
+fn call_key(event: &ProvEvent) -> Option<(CallKind, String)> {
+    match &event.data {
+        ProvEventData::LlmCall {
+            client,
+            function_name,
+            ..
+        } => Some((CallKind::Llm, format!("{}\0{}", client, function_name))),
+        ProvEventData::ToolCall { tool_name, .. } => Some((CallKind::Tool, tool_name.clone())),
+        _ => None,
+    }
+}
+
+pub(crate) fn pair_calls<'a>(events: &[&'a ProvEvent]) -> Vec<Call<'a>> {
+    let mut calls: Vec<Call<'a>> = Vec::new();
+    // Indexes of started calls not yet completed, by call key
+    let mut open: HashMap<(CallKind, String), Vec<usize>> = HashMap::new();
+    for event in events {
+        let Some(key) = call_key(event) else {
+            continue;
+        };
+        let (duration_ms, success) = match &event.data {
+            ProvEventData::LlmCall {
+                duration_ms,
+                success,
+                ..
+            }
+            | ProvEventData::ToolCall {
+                duration_ms,
+                success,
+                ..
+            } => (*duration_ms, *success),
+            _ => (None, None),
+        };
+        let completed = matches!(
+            event.event_type,
+            ProvEventType::LlmCallCompleted | ProvEventType::ToolCallCompleted
+        );
+        let started = open.entry(key.clone()).or_default();
+        if completed && !started.is_empty() {
+            let call = &mut calls[started.remove(0)];
+            call.completion = Some(*event);
+            call.end_time_ms = Some(event.timestamp_ms);
+            call.success = success;
+            call.duration_ms = duration_ms;
+            continue;
+        }
+        // A completion without a recorded start stands for the whole call
+        if !completed {
+            started.push(calls.len());
+        }
+        calls.push(Call {
+            kind: key.0,
+            start: event,
+            completion: completed.then_some(*event),
+            end_time_ms: completed.then_some(event.timestamp_ms),
+            success,
+            duration_ms,
+        });
+    }
+    calls
+}
+
+fn add_call(builder: ProvDocumentBuilder, request: &str, call: &Call<'_>) -> ProvDocumentBuilder {
+    let event = call.start;
+    let id = event.id.as_str();
+    let start_time_ms = match call.duration_ms {
+        Some(duration_ms) if call.end_time_ms == Some(event.timestamp_ms) => {
+            event.timestamp_ms.saturating_sub(duration_ms)
+        }
+        _ => event.timestamp_ms,
+    };
+    let (activity, agent, input, input_role, builder) = match &event.data {
+        ProvEventData::LlmCall {
+            client,
+            model,
+            function_name,
+            prompt,
+            ..
+        } => {
+            let activity = format!("baml:llmCall/{}", id);
+            let agent = format!("baml:client/{}", client);
+            let input = format!("baml:prompt/{}", id);
+            let builder = builder
+                .activity(&activity, |activity| {
+                    timed(activity.start_time_ms(start_time_ms), call)
+                        .type_("baml:LlmCall")
+                        .attr("baml:function", function_name.as_str())
+                        .attr("baml:model", model.as_str())
+                        .build()
+                })
+                .agent(&agent, |agent| {
+                    agent
+                        .type_("prov:SoftwareAgent")
+                        .attr("baml:client", client.as_str())
+                        .build()
+                })
+                .entity(&input, |entity| {
+                    entity
+                        .type_("baml:Prompt")
+                        .attr("prov:value", prompt.clone())
+                        .build()
+                });
+            (activity, agent, input, "baml:prompt", builder)
+        }
+        ProvEventData::ToolCall {
+            tool_name,
+            function_name,
+            args,
+            ..
+        } => {
+            let activity = format!("baml:toolCall/{}", id);
+            let agent = format!("baml:tool/{}", tool_name);
+            let input = format!("baml:toolArgs/{}", id);
+            let builder = builder
+                .activity(&activity, |activity| {
+                    let activity = timed(activity.start_time_ms(start_time_ms), call)
+                        .type_("baml:ToolCall")
+                        .attr("baml:tool", tool_name.as_str());
+                    let activity = match function_name {
+                        Some(function_name) => {
+                            activity.attr("baml:function", function_name.as_str())
+                        }
+                        None => activity,
+                    };
+                    activity.build()
+                })
+                .agent(&agent, |agent| {
+                    agent
+                        .type_("prov:SoftwareAgent")
+                        .attr("baml:tool", tool_name.as_str())
+                        .build()
+                })
+                .entity(&input, |entity| {
+                    entity
+                        .type_("baml:ToolArguments")
+                        .attr("prov:value", args.clone())
+                        .build()
+                });
+            (activity, agent, input, "baml:arguments", builder)
+        }
+        _ => return builder,
+    };
+    builder
+        .was_associated_with(&activity, &agent)
+        .build()
+        .was_associated_with(&activity, RUNTIME_AGENT)
+        .role("baml:request")
+        .build()
+        .used(&activity, &input)
+        .role(input_role)
+        .build()
+        .used(&activity, request)
+        .role("baml:partOf")
+        .build()
+}
+
+/// `activity` with the end time, outcome, and duration of `call`
+fn timed(mut activity: ActivityBuilder, call: &Call<'_>) -> ActivityBuilder {
+    if let Some(end_time_ms) = call.end_time_ms {
+        activity = activity.end_time_ms(end_time_ms);
+    }
+    if let Some(success) = call.success {
+        activity = activity.attr("baml:success", success);
+    }
+    if let Some(duration_ms) = call.duration_ms {
+        activity = activity.attr("baml:durationMs", duration_ms);
+    }
+    activity
+}
+
+fn insert_section<T>(
+    document: &mut Map<String, Value>,
+    name: &str,
+    records: &HashMap<String, T>,
+    render: impl Fn(&T) -> Map<String, Value>,
+) {
+    if records.is_empty() {
+        return;
+    }
+    let section: Map<String, Value> = records
+        .iter()
+        .map(|(id, record)| (id.clone(), Value::Object(render(record))))
+        .collect();
+    document.insert(name.to_string(), Value::Object(section));
+}
+
+/// Like [`insert_section`], for relations identified by blank nodes
+fn insert_blank_section<T>(
+    document: &mut Map<String, Value>,
+    name: &str,
+    records: &HashMap<String, T>,
+    render: impl Fn(&T) -> Map<String, Value>,
+) {
+    if records.is_empty() {
+        return;
+    }
+    let section: Map<String, Value> = records
+        .iter()
+        .map(|(id, record)| (format!("_:{}", id), Value::Object(render(record))))
+        .collect();
+    document.insert(name.to_string(), Value::Object(section));
+}
+
+/// Attributes with structured values written as JSON strings
+fn literal_attributes(attributes: &HashMap<String, Value>) -> Map<String, Value> {
+    attributes
+        .iter()
+        .map(|(key, value)| {
+            let value = match value {
+                Value::Object(_) | Value::Array(_) => Value::String(value.to_string()),
+                literal => literal.clone(),
+            };
+            (key.clone(), value)
+        })
+        .collect()
+}
+
+/// `ms` since the Unix epoch as an `xsd:dateTime` in UTC
+pub(crate) fn date_time(ms: u64) -> String {
+    let seconds = ms / 1000;
+    let days = (seconds / 86_400) as i64;
+    let time = seconds % 86_400;
+
+    // Civil date from days since 1970-01-01 (Howard Hinnant's algorithm)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+        year,
+        month,
+        day,
+        time / 3600,
+        time % 3600 / 60,
+        time % 60,
+        ms % 1000
+    )
+}
+
+/// A name-based UUID for the run of `correlation_id`, stable across exports
+fn run_id(correlation_id: &CorrelationId) -> String {
+    let digest = Sha256::digest(correlation_id.as_str().as_bytes());
+    let mut bytes = [0u8; 16];
+    bytes.copy_from_slice(&digest[..16]);
+    // Version 5 layout with the RFC 4122 variant
+    bytes[6] = (bytes[6] & 0x0f) | 0x50;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+    let hex: String = bytes.iter().map(|byte| format!("{:02x}", byte)).collect();
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..]
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn times_render_as_utc_date_times() {
+        assert_eq!(date_time(0), "1970-01-01T00:00:00.000Z");
+        assert_eq!(date_time(951_782_400_000), "2000-02-29T00:00:00.000Z");
+        assert_eq!(date_time(1_792_149_054_123), "2026-10-16T11:10:54.123Z");
+    }
+
+    #[test]
+    fn run_ids_are_stable_uuids() {
+        let id = run_id(&CorrelationId::from("corr-1"));
+        assert_eq!(id, run_id(&CorrelationId::from("corr-1")));
+        assert_ne!(id, run_id(&CorrelationId::from("corr-2")));
+        assert_eq!(id.len(), 36);
+        assert_eq!(&id[14..15], "5");
+    }
+}
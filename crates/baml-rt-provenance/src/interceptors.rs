@@ -1,4 +1,5 @@
 use crate::events::ProvEvent;
+use crate::redaction::RedactionPolicy;
 use crate::store::ProvenanceWriter;
 use async_trait::async_trait;
 use baml_rt_core::Result;
@@ -10,11 +11,30 @@ use std::sync::Arc;
 
 pub struct ProvenanceInterceptor {
     writer: Arc<dyn ProvenanceWriter>,
+    redaction: RedactionPolicy,
 }
 
 impl ProvenanceInterceptor {
     pub fn new(writer: Arc<dyn ProvenanceWriter>) -> Self {
-        Self { writer }
+        Self {
+            writer,
+            redaction: RedactionPolicy::default(),
+        }
+    }
+
+    /// Redact prompts, tool arguments, results, and metadata before they are
+    /// written
+    pub fn with_redaction(mut self, redaction: RedactionPolicy) -> Self {
+        self.redaction = redaction;
+        self
+    }
+
+    /// A call's result as recorded: redacted output, or the error message
+    fn outcome(&self, result: &Result<Value>) -> std::result::Result<Value, String> {
+        match result {
+            Ok(value) => Ok(self.redaction.apply(value)),
+            Err(e) => Err(e.to_string()),
+        }
     }
 }
 
@@ -27,8 +47,8 @@ impl LLMInterceptor for ProvenanceInterceptor {
             context.client.clone(),
             context.model.clone(),
             context.function_name.clone(),
-            context.prompt.clone(),
-            context.metadata.clone(),
+            self.redaction.apply(&context.prompt),
+            self.redaction.apply(&context.metadata),
         );
         self.writer
             .add_event_with_logging(event, "LLM call start")
@@ -49,15 +69,36 @@ impl LLMInterceptor for ProvenanceInterceptor {
             context.client.clone(),
             context.model.clone(),
             context.function_name.clone(),
-            context.prompt.clone(),
-            context.metadata.clone(),
+            self.redaction.apply(&context.prompt),
+            self.redaction.apply(&context.metadata),
             duration_ms,
             success,
-        );
+        )
+        .with_outcome(self.outcome(result));
         self.writer
             .add_event_with_logging(event, "LLM call completion")
             .await;
     }
+
+    async fn on_llm_stream_chunk(
+        &self,
+        context: &LLMCallContext,
+        chunk: &Value,
+        index: usize,
+    ) -> Result<InterceptorDecision> {
+        let event = ProvEvent::llm_stream_chunk(
+            context.context_id.clone(),
+            None,
+            context.client.clone(),
+            context.function_name.clone(),
+            index,
+            self.redaction.apply(chunk),
+        );
+        self.writer
+            .add_event_with_logging(event, "LLM stream chunk")
+            .await;
+        Ok(InterceptorDecision::Allow)
+    }
 }
 
 #[async_trait]
@@ -68,8 +109,8 @@ impl ToolInterceptor for ProvenanceInterceptor {
             None,
             context.tool_name.clone(),
             context.function_name.clone(),
-            context.args.clone(),
-            context.metadata.clone(),
+            self.redaction.apply(&context.args),
+            self.redaction.apply(&context.metadata),
         );
         self.writer
             .add_event_with_logging(event, "tool call start")
@@ -89,11 +130,12 @@ impl ToolInterceptor for ProvenanceInterceptor {
             None,
             context.tool_name.clone(),
             context.function_name.clone(),
-            context.args.clone(),
-            context.metadata.clone(),
+            self.redaction.apply(&context.args),
+            self.redaction.apply(&context.metadata),
             duration_ms,
             success,
-        );
+        )
+        .with_outcome(self.outcome(result));
         self.writer
             .add_event_with_logging(event, "tool call completion")
             .await;
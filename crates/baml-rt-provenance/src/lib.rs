@@ -1,17 +1,26 @@
 //! Provenance capture and storage.
 //!
 //! This crate provides event types and interceptors for provenance recording,
-//! along with a pluggable storage interface and an in-memory implementation.
+//! along with a pluggable storage interface, an in-memory implementation, and
+//! exporters to W3C PROV-JSON, OpenLineage, and HTML timelines.
 
 pub mod builders;
 pub mod document;
 pub mod error;
 pub mod events;
+pub mod export;
 pub mod interceptors;
+pub mod redaction;
 pub mod store;
+pub mod timeline;
 pub mod types;
 
 pub use error::ProvenanceError;
 pub use events::{ProvEvent, ProvEventData, ProvEventType};
+pub use export::{OpenLineageJob, openlineage_run_events, prov_document};
 pub use interceptors::ProvenanceInterceptor;
-pub use store::{InMemoryProvenanceStore, ProvenanceWriter};
+pub use redaction::{RedactionMode, RedactionPolicy};
+pub use store::{
+    InMemoryProvenanceStore, ProvenanceReader, ProvenanceSubscriber, ProvenanceWriter,
+};
+pub use timeline::timeline_html;
@@ -0,0 +1,212 @@
+//! Redaction of captured payloads
+//!
+//! Prompts, tool arguments, call results, and metadata can carry API keys,
+//! tokens, or personal data. A [`RedactionPolicy`] on the [`ProvenanceInterceptor`]
+//! rewrites them before any event reaches a writer, so nothing a store or
+//! subscriber sees depends on which store is configured.
+//!
+//! [`ProvenanceInterceptor`]: crate::interceptors::ProvenanceInterceptor
+
+use serde_json::{Map, Value, json};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeSet;
+
+/// What a redacted value is replaced with
+pub const REDACTED: &str = "[REDACTED]";
+
+/// Field names redacted by [`RedactionPolicy::secrets`]
+pub const SECRET_FIELDS: [&str; 9] = [
+    "api_key",
+    "apikey",
+    "authorization",
+    "cookie",
+    "password",
+    "private_key",
+    "secret",
+    "token",
+    "x-api-key",
+];
+
+/// How redacted values are written
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RedactionMode {
+    /// Replace the value with [`REDACTED`]
+    #[default]
+    Mask,
+    /// Replace the value with `sha256:<hex>` of its JSON, so equal secrets
+    /// can still be matched across events without being revealed
+    Hash,
+}
+
+/// Rules for rewriting payloads before they are recorded
+///
+/// Field names are matched case-insensitively, at any depth. A denied field
+/// is always redacted; with an allowlist, so is every field not on it.
+/// Payloads whose JSON exceeds the size cap are replaced by a summary of
+/// their size and digest. The default policy records payloads unchanged.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RedactionPolicy {
+    denied_fields: BTreeSet<String>,
+    allowed_fields: Option<BTreeSet<String>>,
+    max_payload_bytes: Option<usize>,
+    mode: RedactionMode,
+}
+
+impl RedactionPolicy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A policy denying [`SECRET_FIELDS`]
+    pub fn secrets() -> Self {
+        Self::new().with_denied_fields(SECRET_FIELDS)
+    }
+
+    /// Redact these fields wherever they appear
+    pub fn with_denied_fields<I, S>(mut self, fields: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.denied_fields.extend(
+            fields
+                .into_iter()
+                .map(|field| field.as_ref().to_lowercase()),
+        );
+        self
+    }
+
+    /// Redact every field but these
+    pub fn with_allowed_fields<I, S>(mut self, fields: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.allowed_fields
+            .get_or_insert_with(BTreeSet::new)
+            .extend(
+                fields
+                    .into_iter()
+                    .map(|field| field.as_ref().to_lowercase()),
+            );
+        self
+    }
+
+    /// Summarize payloads whose JSON is larger than `bytes`
+    pub fn with_max_payload_bytes(mut self, bytes: usize) -> Self {
+        self.max_payload_bytes = Some(bytes);
+        self
+    }
+
+    pub fn with_mode(mut self, mode: RedactionMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Whether the policy leaves every payload as it is
+    pub fn is_passthrough(&self) -> bool {
+        self == &Self::default()
+    }
+
+    /// `payload` with the policy applied
+    pub fn apply(&self, payload: &Value) -> Value {
+        if self.is_passthrough() {
+            return payload.clone();
+        }
+        let redacted = self.redact_fields(payload);
+        match self.max_payload_bytes {
+            Some(max) => {
+                let json = redacted.to_string();
+                if json.len() <= max {
+                    return redacted;
+                }
+                json!({
+                    "truncated": true,
+                    "bytes": json.len(),
+                    "digest": digest(&json),
+                })
+            }
+            None => redacted,
+        }
+    }
+
+    fn redact_fields(&self, value: &Value) -> Value {
+        match value {
+            Value::Object(fields) => {
+                let fields: Map<String, Value> = fields
+                    .iter()
+                    .map(|(name, value)| {
+                        let value = if self.is_redacted(name) {
+                            self.redacted(value)
+                        } else {
+                            self.redact_fields(value)
+                        };
+                        (name.clone(), value)
+                    })
+                    .collect();
+                Value::Object(fields)
+            }
+            Value::Array(items) => {
+                Value::Array(items.iter().map(|item| self.redact_fields(item)).collect())
+            }
+            other => other.clone(),
+        }
+    }
+
+    fn is_redacted(&self, field: &str) -> bool {
+        let field = field.to_lowercase();
+        self.denied_fields.contains(&field)
+            || self
+                .allowed_fields
+                .as_ref()
+                .is_some_and(|allowed| !allowed.contains(&field))
+    }
+
+    fn redacted(&self, value: &Value) -> Value {
+        match self.mode {
+            RedactionMode::Mask => Value::String(REDACTED.to_string()),
+            RedactionMode::Hash => Value::String(digest(&value.to_string())),
+        }
+    }
+}
+
+fn digest(json: &str) -> String {
+    format!("sha256:{:x}", Sha256::digest(json.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn denied_fields_are_redacted_at_any_depth() {
+        let policy = RedactionPolicy::secrets();
+        let payload = json!({
+            "query": "weather",
+            "headers": [{ "Authorization": "Bearer abc", "accept": "json" }],
+        });
+        assert_eq!(
+            policy.apply(&payload),
+            json!({
+                "query": "weather",
+                "headers": [{ "Authorization": REDACTED, "accept": "json" }],
+            })
+        );
+    }
+
+    #[test]
+    fn allowlists_hashing_and_size_caps_combine() {
+        let policy = RedactionPolicy::new()
+            .with_allowed_fields(["city"])
+            .with_mode(RedactionMode::Hash);
+        let redacted = policy.apply(&json!({ "city": "Oslo", "ssn": "123" }));
+        assert_eq!(redacted["city"], "Oslo");
+        assert_eq!(redacted["ssn"], digest("\"123\""));
+
+        let capped = RedactionPolicy::new().with_max_payload_bytes(8);
+        let summary = capped.apply(&json!({ "text": "a long prompt" }));
+        assert_eq!(summary["truncated"], true);
+        assert_eq!(capped.apply(&json!(1)), json!(1));
+        assert!(RedactionPolicy::default().is_passthrough());
+    }
+}
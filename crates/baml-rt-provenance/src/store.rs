@@ -1,7 +1,11 @@
 use crate::error::Result;
 use crate::events::ProvEvent;
 use async_trait::async_trait;
-use tokio::sync::RwLock;
+use baml_rt_core::ids::{CorrelationId, TenantId};
+use tokio::sync::{RwLock, broadcast};
+
+/// Events a live subscriber may fall behind by before it misses some
+pub const SUBSCRIPTION_CAPACITY: usize = 1024;
 
 #[async_trait]
 pub trait ProvenanceWriter: Send + Sync {
@@ -21,14 +25,49 @@ pub trait ProvenanceWriter: Send + Sync {
     }
 }
 
+/// Read access to recorded provenance, e.g. for diagnostics
+#[async_trait]
+pub trait ProvenanceReader: Send + Sync {
+    /// The latest `limit` events, oldest first.
+    async fn recent_events(&self, limit: usize) -> Result<Vec<ProvEvent>>;
+
+    /// The events recorded for the request with `correlation_id`, oldest first.
+    async fn correlated_events(&self, correlation_id: &CorrelationId) -> Result<Vec<ProvEvent>> {
+        let mut events = self.recent_events(usize::MAX).await?;
+        events.retain(|event| event.correlation_id.as_ref() == Some(correlation_id));
+        Ok(events)
+    }
+
+    /// The latest `limit` events recorded for `tenant_id`, oldest first.
+    async fn tenant_events(&self, tenant_id: &TenantId, limit: usize) -> Result<Vec<ProvEvent>> {
+        let mut events = self.recent_events(usize::MAX).await?;
+        events.retain(|event| event.tenant_id.as_ref() == Some(tenant_id));
+        let start = events.len().saturating_sub(limit);
+        Ok(events.split_off(start))
+    }
+}
+
+/// Live feed of provenance as it is recorded, e.g. for dashboards
+pub trait ProvenanceSubscriber: Send + Sync {
+    /// Events written from now on, in the order they are written
+    ///
+    /// A receiver that falls more than [`SUBSCRIPTION_CAPACITY`] events
+    /// behind gets [`broadcast::error::RecvError::Lagged`] and resumes with
+    /// the oldest event still buffered.
+    fn subscribe(&self) -> broadcast::Receiver<ProvEvent>;
+}
+
 pub struct InMemoryProvenanceStore {
     events: RwLock<Vec<ProvEvent>>,
+    live: broadcast::Sender<ProvEvent>,
 }
 
 impl InMemoryProvenanceStore {
     pub fn new() -> Self {
+        let (live, _live_rx) = broadcast::channel(SUBSCRIPTION_CAPACITY);
         Self {
             events: RwLock::new(Vec::new()),
+            live,
         }
     }
 
@@ -50,7 +89,24 @@ impl Default for InMemoryProvenanceStore {
 impl ProvenanceWriter for InMemoryProvenanceStore {
     async fn add_event(&self, event: ProvEvent) -> Result<()> {
         let mut events = self.events.write().await;
+        // Sent under the lock so subscribers see events in write order
+        let _ = self.live.send(event.clone());
         events.push(event);
         Ok(())
     }
 }
+
+#[async_trait]
+impl ProvenanceReader for InMemoryProvenanceStore {
+    async fn recent_events(&self, limit: usize) -> Result<Vec<ProvEvent>> {
+        let events = self.events().await;
+        let skip = events.len().saturating_sub(limit);
+        Ok(events.into_iter().skip(skip).collect())
+    }
+}
+
+impl ProvenanceSubscriber for InMemoryProvenanceStore {
+    fn subscribe(&self) -> broadcast::Receiver<ProvEvent> {
+        self.live.subscribe()
+    }
+}
@@ -0,0 +1,505 @@
+//! HTML timelines of a request
+//!
+//! [`timeline_html`] lays out everything recorded for one correlation ID on
+//! a single page: LLM calls with their prompts, responses, and stream chunks,
+//! tool calls with their arguments and results, A2A messages and task
+//! changes, and the tracing spans tagged with the ID. Each entry is a bar
+//! placed by its start time and duration, with its payloads folded beneath
+//! it. The page has no scripts or external resources, so it can be attached
+//! to a bug report and opened anywhere.
+
+use crate::events::{ProvEvent, ProvEventData, ProvEventType};
+use crate::export::{CallKind, correlated, date_time, pair_calls};
+use baml_rt_core::ids::CorrelationId;
+use baml_rt_observability::SpanRecord;
+use serde_json::Value;
+use std::fmt::Write;
+
+const STYLE: &str = r#"
+body { font: 14px/1.4 system-ui, sans-serif; margin: 2rem; color: #1f2328; }
+h1 { font-size: 1.3rem; margin: 0 0 .25rem; }
+.summary { color: #59636e; margin: 0 0 1.5rem; }
+ol.timeline { list-style: none; padding: 0; margin: 0; }
+.entry { border-top: 1px solid #d1d9e0; padding: .35rem 0; }
+.row {
+  display: grid; grid-template-columns: 5.5rem 22rem 1fr 5rem;
+  gap: .75rem; align-items: center;
+}
+.offset, .duration { color: #59636e; font-variant-numeric: tabular-nums; text-align: right; }
+.label { overflow: hidden; text-overflow: ellipsis; white-space: nowrap; }
+.kind {
+  display: inline-block; min-width: 3.5rem;
+  font-size: .75rem; font-weight: 600; text-transform: uppercase;
+}
+.track { position: relative; height: .8rem; background: #f6f8fa; border-radius: 2px; }
+.bar {
+  position: absolute; top: 0; bottom: 0; min-width: 2px;
+  border-radius: 2px; background: #8c959f;
+}
+.llm .bar { background: #8250df; } .tool .bar { background: #1a7f37; }
+.stream .bar { background: #bf8700; } .message .bar { background: #0969da; }
+.task .bar { background: #57606a; } .span .bar { background: #afb8c1; }
+.approval .bar { background: #9a6700; }
+.failed .bar { background: #cf222e; } .failed .label { color: #cf222e; }
+.pending .bar { opacity: .5; }
+details { margin: .25rem 0 0 6.25rem; }
+summary { cursor: pointer; color: #59636e; }
+pre {
+  background: #f6f8fa; padding: .5rem; margin: .25rem 0;
+  overflow-x: auto; white-space: pre-wrap;
+}
+"#;
+
+/// How a timeline entry ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Status {
+    Ok,
+    Failed,
+    /// A call that started without a recorded completion
+    Pending,
+    /// Not a call
+    None,
+}
+
+/// One row of the timeline
+struct Entry {
+    kind: &'static str,
+    start_ms: f64,
+    end_ms: f64,
+    title: String,
+    status: Status,
+    /// Folded payloads, by label
+    sections: Vec<(String, String)>,
+}
+
+/// A self-contained HTML page of the request with `correlation_id`
+///
+/// Events and spans of other requests are ignored, so `events` can be
+/// everything a store holds and `spans` the process's recent spans. Stream
+/// chunks are shown under the LLM call they were received during.
+pub fn timeline_html(
+    correlation_id: &CorrelationId,
+    events: &[ProvEvent],
+    spans: &[SpanRecord],
+) -> String {
+    let events = correlated(correlation_id, events);
+    let mut entries = call_entries(&events);
+    entries.extend(events.iter().copied().filter_map(point_entry));
+    entries.extend(
+        spans
+            .iter()
+            .filter(|span| {
+                span.fields.get("correlation_id").and_then(Value::as_str)
+                    == Some(correlation_id.as_str())
+            })
+            .map(span_entry),
+    );
+    entries.sort_by(|a, b| a.start_ms.total_cmp(&b.start_ms));
+
+    let first = entries
+        .iter()
+        .map(|e| e.start_ms)
+        .fold(f64::INFINITY, f64::min);
+    let last = entries
+        .iter()
+        .map(|e| e.end_ms)
+        .fold(f64::NEG_INFINITY, f64::max);
+    let total = (last - first).max(1.0);
+
+    let count = |kind: &str| entries.iter().filter(|entry| entry.kind == kind).count();
+    let chunks = events
+        .iter()
+        .filter(|event| event.event_type == ProvEventType::LlmStreamChunk)
+        .count();
+    let failures = entries
+        .iter()
+        .filter(|entry| entry.status == Status::Failed)
+        .count();
+
+    let title = escape(correlation_id.as_str());
+    let mut html = String::new();
+    let _ = write!(
+        html,
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>Trace {}</title>\n<style>{}</style>\n</head>\n<body>\n\
+         <h1>Trace <code>{}</code></h1>\n",
+        title, STYLE, title
+    );
+    if entries.is_empty() {
+        html.push_str("<p class=\"summary\">Nothing was recorded for this correlation ID.</p>\n");
+        html.push_str("</body>\n</html>\n");
+        return html;
+    }
+
+    let mut summary = Vec::new();
+    if let Some(event) = events.first() {
+        summary.push(format!(
+            "context <code>{}</code>",
+            escape(event.context_id.as_str())
+        ));
+    }
+    summary.push(format!("started {}", date_time(first as u64)));
+    summary.push(format!("{:.0} ms", last - first));
+    summary.push(format!("{} LLM call(s)", count("llm")));
+    summary.push(format!("{} tool call(s)", count("tool")));
+    summary.push(format!("{} stream chunk(s)", chunks));
+    summary.push(format!("{} failure(s)", failures));
+    let _ = writeln!(html, "<p class=\"summary\">{}</p>", summary.join(" · "));
+
+    html.push_str("<ol class=\"timeline\">\n");
+    for entry in &entries {
+        let status = match entry.status {
+            Status::Ok => " ok",
+            Status::Failed => " failed",
+            Status::Pending => " pending",
+            Status::None => "",
+        };
+        let left = (entry.start_ms - first) / total * 100.0;
+        let width = (entry.end_ms - entry.start_ms) / total * 100.0;
+        let duration = if entry.end_ms > entry.start_ms {
+            format!("{:.0} ms", entry.end_ms - entry.start_ms)
+        } else {
+            String::new()
+        };
+        let _ = write!(
+            html,
+            "<li class=\"entry {}{}\"><div class=\"row\"><span class=\"offset\">+{:.0} ms</span>\
+             <span class=\"label\" title=\"{}\"><span class=\"kind\">{}</span> {}</span>\
+             <span class=\"track\">\
+             <span class=\"bar\" style=\"left:{:.2}%;width:{:.2}%\"></span></span>\
+             <span class=\"duration\">{}</span></div>\n",
+            entry.kind,
+            status,
+            entry.start_ms - first,
+            escape(&entry.title),
+            entry.kind,
+            escape(&entry.title),
+            left,
+            width,
+            duration
+        );
+        for (label, content) in &entry.sections {
+            let _ = writeln!(
+                html,
+                "<details><summary>{}</summary><pre>{}</pre></details>",
+                escape(label),
+                escape(content)
+            );
+        }
+        html.push_str("</li>\n");
+    }
+    html.push_str("</ol>\n</body>\n</html>\n");
+    html
+}
+
+/// Entries of the LLM and tool calls, with each call's stream chunks
+fn call_entries(events: &[&ProvEvent]) -> Vec<Entry> {
+    let calls = pair_calls(events);
+    let mut chunks: Vec<&ProvEvent> = events
+        .iter()
+        .copied()
+        .filter(|event| event.event_type == ProvEventType::LlmStreamChunk)
+        .collect();
+
+    let mut entries = Vec::new();
+    for call in &calls {
+        let start = call.start;
+        let end_ms = call.end_time_ms.unwrap_or(start.timestamp_ms);
+        let start_ms = match call.duration_ms {
+            Some(duration_ms) if call.end_time_ms == Some(start.timestamp_ms) => {
+                start.timestamp_ms.saturating_sub(duration_ms)
+            }
+            _ => start.timestamp_ms,
+        };
+        let status = match call.success {
+            Some(true) => Status::Ok,
+            Some(false) => Status::Failed,
+            None => Status::Pending,
+        };
+        let completion = call.completion.map(|event| &event.data);
+        let (output, error) = match completion {
+            Some(
+                ProvEventData::LlmCall { output, error, .. }
+                | ProvEventData::ToolCall { output, error, .. },
+            ) => (output.as_ref(), error.as_ref()),
+            _ => (None, None),
+        };
+
+        let mut sections = Vec::new();
+        let (kind, title) = match (&start.data, call.kind) {
+            (
+                ProvEventData::LlmCall {
+                    client,
+                    model,
+                    function_name,
+                    prompt,
+                    metadata,
+                    ..
+                },
+                CallKind::Llm,
+            ) => {
+                sections.push(("Prompt".to_string(), payload(prompt)));
+                push_metadata(&mut sections, metadata);
+                if let Some(output) = output {
+                    sections.push(("Response".to_string(), payload(output)));
+                }
+                let (taken, rest): (Vec<&ProvEvent>, Vec<&ProvEvent>) =
+                    chunks.into_iter().partition(|chunk| {
+                        matches!(
+                            &chunk.data,
+                            ProvEventData::LlmStreamChunk { client: c, function_name: f, .. }
+                                if c == client && f == function_name
+                        ) && chunk.timestamp_ms >= start_ms
+                            && (call.end_time_ms.is_none() || chunk.timestamp_ms <= end_ms)
+                    });
+                chunks = rest;
+                if !taken.is_empty() {
+                    sections.push((
+                        format!("Stream chunks ({})", taken.len()),
+                        chunk_list(&taken, start_ms),
+                    ));
+                }
+                ("llm", format!("{} · {} ({})", function_name, client, model))
+            }
+            (
+                ProvEventData::ToolCall {
+                    tool_name,
+                    function_name,
+                    args,
+                    metadata,
+                    ..
+                },
+                _,
+            ) => {
+                sections.push(("Arguments".to_string(), payload(args)));
+                push_metadata(&mut sections, metadata);
+                if let Some(output) = output {
+                    sections.push(("Result".to_string(), payload(output)));
+                }
+                let title = match function_name {
+                    Some(function_name) => format!("{} (from {})", tool_name, function_name),
+                    None => tool_name.clone(),
+                };
+                ("tool", title)
+            }
+            _ => continue,
+        };
+        if let Some(error) = error {
+            sections.push(("Error".to_string(), error.clone()));
+        }
+        entries.push(Entry {
+            kind,
+            start_ms: start_ms as f64,
+            end_ms: end_ms as f64,
+            title,
+            status,
+            sections,
+        });
+    }
+
+    // Chunks of calls whose start was not recorded get an entry of their own
+    let mut orphans: Vec<(String, String, Vec<&ProvEvent>)> = Vec::new();
+    for chunk in chunks {
+        let ProvEventData::LlmStreamChunk {
+            client,
+            function_name,
+            ..
+        } = &chunk.data
+        else {
+            continue;
+        };
+        match orphans
+            .iter_mut()
+            .find(|(c, f, _)| c == client && f == function_name)
+        {
+            Some((_, _, group)) => group.push(chunk),
+            None => orphans.push((client.clone(), function_name.clone(), vec![chunk])),
+        }
+    }
+    for (client, function_name, group) in orphans {
+        let start_ms = group[0].timestamp_ms;
+        let end_ms = group[group.len() - 1].timestamp_ms;
+        entries.push(Entry {
+            kind: "stream",
+            start_ms: start_ms as f64,
+            end_ms: end_ms as f64,
+            title: format!("{} · {} stream", function_name, client),
+            status: Status::None,
+            sections: vec![(
+                format!("Stream chunks ({})", group.len()),
+                chunk_list(&group, start_ms),
+            )],
+        });
+    }
+    entries
+}
+
+/// Entry of a message or task event
+fn point_entry(event: &ProvEvent) -> Option<Entry> {
+    let (kind, title, sections) = match &event.data {
+        ProvEventData::Message {
+            id,
+            role,
+            content,
+            metadata,
+        } => {
+            let direction = if event.event_type == ProvEventType::MessageSent {
+                "sent"
+            } else {
+                "received"
+            };
+            let mut sections = vec![("Content".to_string(), content.join("\n"))];
+            if let Some(metadata) = metadata.as_ref().filter(|metadata| !metadata.is_empty()) {
+                let metadata = serde_json::to_string_pretty(metadata).unwrap_or_default();
+                sections.push(("Metadata".to_string(), metadata));
+            }
+            let title = format!("Message {} {} ({})", id.as_str(), direction, role);
+            ("message", title, sections)
+        }
+        ProvEventData::TaskCreated {
+            task_id,
+            agent_type,
+        } => {
+            let title = match agent_type {
+                Some(agent_type) => format!("Task {} created ({})", task_id.as_str(), agent_type),
+                None => format!("Task {} created", task_id.as_str()),
+            };
+            ("task", title, Vec::new())
+        }
+        ProvEventData::TaskStatusChanged {
+            task_id,
+            old_status,
+            new_status,
+        } => {
+            let title = format!(
+                "Task {}: {} → {}",
+                task_id.as_str(),
+                old_status.as_deref().unwrap_or("none"),
+                new_status.as_deref().unwrap_or("none")
+            );
+            ("task", title, Vec::new())
+        }
+        ProvEventData::TaskArtifactGenerated {
+            task_id,
+            artifact_id,
+            artifact_type,
+        } => {
+            let mut title = format!("Task {} artifact", task_id.as_str());
+            if let Some(artifact_id) = artifact_id {
+                let _ = write!(title, " {}", artifact_id.as_str());
+            }
+            if let Some(artifact_type) = artifact_type {
+                let _ = write!(title, " ({})", artifact_type);
+            }
+            ("task", title, Vec::new())
+        }
+        ProvEventData::ToolApproval {
+            tool_name,
+            args,
+            approved,
+            decided_by,
+            ..
+        } => {
+            let verdict = if *approved { "approved" } else { "denied" };
+            let title = format!("Tool {} {} by {}", tool_name, verdict, decided_by);
+            let args = serde_json::to_string_pretty(args).unwrap_or_default();
+            ("approval", title, vec![("Arguments".to_string(), args)])
+        }
+        ProvEventData::LlmCall { .. }
+        | ProvEventData::LlmStreamChunk { .. }
+        | ProvEventData::ToolCall { .. } => return None,
+    };
+    Some(Entry {
+        kind,
+        start_ms: event.timestamp_ms as f64,
+        end_ms: event.timestamp_ms as f64,
+        title,
+        status: Status::None,
+        sections,
+    })
+}
+
+fn span_entry(span: &SpanRecord) -> Entry {
+    let mut fields = span.fields.clone();
+    fields.remove("correlation_id");
+    let detail: Vec<String> = fields
+        .iter()
+        .map(|(key, value)| match value {
+            Value::String(value) => format!("{}={}", key, value),
+            value => format!("{}={}", key, value),
+        })
+        .collect();
+    let title = if detail.is_empty() {
+        span.name.clone()
+    } else {
+        format!("{} {}", span.name, detail.join(" "))
+    };
+    Entry {
+        kind: "span",
+        start_ms: span.started_at_ms as f64,
+        end_ms: span.started_at_ms as f64 + span.duration_ms,
+        title,
+        status: Status::None,
+        sections: Vec::new(),
+    }
+}
+
+fn push_metadata(sections: &mut Vec<(String, String)>, metadata: &Value) {
+    let empty = match metadata {
+        Value::Null => true,
+        Value::Object(fields) => fields.is_empty(),
+        _ => false,
+    };
+    if !empty {
+        sections.push(("Metadata".to_string(), payload(metadata)));
+    }
+}
+
+/// A payload as shown on the page: strings as they are, other values as
+/// pretty-printed JSON
+fn payload(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        value => serde_json::to_string_pretty(value).unwrap_or_else(|_| value.to_string()),
+    }
+}
+
+/// One line per chunk, with its index and offset from `start_ms`
+fn chunk_list(chunks: &[&ProvEvent], start_ms: u64) -> String {
+    let mut list = String::new();
+    for chunk in chunks {
+        if let ProvEventData::LlmStreamChunk {
+            index,
+            chunk: value,
+            ..
+        } = &chunk.data
+        {
+            let _ = writeln!(
+                list,
+                "#{} +{} ms  {}",
+                index,
+                chunk.timestamp_ms.saturating_sub(start_ms),
+                match value {
+                    Value::String(text) => text.clone(),
+                    value => value.to_string(),
+                }
+            );
+        }
+    }
+    list
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
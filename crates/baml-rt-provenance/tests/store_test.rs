@@ -1,6 +1,14 @@
-use baml_rt_core::ids::ContextId;
-use baml_rt_provenance::{InMemoryProvenanceStore, ProvEvent, ProvenanceWriter};
-use serde_json::json;
+use baml_rt_core::correlation;
+use baml_rt_core::ids::{ArtifactId, ContextId, CorrelationId, TaskId};
+use baml_rt_core::{BamlRtError, Result};
+use baml_rt_interceptor::{LLMCallContext, LLMInterceptor, ToolCallContext, ToolInterceptor};
+use baml_rt_provenance::export::{OpenLineageJob, openlineage_run_events, prov_document};
+use baml_rt_provenance::{
+    InMemoryProvenanceStore, ProvEvent, ProvenanceInterceptor, ProvenanceReader,
+    ProvenanceSubscriber, ProvenanceWriter, timeline_html,
+};
+use serde_json::{Value, json};
+use std::sync::Arc;
 
 #[tokio::test]
 async fn test_in_memory_store_adds_events() {
@@ -19,3 +27,167 @@ async fn test_in_memory_store_adds_events() {
     assert_eq!(events.len(), 1);
     assert_eq!(events[0].context_id, ContextId::from("ctx-1"));
 }
+
+#[tokio::test]
+async fn test_correlated_events_export_as_prov_and_openlineage() {
+    let store = InMemoryProvenanceStore::new();
+    let correlation_id = CorrelationId::from("corr-export");
+    let llm_call = |completed: bool| {
+        let (client, model) = ("openai".to_string(), "gpt-4o".to_string());
+        let (function, prompt) = ("Summarize".to_string(), json!({"text": "hi"}));
+        if completed {
+            ProvEvent::llm_call_completed(
+                ContextId::from("ctx-1"),
+                None,
+                client,
+                model,
+                function,
+                prompt,
+                json!({}),
+                12,
+                true,
+            )
+        } else {
+            ProvEvent::llm_call_started(
+                ContextId::from("ctx-1"),
+                None,
+                client,
+                model,
+                function,
+                prompt,
+                json!({}),
+            )
+        }
+    };
+    let (started, completed, artifact) =
+        correlation::with_correlation_id_sync(correlation_id.clone(), || {
+            (
+                llm_call(false),
+                llm_call(true),
+                ProvEvent::task_artifact_generated(
+                    ContextId::from("ctx-1"),
+                    TaskId::from("task-1"),
+                    Some(ArtifactId::from("summary")),
+                    None,
+                ),
+            )
+        });
+    for event in [started.clone(), completed, artifact, llm_call(false)] {
+        store.add_event(event).await.expect("add event");
+    }
+
+    let events = store
+        .correlated_events(&correlation_id)
+        .await
+        .expect("correlated events");
+    assert_eq!(events.len(), 3);
+
+    let document = prov_document(&correlation_id, &events).to_prov_json();
+    let activity = &document["activity"][format!("baml:llmCall/{}", started.id.as_str())];
+    assert_eq!(activity["baml:function"], "Summarize");
+    assert_eq!(activity["baml:success"], true);
+    assert!(activity["prov:endTime"].as_str().unwrap().ends_with('Z'));
+    assert_eq!(
+        document["entity"][format!("baml:prompt/{}", started.id.as_str())]["prov:value"],
+        r#"{"text":"hi"}"#
+    );
+    assert!(document["entity"]["a2a:artifact/summary"].is_object());
+    assert!(document["agent"]["baml:client/openai"].is_object());
+    assert_eq!(document["prefix"]["prov"], "http://www.w3.org/ns/prov#");
+
+    let job = OpenLineageJob::new("baml-rt", "summarizer");
+    let run_events = openlineage_run_events(&correlation_id, &events, &job);
+    let types: Vec<_> = run_events.iter().map(|event| &event["eventType"]).collect();
+    assert_eq!(types, ["START", "COMPLETE"]);
+    assert_eq!(run_events[0]["run"]["runId"], run_events[1]["run"]["runId"]);
+    assert_eq!(run_events[1]["outputs"][0]["name"], "artifact/summary");
+}
+
+#[tokio::test]
+async fn test_subscribers_receive_events_as_they_are_written() {
+    let store = InMemoryProvenanceStore::new();
+    store
+        .add_event(ProvEvent::task_created(
+            ContextId::from("ctx-1"),
+            TaskId::from("before"),
+            None,
+        ))
+        .await
+        .expect("add event");
+
+    let mut live = store.subscribe();
+    for task in ["task-1", "task-2"] {
+        store
+            .add_event(ProvEvent::task_created(
+                ContextId::from("ctx-1"),
+                TaskId::from(task),
+                None,
+            ))
+            .await
+            .expect("add event");
+    }
+
+    for task in ["task-1", "task-2"] {
+        let event = live.recv().await.expect("live event");
+        assert_eq!(event.task_id, Some(TaskId::from(task)));
+    }
+    assert!(live.try_recv().is_err());
+}
+
+#[tokio::test]
+async fn test_timeline_shows_calls_with_their_payloads() {
+    let store = Arc::new(InMemoryProvenanceStore::new());
+    let interceptor = ProvenanceInterceptor::new(store.clone());
+    let correlation_id = CorrelationId::from("corr-timeline");
+    let llm = LLMCallContext {
+        client: "Fast".to_string(),
+        model: "gpt-4o-mini".to_string(),
+        function_name: "Summarize".to_string(),
+        context_id: ContextId::from("ctx-1"),
+        prompt: json!([{ "role": "user", "content": "Summarize <this>" }]),
+        metadata: json!({}),
+    };
+    let tool = ToolCallContext {
+        tool_name: "lookup".to_string(),
+        function_name: Some("Summarize".to_string()),
+        args: json!({ "query": "weather" }),
+        context_id: ContextId::from("ctx-1"),
+        metadata: json!({}),
+    };
+
+    correlation::with_correlation_id(correlation_id.clone(), async {
+        interceptor.intercept_llm_call(&llm).await.unwrap();
+        for (index, chunk) in ["Sunny", "Sunny and warm"].into_iter().enumerate() {
+            interceptor
+                .on_llm_stream_chunk(&llm, &json!(chunk), index)
+                .await
+                .unwrap();
+        }
+        let output: Result<Value> = Ok(json!("Sunny and warm"));
+        interceptor.on_llm_call_complete(&llm, &output, 40).await;
+
+        interceptor.intercept_tool_call(&tool).await.unwrap();
+        let failed: Result<Value> = Err(BamlRtError::ToolExecution("lookup is down".to_string()));
+        interceptor.on_tool_call_complete(&tool, &failed, 5).await;
+    })
+    .await;
+
+    let events = store.events().await;
+    let html = timeline_html(&correlation_id, &events, &[]);
+    assert!(html.starts_with("<!DOCTYPE html>"), "{}", html);
+    assert!(html.contains("Summarize · Fast (gpt-4o-mini)"), "{}", html);
+    assert!(html.contains("Summarize &lt;this&gt;"), "{}", html);
+    assert!(html.contains("<summary>Response</summary><pre>Sunny and warm</pre>"));
+    assert!(
+        html.contains("<summary>Stream chunks (2)</summary>"),
+        "{}",
+        html
+    );
+    assert!(html.contains("lookup (from Summarize)"), "{}", html);
+    assert!(html.contains("lookup is down"), "{}", html);
+    assert!(html.contains("1 LLM call(s) · 1 tool call(s) · 2 stream chunk(s) · 1 failure(s)"));
+    assert!(!html.contains("<script"));
+
+    let other = timeline_html(&CorrelationId::from("corr-other"), &events, &[]);
+    assert!(other.contains("Nothing was recorded"), "{}", other);
+}
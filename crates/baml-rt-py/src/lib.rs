@@ -0,0 +1,262 @@
+//! Python bindings for the BAML runtime
+//!
+//! Built with maturin as the `baml_rt` module:
+//!
+//! ```python
+//! from baml_rt import A2aAgent, BamlRuntime
+//!
+//! runtime = BamlRuntime("baml_src")
+//! runtime.register_tool(
+//!     "lookup_order", "Finds an order by id",
+//!     {"type": "object", "properties": {"id": {"type": "string"}}},
+//!     lambda args: orders[args["id"]],
+//! )
+//! greeting = runtime.invoke("SimpleGreeting", {"name": "Ada"})
+//!
+//! agent = A2aAgent(runtime, init_js=[open("dist/index.js").read()])
+//! responses = agent.handle_a2a({"jsonrpc": "2.0", "id": 1, "method": "message/send", ...})
+//! ```
+//!
+//! Calls block the calling Python thread but release the GIL while the
+//! runtime works, on a Tokio runtime shared by every object in the process.
+//! Values cross the boundary as JSON, so arguments and results are the
+//! `dict`/`list`/`str`/number values `json` produces. Python tools are plain
+//! callables taking the arguments `dict`; they run on a blocking thread and
+//! must not call back into the runtime.
+
+use async_trait::async_trait;
+use baml_rt_a2a::{A2aAgent, A2aRequestHandler};
+use baml_rt_core::{BamlRtError, Result};
+use baml_rt_quickjs::BamlRuntimeManager;
+use baml_rt_tools::{ToolExecutor, ToolMetadata};
+use pyo3::create_exception;
+use pyo3::exceptions::{PyException, PyValueError};
+use pyo3::prelude::*;
+use serde_json::Value;
+use std::sync::{Arc, OnceLock};
+use tokio::sync::RwLock;
+
+create_exception!(
+    baml_rt,
+    BamlError,
+    PyException,
+    "Raised when the BAML runtime fails."
+);
+
+static RUNTIME: OnceLock<tokio::runtime::Runtime> = OnceLock::new();
+
+/// The Tokio runtime every binding runs on, started on first use
+fn runtime() -> PyResult<&'static tokio::runtime::Runtime> {
+    if let Some(runtime) = RUNTIME.get() {
+        return Ok(runtime);
+    }
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+        .map_err(|err| BamlError::new_err(format!("Failed to start Tokio runtime: {}", err)))?;
+    Ok(RUNTIME.get_or_init(|| runtime))
+}
+
+fn to_py_err(err: BamlRtError) -> PyErr {
+    BamlError::new_err(err.to_string())
+}
+
+fn to_json(value: &Bound<'_, PyAny>) -> PyResult<Value> {
+    let json = value.py().import_bound("json")?;
+    let text: String = json.call_method1("dumps", (value,))?.extract()?;
+    serde_json::from_str(&text).map_err(|err| PyValueError::new_err(err.to_string()))
+}
+
+fn to_python(py: Python<'_>, value: &Value) -> PyResult<PyObject> {
+    let json = py.import_bound("json")?;
+    Ok(json.call_method1("loads", (value.to_string(),))?.unbind())
+}
+
+/// A Python callable registered as a tool
+struct PythonTool {
+    callable: Arc<Py<PyAny>>,
+}
+
+#[async_trait]
+impl ToolExecutor for PythonTool {
+    async fn execute(&self, args: Value) -> Result<Value> {
+        let callable = self.callable.clone();
+        let outcome = tokio::task::spawn_blocking(move || {
+            Python::with_gil(|py| {
+                let args = to_python(py, &args)?;
+                let result = callable.call1(py, (args,))?;
+                to_json(result.bind(py))
+            })
+        })
+        .await
+        .map_err(|err| BamlRtError::ToolExecution(format!("Python tool panicked: {}", err)))?;
+        outcome.map_err(|err| BamlRtError::ToolExecution(err.to_string()))
+    }
+}
+
+/// A BAML schema loaded for execution, with its tools
+#[pyclass(name = "BamlRuntime", module = "baml_rt")]
+struct PyBamlRuntime {
+    manager: Arc<RwLock<BamlRuntimeManager>>,
+}
+
+#[pymethods]
+impl PyBamlRuntime {
+    /// Load the BAML schema at `schema_path`, or start without functions
+    #[new]
+    #[pyo3(signature = (schema_path = None))]
+    fn new(py: Python<'_>, schema_path: Option<String>) -> PyResult<Self> {
+        let runtime = runtime()?;
+        let manager = py.allow_threads(|| {
+            let _context = runtime.enter();
+            let mut manager = BamlRuntimeManager::new()?;
+            if let Some(path) = &schema_path {
+                manager.load_schema(path)?;
+            }
+            Ok::<_, BamlRtError>(manager)
+        });
+        Ok(Self {
+            manager: Arc::new(RwLock::new(manager.map_err(to_py_err)?)),
+        })
+    }
+
+    /// Names of the BAML functions in the schema
+    fn functions(&self, py: Python<'_>) -> PyResult<Vec<String>> {
+        let manager = self.manager.clone();
+        let runtime = runtime()?;
+        Ok(py.allow_threads(|| runtime.block_on(async { manager.read().await.list_functions() })))
+    }
+
+    /// Names of the registered tools
+    fn tools(&self, py: Python<'_>) -> PyResult<Vec<String>> {
+        let manager = self.manager.clone();
+        let runtime = runtime()?;
+        Ok(
+            py.allow_threads(|| {
+                runtime.block_on(async { manager.read().await.list_tools().await })
+            }),
+        )
+    }
+
+    /// Call the BAML function `function_name` with `args`
+    fn invoke(
+        &self,
+        py: Python<'_>,
+        function_name: &str,
+        args: &Bound<'_, PyAny>,
+    ) -> PyResult<PyObject> {
+        let args = to_json(args)?;
+        let manager = self.manager.clone();
+        let runtime = runtime()?;
+        let result = py.allow_threads(|| {
+            runtime.block_on(async {
+                let manager = manager.read().await;
+                manager.invoke_function(function_name, args).await
+            })
+        });
+        to_python(py, &result.map_err(to_py_err)?)
+    }
+
+    /// Register `callable` as the tool `name`; it is called with the
+    /// arguments `dict` and returns the tool result
+    fn register_tool(
+        &self,
+        py: Python<'_>,
+        name: String,
+        description: String,
+        input_schema: &Bound<'_, PyAny>,
+        callable: Py<PyAny>,
+    ) -> PyResult<()> {
+        if !callable.bind(py).is_callable() {
+            return Err(PyValueError::new_err(format!(
+                "Tool '{}' must be callable",
+                name
+            )));
+        }
+        let metadata = ToolMetadata {
+            name,
+            description,
+            input_schema: to_json(input_schema)?,
+            requires_approval: false,
+        };
+        let executor: Arc<dyn ToolExecutor> = Arc::new(PythonTool {
+            callable: Arc::new(callable),
+        });
+        let manager = self.manager.clone();
+        let runtime = runtime()?;
+        py.allow_threads(|| {
+            runtime.block_on(async {
+                let registry = manager.read().await.tool_registry();
+                let mut registry = registry.lock().await;
+                registry.register_dynamic(metadata, executor)
+            })
+        })
+        .map_err(to_py_err)
+    }
+}
+
+/// An A2A agent serving JSON-RPC requests on a [`PyBamlRuntime`]
+#[pyclass(name = "A2aAgent", module = "baml_rt")]
+struct PyA2aAgent {
+    agent: A2aAgent,
+}
+
+#[pymethods]
+impl PyA2aAgent {
+    /// Build an agent on `runtime`, evaluating each of `init_js` in its
+    /// JavaScript bridge
+    #[new]
+    #[pyo3(signature = (runtime, init_js = Vec::new()))]
+    fn new(py: Python<'_>, runtime: &PyBamlRuntime, init_js: Vec<String>) -> PyResult<Self> {
+        let manager = runtime.manager.clone();
+        let tokio = self::runtime()?;
+        let agent = py.allow_threads(|| {
+            tokio.block_on(async {
+                init_js
+                    .into_iter()
+                    .fold(
+                        A2aAgent::builder().with_runtime_handle(manager),
+                        |builder, code| builder.with_init_js(code),
+                    )
+                    .build()
+                    .await
+            })
+        });
+        Ok(Self {
+            agent: agent.map_err(to_py_err)?,
+        })
+    }
+
+    /// Answer an A2A JSON-RPC request or batch, returning its responses
+    fn handle_a2a(&self, py: Python<'_>, request: &Bound<'_, PyAny>) -> PyResult<Vec<PyObject>> {
+        let request = to_json(request)?;
+        let agent = self.agent.clone();
+        let runtime = runtime()?;
+        let responses = py
+            .allow_threads(|| runtime.block_on(async { agent.handle_a2a(request).await }))
+            .map_err(to_py_err)?;
+        responses
+            .iter()
+            .map(|response| to_python(py, response))
+            .collect()
+    }
+
+    /// Evaluate JavaScript in the agent's bridge and return its result
+    fn evaluate_js(&self, py: Python<'_>, code: &str) -> PyResult<PyObject> {
+        let agent = self.agent.clone();
+        let runtime = runtime()?;
+        let result = py
+            .allow_threads(|| runtime.block_on(async { agent.evaluate_js(code).await }))
+            .map_err(to_py_err)?;
+        to_python(py, &result)
+    }
+}
+
+#[pymodule]
+#[pyo3(name = "baml_rt")]
+fn baml_rt_py(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyBamlRuntime>()?;
+    m.add_class::<PyA2aAgent>()?;
+    m.add("BamlError", m.py().get_type_bound::<BamlError>())?;
+    Ok(())
+}
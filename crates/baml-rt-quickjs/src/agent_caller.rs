@@ -0,0 +1,28 @@
+//! Outbound calls to other agents
+//!
+//! An [`AgentCaller`] sends requests from this agent to another one, so a
+//! handler can delegate a sub-task. JavaScript reaches it through the
+//! `callAgent(target, method, params)` global, which resolves to the remote
+//! result, and `callAgent.stream(target, method, params)`, an async iterable
+//! over the results of a streaming method. What `target` means is up to the
+//! caller; the A2A client takes a URL or a `stdio:` command line.
+
+use async_trait::async_trait;
+use baml_rt_core::Result;
+use serde_json::Value;
+use tokio::sync::mpsc;
+
+/// Results of a streaming call, in the order the remote agent sent them
+///
+/// The channel closes after the final result or the first error.
+pub type AgentStream = mpsc::Receiver<Result<Value>>;
+
+/// Sends requests to other agents
+#[async_trait]
+pub trait AgentCaller: Send + Sync {
+    /// Call `method` on `target` and return its result
+    async fn call(&self, target: &str, method: &str, params: Value) -> Result<Value>;
+
+    /// Call a streaming `method` on `target`
+    async fn stream(&self, target: &str, method: &str, params: Value) -> Result<AgentStream>;
+}
@@ -0,0 +1,249 @@
+//! Content-addressable artifact storage
+//!
+//! Files and blobs produced while handling a task are written to an
+//! [`ArtifactStore`] and referred to by an [`ArtifactId`] derived from their
+//! SHA-256 digest, so storing the same bytes twice yields the same id.
+//! JavaScript stores content with `artifacts.put(content, options)` and
+//! reads it back with `artifacts.get(id)`; A2A clients fetch it with the
+//! `artifacts.get` method.
+
+use async_trait::async_trait;
+use baml_rt_core::ids::ArtifactId;
+use baml_rt_core::{BamlRtError, Result};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use tokio::sync::Mutex;
+
+const ID_PREFIX: &str = "sha256-";
+
+/// Description of a stored artifact
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArtifactInfo {
+    pub artifact_id: ArtifactId,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub media_type: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Content length in bytes
+    pub size: u64,
+}
+
+/// A stored artifact and its content
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StoredArtifact {
+    pub info: ArtifactInfo,
+    pub content: Vec<u8>,
+}
+
+impl StoredArtifact {
+    /// The content as standard base64
+    pub fn content_base64(&self) -> String {
+        STANDARD.encode(&self.content)
+    }
+}
+
+/// Id of `content` in a content-addressable store
+pub fn content_id(content: &[u8]) -> ArtifactId {
+    ArtifactId::from(format!("{}{:x}", ID_PREFIX, Sha256::digest(content)))
+}
+
+/// Decode base64 artifact content
+pub fn decode_base64(content: &str) -> Result<Vec<u8>> {
+    STANDARD
+        .decode(content)
+        .map_err(|e| BamlRtError::InvalidArgument(format!("Invalid base64 content: {}", e)))
+}
+
+/// Storage for artifact content
+#[async_trait]
+pub trait ArtifactStore: Send + Sync {
+    /// Store `content`, returning its description
+    ///
+    /// Storing content that is already present keeps the original bytes
+    /// and replaces the media type and name.
+    async fn put(
+        &self,
+        content: Vec<u8>,
+        media_type: Option<String>,
+        name: Option<String>,
+    ) -> Result<ArtifactInfo>;
+
+    /// Load an artifact, or `None` if the store has no such id
+    async fn get(&self, id: &ArtifactId) -> Result<Option<StoredArtifact>>;
+}
+
+/// Process-local artifact store
+#[derive(Default)]
+pub struct InMemoryArtifactStore {
+    artifacts: Mutex<HashMap<ArtifactId, StoredArtifact>>,
+}
+
+impl InMemoryArtifactStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl ArtifactStore for InMemoryArtifactStore {
+    async fn put(
+        &self,
+        content: Vec<u8>,
+        media_type: Option<String>,
+        name: Option<String>,
+    ) -> Result<ArtifactInfo> {
+        let info = ArtifactInfo {
+            artifact_id: content_id(&content),
+            media_type,
+            name,
+            size: content.len() as u64,
+        };
+        self.artifacts.lock().await.insert(
+            info.artifact_id.clone(),
+            StoredArtifact {
+                info: info.clone(),
+                content,
+            },
+        );
+        Ok(info)
+    }
+
+    async fn get(&self, id: &ArtifactId) -> Result<Option<StoredArtifact>> {
+        Ok(self.artifacts.lock().await.get(id).cloned())
+    }
+}
+
+/// Artifact store keeping each artifact as two files in a directory
+///
+/// `<digest>.bin` holds the content and `<digest>.json` its
+/// [`ArtifactInfo`].
+pub struct FileArtifactStore {
+    root: PathBuf,
+}
+
+impl FileArtifactStore {
+    /// Store artifacts under `root`, creating the directory if needed
+    pub async fn open(root: impl Into<PathBuf>) -> Result<Self> {
+        let root = root.into();
+        tokio::fs::create_dir_all(&root).await?;
+        Ok(Self { root })
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Paths of an artifact's content and metadata files
+    ///
+    /// Ids that are not SHA-256 digests are rejected so they cannot name
+    /// files outside the store.
+    fn paths(&self, id: &ArtifactId) -> Option<(PathBuf, PathBuf)> {
+        let digest = id.as_str().strip_prefix(ID_PREFIX)?;
+        if digest.len() != 64 || !digest.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return None;
+        }
+        Some(self.digest_paths(digest))
+    }
+
+    fn digest_paths(&self, digest: &str) -> (PathBuf, PathBuf) {
+        (
+            self.root.join(format!("{}.bin", digest)),
+            self.root.join(format!("{}.json", digest)),
+        )
+    }
+}
+
+#[async_trait]
+impl ArtifactStore for FileArtifactStore {
+    async fn put(
+        &self,
+        content: Vec<u8>,
+        media_type: Option<String>,
+        name: Option<String>,
+    ) -> Result<ArtifactInfo> {
+        let info = ArtifactInfo {
+            artifact_id: content_id(&content),
+            media_type,
+            name,
+            size: content.len() as u64,
+        };
+        let digest = &info.artifact_id.as_str()[ID_PREFIX.len()..];
+        let (content_path, info_path) = self.digest_paths(digest);
+        if !tokio::fs::try_exists(&content_path).await? {
+            tokio::fs::write(&content_path, &content).await?;
+        }
+        let info_json = serde_json::to_vec(&info).map_err(BamlRtError::Json)?;
+        tokio::fs::write(&info_path, info_json).await?;
+        Ok(info)
+    }
+
+    async fn get(&self, id: &ArtifactId) -> Result<Option<StoredArtifact>> {
+        let Some((content_path, info_path)) = self.paths(id) else {
+            return Ok(None);
+        };
+        let content = match tokio::fs::read(&content_path).await {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        let info = match tokio::fs::read(&info_path).await {
+            Ok(bytes) => serde_json::from_slice(&bytes).map_err(BamlRtError::Json)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => ArtifactInfo {
+                artifact_id: id.clone(),
+                media_type: None,
+                name: None,
+                size: content.len() as u64,
+            },
+            Err(e) => return Err(e.into()),
+        };
+        Ok(Some(StoredArtifact { info, content }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn file_store_round_trips_content_by_digest() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = FileArtifactStore::open(dir.path().join("artifacts"))
+            .await
+            .unwrap();
+
+        let info = store
+            .put(
+                b"col\n1\n".to_vec(),
+                Some("text/csv".to_string()),
+                Some("report.csv".to_string()),
+            )
+            .await
+            .unwrap();
+        assert_eq!(info.artifact_id, content_id(b"col\n1\n"));
+        assert_eq!(info.size, 6);
+
+        let again = store.put(b"col\n1\n".to_vec(), None, None).await.unwrap();
+        assert_eq!(again.artifact_id, info.artifact_id);
+
+        let stored = store.get(&info.artifact_id).await.unwrap().unwrap();
+        assert_eq!(stored.content, b"col\n1\n");
+        assert_eq!(
+            decode_base64(&stored.content_base64()).unwrap(),
+            stored.content
+        );
+
+        assert!(
+            store
+                .get(&ArtifactId::from("sha256-../../etc/passwd"))
+                .await
+                .unwrap()
+                .is_none()
+        );
+        assert!(store.get(&content_id(b"missing")).await.unwrap().is_none());
+    }
+}
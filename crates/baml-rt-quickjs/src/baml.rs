@@ -1,31 +1,83 @@
 //! BAML runtime wrapper and function execution
 
-use crate::baml_execution::BamlExecutor;
+use crate::agent_caller::AgentCaller;
+use crate::artifact_store::ArtifactStore;
+use crate::baml_execution::{BamlExecutor, BamlStream, UNKNOWN_MODEL};
+use crate::baml_stream_interception::StreamChunkObserver;
+use crate::dry_run::{self, DryRun};
+use crate::embeddings::{
+    EmbeddingProvider, EmbeddingSettings, OpenAiEmbeddings, default_embedding_client, embed_cached,
+};
+use crate::kv_store::KvStore;
+use crate::llm_overrides::LlmOverrides;
+use crate::output_guard::{GuardAction, OutputGuardRegistry};
+use crate::repair::RepairPolicy;
+use crate::secrets::SecretStore;
+use crate::session::{SessionHistory, SessionStore};
+use crate::task_operations::TaskOperations;
+use crate::token_limiter::StreamTokenLimiter;
+use crate::tool_schema::{self, ToolSchemaInjection};
 use crate::traits::{BamlFunctionExecutor, SchemaLoader};
 use async_trait::async_trait;
+use baml_rt_core::auth::{self, Access};
+use baml_rt_core::cancellation;
 use baml_rt_core::context;
 use baml_rt_core::correlation::current_correlation_id;
-use baml_rt_core::types::FunctionSignature;
+use baml_rt_core::deadline;
+use baml_rt_core::events::EventBus;
+use baml_rt_core::ids::ContextId;
+use baml_rt_core::media::MediaPolicy;
+use baml_rt_core::types::{BamlType, FunctionDescription, FunctionSignature};
 use baml_rt_core::{BamlRtError, Result};
-use baml_rt_interceptor::InterceptorRegistry;
+use baml_rt_interceptor::{InterceptorRegistry, LLMCallContext};
 use baml_rt_observability::metrics;
-use baml_rt_tools::{ToolMapper, ToolMetadata, ToolRegistry as ConcreteToolRegistry};
+use baml_rt_tools::{
+    ApprovalGate, ProposedToolCall, ToolExecutor, ToolMapper, ToolMetadata, ToolPolicy,
+    ToolRegistry as ConcreteToolRegistry,
+};
+use baml_runtime::type_builder::TypeBuilder;
 use serde_json::{Value, json};
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex as StdMutex};
-use tokio::sync::Mutex as TokioMutex;
+use tokio::sync::{Mutex as TokioMutex, RwLock};
+use tokio_util::sync::CancellationToken;
 
 // BAML executes in Rust. We will implement execution of BAML functions
 // in Rust, then map those function calls to QuickJS so JavaScript can invoke them.
 // use baml;
 
 /// Manages the BAML runtime and function registry
+///
+/// Hosts share the manager as `Arc<RwLock<BamlRuntimeManager>>`. Function
+/// and tool calls only need `&self`, so they run concurrently under read
+/// locks; the write lock is only needed to load a schema or change the
+/// manager's configuration.
 pub struct BamlRuntimeManager {
     function_registry: HashMap<String, FunctionSignature>,
     pub(crate) executor: Option<BamlExecutor>,
     tool_registry: Arc<TokioMutex<ConcreteToolRegistry>>,
     tool_mapper: Arc<StdMutex<ToolMapper>>,
     interceptor_registry: Arc<TokioMutex<InterceptorRegistry>>,
+    stream_token_limiter: Option<Arc<StreamTokenLimiter>>,
+    output_guards: Option<Arc<OutputGuardRegistry>>,
+    repair_policies: HashMap<String, Arc<RepairPolicy>>,
+    tool_policy: ToolPolicy,
+    approvals: Arc<ApprovalGate>,
+    session_store: Option<Arc<dyn SessionStore>>,
+    session_history: Option<SessionHistory>,
+    artifact_store: Option<Arc<dyn ArtifactStore>>,
+    kv_store: Option<Arc<dyn KvStore>>,
+    agent_caller: Option<Arc<dyn AgentCaller>>,
+    task_operations: Option<Arc<dyn TaskOperations>>,
+    agent_name: Option<String>,
+    embeddings: EmbeddingSettings,
+    tool_schema: Option<ToolSchemaInjection>,
+    media_policy: MediaPolicy,
+    dry_run: Option<Arc<DryRun>>,
+    llm_overrides: Option<Arc<LlmOverrides>>,
+    secrets: Arc<SecretStore>,
+    events: EventBus,
 }
 
 impl BamlRuntimeManager {
@@ -33,12 +85,34 @@ impl BamlRuntimeManager {
     pub fn new() -> Result<Self> {
         tracing::info!("Initializing BAML runtime manager");
 
+        let events = EventBus::new();
         Ok(Self {
             function_registry: HashMap::new(),
             executor: None,
             tool_registry: Arc::new(TokioMutex::new(ConcreteToolRegistry::new())),
             tool_mapper: Arc::new(StdMutex::new(ToolMapper::new())),
-            interceptor_registry: Arc::new(TokioMutex::new(InterceptorRegistry::new())),
+            interceptor_registry: Arc::new(TokioMutex::new(
+                InterceptorRegistry::new().with_event_bus(events.clone()),
+            )),
+            stream_token_limiter: None,
+            output_guards: None,
+            repair_policies: HashMap::new(),
+            tool_policy: ToolPolicy::default(),
+            approvals: Arc::new(ApprovalGate::new()),
+            session_store: None,
+            session_history: None,
+            artifact_store: None,
+            kv_store: None,
+            agent_caller: None,
+            task_operations: None,
+            agent_name: None,
+            embeddings: EmbeddingSettings::default(),
+            tool_schema: None,
+            media_policy: MediaPolicy::default(),
+            dry_run: None,
+            llm_overrides: None,
+            secrets: Arc::new(SecretStore::new()),
+            events,
         })
     }
 
@@ -50,16 +124,43 @@ impl BamlRuntimeManager {
     /// Load a compiled BAML schema/configuration
     ///
     /// This loads the BAML IL (Intermediate Language) from the baml_src directory
-    /// and registers all available functions.
+    /// and registers all available functions, replacing any loaded before.
     ///
     /// The schema_path should point to the baml_src directory.
-    pub fn load_schema(&mut self, schema_path: &str) -> Result<()> {
-        tracing::info!(schema_path = schema_path, "Loading BAML IL");
+    pub fn load_schema(&mut self, schema_path: impl AsRef<Path>) -> Result<()> {
+        let schema = self.schema_source(schema_path)?.load()?;
+        self.install_schema(schema);
+        Ok(())
+    }
 
-        use std::path::Path;
+    /// Load the schema at `schema_path` and swap it in for the one `manager`
+    /// has loaded
+    ///
+    /// The schema is compiled on a blocking thread while calls go on being
+    /// served by the old one, and the manager is only locked for writing to
+    /// swap executors, so no call sees half of each. Streams already running
+    /// finish on the old schema. If the new schema fails to load, the old one
+    /// stays in place.
+    ///
+    /// JS wrappers are not updated; use
+    /// [`QuickJSBridge::reload_schema`](crate::QuickJSBridge::reload_schema)
+    /// for a manager that backs a bridge.
+    pub async fn reload_schema(
+        manager: &Arc<RwLock<Self>>,
+        schema_path: impl AsRef<Path>,
+    ) -> Result<SchemaReload> {
+        let source = manager.read().await.schema_source(schema_path)?;
+        let schema = tokio::task::spawn_blocking(move || source.load())
+            .await
+            .map_err(|err| BamlRtError::SchemaLoading(format!("Schema load failed: {}", err)))??;
+        Ok(manager.write().await.install_schema(schema))
+    }
 
+    /// What loading the schema at `schema_path` needs, so that it can be
+    /// loaded without holding the manager
+    fn schema_source(&self, schema_path: impl AsRef<Path>) -> Result<SchemaSource> {
         // Find project root
-        let schema_path_obj = Path::new(schema_path);
+        let schema_path_obj = schema_path.as_ref();
         let project_root = if schema_path_obj.is_file() {
             schema_path_obj.parent().and_then(|p| p.parent())
         } else if schema_path_obj.file_name() == Some(std::ffi::OsStr::new("baml_src")) {
@@ -76,34 +177,50 @@ impl BamlRuntimeManager {
             ));
         }
 
-        // Load BAML IL into executor (pass tool registry)
-        let tool_registry_clone = self.tool_registry.clone();
-        let tool_mapper_clone = self.tool_mapper.clone();
-        let executor =
-            BamlExecutor::load_il(&baml_src_dir, tool_registry_clone, tool_mapper_clone)?;
-
-        // Discover functions from the BAML runtime
-        let function_names = executor.list_functions();
-        for func_name in function_names {
-            // Register function signature
-            self.function_registry.insert(
-                func_name.clone(),
-                FunctionSignature {
-                    name: func_name.clone(),
-                    input_types: vec![],
-                    output_type: baml_rt_core::types::BamlType::String,
-                },
-            );
-        }
+        Ok(SchemaSource {
+            baml_src_dir,
+            tool_registry: self.tool_registry.clone(),
+            tool_mapper: self.tool_mapper.clone(),
+            secrets: self.secrets.clone(),
+            media_policy: self.media_policy.clone(),
+            dry_run: self.dry_run.clone(),
+            llm_overrides: self.llm_overrides.clone(),
+        })
+    }
+
+    /// Replace the loaded executor and function registry with `schema`'s
+    fn install_schema(&mut self, schema: LoadedSchema) -> SchemaReload {
+        let mut added: Vec<String> = schema
+            .functions
+            .keys()
+            .filter(|name| !self.function_registry.contains_key(*name))
+            .cloned()
+            .collect();
+        let mut removed: Vec<String> = self
+            .function_registry
+            .keys()
+            .filter(|name| !schema.functions.contains_key(*name))
+            .cloned()
+            .collect();
+        let mut functions: Vec<String> = schema.functions.keys().cloned().collect();
+        added.sort();
+        removed.sort();
+        functions.sort();
 
-        self.executor = Some(executor);
+        self.function_registry = schema.functions;
+        self.executor = Some(schema.executor);
 
         tracing::info!(
             function_count = self.function_registry.len(),
+            added = added.len(),
+            removed = removed.len(),
             "Loaded BAML IL"
         );
-
-        Ok(())
+        SchemaReload {
+            functions,
+            added,
+            removed,
+        }
     }
 
     /// Get the signature of a function by name
@@ -114,11 +231,180 @@ impl BamlRuntimeManager {
     /// Execute a BAML function with the given arguments
     ///
     /// This is the main entry point for executing BAML functions.
-    /// It validates the function exists and delegates to the executor.
+    /// It validates the function exists and delegates to the executor, then
+    /// checks the output against any output guards, and repairs it under the
+    /// function's repair policy if it has one. Every invocation the caller in
+    /// scope may make counts towards the function's call and latency metrics.
     pub async fn invoke_function(
         &self,
         function_name: &str,
         args: serde_json::Value,
+    ) -> Result<serde_json::Value> {
+        let repair = self.repair_policies.get(function_name).cloned();
+        self.invoke_function_with(function_name, args, repair.as_deref())
+            .await
+    }
+
+    /// Execute a BAML function, validating and repairing its output under
+    /// `repair` instead of the function's own policy
+    pub async fn invoke_function_with_repair(
+        &self,
+        function_name: &str,
+        args: serde_json::Value,
+        repair: &RepairPolicy,
+    ) -> Result<serde_json::Value> {
+        self.invoke_function_with(function_name, args, Some(repair))
+            .await
+    }
+
+    /// Execute a BAML function as a dry run under `dry_run`, whether or not
+    /// the manager makes every call one
+    pub async fn invoke_function_dry_run(
+        &self,
+        function_name: &str,
+        args: serde_json::Value,
+        dry_run: DryRun,
+    ) -> Result<serde_json::Value> {
+        dry_run::with_dry_run(Arc::new(dry_run), self.invoke_function(function_name, args)).await
+    }
+
+    async fn invoke_function_with(
+        &self,
+        function_name: &str,
+        args: serde_json::Value,
+        repair: Option<&RepairPolicy>,
+    ) -> Result<serde_json::Value> {
+        auth::check(Access::Function(function_name))?;
+        let start = std::time::Instant::now();
+        let result = match repair {
+            Some(repair) => {
+                self.invoke_function_repaired(function_name, args, repair)
+                    .await
+            }
+            None => self.invoke_function_guarded(function_name, args).await,
+        };
+        let model = self
+            .executor
+            .as_ref()
+            .and_then(|executor| executor.function_client(function_name));
+        metrics::record_baml_function(
+            function_name,
+            model.as_deref().unwrap_or(UNKNOWN_MODEL),
+            if result.is_ok() { "success" } else { "error" },
+            false,
+            start.elapsed(),
+        );
+        result
+    }
+
+    /// Execute a BAML function, calling it again with the validation errors
+    /// while its output fails `repair`
+    async fn invoke_function_repaired(
+        &self,
+        function_name: &str,
+        args: serde_json::Value,
+        repair: &RepairPolicy,
+    ) -> Result<serde_json::Value> {
+        let takes_feedback = self
+            .function_registry
+            .get(function_name)
+            .is_some_and(|signature| {
+                signature
+                    .param_names
+                    .iter()
+                    .any(|param| param == repair.feedback_param())
+            });
+        let mut output = self
+            .invoke_function_guarded(function_name, args.clone())
+            .await?;
+        let mut errors = repair.errors(&output);
+        let mut repairs = 0;
+        while !errors.is_empty() {
+            if repairs == repair.max_repairs() {
+                return Err(repair.exhausted(&errors));
+            }
+            repairs += 1;
+            tracing::warn!(
+                function = function_name,
+                errors = ?errors,
+                attempt = repairs,
+                "BAML function output invalid; repairing"
+            );
+            let mut repair_args = args.clone();
+            if let Some(map) = repair_args.as_object_mut().filter(|_| takes_feedback) {
+                map.insert(
+                    repair.feedback_param().to_string(),
+                    json!(RepairPolicy::feedback(&output, &errors)),
+                );
+            }
+            output = match self
+                .invoke_function_guarded(function_name, repair_args)
+                .await
+            {
+                Ok(output) => output,
+                Err(e) => {
+                    metrics::record_baml_repair(function_name, "error");
+                    return Err(e);
+                }
+            };
+            errors = repair.errors(&output);
+            let result = if errors.is_empty() {
+                "repaired"
+            } else {
+                "invalid"
+            };
+            metrics::record_baml_repair(function_name, result);
+        }
+        Ok(output)
+    }
+
+    /// Execute a BAML function, checking its output against the output guards
+    async fn invoke_function_guarded(
+        &self,
+        function_name: &str,
+        args: serde_json::Value,
+    ) -> Result<serde_json::Value> {
+        let Some(guards) = self
+            .output_guards
+            .as_ref()
+            .filter(|guards| !guards.is_empty())
+        else {
+            return self.invoke_function_unguarded(function_name, args).await;
+        };
+
+        let mut attempt = 1;
+        loop {
+            let output = self
+                .invoke_function_unguarded(function_name, args.clone())
+                .await?;
+            let Some(violation) = guards
+                .check(function_name, &output, &Unguarded(self))
+                .await?
+            else {
+                return Ok(output);
+            };
+            tracing::warn!(
+                function = function_name,
+                guard = violation.guard.as_str(),
+                reasons = ?violation.reasons,
+                attempt,
+                "BAML function output rejected by guard"
+            );
+            match violation.action {
+                GuardAction::Annotate => return Ok(violation.annotate(output)),
+                GuardAction::Retry { max_attempts } if attempt < max_attempts => attempt += 1,
+                GuardAction::Retry { .. } | GuardAction::Fail => {
+                    return Err(violation.into_error());
+                }
+            }
+        }
+    }
+
+    /// Execute a BAML function without output guards
+    async fn invoke_function_unguarded(
+        &self,
+        function_name: &str,
+        args: serde_json::Value,
     ) -> Result<serde_json::Value> {
         let correlation_id = current_correlation_id();
         if let Some(correlation_id) = correlation_id.as_ref().map(|id| id.as_str()) {
@@ -148,21 +434,49 @@ impl BamlRuntimeManager {
             .as_ref()
             .ok_or_else(|| BamlRtError::BamlRuntime("BAML runtime not loaded".to_string()))?;
 
+        let args = self
+            .with_session_history(function_name, args, context::current_context_id().as_ref())
+            .await;
+
         // Pass tool registry and interceptor registry to executor
         let interceptor_registry = Some(self.interceptor_registry.clone());
+        let type_builder = self.tool_type_builder(function_name).await;
         executor
-            .execute_function(function_name, args, interceptor_registry)
+            .execute_function(
+                function_name,
+                args,
+                interceptor_registry,
+                type_builder.as_ref(),
+            )
             .await
     }
 
     /// Invoke a BAML function with streaming support
     ///
     /// Returns a stream that yields incremental results as the function executes.
-    pub fn invoke_function_stream(
+    /// The stream does not borrow the manager, so the lock it was created
+    /// under can be released before it runs.
+    pub async fn invoke_function_stream(
+        &self,
+        function_name: &str,
+        args: serde_json::Value,
+    ) -> Result<BamlStream> {
+        self.invoke_function_stream_with_cancel(function_name, args, CancellationToken::new(), None)
+            .await
+    }
+
+    /// Invoke a BAML function with streaming support, aborting the generation
+    /// when `cancel` is cancelled
+    ///
+    /// Pass the [`tool_type_builder`](Self::tool_type_builder) of the function
+    /// to offer it the registered tools.
+    pub async fn invoke_function_stream_with_cancel(
         &self,
         function_name: &str,
         args: serde_json::Value,
-    ) -> Result<baml_runtime::FunctionResultStream> {
+        cancel: CancellationToken,
+        type_builder: Option<TypeBuilder>,
+    ) -> Result<BamlStream> {
         tracing::debug!(
             function = function_name,
             args = ?args,
@@ -174,6 +488,7 @@ impl BamlRuntimeManager {
             .function_registry
             .get(function_name)
             .ok_or_else(|| BamlRtError::FunctionNotFound(function_name.to_string()))?;
+        auth::check(Access::Function(function_name))?;
 
         // Execute the BAML function using the executor
         let executor = self
@@ -181,7 +496,376 @@ impl BamlRuntimeManager {
             .as_ref()
             .ok_or_else(|| BamlRtError::BamlRuntime("BAML runtime not loaded".to_string()))?;
 
-        executor.execute_function_stream(function_name, args)
+        executor
+            .execute_function_stream(function_name, args, cancel, type_builder)
+            .await
+    }
+
+    /// Interceptor hook for the chunks of a streaming call to `function_name`
+    ///
+    /// `None` when no LLM interceptors are registered, so streams without
+    /// interceptors skip the per-chunk work entirely.
+    pub async fn stream_chunk_observer(
+        &self,
+        function_name: &str,
+        args: &Value,
+    ) -> Option<StreamChunkObserver> {
+        if self
+            .interceptor_registry
+            .lock()
+            .await
+            .llm_interceptors()
+            .is_empty()
+        {
+            return None;
+        }
+        let executor = self.executor.as_ref()?;
+        let type_builder = self.tool_type_builder(function_name).await;
+        let call_context = match executor
+            .stream_call_context(function_name, args, type_builder.as_ref())
+            .await
+        {
+            Ok(call_context) => call_context,
+            Err(e) => {
+                tracing::warn!(
+                    function = function_name,
+                    error = ?e,
+                    "Could not build LLM call context for stream interception"
+                );
+                LLMCallContext {
+                    client: String::new(),
+                    model: String::new(),
+                    function_name: function_name.to_string(),
+                    context_id: context::current_or_new(),
+                    prompt: Value::Null,
+                    metadata: json!({}),
+                }
+            }
+        };
+        Some(StreamChunkObserver::new(
+            self.interceptor_registry.clone(),
+            call_context,
+        ))
+    }
+
+    /// Cap output tokens of streaming calls made through the JS bridge
+    pub fn set_stream_token_limiter(&mut self, limiter: Arc<StreamTokenLimiter>) {
+        self.stream_token_limiter = Some(limiter);
+    }
+
+    /// Check outputs of `invoke_function` against `guards`
+    pub fn set_output_guards(&mut self, guards: Arc<OutputGuardRegistry>) {
+        self.output_guards = Some(guards);
+    }
+
+    /// Validate and repair the outputs of `function` under `policy`
+    pub fn set_repair_policy(&mut self, function: impl Into<String>, policy: Arc<RepairPolicy>) {
+        self.repair_policies.insert(function.into(), policy);
+    }
+
+    /// Limit the tools each BAML function may call to those `policy` lists
+    pub fn set_tool_policy(&mut self, policy: ToolPolicy) {
+        self.tool_policy = policy;
+    }
+
+    /// Fail unless the tool policy lets a call made on behalf of `function`
+    /// reach tool `name`, which may be qualified
+    pub async fn check_tool_policy(&self, function: Option<&str>, name: &str) -> Result<()> {
+        let qualified = self
+            .tool_registry
+            .lock()
+            .await
+            .resolve(name)
+            .unwrap_or_else(|_| name.to_string());
+        self.check_qualified_tool_policy(function, &qualified)
+    }
+
+    fn check_qualified_tool_policy(&self, function: Option<&str>, qualified: &str) -> Result<()> {
+        match qualified.split_once(':') {
+            Some((namespace, bare)) => self.tool_policy.check(function, namespace, bare),
+            None => self.tool_policy.check(function, "", qualified),
+        }
+    }
+
+    /// Hold calls of tools that require approval at `gate`
+    pub fn set_approval_gate(&mut self, gate: Arc<ApprovalGate>) {
+        self.approvals = gate;
+    }
+
+    /// The gate calls of tools that require approval wait at
+    pub fn approval_gate(&self) -> Arc<ApprovalGate> {
+        self.approvals.clone()
+    }
+
+    /// Keep conversation messages per context in `store`
+    pub fn set_session_store(&mut self, store: Arc<dyn SessionStore>) {
+        self.session_store = Some(store);
+    }
+
+    /// Pass stored messages to BAML functions as described by `history`
+    pub fn set_session_history(&mut self, history: SessionHistory) {
+        self.session_history = Some(history);
+    }
+
+    /// The session store, if one is configured
+    pub fn session_store(&self) -> Option<Arc<dyn SessionStore>> {
+        self.session_store.clone()
+    }
+
+    /// Persist files and blobs produced by tasks in `store`
+    pub fn set_artifact_store(&mut self, store: Arc<dyn ArtifactStore>) {
+        self.artifact_store = Some(store);
+    }
+
+    /// The artifact store, if one is configured
+    pub fn artifact_store(&self) -> Option<Arc<dyn ArtifactStore>> {
+        self.artifact_store.clone()
+    }
+
+    /// Keep the state JavaScript stores with `kvSet` in `store`
+    pub fn set_kv_store(&mut self, store: Arc<dyn KvStore>) {
+        self.kv_store = Some(store);
+    }
+
+    /// The key-value store, if one is configured
+    pub fn kv_store(&self) -> Option<Arc<dyn KvStore>> {
+        self.kv_store.clone()
+    }
+
+    /// Let JavaScript call other agents through `caller`
+    pub fn set_agent_caller(&mut self, caller: Arc<dyn AgentCaller>) {
+        self.agent_caller = Some(caller);
+    }
+
+    /// The agent caller, if one is configured
+    pub fn agent_caller(&self) -> Option<Arc<dyn AgentCaller>> {
+        self.agent_caller.clone()
+    }
+
+    /// Let JavaScript create and update tasks through `tasks`
+    pub fn set_task_operations(&mut self, tasks: Arc<dyn TaskOperations>) {
+        self.task_operations = Some(tasks);
+    }
+
+    /// The task operations, if any are configured
+    pub fn task_operations(&self) -> Option<Arc<dyn TaskOperations>> {
+        self.task_operations.clone()
+    }
+
+    /// Name the agent this manager runs, for its logs
+    ///
+    /// Bridges created afterwards attach it to every `log.*` event.
+    pub fn set_agent_name(&mut self, name: impl Into<String>) {
+        self.agent_name = Some(name.into());
+    }
+
+    /// The agent's name, if one is set
+    pub fn agent_name(&self) -> Option<&str> {
+        self.agent_name.as_deref()
+    }
+
+    /// Compute embeddings with `provider` instead of a schema client
+    pub fn set_embedding_provider(&mut self, provider: Arc<dyn EmbeddingProvider>) {
+        self.embeddings.provider = Some(provider);
+    }
+
+    /// Compute embeddings through the endpoint of schema client `client`
+    ///
+    /// Without one, the schema's only OpenAI-compatible client is used.
+    pub fn set_embedding_client(&mut self, client: impl Into<String>) {
+        self.embeddings.client = Some(client.into());
+    }
+
+    /// Send at most `batch_size` texts per embeddings request
+    pub fn set_embedding_batch_size(&mut self, batch_size: usize) {
+        self.embeddings.batch_size = batch_size.max(1);
+    }
+
+    /// The embedding of `text` by `model`
+    pub async fn embed(&self, text: &str, model: &str) -> Result<Vec<f32>> {
+        let mut vectors = self.embed_batch(&[text.to_string()], model).await?;
+        vectors
+            .pop()
+            .ok_or_else(|| BamlRtError::BamlRuntime("No embedding was returned".to_string()))
+    }
+
+    /// The embeddings of `texts` by `model`, in order
+    ///
+    /// Cached vectors are reused, and the rest are requested in batches.
+    pub async fn embed_batch(&self, texts: &[String], model: &str) -> Result<Vec<Vec<f32>>> {
+        let settings = &self.embeddings;
+        if let Some(provider) = &settings.provider {
+            return embed_cached(
+                provider.as_ref(),
+                &settings.cache,
+                "",
+                model,
+                texts,
+                settings.batch_size,
+            )
+            .await;
+        }
+        let executor = self
+            .executor
+            .as_ref()
+            .ok_or_else(|| BamlRtError::BamlRuntime("BAML runtime not loaded".to_string()))?;
+        let client = match &settings.client {
+            Some(client) => client.as_str(),
+            None => default_embedding_client(
+                executor
+                    .client_names()
+                    .into_iter()
+                    .filter_map(|name| executor.client_config(name).map(|config| (name, config))),
+            )?,
+        };
+        let config = executor.client_config(client).ok_or_else(|| {
+            BamlRtError::Configuration(format!("The schema has no client '{}'", client))
+        })?;
+        let provider = OpenAiEmbeddings::for_client(client, config, &executor.call_env().await)?;
+        embed_cached(
+            &provider,
+            &settings.cache,
+            client,
+            model,
+            texts,
+            settings.batch_size,
+        )
+        .await
+    }
+
+    /// Let media arguments be read from the files `policy` allows
+    ///
+    /// No files are readable by default; URLs, data URIs and bytes always are.
+    pub fn set_media_policy(&mut self, policy: MediaPolicy) {
+        if let Some(executor) = &mut self.executor {
+            executor.set_media_policy(policy.clone());
+        }
+        self.media_policy = policy;
+    }
+
+    /// Make every BAML call a dry run under `dry_run`, or none with `None`
+    ///
+    /// See [`dry_run`](crate::dry_run) for what a dry run skips.
+    pub fn set_dry_run(&mut self, dry_run: Option<DryRun>) {
+        let dry_run = dry_run.map(Arc::new);
+        if let Some(executor) = &mut self.executor {
+            executor.set_dry_run(dry_run.clone());
+        }
+        self.dry_run = dry_run;
+    }
+
+    /// Force the sampling parameters of `overrides` onto BAML calls, or use
+    /// the clients' own with `None`
+    ///
+    /// See [`llm_overrides`](crate::llm_overrides) for how they reach the
+    /// provider.
+    pub fn set_llm_overrides(&mut self, overrides: Option<LlmOverrides>) {
+        let overrides = overrides.map(Arc::new);
+        if let Some(executor) = &mut self.executor {
+            executor.set_llm_overrides(overrides.clone());
+        }
+        self.llm_overrides = overrides;
+    }
+
+    /// Take the API keys BAML calls see from `secrets` instead of the
+    /// process environment
+    pub fn set_secret_store(&mut self, secrets: Arc<SecretStore>) {
+        if let Some(executor) = &mut self.executor {
+            executor.set_secret_store(secrets.clone());
+        }
+        self.secrets = secrets;
+    }
+
+    /// The store BAML calls take their API keys from
+    pub fn secret_store(&self) -> Arc<SecretStore> {
+        self.secrets.clone()
+    }
+
+    /// Offer the registered tools to the functions selected by `injection`
+    pub fn set_tool_schema_injection(&mut self, injection: ToolSchemaInjection) {
+        self.tool_schema = Some(injection);
+    }
+
+    /// Type builder adding the registered tools to the tool choice class of
+    /// `function_name`, when tool schema injection applies to it
+    ///
+    /// Each tool is mapped to its variant name, so the tool the model picks
+    /// is executed with the call.
+    pub async fn tool_type_builder(&self, function_name: &str) -> Option<TypeBuilder> {
+        let injection = self
+            .tool_schema
+            .as_ref()
+            .filter(|injection| injection.applies_to(function_name))?;
+        let mut tools: Vec<ToolMetadata> = self
+            .tool_registry
+            .lock()
+            .await
+            .all_metadata()
+            .into_iter()
+            .cloned()
+            .collect();
+        tools.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let mut mapper = self.tool_mapper.lock().unwrap_or_else(|e| e.into_inner());
+        for tool in &tools {
+            mapper.register_mapping(
+                tool_schema::tool_variant_name(&tool.name),
+                tool.name.clone(),
+            );
+        }
+        drop(mapper);
+
+        Some(tool_schema::build_type_builder(
+            &injection.class_name,
+            &tools,
+        ))
+    }
+
+    /// Add the conversation of `context_id` to `args` when `function_name`
+    /// takes the configured history parameter and the caller left it out
+    ///
+    /// Failing to read the store is logged and the call proceeds without
+    /// history.
+    pub async fn with_session_history(
+        &self,
+        function_name: &str,
+        mut args: Value,
+        context_id: Option<&ContextId>,
+    ) -> Value {
+        let (Some(store), Some(history), Some(context_id)) = (
+            self.session_store.as_ref(),
+            self.session_history.as_ref(),
+            context_id,
+        ) else {
+            return args;
+        };
+        let takes_history = self
+            .function_registry
+            .get(function_name)
+            .is_some_and(|signature| signature.param_names.contains(&history.param));
+        let Some(map) = args.as_object_mut().filter(|_| takes_history) else {
+            return args;
+        };
+        if map.contains_key(&history.param) {
+            return args;
+        }
+        match store.get(context_id, history.limit).await {
+            Ok(messages) => {
+                map.insert(history.param.clone(), json!(messages));
+            }
+            Err(e) => tracing::warn!(
+                function = function_name,
+                context_id = context_id.as_str(),
+                error = ?e,
+                "Could not load session history"
+            ),
+        }
+        args
+    }
+
+    /// The output-token limiter for streaming calls, if one is configured
+    pub fn stream_token_limiter(&self) -> Option<Arc<StreamTokenLimiter>> {
+        self.stream_token_limiter.clone()
     }
 
     /// List all available BAML functions
@@ -189,6 +873,17 @@ impl BamlRuntimeManager {
         self.function_registry.keys().cloned().collect()
     }
 
+    /// Describe the available BAML functions, sorted by name
+    pub fn describe_functions(&self) -> Vec<FunctionDescription> {
+        let mut functions: Vec<FunctionDescription> = self
+            .function_registry
+            .values()
+            .map(FunctionDescription::from)
+            .collect();
+        functions.sort_by(|a, b| a.name.cmp(&b.name));
+        functions
+    }
+
     /// Get the tool registry (for tool registration)
     pub fn tool_registry(&self) -> Arc<TokioMutex<ConcreteToolRegistry>> {
         self.tool_registry.clone()
@@ -199,6 +894,15 @@ impl BamlRuntimeManager {
         self.interceptor_registry.clone()
     }
 
+    /// The bus this runtime's LLM calls, tool calls, and JS errors are
+    /// published on
+    ///
+    /// Hosts publish their own events, such as A2A task updates, on the same
+    /// bus, so subscribing to it observes everything the runtime does.
+    pub fn events(&self) -> EventBus {
+        self.events.clone()
+    }
+
     /// Register an LLM interceptor
     pub async fn register_llm_interceptor<I: baml_rt_interceptor::LLMInterceptor>(
         &self,
@@ -242,23 +946,57 @@ impl BamlRuntimeManager {
     /// }
     ///
     /// # tokio_test::block_on(async {
-    /// let mut manager = BamlRuntimeManager::new()?;
+    /// let manager = BamlRuntimeManager::new()?;
     /// manager.register_tool(MyTool).await?;
     /// # Ok::<(), baml_rt::BamlRtError>(())
     /// # }).unwrap();
     /// ```
-    pub async fn register_tool<T: baml_rt_tools::BamlTool>(&mut self, tool: T) -> Result<()> {
+    pub async fn register_tool<T: baml_rt_tools::BamlTool>(&self, tool: T) -> Result<()> {
         let mut registry = self.tool_registry.lock().await;
         registry.register(tool)
     }
 
     /// Execute a tool function by name
     ///
-    /// This will call tool interceptors before and after execution.
+    /// This will call tool interceptors before and after execution, once the
+    /// caller in scope is known to be allowed to use the tool.
     pub async fn execute_tool(&self, name: &str, args: Value) -> Result<Value> {
+        self.execute_tool_for(None, name, args).await
+    }
+
+    /// Execute a tool function by name on behalf of the BAML function
+    /// `function`, if the tool policy allows it
+    ///
+    /// Interceptors see `function` as the tool call's `function_name`.
+    pub async fn execute_tool_for(
+        &self,
+        function: Option<&str>,
+        name: &str,
+        args: Value,
+    ) -> Result<Value> {
         use baml_rt_interceptor::ToolCallContext;
         use std::time::Instant;
 
+        auth::check(Access::Tool(name))?;
+        let (qualified, requires_approval) = {
+            let registry = self.tool_registry.lock().await;
+            let qualified = registry.resolve(name).unwrap_or_else(|_| name.to_string());
+            let requires_approval = registry
+                .get_metadata(&qualified)
+                .is_some_and(|metadata| metadata.requires_approval);
+            (qualified, requires_approval)
+        };
+        self.check_qualified_tool_policy(function, &qualified)?;
+        if requires_approval {
+            let call = ProposedToolCall::new(
+                context::current_or_new(),
+                qualified,
+                function.map(str::to_string),
+                args.clone(),
+            );
+            self.approvals.authorize(call).await?;
+        }
+
         let start = Instant::now();
         let correlation_id = current_correlation_id();
         let metadata = if let Some(correlation_id) = correlation_id {
@@ -270,7 +1008,7 @@ impl BamlRuntimeManager {
         // Build context for interceptors
         let context = ToolCallContext {
             tool_name: name.to_string(),
-            function_name: None, // Could be enhanced to track which function called this tool
+            function_name: function.map(str::to_string),
             args: args.clone(),
             metadata,
             context_id: context::current_or_new(),
@@ -285,17 +1023,29 @@ impl BamlRuntimeManager {
         // If we get here, the decision is Allow (blocking would have returned Err)
         let final_args = args;
 
-        // Execute the tool
-        let registry = self.tool_registry.lock().await;
-        let result = registry.execute(name, final_args).await;
-        drop(registry);
+        // Execute the tool outside the registry lock, so tools run concurrently
+        let executor = self.tool_registry.lock().await.executor(name);
+        let result = match executor {
+            Ok(executor) => {
+                let operation = format!("tool '{}'", name);
+                let call = deadline::enforce(&operation, executor.execute(final_args));
+                cancellation::enforce(&operation, call).await
+            }
+            Err(e) => Err(e),
+        };
 
         // Calculate duration
         let duration = start.elapsed();
         let duration_ms = duration.as_millis() as u64;
 
-        // Notify interceptors of completion
+        // Let interceptors rewrite the result, then notify them of completion
         let interceptor_registry = self.interceptor_registry.lock().await;
+        let result = match result {
+            Ok(value) => Ok(interceptor_registry
+                .transform_tool_result(&context, value)
+                .await),
+            Err(e) => Err(e),
+        };
         interceptor_registry
             .notify_tool_call_complete(&context, &result, duration_ms)
             .await;
@@ -343,7 +1093,7 @@ impl BamlRuntimeManager {
     /// #     async fn execute(&self, _: serde_json::Value) -> baml_rt::Result<serde_json::Value> { Ok(serde_json::json!({})) }
     /// # }
     /// # tokio_test::block_on(async {
-    /// # let mut manager = BamlRuntimeManager::new()?;
+    /// # let manager = BamlRuntimeManager::new()?;
     /// manager.register_tool(WeatherTool).await?;
     ///
     /// // Map BAML union variant to tool
@@ -354,7 +1104,7 @@ impl BamlRuntimeManager {
     /// # }).unwrap();
     /// ```
     pub fn map_baml_variant_to_tool(
-        &mut self,
+        &self,
         baml_variant_name: impl Into<String>,
         tool_function_name: impl Into<String>,
     ) {
@@ -375,7 +1125,23 @@ impl BamlRuntimeManager {
     ///
     /// # Returns
     /// The result of executing the tool function
+    ///
+    /// The call is not attributed to a function, so the tool policy's
+    /// default applies; use
+    /// [`execute_tool_from_function_result`](Self::execute_tool_from_function_result)
+    /// to check it against the tools of the function that chose it.
     pub async fn execute_tool_from_baml_result(&self, baml_result: Value) -> Result<Value> {
+        self.execute_tool_from_function_result(None, baml_result)
+            .await
+    }
+
+    /// Execute a tool from the result of the BAML function `function`, if
+    /// the tool policy lets that function call it
+    pub async fn execute_tool_from_function_result(
+        &self,
+        function: Option<&str>,
+        baml_result: Value,
+    ) -> Result<Value> {
         // Parse the BAML result to extract tool name and args
         let (variant_name, tool_args_value) = self
             .tool_mapper
@@ -390,8 +1156,9 @@ impl BamlRuntimeManager {
             .map_err(|_| BamlRtError::ToolMapperLockPoisoned)?
             .variant_to_tool_name(&variant_name)?;
 
-        // Execute via execute_tool which handles interceptors
-        self.execute_tool(&tool_name, tool_args_value).await
+        // Execute via execute_tool_for which handles interceptors
+        self.execute_tool_for(function, &tool_name, tool_args_value)
+            .await
     }
 }
 
@@ -407,6 +1174,20 @@ impl BamlFunctionExecutor for BamlRuntimeManager {
     }
 }
 
+/// Runs guard classifiers without guarding their own outputs
+struct Unguarded<'a>(&'a BamlRuntimeManager);
+
+#[async_trait]
+impl BamlFunctionExecutor for Unguarded<'_> {
+    async fn execute_function(&self, function_name: &str, args: Value) -> Result<Value> {
+        self.0.invoke_function_unguarded(function_name, args).await
+    }
+
+    fn list_functions(&self) -> Vec<String> {
+        self.0.list_functions()
+    }
+}
+
 impl SchemaLoader for BamlRuntimeManager {
     fn load_schema(&mut self, schema_path: &str) -> Result<()> {
         self.load_schema(schema_path)
@@ -417,14 +1198,103 @@ impl SchemaLoader for BamlRuntimeManager {
     }
 }
 
+/// Functions of a newly loaded schema, compared with the schema it replaced
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SchemaReload {
+    /// Every function of the new schema
+    pub functions: Vec<String>,
+    /// Functions the old schema did not have
+    pub added: Vec<String>,
+    /// Functions of the old schema the new one does not have
+    pub removed: Vec<String>,
+}
+
+/// Everything a schema is loaded with
+struct SchemaSource {
+    baml_src_dir: PathBuf,
+    tool_registry: Arc<TokioMutex<ConcreteToolRegistry>>,
+    tool_mapper: Arc<StdMutex<ToolMapper>>,
+    secrets: Arc<SecretStore>,
+    media_policy: MediaPolicy,
+    dry_run: Option<Arc<DryRun>>,
+    llm_overrides: Option<Arc<LlmOverrides>>,
+}
+
+/// A compiled schema, not yet installed in a manager
+struct LoadedSchema {
+    executor: BamlExecutor,
+    functions: HashMap<String, FunctionSignature>,
+}
+
+impl SchemaSource {
+    /// Compile the schema and discover its functions
+    fn load(self) -> Result<LoadedSchema> {
+        tracing::info!(baml_src_dir = ?self.baml_src_dir, "Loading BAML IL");
+
+        let mut executor = BamlExecutor::load_il(
+            &self.baml_src_dir,
+            self.tool_registry,
+            self.tool_mapper,
+            self.secrets,
+        )?;
+        executor.set_media_policy(self.media_policy);
+        executor.set_dry_run(self.dry_run);
+        executor.set_llm_overrides(self.llm_overrides);
+
+        let functions = executor
+            .list_functions()
+            .into_iter()
+            .map(|name| {
+                let params = executor.function_params(&name).unwrap_or_default();
+                let signature = FunctionSignature {
+                    name: name.clone(),
+                    param_names: params.iter().map(|(name, _)| name.clone()).collect(),
+                    input_types: params.iter().map(|(_, ty)| ty.clone()).collect(),
+                    output_type: executor
+                        .function_output_type(&name)
+                        .unwrap_or(BamlType::Any),
+                    docstring: executor.function_docstring(&name).map(str::to_string),
+                };
+                (name, signature)
+            })
+            .collect();
+        Ok(LoadedSchema {
+            executor,
+            functions,
+        })
+    }
+}
+
 impl Default for BamlRuntimeManager {
     fn default() -> Self {
+        let events = EventBus::new();
         Self {
             function_registry: HashMap::new(),
             executor: None,
             tool_registry: Arc::new(TokioMutex::new(ConcreteToolRegistry::new())),
             tool_mapper: Arc::new(StdMutex::new(ToolMapper::new())),
-            interceptor_registry: Arc::new(TokioMutex::new(InterceptorRegistry::new())),
+            interceptor_registry: Arc::new(TokioMutex::new(
+                InterceptorRegistry::new().with_event_bus(events.clone()),
+            )),
+            stream_token_limiter: None,
+            output_guards: None,
+            repair_policies: HashMap::new(),
+            tool_policy: ToolPolicy::default(),
+            approvals: Arc::new(ApprovalGate::new()),
+            session_store: None,
+            session_history: None,
+            artifact_store: None,
+            kv_store: None,
+            agent_caller: None,
+            task_operations: None,
+            agent_name: None,
+            embeddings: EmbeddingSettings::default(),
+            tool_schema: None,
+            media_policy: MediaPolicy::default(),
+            dry_run: None,
+            llm_overrides: None,
+            secrets: Arc::new(SecretStore::new()),
+            events,
         }
     }
 }
@@ -4,11 +4,23 @@
 //! from the BAML compiler.
 
 use crate::baml_collector::BamlLLMCollector;
-use crate::baml_pre_execution::intercept_llm_call_pre_execution;
-use baml_rt_core::{BamlRtError, Result};
-use baml_rt_interceptor::{InterceptorDecision, InterceptorRegistry};
-use baml_rt_tools::{ToolMapper, ToolRegistry};
-use baml_runtime::{BamlRuntime, FunctionResultStream, RuntimeContextManager};
+use crate::baml_pre_execution::{build_llm_call_context, intercept_llm_call_pre_execution};
+use crate::dry_run::{self, DryRun};
+use crate::embeddings::{ClientConfig, parse_clients};
+use crate::llm_overrides::LlmOverrides;
+use crate::secrets::SecretStore;
+use baml_rt_core::auth::{self, Access};
+use baml_rt_core::baml_value::BamlSchema;
+use baml_rt_core::media::MediaPolicy;
+use baml_rt_core::tenant::{self, Tenant};
+use baml_rt_core::types::{BamlType, ObjectField};
+use baml_rt_core::{BamlRtError, Result, cancellation, context, correlation, deadline};
+use baml_rt_interceptor::{InterceptorRegistry, LLMCallContext};
+use baml_rt_observability::metrics;
+use baml_rt_tools::{ToolExecutor, ToolMapper, ToolRegistry};
+use baml_runtime::client_registry::ClientRegistry;
+use baml_runtime::type_builder::TypeBuilder;
+use baml_runtime::{BamlRuntime, FunctionResult, FunctionResultStream, RuntimeContextManager};
 use baml_types::BamlValue;
 use serde_json::Value;
 use std::collections::HashMap;
@@ -16,13 +28,45 @@ use std::path::Path;
 use std::sync::{Arc, Mutex as StdMutex};
 use std::time::Instant;
 use tokio::sync::Mutex;
+use tokio_util::sync::CancellationToken;
+
+/// Model label of a function whose client is not known
+pub(crate) const UNKNOWN_MODEL: &str = "unknown";
+
+/// Per-call environment variable holding the correlation ID of the request
+/// being served
+///
+/// BAML clients can forward it to the provider so its logs can be joined with
+/// runtime traces, e.g. `headers { "x-correlation-id" env.BAML_CORRELATION_ID }`.
+pub const CORRELATION_ID_ENV: &str = "BAML_CORRELATION_ID";
+
+/// Per-call environment variable holding the A2A context ID of the request
+pub const CONTEXT_ID_ENV: &str = "BAML_CONTEXT_ID";
+
+/// Tag keys under which the IDs are attached to BAML calls and trace events
+pub const CORRELATION_ID_TAG: &str = "correlation_id";
+pub const CONTEXT_ID_TAG: &str = "context_id";
 
 /// BAML execution engine that executes BAML IL
 pub struct BamlExecutor {
     runtime: Arc<BamlRuntime>,
-    ctx_manager: RuntimeContextManager,
+    ctx_manager: Arc<RuntimeContextManager>,
+    /// Enums and classes arguments are converted against
+    schema: BamlSchema,
+    /// Parameters of each function with their types
+    params: HashMap<String, Vec<(String, BamlType)>>,
+    /// Doc comments of the functions that have one
+    docstrings: HashMap<String, String>,
+    /// The client declarations, for embeddings and LLM overrides
+    clients: HashMap<String, ClientConfig>,
+    /// Set when every call is a dry run
+    dry_run: Option<Arc<DryRun>>,
+    /// Sampling parameters forced onto calls
+    llm_overrides: Option<Arc<LlmOverrides>>,
     tool_registry: Arc<Mutex<ToolRegistry>>,
     tool_mapper: Arc<StdMutex<ToolMapper>>,
+    /// API keys for the calls' environment
+    secrets: Arc<SecretStore>,
 }
 
 impl BamlExecutor {
@@ -33,26 +77,14 @@ impl BamlExecutor {
         baml_src_dir: &Path,
         tool_registry: Arc<Mutex<ToolRegistry>>,
         tool_mapper: Arc<StdMutex<ToolMapper>>,
+        secrets: Arc<SecretStore>,
     ) -> Result<Self> {
         tracing::info!(?baml_src_dir, "Loading BAML runtime from directory");
 
-        // Use from_directory which handles feature flags internally
-        // Load environment variables - BAML uses these for API keys
-        let mut env_vars: HashMap<String, String> = HashMap::new();
-
-        // Load OPENROUTER_API_KEY from environment if present
-        if let Ok(api_key) = std::env::var("OPENROUTER_API_KEY") {
-            env_vars.insert("OPENROUTER_API_KEY".to_string(), api_key);
-            tracing::debug!("Loaded OPENROUTER_API_KEY from environment");
-        }
-
-        // Load other common API key environment variables
-        for key in &["OPENAI_API_KEY", "ANTHROPIC_API_KEY", "GOOGLE_API_KEY"] {
-            if let Ok(value) = std::env::var(key) {
-                env_vars.insert(key.to_string(), value);
-                tracing::debug!(api_key = key, "Loaded API key from environment");
-            }
-        }
+        // Use from_directory which handles feature flags internally. Each call
+        // is given its API keys from `secrets`; loading only sees the ones
+        // already fetched, and does not wait for a secrets manager.
+        let env_vars = secrets.cached_env();
 
         let feature_flags = internal_baml_core::feature_flags::FeatureFlags::default();
 
@@ -65,20 +97,41 @@ impl BamlExecutor {
             None, // baml_src_reader
         );
 
+        let (schema, params) = declared_types(&runtime);
+        let sources = read_sources(baml_src_dir);
+        let docstrings = sources
+            .iter()
+            .flat_map(|source| parse_docstrings(source))
+            .collect();
+        let clients = sources
+            .iter()
+            .flat_map(|source| parse_clients(source))
+            .collect();
+
         Ok(Self {
             runtime: Arc::new(runtime),
-            ctx_manager,
+            ctx_manager: Arc::new(ctx_manager),
+            schema,
+            params,
+            docstrings,
+            clients,
+            dry_run: None,
+            llm_overrides: None,
             tool_registry,
             tool_mapper,
+            secrets,
         })
     }
 
     /// Execute a BAML function using the compiled IL
+    ///
+    /// `type_builder` extends the schema's dynamic types for this call.
     pub async fn execute_function(
         &self,
         function_name: &str,
         args: Value,
         interceptor_registry: Option<Arc<Mutex<InterceptorRegistry>>>,
+        type_builder: Option<&TypeBuilder>,
     ) -> Result<Value> {
         tracing::debug!(
             function = function_name,
@@ -87,21 +140,17 @@ impl BamlExecutor {
         );
 
         // Convert JSON args to BamlValue map
-        let params = self.json_to_baml_map(&args)?;
+        let params = self.args_to_baml(function_name, &args)?;
 
         // Call the function
-        // Load environment variables for API keys
-        let mut env_vars = HashMap::new();
-        if let Ok(api_key) = std::env::var("OPENROUTER_API_KEY") {
-            env_vars.insert("OPENROUTER_API_KEY".to_string(), api_key);
-        }
-        for key in &["OPENAI_API_KEY", "ANTHROPIC_API_KEY", "GOOGLE_API_KEY"] {
-            if let Ok(value) = std::env::var(key) {
-                env_vars.insert(key.to_string(), value);
-            }
-        }
-        let tags = None;
-        let cancel_tripwire = baml_runtime::TripWire::new(None);
+        let ids = RequestIds::current();
+        let env_vars = ids.env_vars(&self.secrets).await;
+        let tags = ids.tags();
+        // The guard stops the request watch once the call returns
+        let cancel = CancellationToken::new();
+        cancel_with_request(&cancel);
+        let _cancel_guard = cancel.clone().drop_guard();
+        let cancel_tripwire = baml_runtime::TripWire::new(Some(cancel));
 
         // Track execution start time for LLM interceptor callbacks
         let _start_time = Instant::now();
@@ -112,33 +161,55 @@ impl BamlExecutor {
             .map(|registry| BamlLLMCollector::new(registry.clone(), function_name.to_string()));
 
         // Pre-execution interception: intercept LLM calls before they're sent
-        if let Some(ref registry) = interceptor_registry {
-            match intercept_llm_call_pre_execution(
-                &self.runtime,
-                function_name,
-                &params,
-                &self.ctx_manager,
-                registry,
-                env_vars.clone(),
-                false, // stream = false for regular calls
-            )
-            .await
-            {
-                Ok(InterceptorDecision::Allow) => {
-                    // Allow the call to proceed
-                }
-                Ok(InterceptorDecision::Block(msg)) => {
-                    // Block the call - return error
-                    return Err(BamlRtError::BamlRuntime(format!(
-                        "LLM call blocked by interceptor: {}",
-                        msg
-                    )));
-                }
-                Err(e) => {
-                    // Interceptor error - return it
-                    return Err(e);
+        let llm_context = match interceptor_registry {
+            Some(ref registry) => Some(
+                intercept_llm_call_pre_execution(
+                    &self.runtime,
+                    function_name,
+                    &params,
+                    &self.ctx_manager,
+                    registry,
+                    type_builder,
+                    env_vars.clone(),
+                    false, // stream = false for regular calls
+                )
+                .await?,
+            ),
+            None => None,
+        };
+
+        if let Some(dry_run) = dry_run::current().or_else(|| self.dry_run.clone()) {
+            let context = match llm_context {
+                Some(context) => context,
+                None => {
+                    build_llm_call_context(
+                        &self.runtime,
+                        function_name,
+                        &params,
+                        &self.ctx_manager,
+                        type_builder,
+                        env_vars,
+                        false,
+                    )
+                    .await?
                 }
+            };
+            tracing::info!(
+                function = function_name,
+                client = context.client.as_str(),
+                "Dry run: LLM call skipped"
+            );
+            let json_value = dry_run.response(function_name, &context);
+            if let Some(registry) = &interceptor_registry {
+                registry
+                    .lock()
+                    .await
+                    .notify_llm_call_complete(&context, &Ok(json_value.clone()), 0)
+                    .await;
             }
+            return self
+                .finish_call(interceptor_registry.as_ref(), Some(&context), json_value)
+                .await;
         }
 
         // Wire up the collector to track function execution
@@ -149,21 +220,22 @@ impl BamlExecutor {
         } else {
             None
         };
-
-        let (result, _call_id) = self
-            .runtime
-            .call_function(
-                function_name.to_string(),
-                &params,
-                &self.ctx_manager,
-                None,       // type_builder
-                None,       // client_registry
-                collectors, // collectors - now wired up to track execution
-                env_vars,
-                tags,
-                cancel_tripwire,
-            )
-            .await;
+        let client_registry = self.override_registry(function_name, &env_vars);
+
+        let call = self.runtime.call_function(
+            function_name.to_string(),
+            &params,
+            &self.ctx_manager,
+            type_builder,
+            client_registry.as_ref(),
+            collectors, // collectors - now wired up to track execution
+            env_vars,
+            Some(&tags),
+            cancel_tripwire,
+        );
+        let operation = format!("BAML function '{}'", function_name);
+        let call = deadline::enforce(&operation, async { Ok(call.await) });
+        let (result, _call_id) = cancellation::enforce(&operation, call).await?;
 
         let function_result = result.map_err(|e| BamlRtError::ExecutionFailed { source: e })?;
 
@@ -191,6 +263,31 @@ impl BamlExecutor {
             }
         }
 
+        self.finish_call(
+            interceptor_registry.as_ref(),
+            llm_context.as_ref(),
+            json_value,
+        )
+        .await
+    }
+
+    /// Pass a call's result through the interceptors' transforms, then run
+    /// the tool it picks, if it picks one
+    async fn finish_call(
+        &self,
+        interceptor_registry: Option<&Arc<Mutex<InterceptorRegistry>>>,
+        llm_context: Option<&LLMCallContext>,
+        json_value: Value,
+    ) -> Result<Value> {
+        // Interceptors may rewrite the result before a tool is picked from it
+        let json_value = match (interceptor_registry, llm_context) {
+            (Some(registry), Some(context)) => {
+                let registry = registry.lock().await;
+                registry.transform_llm_result(context, json_value).await
+            }
+            _ => json_value,
+        };
+
         if let Some(tool_result) =
             maybe_execute_tool_from_result(&self.tool_registry, &self.tool_mapper, &json_value)
                 .await?
@@ -204,11 +301,15 @@ impl BamlExecutor {
     /// Execute a BAML function with streaming support
     ///
     /// Returns a stream of incremental results as the function executes.
-    pub fn execute_function_stream(
+    /// Cancelling `cancel` trips the call's `TripWire` and aborts the generation,
+    /// as does canceling the request or its deadline passing.
+    pub async fn execute_function_stream(
         &self,
         function_name: &str,
         args: Value,
-    ) -> Result<FunctionResultStream> {
+        cancel: CancellationToken,
+        type_builder: Option<TypeBuilder>,
+    ) -> Result<BamlStream> {
         tracing::debug!(
             function = function_name,
             args = ?args,
@@ -216,21 +317,15 @@ impl BamlExecutor {
         );
 
         // Convert JSON args to BamlValue map
-        let params = self.json_to_baml_map(&args)?;
+        let params = self.args_to_baml(function_name, &args)?;
 
         // Create stream function call
-        // Load environment variables for API keys
-        let mut env_vars = HashMap::new();
-        if let Ok(api_key) = std::env::var("OPENROUTER_API_KEY") {
-            env_vars.insert("OPENROUTER_API_KEY".to_string(), api_key);
-        }
-        for key in &["OPENAI_API_KEY", "ANTHROPIC_API_KEY", "GOOGLE_API_KEY"] {
-            if let Ok(value) = std::env::var(key) {
-                env_vars.insert(key.to_string(), value);
-            }
-        }
-        let tags = None;
-        let cancel_tripwire = baml_runtime::TripWire::new(None);
+        let ids = RequestIds::current();
+        let env_vars = ids.env_vars(&self.secrets).await;
+        let tags = ids.tags();
+        cancel_with_request(&cancel);
+        let cancel_tripwire = baml_runtime::TripWire::new(Some(cancel));
+        let client_registry = self.override_registry(function_name, &env_vars);
 
         let stream = self
             .runtime
@@ -238,21 +333,126 @@ impl BamlExecutor {
                 function_name.to_string(),
                 &params,
                 &self.ctx_manager,
-                None, // type_builder
-                None, // client_registry
+                type_builder.as_ref(),
+                client_registry.as_ref(),
                 None, // collectors
                 env_vars,
                 cancel_tripwire,
-                tags,
+                Some(&tags),
             )
             .map_err(|e| BamlRtError::BamlRuntime(format!("Failed to create stream: {}", e)))?;
 
-        Ok(stream)
+        Ok(BamlStream {
+            stream,
+            function_name: function_name.to_string(),
+            model: self.function_client(function_name),
+            _runtime: self.runtime.clone(),
+            ctx_manager: self.ctx_manager.clone(),
+            type_builder,
+            client_registry,
+        })
+    }
+
+    /// Describe the LLM call a streaming invocation of `function_name` will make
+    ///
+    /// Used as the context of stream chunk interception. The request is built
+    /// but never sent.
+    pub async fn stream_call_context(
+        &self,
+        function_name: &str,
+        args: &Value,
+        type_builder: Option<&TypeBuilder>,
+    ) -> Result<LLMCallContext> {
+        let params = self.args_to_baml(function_name, args)?;
+        build_llm_call_context(
+            &self.runtime,
+            function_name,
+            &params,
+            &self.ctx_manager,
+            type_builder,
+            RequestIds::current().env_vars(&self.secrets).await,
+            true,
+        )
+        .await
+    }
+
+    /// Read file media arguments as `policy` allows
+    pub fn set_media_policy(&mut self, policy: MediaPolicy) {
+        self.schema.set_media_policy(policy);
+    }
+
+    /// Skip the LLM in every call, returning what `dry_run` says instead
+    pub fn set_dry_run(&mut self, dry_run: Option<Arc<DryRun>>) {
+        self.dry_run = dry_run;
+    }
+
+    /// Force the sampling parameters of `overrides` onto calls, or stop
+    /// with `None`
+    pub fn set_llm_overrides(&mut self, overrides: Option<Arc<LlmOverrides>>) {
+        self.llm_overrides = overrides;
+    }
+
+    /// A registry with `function_name`'s client under the LLM overrides,
+    /// when they change its calls
+    fn override_registry(
+        &self,
+        function_name: &str,
+        env_vars: &HashMap<String, String>,
+    ) -> Option<ClientRegistry> {
+        let overrides = self.llm_overrides.as_ref()?;
+        let client = self.function_client(function_name)?;
+        overrides.client_registry(function_name, &client, self.clients.get(&client), env_vars)
+    }
+
+    /// Take the calls' API keys from `secrets`
+    pub fn set_secret_store(&mut self, secrets: Arc<SecretStore>) {
+        self.secrets = secrets;
+    }
+
+    /// Parameters of a BAML function with their types, in declaration order
+    pub fn function_params(&self, function_name: &str) -> Option<&[(String, BamlType)]> {
+        self.params.get(function_name).map(Vec::as_slice)
+    }
+
+    /// Return type of a BAML function
+    pub fn function_output_type(&self, function_name: &str) -> Option<BamlType> {
+        self.runtime
+            .ir()
+            .walk_functions()
+            .find(|function| function.name() == function_name)
+            .map(|function| BamlType::parse(&function.output().to_string()))
+    }
+
+    /// Client a BAML function calls, as named in its `client` field
+    pub fn function_client(&self, function_name: &str) -> Option<String> {
+        self.runtime
+            .ir()
+            .walk_functions()
+            .find(|function| function.name() == function_name)
+            .and_then(|function| function.client_name())
+    }
+
+    /// The `///` comment above a BAML function's declaration
+    pub fn function_docstring(&self, function_name: &str) -> Option<&str> {
+        self.docstrings.get(function_name).map(String::as_str)
+    }
+
+    /// A `client<llm>` declared in the schema
+    pub fn client_config(&self, name: &str) -> Option<&ClientConfig> {
+        self.clients.get(name)
+    }
+
+    /// Names of the `client<llm>` declarations, sorted
+    pub fn client_names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.clients.keys().map(String::as_str).collect();
+        names.sort_unstable();
+        names
     }
 
-    /// Get a reference to the context manager (needed for streaming)
-    pub fn ctx_manager(&self) -> &RuntimeContextManager {
-        &self.ctx_manager
+    /// The environment a call made now would get: API keys, the tenant's
+    /// variables, and the request's IDs
+    pub async fn call_env(&self) -> HashMap<String, String> {
+        RequestIds::current().env_vars(&self.secrets).await
     }
 
     /// List all available function names from the loaded BAML runtime
@@ -263,53 +463,198 @@ impl BamlExecutor {
             .collect()
     }
 
-    /// Convert JSON Value to BamlMap<String, BamlValue>
-    fn json_to_baml_map(&self, value: &Value) -> Result<baml_types::BamlMap<String, BamlValue>> {
-        let obj = value
-            .as_object()
-            .ok_or_else(|| BamlRtError::InvalidArgument("Expected JSON object".to_string()))?;
+    /// Convert JSON args against the parameter types of `function_name`
+    fn args_to_baml(
+        &self,
+        function_name: &str,
+        args: &Value,
+    ) -> Result<baml_types::BamlMap<String, BamlValue>> {
+        let params = self.function_params(function_name).unwrap_or_default();
+        self.schema.args_to_baml(args, params)
+    }
+}
+
+/// The enums, classes and function parameters declared in the schema
+///
+/// Types are read back from their BAML syntax, so types the conversion does
+/// not know, such as tuples, are converted untyped.
+fn declared_types(runtime: &BamlRuntime) -> (BamlSchema, HashMap<String, Vec<(String, BamlType)>>) {
+    let ir = runtime.ir();
+    let mut schema = BamlSchema::new();
+    for declared in ir.walk_enums() {
+        let values = declared
+            .walk_values()
+            .map(|value| value.name().to_string())
+            .collect();
+        schema.add_enum(declared.name(), values);
+    }
+    for declared in ir.walk_classes() {
+        let fields = declared
+            .walk_fields()
+            .map(|field| ObjectField {
+                name: field.name().to_string(),
+                ty: BamlType::parse(&field.r#type().to_string()),
+            })
+            .collect();
+        schema.add_class(declared.name(), fields);
+    }
+    let params = ir
+        .walk_functions()
+        .map(|function| {
+            let inputs = function
+                .inputs()
+                .iter()
+                .map(|(name, ty)| (name.clone(), BamlType::parse(&ty.to_string())))
+                .collect();
+            (function.name().to_string(), inputs)
+        })
+        .collect();
+    (schema, params)
+}
 
-        let mut map = baml_types::BamlMap::new();
-        for (k, v) in obj {
-            map.insert(k.clone(), self.json_to_baml_value(v)?);
+/// The `.baml` files under `dir`
+///
+/// The runtime's IR keeps neither comments nor client options, so these are
+/// read from source. Files that cannot be read are skipped; the runtime has
+/// already loaded them, so this only loses their docstrings and clients.
+fn read_sources(dir: &Path) -> Vec<String> {
+    let mut sources = Vec::new();
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return sources;
+    };
+    for path in entries.flatten().map(|entry| entry.path()) {
+        if path.is_dir() {
+            sources.extend(read_sources(&path));
+        } else if path.extension().is_some_and(|ext| ext == "baml")
+            && let Ok(source) = std::fs::read_to_string(&path)
+        {
+            sources.push(source);
         }
-        Ok(map)
-    }
-
-    /// Convert JSON Value to BamlValue
-    #[allow(clippy::only_used_in_recursion)]
-    fn json_to_baml_value(&self, value: &Value) -> Result<BamlValue> {
-        match value {
-            Value::String(s) => Ok(BamlValue::String(s.clone())),
-            Value::Number(n) => {
-                if let Some(i) = n.as_i64() {
-                    Ok(BamlValue::Int(i))
-                } else if let Some(f) = n.as_f64() {
-                    Ok(BamlValue::Float(f))
-                } else {
-                    Err(BamlRtError::TypeConversion(format!(
-                        "Invalid number: {}",
-                        n
-                    )))
-                }
-            }
-            Value::Bool(b) => Ok(BamlValue::Bool(*b)),
-            Value::Array(arr) => {
-                let mut vec = Vec::new();
-                for item in arr {
-                    vec.push(self.json_to_baml_value(item)?);
-                }
-                Ok(BamlValue::List(vec))
-            }
-            Value::Object(obj) => {
-                let mut map = baml_types::BamlMap::new();
-                for (k, v) in obj {
-                    map.insert(k.clone(), self.json_to_baml_value(v)?);
-                }
-                Ok(BamlValue::Map(map))
+    }
+    sources
+}
+
+/// Pair each `function` declaration in `source` with the `///` lines
+/// directly above it
+fn parse_docstrings(source: &str) -> Vec<(String, String)> {
+    let mut docstrings = Vec::new();
+    let mut comment: Vec<&str> = Vec::new();
+    for line in source.lines().map(str::trim) {
+        if let Some(text) = line.strip_prefix("///") {
+            comment.push(text.strip_prefix(' ').unwrap_or(text));
+            continue;
+        }
+        if let Some(declaration) = line.strip_prefix("function ")
+            && !comment.is_empty()
+        {
+            let name: String = declaration
+                .trim_start()
+                .chars()
+                .take_while(|c| c.is_ascii_alphanumeric() || *c == '_')
+                .collect();
+            if !name.is_empty() {
+                docstrings.push((name, comment.join("\n")));
             }
-            Value::Null => Ok(BamlValue::Null),
         }
+        comment.clear();
+    }
+    docstrings
+}
+
+/// A streaming BAML call that owns everything it needs to run
+///
+/// Holds no borrow of the executor, so callers can let go of the runtime
+/// manager before running it; a long generation then does not hold up other
+/// calls or a schema reload.
+pub struct BamlStream {
+    stream: FunctionResultStream,
+    function_name: String,
+    model: Option<String>,
+    /// Keeps the runtime the stream was created from alive across a reload
+    _runtime: Arc<BamlRuntime>,
+    ctx_manager: Arc<RuntimeContextManager>,
+    type_builder: Option<TypeBuilder>,
+    /// The function's client under the LLM overrides, if any
+    client_registry: Option<ClientRegistry>,
+}
+
+impl BamlStream {
+    /// Run the call to completion, passing each partial result to `on_event`
+    ///
+    /// Records the function's streaming invocation metrics when it ends.
+    pub async fn run<F>(
+        &mut self,
+        on_event: F,
+        env_vars: HashMap<String, String>,
+    ) -> anyhow::Result<FunctionResult>
+    where
+        F: FnMut(FunctionResult) + Send,
+    {
+        let start = Instant::now();
+        let (result, _call_id) = self
+            .stream
+            .run(
+                None::<fn()>, // on_tick
+                Some(on_event),
+                &self.ctx_manager,
+                self.type_builder.as_ref(),
+                self.client_registry.as_ref(),
+                env_vars,
+            )
+            .await;
+        metrics::record_baml_function(
+            &self.function_name,
+            self.model.as_deref().unwrap_or(UNKNOWN_MODEL),
+            if result.is_ok() { "success" } else { "error" },
+            true,
+            start.elapsed(),
+        );
+        result
+    }
+}
+
+/// Correlation and context IDs of the request a BAML call serves, and the
+/// tenant it serves it for
+struct RequestIds {
+    correlation_id: String,
+    context_id: String,
+    tenant: Option<Tenant>,
+}
+
+impl RequestIds {
+    /// IDs in scope, or fresh ones for calls made outside a request
+    fn current() -> Self {
+        Self {
+            correlation_id: correlation::current_or_new().to_string(),
+            context_id: context::current_or_new().to_string(),
+            tenant: tenant::current_tenant(),
+        }
+    }
+
+    /// Environment for the call: provider API keys from `secrets`,
+    /// overridden by the tenant's own environment, plus the request IDs
+    async fn env_vars(&self, secrets: &SecretStore) -> HashMap<String, String> {
+        let mut env_vars = secrets.env().await;
+        if let Some(tenant) = &self.tenant {
+            env_vars.extend(tenant.config().env().clone());
+        }
+        env_vars.insert(CORRELATION_ID_ENV.to_string(), self.correlation_id.clone());
+        env_vars.insert(CONTEXT_ID_ENV.to_string(), self.context_id.clone());
+        env_vars
+    }
+
+    /// Call tags, which BAML attaches to the call's trace events
+    fn tags(&self) -> HashMap<String, BamlValue> {
+        HashMap::from([
+            (
+                CORRELATION_ID_TAG.to_string(),
+                BamlValue::String(self.correlation_id.clone()),
+            ),
+            (
+                CONTEXT_ID_TAG.to_string(),
+                BamlValue::String(self.context_id.clone()),
+            ),
+        ])
     }
 }
 
@@ -327,11 +672,48 @@ async fn maybe_execute_tool_from_result(
         return Ok(None);
     };
 
-    let registry = tool_registry.lock().await;
-    let tool_result = registry.execute(&tool_name, tool_args).await?;
+    auth::check(Access::Tool(&tool_name))?;
+    let executor = tool_registry.lock().await.executor(&tool_name)?;
+    let operation = format!("tool '{}'", tool_name);
+    let call = deadline::enforce(&operation, executor.execute(tool_args));
+    let tool_result = cancellation::enforce(&operation, call).await?;
     Ok(Some(tool_result))
 }
 
+/// Cancel `cancel` when the current request is canceled or its deadline passes
+///
+/// The watch stops early if `cancel` is cancelled first.
+fn cancel_with_request(cancel: &CancellationToken) {
+    let deadline = deadline::current_deadline();
+    let request = cancellation::current_cancellation();
+    if deadline.is_none() && request.is_none() {
+        return;
+    }
+    let Ok(handle) = tokio::runtime::Handle::try_current() else {
+        return;
+    };
+    let cancel = cancel.clone();
+    handle.spawn(async move {
+        let expired = async {
+            match deadline {
+                Some(deadline) => tokio::time::sleep_until(deadline.instant()).await,
+                None => std::future::pending().await,
+            }
+        };
+        let canceled = async {
+            match request {
+                Some(request) => request.cancelled().await,
+                None => std::future::pending().await,
+            }
+        };
+        tokio::select! {
+            _ = expired => cancel.cancel(),
+            _ = canceled => cancel.cancel(),
+            _ = cancel.cancelled() => {}
+        }
+    });
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -388,6 +770,33 @@ mod tests {
         assert_eq!(tool_result["echo"]["message"], "hello");
     }
 
+    #[tokio::test]
+    async fn request_ids_follow_the_correlation_scope() {
+        let id = baml_rt_core::CorrelationId::new("corr-test".to_string());
+        let ids = correlation::with_correlation_id(id, async { RequestIds::current() }).await;
+
+        let env_vars = ids.env_vars(&SecretStore::new()).await;
+
+        assert_eq!(env_vars[CORRELATION_ID_ENV], "corr-test");
+        assert_eq!(ids.tags()[CORRELATION_ID_TAG].as_str(), Some("corr-test"));
+        assert_eq!(env_vars[CONTEXT_ID_ENV], ids.context_id);
+    }
+
+    #[tokio::test]
+    async fn tenant_env_overrides_the_process_env() {
+        use baml_rt_core::tenant::TenantConfig;
+
+        let acme = Tenant::new(
+            "acme".into(),
+            Arc::new(TenantConfig::new().with_env("ANTHROPIC_API_KEY", "sk-acme")),
+        );
+        let ids = tenant::with_tenant(Some(acme), async { RequestIds::current() }).await;
+
+        let env_vars = ids.env_vars(&SecretStore::new()).await;
+
+        assert_eq!(env_vars["ANTHROPIC_API_KEY"], "sk-acme");
+    }
+
     #[tokio::test]
     async fn leaves_non_tool_results_untouched() {
         let registry = Arc::new(Mutex::new(ToolRegistry::new()));
@@ -400,4 +809,34 @@ mod tests {
 
         assert!(tool_result.is_none());
     }
+
+    #[test]
+    fn docstrings_are_the_comment_lines_directly_above_a_function() {
+        let source = r##"
+/// Greets the crew.
+///
+/// Keeps it short.
+function Greet(name: string) -> string {
+  client "openai/gpt-4o-mini"
+  prompt #"Hello {{ name }}"#
+}
+
+/// Describes the class, not the function below it
+class Rite {
+  name string
+}
+
+function Choose(message: string) -> Rite {
+  client "openai/gpt-4o-mini"
+  prompt #"{{ message }}"#
+}
+"##;
+        assert_eq!(
+            parse_docstrings(source),
+            [(
+                "Greet".to_string(),
+                "Greets the crew.\n\nKeeps it short.".to_string()
+            )]
+        );
+    }
 }
@@ -5,8 +5,9 @@
 
 use baml_rt_core::context;
 use baml_rt_core::{BamlRtError, Result};
-use baml_rt_interceptor::{InterceptorDecision, InterceptorRegistry, LLMCallContext};
+use baml_rt_interceptor::{InterceptorRegistry, LLMCallContext};
 use baml_runtime::RuntimeContextManager;
+use baml_runtime::type_builder::TypeBuilder;
 use baml_types::{BamlMap, BamlValue};
 use serde_json::json;
 use std::collections::HashMap;
@@ -57,19 +58,16 @@ pub fn extract_context_from_http_request(
     }
 }
 
-/// Intercept an LLM call before execution using build_request
-///
-/// This builds the HTTP request, extracts context, runs interceptors,
-/// and returns the decision. If blocked, returns an error.
-pub async fn intercept_llm_call_pre_execution(
+/// Build the LLM call context of a function without sending the request
+pub async fn build_llm_call_context(
     runtime: &baml_runtime::BamlRuntime,
     function_name: &str,
     params: &BamlMap<String, BamlValue>,
     ctx_manager: &RuntimeContextManager,
-    interceptor_registry: &Arc<Mutex<InterceptorRegistry>>,
+    type_builder: Option<&TypeBuilder>,
     env_vars: HashMap<String, String>,
     stream: bool,
-) -> Result<InterceptorDecision> {
+) -> Result<LLMCallContext> {
     // Build the HTTP request to get LLM call details
     // This doesn't actually send the request, just builds it
     let http_request_result = runtime
@@ -77,7 +75,7 @@ pub async fn intercept_llm_call_pre_execution(
             function_name.to_string(),
             params,
             ctx_manager,
-            None, // type_builder
+            type_builder,
             None, // client_registry
             env_vars,
             stream,
@@ -88,7 +86,37 @@ pub async fn intercept_llm_call_pre_execution(
         http_request_result.map_err(|e| BamlRtError::RequestBuildFailed(e.to_string()))?;
 
     // Extract LLM call context from the HTTP request
-    let context = extract_context_from_http_request(&http_request, function_name);
+    Ok(extract_context_from_http_request(
+        &http_request,
+        function_name,
+    ))
+}
+
+/// Intercept an LLM call before execution using build_request
+///
+/// This builds the HTTP request, extracts context, and runs interceptors.
+/// Returns the context of the call they allowed, or an error if blocked, so
+/// the call's result can be passed through their transforms.
+pub async fn intercept_llm_call_pre_execution(
+    runtime: &baml_runtime::BamlRuntime,
+    function_name: &str,
+    params: &BamlMap<String, BamlValue>,
+    ctx_manager: &RuntimeContextManager,
+    interceptor_registry: &Arc<Mutex<InterceptorRegistry>>,
+    type_builder: Option<&TypeBuilder>,
+    env_vars: HashMap<String, String>,
+    stream: bool,
+) -> Result<LLMCallContext> {
+    let context = build_llm_call_context(
+        runtime,
+        function_name,
+        params,
+        ctx_manager,
+        type_builder,
+        env_vars,
+        stream,
+    )
+    .await?;
 
     tracing::debug!(
         client = context.client,
@@ -96,12 +124,16 @@ pub async fn intercept_llm_call_pre_execution(
         function = function_name,
         "Pre-execution interception: extracted LLM call context"
     );
+    tracing::trace!(
+        function = function_name,
+        prompt = ?context.prompt,
+        "Pre-execution interception: LLM prompt"
+    );
 
-    // Run interceptors
+    // Run interceptors; the registry turns a block into an error
     let registry = interceptor_registry.lock().await;
-    let decision = registry.intercept_llm_call(&context).await?;
+    registry.intercept_llm_call(&context).await?;
     drop(registry);
 
-    // Return the decision
-    Ok(decision)
+    Ok(context)
 }
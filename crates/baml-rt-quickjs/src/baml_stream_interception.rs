@@ -0,0 +1,147 @@
+//! LLM interception of streaming calls
+//!
+//! Each partial output of a streaming BAML call is shown to the registered
+//! LLM interceptors through
+//! [`LLMInterceptor::on_llm_stream_chunk`](baml_rt_interceptor::LLMInterceptor::on_llm_stream_chunk)
+//! before it reaches JavaScript. An interceptor that blocks a chunk cancels
+//! the call, so moderation can stop a generation as soon as it goes wrong
+//! rather than after the full response has been paid for.
+
+use baml_rt_core::Result;
+use baml_rt_interceptor::{InterceptorRegistry, LLMCallContext};
+use serde_json::{Value, json};
+use std::sync::Arc;
+use tokio::sync::{Mutex, mpsc};
+use tokio_util::sync::CancellationToken;
+
+/// Runs LLM interceptors over the chunks of one streaming call
+pub struct StreamChunkObserver {
+    registry: Arc<Mutex<InterceptorRegistry>>,
+    context: LLMCallContext,
+    next_index: usize,
+}
+
+impl StreamChunkObserver {
+    pub fn new(registry: Arc<Mutex<InterceptorRegistry>>, context: LLMCallContext) -> Self {
+        Self {
+            registry,
+            context,
+            next_index: 0,
+        }
+    }
+
+    /// Context passed to the interceptors
+    pub fn context(&self) -> &LLMCallContext {
+        &self.context
+    }
+
+    /// Run the interceptors on the next chunk, failing if any blocks it
+    pub async fn observe(&mut self, chunk: &Value) -> Result<()> {
+        let index = self.next_index;
+        self.next_index += 1;
+        let registry = self.registry.lock().await;
+        registry
+            .intercept_llm_stream_chunk(&self.context, chunk, index)
+            .await?;
+        Ok(())
+    }
+}
+
+/// Forward chunks to `tx`, letting `observer` inspect each one first
+///
+/// When a chunk is blocked, `cancel` is cancelled, an `{"error": ...}` value
+/// is sent in its place, and later chunks are dropped. Returns whether the
+/// stream was blocked.
+pub(crate) async fn forward_stream_chunks(
+    mut chunks: mpsc::UnboundedReceiver<Value>,
+    tx: mpsc::Sender<Value>,
+    mut observer: Option<StreamChunkObserver>,
+    cancel: CancellationToken,
+) -> bool {
+    while let Some(chunk) = chunks.recv().await {
+        if let Some(observer) = observer.as_mut()
+            && let Err(e) = observer.observe(&chunk).await
+        {
+            tracing::info!(
+                function = observer.context().function_name.as_str(),
+                error = %e,
+                "BAML stream stopped by LLM interceptor"
+            );
+            cancel.cancel();
+            if let Err(e) = tx.send(json!({ "error": e.to_string() })).await {
+                tracing::warn!(error = ?e, "Stream channel send failed");
+            }
+            return true;
+        }
+        if let Err(e) = tx.send(chunk).await {
+            tracing::warn!(error = ?e, "Stream channel send failed");
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use baml_rt_core::ids::ContextId;
+    use baml_rt_interceptor::{InterceptorDecision, LLMInterceptor};
+
+    /// Blocks the first chunk containing "forbidden"
+    struct Moderator;
+
+    #[async_trait]
+    impl LLMInterceptor for Moderator {
+        async fn intercept_llm_call(&self, _: &LLMCallContext) -> Result<InterceptorDecision> {
+            Ok(InterceptorDecision::Allow)
+        }
+
+        async fn on_llm_call_complete(&self, _: &LLMCallContext, _: &Result<Value>, _: u64) {}
+
+        async fn on_llm_stream_chunk(
+            &self,
+            _: &LLMCallContext,
+            chunk: &Value,
+            index: usize,
+        ) -> Result<InterceptorDecision> {
+            Ok(match chunk.as_str() {
+                Some(text) if text.contains("forbidden") => {
+                    InterceptorDecision::Block(format!("chunk {} is not allowed", index))
+                }
+                _ => InterceptorDecision::Allow,
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn blocked_chunk_cancels_the_stream() {
+        let mut registry = InterceptorRegistry::new();
+        registry.register_llm_interceptor(Moderator);
+        let observer = StreamChunkObserver::new(
+            Arc::new(Mutex::new(registry)),
+            LLMCallContext {
+                client: "test".to_string(),
+                model: "test".to_string(),
+                function_name: "Summarize".to_string(),
+                context_id: ContextId::new("ctx-stream".to_string()),
+                prompt: Value::Null,
+                metadata: json!({}),
+            },
+        );
+
+        let (chunk_tx, chunk_rx) = mpsc::unbounded_channel();
+        let (tx, mut rx) = mpsc::channel(8);
+        for chunk in ["The", "The forbidden", "The forbidden word"] {
+            chunk_tx.send(json!(chunk)).unwrap();
+        }
+        drop(chunk_tx);
+
+        let cancel = CancellationToken::new();
+        assert!(forward_stream_chunks(chunk_rx, tx, Some(observer), cancel.clone()).await);
+        assert!(cancel.is_cancelled());
+        assert_eq!(rx.recv().await, Some(json!("The")));
+        let error = rx.recv().await.unwrap();
+        assert!(error["error"].as_str().unwrap().contains("chunk 1"));
+        assert_eq!(rx.recv().await, None);
+    }
+}
@@ -6,8 +6,9 @@
 use crate::baml::BamlRuntimeManager;
 use crate::quickjs_bridge::QuickJSBridge;
 use baml_rt_core::Result;
+use baml_rt_core::deadline::{self, Deadline};
 use std::sync::Arc;
-use tokio::sync::Mutex;
+use tokio::sync::RwLock;
 
 /// A BAML execution context with isolated QuickJS runtime
 ///
@@ -22,6 +23,9 @@ pub struct BamlContext {
     pub quickjs: QuickJSBridge,
     /// Optional context-specific metadata
     pub metadata: Option<ContextMetadata>,
+    /// When work in this context must finish, typically set by the
+    /// transport from the request's timeout
+    pub deadline: Option<Deadline>,
 }
 
 /// Optional metadata for context tracking
@@ -47,11 +51,11 @@ impl BamlContext {
     /// use baml_rt::context::{BamlContext, ContextMetadata};
     /// use baml_rt::baml::BamlRuntimeManager;
     /// use std::sync::Arc;
-    /// use tokio::sync::Mutex;
+    /// use tokio::sync::RwLock;
     ///
     /// # tokio_test::block_on(async {
-    /// let baml_manager = Arc::new(Mutex::new(BamlRuntimeManager::new()?));
-    /// baml_manager.lock().await.load_schema("baml_src")?;
+    /// let baml_manager = Arc::new(RwLock::new(BamlRuntimeManager::new()?));
+    /// baml_manager.write().await.load_schema("baml_src")?;
     ///
     /// // Create isolated context for user/request
     /// let mut context = BamlContext::new(
@@ -72,7 +76,7 @@ impl BamlContext {
     /// # }).unwrap();
     /// ```
     pub async fn new(
-        baml_manager: Arc<Mutex<BamlRuntimeManager>>,
+        baml_manager: Arc<RwLock<BamlRuntimeManager>>,
         metadata: Option<ContextMetadata>,
     ) -> Result<Self> {
         tracing::debug!(
@@ -83,9 +87,28 @@ impl BamlContext {
         Ok(Self {
             quickjs: QuickJSBridge::new(baml_manager).await?,
             metadata,
+            deadline: None,
         })
     }
 
+    /// Bound work run through [`BamlContext::scope`] by `deadline`
+    pub fn with_deadline(mut self, deadline: Deadline) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Run `fut` under this context's deadline, if it has one, so BAML calls,
+    /// tool executions, and JS evaluations beneath it give up when it passes
+    pub async fn scope<F, T>(&self, fut: F) -> Result<T>
+    where
+        F: std::future::Future<Output = Result<T>>,
+    {
+        match self.deadline {
+            Some(deadline) => deadline::with_deadline(deadline, "context", fut).await,
+            None => fut.await,
+        }
+    }
+
     ///    /// Get the context ID if metadata is available
     pub fn context_id(&self) -> Option<&str> {
         self.metadata.as_ref().map(|m| m.context_id.as_str())
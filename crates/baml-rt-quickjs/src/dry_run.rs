@@ -0,0 +1,120 @@
+//! Dry runs: BAML calls that skip the LLM
+//!
+//! Under a [`DryRun`], `invoke_function` builds the provider request and runs
+//! the LLM interceptors as usual, but never sends it. The call returns the
+//! function's stub response, or the rendered request when it has none, and
+//! that result goes through interceptor transforms and tool dispatch like a
+//! real one, so agent logic can be tested without provider calls.
+//!
+//! Set a dry run for every call with
+//! [`BamlRuntimeManager::set_dry_run`](crate::baml::BamlRuntimeManager::set_dry_run),
+//! or for the calls made within a future with [`with_dry_run`]. Streaming
+//! calls are not covered.
+
+use baml_rt_interceptor::LLMCallContext;
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Arc;
+
+tokio::task_local! {
+    static DRY_RUN: Arc<DryRun>;
+}
+
+/// What dry-run BAML calls return
+#[derive(Debug, Clone, Default)]
+pub struct DryRun {
+    stubs: HashMap<String, Value>,
+    default_stub: Option<Value>,
+}
+
+impl DryRun {
+    /// A dry run returning the rendered request of every call
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Return `response` from calls to `function`, as if the model produced
+    /// it
+    pub fn with_stub(mut self, function: impl Into<String>, response: Value) -> Self {
+        self.stubs.insert(function.into(), response);
+        self
+    }
+
+    /// Return `response` from calls to functions without a stub of their own
+    pub fn with_default_stub(mut self, response: Value) -> Self {
+        self.default_stub = Some(response);
+        self
+    }
+
+    /// The result of a dry-run call to `function` that would have sent the
+    /// request `context` describes
+    pub fn response(&self, function: &str, context: &LLMCallContext) -> Value {
+        self.stubs
+            .get(function)
+            .or(self.default_stub.as_ref())
+            .cloned()
+            .unwrap_or_else(|| rendered_request(context))
+    }
+}
+
+/// The request a call would have sent, marked as a dry run
+pub fn rendered_request(context: &LLMCallContext) -> Value {
+    json!({
+        "dry_run": true,
+        "function": context.function_name,
+        "client": context.client,
+        "model": context.model,
+        "prompt": context.prompt,
+        "metadata": context.metadata,
+    })
+}
+
+/// The dry run of the calls in scope, if any
+pub fn current() -> Option<Arc<DryRun>> {
+    DRY_RUN.try_with(Arc::clone).ok()
+}
+
+/// Run `fut` with its BAML calls under `dry_run`
+pub async fn with_dry_run<F: Future>(dry_run: Arc<DryRun>, fut: F) -> F::Output {
+    DRY_RUN.scope(dry_run, fut).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use baml_rt_core::ids::ContextId;
+
+    fn context() -> LLMCallContext {
+        LLMCallContext {
+            client: "Fast".to_string(),
+            model: "gpt-4o-mini".to_string(),
+            function_name: "Summarize".to_string(),
+            context_id: ContextId::from("ctx-dry"),
+            prompt: json!([{ "role": "user", "content": "Summarize this" }]),
+            metadata: json!({}),
+        }
+    }
+
+    #[tokio::test]
+    async fn stubs_take_precedence_over_the_rendered_request() {
+        let dry_run = Arc::new(DryRun::new().with_stub("Classify", json!("spam")));
+        assert!(current().is_none());
+
+        with_dry_run(dry_run, async {
+            let dry_run = current().expect("dry run in scope");
+            assert_eq!(dry_run.response("Classify", &context()), json!("spam"));
+            let rendered = dry_run.response("Summarize", &context());
+            assert_eq!(rendered["dry_run"], true);
+            assert_eq!(rendered["model"], "gpt-4o-mini");
+            assert_eq!(rendered["prompt"][0]["content"], "Summarize this");
+        })
+        .await;
+
+        let fallback = DryRun::new().with_default_stub(json!({ "ok": true }));
+        assert_eq!(
+            fallback.response("Summarize", &context()),
+            json!({ "ok": true })
+        );
+    }
+}
@@ -0,0 +1,463 @@
+//! Text embeddings through the schema's LLM clients
+//!
+//! [`BamlRuntimeManager::embed`](crate::baml::BamlRuntimeManager::embed)
+//! turns text into vectors with an [`EmbeddingProvider`]. Unless one is set
+//! explicitly, the provider is built from a `client<llm>` of the loaded
+//! schema, so embeddings use the same endpoint and API key as the agent's
+//! functions; only OpenAI-compatible providers have an embeddings endpoint.
+//! Texts are sent in batches and their vectors cached per client and model,
+//! so repeated texts cost one request. JavaScript calls `embed(text, model)`
+//! with a string or an array of strings.
+
+use async_trait::async_trait;
+use baml_rt_core::{BamlRtError, Result};
+use serde_json::{Value, json};
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+
+/// Texts sent to the provider in one request
+pub const DEFAULT_BATCH_SIZE: usize = 100;
+
+/// Vectors kept by the embedding cache
+pub const DEFAULT_CACHE_CAPACITY: usize = 10_000;
+
+/// An option of a `client<llm>` declaration
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ClientOption {
+    /// A literal, e.g. `model "gpt-4o"`
+    Literal(String),
+    /// An environment variable, e.g. `api_key env.OPENAI_API_KEY`
+    Env(String),
+}
+
+/// A `client<llm>` declared in the schema
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ClientConfig {
+    pub provider: String,
+    pub retry_policy: Option<String>,
+    pub options: HashMap<String, ClientOption>,
+    /// Nested option blocks such as `headers`, by name
+    pub option_blocks: HashMap<String, HashMap<String, ClientOption>>,
+}
+
+impl ClientConfig {
+    /// Value of option `name`, reading variables from `env`
+    pub fn option(&self, name: &str, env: &HashMap<String, String>) -> Option<String> {
+        match self.options.get(name)? {
+            ClientOption::Literal(value) => Some(value.clone()),
+            ClientOption::Env(var) => env.get(var).cloned(),
+        }
+    }
+}
+
+/// The client declarations of a `.baml` source, `client<llm> Name { ... }` or
+/// `client Name { ... }`, by name
+///
+/// `provider`, `retry_policy`, and the single-line entries of `options` and
+/// of its nested blocks such as `headers` are read; deeper nesting is
+/// skipped.
+pub fn parse_clients(source: &str) -> HashMap<String, ClientConfig> {
+    let mut clients = HashMap::new();
+    let mut current: Option<(String, ClientConfig)> = None;
+    let mut depth = 0usize;
+    let mut options_depth = None;
+    let mut block: Option<String> = None;
+    for line in source.lines() {
+        let line = strip_comment(line).trim();
+        if current.is_none() {
+            // `client Name` alone is a function's client, not a declaration
+            let declared = line
+                .strip_prefix("client<llm>")
+                .or_else(|| line.strip_prefix("client "))
+                .and_then(|rest| rest.strip_suffix('{'));
+            if let Some(name) = declared.map(str::trim)
+                && !name.is_empty()
+            {
+                current = Some((name.to_string(), ClientConfig::default()));
+                depth = 1;
+            }
+            continue;
+        }
+        let Some((_, config)) = current.as_mut() else {
+            continue;
+        };
+        let mut words = line.splitn(2, char::is_whitespace);
+        let key = words.next().unwrap_or_default();
+        let value = words.next().unwrap_or_default().trim();
+        let is_entry = !value.is_empty() && !value.ends_with('{');
+        if depth == 1 && key == "provider" {
+            config.provider = unquote(value).to_string();
+        } else if depth == 1 && key == "retry_policy" {
+            config.retry_policy = Some(value.to_string());
+        } else if depth == 1 && key == "options" && value == "{" {
+            options_depth = Some(depth + 1);
+        } else if options_depth == Some(depth) && value == "{" {
+            block = Some(unquote(key).to_string());
+        } else if options_depth == Some(depth) && is_entry {
+            config.options.insert(key.to_string(), client_option(value));
+        } else if let Some(block) = &block
+            && options_depth == Some(depth - 1)
+            && is_entry
+        {
+            config
+                .option_blocks
+                .entry(block.clone())
+                .or_default()
+                .insert(unquote(key).to_string(), client_option(value));
+        }
+        depth += line.matches('{').count();
+        depth = depth.saturating_sub(line.matches('}').count());
+        if options_depth.is_some_and(|options| depth <= options) {
+            block = None;
+        }
+        if options_depth.is_some_and(|options| depth < options) {
+            options_depth = None;
+        }
+        if depth == 0
+            && let Some((name, config)) = current.take()
+        {
+            clients.insert(name, config);
+        }
+    }
+    clients
+}
+
+/// `line` without a trailing `//` comment; `//` within quotes is kept
+fn strip_comment(line: &str) -> &str {
+    let mut quoted = false;
+    let mut previous = ' ';
+    for (index, c) in line.char_indices() {
+        match c {
+            '"' => quoted = !quoted,
+            '/' if previous == '/' && !quoted => return &line[..index - 1],
+            _ => {}
+        }
+        previous = c;
+    }
+    line
+}
+
+fn client_option(value: &str) -> ClientOption {
+    match value.strip_prefix("env.") {
+        Some(var) => ClientOption::Env(var.to_string()),
+        None => ClientOption::Literal(unquote(value).to_string()),
+    }
+}
+
+fn unquote(value: &str) -> &str {
+    value.trim_matches('"')
+}
+
+/// Turns texts into vectors
+#[async_trait]
+pub trait EmbeddingProvider: Send + Sync {
+    /// One vector for each of `texts`, in order
+    async fn embed(&self, model: &str, texts: &[String]) -> Result<Vec<Vec<f32>>>;
+}
+
+/// An OpenAI-compatible `/embeddings` endpoint
+pub struct OpenAiEmbeddings {
+    client: reqwest::Client,
+    base_url: String,
+    api_key: Option<String>,
+}
+
+impl OpenAiEmbeddings {
+    pub fn new(base_url: impl Into<String>, api_key: Option<String>) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+            api_key,
+        }
+    }
+
+    /// The endpoint of a schema client, reading its variables from `env`
+    ///
+    /// Fails for providers without an OpenAI-compatible embeddings endpoint.
+    pub fn for_client(
+        name: &str,
+        config: &ClientConfig,
+        env: &HashMap<String, String>,
+    ) -> Result<Self> {
+        let (default_base_url, default_key_var) = match config.provider.as_str() {
+            "openai" => (Some("https://api.openai.com/v1"), Some("OPENAI_API_KEY")),
+            "openai-generic" => (None, None),
+            "ollama" => (Some("http://localhost:11434/v1"), None),
+            provider => {
+                return Err(BamlRtError::Configuration(format!(
+                    "Client '{}' uses provider '{}', which has no embeddings endpoint",
+                    name, provider
+                )));
+            }
+        };
+        let base_url = config
+            .option("base_url", env)
+            .or(default_base_url.map(str::to_string))
+            .ok_or_else(|| {
+                BamlRtError::Configuration(format!("Client '{}' has no base_url", name))
+            })?;
+        let api_key = config
+            .option("api_key", env)
+            .or_else(|| default_key_var.and_then(|var| env.get(var).cloned()));
+        Ok(Self::new(base_url, api_key))
+    }
+}
+
+impl std::fmt::Debug for OpenAiEmbeddings {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OpenAiEmbeddings")
+            .field("base_url", &self.base_url)
+            .finish_non_exhaustive()
+    }
+}
+
+#[async_trait]
+impl EmbeddingProvider for OpenAiEmbeddings {
+    async fn embed(&self, model: &str, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        let embedding_error = |e: &dyn std::fmt::Display| {
+            BamlRtError::BamlRuntime(format!("Embedding failed: {}", e))
+        };
+        let body = serde_json::to_vec(&json!({ "model": model, "input": texts }))?;
+        let mut request = self
+            .client
+            .post(format!("{}/embeddings", self.base_url))
+            .header("content-type", "application/json")
+            .body(body);
+        if let Some(api_key) = &self.api_key {
+            request = request.bearer_auth(api_key);
+        }
+        let response = request.send().await.map_err(|e| embedding_error(&e))?;
+        let status = response.status();
+        let body = response.text().await.map_err(|e| embedding_error(&e))?;
+        if !status.is_success() {
+            return Err(embedding_error(&format!("{} {}", status, body)));
+        }
+        let body: Value = serde_json::from_str(&body)?;
+        let mut data: Vec<(u64, Vec<f32>)> = body
+            .get("data")
+            .and_then(Value::as_array)
+            .ok_or_else(|| embedding_error(&"response has no data"))?
+            .iter()
+            .map(|item| {
+                let index = item.get("index").and_then(Value::as_u64).unwrap_or(0);
+                let vector = item
+                    .get("embedding")
+                    .and_then(Value::as_array)
+                    .map(|values| {
+                        values
+                            .iter()
+                            .filter_map(Value::as_f64)
+                            .map(|value| value as f32)
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                (index, vector)
+            })
+            .collect();
+        if data.len() != texts.len() {
+            return Err(embedding_error(&format!(
+                "expected {} vectors, got {}",
+                texts.len(),
+                data.len()
+            )));
+        }
+        data.sort_by_key(|(index, _)| *index);
+        Ok(data.into_iter().map(|(_, vector)| vector).collect())
+    }
+}
+
+/// Recently computed vectors, by client, model and text
+///
+/// The oldest vector is dropped first once `capacity` are kept.
+pub struct EmbeddingCache {
+    vectors: Mutex<(HashMap<CacheKey, Arc<Vec<f32>>>, VecDeque<CacheKey>)>,
+    capacity: usize,
+}
+
+type CacheKey = (String, String, String);
+
+impl EmbeddingCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            vectors: Mutex::new((HashMap::new(), VecDeque::new())),
+            capacity,
+        }
+    }
+
+    pub fn get(&self, client: &str, model: &str, text: &str) -> Option<Arc<Vec<f32>>> {
+        let vectors = self.vectors.lock().unwrap_or_else(|e| e.into_inner());
+        let key = (client.to_string(), model.to_string(), text.to_string());
+        vectors.0.get(&key).cloned()
+    }
+
+    pub fn insert(&self, client: &str, model: &str, text: &str, vector: Arc<Vec<f32>>) {
+        if self.capacity == 0 {
+            return;
+        }
+        let mut guard = self.vectors.lock().unwrap_or_else(|e| e.into_inner());
+        let (vectors, order) = &mut *guard;
+        let key = (client.to_string(), model.to_string(), text.to_string());
+        if vectors.insert(key.clone(), vector).is_none() {
+            order.push_back(key);
+        }
+        while order.len() > self.capacity {
+            if let Some(oldest) = order.pop_front() {
+                vectors.remove(&oldest);
+            }
+        }
+    }
+}
+
+impl Default for EmbeddingCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_CACHE_CAPACITY)
+    }
+}
+
+/// How a manager computes embeddings
+pub struct EmbeddingSettings {
+    /// Used instead of a schema client when set
+    pub provider: Option<Arc<dyn EmbeddingProvider>>,
+    /// Schema client whose endpoint is used; the only OpenAI-compatible
+    /// client when `None`
+    pub client: Option<String>,
+    pub batch_size: usize,
+    pub cache: EmbeddingCache,
+}
+
+impl Default for EmbeddingSettings {
+    fn default() -> Self {
+        Self {
+            provider: None,
+            client: None,
+            batch_size: DEFAULT_BATCH_SIZE,
+            cache: EmbeddingCache::default(),
+        }
+    }
+}
+
+/// The client embeddings go through when none is named: the only one of
+/// `clients` with an OpenAI-compatible provider
+pub fn default_embedding_client<'a>(
+    clients: impl IntoIterator<Item = (&'a str, &'a ClientConfig)>,
+) -> Result<&'a str> {
+    let candidates: Vec<&str> = clients
+        .into_iter()
+        .filter(|(_, config)| {
+            matches!(
+                config.provider.as_str(),
+                "openai" | "openai-generic" | "ollama"
+            )
+        })
+        .map(|(name, _)| name)
+        .collect();
+    match candidates.as_slice() {
+        [name] => Ok(name),
+        [] => Err(BamlRtError::Configuration(
+            "No client of the schema has an embeddings endpoint".to_string(),
+        )),
+        names => Err(BamlRtError::Configuration(format!(
+            "Several clients could compute embeddings ({}); choose one with \
+             set_embedding_client",
+            names.join(", ")
+        ))),
+    }
+}
+
+/// Vectors for `texts`, from `cache` where possible and otherwise from
+/// `provider` in batches of at most `batch_size` distinct texts
+pub async fn embed_cached(
+    provider: &dyn EmbeddingProvider,
+    cache: &EmbeddingCache,
+    client: &str,
+    model: &str,
+    texts: &[String],
+    batch_size: usize,
+) -> Result<Vec<Vec<f32>>> {
+    let mut vectors: HashMap<&str, Arc<Vec<f32>>> = HashMap::new();
+    let mut missing: Vec<String> = Vec::new();
+    for text in texts {
+        if vectors.contains_key(text.as_str()) || missing.contains(text) {
+            continue;
+        }
+        match cache.get(client, model, text) {
+            Some(vector) => {
+                vectors.insert(text, vector);
+            }
+            None => missing.push(text.clone()),
+        }
+    }
+    let mut fetched = Vec::with_capacity(missing.len());
+    for batch in missing.chunks(batch_size.max(1)) {
+        let batch_vectors = provider.embed(model, batch).await?;
+        fetched.extend(batch.iter().zip(batch_vectors.into_iter().map(Arc::new)));
+    }
+    for (text, vector) in fetched {
+        cache.insert(client, model, text, vector.clone());
+        vectors.insert(text, vector);
+    }
+    Ok(texts
+        .iter()
+        .map(|text| {
+            vectors
+                .get(text.as_str())
+                .map_or_else(Vec::new, |vector| vector.as_ref().clone())
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clients_are_read_from_source() {
+        let clients = parse_clients(
+            r##"
+            client<llm> Embedder {
+              provider "openai-generic"
+              options {
+                model "gpt-4o-mini" // the chat model
+                base_url "http://localhost:8000/v1"
+                api_key env.EMBEDDER_KEY
+                headers {
+                  "x-team" "rites"
+                }
+              }
+            }
+
+            function Greet(name: string) -> string {
+              client Embedder
+              prompt #"Hi {{ name }}"#
+            }
+
+            client Chat {
+              provider openai-generic
+              retry_policy Exponential
+              options {
+                model "deepseek/deepseek-chat"
+              }
+            }
+            "##,
+        );
+        assert_eq!(clients.len(), 2);
+        assert_eq!(clients["Chat"].provider, "openai-generic");
+        assert_eq!(clients["Chat"].retry_policy.as_deref(), Some("Exponential"));
+        let embedder = &clients["Embedder"];
+        assert_eq!(embedder.provider, "openai-generic");
+        assert_eq!(embedder.options.len(), 3);
+        assert_eq!(
+            embedder.option_blocks["headers"]["x-team"],
+            ClientOption::Literal("rites".to_string())
+        );
+        assert_eq!(
+            embedder.options["api_key"],
+            ClientOption::Env("EMBEDDER_KEY".to_string())
+        );
+
+        let env = HashMap::from([("EMBEDDER_KEY".to_string(), "sk-test".to_string())]);
+        assert_eq!(embedder.option("api_key", &env).as_deref(), Some("sk-test"));
+        let endpoint = OpenAiEmbeddings::for_client("Embedder", embedder, &env).unwrap();
+        assert_eq!(endpoint.base_url, "http://localhost:8000/v1");
+    }
+}
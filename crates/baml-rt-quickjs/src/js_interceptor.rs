@@ -0,0 +1,187 @@
+//! LLM interceptors defined in agent JavaScript.
+//!
+//! `registerLLMInterceptor(fn, { name, priority })` adds `fn` to the
+//! manager's LLM pipeline as a [`JsLlmInterceptor`], so governance logic can
+//! ship in the agent package. `fn` may be async and is called with an event
+//! for each hook:
+//!
+//! - `{ phase: "before", context }` before the request is sent. Returning
+//!   `false` or `{ action: "block", reason }` blocks the call.
+//! - `{ phase: "after", context, result }` with the parsed result of a call
+//!   that is not streamed. Returning `{ action: "modify", result }` replaces
+//!   it.
+//! - `{ phase: "error", context, error, durationMs }` when a call fails. The
+//!   return value is ignored.
+//!
+//! Any other return value allows the call and keeps its result. A function
+//! that throws abstains, like any failing interceptor. `context` holds the
+//! call's `client`, `model`, `functionName`, `contextId`, `prompt`, and
+//! `metadata`.
+
+use crate::js_value_converter::{js_value_facade_to_value, value_to_js_value_facade};
+use async_trait::async_trait;
+use baml_rt_core::{BamlRtError, Result};
+use baml_rt_interceptor::{InterceptorDecision, LLMCallContext, LLMInterceptor};
+use quickjs_runtime::values::{CachedJsFunctionRef, JsValueFacade};
+use serde_json::{Value, json};
+
+/// Reason given for a call blocked without one
+const DEFAULT_BLOCK_REASON: &str = "blocked by JavaScript interceptor";
+
+/// An LLM interceptor calling a JavaScript function
+pub struct JsLlmInterceptor {
+    function: CachedJsFunctionRef,
+}
+
+impl JsLlmInterceptor {
+    pub fn new(function: CachedJsFunctionRef) -> Self {
+        Self { function }
+    }
+
+    /// Call the function with `event`, awaiting the promise it returns
+    async fn call(&self, event: Value) -> Result<Value> {
+        let returned = self
+            .function
+            .invoke_function(vec![value_to_js_value_facade(event)])
+            .await
+            .map_err(|e| BamlRtError::QuickJsWithSource {
+                context: "JavaScript LLM interceptor failed".to_string(),
+                source: Box::new(e),
+            })?;
+        let returned = match returned {
+            JsValueFacade::JsPromise { cached_promise } => {
+                match cached_promise.get_promise_result().await.map_err(|e| {
+                    BamlRtError::QuickJsWithSource {
+                        context: "JavaScript LLM interceptor failed".to_string(),
+                        source: Box::new(e),
+                    }
+                })? {
+                    Ok(value) => value,
+                    Err(reason) => {
+                        let reason = js_value_facade_to_value(reason)
+                            .await
+                            .unwrap_or(Value::Null);
+                        return Err(BamlRtError::BamlRuntime(format!(
+                            "JavaScript LLM interceptor rejected: {}",
+                            reason
+                        )));
+                    }
+                }
+            }
+            value => value,
+        };
+        js_value_facade_to_value(returned).await
+    }
+}
+
+#[async_trait]
+impl LLMInterceptor for JsLlmInterceptor {
+    async fn intercept_llm_call(&self, context: &LLMCallContext) -> Result<InterceptorDecision> {
+        let returned = self
+            .call(json!({ "phase": "before", "context": context_value(context) }))
+            .await?;
+        Ok(decision(&returned))
+    }
+
+    async fn on_llm_call_complete(
+        &self,
+        context: &LLMCallContext,
+        result: &Result<Value>,
+        duration_ms: u64,
+    ) {
+        let Err(error) = result else {
+            return;
+        };
+        let event = json!({
+            "phase": "error",
+            "context": context_value(context),
+            "error": error.to_string(),
+            "durationMs": duration_ms,
+        });
+        if let Err(e) = self.call(event).await {
+            tracing::warn!(
+                function = context.function_name.as_str(),
+                error = ?e,
+                "JavaScript LLM interceptor failed"
+            );
+        }
+    }
+
+    async fn on_llm_result_transform(
+        &self,
+        context: &LLMCallContext,
+        result: Value,
+    ) -> Result<Value> {
+        let returned = self
+            .call(json!({
+                "phase": "after",
+                "context": context_value(context),
+                "result": result.clone(),
+            }))
+            .await?;
+        Ok(modified_result(returned).unwrap_or(result))
+    }
+}
+
+/// The call context as the JavaScript function sees it
+fn context_value(context: &LLMCallContext) -> Value {
+    json!({
+        "client": context.client,
+        "model": context.model,
+        "functionName": context.function_name,
+        "contextId": context.context_id.as_str(),
+        "prompt": context.prompt,
+        "metadata": context.metadata,
+    })
+}
+
+/// Read a `before` hook's return value as a decision
+fn decision(returned: &Value) -> InterceptorDecision {
+    let blocked = match returned {
+        Value::Bool(allowed) => !allowed,
+        returned => returned.get("action").and_then(Value::as_str) == Some("block"),
+    };
+    if !blocked {
+        return InterceptorDecision::Allow;
+    }
+    let reason = returned
+        .get("reason")
+        .and_then(Value::as_str)
+        .unwrap_or(DEFAULT_BLOCK_REASON);
+    InterceptorDecision::Block(reason.to_string())
+}
+
+/// The replacement an `after` hook returned, if it returned one
+fn modified_result(mut returned: Value) -> Option<Value> {
+    if returned.get("action").and_then(Value::as_str) != Some("modify") {
+        return None;
+    }
+    returned.as_object_mut()?.remove("result")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn return_values_are_read_as_decisions() {
+        let blocks = |returned: Value| match decision(&returned) {
+            InterceptorDecision::Allow => None,
+            InterceptorDecision::Block(reason) => Some(reason),
+        };
+        assert_eq!(blocks(Value::Null), None);
+        assert_eq!(blocks(json!(true)), None);
+        assert_eq!(blocks(json!({ "action": "allow" })), None);
+        assert_eq!(blocks(json!(false)).as_deref(), Some(DEFAULT_BLOCK_REASON));
+        assert_eq!(
+            blocks(json!({ "action": "block", "reason": "no PII" })).as_deref(),
+            Some("no PII")
+        );
+
+        assert_eq!(
+            modified_result(json!({ "action": "modify", "result": "[redacted]" })),
+            Some(json!("[redacted]"))
+        );
+        assert_eq!(modified_result(json!({ "result": "ignored" })), None);
+    }
+}
@@ -1,50 +1,198 @@
 //! Direct conversion between JsValueFacade and serde_json::Value
 //!
-//! This avoids JSON.stringify/parse roundtrips where possible for better performance
+//! Objects and arrays are converted property by property, so host functions
+//! can take native JS values without a JSON.stringify/parse round trip.
+//! `Uint8Array`s become [`Bytes`] values and back.
 
+use baml_rt_core::types::Bytes;
 use baml_rt_core::{BamlRtError, Result};
-use quickjs_runtime::values::{JsValueConvertable, JsValueFacade};
-use serde_json::Value;
+use quickjs_runtime::jsutils::JsError;
+use quickjs_runtime::values::{JsValueFacade, TypedArrayType};
+use serde_json::{Map, Number, Value};
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
 
-/// Convert JsValueFacade directly to serde_json::Value
+/// Largest integer a JS number holds exactly
+const MAX_SAFE_INTEGER: f64 = 9_007_199_254_740_991.0;
+
+type Conversion = Pin<Box<dyn Future<Output = Result<Option<Value>>> + Send>>;
+
+/// Convert JsValueFacade to serde_json::Value, descending into objects and arrays
+///
+/// Follows `JSON.stringify`: `undefined` and functions are left out of
+/// objects and become `null` in arrays and at the top level, as do `NaN` and
+/// the infinities. Integral numbers stay integers as long as they are exact.
+pub async fn js_value_facade_to_value(js_value: JsValueFacade) -> Result<Value> {
+    Ok(convert(js_value).await?.unwrap_or(Value::Null))
+}
+
+/// Convert a host function argument carrying a JSON value
 ///
-/// Uses available methods on JsValueFacade to extract values without string serialization
-/// For complex nested structures, falls back to using JSON.stringify in JavaScript
-pub fn js_value_facade_to_value(js_value: JsValueFacade) -> Result<Value> {
-    // Use the available methods on JsValueFacade to determine type and extract values
+/// Native JS values are converted directly. Strings are parsed as JSON, for
+/// callers that still pass `JSON.stringify`'d arguments.
+pub async fn json_arg_to_value(js_value: JsValueFacade) -> Result<Value> {
     if js_value.is_string() {
-        Ok(Value::String(js_value.get_str().to_string()))
-    } else if js_value.is_bool() {
-        // Try to get boolean value - if method exists
-        // Since we don't know the exact API, use a workaround
-        // Convert via string representation as fallback for now
-        let str_val = js_value.get_str();
-        if str_val == "true" {
-            Ok(Value::Bool(true))
-        } else if str_val == "false" {
-            Ok(Value::Bool(false))
-        } else {
-            // For complex types, we need to use JSON.stringify in JS
-            // This is still more efficient than doing it multiple times
-            Err(BamlRtError::TypeConversion(
-                "Complex type - use JSON.stringify in JavaScript".to_string(),
-            ))
-        }
-    } else if js_value.is_null_or_undefined() {
-        Ok(Value::Null)
-    } else {
-        // For numbers, arrays, objects, and other complex types,
-        // we'll need to use JSON.stringify in JavaScript before passing to Rust
-        // This is a limitation of the JsValueFacade API
-        Err(BamlRtError::TypeConversion(
-            "Complex type - use JSON.stringify in JavaScript".to_string(),
-        ))
+        return Ok(serde_json::from_str(js_value.get_str())?);
     }
+    js_value_facade_to_value(js_value).await
 }
 
 /// Convert serde_json::Value to JsValueFacade
 ///
-/// This uses the JsValueConvertable trait implementation for Value
+/// Integers that fit in an `i32` become JS integers, other numbers doubles.
+/// [`Bytes`] values become `Uint8Array`s.
 pub fn value_to_js_value_facade(value: Value) -> JsValueFacade {
-    value.to_js_value_facade()
+    if let Some(bytes) = Bytes::from_value(&value) {
+        return JsValueFacade::TypedArray {
+            buffer: bytes.into_inner(),
+            array_type: TypedArrayType::Uint8,
+        };
+    }
+    match value {
+        Value::Null => JsValueFacade::Null,
+        Value::Bool(val) => JsValueFacade::new_bool(val),
+        Value::Number(number) => match number.as_i64().and_then(|i| i32::try_from(i).ok()) {
+            Some(val) => JsValueFacade::new_i32(val),
+            None => JsValueFacade::new_f64(number.as_f64().unwrap_or(f64::NAN)),
+        },
+        Value::String(val) => JsValueFacade::new_str(&val),
+        Value::Array(items) => JsValueFacade::Array {
+            val: items.into_iter().map(value_to_js_value_facade).collect(),
+        },
+        Value::Object(map) => JsValueFacade::Object {
+            val: map
+                .into_iter()
+                .map(|(key, value)| (key, value_to_js_value_facade(value)))
+                .collect(),
+        },
+    }
+}
+
+/// `None` for the values `JSON.stringify` leaves out
+fn convert(js_value: JsValueFacade) -> Conversion {
+    Box::pin(async move {
+        let value = match js_value {
+            JsValueFacade::Undefined
+            | JsValueFacade::Function { .. }
+            | JsValueFacade::JsFunction { .. } => return Ok(None),
+            JsValueFacade::Null => Value::Null,
+            JsValueFacade::Boolean { val } => Value::Bool(val),
+            JsValueFacade::I32 { val } => Value::from(val),
+            JsValueFacade::F64 { val } => number_value(val),
+            JsValueFacade::String { val } => Value::String(val.to_string()),
+            JsValueFacade::JsonStr { json } => serde_json::from_str(&json)?,
+            JsValueFacade::SerdeValue { value } => value,
+            JsValueFacade::TypedArray { buffer, .. } => Bytes(buffer).to_value(),
+            JsValueFacade::Array { val } => array_value(val).await?,
+            JsValueFacade::JsArray { cached_array } => {
+                array_value(cached_array.get_array().await.map_err(js_error)?).await?
+            }
+            JsValueFacade::Object { val } => object_value(val).await?,
+            JsValueFacade::JsObject { cached_object } => {
+                object_value(cached_object.get_object().await.map_err(js_error)?).await?
+            }
+            _ => {
+                return Err(BamlRtError::TypeConversion(
+                    "Promises and errors cannot be converted to JSON".to_string(),
+                ));
+            }
+        };
+        Ok(Some(value))
+    })
+}
+
+async fn array_value(items: Vec<JsValueFacade>) -> Result<Value> {
+    let mut values = Vec::with_capacity(items.len());
+    for item in items {
+        values.push(convert(item).await?.unwrap_or(Value::Null));
+    }
+    Ok(Value::Array(values))
+}
+
+async fn object_value(properties: HashMap<String, JsValueFacade>) -> Result<Value> {
+    let mut map = Map::new();
+    for (key, property) in properties {
+        if let Some(value) = convert(property).await? {
+            map.insert(key, value);
+        }
+    }
+    Ok(Value::Object(map))
+}
+
+fn number_value(val: f64) -> Value {
+    if val.fract() == 0.0 && val.abs() <= MAX_SAFE_INTEGER {
+        Value::from(val as i64)
+    } else {
+        Number::from_f64(val).map_or(Value::Null, Value::Number)
+    }
+}
+
+fn js_error(err: JsError) -> BamlRtError {
+    BamlRtError::TypeConversion(format!("Failed to read JS value: {}", err))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn nested_values_round_trip() {
+        let value = json!({
+            "name": "probe",
+            "count": 3,
+            "ratio": 2.5,
+            "big": 1_099_511_627_776i64,
+            "flags": [true, false, null],
+            "nested": { "items": [{ "id": 1 }, { "id": 2 }] }
+        });
+        let converted = js_value_facade_to_value(value_to_js_value_facade(value.clone()))
+            .await
+            .unwrap();
+        assert_eq!(converted, value);
+    }
+
+    #[tokio::test]
+    async fn values_json_cannot_hold_follow_json_stringify() {
+        let object = JsValueFacade::Object {
+            val: HashMap::from([
+                ("kept".to_string(), JsValueFacade::new_f64(4.0)),
+                ("dropped".to_string(), JsValueFacade::Undefined),
+            ]),
+        };
+        let array = JsValueFacade::Array {
+            val: vec![JsValueFacade::Undefined, JsValueFacade::new_f64(f64::NAN)],
+        };
+        assert_eq!(
+            js_value_facade_to_value(object).await.unwrap(),
+            json!({ "kept": 4 })
+        );
+        assert_eq!(
+            js_value_facade_to_value(array).await.unwrap(),
+            json!([null, null])
+        );
+        assert_eq!(
+            json_arg_to_value(JsValueFacade::new_str(r#"{"a":1}"#))
+                .await
+                .unwrap(),
+            json!({ "a": 1 })
+        );
+    }
+
+    #[tokio::test]
+    async fn uint8_arrays_map_to_bytes() {
+        let bytes = JsValueFacade::TypedArray {
+            buffer: vec![1, 2, 3],
+            array_type: TypedArrayType::Uint8,
+        };
+        assert_eq!(
+            js_value_facade_to_value(bytes).await.unwrap(),
+            Bytes::new([1, 2, 3]).to_value()
+        );
+        assert!(matches!(
+            value_to_js_value_facade(Bytes::new([1, 2, 3]).to_value()),
+            JsValueFacade::TypedArray { buffer, .. } if buffer == [1, 2, 3]
+        ));
+    }
 }
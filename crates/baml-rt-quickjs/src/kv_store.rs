@@ -0,0 +1,240 @@
+//! Small key-value state kept between invocations
+//!
+//! JavaScript reads and writes through the `kvGet(key, options)`,
+//! `kvSet(key, value, options)`, and `kvDelete(key, options)` globals. Keys
+//! live in a [`KvNamespace`] of the agent and the request's tenant, so
+//! agents sharing a store never see each other's values; passing
+//! `{ scope: "context" }` narrows it further to the current context. Values
+//! are JSON.
+
+use async_trait::async_trait;
+use baml_rt_core::ids::{ContextId, TenantId};
+use baml_rt_core::{BamlRtError, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use tokio::sync::Mutex;
+
+/// Agent name used for the namespaces of an unnamed agent
+pub const DEFAULT_AGENT_NAMESPACE: &str = "default";
+
+/// Where a key lives
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KvNamespace {
+    pub agent: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tenant: Option<TenantId>,
+    /// Set for keys scoped to one context
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context: Option<ContextId>,
+}
+
+impl KvNamespace {
+    /// The agent-wide namespace of `agent`
+    pub fn agent(agent: impl Into<String>) -> Self {
+        Self {
+            agent: agent.into(),
+            tenant: None,
+            context: None,
+        }
+    }
+
+    pub fn with_tenant(mut self, tenant: Option<TenantId>) -> Self {
+        self.tenant = tenant;
+        self
+    }
+
+    pub fn with_context(mut self, context: Option<ContextId>) -> Self {
+        self.context = context;
+        self
+    }
+}
+
+/// Storage for agent key-value state
+///
+/// Implement this to keep state in a database or cache shared between
+/// processes; [`InMemoryKvStore`] covers a single process and
+/// [`FileKvStore`] survives restarts.
+#[async_trait]
+pub trait KvStore: Send + Sync {
+    /// The value of `key`, or `None` if it is unset
+    async fn get(&self, namespace: &KvNamespace, key: &str) -> Result<Option<Value>>;
+
+    /// Set `key` to `value`, replacing any previous value
+    async fn set(&self, namespace: &KvNamespace, key: &str, value: Value) -> Result<()>;
+
+    /// Unset `key`, returning whether it was set
+    async fn delete(&self, namespace: &KvNamespace, key: &str) -> Result<bool>;
+}
+
+/// Process-local key-value store
+#[derive(Default)]
+pub struct InMemoryKvStore {
+    namespaces: Mutex<HashMap<KvNamespace, HashMap<String, Value>>>,
+}
+
+impl InMemoryKvStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl KvStore for InMemoryKvStore {
+    async fn get(&self, namespace: &KvNamespace, key: &str) -> Result<Option<Value>> {
+        let namespaces = self.namespaces.lock().await;
+        Ok(namespaces
+            .get(namespace)
+            .and_then(|values| values.get(key))
+            .cloned())
+    }
+
+    async fn set(&self, namespace: &KvNamespace, key: &str, value: Value) -> Result<()> {
+        self.namespaces
+            .lock()
+            .await
+            .entry(namespace.clone())
+            .or_default()
+            .insert(key.to_string(), value);
+        Ok(())
+    }
+
+    async fn delete(&self, namespace: &KvNamespace, key: &str) -> Result<bool> {
+        let mut namespaces = self.namespaces.lock().await;
+        Ok(namespaces
+            .get_mut(namespace)
+            .and_then(|values| values.remove(key))
+            .is_some())
+    }
+}
+
+/// One namespace as [`FileKvStore`] writes it
+#[derive(Serialize, Deserialize)]
+struct NamespaceFile {
+    namespace: KvNamespace,
+    values: Map<String, Value>,
+}
+
+/// Key-value store keeping each namespace as a JSON file in a directory
+///
+/// Files are named by the SHA-256 digest of their namespace, so agent,
+/// tenant, and context names never become paths. Writes replace the whole
+/// file through a rename, and are serialized within the process.
+pub struct FileKvStore {
+    root: PathBuf,
+    writes: Mutex<()>,
+}
+
+impl FileKvStore {
+    /// Store namespaces under `root`, creating the directory if needed
+    pub async fn open(root: impl Into<PathBuf>) -> Result<Self> {
+        let root = root.into();
+        tokio::fs::create_dir_all(&root).await?;
+        Ok(Self {
+            root,
+            writes: Mutex::new(()),
+        })
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    fn path(&self, namespace: &KvNamespace) -> Result<PathBuf> {
+        let label = serde_json::to_vec(namespace).map_err(BamlRtError::Json)?;
+        Ok(self.root.join(format!("{:x}.json", Sha256::digest(&label))))
+    }
+
+    async fn read(&self, path: &Path) -> Result<Map<String, Value>> {
+        match tokio::fs::read(path).await {
+            Ok(bytes) => {
+                let file: NamespaceFile =
+                    serde_json::from_slice(&bytes).map_err(BamlRtError::Json)?;
+                Ok(file.values)
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Map::new()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    async fn write(
+        &self,
+        path: &Path,
+        namespace: &KvNamespace,
+        values: Map<String, Value>,
+    ) -> Result<()> {
+        let file = NamespaceFile {
+            namespace: namespace.clone(),
+            values,
+        };
+        let bytes = serde_json::to_vec(&file).map_err(BamlRtError::Json)?;
+        let staged = path.with_extension("json.tmp");
+        tokio::fs::write(&staged, bytes).await?;
+        tokio::fs::rename(&staged, path).await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl KvStore for FileKvStore {
+    async fn get(&self, namespace: &KvNamespace, key: &str) -> Result<Option<Value>> {
+        let path = self.path(namespace)?;
+        Ok(self.read(&path).await?.remove(key))
+    }
+
+    async fn set(&self, namespace: &KvNamespace, key: &str, value: Value) -> Result<()> {
+        let path = self.path(namespace)?;
+        let _write = self.writes.lock().await;
+        let mut values = self.read(&path).await?;
+        values.insert(key.to_string(), value);
+        self.write(&path, namespace, values).await
+    }
+
+    async fn delete(&self, namespace: &KvNamespace, key: &str) -> Result<bool> {
+        let path = self.path(namespace)?;
+        let _write = self.writes.lock().await;
+        let mut values = self.read(&path).await?;
+        if values.remove(key).is_none() {
+            return Ok(false);
+        }
+        self.write(&path, namespace, values).await?;
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn file_store_keeps_namespaces_apart_across_reopens() {
+        let dir = tempfile::tempdir().unwrap();
+        let agent = KvNamespace::agent("voidship");
+        let context = agent
+            .clone()
+            .with_context(Some(ContextId::from("ctx-void-001")));
+        let other_tenant = agent.clone().with_tenant(Some(TenantId::from("tenant-b")));
+
+        let store = FileKvStore::open(dir.path()).await.unwrap();
+        store.set(&agent, "heading", json!(42)).await.unwrap();
+        store
+            .set(&context, "heading", json!({ "deck": 7 }))
+            .await
+            .unwrap();
+        assert!(store.get(&other_tenant, "heading").await.unwrap().is_none());
+
+        let store = FileKvStore::open(dir.path()).await.unwrap();
+        assert_eq!(store.get(&agent, "heading").await.unwrap(), Some(json!(42)));
+        assert_eq!(
+            store.get(&context, "heading").await.unwrap(),
+            Some(json!({ "deck": 7 }))
+        );
+        assert!(store.delete(&agent, "heading").await.unwrap());
+        assert!(!store.delete(&agent, "heading").await.unwrap());
+        assert!(store.get(&agent, "heading").await.unwrap().is_none());
+    }
+}
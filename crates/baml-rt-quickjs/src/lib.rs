@@ -1,19 +1,71 @@
 //! BAML runtime with QuickJS integration.
 
+// The engine is a native library driven from its own thread; see docs/wasm.md
+#[cfg(target_family = "wasm")]
+compile_error!(
+    "baml-rt-quickjs does not support wasm targets; depend on baml-rt-core, \
+     baml-rt-tools, and baml-rt-interceptor instead"
+);
+
+pub mod agent_caller;
+pub mod artifact_store;
 pub mod baml;
 pub mod baml_collector;
 pub mod baml_execution;
 pub mod baml_pre_execution;
+pub mod baml_stream_interception;
 pub mod context;
+pub mod dry_run;
+pub mod embeddings;
+pub mod js_interceptor;
 pub mod js_value_converter;
+pub mod kv_store;
+pub mod llm_overrides;
+pub mod memory;
+pub mod output_guard;
 pub mod quickjs_bridge;
+pub mod repair;
 pub mod runtime;
+pub mod secrets;
+pub mod session;
+pub mod shared_runtime;
+pub mod source_map;
+pub mod task_operations;
+pub mod token_limiter;
+pub mod tool_schema;
 pub mod traits;
 
-pub use baml::BamlRuntimeManager;
+pub use agent_caller::{AgentCaller, AgentStream};
+pub use artifact_store::{
+    ArtifactInfo, ArtifactStore, FileArtifactStore, InMemoryArtifactStore, StoredArtifact,
+};
+pub use baml::{BamlRuntimeManager, SchemaReload};
+pub use baml_stream_interception::StreamChunkObserver;
 pub use context::{BamlContext, ContextMetadata};
+pub use dry_run::DryRun;
+pub use embeddings::{EmbeddingCache, EmbeddingProvider, OpenAiEmbeddings};
+pub use js_interceptor::JsLlmInterceptor;
+pub use kv_store::{FileKvStore, InMemoryKvStore, KvNamespace, KvStore};
+pub use llm_overrides::{LlmOverrides, LlmParams};
+pub use memory::{MemoryStats, spawn_memory_reporter};
+pub use output_guard::{GuardAction, GuardViolation, OutputGuard, OutputGuardRegistry};
 pub use quickjs_bridge::QuickJSBridge;
+pub use repair::RepairPolicy;
 pub use runtime::{QuickJSConfig, Runtime, RuntimeBuilder, RuntimeConfig};
+#[cfg(feature = "aws-secrets-manager")]
+pub use secrets::AwsSecretsManager;
+#[cfg(feature = "vault")]
+pub use secrets::VaultSecrets;
+pub use secrets::{EnvSecrets, FileSecrets, SecretProvider, SecretStore};
+pub use session::{InMemorySessionStore, SessionHistory, SessionMessage, SessionStore};
+pub use shared_runtime::SharedQuickJsRuntime;
+pub use source_map::{SourceMap, SourceMapRegistry};
+pub use task_operations::TaskOperations;
+pub use token_limiter::{
+    ApproxTokenCounter, ChunkVerdict, LimitAction, LimitScope, StreamBudget, StreamTokenLimiter,
+    TokenCounter, TokenLimits,
+};
+pub use tool_schema::ToolSchemaInjection;
 pub use traits::{
     BamlFunctionExecutor, BamlGateway, JsRuntimeHost, SchemaLoader, ToolRegistryTrait,
 };
@@ -0,0 +1,326 @@
+//! Sampling overrides for LLM calls
+//!
+//! [`LlmOverrides`] force temperature, seed, and max_tokens onto every BAML
+//! call, or onto the calls of given functions, without editing the schema's
+//! client blocks, e.g. so evaluation runs are more reproducible. They can
+//! also send every call to another `base_url`, such as a proxy or a local
+//! mock.
+//!
+//! BAML reads sampling parameters from a client's options, so an overridden
+//! call is given a copy of its function's client with the parameters set,
+//! registered under the client's own name in the call's client registry.
+//! The copy is built from the schema's declaration; a shorthand client such
+//! as `"openai/gpt-4o"` becomes a client of that provider and model.
+//! Fallback and round-robin clients are left alone, and a seed is only sent
+//! to providers that take one.
+
+use crate::embeddings::{ClientConfig, ClientOption};
+use baml_runtime::client_registry::{ClientProperty, ClientProvider, ClientRegistry};
+use baml_types::{BamlMap, BamlValue};
+use std::collections::HashMap;
+
+/// Sampling parameters forced onto LLM calls; unset ones keep the client's
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct LlmParams {
+    pub temperature: Option<f64>,
+    pub seed: Option<i64>,
+    pub max_tokens: Option<u32>,
+}
+
+impl LlmParams {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_temperature(mut self, temperature: f64) -> Self {
+        self.temperature = Some(temperature);
+        self
+    }
+
+    pub fn with_seed(mut self, seed: i64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    pub fn with_max_tokens(mut self, max_tokens: u32) -> Self {
+        self.max_tokens = Some(max_tokens);
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.temperature.is_none() && self.seed.is_none() && self.max_tokens.is_none()
+    }
+
+    /// These parameters, with the ones `other` sets taking precedence
+    pub fn merged(self, other: LlmParams) -> Self {
+        Self {
+            temperature: other.temperature.or(self.temperature),
+            seed: other.seed.or(self.seed),
+            max_tokens: other.max_tokens.or(self.max_tokens),
+        }
+    }
+
+    /// Set the parameters in the options of a `provider` client
+    fn apply(&self, provider: &str, options: &mut BamlMap<String, BamlValue>) {
+        let (temperature, seed, max_tokens, block) = match provider {
+            "anthropic" => ("temperature", None, "max_tokens", None),
+            "aws-bedrock" => (
+                "temperature",
+                None,
+                "max_tokens",
+                Some("inference_configuration"),
+            ),
+            "google-ai" | "vertex-ai" => (
+                "temperature",
+                Some("seed"),
+                "maxOutputTokens",
+                Some("generationConfig"),
+            ),
+            _ => ("temperature", Some("seed"), "max_tokens", None),
+        };
+        let mut params = BamlMap::new();
+        if let Some(value) = self.temperature {
+            params.insert(temperature.to_string(), BamlValue::Float(value));
+        }
+        if let (Some(value), Some(key)) = (self.seed, seed) {
+            params.insert(key.to_string(), BamlValue::Int(value));
+        }
+        if let Some(value) = self.max_tokens {
+            params.insert(max_tokens.to_string(), BamlValue::Int(i64::from(value)));
+        }
+        match block {
+            None => options.extend(params),
+            Some(block) => match options.get_mut(block) {
+                Some(BamlValue::Map(existing)) => existing.extend(params),
+                _ => {
+                    options.insert(block.to_string(), BamlValue::Map(params));
+                }
+            },
+        }
+    }
+}
+
+/// Sampling parameters for every call, and for the calls of given functions
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LlmOverrides {
+    all: LlmParams,
+    functions: HashMap<String, LlmParams>,
+    base_url: Option<String>,
+}
+
+impl LlmOverrides {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Use `temperature` in every call
+    pub fn with_temperature(mut self, temperature: f64) -> Self {
+        self.all.temperature = Some(temperature);
+        self
+    }
+
+    /// Use `seed` in every call to a provider that takes one
+    pub fn with_seed(mut self, seed: i64) -> Self {
+        self.all.seed = Some(seed);
+        self
+    }
+
+    /// Cap the output of every call at `max_tokens`
+    pub fn with_max_tokens(mut self, max_tokens: u32) -> Self {
+        self.all.max_tokens = Some(max_tokens);
+        self
+    }
+
+    /// Send every call to `base_url` instead of the client's own
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = Some(base_url.into());
+        self
+    }
+
+    /// Use `params` in the calls of `function`, over the ones for every call
+    pub fn with_function(mut self, function: impl Into<String>, params: LlmParams) -> Self {
+        self.functions.insert(function.into(), params);
+        self
+    }
+
+    /// The parameters of calls to `function`
+    pub fn params_for(&self, function: &str) -> LlmParams {
+        match self.functions.get(function) {
+            Some(params) => self.all.merged(*params),
+            None => self.all,
+        }
+    }
+
+    /// A registry holding `client` with the parameters of `function` set, or
+    /// `None` when its calls are not overridden
+    ///
+    /// `config` is the client's declaration, if the schema has one, and
+    /// `env` the call's environment, which its `env.*` options are read
+    /// from.
+    pub fn client_registry(
+        &self,
+        function: &str,
+        client: &str,
+        config: Option<&ClientConfig>,
+        env: &HashMap<String, String>,
+    ) -> Option<ClientRegistry> {
+        let params = self.params_for(function);
+        if params.is_empty() && self.base_url.is_none() {
+            return None;
+        }
+        let (provider, retry_policy, mut options) = match config {
+            Some(config) => (
+                config.provider.clone(),
+                config.retry_policy.clone(),
+                declared_options(config, env),
+            ),
+            None => {
+                let (provider, model) = client.split_once('/')?;
+                let mut options = BamlMap::new();
+                options.insert("model".to_string(), BamlValue::String(model.to_string()));
+                (provider.to_string(), None, options)
+            }
+        };
+        if matches!(
+            provider.as_str(),
+            "fallback" | "round-robin" | "baml-fallback" | "baml-round-robin"
+        ) {
+            tracing::debug!(function, client, "LLM overrides skip composite clients");
+            return None;
+        }
+        let parsed_provider = match provider.parse::<ClientProvider>() {
+            Ok(provider) => provider,
+            Err(e) => {
+                tracing::warn!(
+                    function,
+                    client,
+                    provider = provider.as_str(),
+                    error = %e,
+                    "LLM overrides skip a client of unknown provider"
+                );
+                return None;
+            }
+        };
+        params.apply(&provider, &mut options);
+        if let Some(base_url) = &self.base_url {
+            options.insert("base_url".to_string(), BamlValue::String(base_url.clone()));
+        }
+
+        let mut registry = ClientRegistry::new();
+        registry.add_client(ClientProperty::new(
+            client.to_string(),
+            parsed_provider,
+            retry_policy,
+            options,
+        ));
+        Some(registry)
+    }
+}
+
+/// The options of a declared client, with variables read from `env`
+fn declared_options(
+    config: &ClientConfig,
+    env: &HashMap<String, String>,
+) -> BamlMap<String, BamlValue> {
+    let mut options: BamlMap<String, BamlValue> = config
+        .options
+        .iter()
+        .filter_map(|(key, option)| Some((key.clone(), option_value(option, env)?)))
+        .collect();
+    for (block, entries) in &config.option_blocks {
+        let entries = entries
+            .iter()
+            .filter_map(|(key, option)| Some((key.clone(), option_value(option, env)?)))
+            .collect();
+        options.insert(block.clone(), BamlValue::Map(entries));
+    }
+    options
+}
+
+/// A declared option as BAML would read it; unquoted numbers and booleans
+/// keep their type
+fn option_value(option: &ClientOption, env: &HashMap<String, String>) -> Option<BamlValue> {
+    match option {
+        ClientOption::Env(var) => env.get(var).cloned().map(BamlValue::String),
+        ClientOption::Literal(value) => Some(if let Ok(int) = value.parse::<i64>() {
+            BamlValue::Int(int)
+        } else if let Ok(float) = value.parse::<f64>() {
+            BamlValue::Float(float)
+        } else if let Ok(flag) = value.parse::<bool>() {
+            BamlValue::Bool(flag)
+        } else {
+            BamlValue::String(value.clone())
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn function_params_take_precedence_per_field() {
+        let overrides = LlmOverrides::new()
+            .with_temperature(0.0)
+            .with_seed(7)
+            .with_function("Creative", LlmParams::new().with_temperature(0.9));
+
+        assert_eq!(
+            overrides.params_for("Creative"),
+            LlmParams::new().with_temperature(0.9).with_seed(7)
+        );
+        assert_eq!(
+            overrides.params_for("Other"),
+            LlmParams::new().with_temperature(0.0).with_seed(7)
+        );
+
+        let mut options = BamlMap::new();
+        LlmParams::new()
+            .with_seed(7)
+            .with_max_tokens(256)
+            .apply("anthropic", &mut options);
+        assert_eq!(options.len(), 1);
+        assert!(matches!(options["max_tokens"], BamlValue::Int(256)));
+
+        let mut options = BamlMap::new();
+        LlmParams::new()
+            .with_max_tokens(256)
+            .apply("google-ai", &mut options);
+        let BamlValue::Map(config) = &options["generationConfig"] else {
+            panic!("expected generationConfig, got {:?}", options);
+        };
+        assert!(matches!(config["maxOutputTokens"], BamlValue::Int(256)));
+    }
+
+    #[test]
+    fn base_url_redirects_declared_clients() {
+        let config = ClientConfig {
+            provider: "openai-generic".to_string(),
+            options: [
+                (
+                    "base_url".to_string(),
+                    ClientOption::Literal("https://openrouter.ai/api/v1".to_string()),
+                ),
+                (
+                    "api_key".to_string(),
+                    ClientOption::Env("OPENROUTER_API_KEY".to_string()),
+                ),
+            ]
+            .into_iter()
+            .collect(),
+            ..ClientConfig::default()
+        };
+        let env = HashMap::from([("OPENROUTER_API_KEY".to_string(), "sk-test".to_string())]);
+        let overrides = LlmOverrides::new().with_base_url("http://127.0.0.1:4000/v1");
+        assert!(
+            overrides
+                .client_registry("Greet", "Chat", Some(&config), &env)
+                .is_some()
+        );
+        assert!(
+            LlmOverrides::new()
+                .client_registry("Greet", "Chat", Some(&config), &env)
+                .is_none()
+        );
+    }
+}
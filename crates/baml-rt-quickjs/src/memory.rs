@@ -0,0 +1,116 @@
+//! QuickJS memory statistics
+//!
+//! [`QuickJSBridge::memory_stats`](crate::QuickJSBridge::memory_stats) takes a
+//! snapshot of the engine's allocator and heap. [`spawn_memory_reporter`]
+//! samples a bridge on an interval and records the numbers as
+//! `baml_rt.quickjs.*` gauges, so steadily growing heaps show up on dashboards
+//! well before `memory_limit` is reached.
+
+use crate::quickjs_bridge::QuickJSBridge;
+use baml_rt_observability::metrics;
+use serde::Serialize;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex;
+
+/// Share of `memory_limit` above which the reporter logs a warning
+const HIGH_WATER_RATIO: f64 = 0.9;
+
+/// Snapshot of a QuickJS engine's memory use
+///
+/// Sizes are in bytes. For a bridge in a shared runtime the numbers cover
+/// the whole engine, not just the bridge's realm.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct MemoryStats {
+    /// Bytes currently allocated by the engine
+    pub malloc_size: u64,
+    /// Allocation limit, when `memory_limit` is configured
+    pub malloc_limit: Option<u64>,
+    /// Bytes used by JS values, shapes, atoms, and bytecode
+    pub memory_used_size: u64,
+    /// Number of live allocations
+    pub memory_used_count: u64,
+    pub object_count: u64,
+    pub object_size: u64,
+    pub string_count: u64,
+    pub string_size: u64,
+    pub function_count: u64,
+    pub array_count: u64,
+    /// Collections run through [`QuickJSBridge::collect_garbage`]
+    pub gc_runs: u64,
+}
+
+impl MemoryStats {
+    /// Fraction of `malloc_limit` in use, if a limit is set
+    pub fn limit_ratio(&self) -> Option<f64> {
+        self.malloc_limit
+            .map(|limit| self.malloc_size as f64 / limit as f64)
+    }
+}
+
+/// Convert a QuickJS counter, where negative values mean "not set"
+pub(crate) fn counter(value: i64) -> u64 {
+    u64::try_from(value).unwrap_or(0)
+}
+
+/// Record `bridge`'s memory stats every `interval` under the `scope` label
+///
+/// The task ends once the bridge has been dropped everywhere else.
+pub fn spawn_memory_reporter(
+    bridge: &Arc<Mutex<QuickJSBridge>>,
+    scope: impl Into<String>,
+    interval: Duration,
+) -> tokio::task::JoinHandle<()> {
+    let bridge = Arc::downgrade(bridge);
+    let scope = scope.into();
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            let Some(bridge) = bridge.upgrade() else {
+                break;
+            };
+            let stats = bridge.lock().await.memory_stats();
+            drop(bridge);
+            record(&scope, &stats);
+        }
+        tracing::debug!(scope = scope.as_str(), "QuickJS memory reporter stopped");
+    })
+}
+
+fn record(scope: &str, stats: &MemoryStats) {
+    metrics::record_quickjs_memory(
+        scope,
+        stats.malloc_size,
+        stats.memory_used_size,
+        stats.object_count,
+        stats.gc_runs,
+    );
+    if let Some(ratio) = stats.limit_ratio()
+        && ratio >= HIGH_WATER_RATIO
+    {
+        tracing::warn!(
+            scope,
+            malloc_size = stats.malloc_size,
+            malloc_limit = stats.malloc_limit,
+            "QuickJS heap is close to its memory limit"
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn limit_ratio_needs_a_limit() {
+        let mut stats = MemoryStats {
+            malloc_size: 900,
+            ..MemoryStats::default()
+        };
+        assert_eq!(stats.limit_ratio(), None);
+        stats.malloc_limit = Some(1000);
+        assert_eq!(stats.limit_ratio(), Some(0.9));
+        assert_eq!(counter(-1), 0);
+    }
+}
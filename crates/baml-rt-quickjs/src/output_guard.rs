@@ -0,0 +1,361 @@
+//! Guardrails on BAML function outputs
+//!
+//! An [`OutputGuard`] checks what a BAML function returned before
+//! [`BamlRuntimeManager::invoke_function`](crate::BamlRuntimeManager::invoke_function)
+//! hands it back. Validators are regexes, JSON schemas, or another BAML
+//! function acting as a classifier. When a check fails, the guard's
+//! [`GuardAction`] decides whether the call fails, is retried, or returns the
+//! output annotated with the violation.
+
+use crate::traits::BamlFunctionExecutor;
+use baml_rt_core::{BamlRtError, Result};
+use regex::Regex;
+use serde::Serialize;
+use serde_json::{Value, json};
+use std::collections::HashSet;
+
+/// What happens when a guard rejects an output
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case", tag = "type")]
+pub enum GuardAction {
+    /// Fail the call with [`BamlRtError::OutputRejected`]
+    #[default]
+    Fail,
+    /// Call the function again, failing once `max_attempts` calls were rejected
+    Retry { max_attempts: u32 },
+    /// Return the output wrapped with the violation details
+    Annotate,
+}
+
+enum Validator {
+    /// The output, or its JSON text when it is not a string, must match
+    Regex(Regex),
+    JsonSchema(Box<jsonschema::Validator>),
+    /// A BAML function called with the output as `param`, returning `true`
+    /// or `{ valid, reason? }`
+    Classifier {
+        function: String,
+        param: String,
+    },
+}
+
+/// Validators applied to the outputs of some or all BAML functions
+pub struct OutputGuard {
+    name: String,
+    functions: HashSet<String>,
+    validators: Vec<Validator>,
+    action: GuardAction,
+}
+
+impl OutputGuard {
+    /// Create a guard with no validators that fails rejected calls
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            functions: HashSet::new(),
+            validators: Vec::new(),
+            action: GuardAction::default(),
+        }
+    }
+
+    /// Only check outputs of `function`; without this the guard covers every
+    /// function
+    pub fn for_function(mut self, function: impl Into<String>) -> Self {
+        self.functions.insert(function.into());
+        self
+    }
+
+    /// Require outputs to match `pattern`
+    pub fn with_regex(mut self, pattern: &str) -> Result<Self> {
+        let regex = Regex::new(pattern).map_err(|e| {
+            BamlRtError::InvalidArgument(format!(
+                "Invalid regex for output guard '{}': {}",
+                self.name, e
+            ))
+        })?;
+        self.validators.push(Validator::Regex(regex));
+        Ok(self)
+    }
+
+    /// Require outputs to validate against a JSON schema
+    pub fn with_json_schema(mut self, schema: &Value) -> Result<Self> {
+        let validator = jsonschema::validator_for(schema).map_err(|e| {
+            BamlRtError::InvalidArgument(format!(
+                "Invalid JSON schema for output guard '{}': {}",
+                self.name, e
+            ))
+        })?;
+        self.validators
+            .push(Validator::JsonSchema(Box::new(validator)));
+        Ok(self)
+    }
+
+    /// Ask the BAML function `function` whether an output is acceptable,
+    /// passing the output as its `param` argument
+    pub fn with_classifier(
+        mut self,
+        function: impl Into<String>,
+        param: impl Into<String>,
+    ) -> Self {
+        self.validators.push(Validator::Classifier {
+            function: function.into(),
+            param: param.into(),
+        });
+        self
+    }
+
+    /// Set what happens when an output is rejected
+    pub fn on_violation(mut self, action: GuardAction) -> Self {
+        self.action = action;
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn action(&self) -> GuardAction {
+        self.action
+    }
+
+    /// Whether the guard checks outputs of `function`
+    pub fn applies_to(&self, function: &str) -> bool {
+        self.functions.is_empty() || self.functions.contains(function)
+    }
+
+    /// Reasons `output` is rejected; empty when it passes
+    async fn violations(
+        &self,
+        output: &Value,
+        executor: &dyn BamlFunctionExecutor,
+    ) -> Result<Vec<String>> {
+        let mut reasons = Vec::new();
+        for validator in &self.validators {
+            match validator {
+                Validator::Regex(regex) => {
+                    let text = match output {
+                        Value::String(text) => text.clone(),
+                        other => other.to_string(),
+                    };
+                    if !regex.is_match(&text) {
+                        reasons.push(format!("output does not match /{}/", regex.as_str()));
+                    }
+                }
+                Validator::JsonSchema(validator) => {
+                    reasons.extend(validator.iter_errors(output).map(|error| {
+                        format!("schema violation at '{}': {}", error.instance_path, error)
+                    }));
+                }
+                Validator::Classifier { function, param } => {
+                    let verdict = executor
+                        .execute_function(function, json!({ param.as_str(): output }))
+                        .await?;
+                    if let Some(reason) = classifier_rejection(function, &verdict) {
+                        reasons.push(reason);
+                    }
+                }
+            }
+        }
+        Ok(reasons)
+    }
+}
+
+/// Why a classifier's verdict rejects the output, if it does
+fn classifier_rejection(function: &str, verdict: &Value) -> Option<String> {
+    let valid = match verdict {
+        Value::Bool(valid) => *valid,
+        Value::Object(map) => map.get("valid").and_then(Value::as_bool).unwrap_or(false),
+        _ => false,
+    };
+    if valid {
+        return None;
+    }
+    let reason = verdict
+        .get("reason")
+        .and_then(Value::as_str)
+        .map(str::to_string)
+        .unwrap_or_else(|| verdict.to_string());
+    Some(format!(
+        "classifier {} rejected output: {}",
+        function, reason
+    ))
+}
+
+/// An output rejected by a guard
+#[derive(Debug, Clone, Serialize)]
+pub struct GuardViolation {
+    pub guard: String,
+    pub action: GuardAction,
+    pub reasons: Vec<String>,
+}
+
+impl GuardViolation {
+    /// `output` wrapped with the violation, for [`GuardAction::Annotate`]
+    pub fn annotate(&self, output: Value) -> Value {
+        json!({
+            "value": output,
+            "guard_violation": self,
+        })
+    }
+
+    pub fn into_error(self) -> BamlRtError {
+        BamlRtError::OutputRejected {
+            guard: self.guard,
+            reason: self.reasons.join("; "),
+        }
+    }
+}
+
+/// Output guards in registration order
+#[derive(Default)]
+pub struct OutputGuardRegistry {
+    guards: Vec<OutputGuard>,
+}
+
+impl OutputGuardRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn register(&mut self, guard: OutputGuard) {
+        self.guards.push(guard);
+    }
+
+    pub fn with_guard(mut self, guard: OutputGuard) -> Self {
+        self.register(guard);
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.guards.is_empty()
+    }
+
+    /// Run the guards covering `function` in order, stopping at the first
+    /// that rejects `output`
+    ///
+    /// Classifier functions are called through `executor`.
+    pub async fn check(
+        &self,
+        function: &str,
+        output: &Value,
+        executor: &dyn BamlFunctionExecutor,
+    ) -> Result<Option<GuardViolation>> {
+        for guard in self
+            .guards
+            .iter()
+            .filter(|guard| guard.applies_to(function))
+        {
+            let reasons = guard.violations(output, executor).await?;
+            if !reasons.is_empty() {
+                return Ok(Some(GuardViolation {
+                    guard: guard.name.clone(),
+                    action: guard.action,
+                    reasons,
+                }));
+            }
+        }
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+
+    /// Classifier that rejects any output mentioning a password
+    struct PasswordClassifier;
+
+    #[async_trait]
+    impl BamlFunctionExecutor for PasswordClassifier {
+        async fn execute_function(&self, function_name: &str, args: Value) -> Result<Value> {
+            assert_eq!(function_name, "ContainsSecrets");
+            let leaked = args["text"].to_string().contains("password");
+            Ok(json!({ "valid": !leaked, "reason": "mentions a password" }))
+        }
+
+        fn list_functions(&self) -> Vec<String> {
+            vec!["ContainsSecrets".to_string()]
+        }
+    }
+
+    #[tokio::test]
+    async fn guards_report_the_first_violation() {
+        let guards = OutputGuardRegistry::new()
+            .with_guard(
+                OutputGuard::new("shape")
+                    .for_function("ExtractUser")
+                    .with_json_schema(&json!({
+                        "type": "object",
+                        "required": ["name"],
+                        "properties": { "name": { "type": "string" } }
+                    }))
+                    .unwrap(),
+            )
+            .with_guard(
+                OutputGuard::new("secrets")
+                    .with_classifier("ContainsSecrets", "text")
+                    .on_violation(GuardAction::Annotate),
+            );
+
+        let ok = json!({ "name": "Ada" });
+        assert!(
+            guards
+                .check("ExtractUser", &ok, &PasswordClassifier)
+                .await
+                .unwrap()
+                .is_none()
+        );
+
+        let missing = json!({ "nickname": "Ada" });
+        let violation = guards
+            .check("ExtractUser", &missing, &PasswordClassifier)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(violation.guard, "shape");
+        assert_eq!(violation.action, GuardAction::Fail);
+
+        // The schema guard only covers ExtractUser
+        let leaked = json!("the password is hunter2");
+        let violation = guards
+            .check("Chat", &leaked, &PasswordClassifier)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(violation.guard, "secrets");
+        let annotated = violation.annotate(leaked.clone());
+        assert_eq!(annotated["value"], leaked);
+        assert!(
+            annotated["guard_violation"]["reasons"][0]
+                .as_str()
+                .unwrap()
+                .contains("mentions a password")
+        );
+    }
+
+    #[tokio::test]
+    async fn regex_guards_match_the_json_text_of_structured_outputs() {
+        assert!(OutputGuard::new("bad").with_regex("(").is_err());
+        let guards = OutputGuardRegistry::new()
+            .with_guard(OutputGuard::new("no-colons").with_regex("^[^:]*$").unwrap());
+
+        assert!(
+            guards
+                .check("Chat", &json!("plain text"), &PasswordClassifier)
+                .await
+                .unwrap()
+                .is_none()
+        );
+        // {"a":1} contains a colon once serialized
+        assert!(
+            guards
+                .check("Chat", &json!({ "a": 1 }), &PasswordClassifier)
+                .await
+                .unwrap()
+                .is_some()
+        );
+        assert_eq!(classifier_rejection("C", &json!(true)), None);
+        assert!(classifier_rejection("C", &json!("nope")).is_some());
+    }
+}
@@ -3,35 +3,332 @@
 //! This module maps BAML function calls (executed in Rust) to QuickJS,
 //! allowing JavaScript code to invoke BAML functions.
 
-use crate::baml::BamlRuntimeManager;
-use crate::js_value_converter::value_to_js_value_facade;
+use crate::agent_caller::{AgentCaller, AgentStream};
+use crate::artifact_store::{self, ArtifactStore};
+use crate::baml::{BamlRuntimeManager, SchemaReload};
+use crate::baml_stream_interception::forward_stream_chunks;
+use crate::js_interceptor::JsLlmInterceptor;
+use crate::js_value_converter::{json_arg_to_value, value_to_js_value_facade};
+use crate::kv_store::{DEFAULT_AGENT_NAMESPACE, KvNamespace, KvStore};
+use crate::memory::{self, MemoryStats};
+use crate::session::{SessionMessage, SessionStore};
+use crate::shared_runtime::{RealmHandle, SharedQuickJsRuntime, build_facade};
+use crate::source_map::{SourceMap, SourceMapRegistry};
+use crate::task_operations::TaskOperations;
+use crate::token_limiter::ChunkVerdict;
+use baml_rt_core::auth::{self, Access, Caller};
+use baml_rt_core::cancellation;
 use baml_rt_core::context;
 use baml_rt_core::correlation;
-use baml_rt_core::ids::ContextId;
-use baml_rt_core::{BamlRtError, Result};
-use quickjs_runtime::builder::QuickJsRuntimeBuilder;
+use baml_rt_core::deadline::{self, Deadline};
+use baml_rt_core::events::{EventBus, RuntimeEvent};
+use baml_rt_core::ids::{ArtifactId, ContextId, CorrelationId};
+use baml_rt_core::tenant::{self, Tenant};
+use baml_rt_core::{BamlRtError, JsException, Result};
+use baml_rt_observability::metrics;
+use baml_rt_tools::{JS_NAMESPACE, RUST_NAMESPACE, qualified_tool_name};
 use quickjs_runtime::facades::QuickJsRuntimeFacade;
 use quickjs_runtime::jsutils::Script;
 use quickjs_runtime::quickjsrealmadapter::QuickJsRealmAdapter;
 use quickjs_runtime::values::JsValueFacade;
-use serde_json::Value;
+use serde_json::{Map, Value};
 use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
-use tokio::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+use tokio::sync::{Mutex, RwLock};
+use tokio_util::sync::CancellationToken;
+
+/// Context of the evaluation in progress, captured for host callbacks that
+/// cannot see the evaluating task's task-locals
+#[derive(Debug, Clone, Default)]
+struct ActiveContext {
+    context_id: Option<ContextId>,
+    metadata: context::RequestMetadata,
+    deadline: Option<Deadline>,
+    cancellation: Option<CancellationToken>,
+    tenant: Option<Tenant>,
+    caller: Option<Caller>,
+}
+
+impl ActiveContext {
+    /// The calling task's context, with fresh metadata outside any scope
+    fn capture() -> Self {
+        Self {
+            context_id: context::current_context_id(),
+            metadata: context::current_metadata().unwrap_or_default(),
+            deadline: deadline::current_deadline(),
+            cancellation: cancellation::current_cancellation(),
+            tenant: tenant::current_tenant(),
+            caller: auth::current_caller(),
+        }
+    }
+
+    /// Run `fut` for the evaluating request's tenant and caller, under its
+    /// deadline and cancellation
+    async fn bound<F, T>(self, operation: &str, fut: F) -> Result<T>
+    where
+        F: std::future::Future<Output = Result<T>>,
+    {
+        let Self {
+            deadline: request_deadline,
+            cancellation: token,
+            tenant: request_tenant,
+            caller,
+            ..
+        } = self;
+        let deadline_bound = async {
+            match request_deadline {
+                Some(request_deadline) => {
+                    deadline::with_deadline(request_deadline, operation, fut).await
+                }
+                None => fut.await,
+            }
+        };
+        let bound = async {
+            match token {
+                Some(token) => {
+                    cancellation::with_cancellation(token, operation, deadline_bound).await
+                }
+                None => deadline_bound.await,
+            }
+        };
+        let bound = auth::with_caller(caller, bound);
+        tenant::with_tenant(request_tenant, bound).await
+    }
+}
+
+/// The evaluating request's context, for a host callback
+fn host_active_context(active: &std::sync::Mutex<ActiveContext>) -> ActiveContext {
+    active
+        .lock()
+        .map(|active| active.clone())
+        .unwrap_or_default()
+}
+
+/// Fail unless the evaluating request's caller may call agent `target`
+fn check_agent_access(
+    active: &ActiveContext,
+    target: &str,
+) -> std::result::Result<(), quickjs_runtime::jsutils::JsError> {
+    match &active.caller {
+        Some(caller) => caller
+            .check(Access::Agent(target))
+            .map_err(|e| quickjs_runtime::jsutils::JsError::new_str(&e.to_string())),
+        None => Ok(()),
+    }
+}
+
+/// Correlation ID for a host function call made from JavaScript.
+///
+/// Host callbacks run on the JS thread, where the task-local correlation ID of
+/// the evaluating request is not visible, so fall back to the ID recorded when
+/// the evaluation started.
+fn host_correlation_id(active: &std::sync::Mutex<Option<CorrelationId>>) -> CorrelationId {
+    correlation::current_correlation_id()
+        .or_else(|| active.lock().ok().and_then(|active| active.clone()))
+        .unwrap_or_else(correlation::generate_correlation_id)
+}
+
+/// The manager's session store, or a JS error when none is configured
+async fn session_store(
+    manager: &RwLock<BamlRuntimeManager>,
+) -> std::result::Result<Arc<dyn SessionStore>, quickjs_runtime::jsutils::JsError> {
+    manager
+        .read()
+        .await
+        .session_store()
+        .ok_or_else(|| quickjs_runtime::jsutils::JsError::new_str("No session store is configured"))
+}
+
+/// The manager's artifact store, or a JS error when none is configured
+async fn artifact_store(
+    manager: &RwLock<BamlRuntimeManager>,
+) -> std::result::Result<Arc<dyn ArtifactStore>, quickjs_runtime::jsutils::JsError> {
+    manager.read().await.artifact_store().ok_or_else(|| {
+        quickjs_runtime::jsutils::JsError::new_str("No artifact store is configured")
+    })
+}
+
+/// The manager's agent caller, or a JS error when none is configured
+async fn agent_caller(
+    manager: &RwLock<BamlRuntimeManager>,
+) -> std::result::Result<Arc<dyn AgentCaller>, quickjs_runtime::jsutils::JsError> {
+    manager
+        .read()
+        .await
+        .agent_caller()
+        .ok_or_else(|| quickjs_runtime::jsutils::JsError::new_str("No agent caller is configured"))
+}
+
+/// The manager's task operations, or a JS error when none are configured
+async fn task_operations(
+    manager: &RwLock<BamlRuntimeManager>,
+) -> std::result::Result<Arc<dyn TaskOperations>, quickjs_runtime::jsutils::JsError> {
+    manager
+        .read()
+        .await
+        .task_operations()
+        .ok_or_else(|| quickjs_runtime::jsutils::JsError::new_str("No task store is configured"))
+}
+
+/// The manager's key-value store, or a JS error when none is configured
+async fn kv_store(
+    manager: &RwLock<BamlRuntimeManager>,
+) -> std::result::Result<Arc<dyn KvStore>, quickjs_runtime::jsutils::JsError> {
+    manager.read().await.kv_store().ok_or_else(|| {
+        quickjs_runtime::jsutils::JsError::new_str("No key-value store is configured")
+    })
+}
+
+/// Key, JSON arguments, and namespace of a `kv*` host call
+///
+/// The last argument holds the call's options: keys are agent-wide for the
+/// request's tenant unless `scope` is `"context"`.
+fn kv_args(
+    args: &[JsValueFacade],
+    count: usize,
+    agent: &str,
+    active: &ActiveContext,
+) -> std::result::Result<(String, Vec<Value>, KvNamespace), quickjs_runtime::jsutils::JsError> {
+    if args.len() < count || !args.iter().take(count).all(|value| value.is_string()) {
+        return Err(quickjs_runtime::jsutils::JsError::new_str(&format!(
+            "Expected {} string arguments",
+            count
+        )));
+    }
+    let mut values = args[1..count]
+        .iter()
+        .map(|value| serde_json::from_str(value.get_str()))
+        .collect::<std::result::Result<Vec<Value>, _>>()
+        .map_err(|e| {
+            quickjs_runtime::jsutils::JsError::new_str(&format!(
+                "Failed to parse kv arguments: {}",
+                e
+            ))
+        })?;
+    let options = values.pop().unwrap_or(Value::Null);
+    let namespace = KvNamespace::agent(agent)
+        .with_tenant(active.tenant.as_ref().map(|tenant| tenant.id().clone()));
+    let namespace = match options.get("scope").and_then(Value::as_str) {
+        None | Some("agent") => namespace,
+        Some("context") => {
+            let context = active.context_id.clone().ok_or_else(|| {
+                quickjs_runtime::jsutils::JsError::new_str("No context to scope the key to")
+            })?;
+            namespace.with_context(Some(context))
+        }
+        Some(scope) => {
+            return Err(quickjs_runtime::jsutils::JsError::new_str(&format!(
+                "Unknown kv scope: {}",
+                scope
+            )));
+        }
+    };
+    Ok((args[0].get_str().to_string(), values, namespace))
+}
+
+/// The host call argument at `index`, when it is a string
+fn string_arg(args: &[JsValueFacade], index: usize) -> Option<String> {
+    args.get(index)
+        .filter(|value| value.is_string())
+        .map(|value| value.get_str().to_string())
+}
+
+/// Target, method and parsed params of a `callAgent` host call
+fn agent_call_args(
+    args: &[JsValueFacade],
+) -> std::result::Result<(String, String, Value), quickjs_runtime::jsutils::JsError> {
+    if args.len() < 3 || !args.iter().take(3).all(|value| value.is_string()) {
+        return Err(quickjs_runtime::jsutils::JsError::new_str(
+            "Expected 3 string arguments: target, method and params JSON",
+        ));
+    }
+    let params = serde_json::from_str(args[2].get_str()).map_err(|e| {
+        quickjs_runtime::jsutils::JsError::new_str(&format!("Failed to parse agent params: {}", e))
+    })?;
+    Ok((
+        args[0].get_str().to_string(),
+        args[1].get_str().to_string(),
+        params,
+    ))
+}
+
+/// A `log.*` call from JavaScript
+struct JsLogEvent<'a> {
+    agent: Option<&'a str>,
+    correlation_id: Option<&'a CorrelationId>,
+    context_id: Option<&'a ContextId>,
+    message: &'a str,
+    fields: &'a Value,
+}
+
+/// Emit `event` at `level`; unknown levels are logged as info
+fn emit_js_log(level: &str, event: &JsLogEvent<'_>) {
+    macro_rules! emit {
+        ($level:ident) => {
+            tracing::$level!(
+                target: "baml_rt::js_log",
+                agent = event.agent,
+                correlation_id = event.correlation_id.map(CorrelationId::as_str),
+                context_id = event.context_id.map(ContextId::as_str),
+                fields = %event.fields,
+                "{}",
+                event.message
+            )
+        };
+    }
+    match level {
+        "debug" => emit!(debug),
+        "warn" => emit!(warn),
+        "error" => emit!(error),
+        _ => emit!(info),
+    }
+}
+
+/// Whether a JS error is QuickJS reporting that its heap is exhausted
+///
+/// The engine throws this when an allocation fails, and may be left with
+/// half-built objects, so it is not trusted to run anything afterwards.
+fn is_out_of_memory(name: &str, message: &str) -> bool {
+    name == "InternalError" && message == "out of memory"
+}
+
+fn engine_out_of_memory(context: &str) -> BamlRtError {
+    tracing::error!(context, "QuickJS ran out of memory");
+    BamlRtError::EngineFailure {
+        reason: format!("{}: out of memory", context),
+    }
+}
+
+/// Streams opened by `callAgent.stream`, keyed by the handle JS holds
+type OpenAgentStreams = Arc<std::sync::Mutex<HashMap<String, Arc<Mutex<AgentStream>>>>>;
 
 /// Bridge between QuickJS JavaScript runtime and BAML functions
 ///
 /// BAML functions execute in Rust. This bridge exposes them to QuickJS
 /// so JavaScript code can call them.
 pub struct QuickJSBridge {
-    runtime: QuickJsRuntimeFacade,
-    baml_manager: Arc<Mutex<BamlRuntimeManager>>,
+    runtime: Arc<QuickJsRuntimeFacade>,
+    // This bridge's realm when it shares its engine with other bridges
+    realm: Option<RealmHandle>,
+    baml_manager: Arc<RwLock<BamlRuntimeManager>>,
     js_tools: HashSet<String>, // Track JavaScript-only tools
+    // Correlation ID of the evaluation in progress, for host callbacks that
+    // run on the JS thread outside of any tokio task
+    active_correlation: Arc<std::sync::Mutex<Option<CorrelationId>>>,
+    // Context ID and metadata of the evaluation in progress, for `bamlContext()`
+    active_context: Arc<std::sync::Mutex<ActiveContext>>,
+    // Source maps of scripts loaded with `evaluate_script`
+    source_maps: SourceMapRegistry,
+    // Collections run through `collect_garbage`
+    gc_runs: AtomicU64,
+    // The manager's event bus, which JS exceptions are published on
+    events: EventBus,
 }
 
 impl QuickJSBridge {
     /// Create a new QuickJS bridge with default configuration
-    pub async fn new(baml_manager: Arc<Mutex<BamlRuntimeManager>>) -> Result<Self> {
+    pub async fn new(baml_manager: Arc<RwLock<BamlRuntimeManager>>) -> Result<Self> {
         Self::new_with_config(baml_manager, crate::runtime::QuickJSConfig::default()).await
     }
 
@@ -41,7 +338,7 @@ impl QuickJSBridge {
     /// * `baml_manager` - The BAML runtime manager to use
     /// * `config` - QuickJS runtime configuration options
     pub async fn new_with_config(
-        baml_manager: Arc<Mutex<BamlRuntimeManager>>,
+        baml_manager: Arc<RwLock<BamlRuntimeManager>>,
         config: crate::runtime::QuickJSConfig,
     ) -> Result<Self> {
         tracing::info!(
@@ -52,32 +349,49 @@ impl QuickJSBridge {
             "Initializing QuickJS bridge with configuration"
         );
 
-        // Initialize QuickJS runtime using builder and apply configuration
-        let mut builder = QuickJsRuntimeBuilder::new();
-
-        if let Some(limit) = config.memory_limit {
-            builder = builder.memory_limit(limit);
-        }
-
-        if let Some(stack_size) = config.max_stack_size {
-            builder = builder.max_stack_size(stack_size);
-        }
-
-        if let Some(threshold) = config.gc_threshold {
-            builder = builder.gc_threshold(threshold);
-        }
-
-        if let Some(interval) = config.gc_interval {
-            builder = builder.gc_interval(interval);
-        }
+        let runtime = Arc::new(build_facade(&config));
+        Self::with_runtime(runtime, None, baml_manager).await
+    }
 
-        let runtime = builder.build();
+    /// Create a bridge in its own realm of a shared QuickJS runtime
+    ///
+    /// The bridge has separate globals and registrations from every other
+    /// realm but shares the engine, so many agents can be hosted without a
+    /// runtime each. `realm_id` must be unique within `shared`; the realm is
+    /// released when the bridge is dropped.
+    pub async fn new_in_realm(
+        baml_manager: Arc<RwLock<BamlRuntimeManager>>,
+        shared: &SharedQuickJsRuntime,
+        realm_id: &str,
+    ) -> Result<Self> {
+        tracing::info!(
+            realm = realm_id,
+            "Initializing QuickJS bridge in shared runtime"
+        );
+        shared.create_realm(realm_id)?;
+        let realm = RealmHandle {
+            runtime: shared.clone(),
+            id: realm_id.to_string(),
+        };
+        Self::with_runtime(shared.facade(), Some(realm), baml_manager).await
+    }
 
-        // Create bridge instance
+    async fn with_runtime(
+        runtime: Arc<QuickJsRuntimeFacade>,
+        realm: Option<RealmHandle>,
+        baml_manager: Arc<RwLock<BamlRuntimeManager>>,
+    ) -> Result<Self> {
+        let events = baml_manager.read().await.events();
         let mut bridge = Self {
             runtime,
+            realm,
             baml_manager,
             js_tools: HashSet::new(),
+            active_correlation: Arc::new(std::sync::Mutex::new(None)),
+            active_context: Arc::new(std::sync::Mutex::new(ActiveContext::default())),
+            source_maps: SourceMapRegistry::new(),
+            gc_runs: AtomicU64::new(0),
+            events,
         };
 
         // Initialize sandbox - remove dangerous globals and implement safe console
@@ -93,47 +407,73 @@ impl QuickJSBridge {
     async fn initialize_sandbox(&mut self) -> Result<()> {
         tracing::info!("Initializing QuickJS sandbox environment");
 
+        self.register_console_helper()?;
+        self.register_log_helper().await?;
+
         // Initialize safe console and ensure dangerous globals aren't available
         // QuickJS by default doesn't expose require, fetch, etc., but we ensure console.log works safely
         let sandbox_code = r#"
             (function() {
                 // Implement safe console object - only log methods, no I/O
-                // QuickJS handles console output through its runtime, preventing direct system I/O
-                globalThis.console = {
-                    log: function() {
-                        // console.log output goes to QuickJS runtime logs
-                        // No filesystem or network access
-                        var args = arguments;
-                        for (var i = 0; i < args.length; i++) {
-                            var arg = args[i];
-                            if (typeof arg === 'object') {
-                                try {
-                                    JSON.stringify(arg);
-                                } catch (e) {
-                                    String(arg);
-                                }
+                // Output is forwarded to host tracing at debug level, so it only
+                // appears when enabled globally or for the current request
+                var format = function(args) {
+                    var parts = [];
+                    for (var i = 0; i < args.length; i++) {
+                        var arg = args[i];
+                        if (typeof arg === 'object') {
+                            try {
+                                parts.push(JSON.stringify(arg));
+                            } catch (e) {
+                                parts.push(String(arg));
                             }
+                        } else {
+                            parts.push(String(arg));
                         }
-                    },
-                    info: function() {
-                        globalThis.console.log.apply(globalThis.console, arguments);
-                    },
-                    warn: function() {
-                        globalThis.console.log.apply(globalThis.console, arguments);
-                    },
-                    error: function() {
-                        globalThis.console.log.apply(globalThis.console, arguments);
-                    },
-                    debug: function() {
-                        globalThis.console.log.apply(globalThis.console, arguments);
                     }
+                    return parts.join(' ');
+                };
+                var emit = function(level) {
+                    return function() {
+                        __console_emit(level, format(arguments));
+                    };
+                };
+                globalThis.console = {
+                    log: emit('log'),
+                    info: emit('info'),
+                    warn: emit('warn'),
+                    error: emit('error'),
+                    debug: emit('debug')
+                };
+                // log.info({ fields }) or log.info(message, { fields }) emit
+                // tracing events with the agent and request attached
+                var structured = function(level) {
+                    return function(message, fields) {
+                        if (typeof message !== 'string') {
+                            fields = message;
+                            message = '';
+                        }
+                        var json;
+                        try {
+                            json = JSON.stringify(fields === undefined ? null : fields);
+                        } catch (e) {
+                            json = JSON.stringify({ fields: String(fields) });
+                        }
+                        __log_emit(level, message, json);
+                    };
+                };
+                globalThis.log = {
+                    debug: structured('debug'),
+                    info: structured('info'),
+                    warn: structured('warn'),
+                    error: structured('error')
                 };
             })();
         "#;
 
         let script = Script::new("sandbox_init.js", sandbox_code);
         self.runtime
-            .eval(None, script)
+            .eval(self.realm_id(), script)
             .await
             .map_err(|e| BamlRtError::QuickJsWithSource {
                 context: "Failed to initialize sandbox".to_string(),
@@ -144,6 +484,144 @@ impl QuickJSBridge {
         Ok(())
     }
 
+    /// Realm this bridge evaluates in; `None` for the runtime's main realm
+    pub fn realm_id(&self) -> Option<&str> {
+        self.realm.as_ref().map(|realm| realm.id.as_str())
+    }
+
+    /// Expose a Rust function to this bridge's JavaScript as `globalThis.<name>`
+    fn set_host_function<F>(
+        &self,
+        name: &str,
+        function: F,
+    ) -> std::result::Result<(), quickjs_runtime::jsutils::JsError>
+    where
+        F: Fn(
+                &QuickJsRealmAdapter,
+                Vec<JsValueFacade>,
+            ) -> std::result::Result<JsValueFacade, quickjs_runtime::jsutils::JsError>
+            + Send
+            + Sync
+            + 'static,
+    {
+        match &self.realm {
+            Some(realm) => realm
+                .runtime
+                .set_host_function(&realm.id, name, Arc::new(function)),
+            None => self.runtime.set_function(&[], name, function),
+        }
+    }
+
+    /// Register the host function backing `console.*` in the sandbox.
+    fn register_console_helper(&self) -> Result<()> {
+        let active_correlation = self.active_correlation.clone();
+        self.set_host_function(
+            "__console_emit",
+            move |_realm: &QuickJsRealmAdapter,
+                  args: Vec<JsValueFacade>|
+                  -> std::result::Result<JsValueFacade, quickjs_runtime::jsutils::JsError> {
+                let level = args
+                    .first()
+                    .filter(|value| value.is_string())
+                    .map(|value| value.get_str().to_string())
+                    .unwrap_or_else(|| "log".to_string());
+                let message = args
+                    .get(1)
+                    .filter(|value| value.is_string())
+                    .map(|value| value.get_str().to_string())
+                    .unwrap_or_default();
+                let correlation_id = active_correlation
+                    .lock()
+                    .ok()
+                    .and_then(|active| active.clone());
+                let emit = || {
+                    tracing::debug!(
+                        target: "baml_rt::js_console",
+                        level = level.as_str(),
+                        message = message.as_str(),
+                        "JS console output"
+                    );
+                };
+                match correlation_id {
+                    Some(id) => correlation::with_correlation_id_sync(id, emit),
+                    None => emit(),
+                }
+                Ok(JsValueFacade::Undefined)
+            },
+        )
+        .map_err(|e| BamlRtError::QuickJsWithSource {
+            context: "Failed to register console helper".to_string(),
+            source: Box::new(e),
+        })
+    }
+
+    /// Register the host function backing `log.*` in the sandbox.
+    ///
+    /// Unlike console output, these are events at their own level under the
+    /// `baml_rt::js_log` target, carrying the agent name, correlation ID,
+    /// and context ID, with the JavaScript fields as JSON in `fields`. A
+    /// `message` field is used as the message when none is given.
+    async fn register_log_helper(&self) -> Result<()> {
+        let agent = self
+            .baml_manager
+            .read()
+            .await
+            .agent_name()
+            .map(str::to_string);
+        let active_correlation = self.active_correlation.clone();
+        let active_context = self.active_context.clone();
+        self.set_host_function(
+            "__log_emit",
+            move |_realm: &QuickJsRealmAdapter,
+                  args: Vec<JsValueFacade>|
+                  -> std::result::Result<JsValueFacade, quickjs_runtime::jsutils::JsError> {
+                let arg = |index: usize| {
+                    args.get(index)
+                        .filter(|value| value.is_string())
+                        .map(|value| value.get_str().to_string())
+                };
+                let level = arg(0).unwrap_or_else(|| "info".to_string());
+                let mut fields: Value = arg(2)
+                    .and_then(|json| serde_json::from_str(&json).ok())
+                    .unwrap_or(Value::Null);
+                let mut message = arg(1).unwrap_or_default();
+                if message.is_empty()
+                    && let Some(Value::String(text)) = fields
+                        .as_object_mut()
+                        .and_then(|fields| fields.remove("message"))
+                {
+                    message = text;
+                }
+                let correlation_id = active_correlation
+                    .lock()
+                    .ok()
+                    .and_then(|active| active.clone());
+                let context_id = host_active_context(&active_context).context_id;
+                let emit = || {
+                    emit_js_log(
+                        &level,
+                        &JsLogEvent {
+                            agent: agent.as_deref(),
+                            correlation_id: correlation_id.as_ref(),
+                            context_id: context_id.as_ref(),
+                            message: &message,
+                            fields: &fields,
+                        },
+                    )
+                };
+                match correlation_id.clone() {
+                    Some(id) => correlation::with_correlation_id_sync(id, emit),
+                    None => emit(),
+                }
+                Ok(JsValueFacade::Undefined)
+            },
+        )
+        .map_err(|e| BamlRtError::QuickJsWithSource {
+            context: "Failed to register log helper".to_string(),
+            source: Box::new(e),
+        })
+    }
+
     /// Poll the QuickJS event loop once to advance pending jobs and timers.
     ///
     /// Hosts must call this periodically if they start long-running JS workflows
@@ -154,6 +632,33 @@ impl QuickJSBridge {
         });
     }
 
+    /// Snapshot of the engine's heap and allocator usage
+    pub fn memory_stats(&self) -> MemoryStats {
+        let gc_runs = self.gc_runs.load(Ordering::Relaxed);
+        self.runtime.exe_rt_task_in_event_loop(move |rt| {
+            let usage = rt.memory_usage();
+            MemoryStats {
+                malloc_size: memory::counter(usage.malloc_size),
+                malloc_limit: Some(memory::counter(usage.malloc_limit)).filter(|limit| *limit > 0),
+                memory_used_size: memory::counter(usage.memory_used_size),
+                memory_used_count: memory::counter(usage.memory_used_count),
+                object_count: memory::counter(usage.obj_count),
+                object_size: memory::counter(usage.obj_size),
+                string_count: memory::counter(usage.str_count),
+                string_size: memory::counter(usage.str_size),
+                function_count: memory::counter(usage.js_func_count),
+                array_count: memory::counter(usage.array_count),
+                gc_runs,
+            }
+        })
+    }
+
+    /// Run a full garbage collection now
+    pub fn collect_garbage(&self) {
+        self.runtime.exe_rt_task_in_event_loop(|rt| rt.gc());
+        self.gc_runs.fetch_add(1, Ordering::Relaxed);
+    }
+
     /// Register all BAML functions with the QuickJS context
     ///
     /// This maps Rust BAML functions to JavaScript callables.
@@ -161,7 +666,7 @@ impl QuickJSBridge {
     pub async fn register_baml_functions(&mut self) -> Result<()> {
         tracing::info!("Registering BAML functions with QuickJS");
 
-        let manager = self.baml_manager.lock().await;
+        let manager = self.baml_manager.read().await;
         let functions = manager.list_functions();
         drop(manager); // Release lock before async operation
 
@@ -169,6 +674,16 @@ impl QuickJSBridge {
         self.register_baml_invoke_helper().await?;
         self.register_baml_stream_helper().await?;
         self.register_await_helper().await?;
+        self.register_named_args_helper().await?;
+        self.register_list_functions_helper()?;
+        self.register_session_helpers().await?;
+        self.register_context_helpers().await?;
+        self.register_artifact_helpers().await?;
+        self.register_agent_call_helpers().await?;
+        self.register_task_helpers().await?;
+        self.register_kv_helpers().await?;
+        self.register_embed_helper().await?;
+        self.register_interceptor_helpers().await?;
 
         for function_name in functions {
             self.register_single_function(&function_name).await?;
@@ -181,11 +696,48 @@ impl QuickJSBridge {
         Ok(())
     }
 
+    /// Load the BAML schema at `schema_path` in place of the loaded one and
+    /// update the JS wrappers to match
+    ///
+    /// Wrappers are registered for the functions the new schema has, with
+    /// their new parameters, and deleted for the functions it dropped. Other
+    /// bridges sharing the manager keep their wrappers until they are
+    /// updated with [`register_baml_functions`](Self::register_baml_functions).
+    pub async fn reload_schema(&mut self, schema_path: &str) -> Result<SchemaReload> {
+        let reload = BamlRuntimeManager::reload_schema(&self.baml_manager, schema_path).await?;
+        for function_name in &reload.removed {
+            let js_code = format!(
+                "delete globalThis.{name}; delete globalThis.{name}Stream;",
+                name = function_name
+            );
+            self.runtime
+                .eval(
+                    self.realm_id(),
+                    Script::new("unregister_function.js", &js_code),
+                )
+                .await
+                .map_err(|e| BamlRtError::QuickJsWithSource {
+                    context: "Failed to unregister function".to_string(),
+                    source: Box::new(e),
+                })?;
+        }
+        for function_name in &reload.functions {
+            self.register_single_function(function_name).await?;
+            self.register_single_stream_function(function_name).await?;
+        }
+        tracing::info!(
+            added = ?reload.added,
+            removed = ?reload.removed,
+            "Reloaded BAML schema"
+        );
+        Ok(reload)
+    }
+
     /// Register all tool functions with QuickJS
     async fn register_tool_functions(&mut self) -> Result<()> {
         tracing::info!("Registering tool functions with QuickJS");
 
-        let manager = self.baml_manager.lock().await;
+        let manager = self.baml_manager.read().await;
         let tools = manager.list_tools().await;
         drop(manager);
 
@@ -204,10 +756,12 @@ impl QuickJSBridge {
         let _manager_clone = self.baml_manager.clone();
         let _tool_name_clone = tool_name.to_string();
 
-        // Register a JavaScript wrapper function for the tool
+        // Register a JavaScript wrapper function for the tool; bare names
+        // such as MCP's `server/tool` need not be identifiers
+        let name = serde_json::to_string(tool_name)?;
         let js_code = format!(
             r#"
-            globalThis.{} = async function(...args) {{
+            globalThis[{}] = async function(...args) {{
                 const argObj = {{}};
                 if (args.length === 1 && typeof args[0] === 'object') {{
                     Object.assign(argObj, args[0]);
@@ -216,15 +770,15 @@ impl QuickJSBridge {
                         argObj[`arg${{idx}}`] = arg;
                     }});
                 }}
-                return await __tool_invoke("{}", JSON.stringify(argObj), globalThis.__baml_context_id);
+                return await __tool_invoke({}, __bamlBinaryArgs(argObj), globalThis.__baml_context_id);
             }};
             "#,
-            tool_name, tool_name
+            name, name
         );
 
         let script = Script::new("register_tool.js", &js_code);
         self.runtime
-            .eval(None, script)
+            .eval(self.realm_id(), script)
             .await
             .map_err(|e| BamlRtError::QuickJsWithSource {
                 context: "Failed to register tool function".to_string(),
@@ -238,12 +792,13 @@ impl QuickJSBridge {
     /// Register helper function for tool invocation
     async fn register_tool_invoke_helper(&mut self) -> Result<()> {
         let manager_clone = self.baml_manager.clone();
+        let active_correlation = self.active_correlation.clone();
+        let active_context = self.active_context.clone();
 
         // Register __tool_invoke for Rust tools (low-level helper)
-        self.runtime.set_function(
-            &[],
+        self.set_host_function(
             "__tool_invoke",
-            move |_realm: &QuickJsRealmAdapter, args: Vec<JsValueFacade>| -> std::result::Result<JsValueFacade, quickjs_runtime::jsutils::JsError> {
+            move |_realm: &QuickJsRealmAdapter, mut args: Vec<JsValueFacade>| -> std::result::Result<JsValueFacade, quickjs_runtime::jsutils::JsError> {
                 if args.len() < 2 {
                     return Err(quickjs_runtime::jsutils::JsError::new_str("Expected 2 arguments: tool_name and args"));
                 }
@@ -255,16 +810,6 @@ impl QuickJSBridge {
                     return Err(quickjs_runtime::jsutils::JsError::new_str("First argument must be a string (tool name)"));
                 };
 
-                let args_js = &args[1];
-                let args_json_str = if args_js.is_string() {
-                    args_js.get_str().to_string()
-                } else {
-                    return Err(quickjs_runtime::jsutils::JsError::new_str("Args must be a JSON string"));
-                };
-
-                let args_json: Value = serde_json::from_str(&args_json_str)
-                    .map_err(|e| quickjs_runtime::jsutils::JsError::new_str(&format!("Failed to parse JSON args: {}", e)))?;
-
                 let context_id_arg = args.get(2).and_then(|value| {
                     if value.is_string() {
                         Some(ContextId::from(value.get_str()))
@@ -272,17 +817,26 @@ impl QuickJSBridge {
                         None
                     }
                 });
+                // Optional fourth arg: the BAML function the call is made for
+                let function = string_arg(&args, 3);
+                // Args are a JS object, or a JSON string from older callers
+                let args_js = args.swap_remove(1);
 
                 let tool_name_clone = tool_name.clone();
                 let manager_for_promise = manager_clone.clone();
-                let correlation_id = correlation::current_or_new();
+                let correlation_id = host_correlation_id(&active_correlation);
+                let active = host_active_context(&active_context);
                 let context_id = context_id_arg.unwrap_or_else(context::current_or_new);
 
                 Ok(JsValueFacade::new_promise::<JsValueFacade, _, ()>(async move {
                     correlation::with_correlation_id(correlation_id, async move {
                         context::with_context_id(context_id, async move {
-                        let manager = manager_for_promise.lock().await;
-                        let result = manager.execute_tool(&tool_name_clone, args_json).await;
+                        let args_json = json_arg_to_value(args_js).await.map_err(|e| {
+                            quickjs_runtime::jsutils::JsError::new_str(&format!("Invalid tool args: {}", e))
+                        })?;
+                        let manager = manager_for_promise.read().await;
+                        let call = manager.execute_tool_for(function.as_deref(), &tool_name_clone, args_json);
+                        let result = active.bound("JavaScript tool call", call).await;
 
                         match result {
                             Ok(json_value) => {
@@ -307,26 +861,18 @@ impl QuickJSBridge {
 
         // Register __tool_from_baml_result for executing tools based on BAML union output.
         let manager_clone = self.baml_manager.clone();
-        self.runtime.set_function(
-            &[],
+        let active_correlation = self.active_correlation.clone();
+        let active_context = self.active_context.clone();
+        self.set_host_function(
             "__tool_from_baml_result",
-            move |_realm: &QuickJsRealmAdapter, args: Vec<JsValueFacade>| -> std::result::Result<JsValueFacade, quickjs_runtime::jsutils::JsError> {
+            move |_realm: &QuickJsRealmAdapter, mut args: Vec<JsValueFacade>| -> std::result::Result<JsValueFacade, quickjs_runtime::jsutils::JsError> {
                 if args.is_empty() {
-                    return Err(quickjs_runtime::jsutils::JsError::new_str("Expected 1 argument: baml_result_json"));
+                    return Err(quickjs_runtime::jsutils::JsError::new_str("Expected 1 argument: baml_result"));
                 }
 
-                let baml_result_js = &args[0];
-                let baml_result_str = if baml_result_js.is_string() {
-                    baml_result_js.get_str().to_string()
-                } else {
-                    return Err(quickjs_runtime::jsutils::JsError::new_str("BAML result must be a JSON string"));
-                };
-
-                let baml_result: Value = serde_json::from_str(&baml_result_str)
-                    .map_err(|e| quickjs_runtime::jsutils::JsError::new_str(&format!("Failed to parse BAML result JSON: {}", e)))?;
-
                 let manager_for_promise = manager_clone.clone();
-                let correlation_id = correlation::current_or_new();
+                let correlation_id = host_correlation_id(&active_correlation);
+                let active = host_active_context(&active_context);
                 let context_id = args.get(1).and_then(|value| {
                     if value.is_string() {
                         Some(ContextId::from(value.get_str()))
@@ -334,12 +880,19 @@ impl QuickJSBridge {
                         None
                     }
                 }).unwrap_or_else(context::current_or_new);
+                // Optional third arg: the BAML function that returned the result
+                let function = string_arg(&args, 2);
+                let baml_result_js = args.swap_remove(0);
 
                 Ok(JsValueFacade::new_promise::<JsValueFacade, _, ()>(async move {
                     correlation::with_correlation_id(correlation_id, async move {
                         context::with_context_id(context_id, async move {
-                        let manager = manager_for_promise.lock().await;
-                        let result = manager.execute_tool_from_baml_result(baml_result).await;
+                        let baml_result = json_arg_to_value(baml_result_js).await.map_err(|e| {
+                            quickjs_runtime::jsutils::JsError::new_str(&format!("Invalid BAML result: {}", e))
+                        })?;
+                        let manager = manager_for_promise.read().await;
+                        let call = manager.execute_tool_from_function_result(function.as_deref(), baml_result);
+                        let result = active.bound("JavaScript tool call", call).await;
 
                         match result {
                             Ok(json_value) => Ok(value_to_js_value_facade(json_value)),
@@ -360,12 +913,72 @@ impl QuickJSBridge {
             source: Box::new(e),
         })?;
 
+        // Register __tool_check, which rejects JS tool calls the tool policy refuses
+        let manager_clone = self.baml_manager.clone();
+        self.set_host_function(
+            "__tool_check",
+            move |_realm: &QuickJsRealmAdapter, args: Vec<JsValueFacade>| -> std::result::Result<JsValueFacade, quickjs_runtime::jsutils::JsError> {
+                let tool_name = string_arg(&args, 0).ok_or_else(|| {
+                    quickjs_runtime::jsutils::JsError::new_str("First argument must be a string (tool name)")
+                })?;
+                let function = string_arg(&args, 1);
+                let manager_for_promise = manager_clone.clone();
+
+                Ok(JsValueFacade::new_promise::<JsValueFacade, _, ()>(async move {
+                    let manager = manager_for_promise.read().await;
+                    manager
+                        .check_tool_policy(function.as_deref(), &tool_name)
+                        .await
+                        .map(|()| JsValueFacade::Undefined)
+                        .map_err(|e| quickjs_runtime::jsutils::JsError::new_str(&format!("Tool execution error: {}", e)))
+                }))
+            },
+        ).map_err(|e| BamlRtError::QuickJsWithSource {
+            context: "Failed to register tool policy helper function".to_string(),
+            source: Box::new(e),
+        })?;
+
         // Register unified invokeTool function that dispatches to both Rust and JS tools
         let dispatch_code = r#"
-            globalThis.invokeTool = async function(toolName, args) {
+            // Rust tools receive binary data as Uint8Arrays; other buffers and
+            // views are passed as Uint8Arrays over the same bytes
+            globalThis.__bamlBinaryArgs = function(value) {
+                if (value instanceof ArrayBuffer) {
+                    return new Uint8Array(value);
+                }
+                if (ArrayBuffer.isView(value)) {
+                    return value instanceof Uint8Array
+                        ? value
+                        : new Uint8Array(value.buffer, value.byteOffset, value.byteLength);
+                }
+                if (value === null || typeof value !== 'object') {
+                    return value;
+                }
+                if (Array.isArray(value)) {
+                    return value.map(__bamlBinaryArgs);
+                }
+                const converted = {};
+                for (const [key, item] of Object.entries(value)) {
+                    converted[key] = __bamlBinaryArgs(item);
+                }
+                return converted;
+            };
+
+            globalThis.invokeTool = async function(toolName, args, options) {
                 // Normalize args to object if needed
                 const argsObj = typeof args === 'object' && args !== null ? args : { value: args };
-                
+                // `options.function` names the BAML function whose output chose
+                // the tool, so the tool policy can hold the call to its tools
+                const fn = options && typeof options.function === 'string' ? options.function : null;
+
+                // `js:name` calls the JavaScript tool directly; other qualified
+                // names, like `rust:name` or `mcp:server/tool`, go to the registry
+                const jsName = toolName.startsWith('js:') ? toolName.slice(3) : null;
+                if (jsName !== null && typeof globalThis[jsName] === 'function') {
+                    await __tool_check(toolName, fn);
+                    return await globalThis[jsName](argsObj);
+                }
+
                 // Check if it's a JavaScript tool by checking if it exists as a global function
                 // (and is not one of our helper functions)
                 if (typeof globalThis[toolName] === 'function' && 
@@ -373,20 +986,21 @@ impl QuickJSBridge {
                     toolName !== '__baml_invoke' && 
                     toolName !== '__baml_stream' &&
                     toolName !== '__awaitAndStringify' &&
+                    toolName !== '__tool_check' &&
                     toolName !== 'invokeTool') {
                     // JavaScript tool - call directly
+                    await __tool_check('js:' + toolName, fn);
                     return await globalThis[toolName](argsObj);
                 } else {
                     // Rust tool - use __tool_invoke
-                    const argsJson = JSON.stringify(argsObj);
-                    return await __tool_invoke(toolName, argsJson, globalThis.__baml_context_id);
+                    return await __tool_invoke(toolName, __bamlBinaryArgs(argsObj), globalThis.__baml_context_id, fn);
                 }
             };
         "#;
 
         let script = Script::new("register_tool_dispatch.js", dispatch_code);
         self.runtime
-            .eval(None, script)
+            .eval(self.realm_id(), script)
             .await
             .map_err(|e| BamlRtError::QuickJsWithSource {
                 context: "Failed to register tool dispatch function".to_string(),
@@ -394,7 +1008,7 @@ impl QuickJSBridge {
             })?;
 
         tracing::debug!(
-            "Registered __tool_invoke, __tool_from_baml_result, and invokeTool helper functions"
+            "Registered __tool_invoke, __tool_from_baml_result, __tool_check, and invokeTool helper functions"
         );
         Ok(())
     }
@@ -402,13 +1016,14 @@ impl QuickJSBridge {
     /// Register a helper function that JavaScript can call to invoke BAML functions
     async fn register_baml_invoke_helper(&mut self) -> Result<()> {
         let manager_clone = self.baml_manager.clone();
+        let active_correlation = self.active_correlation.clone();
+        let active_context = self.active_context.clone();
 
         // Register a native Rust function that JavaScript can call
         // This function will handle the async BAML execution using promises
-        self.runtime.set_function(
-            &[],
+        self.set_host_function(
             "__baml_invoke",
-            move |_realm: &QuickJsRealmAdapter, args: Vec<JsValueFacade>| -> std::result::Result<JsValueFacade, quickjs_runtime::jsutils::JsError> {
+            move |_realm: &QuickJsRealmAdapter, mut args: Vec<JsValueFacade>| -> std::result::Result<JsValueFacade, quickjs_runtime::jsutils::JsError> {
                 if args.len() < 2 {
                     return Err(quickjs_runtime::jsutils::JsError::new_str("Expected 2 arguments: function_name and args"));
                 }
@@ -421,36 +1036,35 @@ impl QuickJSBridge {
                     return Err(quickjs_runtime::jsutils::JsError::new_str("First argument must be a string (function name)"));
                 };
 
-                // Extract args (second arg) - for complex objects, we still use JSON.stringify
-                // but we can optimize this in the future
-                let args_js = &args[1];
-                // For now, convert to string and parse back - we can optimize this later
-                // The issue is that JsValueFacade doesn't expose direct access to object properties
-                let args_json_str = if args_js.is_string() {
-                    args_js.get_str().to_string()
-                } else {
-                    // For non-strings, try to convert via debug format (fallback)
-                    // In practice, JavaScript should pass JSON.stringify'd values
-                    return Err(quickjs_runtime::jsutils::JsError::new_str("Args must be a JSON string - use JSON.stringify in JavaScript"));
-                };
-
-                // Parse JSON string to Value
-                let args_json: Value = serde_json::from_str(&args_json_str)
-                    .map_err(|e| quickjs_runtime::jsutils::JsError::new_str(&format!("Failed to parse JSON args: {}", e)))?;
-
                 // Create a promise that will execute the BAML call asynchronously
                 let func_name_clone = func_name.clone();
                 let manager_for_promise = manager_clone.clone();
-                let correlation_id = correlation::current_or_new();
+                let correlation_id = host_correlation_id(&active_correlation);
+                let active = host_active_context(&active_context);
+                // Optional third arg: context ID, which selects the session history
+                let context_id = args.get(2).and_then(|value| {
+                    if value.is_string() {
+                        Some(ContextId::from(value.get_str()))
+                    } else {
+                        None
+                    }
+                }).unwrap_or_else(context::current_or_new);
+                // Args are a JS object, or a JSON string from older callers
+                let args_js = args.swap_remove(1);
 
                 // Use JsValueFacade::new_promise to create a non-blocking promise
                 // The producer is a Future that will be executed asynchronously
                 // Type parameters: R is the result type (JsValueFacade), P is the Future, M is unused/mapper
                 Ok(JsValueFacade::new_promise::<JsValueFacade, _, ()>(async move {
                     correlation::with_correlation_id(correlation_id, async move {
+                        context::with_context_id(context_id, async move {
+                        let args_json = json_arg_to_value(args_js).await.map_err(|e| {
+                            quickjs_runtime::jsutils::JsError::new_str(&format!("Invalid BAML args: {}", e))
+                        })?;
                         // Execute the BAML function asynchronously
-                        let manager = manager_for_promise.lock().await;
-                        let result = manager.invoke_function(&func_name_clone, args_json).await;
+                        let manager = manager_for_promise.read().await;
+                        let call = manager.invoke_function(&func_name_clone, args_json);
+                        let result = active.bound("JavaScript BAML call", call).await;
 
                         match result {
                             Ok(json_value) => {
@@ -463,6 +1077,8 @@ impl QuickJSBridge {
                                 Err(quickjs_runtime::jsutils::JsError::new_str(&error_msg))
                             }
                         }
+                        })
+                        .await
                     })
                     .await
                 }))
@@ -482,13 +1098,35 @@ impl QuickJSBridge {
         // Register a helper that synchronously extracts promise results
         // This will be used by evaluate() to handle promises
         let js_code = r#"
+            // Describe a thrown value as { error, name, message, stack, cause }
+            // so Rust can rebuild it as a structured JsException
+            globalThis.__describeError = function(e, depth) {
+                depth = depth || 0;
+                const isObject = e !== null && typeof e === 'object';
+                const message = isObject && e.message !== undefined ? String(e.message) : String(e);
+                const described = {
+                    error: message,
+                    name: isObject && e.name ? String(e.name) : 'Error',
+                    message: message,
+                };
+                if (isObject && typeof e.stack === 'string') {
+                    described.stack = e.stack;
+                }
+                if (isObject && e.cause !== undefined && depth < 8) {
+                    described.cause = globalThis.__describeError(e.cause, depth + 1);
+                }
+                return described;
+            };
+
             globalThis.__awaitAndStringify = async function(promise) {
                 try {
                     const result = await promise;
                     // Return the result directly, not wrapped in success notification
                     return JSON.stringify(result);
                 } catch (e) {
-                    return JSON.stringify({ error: e.toString() });
+                    const described = __describeError(e);
+                    described.error = String(e);
+                    return JSON.stringify(described);
                 }
             };
             
@@ -500,7 +1138,7 @@ impl QuickJSBridge {
 
         let script = Script::new("await_helper.js", js_code);
         self.runtime
-            .eval(None, script)
+            .eval(self.realm_id(), script)
             .await
             .map_err(|e| BamlRtError::QuickJsWithSource {
                 context: "Failed to register await helper".to_string(),
@@ -524,10 +1162,10 @@ impl QuickJSBridge {
     /// ```rust,no_run
     /// # use baml_rt::quickjs_bridge::QuickJSBridge;
     /// # use std::sync::Arc;
-    /// # use tokio::sync::Mutex;
+    /// # use tokio::sync::RwLock;
     /// # use baml_rt::baml::BamlRuntimeManager;
     /// # tokio_test::block_on(async {
-    /// # let baml_manager = Arc::new(Mutex::new(BamlRuntimeManager::new()?));
+    /// # let baml_manager = Arc::new(RwLock::new(BamlRuntimeManager::new()?));
     /// # let mut bridge = QuickJSBridge::new(baml_manager.clone()).await?;
     /// bridge.register_js_tool("greet_js", r#"
     ///     async function(name) {
@@ -550,18 +1188,39 @@ impl QuickJSBridge {
         let tool_name = name.into();
         let function_code = js_function_code.as_ref();
 
-        // Check if tool name conflicts with existing Rust tools
-        {
-            let manager = self.baml_manager.lock().await;
-            let rust_tools = manager.list_tools().await;
-            if rust_tools.contains(&tool_name) {
-                return Err(BamlRtError::InvalidArgument(format!(
+        // Check if tool name conflicts with tools of other namespaces
+        let registry = self.baml_manager.read().await.tool_registry();
+        let collision = registry.lock().await.collision(JS_NAMESPACE, &tool_name);
+        if let Some(existing) = collision {
+            let message = if existing == qualified_tool_name(RUST_NAMESPACE, &tool_name) {
+                format!(
                     "Tool name '{}' conflicts with existing Rust tool",
                     tool_name
-                )));
-            }
+                )
+            } else {
+                format!(
+                    "Tool name '{}' conflicts with existing tool '{}'",
+                    tool_name, existing
+                )
+            };
+            return Err(BamlRtError::InvalidArgument(message));
         }
 
+        self.install_js_tool(tool_name, function_code).await
+    }
+
+    /// Install a JavaScript tool without checking for Rust tool name conflicts.
+    ///
+    /// Used when rebuilding a bridge whose JS tools were already validated and
+    /// exposed through the tool registry by a previous bridge.
+    pub async fn install_js_tool(
+        &mut self,
+        name: impl Into<String>,
+        js_function_code: impl AsRef<str>,
+    ) -> Result<()> {
+        let tool_name = name.into();
+        let function_code = js_function_code.as_ref();
+
         // Check if already registered as a JS tool
         if self.js_tools.contains(&tool_name) {
             return Err(BamlRtError::InvalidArgument(format!(
@@ -570,6 +1229,45 @@ impl QuickJSBridge {
             )));
         }
 
+        self.define_js_tool(tool_name, function_code).await
+    }
+
+    /// Install a JavaScript tool, replacing the function of a JavaScript
+    /// tool already registered with that name
+    ///
+    /// Like [`install_js_tool`](Self::install_js_tool), this does not check
+    /// for conflicts with Rust tools.
+    pub async fn replace_js_tool(
+        &mut self,
+        name: impl Into<String>,
+        js_function_code: impl AsRef<str>,
+    ) -> Result<()> {
+        self.define_js_tool(name.into(), js_function_code.as_ref())
+            .await
+    }
+
+    /// Remove a JavaScript tool, returning whether it was registered
+    pub async fn unregister_js_tool(&mut self, name: &str) -> Result<bool> {
+        if !self.js_tools.contains(name) {
+            return Ok(false);
+        }
+        let js_code = format!("delete globalThis[{}];", serde_json::to_string(name)?);
+        self.runtime
+            .eval(
+                self.realm_id(),
+                Script::new("unregister_js_tool.js", &js_code),
+            )
+            .await
+            .map_err(|e| BamlRtError::QuickJsWithSource {
+                context: format!("Failed to unregister JavaScript tool '{}'", name),
+                source: Box::new(e),
+            })?;
+        self.js_tools.remove(name);
+        tracing::info!(tool = name, "Unregistered JavaScript tool function");
+        Ok(true)
+    }
+
+    async fn define_js_tool(&mut self, tool_name: String, function_code: &str) -> Result<()> {
         // Register the JavaScript function in the QuickJS runtime
         let js_code = format!(
             r#"
@@ -580,7 +1278,7 @@ impl QuickJSBridge {
 
         let script = Script::new("register_js_tool.js", &js_code);
         self.runtime
-            .eval(None, script)
+            .eval(self.realm_id(), script)
             .await
             .map_err(|e| BamlRtError::QuickJsWithSource {
                 context: format!("Failed to register JavaScript tool '{}'", tool_name),
@@ -610,12 +1308,13 @@ impl QuickJSBridge {
     /// Register a helper function for streaming BAML function execution
     async fn register_baml_stream_helper(&mut self) -> Result<()> {
         let manager_clone = self.baml_manager.clone();
+        let active_correlation = self.active_correlation.clone();
+        let active_context = self.active_context.clone();
 
         // Register a native Rust function that JavaScript can call for streaming
-        self.runtime.set_function(
-            &[],
+        self.set_host_function(
             "__baml_stream",
-            move |_realm: &QuickJsRealmAdapter, args: Vec<JsValueFacade>| -> std::result::Result<JsValueFacade, quickjs_runtime::jsutils::JsError> {
+            move |_realm: &QuickJsRealmAdapter, mut args: Vec<JsValueFacade>| -> std::result::Result<JsValueFacade, quickjs_runtime::jsutils::JsError> {
                 if args.len() < 2 {
                     return Err(quickjs_runtime::jsutils::JsError::new_str("Expected 2 arguments: function_name and args"));
                 }
@@ -628,93 +1327,147 @@ impl QuickJSBridge {
                     return Err(quickjs_runtime::jsutils::JsError::new_str("First argument must be a string (function name)"));
                 };
 
-                // Extract args (second arg) - JSON string from JavaScript
-                let args_js = &args[1];
-                let args_json_str = if args_js.is_string() {
-                    args_js.get_str().to_string()
-                } else {
-                    return Err(quickjs_runtime::jsutils::JsError::new_str("Second argument must be a JSON string"));
-                };
-
-                // Parse JSON string to Value
-                let args_json: Value = match serde_json::from_str(&args_json_str) {
-                    Ok(v) => v,
-                    Err(e) => return Err(quickjs_runtime::jsutils::JsError::new_str(&format!("Failed to parse JSON args: {}", e))),
-                };
+                // Optional third arg: context ID, which keys per-tenant token usage
+                let tenant = args.get(2).filter(|value| value.is_string()).map(|value| value.get_str().to_string());
+                let session_context = tenant.as_deref().map(ContextId::from);
+                // Args are a JS object, or a JSON string from older callers
+                let args_js = args.swap_remove(1);
 
                 let func_name_clone = func_name.clone();
-                let correlation_id = correlation::current_or_new();
+                let correlation_id = host_correlation_id(&active_correlation);
+                let active = host_active_context(&active_context);
+                // Without a context ID, token usage is kept per request tenant
+                let tenant = tenant.or_else(|| active.tenant.as_ref().map(|t| t.id().to_string()));
 
                 // Create a promise that will execute the streaming BAML call
                 let manager_for_stream = manager_clone.clone();
                 Ok(JsValueFacade::new_promise::<JsValueFacade, _, ()>(async move {
                     correlation::with_correlation_id(correlation_id, async move {
                         use tokio::sync::mpsc;
+                        let args_json_stream = json_arg_to_value(args_js).await.map_err(|e| {
+                            quickjs_runtime::jsutils::JsError::new_str(&format!("Invalid BAML args: {}", e))
+                        })?;
                         let (tx, mut rx) = mpsc::channel::<serde_json::Value>(100);
 
                         let func_name_stream = func_name_clone.clone();
-                        let args_json_stream = args_json.clone();
                         let spawn_correlation_id = correlation::current_or_new();
 
                         // Spawn a task to run the stream and send incremental results
                         tokio::spawn(async move {
                             correlation::with_correlation_id(spawn_correlation_id, async move {
                                 // Create the stream
-                                let manager = manager_for_stream.lock().await;
-                                let stream_result = manager.invoke_function_stream(&func_name_stream, args_json_stream);
-
-                                // Get context manager reference while we have the lock
-                                let executor_ref = match manager.executor.as_ref() {
-                                    Some(exec) => exec,
-                                    None => {
-                                        let error_value = serde_json::json!({
-                                            "error": "BAML executor not initialized"
-                                        });
-                                        if let Err(e) = tx.send(error_value).await {
-                                            tracing::warn!(error = ?e, "Stream channel send failed");
-                                        }
-                                        return;
-                                    }
-                                };
-                                let ctx_manager = executor_ref.ctx_manager();
+                                let manager = manager_for_stream.read().await;
+                                let mut budget = manager
+                                    .stream_token_limiter()
+                                    .map(|limiter| limiter.begin(tenant));
+                                let cancel = budget
+                                    .as_ref()
+                                    .map(|budget| budget.cancellation_token())
+                                    .unwrap_or_default();
+                                let args_json_stream = manager
+                                    .with_session_history(&func_name_stream, args_json_stream, session_context.as_ref())
+                                    .await;
+                                let observer = manager.stream_chunk_observer(&func_name_stream, &args_json_stream).await;
+                                let type_builder = manager.tool_type_builder(&func_name_stream).await;
+                                // Created in the request's scope, so canceling the request
+                                // or passing its deadline trips the stream
+                                let stream_result = active
+                                    .bound("JavaScript BAML stream", async {
+                                        manager
+                                            .invoke_function_stream_with_cancel(
+                                                &func_name_stream,
+                                                args_json_stream,
+                                                cancel.clone(),
+                                                type_builder,
+                                            )
+                                            .await
+                                    })
+                                    .await;
+                                // The stream owns what it needs, so other calls can take the
+                                // manager while it runs
+                                drop(manager);
 
-                                // Create the stream
                                 let mut stream = match stream_result {
                                     Ok(s) => s,
                                     Err(e) => {
-                                        drop(manager); // Release lock
                                         let error_value = serde_json::json!({"error": format!("Failed to create stream: {}", e)});
-                                    if let Err(e) = tx.send(error_value).await {
-                                        tracing::warn!(error = ?e, "Stream channel send failed");
-                                    }
+                                        if let Err(e) = tx.send(error_value).await {
+                                            tracing::warn!(error = ?e, "Stream channel send failed");
+                                        }
                                         return;
                                     }
                                 };
-                                // We need to keep the manager lock during stream execution
-                                // because ctx_manager is a reference. For now, we'll collect all results
-                                // in the callback and then drop the lock.
+                                // Chunks pass through the LLM interceptors on their way to JS
+                                let (chunk_tx, chunk_rx) = mpsc::unbounded_channel::<serde_json::Value>();
+                                let forwarder = tokio::spawn(correlation::with_correlation_id(
+                                    correlation::current_or_new(),
+                                    forward_stream_chunks(chunk_rx, tx.clone(), observer, cancel),
+                                ));
+
                                 let env_vars = HashMap::new();
-                                let (final_result, _call_id) = {
-                                    stream.run(
-                                        None::<fn()>, // on_tick
-                                        Some(|result: baml_runtime::FunctionResult| {
+                                let final_result = stream
+                                    .run(
+                                        |result: baml_runtime::FunctionResult| {
                                             // Extract incremental result and send it
                                             // parsed() returns Option<Result<ResponseBamlValue, Error>>
                                             if let Some(Ok(parsed)) = result.parsed()
                                                 && let Ok(parsed_value) =
                                                     serde_json::to_value(parsed.serialize_partial())
-                                                && let Err(e) = tx.try_send(parsed_value)
                                             {
-                                                tracing::warn!(error = ?e, "Stream channel try_send failed");
+                                                let parsed_value = match budget.as_mut().map(|budget| budget.observe(parsed_value.clone())) {
+                                                    Some(ChunkVerdict::Exceeded) => return,
+                                                    Some(ChunkVerdict::Forward(value)) => value,
+                                                    None => parsed_value,
+                                                };
+                                                tracing::trace!(
+                                                    function = func_name_stream.as_str(),
+                                                    chunk = ?parsed_value,
+                                                    "BAML stream chunk"
+                                                );
+                                                if let Err(e) = chunk_tx.send(parsed_value) {
+                                                    tracing::warn!(error = ?e, "Stream chunk send failed");
+                                                }
                                             }
-                                        }),
-                                        ctx_manager,
-                                        None, // type_builder
-                                        None, // client_registry
+                                        },
                                         env_vars,
-                                    ).await
+                                    )
+                                    .await;
+
+                                // A stream stopped by an interceptor has already reported why
+                                drop(chunk_tx);
+                                if forwarder.await.unwrap_or(false) {
+                                    return;
+                                }
+
+                                // The final value counts against the budget as well
+                                let final_result = match (final_result, budget.as_mut()) {
+                                    (Ok(result), Some(budget)) => {
+                                        let final_value = match result.parsed() {
+                                            Some(Ok(parsed)) => serde_json::to_value(parsed.serialize_partial()).ok(),
+                                            _ => None,
+                                        };
+                                        if let Some(final_value) = final_value {
+                                            budget.observe(final_value);
+                                        }
+                                        Ok(result)
+                                    }
+                                    (result, _) => result,
                                 };
-                                drop(manager); // Release lock after stream completes
+
+                                // A stream cut off by the token limiter ends with the
+                                // annotated partial result instead of the abort error
+                                if let Some(budget) = budget.as_ref().filter(|budget| budget.exceeded()) {
+                                    tracing::info!(
+                                        function = func_name_stream.as_str(),
+                                        tokens = budget.used(),
+                                        "BAML stream truncated by output token limit"
+                                    );
+                                    metrics::record_stream_truncation(&func_name_stream);
+                                    if let Err(e) = tx.send(budget.annotate()).await {
+                                        tracing::warn!(error = ?e, "Stream channel send failed");
+                                    }
+                                    return;
+                                }
 
                                 // Send final result
                                 match final_result {
@@ -763,45 +1516,872 @@ impl QuickJSBridge {
     /// Register a single BAML function with QuickJS
     async fn register_single_function(&mut self, function_name: &str) -> Result<()> {
         // Register a JavaScript wrapper function that calls the Rust helper
-        // Use JSON.stringify to convert arguments to JSON
-        // Note: For now, we're using a synchronous approach, but the JS function is async
-        // to match the expected interface
+        // Positional arguments are named after the BAML parameters
+        let param_names = self.function_param_names_json(function_name).await?;
         let js_code = format!(
             r#"
             globalThis.{} = async function(...args) {{
-                // Convert arguments to a JSON object
-                const argObj = {{}};
-                // For now, handle simple cases - can be enhanced later
-                if (args.length === 1 && typeof args[0] === 'object') {{
-                    Object.assign(argObj, args[0]);
-                }} else {{
-                    // Try to map positional args to object properties
-                    // This is a simplified mapping - could be improved with function signatures
-                    args.forEach((arg, idx) => {{
-                        argObj[`arg${{idx}}`] = arg;
-                    }});
-                }}
-                
-                // Call the Rust helper function - JSON.stringify once here is efficient
+                const argObj = __bamlNamedArgs({}, args);
+
+                // The args object is converted on the Rust side without a JSON round trip
                 // The helper returns a promise that will resolve asynchronously
-                return await __baml_invoke("{}", JSON.stringify(argObj));
+                return await __baml_invoke("{}", argObj, globalThis.__baml_context_id);
             }};
             "#,
-            function_name, function_name
+            function_name, param_names, function_name
         );
 
         let script = Script::new("register_function.js", &js_code);
-        let _result =
-            self.runtime
-                .eval(None, script)
-                .await
-                .map_err(|e| BamlRtError::QuickJsWithSource {
-                    context: "Failed to register function".to_string(),
-                    source: Box::new(e),
-                })?;
-
-        tracing::debug!(function = function_name, "Registered function with QuickJS");
-
+        let _result = self
+            .runtime
+            .eval(self.realm_id(), script)
+            .await
+            .map_err(|e| BamlRtError::QuickJsWithSource {
+                context: "Failed to register function".to_string(),
+                source: Box::new(e),
+            })?;
+
+        tracing::debug!(function = function_name, "Registered function with QuickJS");
+
+        Ok(())
+    }
+
+    /// BAML parameter names for a function, as a JavaScript array literal
+    async fn function_param_names_json(&self, function_name: &str) -> Result<String> {
+        let manager = self.baml_manager.read().await;
+        let param_names = manager
+            .get_function_signature(function_name)
+            .map(|signature| signature.param_names.clone())
+            .unwrap_or_default();
+        drop(manager);
+        serde_json::to_string(&param_names).map_err(BamlRtError::Json)
+    }
+
+    /// Register the JS helper that maps wrapper arguments to a named argument object
+    ///
+    /// A single plain object whose keys are all parameter names is passed through
+    /// as named arguments. Otherwise arguments are assigned to parameters by
+    /// position. Functions without known parameter names fall back to `argN`.
+    async fn register_named_args_helper(&mut self) -> Result<()> {
+        let js_code = r#"
+            globalThis.__bamlNamedArgs = function(paramNames, args) {
+                const argObj = {};
+                const first = args[0];
+                const isPlainObject = args.length === 1
+                    && first !== null
+                    && typeof first === 'object'
+                    && !Array.isArray(first);
+                if (isPlainObject
+                    && (paramNames.length === 0
+                        || Object.keys(first).every((key) => paramNames.includes(key)))) {
+                    Object.assign(argObj, first);
+                    return argObj;
+                }
+                args.forEach((arg, idx) => {
+                    const name = idx < paramNames.length ? paramNames[idx] : `arg${idx}`;
+                    argObj[name] = arg;
+                });
+                return argObj;
+            };
+        "#;
+
+        let script = Script::new("register_named_args.js", js_code);
+        self.runtime
+            .eval(self.realm_id(), script)
+            .await
+            .map_err(|e| BamlRtError::QuickJsWithSource {
+                context: "Failed to register named argument helper".to_string(),
+                source: Box::new(e),
+            })?;
+        Ok(())
+    }
+
+    /// Register `__baml_list_functions()`, which resolves to a description
+    /// of each BAML function of the loaded schema: its `name`, `params`,
+    /// `returnType`, whether it is `streaming`, and its `docstring`
+    fn register_list_functions_helper(&self) -> Result<()> {
+        let manager_clone = self.baml_manager.clone();
+        self.set_host_function(
+            "__baml_list_functions",
+            move |_realm: &QuickJsRealmAdapter, _args: Vec<JsValueFacade>| -> std::result::Result<JsValueFacade, quickjs_runtime::jsutils::JsError> {
+                let manager_for_promise = manager_clone.clone();
+                Ok(JsValueFacade::new_promise::<JsValueFacade, _, ()>(async move {
+                    let functions = manager_for_promise.read().await.describe_functions();
+                    Ok(value_to_js_value_facade(serde_json::json!(functions)))
+                }))
+            },
+        ).map_err(|e| BamlRtError::QuickJsWithSource {
+            context: "Failed to register function listing helper".to_string(),
+            source: Box::new(e),
+        })
+    }
+
+    /// Register the `taskCreate`, `taskUpdateStatus`, and `taskAddArtifact`
+    /// globals over the manager's task operations
+    ///
+    /// Each resolves to the task as stored, and rejects when no task store
+    /// is configured. They act for the tenant, caller, and context of the
+    /// request being handled.
+    async fn register_task_helpers(&mut self) -> Result<()> {
+        let manager_clone = self.baml_manager.clone();
+        let active_context = self.active_context.clone();
+        self.set_host_function(
+            "__task_create",
+            move |_realm: &QuickJsRealmAdapter, args: Vec<JsValueFacade>| -> std::result::Result<JsValueFacade, quickjs_runtime::jsutils::JsError> {
+                let task: Value = match args.first() {
+                    Some(value) if value.is_string() => serde_json::from_str(value.get_str())
+                        .map_err(|e| quickjs_runtime::jsutils::JsError::new_str(&format!("Failed to parse task: {}", e)))?,
+                    _ => return Err(quickjs_runtime::jsutils::JsError::new_str("Expected a task JSON string")),
+                };
+                let active = host_active_context(&active_context);
+                let manager_for_promise = manager_clone.clone();
+                Ok(JsValueFacade::new_promise::<JsValueFacade, _, ()>(async move {
+                    let tasks = task_operations(&manager_for_promise).await?;
+                    let context_id = active.context_id.clone();
+                    let create = active.bound("JavaScript task creation", async move {
+                        match context_id {
+                            Some(context_id) => context::with_context_id(context_id, tasks.create(task)).await,
+                            None => tasks.create(task).await,
+                        }
+                    });
+                    let task = create.await
+                        .map_err(|e| quickjs_runtime::jsutils::JsError::new_str(&format!("Task create error: {}", e)))?;
+                    Ok(value_to_js_value_facade(task))
+                }))
+            },
+        ).map_err(|e| BamlRtError::QuickJsWithSource {
+            context: "Failed to register task create helper".to_string(),
+            source: Box::new(e),
+        })?;
+
+        let manager_clone = self.baml_manager.clone();
+        let active_context = self.active_context.clone();
+        self.set_host_function(
+            "__task_update_status",
+            move |_realm: &QuickJsRealmAdapter, args: Vec<JsValueFacade>| -> std::result::Result<JsValueFacade, quickjs_runtime::jsutils::JsError> {
+                if args.len() < 3 || !args.iter().take(3).all(|value| value.is_string()) {
+                    return Err(quickjs_runtime::jsutils::JsError::new_str("Expected 3 string arguments: task ID, state and message JSON"));
+                }
+                let task_id = args[0].get_str().to_string();
+                let state = args[1].get_str().to_string();
+                let message: Value = serde_json::from_str(args[2].get_str())
+                    .map_err(|e| quickjs_runtime::jsutils::JsError::new_str(&format!("Failed to parse status message: {}", e)))?;
+                let message = Some(message).filter(|message| !message.is_null());
+                let active = host_active_context(&active_context);
+                let manager_for_promise = manager_clone.clone();
+                Ok(JsValueFacade::new_promise::<JsValueFacade, _, ()>(async move {
+                    let tasks = task_operations(&manager_for_promise).await?;
+                    let task = active
+                        .bound("JavaScript task update", tasks.update_status(&task_id, &state, message))
+                        .await
+                        .map_err(|e| quickjs_runtime::jsutils::JsError::new_str(&format!("Task update error: {}", e)))?;
+                    Ok(value_to_js_value_facade(task))
+                }))
+            },
+        ).map_err(|e| BamlRtError::QuickJsWithSource {
+            context: "Failed to register task status helper".to_string(),
+            source: Box::new(e),
+        })?;
+
+        let manager_clone = self.baml_manager.clone();
+        let active_context = self.active_context.clone();
+        self.set_host_function(
+            "__task_add_artifact",
+            move |_realm: &QuickJsRealmAdapter, args: Vec<JsValueFacade>| -> std::result::Result<JsValueFacade, quickjs_runtime::jsutils::JsError> {
+                if args.len() < 2 || !args.iter().take(2).all(|value| value.is_string()) {
+                    return Err(quickjs_runtime::jsutils::JsError::new_str("Expected 2 string arguments: task ID and artifact JSON"));
+                }
+                let task_id = args[0].get_str().to_string();
+                let artifact: Value = serde_json::from_str(args[1].get_str())
+                    .map_err(|e| quickjs_runtime::jsutils::JsError::new_str(&format!("Failed to parse artifact: {}", e)))?;
+                let active = host_active_context(&active_context);
+                let manager_for_promise = manager_clone.clone();
+                Ok(JsValueFacade::new_promise::<JsValueFacade, _, ()>(async move {
+                    let tasks = task_operations(&manager_for_promise).await?;
+                    let task = active
+                        .bound("JavaScript task artifact", tasks.add_artifact(&task_id, artifact))
+                        .await
+                        .map_err(|e| quickjs_runtime::jsutils::JsError::new_str(&format!("Task artifact error: {}", e)))?;
+                    Ok(value_to_js_value_facade(task))
+                }))
+            },
+        ).map_err(|e| BamlRtError::QuickJsWithSource {
+            context: "Failed to register task artifact helper".to_string(),
+            source: Box::new(e),
+        })?;
+
+        let js_code = r#"
+            globalThis.taskCreate = function(task) {
+                return __task_create(JSON.stringify(task ?? {}));
+            };
+            globalThis.taskUpdateStatus = function(taskId, state, message) {
+                return __task_update_status(String(taskId), String(state), JSON.stringify(message ?? null));
+            };
+            globalThis.taskAddArtifact = function(taskId, artifact) {
+                return __task_add_artifact(String(taskId), JSON.stringify(artifact ?? {}));
+            };
+        "#;
+
+        let script = Script::new("register_tasks.js", js_code);
+        self.runtime
+            .eval(self.realm_id(), script)
+            .await
+            .map_err(|e| BamlRtError::QuickJsWithSource {
+                context: "Failed to register task API".to_string(),
+                source: Box::new(e),
+            })?;
+        Ok(())
+    }
+
+    /// Register the `kvGet`, `kvSet`, and `kvDelete` globals over the
+    /// manager's key-value store
+    ///
+    /// `kvGet` resolves to the stored value or `null`, and `kvDelete` to
+    /// whether the key was set. Each takes `{ scope: "context" }` as its last
+    /// argument to use keys of the current context only.
+    async fn register_kv_helpers(&mut self) -> Result<()> {
+        let agent = self
+            .baml_manager
+            .read()
+            .await
+            .agent_name()
+            .unwrap_or(DEFAULT_AGENT_NAMESPACE)
+            .to_string();
+
+        let manager_clone = self.baml_manager.clone();
+        let active_context = self.active_context.clone();
+        let agent_clone = agent.clone();
+        self.set_host_function(
+            "__kv_get",
+            move |_realm: &QuickJsRealmAdapter, args: Vec<JsValueFacade>| -> std::result::Result<JsValueFacade, quickjs_runtime::jsutils::JsError> {
+                let active = host_active_context(&active_context);
+                let (key, _, namespace) = kv_args(&args, 2, &agent_clone, &active)?;
+                let manager_for_promise = manager_clone.clone();
+                Ok(JsValueFacade::new_promise::<JsValueFacade, _, ()>(async move {
+                    let store = kv_store(&manager_for_promise).await?;
+                    let value = active
+                        .bound("JavaScript kv read", store.get(&namespace, &key))
+                        .await
+                        .map_err(|e| quickjs_runtime::jsutils::JsError::new_str(&format!("kv error: {}", e)))?;
+                    Ok(value_to_js_value_facade(value.unwrap_or(Value::Null)))
+                }))
+            },
+        ).map_err(|e| BamlRtError::QuickJsWithSource {
+            context: "Failed to register kv get helper".to_string(),
+            source: Box::new(e),
+        })?;
+
+        let manager_clone = self.baml_manager.clone();
+        let active_context = self.active_context.clone();
+        let agent_clone = agent.clone();
+        self.set_host_function(
+            "__kv_set",
+            move |_realm: &QuickJsRealmAdapter, args: Vec<JsValueFacade>| -> std::result::Result<JsValueFacade, quickjs_runtime::jsutils::JsError> {
+                let active = host_active_context(&active_context);
+                let (key, mut values, namespace) = kv_args(&args, 3, &agent_clone, &active)?;
+                let value = values.pop().unwrap_or(Value::Null);
+                let manager_for_promise = manager_clone.clone();
+                Ok(JsValueFacade::new_promise::<JsValueFacade, _, ()>(async move {
+                    let store = kv_store(&manager_for_promise).await?;
+                    active
+                        .bound("JavaScript kv write", store.set(&namespace, &key, value))
+                        .await
+                        .map_err(|e| quickjs_runtime::jsutils::JsError::new_str(&format!("kv error: {}", e)))?;
+                    Ok(JsValueFacade::Null)
+                }))
+            },
+        ).map_err(|e| BamlRtError::QuickJsWithSource {
+            context: "Failed to register kv set helper".to_string(),
+            source: Box::new(e),
+        })?;
+
+        let manager_clone = self.baml_manager.clone();
+        let active_context = self.active_context.clone();
+        self.set_host_function(
+            "__kv_delete",
+            move |_realm: &QuickJsRealmAdapter, args: Vec<JsValueFacade>| -> std::result::Result<JsValueFacade, quickjs_runtime::jsutils::JsError> {
+                let active = host_active_context(&active_context);
+                let (key, _, namespace) = kv_args(&args, 2, &agent, &active)?;
+                let manager_for_promise = manager_clone.clone();
+                Ok(JsValueFacade::new_promise::<JsValueFacade, _, ()>(async move {
+                    let store = kv_store(&manager_for_promise).await?;
+                    let deleted = active
+                        .bound("JavaScript kv delete", store.delete(&namespace, &key))
+                        .await
+                        .map_err(|e| quickjs_runtime::jsutils::JsError::new_str(&format!("kv error: {}", e)))?;
+                    Ok(JsValueFacade::new_bool(deleted))
+                }))
+            },
+        ).map_err(|e| BamlRtError::QuickJsWithSource {
+            context: "Failed to register kv delete helper".to_string(),
+            source: Box::new(e),
+        })?;
+
+        let js_code = r#"
+            globalThis.kvGet = function(key, options) {
+                return __kv_get(String(key), JSON.stringify(options ?? {}));
+            };
+            globalThis.kvSet = function(key, value, options) {
+                return __kv_set(String(key), JSON.stringify(value ?? null), JSON.stringify(options ?? {}));
+            };
+            globalThis.kvDelete = function(key, options) {
+                return __kv_delete(String(key), JSON.stringify(options ?? {}));
+            };
+        "#;
+
+        let script = Script::new("register_kv.js", js_code);
+        self.runtime
+            .eval(self.realm_id(), script)
+            .await
+            .map_err(|e| BamlRtError::QuickJsWithSource {
+                context: "Failed to register kv API".to_string(),
+                source: Box::new(e),
+            })?;
+        Ok(())
+    }
+
+    /// Register the `embed(textOrTexts, model)` global
+    ///
+    /// Resolves to one vector for a string and to a vector per text for an
+    /// array, computed by [`BamlRuntimeManager::embed_batch`].
+    async fn register_embed_helper(&mut self) -> Result<()> {
+        let manager_clone = self.baml_manager.clone();
+        let active_context = self.active_context.clone();
+        self.set_host_function(
+            "__baml_embed",
+            move |_realm: &QuickJsRealmAdapter, args: Vec<JsValueFacade>| -> std::result::Result<JsValueFacade, quickjs_runtime::jsutils::JsError> {
+                if args.len() < 2 || !args[0].is_string() || !args[1].is_string() {
+                    return Err(quickjs_runtime::jsutils::JsError::new_str("Expected 2 string arguments: texts JSON and model"));
+                }
+                let texts: Vec<String> = serde_json::from_str(args[0].get_str())
+                    .map_err(|e| quickjs_runtime::jsutils::JsError::new_str(&format!("Failed to parse texts: {}", e)))?;
+                let model = args[1].get_str().to_string();
+                let active = host_active_context(&active_context);
+                let manager_for_promise = manager_clone.clone();
+                Ok(JsValueFacade::new_promise::<JsValueFacade, _, ()>(async move {
+                    let manager = manager_for_promise.read().await;
+                    let vectors = active
+                        .bound("JavaScript embedding", manager.embed_batch(&texts, &model))
+                        .await
+                        .map_err(|e| quickjs_runtime::jsutils::JsError::new_str(&format!("Embedding error: {}", e)))?;
+                    Ok(value_to_js_value_facade(serde_json::json!(vectors)))
+                }))
+            },
+        ).map_err(|e| BamlRtError::QuickJsWithSource {
+            context: "Failed to register embed helper".to_string(),
+            source: Box::new(e),
+        })?;
+
+        let js_code = r#"
+            globalThis.embed = async function(input, model) {
+                const texts = Array.isArray(input) ? input.map(String) : [String(input)];
+                const vectors = await __baml_embed(JSON.stringify(texts), String(model));
+                return Array.isArray(input) ? vectors : vectors[0];
+            };
+        "#;
+
+        let script = Script::new("register_embed.js", js_code);
+        self.runtime
+            .eval(self.realm_id(), script)
+            .await
+            .map_err(|e| BamlRtError::QuickJsWithSource {
+                context: "Failed to register embed API".to_string(),
+                source: Box::new(e),
+            })?;
+        Ok(())
+    }
+
+    /// Register the `registerLLMInterceptor(fn, { name, priority })` global
+    ///
+    /// `fn` joins the manager's LLM pipeline as a [`JsLlmInterceptor`]. The
+    /// returned promise resolves to the interceptor's name once it is
+    /// registered. Unnamed interceptors are numbered in registration order,
+    /// so a replacement bridge running the same init code replaces the
+    /// interceptors of the bridge before it instead of adding to them.
+    async fn register_interceptor_helpers(&mut self) -> Result<()> {
+        let manager_clone = self.baml_manager.clone();
+        self.set_host_function(
+            "__register_llm_interceptor",
+            move |_realm: &QuickJsRealmAdapter, mut args: Vec<JsValueFacade>| -> std::result::Result<JsValueFacade, quickjs_runtime::jsutils::JsError> {
+                if args.len() < 3 || !args[1].is_string() || !args[2].is_string() {
+                    return Err(quickjs_runtime::jsutils::JsError::new_str("Expected a function, a name and a priority"));
+                }
+                let name = args[1].get_str().to_string();
+                // The priority arrives as a string; numbers are awkward to read from JsValueFacade
+                let priority = args[2].get_str().parse::<i32>()
+                    .map_err(|e| quickjs_runtime::jsutils::JsError::new_str(&format!("Invalid interceptor priority: {}", e)))?;
+                let JsValueFacade::JsFunction { cached_function } = args.swap_remove(0) else {
+                    return Err(quickjs_runtime::jsutils::JsError::new_str("First argument must be a function"));
+                };
+                let interceptor = JsLlmInterceptor::new(cached_function);
+
+                let manager_for_promise = manager_clone.clone();
+                Ok(JsValueFacade::new_promise::<JsValueFacade, _, ()>(async move {
+                    let registry = manager_for_promise.read().await.interceptor_registry();
+                    let mut registry = registry.lock().await;
+                    let registered = if registry.llm_pipeline().contains(&name) {
+                        registry.replace_llm_interceptor(&name, interceptor)
+                    } else {
+                        registry.register_named_llm_interceptor(&name, priority, interceptor)
+                    };
+                    registered.map_err(|e| quickjs_runtime::jsutils::JsError::new_str(&format!("Interceptor registration error: {}", e)))?;
+                    Ok(JsValueFacade::new_str(&name))
+                }))
+            },
+        ).map_err(|e| BamlRtError::QuickJsWithSource {
+            context: "Failed to register LLM interceptor helper".to_string(),
+            source: Box::new(e),
+        })?;
+
+        let js_code = r#"
+            globalThis.__llm_interceptor_count = 0;
+            globalThis.registerLLMInterceptor = function(fn, options = {}) {
+                if (typeof fn !== 'function') {
+                    throw new TypeError('registerLLMInterceptor expects a function');
+                }
+                globalThis.__llm_interceptor_count += 1;
+                const name = options.name === undefined
+                    ? `js-llm-interceptor-${globalThis.__llm_interceptor_count}`
+                    : String(options.name);
+                return __register_llm_interceptor(fn, name, String(options.priority ?? 0));
+            };
+        "#;
+
+        let script = Script::new("register_interceptors.js", js_code);
+        self.runtime
+            .eval(self.realm_id(), script)
+            .await
+            .map_err(|e| BamlRtError::QuickJsWithSource {
+                context: "Failed to register registerLLMInterceptor API".to_string(),
+                source: Box::new(e),
+            })?;
+        Ok(())
+    }
+
+    /// Register the `session` global over the manager's session store
+    ///
+    /// `session.get(limit)` and `session.append(role, content)` act on the
+    /// conversation of `globalThis.__baml_context_id` and reject when no
+    /// store is configured.
+    async fn register_session_helpers(&mut self) -> Result<()> {
+        let manager_clone = self.baml_manager.clone();
+        self.set_host_function(
+            "__session_get",
+            move |_realm: &QuickJsRealmAdapter, args: Vec<JsValueFacade>| -> std::result::Result<JsValueFacade, quickjs_runtime::jsutils::JsError> {
+                let context_id = match args.first() {
+                    Some(value) if value.is_string() => ContextId::from(value.get_str()),
+                    _ => return Err(quickjs_runtime::jsutils::JsError::new_str("First argument must be a string (context ID)")),
+                };
+                // The limit arrives as a string; numbers are awkward to read from JsValueFacade
+                let limit = args
+                    .get(1)
+                    .filter(|value| value.is_string())
+                    .and_then(|value| value.get_str().parse::<usize>().ok());
+
+                let manager_for_promise = manager_clone.clone();
+                Ok(JsValueFacade::new_promise::<JsValueFacade, _, ()>(async move {
+                    let store = session_store(&manager_for_promise).await?;
+                    let messages = store
+                        .get(&context_id, limit)
+                        .await
+                        .map_err(|e| quickjs_runtime::jsutils::JsError::new_str(&format!("Session read error: {}", e)))?;
+                    Ok(value_to_js_value_facade(serde_json::json!(messages)))
+                }))
+            },
+        ).map_err(|e| BamlRtError::QuickJsWithSource {
+            context: "Failed to register session get helper".to_string(),
+            source: Box::new(e),
+        })?;
+
+        let manager_clone = self.baml_manager.clone();
+        self.set_host_function(
+            "__session_append",
+            move |_realm: &QuickJsRealmAdapter, args: Vec<JsValueFacade>| -> std::result::Result<JsValueFacade, quickjs_runtime::jsutils::JsError> {
+                if args.len() < 3 || !args.iter().take(3).all(|value| value.is_string()) {
+                    return Err(quickjs_runtime::jsutils::JsError::new_str("Expected 3 string arguments: context ID, role and content JSON"));
+                }
+                let context_id = ContextId::from(args[0].get_str());
+                let role = args[1].get_str().to_string();
+                let content: Value = serde_json::from_str(args[2].get_str())
+                    .map_err(|e| quickjs_runtime::jsutils::JsError::new_str(&format!("Failed to parse message content: {}", e)))?;
+
+                let manager_for_promise = manager_clone.clone();
+                Ok(JsValueFacade::new_promise::<JsValueFacade, _, ()>(async move {
+                    let store = session_store(&manager_for_promise).await?;
+                    store
+                        .append(&context_id, SessionMessage { role, content })
+                        .await
+                        .map_err(|e| quickjs_runtime::jsutils::JsError::new_str(&format!("Session write error: {}", e)))?;
+                    Ok(value_to_js_value_facade(Value::Null))
+                }))
+            },
+        ).map_err(|e| BamlRtError::QuickJsWithSource {
+            context: "Failed to register session append helper".to_string(),
+            source: Box::new(e),
+        })?;
+
+        let js_code = r#"
+            globalThis.session = {
+                __contextId() {
+                    const contextId = globalThis.__baml_context_id;
+                    if (typeof contextId !== 'string') {
+                        throw new Error('session is only available while handling a request with a context ID');
+                    }
+                    return contextId;
+                },
+                get(limit) {
+                    return __session_get(this.__contextId(), limit === undefined ? undefined : String(limit));
+                },
+                append(role, content) {
+                    return __session_append(this.__contextId(), String(role), JSON.stringify(content ?? null));
+                },
+            };
+        "#;
+
+        let script = Script::new("register_session.js", js_code);
+        self.runtime
+            .eval(self.realm_id(), script)
+            .await
+            .map_err(|e| BamlRtError::QuickJsWithSource {
+                context: "Failed to register session API".to_string(),
+                source: Box::new(e),
+            })?;
+        Ok(())
+    }
+
+    /// Record the calling task's correlation and context for host callbacks
+    fn capture_active_context(&self) {
+        if let Ok(mut active) = self.active_correlation.lock() {
+            *active = correlation::current_correlation_id();
+        }
+        if let Ok(mut active) = self.active_context.lock() {
+            *active = ActiveContext::capture();
+        }
+    }
+
+    /// Register the `bamlContext()` global
+    ///
+    /// `bamlContext()` returns `{ contextId, correlationId, metadata }` for
+    /// the request being handled (IDs are `null` outside one), and
+    /// `setMetadata(key, value)` on the result adds to the request's
+    /// metadata, where host code and later calls in the request see it.
+    async fn register_context_helpers(&mut self) -> Result<()> {
+        let active_correlation = self.active_correlation.clone();
+        let active_context = self.active_context.clone();
+        self.set_host_function(
+            "__baml_context_get",
+            move |_realm: &QuickJsRealmAdapter, _args: Vec<JsValueFacade>| -> std::result::Result<JsValueFacade, quickjs_runtime::jsutils::JsError> {
+                let correlation_id = active_correlation.lock().ok().and_then(|active| active.clone());
+                let active = host_active_context(&active_context);
+                Ok(value_to_js_value_facade(serde_json::json!({
+                    "contextId": active.context_id,
+                    "correlationId": correlation_id,
+                    "metadata": active.metadata.snapshot(),
+                })))
+            },
+        ).map_err(|e| BamlRtError::QuickJsWithSource {
+            context: "Failed to register context get helper".to_string(),
+            source: Box::new(e),
+        })?;
+
+        let active_context = self.active_context.clone();
+        self.set_host_function(
+            "__baml_context_set_metadata",
+            move |_realm: &QuickJsRealmAdapter, args: Vec<JsValueFacade>| -> std::result::Result<JsValueFacade, quickjs_runtime::jsutils::JsError> {
+                if args.len() < 2 || !args.iter().take(2).all(|value| value.is_string()) {
+                    return Err(quickjs_runtime::jsutils::JsError::new_str("Expected 2 string arguments: key and value JSON"));
+                }
+                let value: Value = serde_json::from_str(args[1].get_str())
+                    .map_err(|e| quickjs_runtime::jsutils::JsError::new_str(&format!("Failed to parse metadata value: {}", e)))?;
+                if let Ok(active) = active_context.lock() {
+                    active.metadata.set(args[0].get_str(), value);
+                }
+                Ok(JsValueFacade::Undefined)
+            },
+        ).map_err(|e| BamlRtError::QuickJsWithSource {
+            context: "Failed to register context metadata helper".to_string(),
+            source: Box::new(e),
+        })?;
+
+        let js_code = r#"
+            globalThis.bamlContext = function() {
+                const context = __baml_context_get();
+                context.setMetadata = function(key, value) {
+                    const normalized = value === undefined ? null : value;
+                    __baml_context_set_metadata(String(key), JSON.stringify(normalized));
+                    context.metadata[String(key)] = normalized;
+                    return context;
+                };
+                return context;
+            };
+        "#;
+
+        let script = Script::new("register_context.js", js_code);
+        self.runtime
+            .eval(self.realm_id(), script)
+            .await
+            .map_err(|e| BamlRtError::QuickJsWithSource {
+                context: "Failed to register bamlContext API".to_string(),
+                source: Box::new(e),
+            })?;
+        Ok(())
+    }
+
+    /// Register the `artifacts` global over the manager's artifact store
+    ///
+    /// `artifacts.put(content, { mediaType, name, encoding })` stores a
+    /// string (UTF-8, or base64 with `encoding: 'base64'`) or a JSON value
+    /// and resolves to its `{ artifactId, mediaType, name, size }`.
+    /// `artifacts.get(id)` resolves to the same fields plus base64
+    /// `content`, or `null` for unknown ids.
+    async fn register_artifact_helpers(&mut self) -> Result<()> {
+        let manager_clone = self.baml_manager.clone();
+        self.set_host_function(
+            "__artifact_put",
+            move |_realm: &QuickJsRealmAdapter, args: Vec<JsValueFacade>| -> std::result::Result<JsValueFacade, quickjs_runtime::jsutils::JsError> {
+                if args.len() < 2 || !args.iter().take(2).all(|value| value.is_string()) {
+                    return Err(quickjs_runtime::jsutils::JsError::new_str("Expected 2 string arguments: content and options JSON"));
+                }
+                let options: Value = serde_json::from_str(args[1].get_str())
+                    .map_err(|e| quickjs_runtime::jsutils::JsError::new_str(&format!("Failed to parse artifact options: {}", e)))?;
+                let option = |key: &str| options.get(key).and_then(Value::as_str).map(str::to_string);
+                let content = match option("encoding").as_deref() {
+                    Some("base64") => artifact_store::decode_base64(args[0].get_str())
+                        .map_err(|e| quickjs_runtime::jsutils::JsError::new_str(&e.to_string()))?,
+                    _ => args[0].get_str().as_bytes().to_vec(),
+                };
+                let media_type = option("mediaType");
+                let name = option("name");
+
+                let manager_for_promise = manager_clone.clone();
+                Ok(JsValueFacade::new_promise::<JsValueFacade, _, ()>(async move {
+                    let store = artifact_store(&manager_for_promise).await?;
+                    let info = store
+                        .put(content, media_type, name)
+                        .await
+                        .map_err(|e| quickjs_runtime::jsutils::JsError::new_str(&format!("Artifact write error: {}", e)))?;
+                    Ok(value_to_js_value_facade(serde_json::json!(info)))
+                }))
+            },
+        ).map_err(|e| BamlRtError::QuickJsWithSource {
+            context: "Failed to register artifact put helper".to_string(),
+            source: Box::new(e),
+        })?;
+
+        let manager_clone = self.baml_manager.clone();
+        self.set_host_function(
+            "__artifact_get",
+            move |_realm: &QuickJsRealmAdapter, args: Vec<JsValueFacade>| -> std::result::Result<JsValueFacade, quickjs_runtime::jsutils::JsError> {
+                let artifact_id = match args.first() {
+                    Some(value) if value.is_string() => ArtifactId::from(value.get_str()),
+                    _ => return Err(quickjs_runtime::jsutils::JsError::new_str("First argument must be a string (artifact ID)")),
+                };
+
+                let manager_for_promise = manager_clone.clone();
+                Ok(JsValueFacade::new_promise::<JsValueFacade, _, ()>(async move {
+                    let store = artifact_store(&manager_for_promise).await?;
+                    let stored = store
+                        .get(&artifact_id)
+                        .await
+                        .map_err(|e| quickjs_runtime::jsutils::JsError::new_str(&format!("Artifact read error: {}", e)))?;
+                    let value = match stored {
+                        Some(stored) => {
+                            let mut value = serde_json::json!(stored.info);
+                            value["content"] = Value::String(stored.content_base64());
+                            value
+                        }
+                        None => Value::Null,
+                    };
+                    Ok(value_to_js_value_facade(value))
+                }))
+            },
+        ).map_err(|e| BamlRtError::QuickJsWithSource {
+            context: "Failed to register artifact get helper".to_string(),
+            source: Box::new(e),
+        })?;
+
+        let js_code = r#"
+            globalThis.artifacts = {
+                put(content, options) {
+                    const opts = options || {};
+                    let mediaType = opts.mediaType;
+                    if (typeof content !== 'string') {
+                        content = JSON.stringify(content);
+                        mediaType = mediaType || 'application/json';
+                    }
+                    return __artifact_put(content, JSON.stringify({
+                        mediaType: mediaType,
+                        name: opts.name,
+                        encoding: opts.encoding,
+                    }));
+                },
+                get(id) {
+                    return __artifact_get(String(id));
+                },
+            };
+        "#;
+
+        let script = Script::new("register_artifacts.js", js_code);
+        self.runtime
+            .eval(self.realm_id(), script)
+            .await
+            .map_err(|e| BamlRtError::QuickJsWithSource {
+                context: "Failed to register artifacts API".to_string(),
+                source: Box::new(e),
+            })?;
+        Ok(())
+    }
+
+    /// Register the `callAgent` global over the manager's agent caller
+    ///
+    /// `callAgent(target, method, params)` resolves to the remote result.
+    /// `callAgent.stream(target, method, params)` returns an async iterable
+    /// over the results of a streaming method; leaving a `for await` loop
+    /// early closes the stream.
+    async fn register_agent_call_helpers(&mut self) -> Result<()> {
+        let manager_clone = self.baml_manager.clone();
+        let active_context = self.active_context.clone();
+        self.set_host_function(
+            "__call_agent",
+            move |_realm: &QuickJsRealmAdapter, args: Vec<JsValueFacade>| -> std::result::Result<JsValueFacade, quickjs_runtime::jsutils::JsError> {
+                let (target, method, params) = agent_call_args(&args)?;
+                check_agent_access(&host_active_context(&active_context), &target)?;
+                let manager_for_promise = manager_clone.clone();
+                Ok(JsValueFacade::new_promise::<JsValueFacade, _, ()>(async move {
+                    let caller = agent_caller(&manager_for_promise).await?;
+                    let result = caller
+                        .call(&target, &method, params)
+                        .await
+                        .map_err(|e| quickjs_runtime::jsutils::JsError::new_str(&e.to_string()))?;
+                    Ok(value_to_js_value_facade(result))
+                }))
+            },
+        ).map_err(|e| BamlRtError::QuickJsWithSource {
+            context: "Failed to register agent call helper".to_string(),
+            source: Box::new(e),
+        })?;
+
+        let streams: OpenAgentStreams = Arc::default();
+        let next_handle = Arc::new(AtomicU64::new(0));
+
+        let manager_clone = self.baml_manager.clone();
+        let streams_clone = streams.clone();
+        let active_context = self.active_context.clone();
+        self.set_host_function(
+            "__call_agent_stream",
+            move |_realm: &QuickJsRealmAdapter, args: Vec<JsValueFacade>| -> std::result::Result<JsValueFacade, quickjs_runtime::jsutils::JsError> {
+                let (target, method, params) = agent_call_args(&args)?;
+                check_agent_access(&host_active_context(&active_context), &target)?;
+                let manager_for_promise = manager_clone.clone();
+                let streams = streams_clone.clone();
+                let handle_id = next_handle.fetch_add(1, Ordering::Relaxed);
+                let handle = format!("agent-stream-{}", handle_id);
+                Ok(JsValueFacade::new_promise::<JsValueFacade, _, ()>(async move {
+                    let caller = agent_caller(&manager_for_promise).await?;
+                    let stream = caller
+                        .stream(&target, &method, params)
+                        .await
+                        .map_err(|e| quickjs_runtime::jsutils::JsError::new_str(&e.to_string()))?;
+                    streams
+                        .lock()
+                        .unwrap_or_else(|e| e.into_inner())
+                        .insert(handle.clone(), Arc::new(Mutex::new(stream)));
+                    Ok(value_to_js_value_facade(Value::String(handle)))
+                }))
+            },
+        ).map_err(|e| BamlRtError::QuickJsWithSource {
+            context: "Failed to register agent stream helper".to_string(),
+            source: Box::new(e),
+        })?;
+
+        let streams_clone = streams.clone();
+        self.set_host_function(
+            "__call_agent_next",
+            move |_realm: &QuickJsRealmAdapter, args: Vec<JsValueFacade>| -> std::result::Result<JsValueFacade, quickjs_runtime::jsutils::JsError> {
+                let handle = match args.first() {
+                    Some(value) if value.is_string() => value.get_str().to_string(),
+                    _ => return Err(quickjs_runtime::jsutils::JsError::new_str("First argument must be a string (stream handle)")),
+                };
+                let streams = streams_clone.clone();
+                Ok(JsValueFacade::new_promise::<JsValueFacade, _, ()>(async move {
+                    let stream = streams
+                        .lock()
+                        .unwrap_or_else(|e| e.into_inner())
+                        .get(&handle)
+                        .cloned();
+                    let next = match stream {
+                        Some(stream) => stream.lock().await.recv().await,
+                        None => None,
+                    };
+                    let step = match next {
+                        Some(Ok(value)) => serde_json::json!({ "done": false, "value": value }),
+                        Some(Err(e)) => {
+                            streams.lock().unwrap_or_else(|e| e.into_inner()).remove(&handle);
+                            return Err(quickjs_runtime::jsutils::JsError::new_str(&e.to_string()));
+                        }
+                        None => {
+                            streams.lock().unwrap_or_else(|e| e.into_inner()).remove(&handle);
+                            serde_json::json!({ "done": true })
+                        }
+                    };
+                    Ok(value_to_js_value_facade(step))
+                }))
+            },
+        ).map_err(|e| BamlRtError::QuickJsWithSource {
+            context: "Failed to register agent stream next helper".to_string(),
+            source: Box::new(e),
+        })?;
+
+        self.set_host_function(
+            "__call_agent_close",
+            move |_realm: &QuickJsRealmAdapter, args: Vec<JsValueFacade>| -> std::result::Result<JsValueFacade, quickjs_runtime::jsutils::JsError> {
+                if let Some(handle) = args.first().filter(|value| value.is_string()) {
+                    streams.lock().unwrap_or_else(|e| e.into_inner()).remove(handle.get_str());
+                }
+                Ok(JsValueFacade::Undefined)
+            },
+        ).map_err(|e| BamlRtError::QuickJsWithSource {
+            context: "Failed to register agent stream close helper".to_string(),
+            source: Box::new(e),
+        })?;
+
+        let js_code = r#"
+            (function() {
+                const encodeParams = (params) => JSON.stringify(params === undefined ? {} : params);
+                globalThis.callAgent = function(target, method, params) {
+                    return __call_agent(String(target), String(method), encodeParams(params));
+                };
+                globalThis.callAgent.stream = function(target, method, params) {
+                    const opened = __call_agent_stream(
+                        String(target),
+                        String(method),
+                        encodeParams(params),
+                    );
+                    return {
+                        [Symbol.asyncIterator]() {
+                            return {
+                                async next() {
+                                    return __call_agent_next(await opened);
+                                },
+                                async return() {
+                                    __call_agent_close(await opened);
+                                    return { done: true };
+                                },
+                            };
+                        },
+                    };
+                };
+            })();
+        "#;
+
+        let script = Script::new("register_call_agent.js", js_code);
+        self.runtime
+            .eval(self.realm_id(), script)
+            .await
+            .map_err(|e| BamlRtError::QuickJsWithSource {
+                context: "Failed to register callAgent API".to_string(),
+                source: Box::new(e),
+            })?;
         Ok(())
     }
 
@@ -809,39 +2389,32 @@ impl QuickJSBridge {
     async fn register_single_stream_function(&mut self, function_name: &str) -> Result<()> {
         // Register a JavaScript wrapper function for streaming
         let stream_function_name = format!("{}Stream", function_name);
+        let param_names = self.function_param_names_json(function_name).await?;
         let js_code = format!(
             r#"
             globalThis.{} = async function(...args) {{
-                // Convert arguments to a JSON object
-                const argObj = {{}};
-                if (args.length === 1 && typeof args[0] === 'object') {{
-                    Object.assign(argObj, args[0]);
-                }} else {{
-                    args.forEach((arg, idx) => {{
-                        argObj[`arg${{idx}}`] = arg;
-                    }});
-                }}
-                
-                // Call the Rust streaming helper function - JSON.stringify once here
+                const argObj = __bamlNamedArgs({}, args);
+
+                // Call the Rust streaming helper function
                 // This returns an array of incremental results
-                const results = await __baml_stream("{}", JSON.stringify(argObj));
+                const results = await __baml_stream("{}", argObj, globalThis.__baml_context_id);
                 
                 // Return the array directly - JavaScript can iterate over it
                 return results;
             }};
             "#,
-            stream_function_name, function_name
+            stream_function_name, param_names, function_name
         );
 
         let script = Script::new("register_stream_function.js", &js_code);
-        let _result =
-            self.runtime
-                .eval(None, script)
-                .await
-                .map_err(|e| BamlRtError::QuickJsWithSource {
-                    context: "Failed to register stream function".to_string(),
-                    source: Box::new(e),
-                })?;
+        let _result = self
+            .runtime
+            .eval(self.realm_id(), script)
+            .await
+            .map_err(|e| BamlRtError::QuickJsWithSource {
+                context: "Failed to register stream function".to_string(),
+                source: Box::new(e),
+            })?;
 
         tracing::debug!(
             function = function_name,
@@ -852,12 +2425,100 @@ impl QuickJSBridge {
         Ok(())
     }
 
+    /// Register the source map of a script loaded under `script_name`
+    ///
+    /// Stack frames in that script are then reported at their original
+    /// positions in JS error messages.
+    pub fn register_source_map(&mut self, script_name: &str, source_map: &str) -> Result<()> {
+        self.source_maps
+            .register(script_name, SourceMap::parse(source_map)?, 0);
+        Ok(())
+    }
+
+    /// Load a script such as an agent bundle under its own name
+    ///
+    /// The code runs once for its side effects (typically assigning to
+    /// `globalThis`). With a `source_map`, errors thrown from the script,
+    /// now or when its functions are called later, are mapped back to the
+    /// original sources.
+    pub async fn evaluate_script(
+        &mut self,
+        script_name: &str,
+        code: &str,
+        source_map: Option<&str>,
+    ) -> Result<()> {
+        if let Some(source_map) = source_map {
+            // The wrapper below puts the script's first line on line 2
+            self.source_maps
+                .register(script_name, SourceMap::parse(source_map)?, 1);
+        }
+        self.capture_active_context();
+
+        let wrapped = format!("(function() {{\n{}\n}})()", code);
+        let script = Script::new(script_name, &wrapped);
+        self.runtime
+            .eval(self.realm_id(), script)
+            .await
+            .map_err(|e| self.eval_error(&format!("Failed to execute {}", script_name), e))?;
+        Ok(())
+    }
+
+    /// Error for code the engine failed to run, with stack frames mapped to
+    /// original sources where possible
+    fn eval_error(&self, context: &str, error: quickjs_runtime::jsutils::JsError) -> BamlRtError {
+        if is_out_of_memory(error.get_name(), error.get_message()) {
+            return engine_out_of_memory(context);
+        }
+        let message = self.source_maps.rewrite(&error.to_string());
+        BamlRtError::QuickJsWithSource {
+            context: format!("{}: {}", context, message),
+            source: Box::new(error),
+        }
+    }
+
+    /// Error for an error object returned by the JS wrappers, with stack
+    /// frames mapped to original sources where possible
+    fn js_error(&self, context: &str, error: &Map<String, Value>) -> BamlRtError {
+        let error = Value::Object(error.clone());
+        let Some(mut exception) = JsException::from_error_object(&error) else {
+            return BamlRtError::QuickJs(format!("{}: {}", context, error));
+        };
+        if is_out_of_memory(&exception.name, &exception.message) {
+            return engine_out_of_memory(context);
+        }
+        tracing::debug!(context, name = %exception.name, "JS exception");
+        self.map_exception_stacks(&mut exception);
+        self.events
+            .publish_with(|| RuntimeEvent::js_error(context, exception.clone()));
+        BamlRtError::JsException(exception)
+    }
+
+    fn map_exception_stacks(&self, exception: &mut JsException) {
+        if let Some(stack) = &exception.stack {
+            exception.stack = self
+                .source_maps
+                .map_stack(stack)
+                .or_else(|| self.source_maps.is_empty().then(|| stack.clone()));
+        }
+        if let Some(cause) = exception.cause.as_deref_mut() {
+            self.map_exception_stacks(cause);
+        }
+    }
+
     /// Execute JavaScript code in the QuickJS context
     ///
     /// The code should return a JSON string or a promise that resolves to a JSON string.
-    /// If code returns a promise, we wait for it to resolve.
+    /// If code returns a promise, we wait for it to resolve, but no longer than the
+    /// current request's deadline, and not once the request is canceled.
     pub async fn evaluate(&mut self, code: &str) -> Result<Value> {
+        let operation = "JavaScript evaluation";
+        let evaluation = deadline::enforce(operation, self.evaluate_code(code));
+        cancellation::enforce(operation, evaluation).await
+    }
+
+    async fn evaluate_code(&mut self, code: &str) -> Result<Value> {
         tracing::trace!(code = code, "Executing JavaScript code");
+        self.capture_active_context();
 
         // First, try executing the code directly (for synchronous code like assignments)
         // This handles agent initialization code that just assigns to globalThis
@@ -878,13 +2539,9 @@ impl QuickJSBridge {
             format!("(function() {{ {} }})()", code)
         };
         let direct_script = Script::new("eval_direct.js", &direct_code);
-        let direct_result = self.runtime.eval(None, direct_script).await;
+        let direct_result = self.runtime.eval(self.realm_id(), direct_script).await;
         if let Err(e) = direct_result {
-            let message = e.to_string();
-            return Err(BamlRtError::QuickJsWithSource {
-                context: format!("Failed to execute JavaScript: {}", message),
-                source: Box::new(e),
-            });
+            return Err(self.eval_error("Failed to execute JavaScript", e));
         }
 
         // If direct execution succeeds and returns a non-promise, we're done
@@ -920,7 +2577,7 @@ impl QuickJSBridge {
                     // result is the JSON string from __awaitAndStringify
                     globalThis.__eval_result = typeof result === 'string' ? result : JSON.stringify(result);
                 }} catch (error) {{
-                    globalThis.__eval_result = JSON.stringify({{ error: error.toString() }});
+                    globalThis.__eval_result = JSON.stringify(__describeError(error));
                 }}
             }})()
             "#,
@@ -930,13 +2587,11 @@ impl QuickJSBridge {
         let script = Script::new("eval.js", &wrapped_code);
 
         // Execute the code - this will set __eval_result when the promise resolves
-        let js_result = self.runtime.eval(None, script).await.map_err(|e| {
-            let message = e.to_string();
-            BamlRtError::QuickJsWithSource {
-                context: format!("Failed to execute JavaScript: {}", message),
-                source: Box::new(e),
-            }
-        })?;
+        let js_result = self
+            .runtime
+            .eval(self.realm_id(), script)
+            .await
+            .map_err(|e| self.eval_error("Failed to execute JavaScript", e))?;
 
         // Check if result is a string (synchronous code returned immediately)
         if js_result.is_string() {
@@ -967,13 +2622,11 @@ impl QuickJSBridge {
                         })()
                     "#;
                     let check_script = Script::new("check_result.js", check_code);
-                    let check_result =
-                        self.runtime.eval(None, check_script).await.map_err(|e| {
-                            BamlRtError::QuickJsWithSource {
-                                context: "Failed to check result".to_string(),
-                                source: Box::new(e),
-                            }
-                        })?;
+                    let check_result = self
+                        .runtime
+                        .eval(self.realm_id(), check_script)
+                        .await
+                        .map_err(|e| self.eval_error("Failed to check result", e))?;
 
                     if check_result.is_string() {
                         let result_str = check_result.get_str();
@@ -981,7 +2634,7 @@ impl QuickJSBridge {
                         if let Err(e) = self
                             .runtime
                             .eval(
-                                None,
+                                self.realm_id(),
                                 Script::new("cleanup.js", "delete globalThis.__eval_result"),
                             )
                             .await
@@ -1010,7 +2663,7 @@ impl QuickJSBridge {
                         if let Err(e) = self
                             .runtime
                             .eval(
-                                None,
+                                self.realm_id(),
                                 Script::new("cleanup.js", "delete globalThis.__eval_result"),
                             )
                             .await
@@ -1052,10 +2705,10 @@ impl QuickJSBridge {
             (function() {{
                 try {{
                     const args = {};
-                    const promise = __baml_invoke("{}", JSON.stringify(args));
+                    const promise = __baml_invoke("{}", args);
                     return __awaitAndStringify(promise);
                 }} catch (error) {{
-                    return JSON.stringify({{ error: error.message || String(error) }});
+                    return JSON.stringify(__describeError(error));
                 }}
             }})()
             "#,
@@ -1098,7 +2751,7 @@ impl QuickJSBridge {
                     }}
                     return __awaitAndStringify(func(args));
                 }} catch (error) {{
-                    return JSON.stringify({{ error: error.message || String(error) }});
+                    return JSON.stringify(__describeError(error));
                 }}
             }})()
             "#,
@@ -1138,7 +2791,7 @@ impl QuickJSBridge {
                     }}
                     return __awaitAndStringify(func(args));
                 }} catch (error) {{
-                    return JSON.stringify({{ error: error.message || String(error) }});
+                    return JSON.stringify(__describeError(error));
                 }}
             }})()
             "#,
@@ -1156,12 +2809,9 @@ impl QuickJSBridge {
         };
 
         match &result {
-            Value::Object(map) if map.get("error").is_some() => Err(BamlRtError::QuickJs(format!(
-                "JS function invocation error: {}",
-                map.get("error")
-                    .and_then(Value::as_str)
-                    .unwrap_or("unknown")
-            ))),
+            Value::Object(map) if map.get("error").is_some() => {
+                Err(self.js_error("JS function invocation error", map))
+            }
             _ => Ok(result),
         }
     }
@@ -1192,7 +2842,7 @@ impl QuickJSBridge {
                     }}
                     return __awaitAndStringify(func(args));
                 }} catch (error) {{
-                    return JSON.stringify({{ error: error.message || String(error) }});
+                    return JSON.stringify(__describeError(error));
                 }}
             }})()
             "#,
@@ -1217,11 +2867,8 @@ impl QuickJSBridge {
             {
                 return Ok(None);
             }
-            if let Some(error) = map.get("error").and_then(Value::as_str) {
-                return Err(BamlRtError::QuickJs(format!(
-                    "JS function invocation error: {}",
-                    error
-                )));
+            if map.get("error").and_then(Value::as_str).is_some() {
+                return Err(self.js_error("JS function invocation error", map));
             }
         }
 
@@ -1250,11 +2897,11 @@ impl QuickJSBridge {
                     if (streamFunc !== undefined && typeof streamFunc === 'function') {{
                         promise = streamFunc(args);
                     }} else {{
-                        promise = __baml_stream("{}", JSON.stringify(args));
+                        promise = __baml_stream("{}", args);
                     }}
                     return __awaitAndStringify(promise);
                 }} catch (error) {{
-                    return JSON.stringify({{ error: error.message || String(error) }});
+                    return JSON.stringify(__describeError(error));
                 }}
             }})()
             "#,
@@ -1272,12 +2919,9 @@ impl QuickJSBridge {
         };
         match result {
             Value::Array(values) => Ok(values),
-            Value::Object(map) if map.get("error").is_some() => Err(BamlRtError::QuickJs(format!(
-                "A2A stream invocation error: {}",
-                map.get("error")
-                    .and_then(|v| v.as_str())
-                    .unwrap_or("unknown")
-            ))),
+            Value::Object(map) if map.get("error").is_some() => {
+                Err(self.js_error("A2A stream invocation error", &map))
+            }
             other => Ok(vec![other]),
         }
     }
@@ -0,0 +1,171 @@
+//! Validate-and-repair for BAML function outputs
+//!
+//! A [`RepairPolicy`] checks a function's parsed output against a JSON schema
+//! and caller checks. When the output fails them, the function is called
+//! again with the errors, up to `max_repairs` times, before the call fails
+//! with [`BamlRtError::OutputRejected`].
+//!
+//! BAML renders prompts from templates, so the errors reach the model through
+//! an argument: a function taking the policy's feedback parameter
+//! (`repair_feedback: string?` by default) receives the previous output and
+//! what was wrong with it, and its prompt can include them, e.g.
+//! `{% if repair_feedback %}{{ repair_feedback }}{% endif %}`. Functions
+//! without the parameter are simply called again.
+
+use baml_rt_core::{BamlRtError, Result};
+use serde_json::Value;
+use std::fmt::Write;
+use std::sync::Arc;
+
+/// Parameter the validation errors are passed in
+pub const DEFAULT_FEEDBACK_PARAM: &str = "repair_feedback";
+
+/// Guard name of the errors of exhausted repairs
+pub const REPAIR_GUARD: &str = "repair";
+
+/// A caller check on an output, returning what is wrong with it
+pub type OutputCheck = Arc<dyn Fn(&Value) -> Vec<String> + Send + Sync>;
+
+/// How a function's output is validated and repaired
+#[derive(Clone)]
+pub struct RepairPolicy {
+    schema: Option<Arc<jsonschema::Validator>>,
+    checks: Vec<OutputCheck>,
+    max_repairs: u32,
+    feedback_param: String,
+}
+
+impl RepairPolicy {
+    /// A policy making at most `max_repairs` calls after the first, with no
+    /// validation yet
+    pub fn new(max_repairs: u32) -> Self {
+        Self {
+            schema: None,
+            checks: Vec::new(),
+            max_repairs,
+            feedback_param: DEFAULT_FEEDBACK_PARAM.to_string(),
+        }
+    }
+
+    /// Require outputs to validate against a JSON schema
+    pub fn with_json_schema(mut self, schema: &Value) -> Result<Self> {
+        let validator = jsonschema::validator_for(schema).map_err(|e| {
+            BamlRtError::InvalidArgument(format!("Invalid JSON schema for repair policy: {}", e))
+        })?;
+        self.schema = Some(Arc::new(validator));
+        Ok(self)
+    }
+
+    /// Require outputs to pass `check`, which returns what is wrong with them
+    pub fn with_check(
+        mut self,
+        check: impl Fn(&Value) -> Vec<String> + Send + Sync + 'static,
+    ) -> Self {
+        self.checks.push(Arc::new(check));
+        self
+    }
+
+    /// Pass the errors in `param` instead of `repair_feedback`
+    pub fn with_feedback_param(mut self, param: impl Into<String>) -> Self {
+        self.feedback_param = param.into();
+        self
+    }
+
+    pub fn max_repairs(&self) -> u32 {
+        self.max_repairs
+    }
+
+    pub fn feedback_param(&self) -> &str {
+        &self.feedback_param
+    }
+
+    /// What is wrong with `output`; empty when it is valid
+    pub fn errors(&self, output: &Value) -> Vec<String> {
+        let mut errors: Vec<String> = self
+            .schema
+            .iter()
+            .flat_map(|schema| schema.iter_errors(output))
+            .map(|error| format!("at '{}': {}", error.instance_path, error))
+            .collect();
+        for check in &self.checks {
+            errors.extend(check(output));
+        }
+        errors
+    }
+
+    /// Feedback telling the model why `output` was rejected
+    pub fn feedback(output: &Value, errors: &[String]) -> String {
+        let mut feedback = format!(
+            "Your previous answer was invalid:\n{}\n\nProblems:\n",
+            output
+        );
+        for error in errors {
+            let _ = writeln!(feedback, "- {}", error);
+        }
+        feedback.push_str("\nAnswer again, fixing these problems.");
+        feedback
+    }
+
+    /// The error of a call whose output was still invalid after its repairs
+    pub fn exhausted(&self, errors: &[String]) -> BamlRtError {
+        BamlRtError::OutputRejected {
+            guard: REPAIR_GUARD.to_string(),
+            reason: format!(
+                "output still invalid after {} repair attempts: {}",
+                self.max_repairs,
+                errors.join("; ")
+            ),
+        }
+    }
+}
+
+impl std::fmt::Debug for RepairPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RepairPolicy")
+            .field("has_schema", &self.schema.is_some())
+            .field("checks", &self.checks.len())
+            .field("max_repairs", &self.max_repairs)
+            .field("feedback_param", &self.feedback_param)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn errors_combine_the_schema_and_checks() {
+        let policy = RepairPolicy::new(2)
+            .with_json_schema(&json!({
+                "type": "object",
+                "required": ["name", "age"],
+                "properties": { "age": { "type": "integer" } }
+            }))
+            .unwrap()
+            .with_check(|output| match output["age"].as_i64() {
+                Some(age) if age < 0 => vec!["age must not be negative".to_string()],
+                _ => Vec::new(),
+            });
+
+        assert!(
+            policy
+                .errors(&json!({ "name": "Ada", "age": 36 }))
+                .is_empty()
+        );
+        assert_eq!(policy.errors(&json!({ "age": "old" })).len(), 2);
+        let errors = policy.errors(&json!({ "name": "Ada", "age": -1 }));
+        assert_eq!(errors, vec!["age must not be negative".to_string()]);
+
+        let feedback = RepairPolicy::feedback(&json!({ "age": -1 }), &errors);
+        assert!(feedback.contains(r#"{"age":-1}"#), "{}", feedback);
+        assert!(
+            feedback.contains("- age must not be negative"),
+            "{}",
+            feedback
+        );
+        let error = policy.exhausted(&errors).to_string();
+        assert!(error.contains("after 2 repair attempts"), "{}", error);
+    }
+}
@@ -2,14 +2,29 @@
 //!
 //! Provides a builder pattern for constructing and configuring the BAML runtime environment.
 
+use crate::agent_caller::AgentCaller;
+use crate::artifact_store::ArtifactStore;
 use crate::baml::BamlRuntimeManager;
+use crate::dry_run::DryRun;
+use crate::llm_overrides::LlmOverrides;
+use crate::output_guard::OutputGuardRegistry;
 use crate::quickjs_bridge::QuickJSBridge;
+use crate::repair::RepairPolicy;
+use crate::secrets::SecretStore;
+use crate::session::{SessionHistory, SessionStore};
+use crate::token_limiter::{StreamTokenLimiter, TokenLimits};
+use crate::tool_schema::ToolSchemaInjection;
+use baml_rt_core::media::MediaPolicy;
 use baml_rt_core::{BamlRtError, Result};
-use baml_rt_interceptor::{InterceptorPipeline, LLMInterceptor, ToolInterceptor};
+use baml_rt_interceptor::{DecisionPolicy, InterceptorPipeline, LLMInterceptor, ToolInterceptor};
+use baml_rt_tools::ToolPolicy;
+#[cfg(feature = "http")]
+use baml_rt_tools::{HttpPolicy, HttpRequestTool};
+use std::collections::HashMap;
 use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::Duration;
-use tokio::sync::Mutex;
+use tokio::sync::{Mutex, RwLock};
 
 /// Configuration for QuickJS runtime options
 ///
@@ -82,6 +97,52 @@ pub struct RuntimeConfig {
 
     /// Tool interceptor pipeline
     pub tool_interceptor_pipeline: Option<InterceptorPipeline<dyn ToolInterceptor>>,
+
+    /// How conflicting interceptor decisions are resolved
+    pub interceptor_decision_policy: DecisionPolicy,
+
+    /// Output-token caps for streaming calls
+    pub stream_token_limiter: Option<Arc<StreamTokenLimiter>>,
+
+    /// Checks applied to BAML function outputs
+    pub output_guards: Option<Arc<OutputGuardRegistry>>,
+
+    /// Skip the LLM in every BAML call
+    pub dry_run: Option<DryRun>,
+
+    /// Temperature, seed, and max_tokens forced onto BAML calls
+    pub llm_overrides: Option<LlmOverrides>,
+
+    /// Validate-and-repair policies, by BAML function
+    pub repair_policies: HashMap<String, Arc<RepairPolicy>>,
+
+    /// Tools each BAML function may call
+    pub tool_policy: Option<ToolPolicy>,
+
+    /// What the built-in `http_request` tool may reach, when it is registered
+    #[cfg(feature = "http")]
+    pub http_policy: Option<HttpPolicy>,
+
+    /// Conversation memory exposed to JS as `session`
+    pub session_store: Option<Arc<dyn SessionStore>>,
+
+    /// How stored conversations are passed to BAML functions
+    pub session_history: Option<SessionHistory>,
+
+    /// Storage for files and blobs produced by tasks
+    pub artifact_store: Option<Arc<dyn ArtifactStore>>,
+
+    /// Client JS uses to call other agents via `callAgent`
+    pub agent_caller: Option<Arc<dyn AgentCaller>>,
+
+    /// BAML functions offered the registered tools in their output type
+    pub tool_schema: Option<ToolSchemaInjection>,
+
+    /// Directories media arguments may be read from
+    pub media_policy: Option<MediaPolicy>,
+
+    /// Where BAML calls get their API keys
+    pub secret_store: Option<Arc<SecretStore>>,
 }
 
 impl RuntimeConfig {
@@ -121,7 +182,7 @@ impl RuntimeConfig {
 /// Built runtime environment
 pub struct Runtime {
     /// The BAML runtime manager
-    pub baml_manager: Arc<Mutex<BamlRuntimeManager>>,
+    pub baml_manager: Arc<RwLock<BamlRuntimeManager>>,
 
     /// The QuickJS bridge (if enabled)
     pub quickjs_bridge: Option<Arc<Mutex<QuickJSBridge>>>,
@@ -132,7 +193,7 @@ pub struct Runtime {
 
 impl Runtime {
     /// Get the BAML runtime manager
-    pub fn baml_manager(&self) -> Arc<Mutex<BamlRuntimeManager>> {
+    pub fn baml_manager(&self) -> Arc<RwLock<BamlRuntimeManager>> {
         self.baml_manager.clone()
     }
 
@@ -181,7 +242,8 @@ impl RuntimeBuilder {
     ///
     /// # Example
     /// ```rust,no_run
-    /// use baml_rt::{RuntimeBuilder, QuickJSConfig};
+    /// use baml_rt::QuickJSConfig;
+    /// use baml_rt::runtime::RuntimeBuilder;
     /// use std::time::Duration;
     ///
     /// # tokio_test::block_on(async {
@@ -291,12 +353,121 @@ impl RuntimeBuilder {
         self
     }
 
+    /// Set how conflicting interceptor decisions are resolved
+    pub fn with_interceptor_decision_policy(mut self, policy: DecisionPolicy) -> Self {
+        self.config.interceptor_decision_policy = policy;
+        self
+    }
+
+    /// Cap output tokens of streaming calls, per request and per tenant
+    pub fn with_stream_token_limits(mut self, limits: TokenLimits) -> Self {
+        self.config.stream_token_limiter = Some(Arc::new(StreamTokenLimiter::new(limits)));
+        self
+    }
+
+    /// Use a preconfigured limiter, e.g. one shared between runtimes
+    pub fn with_stream_token_limiter(mut self, limiter: Arc<StreamTokenLimiter>) -> Self {
+        self.config.stream_token_limiter = Some(limiter);
+        self
+    }
+
+    /// Check BAML function outputs against `guards`
+    pub fn with_output_guards(mut self, guards: OutputGuardRegistry) -> Self {
+        self.config.output_guards = Some(Arc::new(guards));
+        self
+    }
+
+    /// Make every BAML call a dry run under `dry_run`
+    pub fn with_dry_run(mut self, dry_run: DryRun) -> Self {
+        self.config.dry_run = Some(dry_run);
+        self
+    }
+
+    /// Force the temperature, seed, and max_tokens of `overrides` onto BAML
+    /// calls, e.g. for reproducible evaluation runs
+    pub fn with_llm_overrides(mut self, overrides: LlmOverrides) -> Self {
+        self.config.llm_overrides = Some(overrides);
+        self
+    }
+
+    /// Validate the outputs of `function` under `policy`, calling it again
+    /// with the errors while they fail
+    pub fn with_repair_policy(mut self, function: impl Into<String>, policy: RepairPolicy) -> Self {
+        self.config
+            .repair_policies
+            .insert(function.into(), Arc::new(policy));
+        self
+    }
+
+    /// Refuse tool calls a BAML function is not allowed to make under
+    /// `policy`, even when its output names the tool
+    pub fn with_tool_policy(mut self, policy: ToolPolicy) -> Self {
+        self.config.tool_policy = Some(policy);
+        self
+    }
+
+    /// Register the `http_request` tool, limited to the hosts, response
+    /// size, redirects, and timeout of `policy`
+    #[cfg(feature = "http")]
+    pub fn with_http_tool(mut self, policy: HttpPolicy) -> Self {
+        self.config.http_policy = Some(policy);
+        self
+    }
+
+    /// Keep conversation messages per context in `store`
+    pub fn with_session_store(mut self, store: Arc<dyn SessionStore>) -> Self {
+        self.config.session_store = Some(store);
+        self
+    }
+
+    /// Pass stored conversations to BAML functions taking a history parameter
+    pub fn with_session_history(mut self, history: SessionHistory) -> Self {
+        self.config.session_history = Some(history);
+        self
+    }
+
+    /// Persist files and blobs produced by tasks in `store`
+    pub fn with_artifact_store(mut self, store: Arc<dyn ArtifactStore>) -> Self {
+        self.config.artifact_store = Some(store);
+        self
+    }
+
+    /// Let JavaScript call other agents through `caller`
+    pub fn with_agent_caller(mut self, caller: Arc<dyn AgentCaller>) -> Self {
+        self.config.agent_caller = Some(caller);
+        self
+    }
+
+    /// Add the registered tools to the tool choice class of the functions
+    /// selected by `injection`
+    pub fn with_tool_schema_injection(mut self, injection: ToolSchemaInjection) -> Self {
+        self.config.tool_schema = Some(injection);
+        self
+    }
+
+    /// Let `image`/`audio` arguments given as `{ "file": ... }` be read from
+    /// the directories `policy` allows
+    pub fn with_media_policy(mut self, policy: MediaPolicy) -> Self {
+        self.config.media_policy = Some(policy);
+        self
+    }
+
+    /// Give BAML calls the API keys `secrets` resolves instead of reading
+    /// them from the process environment
+    pub fn with_secret_store(mut self, secrets: Arc<SecretStore>) -> Self {
+        self.config.secret_store = Some(secrets);
+        self
+    }
+
     /// Build the runtime environment
     pub async fn build(mut self) -> Result<Runtime> {
         tracing::info!("Building runtime environment");
 
         // Create BAML runtime manager
         let mut baml_manager = BamlRuntimeManager::new()?;
+        if let Some(secrets) = &self.config.secret_store {
+            baml_manager.set_secret_store(secrets.clone());
+        }
 
         // Load schema if path is provided
         if let Some(schema_path) = &self.config.schema_path {
@@ -327,7 +498,69 @@ impl RuntimeBuilder {
             registry_guard.merge_tool_pipeline(tool_pipeline);
         }
 
-        let baml_manager = Arc::new(Mutex::new(baml_manager));
+        baml_manager
+            .interceptor_registry()
+            .lock()
+            .await
+            .set_decision_policy(self.config.interceptor_decision_policy);
+
+        if let Some(limiter) = &self.config.stream_token_limiter {
+            baml_manager.set_stream_token_limiter(limiter.clone());
+        }
+
+        if let Some(guards) = &self.config.output_guards {
+            baml_manager.set_output_guards(guards.clone());
+        }
+
+        if let Some(dry_run) = &self.config.dry_run {
+            baml_manager.set_dry_run(Some(dry_run.clone()));
+        }
+
+        if let Some(overrides) = &self.config.llm_overrides {
+            baml_manager.set_llm_overrides(Some(overrides.clone()));
+        }
+
+        for (function, policy) in &self.config.repair_policies {
+            baml_manager.set_repair_policy(function.clone(), policy.clone());
+        }
+
+        if let Some(policy) = &self.config.tool_policy {
+            baml_manager.set_tool_policy(policy.clone());
+        }
+
+        if let Some(store) = &self.config.session_store {
+            baml_manager.set_session_store(store.clone());
+        }
+
+        if let Some(history) = &self.config.session_history {
+            baml_manager.set_session_history(history.clone());
+        }
+
+        if let Some(store) = &self.config.artifact_store {
+            baml_manager.set_artifact_store(store.clone());
+        }
+
+        if let Some(caller) = &self.config.agent_caller {
+            baml_manager.set_agent_caller(caller.clone());
+        }
+
+        if let Some(injection) = &self.config.tool_schema {
+            baml_manager.set_tool_schema_injection(injection.clone());
+        }
+
+        if let Some(policy) = &self.config.media_policy {
+            baml_manager.set_media_policy(policy.clone());
+        }
+
+        // Registered before the bridge, which wraps the registry's tools
+        #[cfg(feature = "http")]
+        if let Some(policy) = &self.config.http_policy {
+            baml_manager
+                .register_tool(HttpRequestTool::new(policy.clone())?)
+                .await?;
+        }
+
+        let baml_manager = Arc::new(RwLock::new(baml_manager));
 
         // Create QuickJS bridge if enabled
         let quickjs_config_clone = self.config.quickjs_config.clone();
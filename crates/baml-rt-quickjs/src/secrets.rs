@@ -0,0 +1,462 @@
+//! Where BAML calls get their provider API keys
+//!
+//! Each BAML call is given an environment holding the API keys its clients
+//! read, e.g. `env.OPENAI_API_KEY`. The [`SecretStore`] builds it by asking
+//! its [`SecretProvider`]s for each secret it is configured with, in order,
+//! and caches what they return. Cached values are fetched again once they
+//! are older than the store's TTL, or right away after
+//! [`SecretStore::invalidate`], so rotated keys are picked up without a
+//! restart.
+//!
+//! The process environment and a directory of secret files are always
+//! available; HashiCorp Vault and AWS Secrets Manager are behind the `vault`
+//! and `aws-secrets-manager` features.
+
+use async_trait::async_trait;
+use baml_rt_core::{BamlRtError, Result};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Secrets resolved for every BAML call unless the store is told otherwise
+pub const DEFAULT_SECRET_NAMES: [&str; 4] = [
+    "OPENROUTER_API_KEY",
+    "OPENAI_API_KEY",
+    "ANTHROPIC_API_KEY",
+    "GOOGLE_API_KEY",
+];
+
+/// How long a fetched secret is used before it is fetched again
+pub const DEFAULT_SECRET_TTL: Duration = Duration::from_secs(300);
+
+/// A source of secrets, e.g. a secrets manager
+#[async_trait]
+pub trait SecretProvider: Send + Sync {
+    /// Value of secret `name`, or `None` when this provider does not have it
+    async fn get_secret(&self, name: &str) -> Result<Option<String>>;
+}
+
+/// Reads secrets from the process environment
+#[derive(Debug, Clone, Copy, Default)]
+pub struct EnvSecrets;
+
+#[async_trait]
+impl SecretProvider for EnvSecrets {
+    async fn get_secret(&self, name: &str) -> Result<Option<String>> {
+        Ok(std::env::var(name).ok())
+    }
+}
+
+/// Reads each secret from the file of the same name in a directory, as
+/// Docker and Kubernetes mount them
+#[derive(Debug, Clone)]
+pub struct FileSecrets {
+    dir: PathBuf,
+}
+
+impl FileSecrets {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+}
+
+#[async_trait]
+impl SecretProvider for FileSecrets {
+    async fn get_secret(&self, name: &str) -> Result<Option<String>> {
+        // Names come from configuration, but never read outside the directory
+        if name.contains(['/', '\\']) || name.starts_with('.') {
+            return Ok(None);
+        }
+        match tokio::fs::read_to_string(self.dir.join(name)).await {
+            Ok(value) => Ok(Some(value.trim_end_matches(['\r', '\n']).to_string())),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(secret_error(name, err)),
+        }
+    }
+}
+
+/// Reads secrets from one path of a HashiCorp Vault KV v2 secrets engine,
+/// whose keys are the secret names
+#[cfg(feature = "vault")]
+#[derive(Clone)]
+pub struct VaultSecrets {
+    client: reqwest::Client,
+    address: String,
+    token: String,
+    mount: String,
+    path: String,
+}
+
+#[cfg(feature = "vault")]
+impl VaultSecrets {
+    /// Read the secrets at `path` of the `secret` mount from the Vault
+    /// server at `address`, authenticating with `token`
+    pub fn new(
+        address: impl Into<String>,
+        token: impl Into<String>,
+        path: impl Into<String>,
+    ) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            address: address.into().trim_end_matches('/').to_string(),
+            token: token.into(),
+            mount: "secret".to_string(),
+            path: path.into(),
+        }
+    }
+
+    /// Connect to the server named by `VAULT_ADDR` with `VAULT_TOKEN`
+    pub fn from_env(path: impl Into<String>) -> Result<Self> {
+        let var = |name: &str| {
+            std::env::var(name)
+                .map_err(|_| BamlRtError::Configuration(format!("{} is not set", name)))
+        };
+        Ok(Self::new(var("VAULT_ADDR")?, var("VAULT_TOKEN")?, path))
+    }
+
+    /// Read from the KV v2 engine mounted at `mount` instead
+    pub fn with_mount(mut self, mount: impl Into<String>) -> Self {
+        self.mount = mount.into();
+        self
+    }
+}
+
+#[cfg(feature = "vault")]
+impl std::fmt::Debug for VaultSecrets {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("VaultSecrets")
+            .field("address", &self.address)
+            .field("mount", &self.mount)
+            .field("path", &self.path)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(feature = "vault")]
+#[async_trait]
+impl SecretProvider for VaultSecrets {
+    async fn get_secret(&self, name: &str) -> Result<Option<String>> {
+        let url = format!("{}/v1/{}/data/{}", self.address, self.mount, self.path);
+        let response = self
+            .client
+            .get(&url)
+            .header("X-Vault-Token", &self.token)
+            .send()
+            .await
+            .map_err(|err| secret_error(name, err))?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !response.status().is_success() {
+            return Err(secret_error(
+                name,
+                format!("Vault answered {}", response.status()),
+            ));
+        }
+        let body = response
+            .text()
+            .await
+            .map_err(|err| secret_error(name, err))?;
+        let body: serde_json::Value =
+            serde_json::from_str(&body).map_err(|err| secret_error(name, err))?;
+        Ok(body
+            .pointer("/data/data")
+            .and_then(|data| data.get(name))
+            .and_then(serde_json::Value::as_str)
+            .map(str::to_string))
+    }
+}
+
+/// Reads secrets from AWS Secrets Manager
+///
+/// Each secret name is a secret ID, unless a single secret holding a JSON
+/// object of names to values is given with [`with_secret_id`](Self::with_secret_id).
+#[cfg(feature = "aws-secrets-manager")]
+#[derive(Debug, Clone)]
+pub struct AwsSecretsManager {
+    client: aws_sdk_secretsmanager::Client,
+    secret_id: Option<String>,
+}
+
+#[cfg(feature = "aws-secrets-manager")]
+impl AwsSecretsManager {
+    pub fn new(client: aws_sdk_secretsmanager::Client) -> Self {
+        Self {
+            client,
+            secret_id: None,
+        }
+    }
+
+    /// Use the region and credentials of the default AWS config chain
+    pub async fn from_env() -> Self {
+        let config = aws_config::load_from_env().await;
+        Self::new(aws_sdk_secretsmanager::Client::new(&config))
+    }
+
+    /// Read every secret from the JSON object stored in `secret_id`
+    pub fn with_secret_id(mut self, secret_id: impl Into<String>) -> Self {
+        self.secret_id = Some(secret_id.into());
+        self
+    }
+}
+
+#[cfg(feature = "aws-secrets-manager")]
+#[async_trait]
+impl SecretProvider for AwsSecretsManager {
+    async fn get_secret(&self, name: &str) -> Result<Option<String>> {
+        let secret_id = self.secret_id.as_deref().unwrap_or(name);
+        let output = match self
+            .client
+            .get_secret_value()
+            .secret_id(secret_id)
+            .send()
+            .await
+        {
+            Ok(output) => output,
+            Err(err) => {
+                let err = err.into_service_error();
+                if err.is_resource_not_found_exception() {
+                    return Ok(None);
+                }
+                return Err(secret_error(name, err));
+            }
+        };
+        let Some(value) = output.secret_string() else {
+            return Ok(None);
+        };
+        if self.secret_id.is_none() {
+            return Ok(Some(value.to_string()));
+        }
+        let values: serde_json::Value =
+            serde_json::from_str(value).map_err(|err| secret_error(name, err))?;
+        Ok(values
+            .get(name)
+            .and_then(serde_json::Value::as_str)
+            .map(str::to_string))
+    }
+}
+
+/// A secret and when it was fetched
+#[derive(Debug, Clone)]
+struct CachedSecret {
+    value: Option<String>,
+    fetched: Instant,
+}
+
+/// Resolves the secrets BAML calls see, caching them for a while
+///
+/// By default the store reads [`DEFAULT_SECRET_NAMES`] from the process
+/// environment. Adding a provider replaces the environment; add
+/// [`EnvSecrets`] back to fall back to it.
+pub struct SecretStore {
+    providers: Vec<Arc<dyn SecretProvider>>,
+    names: Vec<String>,
+    ttl: Duration,
+    cache: Mutex<HashMap<String, CachedSecret>>,
+}
+
+impl SecretStore {
+    pub fn new() -> Self {
+        Self {
+            providers: Vec::new(),
+            names: DEFAULT_SECRET_NAMES
+                .iter()
+                .map(|name| name.to_string())
+                .collect(),
+            ttl: DEFAULT_SECRET_TTL,
+            cache: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Ask `provider` for secrets after the providers added before it
+    pub fn with_provider(mut self, provider: Arc<dyn SecretProvider>) -> Self {
+        self.providers.push(provider);
+        self
+    }
+
+    /// Also resolve secret `name` for BAML calls
+    pub fn with_secret(mut self, name: impl Into<String>) -> Self {
+        let name = name.into();
+        if !self.names.contains(&name) {
+            self.names.push(name);
+        }
+        self
+    }
+
+    /// Fetch secrets again once they are older than `ttl`
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Value of secret `name` from the first provider that has it
+    ///
+    /// If every provider fails, the last value fetched is used until one
+    /// succeeds again.
+    pub async fn get(&self, name: &str) -> Option<String> {
+        let cached = self.lock_cache().get(name).cloned();
+        if let Some(cached) = &cached
+            && cached.fetched.elapsed() < self.ttl
+        {
+            return cached.value.clone();
+        }
+        match self.fetch(name).await {
+            Ok(value) => {
+                self.lock_cache().insert(
+                    name.to_string(),
+                    CachedSecret {
+                        value: value.clone(),
+                        fetched: Instant::now(),
+                    },
+                );
+                value
+            }
+            Err(err) => {
+                tracing::warn!(secret = name, error = %err, "Using the last value of a secret");
+                cached.and_then(|cached| cached.value)
+            }
+        }
+    }
+
+    /// Environment of the configured secrets that have a value
+    pub async fn env(&self) -> HashMap<String, String> {
+        let mut env = HashMap::new();
+        for name in &self.names {
+            if let Some(value) = self.get(name).await {
+                env.insert(name.clone(), value);
+            }
+        }
+        env
+    }
+
+    /// Environment of the configured secrets fetched so far, without asking
+    /// the providers
+    pub fn cached_env(&self) -> HashMap<String, String> {
+        self.lock_cache()
+            .iter()
+            .filter_map(|(name, cached)| Some((name.clone(), cached.value.clone()?)))
+            .collect()
+    }
+
+    /// Fetch secret `name` again on its next use, e.g. after rotating it;
+    /// `None` fetches every secret again
+    pub fn invalidate(&self, name: Option<&str>) {
+        let mut cache = self.lock_cache();
+        match name {
+            Some(name) => {
+                cache.remove(name);
+            }
+            None => cache.clear(),
+        }
+    }
+
+    async fn fetch(&self, name: &str) -> Result<Option<String>> {
+        if self.providers.is_empty() {
+            return EnvSecrets.get_secret(name).await;
+        }
+        for provider in &self.providers {
+            if let Some(value) = provider.get_secret(name).await? {
+                return Ok(Some(value));
+            }
+        }
+        Ok(None)
+    }
+
+    fn lock_cache(&self) -> std::sync::MutexGuard<'_, HashMap<String, CachedSecret>> {
+        self.cache
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl Default for SecretStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Debug for SecretStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Never print secret values
+        f.debug_struct("SecretStore")
+            .field("providers", &self.providers.len())
+            .field("names", &self.names)
+            .field("ttl", &self.ttl)
+            .finish_non_exhaustive()
+    }
+}
+
+fn secret_error(name: &str, err: impl std::fmt::Display) -> BamlRtError {
+    BamlRtError::Secret {
+        name: name.to_string(),
+        message: err.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+    /// Hands out a new key on every fetch, failing once told to
+    #[derive(Default)]
+    struct RotatingKeys {
+        fetches: AtomicUsize,
+        failing: AtomicBool,
+    }
+
+    #[async_trait]
+    impl SecretProvider for RotatingKeys {
+        async fn get_secret(&self, name: &str) -> Result<Option<String>> {
+            if self.failing.load(Ordering::SeqCst) {
+                return Err(secret_error(name, "unreachable"));
+            }
+            let fetch = self.fetches.fetch_add(1, Ordering::SeqCst) + 1;
+            Ok((name == "OPENAI_API_KEY").then(|| format!("sk-{}", fetch)))
+        }
+    }
+
+    #[tokio::test]
+    async fn secrets_are_cached_until_invalidated_or_expired() {
+        let keys = Arc::new(RotatingKeys::default());
+        let store = SecretStore::new()
+            .with_provider(keys.clone())
+            .with_ttl(Duration::from_secs(3600));
+
+        assert_eq!(store.get("OPENAI_API_KEY").await.as_deref(), Some("sk-1"));
+        assert_eq!(store.get("OPENAI_API_KEY").await.as_deref(), Some("sk-1"));
+        store.invalidate(Some("OPENAI_API_KEY"));
+        assert_eq!(store.get("OPENAI_API_KEY").await.as_deref(), Some("sk-2"));
+
+        let expiring = SecretStore::new()
+            .with_provider(keys.clone())
+            .with_ttl(Duration::ZERO);
+        let first = expiring.get("OPENAI_API_KEY").await;
+        keys.failing.store(true, Ordering::SeqCst);
+        // A provider outage keeps the last key in use
+        assert_eq!(expiring.get("OPENAI_API_KEY").await, first);
+        assert_eq!(expiring.env().await.get("OPENAI_API_KEY").cloned(), first);
+    }
+
+    #[tokio::test]
+    async fn file_secrets_are_read_from_their_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("ANTHROPIC_API_KEY"), "sk-file\n").unwrap();
+        let files = FileSecrets::new(dir.path());
+
+        assert_eq!(
+            files
+                .get_secret("ANTHROPIC_API_KEY")
+                .await
+                .unwrap()
+                .as_deref(),
+            Some("sk-file")
+        );
+        assert_eq!(files.get_secret("OPENAI_API_KEY").await.unwrap(), None);
+        assert_eq!(
+            files.get_secret("../ANTHROPIC_API_KEY").await.unwrap(),
+            None
+        );
+    }
+}
@@ -0,0 +1,169 @@
+//! Conversation memory keyed by context
+//!
+//! A [`SessionStore`] keeps the messages exchanged under each [`ContextId`],
+//! so multi-turn agents can pick up where the previous request left off.
+//! JavaScript reads and appends through the `session.get(limit)` and
+//! `session.append(role, content)` globals, which act on the context of the
+//! current invocation. With [`SessionHistory`] configured, the stored messages
+//! are also passed to BAML functions that declare a history parameter.
+
+use async_trait::async_trait;
+use baml_rt_core::Result;
+use baml_rt_core::ids::ContextId;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// One message in a conversation
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionMessage {
+    /// Who produced the message, e.g. `"user"` or `"assistant"`
+    pub role: String,
+    pub content: Value,
+}
+
+impl SessionMessage {
+    pub fn new(role: impl Into<String>, content: impl Into<Value>) -> Self {
+        Self {
+            role: role.into(),
+            content: content.into(),
+        }
+    }
+}
+
+/// Storage for conversation messages
+///
+/// Implement this to keep sessions in a database or cache shared between
+/// processes; [`InMemorySessionStore`] covers a single process.
+#[async_trait]
+pub trait SessionStore: Send + Sync {
+    /// Add a message to the end of a context's conversation
+    async fn append(&self, context_id: &ContextId, message: SessionMessage) -> Result<()>;
+
+    /// Messages of a context, oldest first, keeping only the last `limit`
+    /// when set
+    async fn get(
+        &self,
+        context_id: &ContextId,
+        limit: Option<usize>,
+    ) -> Result<Vec<SessionMessage>>;
+
+    /// Forget a context's conversation
+    async fn clear(&self, context_id: &ContextId) -> Result<()>;
+}
+
+/// Process-local session store
+#[derive(Default)]
+pub struct InMemorySessionStore {
+    sessions: Mutex<HashMap<ContextId, Vec<SessionMessage>>>,
+    max_messages: Option<usize>,
+}
+
+impl InMemorySessionStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Drop the oldest messages of a context beyond `max_messages`
+    pub fn with_max_messages(mut self, max_messages: usize) -> Self {
+        self.max_messages = Some(max_messages);
+        self
+    }
+}
+
+#[async_trait]
+impl SessionStore for InMemorySessionStore {
+    async fn append(&self, context_id: &ContextId, message: SessionMessage) -> Result<()> {
+        let mut sessions = self.sessions.lock().unwrap_or_else(|e| e.into_inner());
+        let messages = sessions.entry(context_id.clone()).or_default();
+        messages.push(message);
+        if let Some(max) = self.max_messages
+            && messages.len() > max
+        {
+            let excess = messages.len() - max;
+            messages.drain(..excess);
+        }
+        Ok(())
+    }
+
+    async fn get(
+        &self,
+        context_id: &ContextId,
+        limit: Option<usize>,
+    ) -> Result<Vec<SessionMessage>> {
+        let sessions = self.sessions.lock().unwrap_or_else(|e| e.into_inner());
+        let messages = sessions.get(context_id).map(Vec::as_slice).unwrap_or(&[]);
+        let start = limit.map_or(0, |limit| messages.len().saturating_sub(limit));
+        Ok(messages[start..].to_vec())
+    }
+
+    async fn clear(&self, context_id: &ContextId) -> Result<()> {
+        self.sessions
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(context_id);
+        Ok(())
+    }
+}
+
+/// How stored messages are passed to BAML functions
+///
+/// A function receives the history when it declares a parameter named
+/// `param` and the caller did not supply that argument.
+#[derive(Debug, Clone)]
+pub struct SessionHistory {
+    pub param: String,
+    /// Most recent messages to pass; all of them when `None`
+    pub limit: Option<usize>,
+}
+
+impl Default for SessionHistory {
+    fn default() -> Self {
+        Self {
+            param: "history".to_string(),
+            limit: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn in_memory_store_keeps_recent_messages_per_context() {
+        let store = InMemorySessionStore::new().with_max_messages(3);
+        let alice = ContextId::new("ctx-alice".to_string());
+        let bob = ContextId::new("ctx-bob".to_string());
+
+        for turn in 0..4 {
+            store
+                .append(&alice, SessionMessage::new("user", json!(turn)))
+                .await
+                .unwrap();
+        }
+        store
+            .append(&bob, SessionMessage::new("user", "hi"))
+            .await
+            .unwrap();
+
+        let turns: Vec<Value> = store
+            .get(&alice, None)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|message| message.content)
+            .collect();
+        assert_eq!(turns, vec![json!(1), json!(2), json!(3)]);
+        assert_eq!(
+            store.get(&alice, Some(1)).await.unwrap()[0].content,
+            json!(3)
+        );
+        assert_eq!(store.get(&bob, None).await.unwrap().len(), 1);
+
+        store.clear(&alice).await.unwrap();
+        assert!(store.get(&alice, None).await.unwrap().is_empty());
+    }
+}
@@ -0,0 +1,198 @@
+//! One QuickJS engine shared by several bridges
+//!
+//! A bridge created with [`QuickJSBridge::new_in_realm`](crate::QuickJSBridge::new_in_realm)
+//! runs in its own realm of a [`SharedQuickJsRuntime`]: globals, BAML function
+//! wrappers, and tools are separate per realm, while the engine thread, heap,
+//! and garbage collector are shared. Hosting many agents this way costs one
+//! realm each instead of one full runtime each.
+//!
+//! QuickJS host functions are installed on the engine, not on a realm, so each
+//! name is installed once and dispatches on the realm making the call. That
+//! keeps every agent's `__baml_invoke` pointed at its own BAML runtime.
+
+use crate::runtime::QuickJSConfig;
+use baml_rt_core::{BamlRtError, Result};
+use quickjs_runtime::builder::QuickJsRuntimeBuilder;
+use quickjs_runtime::facades::QuickJsRuntimeFacade;
+use quickjs_runtime::jsutils::JsError;
+use quickjs_runtime::quickjsrealmadapter::QuickJsRealmAdapter;
+use quickjs_runtime::values::JsValueFacade;
+use std::collections::{BTreeSet, HashMap};
+use std::sync::{Arc, Mutex};
+
+/// A host function callable from JavaScript
+pub(crate) type HostFunction = dyn Fn(&QuickJsRealmAdapter, Vec<JsValueFacade>) -> std::result::Result<JsValueFacade, JsError>
+    + Send
+    + Sync;
+
+/// Host functions by name, then by realm
+type HostFunctionTable = HashMap<String, HashMap<String, Arc<HostFunction>>>;
+
+/// Build a QuickJS engine with `config` applied
+pub(crate) fn build_facade(config: &QuickJSConfig) -> QuickJsRuntimeFacade {
+    let mut builder = QuickJsRuntimeBuilder::new();
+    if let Some(limit) = config.memory_limit {
+        builder = builder.memory_limit(limit);
+    }
+    if let Some(stack_size) = config.max_stack_size {
+        builder = builder.max_stack_size(stack_size);
+    }
+    if let Some(threshold) = config.gc_threshold {
+        builder = builder.gc_threshold(threshold);
+    }
+    if let Some(interval) = config.gc_interval {
+        builder = builder.gc_interval(interval);
+    }
+    builder.build()
+}
+
+/// A QuickJS engine whose realms host separate bridges
+///
+/// Cloning is cheap; all clones share the same engine, which shuts down once
+/// the last clone and the last bridge using it are dropped.
+#[derive(Clone)]
+pub struct SharedQuickJsRuntime {
+    inner: Arc<SharedInner>,
+}
+
+struct SharedInner {
+    facade: Arc<QuickJsRuntimeFacade>,
+    config: QuickJSConfig,
+    realms: Mutex<BTreeSet<String>>,
+    host_functions: Arc<Mutex<HostFunctionTable>>,
+}
+
+impl SharedQuickJsRuntime {
+    /// Start an engine with the given configuration
+    ///
+    /// Memory and stack limits apply to the engine as a whole, so size them
+    /// for every agent it will host.
+    pub fn new(config: QuickJSConfig) -> Self {
+        tracing::info!(
+            memory_limit = ?config.memory_limit,
+            max_stack_size = ?config.max_stack_size,
+            "Starting shared QuickJS runtime"
+        );
+        Self {
+            inner: Arc::new(SharedInner {
+                facade: Arc::new(build_facade(&config)),
+                config,
+                realms: Mutex::new(BTreeSet::new()),
+                host_functions: Arc::new(Mutex::new(HashMap::new())),
+            }),
+        }
+    }
+
+    /// Configuration the engine was started with
+    pub fn config(&self) -> &QuickJSConfig {
+        &self.inner.config
+    }
+
+    /// Realms currently in use, in name order
+    pub fn realms(&self) -> Vec<String> {
+        self.inner
+            .realms
+            .lock()
+            .map(|realms| realms.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    pub(crate) fn facade(&self) -> Arc<QuickJsRuntimeFacade> {
+        self.inner.facade.clone()
+    }
+
+    /// Create realm `id`, which must not be in use
+    pub(crate) fn create_realm(&self, id: &str) -> Result<()> {
+        let mut realms = self.inner.realms.lock().map_err(|_| {
+            BamlRtError::QuickJs("Shared runtime realm registry poisoned".to_string())
+        })?;
+        if realms.contains(id) {
+            return Err(BamlRtError::InvalidArgument(format!(
+                "QuickJS realm '{}' is already in use",
+                id
+            )));
+        }
+        self.inner
+            .facade
+            .create_realm(id)
+            .map_err(|e| BamlRtError::QuickJsWithSource {
+                context: format!("Failed to create QuickJS realm '{}'", id),
+                source: Box::new(e),
+            })?;
+        realms.insert(id.to_string());
+        tracing::debug!(realm = id, "Created QuickJS realm");
+        Ok(())
+    }
+
+    /// Drop realm `id` and the host functions registered for it
+    pub(crate) fn release_realm(&self, id: &str) {
+        if let Ok(mut table) = self.inner.host_functions.lock() {
+            for functions in table.values_mut() {
+                functions.remove(id);
+            }
+        }
+        if let Ok(mut realms) = self.inner.realms.lock() {
+            realms.remove(id);
+        }
+        if let Err(e) = self.inner.facade.destroy_realm(id) {
+            tracing::warn!(realm = id, error = ?e, "Failed to destroy QuickJS realm");
+        }
+    }
+
+    /// Register `function` as `name` for calls made from realm `id`
+    pub(crate) fn set_host_function(
+        &self,
+        id: &str,
+        name: &str,
+        function: Arc<HostFunction>,
+    ) -> std::result::Result<(), JsError> {
+        let first_registration = {
+            let mut table = self
+                .inner
+                .host_functions
+                .lock()
+                .map_err(|_| JsError::new_str("Host function table poisoned"))?;
+            let first_registration = !table.contains_key(name);
+            table
+                .entry(name.to_string())
+                .or_default()
+                .insert(id.to_string(), function);
+            first_registration
+        };
+        if !first_registration {
+            return Ok(());
+        }
+
+        let table = self.inner.host_functions.clone();
+        let function_name = name.to_string();
+        self.inner.facade.set_function(
+            &[],
+            name,
+            move |realm: &QuickJsRealmAdapter, args: Vec<JsValueFacade>| {
+                let function = table
+                    .lock()
+                    .ok()
+                    .and_then(|table| table.get(&function_name)?.get(&realm.id).cloned());
+                match function {
+                    Some(function) => function(realm, args),
+                    None => Err(JsError::new_string(format!(
+                        "{} is not available in realm {}",
+                        function_name, realm.id
+                    ))),
+                }
+            },
+        )
+    }
+}
+
+/// A bridge's realm in a shared runtime, released when dropped
+pub(crate) struct RealmHandle {
+    pub(crate) runtime: SharedQuickJsRuntime,
+    pub(crate) id: String,
+}
+
+impl Drop for RealmHandle {
+    fn drop(&mut self) {
+        self.runtime.release_realm(&self.id);
+    }
+}
@@ -0,0 +1,258 @@
+//! Source maps for agent scripts
+//!
+//! Agent bundles are compiled from TypeScript and ship with a v3 source map
+//! next to `dist/index.js`. When a script is loaded with
+//! [`QuickJSBridge::evaluate_script`](crate::QuickJSBridge::evaluate_script),
+//! its map is kept in a [`SourceMapRegistry`], and stack frames such as
+//! `at greetUser (dist/index.js:12:9)` in JS errors are rewritten to point at
+//! the original file, e.g. `at greetUser (src/index.ts:17:5)`.
+
+use baml_rt_core::{BamlRtError, Result};
+use regex::Regex;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+const BASE64: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// A position in an original source file, 1-based
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OriginalPosition {
+    pub source: String,
+    pub line: u32,
+    pub column: u32,
+}
+
+#[derive(Deserialize)]
+struct RawSourceMap {
+    version: u32,
+    #[serde(default)]
+    sources: Vec<String>,
+    #[serde(default, rename = "sourceRoot")]
+    source_root: Option<String>,
+    mappings: String,
+}
+
+/// A parsed v3 source map
+#[derive(Debug, Clone)]
+pub struct SourceMap {
+    sources: Vec<String>,
+    /// Per generated line: `[generated column, source, line, column]`, 0-based
+    lines: Vec<Vec<[i64; 4]>>,
+}
+
+impl SourceMap {
+    /// Parse a v3 source map from JSON
+    pub fn parse(json: &str) -> Result<Self> {
+        let raw: RawSourceMap = serde_json::from_str(json)?;
+        if raw.version != 3 {
+            return Err(BamlRtError::InvalidArgument(format!(
+                "Unsupported source map version: {}",
+                raw.version
+            )));
+        }
+        let root = raw
+            .source_root
+            .filter(|root| !root.is_empty())
+            .map(|root| format!("{}/", root.trim_end_matches('/')))
+            .unwrap_or_default();
+        let mut lines = decode_mappings(&raw.mappings);
+        for line in &mut lines {
+            line.sort_by_key(|segment| segment[0]);
+        }
+        Ok(Self {
+            sources: raw
+                .sources
+                .into_iter()
+                .map(|source| format!("{}{}", root, source))
+                .collect(),
+            lines,
+        })
+    }
+
+    /// Original position of a 1-based generated line and column
+    ///
+    /// Without a column, the first mapping on the line is used.
+    pub fn lookup(&self, line: u32, column: Option<u32>) -> Option<OriginalPosition> {
+        let segments = self.lines.get(line.checked_sub(1)? as usize)?;
+        let segment = match column {
+            Some(column) => {
+                let column = i64::from(column.saturating_sub(1));
+                segments
+                    .iter()
+                    .rev()
+                    .find(|segment| segment[0] <= column)
+                    .or_else(|| segments.first())?
+            }
+            None => segments.first()?,
+        };
+        let source = self.sources.get(usize::try_from(segment[1]).ok()?)?;
+        Some(OriginalPosition {
+            source: source.clone(),
+            line: u32::try_from(segment[2]).ok()? + 1,
+            column: u32::try_from(segment[3]).ok()? + 1,
+        })
+    }
+}
+
+/// Decode VLQ mappings into absolute `[column, source, line, column]` segments
+/// per generated line. Segments without a source position are skipped.
+pub fn decode_mappings(mappings: &str) -> Vec<Vec<[i64; 4]>> {
+    let mut state = [0i64; 5];
+    mappings
+        .split(';')
+        .map(|line| {
+            state[0] = 0;
+            line.split(',')
+                .filter(|segment| !segment.is_empty())
+                .filter_map(|segment| {
+                    let fields = decode_vlq(segment);
+                    for (slot, delta) in state.iter_mut().zip(&fields) {
+                        *slot += delta;
+                    }
+                    (fields.len() >= 4).then(|| [state[0], state[1], state[2], state[3]])
+                })
+                .collect()
+        })
+        .collect()
+}
+
+fn decode_vlq(segment: &str) -> Vec<i64> {
+    let mut values = Vec::new();
+    let (mut value, mut shift) = (0i64, 0);
+    for byte in segment.bytes() {
+        let Some(digit) = BASE64.iter().position(|&b| b == byte) else {
+            break;
+        };
+        let digit = digit as i64;
+        value += (digit & 31) << shift;
+        if digit & 32 != 0 {
+            shift += 5;
+        } else {
+            let negative = value & 1 == 1;
+            value >>= 1;
+            values.push(if negative { -value } else { value });
+            value = 0;
+            shift = 0;
+        }
+    }
+    values
+}
+
+struct RegisteredMap {
+    map: SourceMap,
+    /// Lines the loader prepended before the script's first line
+    line_offset: u32,
+    frame: Regex,
+}
+
+/// Source maps of loaded scripts, keyed by script name
+#[derive(Default)]
+pub struct SourceMapRegistry {
+    maps: HashMap<String, RegisteredMap>,
+}
+
+impl SourceMapRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register the map of a script evaluated under `script_name`
+    pub fn register(&mut self, script_name: &str, map: SourceMap, line_offset: u32) {
+        let frame = Regex::new(&format!(r"{}:(\d+)(?::(\d+))?", regex::escape(script_name)))
+            .expect("escaped script name is a valid pattern");
+        self.maps.insert(
+            script_name.to_string(),
+            RegisteredMap {
+                map,
+                line_offset,
+                frame,
+            },
+        );
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.maps.is_empty()
+    }
+
+    /// Rewrite every mapped `script:line[:column]` location in `text`
+    pub fn rewrite(&self, text: &str) -> String {
+        let mut text = text.to_string();
+        for registered in self.maps.values() {
+            text = registered
+                .frame
+                .replace_all(&text, |captures: &regex::Captures| {
+                    let line = captures[1]
+                        .parse::<u32>()
+                        .ok()
+                        .and_then(|line| line.checked_sub(registered.line_offset));
+                    let column = captures.get(2).and_then(|c| c.as_str().parse().ok());
+                    match line.and_then(|line| registered.map.lookup(line, column)) {
+                        Some(position) => {
+                            format!("{}:{}:{}", position.source, position.line, position.column)
+                        }
+                        None => captures[0].to_string(),
+                    }
+                })
+                .into_owned();
+        }
+        text
+    }
+
+    /// The frames of `stack` that fall in registered scripts, rewritten to
+    /// original positions; `None` when no frame does
+    pub fn map_stack(&self, stack: &str) -> Option<String> {
+        let frames: Vec<String> = stack
+            .lines()
+            .filter(|line| {
+                self.maps
+                    .values()
+                    .any(|registered| registered.frame.is_match(line))
+            })
+            .map(|line| self.rewrite(line.trim_end()))
+            .collect();
+        (!frames.is_empty()).then(|| frames.join("\n"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Generated line 1 maps to src/index.ts line 1; generated line 2,
+    // columns 0 and 4, map to src/util.ts lines 3 and 4.
+    const MAP: &str = r#"{
+        "version": 3,
+        "sources": ["src/index.ts", "src/util.ts"],
+        "names": [],
+        "mappings": "AAAA;ACEA,IACC"
+    }"#;
+
+    #[test]
+    fn looks_up_original_positions() {
+        let map = SourceMap::parse(MAP).unwrap();
+        assert_eq!(
+            map.lookup(1, Some(1)),
+            Some(OriginalPosition {
+                source: "src/index.ts".to_string(),
+                line: 1,
+                column: 1,
+            })
+        );
+        assert_eq!(map.lookup(2, Some(7)).unwrap().line, 4);
+        assert_eq!(map.lookup(2, None).unwrap().line, 3);
+        assert_eq!(map.lookup(9, Some(1)), None);
+    }
+
+    #[test]
+    fn rewrites_only_frames_in_registered_scripts() {
+        let mut registry = SourceMapRegistry::new();
+        registry.register("dist/index.js", SourceMap::parse(MAP).unwrap(), 1);
+
+        let stack = "    at shout (dist/index.js:3:6)\n    at <anonymous> (eval.js:4)\n";
+        assert_eq!(
+            registry.map_stack(stack).as_deref(),
+            Some("    at shout (src/util.ts:4:2)")
+        );
+        assert_eq!(registry.map_stack("    at <anonymous> (eval.js:4)"), None);
+    }
+}
@@ -0,0 +1,36 @@
+//! Task management from agent JavaScript
+//!
+//! A handler doing long-running work can track it as a task of its own
+//! instead of encoding task JSON in its return value. [`TaskOperations`]
+//! backs the `taskCreate(task)`, `taskUpdateStatus(taskId, state, message)`,
+//! and `taskAddArtifact(taskId, artifact)` globals, each of which resolves to
+//! the task as stored. The A2A agent implements it over its task store, so
+//! these tasks can be fetched, listed, and subscribed to like any other.
+
+use async_trait::async_trait;
+use baml_rt_core::Result;
+use serde_json::Value;
+
+/// Creates and updates tasks on behalf of agent JavaScript
+///
+/// Tasks and states use the A2A wire format, e.g. `TASK_STATE_WORKING`.
+#[async_trait]
+pub trait TaskOperations: Send + Sync {
+    /// Store a new task from the fields given in `task`, returning it
+    ///
+    /// The task gets an ID and the request's context unless it names its
+    /// own, and starts in `TASK_STATE_SUBMITTED` unless it has a status.
+    async fn create(&self, task: Value) -> Result<Value>;
+
+    /// Move task `task_id` to `state`, with an optional status message:
+    /// either a message or the text of one
+    async fn update_status(
+        &self,
+        task_id: &str,
+        state: &str,
+        message: Option<Value>,
+    ) -> Result<Value>;
+
+    /// Attach `artifact` to task `task_id`
+    async fn add_artifact(&self, task_id: &str, artifact: Value) -> Result<Value>;
+}
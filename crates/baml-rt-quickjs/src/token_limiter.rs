@@ -0,0 +1,315 @@
+//! Output-token limits for streaming BAML calls
+//!
+//! A [`StreamTokenLimiter`] counts tokens as stream chunks arrive and stops the
+//! generation once a per-request or per-tenant cap is reached. The call is
+//! aborted through its BAML `TripWire`, and the final value handed back to
+//! JavaScript is wrapped by [`StreamBudget::annotate`] so callers can tell a
+//! policy cut-off from a normal completion.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio_util::sync::CancellationToken;
+
+/// Counts tokens in generated text
+pub trait TokenCounter: Send + Sync {
+    fn count(&self, text: &str) -> u64;
+}
+
+/// Approximates tokens as one per four characters, rounded up
+pub struct ApproxTokenCounter;
+
+impl TokenCounter for ApproxTokenCounter {
+    fn count(&self, text: &str) -> u64 {
+        (text.chars().count() as u64).div_ceil(4)
+    }
+}
+
+/// What to keep when a stream hits its cap
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LimitAction {
+    /// Keep the chunk that crossed the cap, cut down to the cap when it is text
+    #[default]
+    Truncate,
+    /// Discard the chunk that crossed the cap and keep the last one within it
+    Stop,
+}
+
+/// Which cap ended a stream
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LimitScope {
+    Request,
+    Tenant,
+}
+
+/// Output-token caps applied to streaming calls
+#[derive(Debug, Clone, Default)]
+pub struct TokenLimits {
+    pub per_request: Option<u64>,
+    pub per_tenant: Option<u64>,
+    pub action: LimitAction,
+}
+
+impl TokenLimits {
+    /// Cap the output tokens of a single streaming call
+    pub fn with_per_request(mut self, max_tokens: u64) -> Self {
+        self.per_request = Some(max_tokens);
+        self
+    }
+
+    /// Cap the output tokens a tenant can use across all its streaming calls
+    pub fn with_per_tenant(mut self, max_tokens: u64) -> Self {
+        self.per_tenant = Some(max_tokens);
+        self
+    }
+
+    /// Choose what to keep when a cap is hit
+    pub fn with_action(mut self, action: LimitAction) -> Self {
+        self.action = action;
+        self
+    }
+}
+
+/// Tracks output-token usage per tenant and hands out per-stream budgets
+pub struct StreamTokenLimiter {
+    limits: TokenLimits,
+    counter: Arc<dyn TokenCounter>,
+    tenant_usage: Mutex<HashMap<String, u64>>,
+}
+
+impl StreamTokenLimiter {
+    pub fn new(limits: TokenLimits) -> Self {
+        Self {
+            limits,
+            counter: Arc::new(ApproxTokenCounter),
+            tenant_usage: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Use a tokenizer-accurate counter instead of the approximation
+    pub fn with_counter(mut self, counter: Arc<dyn TokenCounter>) -> Self {
+        self.counter = counter;
+        self
+    }
+
+    pub fn limits(&self) -> &TokenLimits {
+        &self.limits
+    }
+
+    /// Output tokens recorded for a tenant so far
+    pub fn tenant_usage(&self, tenant: &str) -> u64 {
+        self.tenant_usage
+            .lock()
+            .map(|usage| usage.get(tenant).copied().unwrap_or(0))
+            .unwrap_or(0)
+    }
+
+    /// Forget a tenant's usage, e.g. at the start of a billing window
+    pub fn reset_tenant(&self, tenant: &str) {
+        if let Ok(mut usage) = self.tenant_usage.lock() {
+            usage.remove(tenant);
+        }
+    }
+
+    /// Start a budget for one streaming call made on behalf of `tenant`
+    pub fn begin(self: &Arc<Self>, tenant: Option<String>) -> StreamBudget {
+        let tenant_remaining = match (&tenant, self.limits.per_tenant) {
+            (Some(tenant), Some(cap)) => Some(cap.saturating_sub(self.tenant_usage(tenant))),
+            _ => None,
+        };
+        let (cap, scope) = match (self.limits.per_request, tenant_remaining) {
+            (Some(request), Some(tenant)) if tenant < request => (Some(tenant), LimitScope::Tenant),
+            (Some(request), _) => (Some(request), LimitScope::Request),
+            (None, Some(tenant)) => (Some(tenant), LimitScope::Tenant),
+            (None, None) => (None, LimitScope::Request),
+        };
+        StreamBudget {
+            limiter: self.clone(),
+            tenant,
+            cap,
+            scope,
+            used: 0,
+            last_value: None,
+            exceeded: false,
+            cancel: CancellationToken::new(),
+        }
+    }
+
+    fn record(&self, tenant: &str, tokens: u64) {
+        if let Ok(mut usage) = self.tenant_usage.lock() {
+            *usage.entry(tenant.to_string()).or_insert(0) += tokens;
+        }
+    }
+}
+
+/// Outcome of feeding a chunk to a [`StreamBudget`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum ChunkVerdict {
+    /// The chunk is within budget and should be forwarded
+    Forward(Value),
+    /// The cap was hit; the stream has been cancelled and nothing more should
+    /// be forwarded
+    Exceeded,
+}
+
+/// Token budget for a single streaming call
+///
+/// Chunks are cumulative partial results, so each one is counted in full
+/// rather than added to the previous count.
+pub struct StreamBudget {
+    limiter: Arc<StreamTokenLimiter>,
+    tenant: Option<String>,
+    cap: Option<u64>,
+    scope: LimitScope,
+    used: u64,
+    last_value: Option<Value>,
+    exceeded: bool,
+    cancel: CancellationToken,
+}
+
+impl StreamBudget {
+    /// Token that aborts the underlying BAML call when cancelled
+    pub fn cancellation_token(&self) -> CancellationToken {
+        self.cancel.clone()
+    }
+
+    /// Whether the stream was cut off by policy
+    pub fn exceeded(&self) -> bool {
+        self.exceeded
+    }
+
+    /// Tokens counted so far
+    pub fn used(&self) -> u64 {
+        self.used
+    }
+
+    /// Count a chunk against the budget, cancelling the stream if it crosses the cap
+    pub fn observe(&mut self, chunk: Value) -> ChunkVerdict {
+        if self.exceeded {
+            return ChunkVerdict::Exceeded;
+        }
+        let tokens = self.count(&chunk);
+        let Some(cap) = self.cap.filter(|cap| tokens > *cap) else {
+            self.used = tokens;
+            self.last_value = Some(chunk.clone());
+            return ChunkVerdict::Forward(chunk);
+        };
+
+        self.exceeded = true;
+        self.cancel.cancel();
+        if self.limiter.limits.action == LimitAction::Truncate {
+            let truncated = self.truncate(chunk, cap);
+            self.used = self.count(&truncated);
+            self.last_value = Some(truncated);
+        }
+        ChunkVerdict::Exceeded
+    }
+
+    /// Final value for a stream that hit its cap, wrapped with the policy details
+    pub fn annotate(&self) -> Value {
+        json!({
+            "value": self.last_value.clone().unwrap_or(Value::Null),
+            "truncated_by_policy": {
+                "reason": "output_token_limit",
+                "scope": self.scope,
+                "limit": self.cap,
+                "tokens": self.used,
+                "action": self.limiter.limits.action,
+            }
+        })
+    }
+
+    fn count(&self, value: &Value) -> u64 {
+        match value {
+            Value::String(text) => self.limiter.counter.count(text),
+            other => self.limiter.counter.count(&other.to_string()),
+        }
+    }
+
+    /// Shorten string content to fit `cap`; structured values are kept whole
+    fn truncate(&self, value: Value, cap: u64) -> Value {
+        let Value::String(text) = value else {
+            return value;
+        };
+        let chars: Vec<char> = text.chars().collect();
+        let (mut low, mut high) = (0, chars.len());
+        while low < high {
+            let mid = (low + high).div_ceil(2);
+            let prefix: String = chars[..mid].iter().collect();
+            if self.limiter.counter.count(&prefix) <= cap {
+                low = mid;
+            } else {
+                high = mid - 1;
+            }
+        }
+        Value::String(chars[..low].iter().collect())
+    }
+}
+
+impl Drop for StreamBudget {
+    fn drop(&mut self) {
+        if let Some(tenant) = &self.tenant {
+            self.limiter.record(tenant, self.used);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn truncates_text_to_request_cap() {
+        let limiter = Arc::new(StreamTokenLimiter::new(
+            TokenLimits::default().with_per_request(2),
+        ));
+        let mut budget = limiter.begin(None);
+
+        assert_eq!(
+            budget.observe(json!("abcd")),
+            ChunkVerdict::Forward(json!("abcd"))
+        );
+        assert_eq!(
+            budget.observe(json!("abcdefghijkl")),
+            ChunkVerdict::Exceeded
+        );
+        assert!(budget.cancellation_token().is_cancelled());
+
+        let annotated = budget.annotate();
+        assert_eq!(annotated["value"], "abcdefgh");
+        assert_eq!(annotated["truncated_by_policy"]["scope"], "request");
+        assert_eq!(annotated["truncated_by_policy"]["tokens"], 2);
+    }
+
+    #[test]
+    fn tenant_usage_carries_across_streams() {
+        let limiter = Arc::new(StreamTokenLimiter::new(
+            TokenLimits::default()
+                .with_per_tenant(3)
+                .with_action(LimitAction::Stop),
+        ));
+
+        let mut first = limiter.begin(Some("acme".to_string()));
+        assert!(matches!(
+            first.observe(json!("abcdefgh")),
+            ChunkVerdict::Forward(_)
+        ));
+        drop(first);
+        assert_eq!(limiter.tenant_usage("acme"), 2);
+
+        let mut second = limiter.begin(Some("acme".to_string()));
+        assert!(matches!(
+            second.observe(json!("abc")),
+            ChunkVerdict::Forward(_)
+        ));
+        assert_eq!(second.observe(json!("abcdefgh")), ChunkVerdict::Exceeded);
+        let annotated = second.annotate();
+        assert_eq!(annotated["value"], "abc");
+        assert_eq!(annotated["truncated_by_policy"]["scope"], "tenant");
+        drop(second);
+        assert_eq!(limiter.tenant_usage("acme"), 3);
+    }
+}
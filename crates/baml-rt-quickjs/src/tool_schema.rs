@@ -0,0 +1,186 @@
+//! Tool schema injection
+//!
+//! Lets selected BAML functions pick one of the registered tools without the
+//! tools being spelled out in the schema. Such a function returns a
+//! `@@dynamic` class and renders its output format:
+//!
+//! ```baml
+//! class ToolChoice {
+//!   @@dynamic
+//! }
+//!
+//! function PickTool(request: string) -> ToolChoice {
+//!   client GPT4
+//!   prompt #"
+//!     {{ request }}
+//!     {{ ctx.output_format }}
+//!   "#
+//! }
+//! ```
+//!
+//! On every call the class gains one optional property per registered tool,
+//! named after the tool (`get_weather` becomes `GetWeatherTool`), typed after
+//! its input schema and described by its description. The model fills in the
+//! property of the tool it picks, and the runtime executes that tool like any
+//! other mapped tool variant. Registering a new tool is all it takes to offer
+//! it to the model.
+
+use baml_rt_tools::ToolMetadata;
+use baml_runtime::type_builder::{TypeBuilder, WithMeta};
+use baml_types::BamlValue;
+use internal_baml_core::ir::FieldType;
+use serde_json::Value;
+use std::collections::HashSet;
+use std::sync::{Mutex, MutexGuard};
+
+/// Tool choice class used when none is configured
+pub const DEFAULT_TOOL_CHOICE_CLASS: &str = "ToolChoice";
+
+/// Which BAML functions are offered the registered tools
+#[derive(Debug, Clone)]
+pub struct ToolSchemaInjection {
+    /// Functions returning the tool choice class
+    pub functions: HashSet<String>,
+    /// The `@@dynamic` class that receives one property per tool
+    pub class_name: String,
+}
+
+impl ToolSchemaInjection {
+    pub fn new<I, S>(functions: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            functions: functions.into_iter().map(Into::into).collect(),
+            class_name: DEFAULT_TOOL_CHOICE_CLASS.to_string(),
+        }
+    }
+
+    /// Fill in `class_name` instead of `ToolChoice`
+    pub fn with_class_name(mut self, class_name: impl Into<String>) -> Self {
+        self.class_name = class_name.into();
+        self
+    }
+
+    /// Whether calls to `function_name` are offered the tools
+    pub fn applies_to(&self, function_name: &str) -> bool {
+        self.functions.contains(function_name)
+    }
+}
+
+/// Class and tool choice property name of `tool_name`, e.g. `get_weather`
+/// becomes `GetWeatherTool`
+pub fn tool_variant_name(tool_name: &str) -> String {
+    format!("{}Tool", pascal_case(tool_name))
+}
+
+/// Add `tools` to `class_name` in a fresh type builder
+pub fn build_type_builder(class_name: &str, tools: &[ToolMetadata]) -> TypeBuilder {
+    let type_builder = TypeBuilder::new();
+    let choice = type_builder.class(class_name);
+    for tool in tools {
+        let variant = tool_variant_name(&tool.name);
+        let args = object_type(&type_builder, &variant, &tool.input_schema);
+        let property = lock(&choice).property(&variant);
+        lock(&property)
+            .r#type(FieldType::optional(args))
+            .with_meta("description", BamlValue::String(tool.description.clone()));
+    }
+    type_builder
+}
+
+/// The BAML type of a JSON schema object, declared as `class_name`
+///
+/// Objects without declared properties become string maps.
+fn object_type(type_builder: &TypeBuilder, class_name: &str, schema: &Value) -> FieldType {
+    let Some(properties) = schema.get("properties").and_then(Value::as_object) else {
+        return FieldType::map(FieldType::string(), FieldType::string());
+    };
+    let required: HashSet<&str> = schema
+        .get("required")
+        .and_then(Value::as_array)
+        .map(|required| required.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default();
+
+    let class = type_builder.class(class_name);
+    for (name, property_schema) in properties {
+        let nested_name = format!("{}{}", class_name, pascal_case(name));
+        let field_type = schema_type(type_builder, &nested_name, property_schema);
+        let field_type = if required.contains(name.as_str()) {
+            field_type
+        } else {
+            FieldType::optional(field_type)
+        };
+        let property = lock(&class).property(name);
+        let property = lock(&property);
+        property.r#type(field_type);
+        if let Some(description) = property_schema.get("description").and_then(Value::as_str) {
+            property.with_meta("description", BamlValue::String(description.to_string()));
+        }
+    }
+    FieldType::class(class_name)
+}
+
+/// The BAML type of a JSON schema; untyped values are taken as strings
+fn schema_type(type_builder: &TypeBuilder, class_name: &str, schema: &Value) -> FieldType {
+    match schema.get("type").and_then(Value::as_str) {
+        Some("integer") => FieldType::int(),
+        Some("number") => FieldType::float(),
+        Some("boolean") => FieldType::bool(),
+        Some("array") => {
+            let items = schema.get("items").unwrap_or(&Value::Null);
+            schema_type(type_builder, class_name, items).as_list()
+        }
+        Some("object") => object_type(type_builder, class_name, schema),
+        _ => FieldType::string(),
+    }
+}
+
+fn pascal_case(name: &str) -> String {
+    name.split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            let mut chars = word.chars();
+            chars
+                .next()
+                .map(|first| first.to_ascii_uppercase().to_string() + chars.as_str())
+                .unwrap_or_default()
+        })
+        .collect()
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use baml_rt_tools::ToolMapper;
+    use serde_json::json;
+
+    #[test]
+    fn tool_choices_map_back_to_registered_tools() {
+        assert_eq!(tool_variant_name("get_weather"), "GetWeatherTool");
+        assert_eq!(tool_variant_name("fs.read-file"), "FsReadFileTool");
+
+        let injection = ToolSchemaInjection::new(["PickTool"]).with_class_name("Action");
+        assert!(injection.applies_to("PickTool"));
+        assert!(!injection.applies_to("Other"));
+        assert_eq!(injection.class_name, "Action");
+
+        // The model leaves the properties of the tools it did not pick empty
+        let mut mapper = ToolMapper::new();
+        mapper.register_mapping(tool_variant_name("get_weather"), "get_weather");
+        mapper.register_mapping(tool_variant_name("calculate"), "calculate");
+        let choice = json!({
+            "GetWeatherTool": { "location": "Paris" },
+            "CalculateTool": null
+        });
+        assert_eq!(
+            mapper.extract_explicit_tool_call(&choice).unwrap(),
+            Some(("get_weather".to_string(), json!({ "location": "Paris" })))
+        );
+    }
+}
@@ -58,3 +58,32 @@ async fn test_js_invoke_baml_function() {
     // At minimum, verify that we received a non-null response payload.
     assert!(!json_result.is_null(), "Expected a non-null response value");
 }
+
+#[tokio::test]
+async fn test_positional_args_use_schema_param_names() {
+    let baml_manager = setup_baml_runtime_from_fixture("voidship-rites");
+    let mut bridge = setup_bridge(baml_manager.clone()).await;
+
+    let param_names = baml_manager
+        .read()
+        .await
+        .get_function_signature("SimpleGreeting")
+        .map(|signature| signature.param_names.clone())
+        .expect("SimpleGreeting signature");
+    assert_eq!(param_names, vec!["name".to_string()]);
+
+    let result = bridge
+        .evaluate(
+            r#"JSON.stringify({
+                positional: __bamlNamedArgs(["name"], ["World"]),
+                named: __bamlNamedArgs(["name"], [{ name: "World" }]),
+                unknown: __bamlNamedArgs([], ["World"]),
+            })"#,
+        )
+        .await
+        .expect("named args helper");
+
+    assert_eq!(result["positional"]["name"], "World");
+    assert_eq!(result["named"]["name"], "World");
+    assert_eq!(result["unknown"]["arg0"], "World");
+}
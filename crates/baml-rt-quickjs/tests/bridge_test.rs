@@ -1,15 +1,17 @@
 //! Tests for QuickJS bridge integration
 
+use baml_rt::QuickJSConfig;
 use baml_rt::baml::BamlRuntimeManager;
 use baml_rt::quickjs_bridge::QuickJSBridge;
+use baml_rt::shared_runtime::SharedQuickJsRuntime;
 use std::sync::Arc;
-use tokio::sync::Mutex;
+use tokio::sync::RwLock;
 use tokio::time::{Duration, sleep};
 
 #[tokio::test]
 async fn test_quickjs_bridge_creation() {
     // Test that we can create a QuickJS bridge
-    let baml_manager = Arc::new(Mutex::new(BamlRuntimeManager::new().unwrap()));
+    let baml_manager = Arc::new(RwLock::new(BamlRuntimeManager::new().unwrap()));
     let bridge = QuickJSBridge::new(baml_manager);
 
     let bridge = bridge.await;
@@ -19,7 +21,7 @@ async fn test_quickjs_bridge_creation() {
 #[tokio::test]
 async fn test_quickjs_evaluate_simple_code() {
     // Test that we can execute simple JavaScript code
-    let baml_manager = Arc::new(Mutex::new(BamlRuntimeManager::new().unwrap()));
+    let baml_manager = Arc::new(RwLock::new(BamlRuntimeManager::new().unwrap()));
     let mut bridge = QuickJSBridge::new(baml_manager).await.unwrap();
 
     // Execute a simple JavaScript expression
@@ -33,7 +35,7 @@ async fn test_quickjs_evaluate_simple_code() {
 #[tokio::test]
 async fn test_quickjs_evaluate_json() {
     // Test JSON stringify/parse
-    let baml_manager = Arc::new(Mutex::new(BamlRuntimeManager::new().unwrap()));
+    let baml_manager = Arc::new(RwLock::new(BamlRuntimeManager::new().unwrap()));
     let mut bridge = QuickJSBridge::new(baml_manager).await.unwrap();
 
     // Execute code that returns a JSON object
@@ -47,7 +49,7 @@ async fn test_quickjs_evaluate_json() {
 
 #[tokio::test]
 async fn test_quickjs_poll_event_loop_advances_timers() {
-    let baml_manager = Arc::new(Mutex::new(BamlRuntimeManager::new().unwrap()));
+    let baml_manager = Arc::new(RwLock::new(BamlRuntimeManager::new().unwrap()));
     let mut bridge = QuickJSBridge::new(baml_manager).await.unwrap();
 
     bridge
@@ -74,3 +76,437 @@ async fn test_quickjs_poll_event_loop_advances_timers() {
 
     panic!("Timer did not fire after polling the event loop");
 }
+
+#[tokio::test]
+async fn test_quickjs_errors_are_mapped_to_original_sources() {
+    let baml_manager = Arc::new(RwLock::new(BamlRuntimeManager::new().unwrap()));
+    let mut bridge = QuickJSBridge::new(baml_manager).await.unwrap();
+
+    // Each generated line maps to the start of src/index.ts line 10 onwards
+    let code = "function fail() {\n  throw new Error(\"boom\");\n}\nglobalThis.fail = fail;\n";
+    let source_map =
+        r#"{"version":3,"sources":["src/index.ts"],"names":[],"mappings":"AASA;AACA;AACA;AACA"}"#;
+    bridge
+        .evaluate_script("dist/index.js", code, Some(source_map))
+        .await
+        .unwrap();
+
+    let error = bridge
+        .invoke_js_function("fail", serde_json::json!({}))
+        .await
+        .unwrap_err();
+    let exception = error.js_exception().expect("structured JS exception");
+    assert_eq!(exception.name, "Error");
+    assert_eq!(exception.message, "boom");
+
+    let error = error.to_string();
+    assert!(
+        error.contains("src/index.ts:11:1"),
+        "stack should point at the original source: {}",
+        error
+    );
+    assert!(
+        !error.contains("dist/index.js"),
+        "unmapped frame: {}",
+        error
+    );
+}
+
+#[tokio::test]
+async fn test_js_exceptions_keep_name_and_cause() {
+    let baml_manager = Arc::new(RwLock::new(BamlRuntimeManager::new().unwrap()));
+    let mut bridge = QuickJSBridge::new(baml_manager).await.unwrap();
+    bridge
+        .evaluate(
+            r#"globalThis.lookup = function() {
+                const cause = new RangeError("index 7 out of range");
+                const error = new TypeError("lookup failed");
+                error.cause = cause;
+                throw error;
+            };"#,
+        )
+        .await
+        .unwrap();
+
+    let error = bridge
+        .invoke_js_function("lookup", serde_json::json!({}))
+        .await
+        .unwrap_err();
+    let exception = error.js_exception().expect("structured JS exception");
+    assert_eq!(exception.name, "TypeError");
+    assert_eq!(exception.message, "lookup failed");
+    assert!(exception.stack.is_some());
+    let cause = exception.cause.as_deref().expect("cause");
+    assert_eq!(cause.name, "RangeError");
+    assert_eq!(cause.message, "index 7 out of range");
+}
+
+#[tokio::test]
+async fn test_realms_in_shared_runtime_are_isolated() {
+    let shared = SharedQuickJsRuntime::new(QuickJSConfig::default());
+    let mut bridges = Vec::new();
+    for name in ["agent-a", "agent-b"] {
+        let baml_manager = Arc::new(RwLock::new(BamlRuntimeManager::new().unwrap()));
+        let mut bridge = QuickJSBridge::new_in_realm(baml_manager, &shared, name)
+            .await
+            .unwrap();
+        bridge.register_baml_functions().await.unwrap();
+        bridge
+            .evaluate(&format!(
+                "globalThis.whoami = () => ({{ name: '{}', seen: typeof globalThis.marker }});",
+                name
+            ))
+            .await
+            .unwrap();
+        bridges.push(bridge);
+    }
+    bridges[0].evaluate("globalThis.marker = 1;").await.unwrap();
+
+    let a = bridges[0]
+        .invoke_js_function("whoami", serde_json::json!({}))
+        .await
+        .unwrap();
+    let b = bridges[1]
+        .invoke_js_function("whoami", serde_json::json!({}))
+        .await
+        .unwrap();
+    assert_eq!(
+        a,
+        serde_json::json!({ "name": "agent-a", "seen": "number" })
+    );
+    assert_eq!(
+        b,
+        serde_json::json!({ "name": "agent-b", "seen": "undefined" })
+    );
+    assert_eq!(shared.realms(), vec!["agent-a", "agent-b"]);
+
+    let baml_manager = Arc::new(RwLock::new(BamlRuntimeManager::new().unwrap()));
+    assert!(
+        QuickJSBridge::new_in_realm(baml_manager, &shared, "agent-a")
+            .await
+            .is_err(),
+        "realm names must be unique"
+    );
+
+    bridges.remove(0);
+    assert_eq!(shared.realms(), vec!["agent-b"]);
+}
+
+#[tokio::test]
+async fn test_memory_stats_track_heap_and_gc() {
+    let baml_manager = Arc::new(RwLock::new(BamlRuntimeManager::new().unwrap()));
+    let mut bridge = QuickJSBridge::new(baml_manager).await.unwrap();
+
+    let before = bridge.memory_stats();
+    assert!(before.malloc_size > 0);
+    assert_eq!(before.malloc_limit, None);
+
+    bridge
+        .evaluate("globalThis.retained = Array.from({ length: 5000 }, (_, i) => ({ i }));")
+        .await
+        .unwrap();
+    let after = bridge.memory_stats();
+    assert!(
+        after.object_count >= before.object_count + 5000,
+        "retained objects should be counted: {:?} -> {:?}",
+        before,
+        after
+    );
+
+    bridge.collect_garbage();
+    assert_eq!(bridge.memory_stats().gc_runs, 1);
+}
+
+#[tokio::test]
+async fn test_exhausting_the_heap_is_an_engine_failure() {
+    let baml_manager = Arc::new(RwLock::new(BamlRuntimeManager::new().unwrap()));
+    let config = QuickJSConfig::default().with_memory_limit(Some(16 * 1024 * 1024));
+    let mut bridge = QuickJSBridge::new_with_config(baml_manager, config)
+        .await
+        .unwrap();
+
+    let error = bridge
+        .evaluate("const chunks = []; while (true) { chunks.push('x'.repeat(1 << 20)); }")
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(error, baml_rt::BamlRtError::EngineFailure { .. }),
+        "{:?}",
+        error
+    );
+}
+
+#[tokio::test]
+async fn test_baml_context_exposes_and_extends_request_metadata() {
+    use baml_rt_core::context::{self, RequestMetadata};
+    use baml_rt_core::ids::ContextId;
+
+    let baml_manager = Arc::new(RwLock::new(BamlRuntimeManager::new().unwrap()));
+    let mut bridge = QuickJSBridge::new(baml_manager).await.unwrap();
+    bridge.register_baml_functions().await.unwrap();
+    bridge
+        .evaluate(
+            r#"globalThis.tag = function(args) {
+                const ctx = bamlContext().setMetadata("handled_by", args.agent);
+                return { contextId: ctx.contextId, tenant: ctx.metadata.tenant };
+            };"#,
+        )
+        .await
+        .unwrap();
+
+    let mut values = serde_json::Map::new();
+    values.insert("tenant".to_string(), serde_json::json!("acme"));
+    let metadata = RequestMetadata::new(values);
+    let result = context::with_context(ContextId::from("ctx-42"), metadata.clone(), async {
+        bridge
+            .invoke_js_function("tag", serde_json::json!({ "agent": "support" }))
+            .await
+    })
+    .await
+    .unwrap();
+
+    assert_eq!(
+        result,
+        serde_json::json!({ "contextId": "ctx-42", "tenant": "acme" })
+    );
+    assert_eq!(
+        metadata.get("handled_by"),
+        Some(serde_json::json!("support"))
+    );
+}
+
+#[tokio::test]
+async fn test_reloading_the_schema_updates_functions_and_wrappers() {
+    let fixture = test_support::common::agent_fixture("voidship-rites").join("baml_src");
+    let source = std::fs::read_to_string(fixture.join("voidship_prompt.baml")).unwrap();
+    let revised = tempfile::tempdir().unwrap();
+    std::fs::create_dir(revised.path().join("baml_src")).unwrap();
+    std::fs::write(
+        revised.path().join("baml_src").join("voidship_prompt.baml"),
+        source.replace("ChooseRiteTool", "ChooseRite"),
+    )
+    .unwrap();
+
+    let baml_manager = Arc::new(RwLock::new(BamlRuntimeManager::new().unwrap()));
+    let mut bridge = QuickJSBridge::new(baml_manager.clone()).await.unwrap();
+    bridge.register_baml_functions().await.unwrap();
+
+    let loaded = bridge
+        .reload_schema(fixture.to_str().unwrap())
+        .await
+        .unwrap();
+    assert_eq!(loaded.added, ["ChooseRiteTool", "VoidshipGreeting"]);
+    assert!(loaded.removed.is_empty());
+
+    let reload = bridge
+        .reload_schema(revised.path().to_str().unwrap())
+        .await
+        .unwrap();
+    assert_eq!(reload.added, ["ChooseRite"]);
+    assert_eq!(reload.removed, ["ChooseRiteTool"]);
+    let wrappers = bridge
+        .evaluate(
+            "[typeof ChooseRite, typeof ChooseRiteStream, \
+             typeof ChooseRiteTool, typeof ChooseRiteToolStream]",
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        wrappers,
+        serde_json::json!(["function", "function", "undefined", "undefined"])
+    );
+
+    // A schema that does not load leaves the current one in place
+    assert!(bridge.reload_schema("/nonexistent").await.is_err());
+    let mut functions = baml_manager.read().await.list_functions();
+    functions.sort();
+    assert_eq!(functions, ["ChooseRite", "VoidshipGreeting"]);
+}
+
+#[tokio::test]
+async fn test_js_llm_interceptors_join_the_interceptor_registry() {
+    use baml_rt::interceptor::{InterceptorDecision, LLMCallContext};
+
+    let baml_manager = Arc::new(RwLock::new(BamlRuntimeManager::new().unwrap()));
+    let mut bridge = QuickJSBridge::new(baml_manager.clone()).await.unwrap();
+    bridge.register_baml_functions().await.unwrap();
+
+    let registered = bridge
+        .evaluate(
+            r#"registerLLMInterceptor(async (event) => {
+                globalThis.phases = [...(globalThis.phases ?? []), event.phase];
+                if (event.phase === 'before' && event.context.model === 'unvetted') {
+                    const reason = `${event.context.functionName} uses an unvetted model`;
+                    return { action: 'block', reason };
+                }
+                if (event.phase === 'after') {
+                    return { action: 'modify', result: String(event.result).toUpperCase() };
+                }
+            }).then((name) => ({ name }))"#,
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        registered,
+        serde_json::json!({ "name": "js-llm-interceptor-1" })
+    );
+
+    let context = |model: &str| LLMCallContext {
+        client: "openai".to_string(),
+        model: model.to_string(),
+        function_name: "Summarize".to_string(),
+        context_id: "ctx-1".into(),
+        prompt: serde_json::json!("Summarize this"),
+        metadata: serde_json::json!({}),
+    };
+    let registry = baml_manager.read().await.interceptor_registry();
+    let registry = registry.lock().await;
+    assert!(matches!(
+        registry.intercept_llm_call(&context("gpt-4o")).await,
+        Ok(InterceptorDecision::Allow)
+    ));
+    let blocked = registry
+        .intercept_llm_call(&context("unvetted"))
+        .await
+        .unwrap_err();
+    assert!(
+        blocked
+            .to_string()
+            .contains("Summarize uses an unvetted model"),
+        "{}",
+        blocked
+    );
+    let result = registry
+        .transform_llm_result(&context("gpt-4o"), serde_json::json!("quiet"))
+        .await;
+    assert_eq!(result, serde_json::json!("QUIET"));
+    drop(registry);
+
+    let phases = bridge.evaluate("globalThis.phases").await.unwrap();
+    assert_eq!(phases, serde_json::json!(["before", "before", "after"]));
+}
+
+#[tokio::test]
+async fn test_kv_state_is_namespaced_per_agent_and_context() {
+    use baml_rt::kv_store::{InMemoryKvStore, KvStore};
+    use baml_rt_core::context;
+    use baml_rt_core::ids::ContextId;
+    use serde_json::{Value, json};
+
+    async fn bridge_for(store: Arc<dyn KvStore>, agent: &str) -> QuickJSBridge {
+        let mut manager = BamlRuntimeManager::new().unwrap();
+        manager.set_agent_name(agent);
+        manager.set_kv_store(store);
+        let mut bridge = QuickJSBridge::new(Arc::new(RwLock::new(manager)))
+            .await
+            .unwrap();
+        bridge.register_baml_functions().await.unwrap();
+        bridge
+            .evaluate(
+                r#"globalThis.remember = async function(args) {
+                    const seen = await kvGet("visits");
+                    await kvSet("visits", (seen ?? 0) + 1);
+                    await kvSet("topic", args.topic, { scope: "context" });
+                    return { seen, topic: await kvGet("topic", { scope: "context" }) };
+                };"#,
+            )
+            .await
+            .unwrap();
+        bridge
+    }
+
+    async fn remember(bridge: &mut QuickJSBridge, context_id: &str, topic: &str) -> Value {
+        let call = bridge.invoke_js_function("remember", json!({ "topic": topic }));
+        context::with_context_id(ContextId::from(context_id), call)
+            .await
+            .unwrap()
+    }
+
+    let store: Arc<dyn KvStore> = Arc::new(InMemoryKvStore::new());
+    let mut planner = bridge_for(store.clone(), "planner").await;
+    let mut writer = bridge_for(store, "writer").await;
+
+    assert_eq!(
+        remember(&mut planner, "ctx-1", "orbits").await,
+        json!({ "seen": null, "topic": "orbits" })
+    );
+    assert_eq!(
+        remember(&mut planner, "ctx-2", "comets").await,
+        json!({ "seen": 1, "topic": "comets" })
+    );
+    assert_eq!(
+        remember(&mut writer, "ctx-1", "drafts").await,
+        json!({ "seen": null, "topic": "drafts" })
+    );
+
+    let deleted = planner
+        .evaluate(r#"kvDelete("visits").then((deleted) => ({ deleted }))"#)
+        .await
+        .unwrap();
+    assert_eq!(deleted, json!({ "deleted": true }));
+    let error = planner
+        .evaluate(r#"kvGet("topic", { scope: "context" })"#)
+        .await
+        .unwrap_err();
+    assert!(error.to_string().contains("No context"), "{}", error);
+}
+
+#[tokio::test]
+async fn test_embeddings_are_batched_and_cached() {
+    use async_trait::async_trait;
+    use baml_rt::embeddings::EmbeddingProvider;
+    use serde_json::json;
+    use std::sync::Mutex;
+
+    /// Embeds each text as its length, recording the batches it was sent
+    #[derive(Default)]
+    struct LengthEmbeddings {
+        batches: Mutex<Vec<Vec<String>>>,
+    }
+
+    #[async_trait]
+    impl EmbeddingProvider for LengthEmbeddings {
+        async fn embed(
+            &self,
+            _model: &str,
+            texts: &[String],
+        ) -> baml_rt_core::Result<Vec<Vec<f32>>> {
+            self.batches.lock().unwrap().push(texts.to_vec());
+            Ok(texts.iter().map(|text| vec![text.len() as f32]).collect())
+        }
+    }
+
+    let provider = Arc::new(LengthEmbeddings::default());
+    let mut manager = BamlRuntimeManager::new().unwrap();
+    manager.set_embedding_provider(provider.clone());
+    manager.set_embedding_batch_size(2);
+    let manager = Arc::new(RwLock::new(manager));
+
+    let texts: Vec<String> = ["a", "bb", "a", "ccc"].map(String::from).to_vec();
+    let vectors = manager
+        .read()
+        .await
+        .embed_batch(&texts, "mini")
+        .await
+        .unwrap();
+    assert_eq!(vectors, vec![vec![1.0], vec![2.0], vec![1.0], vec![3.0]]);
+    assert_eq!(
+        *provider.batches.lock().unwrap(),
+        vec![
+            vec!["a".to_string(), "bb".to_string()],
+            vec!["ccc".to_string()]
+        ]
+    );
+
+    let mut bridge = QuickJSBridge::new(manager).await.unwrap();
+    bridge.register_baml_functions().await.unwrap();
+    let embedded = bridge
+        .evaluate(
+            r#"Promise.all([embed("bb", "mini"), embed(["dddd", "a"], "mini")])
+                .then(([one, many]) => ({ one, many }))"#,
+        )
+        .await
+        .unwrap();
+    assert_eq!(embedded, json!({ "one": [2], "many": [[4], [1]] }));
+    assert_eq!(provider.batches.lock().unwrap().len(), 3);
+}
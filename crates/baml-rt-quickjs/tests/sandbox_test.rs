@@ -1,6 +1,12 @@
 //! Tests for QuickJS sandboxing
 
 use baml_rt::A2aAgent;
+use baml_rt::baml::BamlRuntimeManager;
+use baml_rt::quickjs_bridge::QuickJSBridge;
+use baml_rt_observability::{RecentActivity, RecentActivityLayer};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tracing_subscriber::layer::SubscriberExt;
 
 #[tokio::test]
 async fn test_sandbox_prevents_require() {
@@ -66,6 +72,44 @@ async fn test_sandbox_console_log_works() {
     );
 }
 
+#[tokio::test]
+async fn test_structured_logs_carry_the_agent_and_request() {
+    // Host functions run on the QuickJS thread, so only a global subscriber sees them
+    let activity = Arc::new(RecentActivity::new(100, 0));
+    let subscriber =
+        tracing_subscriber::registry().with(RecentActivityLayer::new(activity.clone()));
+    tracing::subscriber::set_global_default(subscriber).unwrap();
+
+    let mut manager = BamlRuntimeManager::new().unwrap();
+    manager.set_agent_name("voidship-rites");
+    let mut bridge = QuickJSBridge::new(Arc::new(RwLock::new(manager)))
+        .await
+        .unwrap();
+    let code = r#"
+        (() => {
+            log.warn({ message: "Hull breach", deck: 7 });
+            log.info("Course set", { heading: 42 });
+            return { logged: true };
+        })()
+    "#;
+    bridge.evaluate(code).await.unwrap();
+
+    let logs: Vec<_> = activity
+        .logs()
+        .into_iter()
+        .filter(|log| log.target == "baml_rt::js_log")
+        .collect();
+    assert_eq!(logs.len(), 2);
+    assert_eq!(logs[0].level, "WARN");
+    assert_eq!(logs[0].message, "Hull breach");
+    assert_eq!(logs[0].fields["agent"], "voidship-rites");
+    assert_eq!(logs[0].fields["fields"], r#"{"deck":7}"#);
+    assert!(logs[0].fields.contains_key("correlation_id"));
+    assert_eq!(logs[1].level, "INFO");
+    assert_eq!(logs[1].message, "Course set");
+    assert_eq!(logs[1].fields["fields"], r#"{"heading":42}"#);
+}
+
 #[tokio::test]
 async fn test_sandbox_prevents_fetch() {
     let agent = A2aAgent::builder().build().await.unwrap();
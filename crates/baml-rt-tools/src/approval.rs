@@ -0,0 +1,235 @@
+//! Approval gate - tool calls that wait for a person to allow them
+//!
+//! A tool whose metadata sets `requires_approval` does not run until its call
+//! is approved. The [`ApprovalGate`] first asks its [`ToolApprover`], if one
+//! is set; when there is none, or it leaves the call to a person, the call
+//! fails with [`BamlRtError::ApprovalRequired`] and waits as a
+//! [`ProposedToolCall`] until someone approves it with
+//! [`decide`](ApprovalGate::decide). An approved call runs when it is made
+//! again, with the same arguments, in the same context.
+
+use async_trait::async_trait;
+use baml_rt_core::ids::ContextId;
+use baml_rt_core::{BamlRtError, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, Mutex};
+
+/// A call to a tool that requires approval
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProposedToolCall {
+    /// Same for every call of the tool with these arguments in the context
+    pub id: String,
+    pub context_id: ContextId,
+    /// Qualified name of the tool
+    pub tool: String,
+    /// BAML function the call was made for, if known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub function: Option<String>,
+    pub args: Value,
+}
+
+impl ProposedToolCall {
+    pub fn new(
+        context_id: ContextId,
+        tool: impl Into<String>,
+        function: Option<String>,
+        args: Value,
+    ) -> Self {
+        let tool = tool.into();
+        let mut hasher = DefaultHasher::new();
+        (context_id.as_str(), tool.as_str(), args.to_string()).hash(&mut hasher);
+        Self {
+            id: format!("approval-{:016x}", hasher.finish()),
+            context_id,
+            tool,
+            function,
+            args,
+        }
+    }
+}
+
+/// What was decided about a proposed tool call
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApprovalDecision {
+    Approve,
+    Deny,
+}
+
+/// Decides tool calls as they are proposed, instead of a person
+#[async_trait]
+pub trait ToolApprover: Send + Sync {
+    /// The decision about `call`, or `None` to leave it to a person
+    async fn decide(&self, call: &ProposedToolCall) -> Option<ApprovalDecision>;
+}
+
+/// Keeps a record of approval decisions, e.g. in provenance
+#[async_trait]
+pub trait ApprovalRecorder: Send + Sync {
+    /// `decided_by` says where the decision came from, e.g. `approver`
+    async fn record_decision(
+        &self,
+        call: &ProposedToolCall,
+        decision: ApprovalDecision,
+        decided_by: &str,
+    );
+}
+
+/// Holds tool calls that require approval until they are decided
+#[derive(Default)]
+pub struct ApprovalGate {
+    approver: Option<Arc<dyn ToolApprover>>,
+    recorder: Option<Arc<dyn ApprovalRecorder>>,
+    /// Approved calls not made yet, by ID
+    approved: Mutex<HashSet<String>>,
+    /// The latest call waiting for a person, by context
+    pending: Mutex<HashMap<ContextId, ProposedToolCall>>,
+}
+
+impl ApprovalGate {
+    /// A gate that leaves every call to a person
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Ask `approver` about each call first
+    pub fn with_approver(mut self, approver: Arc<dyn ToolApprover>) -> Self {
+        self.approver = Some(approver);
+        self
+    }
+
+    /// Report every decision to `recorder`
+    pub fn with_recorder(mut self, recorder: Arc<dyn ApprovalRecorder>) -> Self {
+        self.recorder = Some(recorder);
+        self
+    }
+
+    /// Let `call` run, or fail it
+    ///
+    /// A call approved earlier runs once. Otherwise the approver decides;
+    /// when it does not, the call is kept as the pending call of its context
+    /// and fails with [`BamlRtError::ApprovalRequired`]. A denied call fails
+    /// with [`BamlRtError::Forbidden`].
+    pub async fn authorize(&self, call: ProposedToolCall) -> Result<()> {
+        if lock(&self.approved).remove(&call.id) {
+            return Ok(());
+        }
+        let decision = match &self.approver {
+            Some(approver) => approver.decide(&call).await,
+            None => None,
+        };
+        match decision {
+            Some(decision) => {
+                self.record(&call, decision, "approver").await;
+                match decision {
+                    ApprovalDecision::Approve => Ok(()),
+                    ApprovalDecision::Deny => Err(denied(&call)),
+                }
+            }
+            None => {
+                let error = BamlRtError::ApprovalRequired {
+                    tool: call.tool.clone(),
+                    approval_id: call.id.clone(),
+                };
+                lock(&self.pending).insert(call.context_id.clone(), call);
+                Err(error)
+            }
+        }
+    }
+
+    /// Take the call of `context_id` that waits for a person, if any
+    pub fn take_pending(&self, context_id: &ContextId) -> Option<ProposedToolCall> {
+        lock(&self.pending).remove(context_id)
+    }
+
+    /// Record a person's decision about `call`; an approved call runs the
+    /// next time it is made
+    pub async fn decide(
+        &self,
+        call: &ProposedToolCall,
+        decision: ApprovalDecision,
+        decided_by: &str,
+    ) {
+        if decision == ApprovalDecision::Approve {
+            lock(&self.approved).insert(call.id.clone());
+        }
+        self.record(call, decision, decided_by).await;
+    }
+
+    async fn record(&self, call: &ProposedToolCall, decision: ApprovalDecision, decided_by: &str) {
+        tracing::info!(
+            tool = call.tool.as_str(),
+            approval_id = call.id.as_str(),
+            ?decision,
+            decided_by,
+            "Decided tool call approval"
+        );
+        if let Some(recorder) = &self.recorder {
+            recorder.record_decision(call, decision, decided_by).await;
+        }
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    mutex
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+fn denied(call: &ProposedToolCall) -> BamlRtError {
+    BamlRtError::Forbidden {
+        principal: call.function.as_ref().map_or_else(
+            || "tool approver".to_string(),
+            |function| format!("function {}", function),
+        ),
+        action: format!("execute tool '{}'", call.tool),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    struct DenyLargePayments;
+
+    #[async_trait]
+    impl ToolApprover for DenyLargePayments {
+        async fn decide(&self, call: &ProposedToolCall) -> Option<ApprovalDecision> {
+            let amount = call.args["amount"].as_u64()?;
+            (amount > 100).then_some(ApprovalDecision::Deny)
+        }
+    }
+
+    fn payment(amount: u64) -> ProposedToolCall {
+        let context_id = ContextId::from("ctx-1".to_string());
+        ProposedToolCall::new(context_id, "rust:pay", None, json!({ "amount": amount }))
+    }
+
+    #[tokio::test]
+    async fn calls_wait_until_approved_once() {
+        let gate = ApprovalGate::new().with_approver(Arc::new(DenyLargePayments));
+        let context_id = payment(0).context_id;
+
+        assert!(matches!(
+            gate.authorize(payment(500)).await,
+            Err(BamlRtError::Forbidden { .. })
+        ));
+        assert!(matches!(
+            gate.authorize(payment(5)).await,
+            Err(BamlRtError::ApprovalRequired { .. })
+        ));
+        let pending = gate.take_pending(&context_id).expect("pending call");
+        assert_eq!(pending, payment(5));
+        assert!(gate.take_pending(&context_id).is_none());
+
+        gate.decide(&pending, ApprovalDecision::Approve, "test")
+            .await;
+        assert!(gate.authorize(payment(5)).await.is_ok());
+        assert!(gate.authorize(payment(5)).await.is_err());
+    }
+}
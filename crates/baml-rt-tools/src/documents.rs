@@ -0,0 +1,559 @@
+//! Document loading and chunking tools
+//!
+//! [`register_document_tools`] adds three tools to a registry:
+//!
+//! - `load_document` reads a PDF, HTML, markdown, or text file under a root
+//!   directory and returns its text in chunks
+//! - `html_to_text` turns an HTML string into plain text
+//! - `chunk_text` splits text, markdown-aware if asked, into chunks
+//!
+//! Chunks hold whole paragraphs where they fit in `max_chunk_chars`; longer
+//! paragraphs are cut at whitespace, each piece repeating the last
+//! `overlap_chars` of the one before. Markdown is split at headings first,
+//! and each chunk names the heading it falls under. PDF chunks carry their
+//! page number.
+
+use crate::tools::{BamlTool, ToolRegistry};
+use async_trait::async_trait;
+use baml_rt_core::{BamlRtError, Result};
+use serde_json::{Value, json};
+use std::path::{Component, Path, PathBuf};
+
+/// Characters in a chunk unless the caller asks otherwise
+pub const DEFAULT_CHUNK_CHARS: usize = 2000;
+
+/// Characters repeated between the pieces of a long paragraph
+pub const DEFAULT_OVERLAP_CHARS: usize = 200;
+
+/// Largest file `load_document` reads
+pub const MAX_DOCUMENT_BYTES: u64 = 50 * 1024 * 1024;
+
+/// Line width of text rendered from HTML
+const HTML_TEXT_WIDTH: usize = 120;
+
+/// Register `load_document`, `html_to_text`, and `chunk_text`
+///
+/// `load_document` reads only files under `root`; paths are relative to it.
+pub fn register_document_tools(
+    registry: &mut ToolRegistry,
+    root: impl Into<PathBuf>,
+) -> Result<()> {
+    registry.register(LoadDocumentTool::new(root))?;
+    registry.register(HtmlToTextTool)?;
+    registry.register(ChunkTextTool)
+}
+
+/// How a document's bytes are read
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DocumentFormat {
+    Pdf,
+    Html,
+    Markdown,
+    Text,
+}
+
+impl DocumentFormat {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "pdf" => Some(Self::Pdf),
+            "html" | "htm" => Some(Self::Html),
+            "markdown" | "md" => Some(Self::Markdown),
+            "text" | "txt" => Some(Self::Text),
+            _ => None,
+        }
+    }
+
+    /// The format of `path` by its extension; text when it has none we know
+    pub fn from_path(path: &Path) -> Self {
+        path.extension()
+            .and_then(|ext| ext.to_str())
+            .and_then(Self::from_name)
+            .unwrap_or(Self::Text)
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Pdf => "pdf",
+            Self::Html => "html",
+            Self::Markdown => "markdown",
+            Self::Text => "text",
+        }
+    }
+}
+
+/// A piece of a document
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Chunk {
+    pub text: String,
+    /// The markdown heading the chunk falls under
+    pub heading: Option<String>,
+    /// The PDF page the chunk is from, counting from 1
+    pub page: Option<usize>,
+}
+
+impl Chunk {
+    fn to_value(&self, index: usize) -> Value {
+        let mut value = json!({ "index": index, "text": self.text });
+        if let Some(heading) = &self.heading {
+            value["heading"] = json!(heading);
+        }
+        if let Some(page) = self.page {
+            value["page"] = json!(page);
+        }
+        value
+    }
+}
+
+/// Chunk sizes of a tool call
+#[derive(Debug, Clone, Copy)]
+pub struct ChunkLimits {
+    pub max_chars: usize,
+    pub overlap_chars: usize,
+}
+
+impl Default for ChunkLimits {
+    fn default() -> Self {
+        Self {
+            max_chars: DEFAULT_CHUNK_CHARS,
+            overlap_chars: DEFAULT_OVERLAP_CHARS,
+        }
+    }
+}
+
+impl ChunkLimits {
+    /// Limits from the `max_chunk_chars` and `overlap_chars` of `args`
+    fn from_args(args: &Value) -> Result<Self> {
+        let defaults = Self::default();
+        let read = |name: &str, default: usize| match args.get(name) {
+            None | Some(Value::Null) => Ok(default),
+            Some(value) => value.as_u64().map(|n| n as usize).ok_or_else(|| {
+                BamlRtError::InvalidArgument(format!("'{}' must be a non-negative integer", name))
+            }),
+        };
+        let limits = Self {
+            max_chars: read("max_chunk_chars", defaults.max_chars)?,
+            overlap_chars: read("overlap_chars", defaults.overlap_chars)?,
+        };
+        if limits.max_chars == 0 || limits.overlap_chars >= limits.max_chars {
+            return Err(BamlRtError::InvalidArgument(
+                "'max_chunk_chars' must be positive and larger than 'overlap_chars'".to_string(),
+            ));
+        }
+        Ok(limits)
+    }
+}
+
+/// Split `text` into chunks of at most `limits.max_chars` characters
+pub fn chunk_text(text: &str, limits: ChunkLimits) -> Vec<String> {
+    let mut chunks = Vec::new();
+    let mut current = String::new();
+    let mut current_chars = 0;
+    for paragraph in text.split("\n\n").map(str::trim).filter(|p| !p.is_empty()) {
+        let length = paragraph.chars().count();
+        if !current.is_empty() && current_chars + 2 + length > limits.max_chars {
+            chunks.push(std::mem::take(&mut current));
+            current_chars = 0;
+        }
+        if length > limits.max_chars {
+            split_long(paragraph, limits, &mut chunks);
+            continue;
+        }
+        if !current.is_empty() {
+            current.push_str("\n\n");
+            current_chars += 2;
+        }
+        current.push_str(paragraph);
+        current_chars += length;
+    }
+    if !current.is_empty() {
+        chunks.push(current);
+    }
+    chunks
+}
+
+/// Cut `paragraph` into overlapping pieces, ending each at whitespace when
+/// there is some in its second half
+fn split_long(paragraph: &str, limits: ChunkLimits, chunks: &mut Vec<String>) {
+    let chars: Vec<char> = paragraph.chars().collect();
+    let mut start = 0;
+    while start < chars.len() {
+        let mut end = (start + limits.max_chars).min(chars.len());
+        if end < chars.len() {
+            let half = start + limits.max_chars / 2;
+            if let Some(space) = (half..end).rev().find(|&i| chars[i].is_whitespace()) {
+                end = space;
+            }
+        }
+        chunks.push(
+            chars[start..end]
+                .iter()
+                .collect::<String>()
+                .trim()
+                .to_string(),
+        );
+        if end == chars.len() {
+            break;
+        }
+        // The next piece starts at a word within the overlap, if one starts there
+        let overlap_start = end.saturating_sub(limits.overlap_chars);
+        start = (overlap_start..end)
+            .find(|&i| i > 0 && chars[i - 1].is_whitespace() && !chars[i].is_whitespace())
+            .unwrap_or(overlap_start)
+            .max(start + 1);
+    }
+}
+
+/// Split markdown at its headings, then chunk each section
+///
+/// Headings within fenced code blocks are not headings.
+pub fn split_markdown(markdown: &str, limits: ChunkLimits) -> Vec<Chunk> {
+    let mut sections: Vec<(Option<String>, String)> = vec![(None, String::new())];
+    let mut fenced = false;
+    for line in markdown.lines() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            fenced = !fenced;
+        }
+        if !fenced && let Some(heading) = heading_text(trimmed) {
+            sections.push((Some(heading.to_string()), String::new()));
+        }
+        let (_, body) = sections.last_mut().expect("sections start non-empty");
+        body.push_str(line);
+        body.push('\n');
+    }
+    sections
+        .into_iter()
+        .flat_map(|(heading, body)| {
+            chunk_text(&body, limits)
+                .into_iter()
+                .map(move |text| Chunk {
+                    text,
+                    heading: heading.clone(),
+                    page: None,
+                })
+        })
+        .collect()
+}
+
+/// The text of an ATX heading line such as `## Install`
+fn heading_text(line: &str) -> Option<&str> {
+    let level = line.chars().take_while(|&c| c == '#').count();
+    if !(1..=6).contains(&level) {
+        return None;
+    }
+    let rest = &line[level..];
+    if !rest.is_empty() && !rest.starts_with([' ', '\t']) {
+        return None;
+    }
+    Some(rest.trim().trim_end_matches('#').trim_end())
+}
+
+/// Plain text of `html`, without scripts and styles
+pub fn html_to_text(html: &str) -> String {
+    html2text::from_read(html.as_bytes(), HTML_TEXT_WIDTH)
+}
+
+/// The text of each page of a PDF
+pub fn pdf_pages(bytes: &[u8]) -> Result<Vec<String>> {
+    pdf_extract::extract_text_from_mem_by_pages(bytes)
+        .map_err(|e| BamlRtError::ToolExecution(format!("Failed to read PDF: {}", e)))
+}
+
+/// Chunks of a document's bytes
+pub fn document_chunks(
+    bytes: &[u8],
+    format: DocumentFormat,
+    limits: ChunkLimits,
+) -> Result<Vec<Chunk>> {
+    let text = || String::from_utf8_lossy(bytes);
+    let plain = |text: &str| {
+        chunk_text(text, limits)
+            .into_iter()
+            .map(|text| Chunk {
+                text,
+                heading: None,
+                page: None,
+            })
+            .collect()
+    };
+    Ok(match format {
+        DocumentFormat::Pdf => pdf_pages(bytes)?
+            .iter()
+            .enumerate()
+            .flat_map(|(page, text)| {
+                chunk_text(text, limits).into_iter().map(move |text| Chunk {
+                    text,
+                    heading: None,
+                    page: Some(page + 1),
+                })
+            })
+            .collect(),
+        DocumentFormat::Html => plain(&html_to_text(&text())),
+        DocumentFormat::Markdown => split_markdown(&text(), limits),
+        DocumentFormat::Text => plain(&text()),
+    })
+}
+
+fn chunks_value(chunks: &[Chunk]) -> Value {
+    Value::Array(
+        chunks
+            .iter()
+            .enumerate()
+            .map(|(index, chunk)| chunk.to_value(index))
+            .collect(),
+    )
+}
+
+fn string_arg<'a>(args: &'a Value, name: &str) -> Result<&'a str> {
+    args.get(name)
+        .and_then(Value::as_str)
+        .ok_or_else(|| BamlRtError::InvalidArgument(format!("'{}' must be a string", name)))
+}
+
+fn format_arg(args: &Value, name: &str) -> Result<Option<DocumentFormat>> {
+    match args.get(name) {
+        None | Some(Value::Null) => Ok(None),
+        Some(value) => value
+            .as_str()
+            .and_then(DocumentFormat::from_name)
+            .map(Some)
+            .ok_or_else(|| {
+                BamlRtError::InvalidArgument(format!(
+                    "'{}' must be one of pdf, html, markdown, text",
+                    name
+                ))
+            }),
+    }
+}
+
+/// Reads documents under a root directory
+pub struct LoadDocumentTool {
+    root: PathBuf,
+}
+
+impl LoadDocumentTool {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// `path` within the root, refusing paths that lead out of it
+    async fn resolve(&self, path: &str) -> Result<PathBuf> {
+        let relative = Path::new(path);
+        let escapes = relative
+            .components()
+            .any(|component| !matches!(component, Component::Normal(_) | Component::CurDir));
+        if escapes {
+            return Err(BamlRtError::InvalidArgument(format!(
+                "Document path must be relative and stay within the document root: {}",
+                path
+            )));
+        }
+        let root = tokio::fs::canonicalize(&self.root).await?;
+        let resolved = tokio::fs::canonicalize(root.join(relative)).await?;
+        if !resolved.starts_with(&root) {
+            return Err(BamlRtError::InvalidArgument(format!(
+                "Document path leads out of the document root: {}",
+                path
+            )));
+        }
+        Ok(resolved)
+    }
+}
+
+#[async_trait]
+impl BamlTool for LoadDocumentTool {
+    const NAME: &'static str = "load_document";
+
+    fn description(&self) -> &'static str {
+        "Reads a PDF, HTML, markdown, or text document and returns its text in chunks"
+    }
+
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "path": {
+                    "type": "string",
+                    "description": "Path of the document, relative to the document root"
+                },
+                "format": {
+                    "type": "string",
+                    "enum": ["pdf", "html", "markdown", "text"],
+                    "description": "Document format, by default from the extension"
+                },
+                "max_chunk_chars": {
+                    "type": "integer",
+                    "description": "Largest chunk, in characters"
+                },
+                "overlap_chars": {
+                    "type": "integer",
+                    "description": "Characters shared by the pieces of a split paragraph"
+                }
+            },
+            "required": ["path"]
+        })
+    }
+
+    async fn execute(&self, args: Value) -> Result<Value> {
+        let path = string_arg(&args, "path")?;
+        let limits = ChunkLimits::from_args(&args)?;
+        let resolved = self.resolve(path).await?;
+        let format =
+            format_arg(&args, "format")?.unwrap_or_else(|| DocumentFormat::from_path(&resolved));
+        let size = tokio::fs::metadata(&resolved).await?.len();
+        if size > MAX_DOCUMENT_BYTES {
+            return Err(BamlRtError::InvalidArgument(format!(
+                "Document {} is {} bytes; at most {} can be loaded",
+                path, size, MAX_DOCUMENT_BYTES
+            )));
+        }
+        let bytes = tokio::fs::read(&resolved).await?;
+        // PDF extraction is CPU-bound, and panics on some malformed files
+        let chunks = tokio::task::spawn_blocking(move || document_chunks(&bytes, format, limits))
+            .await
+            .map_err(|e| BamlRtError::ToolExecution(format!("Failed to read {}: {}", path, e)))??;
+        Ok(json!({
+            "path": path,
+            "format": format.as_str(),
+            "chunks": chunks_value(&chunks),
+        }))
+    }
+}
+
+/// Renders HTML as plain text
+pub struct HtmlToTextTool;
+
+#[async_trait]
+impl BamlTool for HtmlToTextTool {
+    const NAME: &'static str = "html_to_text";
+
+    fn description(&self) -> &'static str {
+        "Converts an HTML page to plain text"
+    }
+
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "html": {
+                    "type": "string",
+                    "description": "HTML to convert"
+                }
+            },
+            "required": ["html"]
+        })
+    }
+
+    async fn execute(&self, args: Value) -> Result<Value> {
+        Ok(json!({ "text": html_to_text(string_arg(&args, "html")?) }))
+    }
+}
+
+/// Splits text into chunks
+pub struct ChunkTextTool;
+
+#[async_trait]
+impl BamlTool for ChunkTextTool {
+    const NAME: &'static str = "chunk_text";
+
+    fn description(&self) -> &'static str {
+        "Splits text into chunks of whole paragraphs, at headings for markdown"
+    }
+
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "text": {
+                    "type": "string",
+                    "description": "Text to split"
+                },
+                "format": {
+                    "type": "string",
+                    "enum": ["markdown", "text"],
+                    "description": "Split markdown at its headings; text by default"
+                },
+                "max_chunk_chars": {
+                    "type": "integer",
+                    "description": "Largest chunk, in characters"
+                },
+                "overlap_chars": {
+                    "type": "integer",
+                    "description": "Characters shared by the pieces of a split paragraph"
+                }
+            },
+            "required": ["text"]
+        })
+    }
+
+    async fn execute(&self, args: Value) -> Result<Value> {
+        let text = string_arg(&args, "text")?;
+        let limits = ChunkLimits::from_args(&args)?;
+        let format = match format_arg(&args, "format")? {
+            None | Some(DocumentFormat::Text) => DocumentFormat::Text,
+            Some(DocumentFormat::Markdown) => DocumentFormat::Markdown,
+            Some(format) => {
+                return Err(BamlRtError::InvalidArgument(format!(
+                    "chunk_text splits markdown or text, not {}",
+                    format.as_str()
+                )));
+            }
+        };
+        let chunks = document_chunks(text.as_bytes(), format, limits)?;
+        Ok(json!({ "chunks": chunks_value(&chunks) }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limits(max_chars: usize, overlap_chars: usize) -> ChunkLimits {
+        ChunkLimits {
+            max_chars,
+            overlap_chars,
+        }
+    }
+
+    #[test]
+    fn paragraphs_are_packed_and_long_ones_split_with_overlap() {
+        let text = "alpha beta\n\ngamma\n\n".to_string() + &"word ".repeat(10);
+        let chunks = chunk_text(&text, limits(20, 5));
+        assert_eq!(chunks[0], "alpha beta\n\ngamma");
+        assert!(chunks[1..].iter().all(|chunk| chunk.chars().count() <= 20));
+        assert!(chunks[1..].iter().all(|chunk| chunk.starts_with("word")));
+        assert!(chunks.len() >= 4, "{:?}", chunks);
+    }
+
+    #[test]
+    fn markdown_chunks_name_their_heading() {
+        let markdown =
+            "Intro\n\n# Install\n\nRun it.\n\n```sh\n# not a heading\n```\n\n## Linux ##\n\napt\n";
+        let chunks = split_markdown(markdown, ChunkLimits::default());
+        let headings: Vec<Option<&str>> = chunks.iter().map(|c| c.heading.as_deref()).collect();
+        assert_eq!(headings, vec![None, Some("Install"), Some("Linux")]);
+        assert!(chunks[1].text.contains("# not a heading"));
+    }
+
+    #[tokio::test]
+    async fn documents_are_loaded_from_the_root_only() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("guide.html"),
+            "<html><body><h1>Guide</h1><p>Hello <b>there</b></p><script>x()</script></body></html>",
+        )
+        .unwrap();
+        let tool = LoadDocumentTool::new(dir.path());
+
+        let loaded = tool.execute(json!({ "path": "guide.html" })).await.unwrap();
+        assert_eq!(loaded["format"], "html");
+        let text = loaded["chunks"][0]["text"].as_str().unwrap();
+        assert!(text.contains("Hello") && !text.contains("x()"), "{}", text);
+
+        let error = tool
+            .execute(json!({ "path": "../guide.html" }))
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("document root"), "{}", error);
+    }
+}
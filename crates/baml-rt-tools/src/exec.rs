@@ -0,0 +1,436 @@
+//! Command execution tool
+//!
+//! [`ExecCommandTool`] registers as `exec_command` and runs only the commands
+//! an [`ExecPolicy`] lists, by name. Each command is a program and argument
+//! templates; a call fills the templates' `{placeholders}` from its `args`
+//! and may pick a working directory under the policy's root. Programs run
+//! without a shell and with only `PATH` in their environment, so a value
+//! becomes exactly one argument. Runs are killed after the policy's timeout,
+//! limited in CPU time on Unix, and their output is captured up to a size.
+
+use crate::tools::BamlTool;
+use async_trait::async_trait;
+use baml_rt_core::{BamlRtError, Result};
+use serde_json::{Map, Value, json};
+use std::collections::BTreeMap;
+use std::path::{Component, Path, PathBuf};
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncReadExt};
+use tokio::process::Command;
+
+/// How long a command may run unless the policy says otherwise
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Bytes of stdout, and of stderr, kept unless the policy says otherwise
+pub const DEFAULT_MAX_OUTPUT_BYTES: usize = 64 * 1024;
+
+/// A command `exec_command` may run
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommandTemplate {
+    /// Program to run, by path or by name on `PATH`
+    pub program: String,
+    /// Arguments, with `{name}` replaced by the call's `args.name`
+    pub args: Vec<String>,
+}
+
+impl CommandTemplate {
+    /// The arguments with their placeholders filled from `values`
+    ///
+    /// Every value must be used, and none may start with `-`, so a value
+    /// cannot pass itself off as an option.
+    pub fn render(&self, values: &Map<String, Value>) -> Result<Vec<String>> {
+        let mut used = Vec::new();
+        let args = self
+            .args
+            .iter()
+            .map(|template| render_arg(template, values, &mut used))
+            .collect::<Result<Vec<_>>>()?;
+        if let Some(unused) = values.keys().find(|name| !used.contains(name)) {
+            return Err(BamlRtError::InvalidArgument(format!(
+                "Command {} takes no argument '{}'",
+                self.program, unused
+            )));
+        }
+        Ok(args)
+    }
+}
+
+fn render_arg(
+    template: &str,
+    values: &Map<String, Value>,
+    used: &mut Vec<String>,
+) -> Result<String> {
+    let mut rendered = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        let Some(end) = rest[start..].find('}') else {
+            break;
+        };
+        let name = &rest[start + 1..start + end];
+        let value = match values.get(name) {
+            Some(Value::String(value)) => value.clone(),
+            Some(value @ (Value::Number(_) | Value::Bool(_))) => value.to_string(),
+            Some(_) => {
+                return Err(BamlRtError::InvalidArgument(format!(
+                    "Argument '{}' must be a string, number, or boolean",
+                    name
+                )));
+            }
+            None => {
+                return Err(BamlRtError::InvalidArgument(format!(
+                    "Missing argument '{}'",
+                    name
+                )));
+            }
+        };
+        if value.starts_with('-') {
+            return Err(BamlRtError::InvalidArgument(format!(
+                "Argument '{}' may not start with '-'",
+                name
+            )));
+        }
+        rendered.push_str(&rest[..start]);
+        rendered.push_str(&value);
+        used.push(name.to_string());
+        rest = &rest[start + end + 1..];
+    }
+    rendered.push_str(rest);
+    Ok(rendered)
+}
+
+/// The commands `exec_command` may run, where, and for how long
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExecPolicy {
+    root: PathBuf,
+    commands: BTreeMap<String, CommandTemplate>,
+    /// Wall-clock time a run may take before it is killed
+    pub timeout: Duration,
+    /// CPU time a run may use, enforced with `RLIMIT_CPU` on Unix
+    pub cpu_limit: Option<Duration>,
+    /// Bytes of stdout, and of stderr, returned; the rest is dropped
+    pub max_output_bytes: usize,
+    /// Whether each call waits for approval
+    pub requires_approval: bool,
+}
+
+impl ExecPolicy {
+    /// A policy running no commands yet, in `root` or directories under it
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            root: root.into(),
+            commands: BTreeMap::new(),
+            timeout: DEFAULT_TIMEOUT,
+            cpu_limit: None,
+            max_output_bytes: DEFAULT_MAX_OUTPUT_BYTES,
+            requires_approval: false,
+        }
+    }
+
+    /// Allow `name` to run `program` with `args`, templates included
+    pub fn with_command<I, S>(
+        mut self,
+        name: impl Into<String>,
+        program: impl Into<String>,
+        args: I,
+    ) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let template = CommandTemplate {
+            program: program.into(),
+            args: args.into_iter().map(Into::into).collect(),
+        };
+        self.commands.insert(name.into(), template);
+        self
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn with_cpu_limit(mut self, cpu_limit: Duration) -> Self {
+        self.cpu_limit = Some(cpu_limit);
+        self
+    }
+
+    pub fn with_max_output_bytes(mut self, max_output_bytes: usize) -> Self {
+        self.max_output_bytes = max_output_bytes;
+        self
+    }
+
+    /// Make each call wait for approval, see [`crate::approval`]
+    pub fn with_approval(mut self, required: bool) -> Self {
+        self.requires_approval = required;
+        self
+    }
+
+    pub fn command(&self, name: &str) -> Option<&CommandTemplate> {
+        self.commands.get(name)
+    }
+
+    /// `cwd` within the root, refusing paths that lead out of it
+    async fn working_dir(&self, cwd: Option<&str>) -> Result<PathBuf> {
+        let root = tokio::fs::canonicalize(&self.root).await?;
+        let Some(cwd) = cwd else {
+            return Ok(root);
+        };
+        let relative = Path::new(cwd);
+        let escapes = relative
+            .components()
+            .any(|component| !matches!(component, Component::Normal(_) | Component::CurDir));
+        if escapes {
+            return Err(BamlRtError::InvalidArgument(format!(
+                "Working directory must be relative and stay within the root: {}",
+                cwd
+            )));
+        }
+        let resolved = tokio::fs::canonicalize(root.join(relative)).await?;
+        if !resolved.starts_with(&root) {
+            return Err(BamlRtError::InvalidArgument(format!(
+                "Working directory leads out of the root: {}",
+                cwd
+            )));
+        }
+        Ok(resolved)
+    }
+}
+
+/// Runs the commands an [`ExecPolicy`] allows
+pub struct ExecCommandTool {
+    policy: ExecPolicy,
+}
+
+impl ExecCommandTool {
+    pub fn new(policy: ExecPolicy) -> Self {
+        Self { policy }
+    }
+
+    pub fn policy(&self) -> &ExecPolicy {
+        &self.policy
+    }
+}
+
+/// Up to `max` bytes of `pipe`, read to its end, and whether more followed
+async fn capture(
+    pipe: Option<impl AsyncRead + Unpin>,
+    max: usize,
+) -> std::io::Result<(Vec<u8>, bool)> {
+    let Some(mut pipe) = pipe else {
+        return Ok((Vec::new(), false));
+    };
+    let mut captured = Vec::new();
+    (&mut pipe)
+        .take(max as u64)
+        .read_to_end(&mut captured)
+        .await?;
+    // Drained so a chatty command does not block on a full pipe
+    let dropped = tokio::io::copy(&mut pipe, &mut tokio::io::sink()).await?;
+    Ok((captured, dropped > 0))
+}
+
+#[cfg(unix)]
+fn limit_cpu(command: &mut Command, cpu_limit: Duration) {
+    let seconds = cpu_limit.as_secs().max(1) as libc::rlim_t;
+    // SAFETY: the closure runs in the forked child before exec and only
+    // calls setrlimit, which is async-signal-safe
+    unsafe {
+        command.pre_exec(move || {
+            let limit = libc::rlimit {
+                rlim_cur: seconds,
+                rlim_max: seconds,
+            };
+            if libc::setrlimit(libc::RLIMIT_CPU, &limit) != 0 {
+                return Err(std::io::Error::last_os_error());
+            }
+            Ok(())
+        });
+    }
+}
+
+#[cfg(not(unix))]
+fn limit_cpu(_command: &mut Command, _cpu_limit: Duration) {
+    tracing::warn!("CPU limits of exec_command are only enforced on Unix");
+}
+
+#[async_trait]
+impl BamlTool for ExecCommandTool {
+    const NAME: &'static str = "exec_command";
+
+    fn description(&self) -> &'static str {
+        "Runs an allowed command and returns its exit code, stdout, and stderr"
+    }
+
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "command": {
+                    "type": "string",
+                    "enum": self.policy.commands.keys().collect::<Vec<_>>(),
+                    "description": "Name of the command to run"
+                },
+                "args": {
+                    "type": "object",
+                    "description": "Values of the command's argument placeholders"
+                },
+                "cwd": {
+                    "type": "string",
+                    "description": "Working directory, relative to the root"
+                }
+            },
+            "required": ["command"]
+        })
+    }
+
+    fn requires_approval(&self) -> bool {
+        self.policy.requires_approval
+    }
+
+    async fn execute(&self, args: Value) -> Result<Value> {
+        let name = args.get("command").and_then(Value::as_str).ok_or_else(|| {
+            BamlRtError::InvalidArgument("'command' must be a string".to_string())
+        })?;
+        let template = self
+            .policy
+            .command(name)
+            .ok_or_else(|| BamlRtError::Forbidden {
+                principal: Self::NAME.to_string(),
+                action: format!("run command '{}'", name),
+            })?;
+        let values = match args.get("args") {
+            None | Some(Value::Null) => Map::new(),
+            Some(Value::Object(values)) => values.clone(),
+            Some(_) => {
+                return Err(BamlRtError::InvalidArgument(
+                    "'args' must be an object".to_string(),
+                ));
+            }
+        };
+        let argv = template.render(&values)?;
+        let cwd = self
+            .policy
+            .working_dir(args.get("cwd").and_then(Value::as_str))
+            .await?;
+
+        let mut command = Command::new(&template.program);
+        command
+            .args(&argv)
+            .current_dir(&cwd)
+            .env_clear()
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+        if let Some(path) = std::env::var_os("PATH") {
+            command.env("PATH", path);
+        }
+        if let Some(cpu_limit) = self.policy.cpu_limit {
+            limit_cpu(&mut command, cpu_limit);
+        }
+
+        let mut child = command.spawn().map_err(|e| {
+            BamlRtError::ToolExecution(format!("Failed to run command '{}': {}", name, e))
+        })?;
+        let max = self.policy.max_output_bytes;
+        let stdout = capture(child.stdout.take(), max);
+        let stderr = capture(child.stderr.take(), max);
+        let run = async {
+            let (stdout, stderr, status) = tokio::join!(stdout, stderr, child.wait());
+            Ok::<_, std::io::Error>((stdout?, stderr?, status?))
+        };
+        let ((stdout, stdout_truncated), (stderr, stderr_truncated), status) =
+            match tokio::time::timeout(self.policy.timeout, run).await {
+                Ok(finished) => finished?,
+                Err(_) => {
+                    let _ = child.start_kill();
+                    return Err(BamlRtError::ToolExecution(format!(
+                        "Command '{}' ran longer than {:?}",
+                        name, self.policy.timeout
+                    )));
+                }
+            };
+
+        let mut result = json!({
+            "exit_code": status.code(),
+            "success": status.success(),
+            "stdout": String::from_utf8_lossy(&stdout),
+            "stderr": String::from_utf8_lossy(&stderr),
+            "stdout_truncated": stdout_truncated,
+            "stderr_truncated": stderr_truncated,
+        });
+        #[cfg(unix)]
+        if let Some(signal) = std::os::unix::process::ExitStatusExt::signal(&status) {
+            result["signal"] = json!(signal);
+        }
+        Ok(result)
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    fn tool(root: &Path) -> ExecCommandTool {
+        ExecCommandTool::new(
+            ExecPolicy::new(root)
+                .with_command("greet", "echo", ["hello,", "{name}!"])
+                .with_command("where", "pwd", Vec::<String>::new())
+                .with_command("wait", "sleep", ["5"])
+                .with_timeout(Duration::from_millis(200))
+                .with_max_output_bytes(4),
+        )
+    }
+
+    #[tokio::test]
+    async fn allowed_commands_run_in_the_jail() {
+        let root = tempfile::tempdir().unwrap();
+        std::fs::create_dir(root.path().join("sub")).unwrap();
+        let tool = tool(root.path());
+
+        let greeted = tool
+            .execute(json!({ "command": "greet", "args": { "name": "Ada" } }))
+            .await
+            .unwrap();
+        assert_eq!(greeted["exit_code"], 0);
+        assert_eq!(greeted["stdout"], "hell");
+        assert_eq!(greeted["stdout_truncated"], true);
+
+        let tool = ExecCommandTool::new(tool.policy().clone().with_max_output_bytes(1024));
+        let cwd = tool
+            .execute(json!({ "command": "where", "cwd": "sub" }))
+            .await
+            .unwrap();
+        let expected = std::fs::canonicalize(root.path().join("sub")).unwrap();
+        assert_eq!(
+            cwd["stdout"].as_str().unwrap().trim(),
+            expected.to_str().unwrap()
+        );
+
+        for (args, error) in [
+            (json!({ "command": "rm" }), "Forbidden"),
+            (
+                json!({ "command": "greet", "args": { "name": "-n" } }),
+                "InvalidArgument",
+            ),
+            (json!({ "command": "greet", "args": {} }), "InvalidArgument"),
+            (
+                json!({ "command": "greet", "args": { "name": "x", "extra": "y" } }),
+                "InvalidArgument",
+            ),
+            (
+                json!({ "command": "where", "cwd": "../" }),
+                "InvalidArgument",
+            ),
+            (json!({ "command": "wait" }), "ToolExecution"),
+        ] {
+            let failure = tool.execute(args.clone()).await.unwrap_err();
+            assert!(
+                format!("{:?}", failure).starts_with(error),
+                "{args}: {failure:?}"
+            );
+        }
+    }
+}
@@ -0,0 +1,441 @@
+//! File tools confined to a virtual root
+//!
+//! [`register_file_tools`] adds three tools to a registry:
+//!
+//! - `read_file` returns the text of a file
+//! - `write_file` writes, or appends to, a file, creating its directories
+//! - `list_dir` lists the entries of a directory
+//!
+//! Paths are relative to the [`FilePolicy`]'s root, which tools see as `/`;
+//! `..` and paths through symlinks are refused. A quota bounds the bytes
+//! stored under the root, and an extension list, when set, the files that
+//! can be read or written. Give each agent its own root for a private
+//! scratch workspace.
+
+use crate::tools::{BamlTool, ToolRegistry};
+use async_trait::async_trait;
+use baml_rt_core::{BamlRtError, Result};
+use serde_json::{Value, json};
+use std::collections::HashSet;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+/// Bytes stored under the root unless the policy says otherwise
+pub const DEFAULT_QUOTA_BYTES: u64 = 100 * 1024 * 1024;
+
+/// Register `read_file`, `write_file`, and `list_dir`, all confined by
+/// `policy`
+pub fn register_file_tools(registry: &mut ToolRegistry, policy: FilePolicy) -> Result<()> {
+    let policy = Arc::new(policy);
+    registry.register(ReadFileTool::new(policy.clone()))?;
+    registry.register(WriteFileTool::new(policy.clone()))?;
+    registry.register(ListDirTool::new(policy))
+}
+
+/// Where the file tools work, and how much they may store
+#[derive(Debug, Clone)]
+pub struct FilePolicy {
+    root: PathBuf,
+    extensions: Option<HashSet<String>>,
+    /// Bytes all files under the root may take together
+    pub quota_bytes: u64,
+    /// Held from a write's quota check until it is written, so concurrent
+    /// writes cannot both fit the same free space
+    writes: Arc<Mutex<()>>,
+}
+
+impl FilePolicy {
+    /// Work in `root`, created when first needed
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            root: root.into(),
+            extensions: None,
+            quota_bytes: DEFAULT_QUOTA_BYTES,
+            writes: Arc::new(Mutex::new(())),
+        }
+    }
+
+    pub fn with_quota_bytes(mut self, quota_bytes: u64) -> Self {
+        self.quota_bytes = quota_bytes;
+        self
+    }
+
+    /// Read and write only files with these extensions, given without the
+    /// dot; directories are not restricted
+    pub fn with_extensions<I, S>(mut self, extensions: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let extensions = extensions.into_iter().map(|extension| {
+            extension
+                .into()
+                .trim_start_matches('.')
+                .to_ascii_lowercase()
+        });
+        self.extensions = Some(extensions.collect());
+        self
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// The real root, created if missing
+    async fn real_root(&self) -> Result<PathBuf> {
+        tokio::fs::create_dir_all(&self.root).await?;
+        Ok(tokio::fs::canonicalize(&self.root).await?)
+    }
+
+    /// `path` under the root, refusing paths that lead out of it or through
+    /// a symlink
+    async fn resolve(&self, path: &str) -> Result<PathBuf> {
+        let relative = Path::new(path.trim_start_matches('/'));
+        let escapes = relative
+            .components()
+            .any(|component| !matches!(component, Component::Normal(_) | Component::CurDir));
+        if escapes {
+            return Err(BamlRtError::InvalidArgument(format!(
+                "Path must stay within the root: {}",
+                path
+            )));
+        }
+        // A symlink could lead anywhere, even a dangling one, which writing
+        // would create at its target, so none is followed
+        let mut resolved = self.real_root().await?;
+        for component in relative.components() {
+            let Component::Normal(name) = component else {
+                continue;
+            };
+            resolved.push(name);
+            match tokio::fs::symlink_metadata(&resolved).await {
+                Ok(metadata) if metadata.file_type().is_symlink() => {
+                    return Err(BamlRtError::InvalidArgument(format!(
+                        "Path goes through a symlink: {}",
+                        path
+                    )));
+                }
+                Ok(_) => {}
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
+        Ok(resolved)
+    }
+
+    /// Fail unless files like `path` may be read and written
+    fn check_extension(&self, path: &str) -> Result<()> {
+        let Some(extensions) = &self.extensions else {
+            return Ok(());
+        };
+        let extension = Path::new(path)
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(str::to_ascii_lowercase);
+        if extension.is_some_and(|extension| extensions.contains(&extension)) {
+            return Ok(());
+        }
+        let mut allowed: Vec<&str> = extensions.iter().map(String::as_str).collect();
+        allowed.sort_unstable();
+        Err(BamlRtError::InvalidArgument(format!(
+            "{} is not a file the tools may use; allowed extensions: {}",
+            path,
+            allowed.join(", ")
+        )))
+    }
+
+    /// Bytes stored under the root
+    async fn usage(&self) -> Result<u64> {
+        let mut total = 0;
+        let mut directories = vec![self.real_root().await?];
+        while let Some(directory) = directories.pop() {
+            let mut entries = tokio::fs::read_dir(&directory).await?;
+            while let Some(entry) = entries.next_entry().await? {
+                let metadata = tokio::fs::symlink_metadata(entry.path()).await?;
+                if metadata.is_dir() {
+                    directories.push(entry.path());
+                } else {
+                    total += metadata.len();
+                }
+            }
+        }
+        Ok(total)
+    }
+}
+
+fn string_arg<'a>(args: &'a Value, name: &str) -> Result<&'a str> {
+    args.get(name)
+        .and_then(Value::as_str)
+        .ok_or_else(|| BamlRtError::InvalidArgument(format!("'{}' must be a string", name)))
+}
+
+/// Reads files under the root
+pub struct ReadFileTool {
+    policy: Arc<FilePolicy>,
+}
+
+impl ReadFileTool {
+    pub fn new(policy: Arc<FilePolicy>) -> Self {
+        Self { policy }
+    }
+}
+
+#[async_trait]
+impl BamlTool for ReadFileTool {
+    const NAME: &'static str = "read_file";
+
+    fn description(&self) -> &'static str {
+        "Reads a text file from the workspace"
+    }
+
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "path": {
+                    "type": "string",
+                    "description": "Path of the file in the workspace"
+                }
+            },
+            "required": ["path"]
+        })
+    }
+
+    async fn execute(&self, args: Value) -> Result<Value> {
+        let path = string_arg(&args, "path")?;
+        self.policy.check_extension(path)?;
+        let bytes = tokio::fs::read(self.policy.resolve(path).await?).await?;
+        Ok(json!({
+            "path": path,
+            "size": bytes.len(),
+            "content": String::from_utf8_lossy(&bytes),
+        }))
+    }
+}
+
+/// Writes files under the root, within its quota
+pub struct WriteFileTool {
+    policy: Arc<FilePolicy>,
+}
+
+impl WriteFileTool {
+    pub fn new(policy: Arc<FilePolicy>) -> Self {
+        Self { policy }
+    }
+}
+
+#[async_trait]
+impl BamlTool for WriteFileTool {
+    const NAME: &'static str = "write_file";
+
+    fn description(&self) -> &'static str {
+        "Writes or appends text to a file in the workspace, creating its directories"
+    }
+
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "path": {
+                    "type": "string",
+                    "description": "Path of the file in the workspace"
+                },
+                "content": {
+                    "type": "string",
+                    "description": "Text to write"
+                },
+                "append": {
+                    "type": "boolean",
+                    "description": "Add to the end of the file instead of replacing it"
+                }
+            },
+            "required": ["path", "content"]
+        })
+    }
+
+    async fn execute(&self, args: Value) -> Result<Value> {
+        let path = string_arg(&args, "path")?;
+        let content = string_arg(&args, "content")?;
+        let append = args.get("append").and_then(Value::as_bool).unwrap_or(false);
+        self.policy.check_extension(path)?;
+        let _write = self.policy.writes.lock().await;
+        let resolved = self.policy.resolve(path).await?;
+
+        let existing = match tokio::fs::symlink_metadata(&resolved).await {
+            Ok(metadata) if metadata.is_dir() => {
+                return Err(BamlRtError::InvalidArgument(format!(
+                    "{} is a directory",
+                    path
+                )));
+            }
+            Ok(metadata) => metadata.len(),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => 0,
+            Err(e) => return Err(e.into()),
+        };
+        let replaced = if append { 0 } else { existing };
+        let usage = self
+            .policy
+            .usage()
+            .await?
+            .saturating_sub(replaced)
+            .saturating_add(content.len() as u64);
+        if usage > self.policy.quota_bytes {
+            return Err(BamlRtError::InvalidArgument(format!(
+                "Writing {} would store {} bytes in the workspace; the quota is {}",
+                path, usage, self.policy.quota_bytes
+            )));
+        }
+
+        if let Some(parent) = resolved.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        if append {
+            use tokio::io::AsyncWriteExt;
+            let mut file = tokio::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&resolved)
+                .await?;
+            file.write_all(content.as_bytes()).await?;
+            file.flush().await?;
+        } else {
+            tokio::fs::write(&resolved, content).await?;
+        }
+        let size = if append {
+            existing + content.len() as u64
+        } else {
+            content.len() as u64
+        };
+        Ok(json!({ "path": path, "size": size }))
+    }
+}
+
+/// Lists directories under the root
+pub struct ListDirTool {
+    policy: Arc<FilePolicy>,
+}
+
+impl ListDirTool {
+    pub fn new(policy: Arc<FilePolicy>) -> Self {
+        Self { policy }
+    }
+}
+
+#[async_trait]
+impl BamlTool for ListDirTool {
+    const NAME: &'static str = "list_dir";
+
+    fn description(&self) -> &'static str {
+        "Lists the files and directories in a workspace directory"
+    }
+
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "path": {
+                    "type": "string",
+                    "description": "Directory in the workspace, the workspace root by default"
+                }
+            }
+        })
+    }
+
+    async fn execute(&self, args: Value) -> Result<Value> {
+        let path = args.get("path").and_then(Value::as_str).unwrap_or("/");
+        let resolved = self.policy.resolve(path).await?;
+        let mut entries = Vec::new();
+        let mut listing = tokio::fs::read_dir(&resolved).await?;
+        while let Some(entry) = listing.next_entry().await? {
+            let metadata = tokio::fs::symlink_metadata(entry.path()).await?;
+            let kind = if metadata.is_dir() {
+                "directory"
+            } else if metadata.is_symlink() {
+                "symlink"
+            } else {
+                "file"
+            };
+            entries.push(json!({
+                "name": entry.file_name().to_string_lossy(),
+                "type": kind,
+                "size": metadata.len(),
+            }));
+        }
+        entries.sort_by(|a, b| a["name"].as_str().cmp(&b["name"].as_str()));
+        Ok(json!({ "path": path, "entries": entries }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn tools_stay_within_the_root_and_its_quota() {
+        let dir = tempfile::tempdir().unwrap();
+        let policy = Arc::new(
+            FilePolicy::new(dir.path().join("workspace"))
+                .with_quota_bytes(10)
+                .with_extensions(["txt", ".MD"]),
+        );
+        let read = ReadFileTool::new(policy.clone());
+        let write = WriteFileTool::new(policy.clone());
+        let list = ListDirTool::new(policy);
+
+        let written = write
+            .execute(json!({ "path": "/notes/a.txt", "content": "hello" }))
+            .await
+            .unwrap();
+        assert_eq!(written["size"], 5);
+        write
+            .execute(json!({ "path": "notes/a.txt", "content": "!", "append": true }))
+            .await
+            .unwrap();
+        let content = read
+            .execute(json!({ "path": "notes/a.txt" }))
+            .await
+            .unwrap();
+        assert_eq!(content["content"], "hello!");
+
+        let listed = list.execute(json!({})).await.unwrap();
+        assert_eq!(listed["entries"][0]["name"], "notes");
+        assert_eq!(listed["entries"][0]["type"], "directory");
+
+        // Replacing the file frees what it held
+        write
+            .execute(json!({ "path": "notes/a.txt", "content": "0123456789" }))
+            .await
+            .unwrap();
+        for args in [
+            json!({ "path": "b.md", "content": "x" }),
+            json!({ "path": "c.sh", "content": "" }),
+            json!({ "path": "../escape.txt", "content": "" }),
+        ] {
+            assert!(write.execute(args.clone()).await.is_err(), "{args}");
+        }
+
+        std::fs::write(dir.path().join("secret.txt"), "secret").unwrap();
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink(dir.path(), dir.path().join("workspace/out")).unwrap();
+            assert!(
+                read.execute(json!({ "path": "out/secret.txt" }))
+                    .await
+                    .is_err()
+            );
+
+            // A dangling link would be written through to its target
+            let outside = dir.path().join("created.txt");
+            std::os::unix::fs::symlink(&outside, dir.path().join("workspace/link.txt")).unwrap();
+            std::fs::write(dir.path().join("workspace/notes/a.txt"), "").unwrap();
+            assert!(
+                write
+                    .execute(json!({ "path": "link.txt", "content": "x" }))
+                    .await
+                    .is_err()
+            );
+            assert!(!outside.exists());
+        }
+    }
+}
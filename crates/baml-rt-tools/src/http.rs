@@ -0,0 +1,393 @@
+//! HTTP request tool
+//!
+//! [`HttpRequestTool`] registers as `http_request` and gives agents network
+//! access on the terms of an [`HttpPolicy`]: the hosts they may reach, how
+//! much of a response they may read, how many redirects are followed, and how
+//! long a request may take. Every redirect is checked against the policy like
+//! the request that led to it.
+
+use crate::tools::BamlTool;
+use async_trait::async_trait;
+use baml_rt_core::{BamlRtError, Result};
+use reqwest::header::{CONTENT_TYPE, HeaderMap};
+use reqwest::{Method, Url};
+use serde_json::{Map, Value, json};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Largest response body read unless the policy says otherwise
+pub const DEFAULT_MAX_RESPONSE_BYTES: usize = 1024 * 1024;
+
+/// Redirects followed unless the policy says otherwise
+pub const DEFAULT_MAX_REDIRECTS: usize = 5;
+
+/// How long a request may take unless the policy says otherwise
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// What `http_request` may reach and read
+///
+/// Hosts are named exactly (`api.example.com`), by subdomain
+/// (`*.example.com`, which does not match `example.com` itself), or `*` for
+/// any host. A new policy allows no host; a denied host is refused even
+/// when it is also allowed. Only `http` and `https` URLs are requested.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpPolicy {
+    allowed_hosts: Vec<String>,
+    denied_hosts: Vec<String>,
+    /// Largest response body read; larger responses fail the call
+    pub max_response_bytes: usize,
+    /// Redirects followed before the call fails; 0 follows none
+    pub max_redirects: usize,
+    /// Time allowed for the whole request, redirects included
+    pub timeout: Duration,
+}
+
+impl Default for HttpPolicy {
+    fn default() -> Self {
+        Self {
+            allowed_hosts: Vec::new(),
+            denied_hosts: Vec::new(),
+            max_response_bytes: DEFAULT_MAX_RESPONSE_BYTES,
+            max_redirects: DEFAULT_MAX_REDIRECTS,
+            timeout: DEFAULT_TIMEOUT,
+        }
+    }
+}
+
+impl HttpPolicy {
+    /// A policy that allows no host yet
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Allow requests to `hosts`
+    pub fn with_allowed_hosts<I, S>(mut self, hosts: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.allowed_hosts.extend(
+            hosts
+                .into_iter()
+                .map(|host| host.into().to_ascii_lowercase()),
+        );
+        self
+    }
+
+    /// Refuse requests to `hosts`, whatever else is allowed
+    pub fn with_denied_hosts<I, S>(mut self, hosts: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.denied_hosts.extend(
+            hosts
+                .into_iter()
+                .map(|host| host.into().to_ascii_lowercase()),
+        );
+        self
+    }
+
+    pub fn with_max_response_bytes(mut self, max_response_bytes: usize) -> Self {
+        self.max_response_bytes = max_response_bytes;
+        self
+    }
+
+    pub fn with_max_redirects(mut self, max_redirects: usize) -> Self {
+        self.max_redirects = max_redirects;
+        self
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Whether the policy lets `url` be requested
+    pub fn allows(&self, url: &Url) -> bool {
+        let Some(host) = url.host_str().map(str::to_ascii_lowercase) else {
+            return false;
+        };
+        matches!(url.scheme(), "http" | "https")
+            && !self.denied_hosts.iter().any(|p| host_matches(p, &host))
+            && self.allowed_hosts.iter().any(|p| host_matches(p, &host))
+    }
+
+    /// Fail with [`BamlRtError::Forbidden`] unless the policy allows `url`
+    pub fn check(&self, url: &Url) -> Result<()> {
+        if self.allows(url) {
+            return Ok(());
+        }
+        Err(BamlRtError::Forbidden {
+            principal: HttpRequestTool::NAME.to_string(),
+            action: format!("request {}", url),
+        })
+    }
+}
+
+fn host_matches(pattern: &str, host: &str) -> bool {
+    match pattern.strip_prefix("*.") {
+        Some(domain) => host
+            .strip_suffix(domain)
+            .is_some_and(|subdomain| subdomain.len() > 1 && subdomain.ends_with('.')),
+        None => pattern == "*" || pattern == host,
+    }
+}
+
+/// Makes HTTP requests allowed by an [`HttpPolicy`]
+pub struct HttpRequestTool {
+    client: reqwest::Client,
+    policy: Arc<HttpPolicy>,
+}
+
+impl HttpRequestTool {
+    pub fn new(policy: HttpPolicy) -> Result<Self> {
+        let policy = Arc::new(policy);
+        let redirects = policy.clone();
+        let client = reqwest::Client::builder()
+            .timeout(policy.timeout)
+            .redirect(reqwest::redirect::Policy::custom(move |attempt| {
+                if attempt.previous().len() > redirects.max_redirects {
+                    let error = format!("more than {} redirects", redirects.max_redirects);
+                    attempt.error(error)
+                } else if let Err(e) = redirects.check(attempt.url()) {
+                    attempt.error(e.to_string())
+                } else {
+                    attempt.follow()
+                }
+            }))
+            .build()
+            .map_err(|e| {
+                BamlRtError::Configuration(format!("Failed to build HTTP client: {}", e))
+            })?;
+        Ok(Self { client, policy })
+    }
+
+    pub fn policy(&self) -> &HttpPolicy {
+        &self.policy
+    }
+
+    /// The body of `response`, failing once it outgrows the policy
+    async fn read_body(&self, url: &Url, mut response: reqwest::Response) -> Result<Vec<u8>> {
+        let max = self.policy.max_response_bytes;
+        let too_large = || {
+            BamlRtError::ToolExecution(format!(
+                "Response from {} is larger than {} bytes",
+                url, max
+            ))
+        };
+        if response
+            .content_length()
+            .is_some_and(|length| length > max as u64)
+        {
+            return Err(too_large());
+        }
+        let mut body = Vec::new();
+        while let Some(chunk) = response.chunk().await.map_err(|e| failed(url, e))? {
+            if body.len() + chunk.len() > max {
+                return Err(too_large());
+            }
+            body.extend_from_slice(&chunk);
+        }
+        Ok(body)
+    }
+}
+
+fn failed(url: &Url, error: reqwest::Error) -> BamlRtError {
+    BamlRtError::ToolExecution(format!("HTTP request to {} failed: {}", url, error))
+}
+
+fn headers_value(headers: &HeaderMap) -> Value {
+    let mut map = Map::new();
+    for (name, value) in headers {
+        let value = String::from_utf8_lossy(value.as_bytes()).into_owned();
+        match map.get_mut(name.as_str()) {
+            // Repeated headers are joined, as a client reading them would
+            Some(Value::String(joined)) => {
+                joined.push_str(", ");
+                joined.push_str(&value);
+            }
+            _ => {
+                map.insert(name.as_str().to_string(), Value::String(value));
+            }
+        }
+    }
+    Value::Object(map)
+}
+
+#[async_trait]
+impl BamlTool for HttpRequestTool {
+    const NAME: &'static str = "http_request";
+
+    fn description(&self) -> &'static str {
+        "Makes an HTTP request and returns the response status, headers, and body"
+    }
+
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "method": {
+                    "type": "string",
+                    "description": "HTTP method, GET by default"
+                },
+                "url": {
+                    "type": "string",
+                    "description": "URL to request"
+                },
+                "headers": {
+                    "type": "object",
+                    "additionalProperties": { "type": "string" },
+                    "description": "Request headers"
+                },
+                "body": {
+                    "description": "Request body; anything but a string is sent as JSON"
+                }
+            },
+            "required": ["url"]
+        })
+    }
+
+    async fn execute(&self, args: Value) -> Result<Value> {
+        let method = args.get("method").and_then(Value::as_str).unwrap_or("GET");
+        let method = Method::from_bytes(method.to_ascii_uppercase().as_bytes()).map_err(|_| {
+            BamlRtError::InvalidArgument(format!("'{}' is not an HTTP method", method))
+        })?;
+        let url = args
+            .get("url")
+            .and_then(Value::as_str)
+            .ok_or_else(|| BamlRtError::InvalidArgument("'url' must be a string".to_string()))?;
+        let url = Url::parse(url)
+            .map_err(|e| BamlRtError::InvalidArgument(format!("Invalid URL {}: {}", url, e)))?;
+        self.policy.check(&url)?;
+
+        let mut request = self.client.request(method, url.clone());
+        let mut has_content_type = false;
+        match args.get("headers") {
+            None | Some(Value::Null) => {}
+            Some(Value::Object(headers)) => {
+                for (name, value) in headers {
+                    let value = value.as_str().ok_or_else(|| {
+                        BamlRtError::InvalidArgument(format!("Header '{}' must be a string", name))
+                    })?;
+                    has_content_type |= name.eq_ignore_ascii_case(CONTENT_TYPE.as_str());
+                    request = request.header(name.as_str(), value);
+                }
+            }
+            Some(_) => {
+                return Err(BamlRtError::InvalidArgument(
+                    "'headers' must be an object of strings".to_string(),
+                ));
+            }
+        }
+        match args.get("body") {
+            None | Some(Value::Null) => {}
+            Some(Value::String(body)) => request = request.body(body.clone()),
+            Some(body) => {
+                if !has_content_type {
+                    request = request.header(CONTENT_TYPE, "application/json");
+                }
+                request = request.body(body.to_string());
+            }
+        }
+
+        let response = request.send().await.map_err(|e| failed(&url, e))?;
+        let status = response.status().as_u16();
+        let final_url = response.url().to_string();
+        let headers = headers_value(response.headers());
+        let body = self.read_body(&url, response).await?;
+        Ok(json!({
+            "status": status,
+            "url": final_url,
+            "headers": headers,
+            "body": String::from_utf8_lossy(&body),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// Serves `/hello`, a redirect to it from `/moved`, and 2 KiB at `/large`
+    async fn serve() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let mut request = [0u8; 4096];
+                let read = stream.read(&mut request).await.unwrap_or(0);
+                let request = String::from_utf8_lossy(&request[..read]);
+                let path = request.split_whitespace().nth(1).unwrap_or("/");
+                let response = match path {
+                    "/moved" => "HTTP/1.1 302 Found\r\nLocation: /hello\r\n\
+                                 Content-Length: 0\r\nConnection: close\r\n\r\n"
+                        .to_string(),
+                    "/large" => format!(
+                        "HTTP/1.1 200 OK\r\nConnection: close\r\n\r\n{}",
+                        "x".repeat(2048)
+                    ),
+                    _ => "HTTP/1.1 200 OK\r\nContent-Type: text/plain\r\n\
+                          Content-Length: 2\r\nConnection: close\r\n\r\nhi"
+                        .to_string(),
+                };
+                let _ = stream.write_all(response.as_bytes()).await;
+            }
+        });
+        format!("http://{}", address)
+    }
+
+    #[test]
+    fn hosts_are_allowed_by_name_or_subdomain_unless_denied() {
+        let policy = HttpPolicy::new()
+            .with_allowed_hosts(["api.example.com", "*.github.com"])
+            .with_denied_hosts(["gist.github.com"]);
+        let allows = |url: &str| policy.allows(&Url::parse(url).unwrap());
+        assert!(allows("https://API.example.com/v1"));
+        assert!(allows("https://raw.github.com/x"));
+        assert!(!allows("https://github.com/x"));
+        assert!(!allows("https://gist.github.com/x"));
+        assert!(!allows("https://example.com/"));
+        assert!(!allows("ftp://api.example.com/"));
+        assert!(!HttpPolicy::new().allows(&Url::parse("https://example.com").unwrap()));
+    }
+
+    #[tokio::test]
+    async fn requests_follow_the_policy() {
+        let base = serve().await;
+        let tool = HttpRequestTool::new(
+            HttpPolicy::new()
+                .with_allowed_hosts(["127.0.0.1"])
+                .with_max_response_bytes(1024),
+        )
+        .unwrap();
+
+        let response = tool
+            .execute(json!({ "url": format!("{}/moved", base) }))
+            .await
+            .unwrap();
+        assert_eq!(response["status"], 200);
+        assert_eq!(response["body"], "hi");
+        assert_eq!(response["url"], format!("{}/hello", base));
+        assert_eq!(response["headers"]["content-type"], "text/plain");
+
+        let large = tool
+            .execute(json!({ "url": format!("{}/large", base) }))
+            .await;
+        assert!(matches!(large, Err(BamlRtError::ToolExecution(_))));
+
+        let denied = tool
+            .execute(json!({ "url": "http://localhost/hello" }))
+            .await;
+        assert!(matches!(denied, Err(BamlRtError::Forbidden { .. })));
+
+        let no_redirects =
+            HttpRequestTool::new(tool.policy().clone().with_max_redirects(0)).unwrap();
+        let moved = no_redirects
+            .execute(json!({ "method": "post", "url": format!("{}/moved", base), "body": {} }))
+            .await;
+        assert!(moved.is_err());
+    }
+}
@@ -1,7 +1,32 @@
 //! Tool registry and mapping utilities.
 
+pub mod approval;
+#[cfg(feature = "documents")]
+pub mod documents;
+#[cfg(feature = "exec")]
+pub mod exec;
+#[cfg(feature = "files")]
+pub mod files;
+#[cfg(feature = "http")]
+pub mod http;
+pub mod policy;
 pub mod tool_mapper;
 pub mod tools;
 
+pub use approval::{
+    ApprovalDecision, ApprovalGate, ApprovalRecorder, ProposedToolCall, ToolApprover,
+};
+#[cfg(feature = "documents")]
+pub use documents::register_document_tools;
+#[cfg(feature = "exec")]
+pub use exec::{ExecCommandTool, ExecPolicy};
+#[cfg(feature = "files")]
+pub use files::{FilePolicy, register_file_tools};
+#[cfg(feature = "http")]
+pub use http::{HttpPolicy, HttpRequestTool};
+pub use policy::ToolPolicy;
 pub use tool_mapper::ToolMapper;
-pub use tools::{BamlTool, ToolExecutor, ToolMetadata, ToolRegistry};
+pub use tools::{
+    BamlTool, CollisionPolicy, JS_NAMESPACE, MCP_NAMESPACE, RUST_NAMESPACE, ToolExecutor,
+    ToolMetadata, ToolRegistry, qualified_tool_name,
+};
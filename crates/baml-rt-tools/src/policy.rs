@@ -0,0 +1,100 @@
+//! Tool usage policy - which tools each BAML function may trigger
+//!
+//! A model can name any tool variant in its output, including ones the
+//! function was never meant to reach. A [`ToolPolicy`] lists the tools each
+//! function may call, so a tool call attributed to `Summarize` cannot reach
+//! `send_payment` whatever the model returned.
+
+use crate::tools::qualified_tool_name;
+use baml_rt_core::{BamlRtError, Result};
+use std::collections::{HashMap, HashSet};
+
+/// Tools each BAML function may call
+///
+/// Tools are named bare (`search`), which allows the tool in any namespace,
+/// or qualified (`rust:search`). Functions the policy does not list, and
+/// tool calls not attributed to a function, may call any tool unless
+/// [`with_default`](Self::with_default) restricts them too.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ToolPolicy {
+    functions: HashMap<String, HashSet<String>>,
+    default: Option<HashSet<String>>,
+}
+
+impl ToolPolicy {
+    /// A policy that allows every tool
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Allow `function` to call `tools`, and no others
+    pub fn with_function<I, S>(mut self, function: impl Into<String>, tools: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.functions
+            .insert(function.into(), tools.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Allow unlisted functions and unattributed calls only `tools`
+    ///
+    /// An empty list refuses every tool call the policy does not attribute
+    /// to a listed function.
+    pub fn with_default<I, S>(mut self, tools: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.default = Some(tools.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Whether a call made on behalf of `function` may reach the tool named
+    /// `name` in `namespace`
+    pub fn allows(&self, function: Option<&str>, namespace: &str, name: &str) -> bool {
+        let allowed = function
+            .and_then(|function| self.functions.get(function))
+            .or(self.default.as_ref());
+        allowed.is_none_or(|tools| {
+            tools.contains(name) || tools.contains(&qualified_tool_name(namespace, name))
+        })
+    }
+
+    /// Fail with [`BamlRtError::Forbidden`] unless the policy allows the call
+    pub fn check(&self, function: Option<&str>, namespace: &str, name: &str) -> Result<()> {
+        if self.allows(function, namespace, name) {
+            return Ok(());
+        }
+        Err(BamlRtError::Forbidden {
+            principal: function.map_or_else(
+                || "unattributed tool call".to_string(),
+                |function| format!("function {}", function),
+            ),
+            action: format!("execute tool '{}'", qualified_tool_name(namespace, name)),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn functions_only_reach_their_tools() {
+        let policy = ToolPolicy::new()
+            .with_function("Summarize", ["search", "js:format"])
+            .with_default(["search"]);
+        assert!(policy.allows(Some("Summarize"), "mcp", "search"));
+        assert!(policy.allows(Some("Summarize"), "js", "format"));
+        assert!(!policy.allows(Some("Summarize"), "rust", "format"));
+        assert!(!policy.allows(Some("Summarize"), "rust", "send_payment"));
+        assert!(policy.allows(Some("Plan"), "rust", "search"));
+        assert!(policy.check(None, "rust", "send_payment").is_err());
+
+        let open = ToolPolicy::new().with_function("Summarize", ["search"]);
+        assert!(open.allows(None, "rust", "send_payment"));
+        assert!(open.allows(Some("Plan"), "rust", "send_payment"));
+    }
+}
@@ -147,7 +147,7 @@ impl ToolMapper {
     /// Extract an explicit tool call from a BAML result if present.
     ///
     /// This only triggers when the result explicitly identifies a mapped tool:
-    /// - Nested object form: { "WeatherTool": { ... } }
+    /// - Nested object form: { "WeatherTool": { ... } }, ignoring `null` variants
     /// - Explicit __type field: { "__type": "WeatherTool", ... }
     pub fn extract_explicit_tool_call(
         &self,
@@ -158,24 +158,22 @@ impl ToolMapper {
             None => return Ok(None),
         };
 
-        if obj.len() == 1 {
-            let (key, value) = obj.iter().next().ok_or_else(|| {
-                BamlRtError::InvalidArgument("Expected non-empty tool object".to_string())
+        // Empty variants are left out, so a class with one optional property
+        // per tool reads like the nested form
+        let mut chosen = obj.iter().filter(|(_, value)| !value.is_null());
+        if let (Some((key, value)), None) = (chosen.next(), chosen.next())
+            && self.variant_to_tool.contains_key(key)
+        {
+            let tool_obj = value.as_object().ok_or_else(|| {
+                BamlRtError::InvalidArgument("Expected tool payload to be an object".to_string())
             })?;
-            if self.variant_to_tool.contains_key(key) {
-                let tool_obj = value.as_object().ok_or_else(|| {
-                    BamlRtError::InvalidArgument(
-                        "Expected tool payload to be an object".to_string(),
-                    )
-                })?;
-                let tool_name = self.variant_to_tool_name(key)?;
-                let tool_args = tool_obj
-                    .iter()
-                    .filter(|(k, _)| k.as_str() != "__type")
-                    .map(|(k, v)| (k.clone(), v.clone()))
-                    .collect();
-                return Ok(Some((tool_name, Value::Object(tool_args))));
-            }
+            let tool_name = self.variant_to_tool_name(key)?;
+            let tool_args = tool_obj
+                .iter()
+                .filter(|(k, _)| k.as_str() != "__type")
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect();
+            return Ok(Some((tool_name, Value::Object(tool_args))));
         }
 
         if let Some(variant) = obj.get("__type").and_then(|v| v.as_str())
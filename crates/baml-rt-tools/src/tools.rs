@@ -2,9 +2,17 @@
 //!
 //! This module provides a trait-based system for registering tool functions
 //! that can be called by LLMs during BAML function execution or directly from JavaScript.
+//!
+//! Every tool lives in a namespace, named by where it is implemented: Rust
+//! tools in `rust`, JavaScript tools in `js`, and tools of other hosts in a
+//! namespace of their own, such as `mcp` for `mcp:server/tool`. A tool can
+//! always be called by its qualified `namespace:name`; calls by the bare
+//! name keep working, and when several namespaces hold the same name the
+//! registry's [`CollisionPolicy`] decides which one answers.
 
 use async_trait::async_trait;
 use baml_rt_core::{BamlRtError, Result};
+use baml_rt_core::{cancellation, deadline};
 use serde_json::Value;
 use std::collections::HashMap;
 use std::sync::Arc;
@@ -60,6 +68,11 @@ pub trait BamlTool: Send + Sync + 'static {
     /// JSON schema describing the tool's input parameters
     fn input_schema(&self) -> Value;
 
+    /// Whether a person must approve each call before it runs
+    fn requires_approval(&self) -> bool {
+        false
+    }
+
     /// Execute the tool with the given arguments
     ///
     /// # Arguments
@@ -79,11 +92,48 @@ pub struct ToolMetadata {
     pub description: String,
     /// JSON schema for the tool's input parameters
     pub input_schema: Value,
+    /// Calls wait for approval before they run
+    pub requires_approval: bool,
+}
+
+/// Namespace of tools implemented in Rust
+pub const RUST_NAMESPACE: &str = "rust";
+
+/// Namespace of tools implemented in JavaScript
+pub const JS_NAMESPACE: &str = "js";
+
+/// Namespace of tools served by MCP servers, named `server/tool`
+pub const MCP_NAMESPACE: &str = "mcp";
+
+/// The qualified name of tool `name` in `namespace`
+pub fn qualified_tool_name(namespace: &str, name: &str) -> String {
+    format!("{}:{}", namespace, name)
+}
+
+/// What happens when a tool's bare name is already taken in another
+/// namespace
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CollisionPolicy {
+    /// Refuse to register the second tool, as if the registry were flat
+    #[default]
+    Reject,
+    /// Register both; the bare name calls the first one registered
+    FirstWins,
+    /// Register both; the bare name calls the last one registered
+    LastWins,
+    /// Register both; the bare name is ambiguous and calls fail until
+    /// they use a qualified name
+    RequireQualified,
 }
 
 /// Registry for dynamically registered tool functions
 pub struct ToolRegistry {
+    /// Tools by qualified name
     tools: HashMap<String, (ToolMetadata, Arc<dyn ToolExecutor>)>,
+    /// Qualified names of the tools with each bare name, in registration
+    /// order
+    by_name: HashMap<String, Vec<String>>,
+    collisions: CollisionPolicy,
 }
 
 /// Internal trait for executing tools (bridges trait objects to async trait)
@@ -109,10 +159,27 @@ impl ToolRegistry {
     pub fn new() -> Self {
         Self {
             tools: HashMap::new(),
+            by_name: HashMap::new(),
+            collisions: CollisionPolicy::default(),
         }
     }
 
-    /// Register a tool that implements the BamlTool trait
+    pub fn with_collision_policy(mut self, policy: CollisionPolicy) -> Self {
+        self.collisions = policy;
+        self
+    }
+
+    /// Decide how tools registered from now on share bare names
+    pub fn set_collision_policy(&mut self, policy: CollisionPolicy) {
+        self.collisions = policy;
+    }
+
+    pub fn collision_policy(&self) -> CollisionPolicy {
+        self.collisions
+    }
+
+    /// Register a tool that implements the BamlTool trait, in the `rust`
+    /// namespace
     ///
     /// # Arguments
     /// * `tool` - An instance of a type implementing `BamlTool`
@@ -140,24 +207,19 @@ impl ToolRegistry {
     /// ```
     pub fn register<T: BamlTool>(&mut self, tool: T) -> Result<()> {
         let name = T::NAME.to_string();
-
-        if self.tools.contains_key(&name) {
-            return Err(BamlRtError::InvalidArgument(format!(
-                "Tool '{}' is already registered",
-                name
-            )));
-        }
+        self.check_name(RUST_NAMESPACE, &name)?;
 
         let description_str = tool.description().to_string();
         let metadata = ToolMetadata {
             name: name.clone(),
             description: description_str.clone(),
             input_schema: tool.input_schema(),
+            requires_approval: tool.requires_approval(),
         };
 
         let tool_executor: Arc<dyn ToolExecutor> = Arc::new(ToolWrapper { tool });
 
-        self.tools.insert(name.clone(), (metadata, tool_executor));
+        self.insert(RUST_NAMESPACE, metadata, tool_executor);
 
         tracing::info!(
             tool = name.as_str(),
@@ -168,38 +230,174 @@ impl ToolRegistry {
         Ok(())
     }
 
-    /// Register a tool with dynamic metadata and executor.
+    /// Register a tool with dynamic metadata and executor, in the `rust`
+    /// namespace.
     pub fn register_dynamic(
         &mut self,
         metadata: ToolMetadata,
         executor: Arc<dyn ToolExecutor>,
     ) -> Result<()> {
-        if self.tools.contains_key(&metadata.name) {
-            return Err(BamlRtError::InvalidArgument(format!(
-                "Tool '{}' is already registered",
-                metadata.name
-            )));
-        }
+        self.register_in(RUST_NAMESPACE, metadata, executor)
+    }
+
+    /// Register a tool with dynamic metadata and executor in `namespace`
+    pub fn register_in(
+        &mut self,
+        namespace: &str,
+        metadata: ToolMetadata,
+        executor: Arc<dyn ToolExecutor>,
+    ) -> Result<()> {
+        self.check_name(namespace, &metadata.name)?;
 
         tracing::info!(
             tool = metadata.name.as_str(),
+            namespace,
             description = metadata.description.as_str(),
             "Registered dynamic tool function"
         );
 
-        self.tools
-            .insert(metadata.name.clone(), (metadata, executor));
+        self.insert(namespace, metadata, executor);
+
+        Ok(())
+    }
+
+    /// Register a tool with dynamic metadata and executor in the `rust`
+    /// namespace, replacing a tool of the same name there
+    ///
+    /// Returns the metadata of the tool it replaced.
+    pub fn replace(
+        &mut self,
+        metadata: ToolMetadata,
+        executor: Arc<dyn ToolExecutor>,
+    ) -> Result<Option<ToolMetadata>> {
+        self.replace_in(RUST_NAMESPACE, metadata, executor)
+    }
 
+    /// Register a tool with dynamic metadata and executor in `namespace`,
+    /// replacing a tool of the same name there
+    ///
+    /// A replaced tool keeps its place among tools sharing its bare name, so
+    /// which one a bare name calls does not change. Calls already running
+    /// finish on the old executor.
+    pub fn replace_in(
+        &mut self,
+        namespace: &str,
+        metadata: ToolMetadata,
+        executor: Arc<dyn ToolExecutor>,
+    ) -> Result<Option<ToolMetadata>> {
+        let qualified = qualified_tool_name(namespace, &metadata.name);
+        let Some(entry) = self.tools.get_mut(&qualified) else {
+            self.register_in(namespace, metadata, executor)?;
+            return Ok(None);
+        };
+        tracing::info!(
+            tool = metadata.name.as_str(),
+            namespace,
+            description = metadata.description.as_str(),
+            "Replaced dynamic tool function"
+        );
+        let (previous, _) = std::mem::replace(entry, (metadata, executor));
+        Ok(Some(previous))
+    }
+
+    /// Remove the tool `name` resolves to, returning its metadata
+    ///
+    /// A bare name removes the tool the collision policy would call; use a
+    /// qualified name to pick one of several.
+    pub fn unregister(&mut self, name: &str) -> Result<ToolMetadata> {
+        let qualified = self.resolve(name)?;
+        let (metadata, _) = self
+            .tools
+            .remove(&qualified)
+            .ok_or_else(|| BamlRtError::FunctionNotFound(format!("Tool '{}' not found", name)))?;
+        if let Some(candidates) = self.by_name.get_mut(&metadata.name) {
+            candidates.retain(|candidate| *candidate != qualified);
+            if candidates.is_empty() {
+                self.by_name.remove(&metadata.name);
+            }
+        }
+        tracing::info!(tool = qualified.as_str(), "Unregistered tool function");
+        Ok(metadata)
+    }
+
+    /// The qualified name of the tool a registration of `name` in
+    /// `namespace` would collide with, when the collision policy refuses it
+    pub fn collision(&self, namespace: &str, name: &str) -> Option<String> {
+        if self.collisions != CollisionPolicy::Reject {
+            return None;
+        }
+        self.by_name
+            .get(name)?
+            .iter()
+            .find(|qualified| **qualified != qualified_tool_name(namespace, name))
+            .cloned()
+    }
+
+    fn check_name(&self, namespace: &str, name: &str) -> Result<()> {
+        let qualified = qualified_tool_name(namespace, name);
+        if self.tools.contains_key(&qualified) {
+            return Err(BamlRtError::InvalidArgument(format!(
+                "Tool '{}' is already registered",
+                qualified
+            )));
+        }
+        if let Some(existing) = self.collision(namespace, name) {
+            return Err(BamlRtError::InvalidArgument(format!(
+                "Tool '{}' is already registered as '{}'",
+                name, existing
+            )));
+        }
         Ok(())
     }
 
+    fn insert(&mut self, namespace: &str, metadata: ToolMetadata, executor: Arc<dyn ToolExecutor>) {
+        let qualified = qualified_tool_name(namespace, &metadata.name);
+        self.by_name
+            .entry(metadata.name.clone())
+            .or_default()
+            .push(qualified.clone());
+        self.tools.insert(qualified, (metadata, executor));
+    }
+
+    /// The qualified name a call to `name` reaches: `name` itself when it
+    /// is qualified, or the tool with that bare name the collision policy
+    /// picks
+    pub fn resolve(&self, name: &str) -> Result<String> {
+        if self.tools.contains_key(name) {
+            return Ok(name.to_string());
+        }
+        let not_found = || BamlRtError::FunctionNotFound(format!("Tool '{}' not found", name));
+        let candidates = self.by_name.get(name).ok_or_else(not_found)?;
+        let resolved = match (candidates.as_slice(), self.collisions) {
+            ([only], _) => only,
+            (_, CollisionPolicy::Reject | CollisionPolicy::FirstWins) => {
+                candidates.first().ok_or_else(not_found)?
+            }
+            (_, CollisionPolicy::LastWins) => candidates.last().ok_or_else(not_found)?,
+            (_, CollisionPolicy::RequireQualified) => {
+                return Err(BamlRtError::InvalidArgument(format!(
+                    "Tool name '{}' is ambiguous; call one of {}",
+                    name,
+                    candidates.join(", ")
+                )));
+            }
+        };
+        Ok(resolved.clone())
+    }
+
     /// Get tool metadata by name
     pub fn get_metadata(&self, name: &str) -> Option<&ToolMetadata> {
-        self.tools.get(name).map(|(metadata, _)| metadata)
+        let qualified = self.resolve(name).ok()?;
+        self.tools.get(&qualified).map(|(metadata, _)| metadata)
     }
 
-    /// List all registered tool names
+    /// List all registered tool names, unqualified
     pub fn list_tools(&self) -> Vec<String> {
+        self.by_name.keys().cloned().collect()
+    }
+
+    /// List the qualified names of all registered tools
+    pub fn list_qualified_tools(&self) -> Vec<String> {
         self.tools.keys().cloned().collect()
     }
 
@@ -208,12 +406,20 @@ impl ToolRegistry {
         self.tools.values().map(|(metadata, _)| metadata).collect()
     }
 
+    /// Get the executor of a tool by name
+    ///
+    /// Lets callers release the registry before running a slow tool.
+    pub fn executor(&self, name: &str) -> Result<Arc<dyn ToolExecutor>> {
+        let qualified = self.resolve(name)?;
+        self.tools
+            .get(&qualified)
+            .map(|(_, tool_executor)| tool_executor.clone())
+            .ok_or_else(|| BamlRtError::FunctionNotFound(format!("Tool '{}' not found", name)))
+    }
+
     /// Execute a tool function by name
     pub async fn execute(&self, name: &str, args: Value) -> Result<Value> {
-        let (_, tool_executor) = self
-            .tools
-            .get(name)
-            .ok_or_else(|| BamlRtError::FunctionNotFound(format!("Tool '{}' not found", name)))?;
+        let tool_executor = self.executor(name)?;
 
         tracing::debug!(
             tool = name,
@@ -221,7 +427,9 @@ impl ToolRegistry {
             "Executing tool function"
         );
 
-        tool_executor.execute(args).await
+        let operation = format!("tool '{}'", name);
+        let call = deadline::enforce(&operation, tool_executor.execute(args));
+        cancellation::enforce(&operation, call).await
     }
 }
 
@@ -1,8 +1,16 @@
 //! Integration tests for direct BAML tool execution (Rust + JS tools).
 
-use baml_rt::A2aAgent;
-use serde_json::json;
+use async_trait::async_trait;
+use baml_rt::baml::BamlRuntimeManager;
+use baml_rt::deadline::{self, Deadline};
+use baml_rt::tool_policy::ToolPolicy;
+use baml_rt::tools::BamlTool;
+use baml_rt::{A2aAgent, BamlRtError, Bytes};
+use serde_json::{Value, json};
+use std::sync::Arc;
+use std::time::Duration;
 use test_support::support;
+use tokio::sync::Notify;
 
 #[tokio::test]
 async fn test_direct_tool_execution_rust_and_js() {
@@ -10,7 +18,7 @@ async fn test_direct_tool_execution_rust_and_js() {
 
     {
         let runtime = agent.runtime();
-        let mut runtime = runtime.lock().await;
+        let runtime = runtime.read().await;
         runtime
             .register_tool(support::tools::CalculatorTool)
             .await
@@ -19,7 +27,7 @@ async fn test_direct_tool_execution_rust_and_js() {
 
     let rust_result = {
         let runtime = agent.runtime();
-        let runtime = runtime.lock().await;
+        let runtime = runtime.read().await;
         runtime
             .execute_tool(
                 "calculate",
@@ -53,7 +61,7 @@ async fn test_direct_tool_execution_rust_and_js() {
 
     let js_result = {
         let runtime = agent.runtime();
-        let runtime = runtime.lock().await;
+        let runtime = runtime.read().await;
         runtime
             .execute_tool("add_js", json!({"a": 10, "b": 5}))
             .await
@@ -62,3 +70,314 @@ async fn test_direct_tool_execution_rust_and_js() {
 
     assert_eq!(js_result.get("sum").and_then(|v| v.as_i64()), Some(15));
 }
+
+/// Waits until `SignalTool` runs
+struct WaitTool(Arc<Notify>);
+
+#[async_trait]
+impl BamlTool for WaitTool {
+    const NAME: &'static str = "wait_for_signal";
+
+    fn description(&self) -> &'static str {
+        "Waits for a signal"
+    }
+
+    fn input_schema(&self) -> Value {
+        json!({ "type": "object", "properties": {} })
+    }
+
+    async fn execute(&self, _args: Value) -> baml_rt::Result<Value> {
+        self.0.notified().await;
+        Ok(json!({ "signalled": true }))
+    }
+}
+
+struct SignalTool(Arc<Notify>);
+
+#[async_trait]
+impl BamlTool for SignalTool {
+    const NAME: &'static str = "signal";
+
+    fn description(&self) -> &'static str {
+        "Sends the signal"
+    }
+
+    fn input_schema(&self) -> Value {
+        json!({ "type": "object", "properties": {} })
+    }
+
+    async fn execute(&self, _args: Value) -> baml_rt::Result<Value> {
+        self.0.notify_one();
+        Ok(json!({ "sent": true }))
+    }
+}
+
+#[tokio::test]
+async fn test_tool_calls_on_a_shared_runtime_run_concurrently() {
+    let agent = A2aAgent::builder().build().await.expect("agent build");
+    let runtime = agent.runtime();
+    let signal = Arc::new(Notify::new());
+    {
+        let runtime = runtime.read().await;
+        runtime
+            .register_tool(WaitTool(signal.clone()))
+            .await
+            .expect("register wait tool");
+        runtime
+            .register_tool(SignalTool(signal))
+            .await
+            .expect("register signal tool");
+    }
+
+    // The waiting call only finishes if the signalling call runs while it is
+    // still in flight
+    let (waited, sent) = tokio::time::timeout(Duration::from_secs(5), async {
+        let waiting = async {
+            runtime
+                .read()
+                .await
+                .execute_tool("wait_for_signal", json!({}))
+                .await
+        };
+        let signalling = async { runtime.read().await.execute_tool("signal", json!({})).await };
+        tokio::join!(waiting, signalling)
+    })
+    .await
+    .expect("tool calls should not serialize on the runtime");
+
+    assert_eq!(waited.expect("wait tool")["signalled"], json!(true));
+    assert_eq!(sent.expect("signal tool")["sent"], json!(true));
+}
+
+#[tokio::test]
+async fn test_tool_calls_give_up_at_the_request_deadline() {
+    let agent = A2aAgent::builder().build().await.expect("agent build");
+    let runtime = agent.runtime();
+    runtime
+        .read()
+        .await
+        .register_tool(WaitTool(Arc::new(Notify::new())))
+        .await
+        .expect("register wait tool");
+
+    let outcome = deadline::with_deadline(
+        Deadline::after(Duration::from_millis(50)),
+        "request",
+        async {
+            runtime
+                .read()
+                .await
+                .execute_tool("wait_for_signal", json!({}))
+                .await
+        },
+    )
+    .await;
+
+    assert!(matches!(
+        outcome,
+        Err(BamlRtError::DeadlineExceeded { operation }) if operation == "tool 'wait_for_signal'"
+    ));
+}
+
+/// Returns the args it was called with
+struct EchoArgsTool;
+
+#[async_trait]
+impl BamlTool for EchoArgsTool {
+    const NAME: &'static str = "echo_args";
+
+    fn description(&self) -> &'static str {
+        "Echoes its args"
+    }
+
+    fn input_schema(&self) -> Value {
+        json!({ "type": "object" })
+    }
+
+    async fn execute(&self, args: Value) -> baml_rt::Result<Value> {
+        Ok(args)
+    }
+}
+
+#[tokio::test]
+async fn test_js_passes_native_objects_to_rust_tools() {
+    let runtime = BamlRuntimeManager::new().expect("runtime");
+    runtime
+        .register_tool(EchoArgsTool)
+        .await
+        .expect("register echo tool");
+    let agent = A2aAgent::builder()
+        .with_runtime_manager(runtime)
+        .with_init_js(
+            r#"
+            globalThis.echoBoth = async function() {
+                const args = {
+                    query: "rust",
+                    limit: 2 ** 40,
+                    ratio: 0.25,
+                    filters: [{ tag: "a", exact: true }, null],
+                    skipped: undefined
+                };
+                const native = await invokeTool("echo_args", args);
+                const stringified = await __tool_invoke("echo_args", JSON.stringify(args));
+                return { native, stringified };
+            };
+            "#,
+        )
+        .build()
+        .await
+        .expect("agent build");
+
+    let result = agent
+        .bridge()
+        .lock()
+        .await
+        .invoke_js_function("echoBoth", json!({}))
+        .await
+        .expect("invoke echoBoth");
+
+    let expected = json!({
+        "query": "rust",
+        "limit": 1_099_511_627_776i64,
+        "ratio": 0.25,
+        "filters": [{ "tag": "a", "exact": true }, null]
+    });
+    assert_eq!(result["native"], expected);
+    assert_eq!(result["stringified"], expected);
+}
+
+/// Reverses the bytes of `data`
+struct ReverseBytesTool;
+
+#[async_trait]
+impl BamlTool for ReverseBytesTool {
+    const NAME: &'static str = "reverse_bytes";
+
+    fn description(&self) -> &'static str {
+        "Reverses binary data"
+    }
+
+    fn input_schema(&self) -> Value {
+        json!({ "type": "object", "properties": { "data": {} }, "required": ["data"] })
+    }
+
+    async fn execute(&self, args: Value) -> baml_rt::Result<Value> {
+        let Some(mut data) = Bytes::from_value(&args["data"]) else {
+            return Err(baml_rt::BamlRtError::InvalidArgument(
+                "data must be binary".to_string(),
+            ));
+        };
+        data.0.reverse();
+        Ok(json!({ "data": data }))
+    }
+}
+
+#[tokio::test]
+async fn test_binary_data_crosses_the_js_bridge() {
+    let runtime = BamlRuntimeManager::new().expect("runtime");
+    runtime
+        .register_tool(ReverseBytesTool)
+        .await
+        .expect("register reverse tool");
+    let agent = A2aAgent::builder()
+        .with_runtime_manager(runtime)
+        .with_init_js(
+            r#"
+            globalThis.reverseBoth = async function() {
+                const fromArray = await invokeTool("reverse_bytes", {
+                    data: new Uint8Array([1, 2, 3])
+                });
+                const fromBuffer = await invokeTool("reverse_bytes", {
+                    data: new Uint8Array([4, 5]).buffer
+                });
+                return {
+                    isUint8Array: fromArray.data instanceof Uint8Array,
+                    fromArray: Array.from(fromArray.data),
+                    fromBuffer: Array.from(fromBuffer.data)
+                };
+            };
+            "#,
+        )
+        .build()
+        .await
+        .expect("agent build");
+
+    let result = agent
+        .bridge()
+        .lock()
+        .await
+        .invoke_js_function("reverseBoth", json!({}))
+        .await
+        .expect("invoke reverseBoth");
+
+    assert_eq!(
+        result,
+        json!({ "isUint8Array": true, "fromArray": [3, 2, 1], "fromBuffer": [5, 4] })
+    );
+}
+
+#[tokio::test]
+async fn test_tool_policy_limits_tools_per_function() {
+    let mut runtime = BamlRuntimeManager::new().expect("runtime");
+    runtime.set_tool_policy(ToolPolicy::new().with_function("Summarize", ["js:format_js"]));
+    runtime
+        .register_tool(EchoArgsTool)
+        .await
+        .expect("register echo tool");
+    let agent = A2aAgent::builder()
+        .with_runtime_manager(runtime)
+        .with_init_js(
+            r#"
+            globalThis.callTools = async function() {
+                const outcomes = {};
+                for (const [tool, fn] of [["echo_args", "Summarize"], ["format_js", "Summarize"],
+                                          ["echo_args", "Plan"], ["js:format_js", null]]) {
+                    try {
+                        await invokeTool(tool, { text: "hi" }, { function: fn });
+                        outcomes[`${tool}@${fn}`] = "allowed";
+                    } catch (e) {
+                        outcomes[`${tool}@${fn}`] = "refused";
+                    }
+                }
+                return outcomes;
+            };
+            "#,
+        )
+        .build()
+        .await
+        .expect("agent build");
+    agent
+        .register_js_tool(
+            "format_js",
+            "Formats text",
+            json!({ "type": "object" }),
+            r#"(args) => ({ text: args.text.toUpperCase() })"#,
+        )
+        .await
+        .expect("register js tool");
+
+    let outcomes = agent
+        .bridge()
+        .lock()
+        .await
+        .invoke_js_function("callTools", json!({}))
+        .await
+        .expect("invoke callTools");
+    assert_eq!(
+        outcomes,
+        json!({
+            "echo_args@Summarize": "refused",
+            "format_js@Summarize": "allowed",
+            "echo_args@Plan": "allowed",
+            "js:format_js@null": "allowed"
+        })
+    );
+
+    let runtime = agent.runtime();
+    let refused = runtime
+        .read()
+        .await
+        .execute_tool_for(Some("Summarize"), "echo_args", json!({}))
+        .await;
+    assert!(matches!(refused, Err(BamlRtError::Forbidden { .. })));
+}
@@ -16,14 +16,14 @@ async fn test_e2e_llm_with_tools() {
     // Set up BAML runtime
     let baml_manager = setup_baml_runtime_default();
     {
-        let mut manager = baml_manager.lock().await;
+        let manager = baml_manager.read().await;
         manager.register_tool(WeatherTool).await.unwrap();
         manager.register_tool(CalculatorTool).await.unwrap();
     }
 
     // Test 1: Verify tools are registered
     {
-        let manager = baml_manager.lock().await;
+        let manager = baml_manager.read().await;
         let tools = manager.list_tools().await;
         assert!(
             tools.contains(&"get_weather".to_string()),
@@ -38,7 +38,7 @@ async fn test_e2e_llm_with_tools() {
 
     // Test 2: Test tool execution directly
     {
-        let manager = baml_manager.lock().await;
+        let manager = baml_manager.read().await;
 
         // Test weather tool
         let weather_result = manager
@@ -90,7 +90,7 @@ async fn test_e2e_llm_with_tools() {
     // Note: The LLM might not actually call tools unless BAML's tool calling
     // is properly configured, but we verify the infrastructure is in place
     {
-        let manager = baml_manager.lock().await;
+        let manager = baml_manager.read().await;
 
         tracing::info!("Calling BAML function GetWeatherInfo with location 'London'");
         let result = manager
@@ -128,7 +128,7 @@ async fn test_e2e_js_tool_workflow_llm_to_js() {
     // Set up BAML runtime
     let baml_manager = setup_baml_runtime_default();
     {
-        let mut manager = baml_manager.lock().await;
+        let manager = baml_manager.read().await;
         manager.register_tool(UppercaseTool).await.unwrap();
     }
 
@@ -140,7 +140,7 @@ async fn test_e2e_js_tool_workflow_llm_to_js() {
 
     // Invoke function that uses tool
     {
-        let manager = baml_manager.lock().await;
+        let manager = baml_manager.read().await;
 
         let result = manager
             .invoke_function("UppercaseText", json!({"text": "hello world"}))
@@ -178,13 +178,13 @@ async fn test_e2e_llm_tool_execution_flow() {
     // Set up BAML runtime
     let baml_manager = setup_baml_runtime_default();
     {
-        let mut manager = baml_manager.lock().await;
+        let manager = baml_manager.read().await;
         manager.register_tool(DelayedResponseTool).await.unwrap();
     }
 
     // Invoke function that should call tool
     {
-        let manager = baml_manager.lock().await;
+        let manager = baml_manager.read().await;
 
         let result = manager
             .invoke_function("DelayedResponse", json!({"message": "Test delay"}))
@@ -235,7 +235,7 @@ async fn test_e2e_js_tool_with_llm() {
 
     // Invoke function that should call JS tool
     {
-        let manager = baml_manager.lock().await;
+        let manager = baml_manager.read().await;
 
         let result = manager
             .invoke_function("ConcatStrings", json!({"a": "Hello", "b": "World"}))
@@ -1,7 +1,10 @@
 //! Tests for tool registration (Rust and JavaScript)
 
 use async_trait::async_trait;
-use baml_rt::tools::BamlTool;
+use baml_rt::tools::{
+    BamlTool, CollisionPolicy, JS_NAMESPACE, MCP_NAMESPACE, ToolExecutor, ToolMetadata,
+    ToolRegistry,
+};
 use serde_json::json;
 
 use std::sync::Arc;
@@ -9,7 +12,7 @@ use test_support::common::{
     assert_tool_registered_in_js, setup_baml_runtime_default, setup_baml_runtime_manager_default,
     setup_bridge,
 };
-use tokio::sync::Mutex;
+use tokio::sync::RwLock;
 // Simple test tools
 struct AddNumbersTool;
 
@@ -122,13 +125,13 @@ async fn test_register_and_execute_tool_rust() {
 
     // Register a simple calculator tool using the trait
     {
-        let mut manager = baml_manager.lock().await;
+        let manager = baml_manager.read().await;
         manager.register_tool(AddNumbersTool).await.unwrap();
     }
 
     // Test executing the tool directly from Rust
     {
-        let manager = baml_manager.lock().await;
+        let manager = baml_manager.read().await;
         let result = manager
             .execute_tool("add_numbers", json!({"a": 5, "b": 3}))
             .await
@@ -145,7 +148,7 @@ async fn test_register_and_execute_tool_rust() {
 
     // Test listing tools
     {
-        let manager = baml_manager.lock().await;
+        let manager = baml_manager.read().await;
         let tools = manager.list_tools().await;
         assert!(
             tools.contains(&"add_numbers".to_string()),
@@ -161,7 +164,7 @@ async fn test_register_and_execute_tool_js() {
 
     // Register a tool using the trait
     {
-        let mut manager = baml_manager.lock().await;
+        let manager = baml_manager.read().await;
         manager.register_tool(GreetTool).await.unwrap();
     }
 
@@ -174,7 +177,7 @@ async fn test_register_and_execute_tool_js() {
 
     // Test executing the tool directly from Rust to verify it works end-to-end
     {
-        let manager = baml_manager.lock().await;
+        let manager = baml_manager.read().await;
         let result = manager
             .execute_tool("greet", json!({"name": "World"}))
             .await
@@ -193,13 +196,13 @@ async fn test_async_streaming_tool() {
 
     // Register an async streaming tool using the trait
     {
-        let mut manager = baml_manager.lock().await;
+        let manager = baml_manager.read().await;
         manager.register_tool(StreamLettersTool).await.unwrap();
     }
 
     // Test executing the streaming tool
     {
-        let manager = baml_manager.lock().await;
+        let manager = baml_manager.read().await;
         let result = manager
             .execute_tool("stream_letters", json!({"word": "test"}))
             .await
@@ -351,7 +354,7 @@ async fn test_js_tool_not_available_in_rust() {
         .unwrap();
 
     // Verify it's NOT in the Rust tool registry
-    let manager = baml_manager.lock().await;
+    let manager = baml_manager.read().await;
     let rust_tools = manager.list_tools().await;
     assert!(
         !rust_tools.contains(&"js_only_tool".to_string()),
@@ -396,7 +399,7 @@ async fn test_js_tool_name_conflict_with_rust_tool() {
     // Register Rust tool first
     baml_manager.register_tool(TestRustTool).await.unwrap();
 
-    let baml_manager = Arc::new(Mutex::new(baml_manager));
+    let baml_manager = Arc::new(RwLock::new(baml_manager));
     let mut bridge = setup_bridge(baml_manager.clone()).await;
 
     // Try to register a JS tool with the same name - should fail
@@ -457,3 +460,74 @@ async fn test_register_multiple_js_tools() {
 
     tracing::info!("✅ Multiple JavaScript tools registered successfully");
 }
+
+struct Reply(&'static str);
+
+#[async_trait]
+impl ToolExecutor for Reply {
+    async fn execute(&self, _args: serde_json::Value) -> baml_rt::Result<serde_json::Value> {
+        Ok(json!(self.0))
+    }
+}
+
+fn greet_metadata() -> ToolMetadata {
+    ToolMetadata {
+        name: "greet".to_string(),
+        description: "Returns a greeting message".to_string(),
+        input_schema: json!({}),
+        requires_approval: false,
+    }
+}
+
+#[tokio::test]
+async fn test_namespaced_tools_and_collision_policies() {
+    let mut registry = ToolRegistry::new();
+    registry.register(GreetTool).unwrap();
+    registry
+        .register_in(
+            MCP_NAMESPACE,
+            ToolMetadata {
+                name: "docs/search".to_string(),
+                ..greet_metadata()
+            },
+            Arc::new(Reply("mcp")),
+        )
+        .unwrap();
+    assert!(
+        registry
+            .register_in(JS_NAMESPACE, greet_metadata(), Arc::new(Reply("js")))
+            .is_err(),
+        "Reject is the default policy"
+    );
+    assert_eq!(registry.resolve("greet").unwrap(), "rust:greet");
+    assert_eq!(
+        registry
+            .execute("mcp:docs/search", json!({}))
+            .await
+            .unwrap(),
+        json!("mcp")
+    );
+
+    for (policy, bare) in [
+        (CollisionPolicy::FirstWins, Some("rust:greet")),
+        (CollisionPolicy::LastWins, Some("js:greet")),
+        (CollisionPolicy::RequireQualified, None),
+    ] {
+        let mut registry = ToolRegistry::new().with_collision_policy(policy);
+        registry.register(GreetTool).unwrap();
+        registry
+            .register_in(JS_NAMESPACE, greet_metadata(), Arc::new(Reply("js")))
+            .unwrap();
+        assert_eq!(registry.list_tools(), vec!["greet".to_string()]);
+        assert_eq!(
+            registry.resolve("greet").ok().as_deref(),
+            bare,
+            "{:?}",
+            policy
+        );
+        assert_eq!(
+            registry.execute("js:greet", json!({})).await.unwrap(),
+            json!("js")
+        );
+    }
+}
@@ -166,14 +166,14 @@ async fn test_e2e_trait_tool_registration_rust_execution() {
 
     // Register tools using trait system
     {
-        let mut manager = baml_manager.lock().await;
+        let manager = baml_manager.read().await;
         manager.register_tool(ArithmeticTool).await.unwrap();
         manager.register_tool(StringManipulationTool).await.unwrap();
     }
 
     // Execute tools from Rust
     {
-        let manager = baml_manager.lock().await;
+        let manager = baml_manager.read().await;
 
         let arithmetic_result = manager
             .execute_tool(
@@ -212,7 +212,7 @@ async fn test_e2e_trait_tool_js_registration() {
 
     // Register tools using trait system
     {
-        let mut manager = baml_manager.lock().await;
+        let manager = baml_manager.read().await;
         manager.register_tool(ArithmeticTool).await.unwrap();
     }
 
@@ -229,14 +229,14 @@ async fn test_e2e_trait_tool_metadata_and_listing() {
 
     // Register tools
     {
-        let mut manager = baml_manager.lock().await;
+        let manager = baml_manager.read().await;
         manager.register_tool(ArithmeticTool).await.unwrap();
         manager.register_tool(StringManipulationTool).await.unwrap();
     }
 
     // Test listing and metadata
     {
-        let manager = baml_manager.lock().await;
+        let manager = baml_manager.read().await;
         let tools = manager.list_tools().await;
 
         assert!(tools.contains(&"arithmetic".to_string()));
@@ -257,14 +257,14 @@ async fn test_e2e_trait_tool_llm_calling() {
 
     // Register tools
     {
-        let mut manager = baml_manager.lock().await;
+        let manager = baml_manager.read().await;
         manager.register_tool(WeatherTool).await.unwrap();
         manager.register_tool(CalculatorTool).await.unwrap();
     }
 
     // Invoke a function that may call tools
     {
-        let manager = baml_manager.lock().await;
+        let manager = baml_manager.read().await;
 
         let result = manager
             .invoke_function("ChooseTool", json!({"message": "What is 42 times 7?"}))
@@ -0,0 +1,309 @@
+//! One builder for an embedded runtime
+//!
+//! [`RuntimeBuilder`] composes the sub-crates: it loads the BAML schema,
+//! registers Rust and JavaScript tools and interceptors, evaluates agent
+//! JavaScript, and wraps the result in an [`A2aAgent`] with its task store,
+//! provenance, and failover. The built [`Runtime`] answers A2A JSON-RPC
+//! directly or serves it as newline-delimited JSON over any byte stream:
+//!
+//! ```rust,no_run
+//! use baml_rt::RuntimeBuilder;
+//!
+//! # tokio_test::block_on(async {
+//! let runtime = RuntimeBuilder::new()
+//!     .with_schema_path("baml_src")
+//!     .with_js("globalThis.greet = async ({ name }) => `Hello, ${name}`;")
+//!     .build()
+//!     .await?;
+//! runtime.serve_stdio().await?;
+//! # Ok::<(), baml_rt::BamlRtError>(())
+//! # }).unwrap();
+//! ```
+//!
+//! Settings without a method here are reachable through
+//! [`RuntimeBuilder::configure_baml`] and [`RuntimeBuilder::configure_agent`].
+
+use crate::serverless;
+use baml_rt_a2a::a2a_store::TaskStoreBackend;
+use baml_rt_a2a::{
+    A2aAgent, A2aAgentBuilder, A2aRequestHandler, BatchExecution, TransportMetadata,
+};
+use baml_rt_core::events::RuntimeEvent;
+use baml_rt_core::{BamlRtError, Result};
+use baml_rt_interceptor::{DecisionPolicy, LLMInterceptor, ToolInterceptor};
+use baml_rt_provenance::{ProvenanceWriter, RedactionPolicy};
+use baml_rt_quickjs::{BamlRuntimeManager, QuickJSBridge, QuickJSConfig};
+use baml_rt_tools::{BamlTool, CollisionPolicy, ToolExecutor, ToolMetadata, ToolRegistry};
+use serde_json::Value;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::sync::{Mutex, RwLock, broadcast};
+
+type ToolRegistration = Box<dyn FnOnce(&mut ToolRegistry) -> Result<()> + Send>;
+
+/// A JavaScript tool: name, description, input schema, and function source
+struct JsTool {
+    name: String,
+    description: String,
+    input_schema: Value,
+    code: String,
+}
+
+/// Builder for a [`Runtime`]
+pub struct RuntimeBuilder {
+    baml: baml_rt_quickjs::RuntimeBuilder,
+    agent: A2aAgentBuilder,
+    tools: Vec<ToolRegistration>,
+    js_tools: Vec<JsTool>,
+    tool_collisions: CollisionPolicy,
+}
+
+impl RuntimeBuilder {
+    pub fn new() -> Self {
+        Self {
+            baml: baml_rt_quickjs::RuntimeBuilder::new(),
+            agent: A2aAgentBuilder::new(),
+            tools: Vec::new(),
+            js_tools: Vec::new(),
+            tool_collisions: CollisionPolicy::default(),
+        }
+    }
+
+    /// Load BAML functions from the schema at `path`
+    pub fn with_schema_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.baml = self.baml.with_schema_path(path);
+        self
+    }
+
+    /// Configure the QuickJS engine agent JavaScript runs in
+    pub fn with_quickjs_config(mut self, config: QuickJSConfig) -> Self {
+        self.agent = self.agent.with_quickjs_config(config);
+        self
+    }
+
+    /// Evaluate `code` when the JS bridge starts, and again on every
+    /// replacement bridge
+    pub fn with_js(mut self, code: impl Into<String>) -> Self {
+        self.agent = self.agent.with_init_js(code);
+        self
+    }
+
+    pub fn with_tool<T: BamlTool>(mut self, tool: T) -> Self {
+        self.tools
+            .push(Box::new(move |registry| registry.register(tool)));
+        self
+    }
+
+    /// Register a tool described at runtime, e.g. one loaded from config
+    pub fn with_dynamic_tool(
+        mut self,
+        metadata: ToolMetadata,
+        executor: Arc<dyn ToolExecutor>,
+    ) -> Self {
+        self.tools.push(Box::new(move |registry| {
+            registry.register_dynamic(metadata, executor)
+        }));
+        self
+    }
+
+    /// Decide what happens when a Rust and a JavaScript tool share a name
+    pub fn with_tool_collision_policy(mut self, policy: CollisionPolicy) -> Self {
+        self.tool_collisions = policy;
+        self
+    }
+
+    /// Register a tool implemented by the JavaScript function `code`
+    pub fn with_js_tool(
+        mut self,
+        name: impl Into<String>,
+        description: impl Into<String>,
+        input_schema: Value,
+        code: impl Into<String>,
+    ) -> Self {
+        self.js_tools.push(JsTool {
+            name: name.into(),
+            description: description.into(),
+            input_schema,
+            code: code.into(),
+        });
+        self
+    }
+
+    pub fn with_llm_interceptor<I: LLMInterceptor>(mut self, interceptor: I) -> Self {
+        self.baml = self.baml.with_llm_interceptor(interceptor);
+        self
+    }
+
+    pub fn with_tool_interceptor<I: ToolInterceptor>(mut self, interceptor: I) -> Self {
+        self.baml = self.baml.with_tool_interceptor(interceptor);
+        self
+    }
+
+    /// Set how conflicting interceptor decisions are resolved
+    pub fn with_interceptor_decision_policy(mut self, policy: DecisionPolicy) -> Self {
+        self.baml = self.baml.with_interceptor_decision_policy(policy);
+        self
+    }
+
+    /// Keep A2A tasks in `store` instead of in memory
+    pub fn with_task_store(mut self, store: Arc<dyn TaskStoreBackend>) -> Self {
+        self.agent = self.agent.with_task_store_backend(store);
+        self
+    }
+
+    /// Record provenance with `writer` instead of in memory
+    pub fn with_provenance_writer(mut self, writer: Arc<dyn ProvenanceWriter>) -> Self {
+        self.agent = self.agent.with_provenance_writer(writer);
+        self
+    }
+
+    /// Redact LLM and tool payloads before they are recorded as provenance
+    pub fn with_provenance_redaction(mut self, policy: RedactionPolicy) -> Self {
+        self.agent = self.agent.with_provenance_redaction(policy);
+        self
+    }
+
+    /// Set how the members of JSON-RPC batches are run
+    pub fn with_batch_execution(mut self, execution: BatchExecution) -> Self {
+        self.agent = self.agent.with_batch_execution(execution);
+        self
+    }
+
+    /// Adjust BAML runtime settings this builder has no method for
+    pub fn configure_baml(
+        mut self,
+        configure: impl FnOnce(baml_rt_quickjs::RuntimeBuilder) -> baml_rt_quickjs::RuntimeBuilder,
+    ) -> Self {
+        self.baml = configure(self.baml);
+        self
+    }
+
+    /// Adjust A2A agent settings this builder has no method for
+    pub fn configure_agent(
+        mut self,
+        configure: impl FnOnce(A2aAgentBuilder) -> A2aAgentBuilder,
+    ) -> Self {
+        self.agent = configure(self.agent);
+        self
+    }
+
+    pub async fn build(self) -> Result<Runtime> {
+        // The agent creates the bridge, so it can replace it after failures
+        let baml = self.baml.with_quickjs(false).build().await?;
+        let manager = baml.baml_manager();
+        {
+            let registry = manager.read().await.tool_registry();
+            let mut registry = registry.lock().await;
+            registry.set_collision_policy(self.tool_collisions);
+            for register in self.tools {
+                register(&mut *registry)?;
+            }
+        }
+
+        let agent = self.agent.with_runtime_handle(manager).build().await?;
+        for tool in self.js_tools {
+            agent
+                .register_js_tool(tool.name, tool.description, tool.input_schema, tool.code)
+                .await?;
+        }
+        Ok(Runtime { agent })
+    }
+}
+
+impl Default for RuntimeBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A runtime built by [`RuntimeBuilder`]
+#[derive(Clone)]
+pub struct Runtime {
+    agent: A2aAgent,
+}
+
+impl Runtime {
+    pub fn builder() -> RuntimeBuilder {
+        RuntimeBuilder::new()
+    }
+
+    /// The A2A agent serving requests, for task updates, provenance, and
+    /// other agent-level access
+    pub fn agent(&self) -> &A2aAgent {
+        &self.agent
+    }
+
+    pub fn baml_manager(&self) -> Arc<RwLock<BamlRuntimeManager>> {
+        self.agent.runtime()
+    }
+
+    pub fn bridge(&self) -> Arc<Mutex<QuickJSBridge>> {
+        self.agent.bridge()
+    }
+
+    /// Subscribe to everything this runtime does: LLM and tool calls, JS
+    /// errors, task updates, and bridge failover
+    pub fn subscribe_events(&self) -> broadcast::Receiver<RuntimeEvent> {
+        self.agent.subscribe_events()
+    }
+
+    /// Call the BAML function `function_name`
+    pub async fn invoke(&self, function_name: &str, args: Value) -> Result<Value> {
+        let manager = self.agent.runtime();
+        let manager = manager.read().await;
+        manager.invoke_function(function_name, args).await
+    }
+
+    /// Call the global JavaScript function `function_name`
+    pub async fn invoke_js(&self, function_name: &str, args: Value) -> Result<Value> {
+        let bridge = self.agent.bridge();
+        let mut bridge = bridge.lock().await;
+        bridge.invoke_js_function(function_name, args).await
+    }
+
+    /// Answer an A2A JSON-RPC request or batch
+    pub async fn handle_a2a(&self, request: Value) -> Result<Vec<Value>> {
+        self.agent.handle_a2a(request).await
+    }
+
+    /// [`serverless::serve_request`] on this runtime's agent
+    pub async fn serve_request(&self, raw: &[u8]) -> Vec<u8> {
+        serverless::serve_request(&self.agent, raw).await
+    }
+
+    /// Serve newline-delimited A2A JSON-RPC from `reader` to `writer` until
+    /// `reader` ends
+    ///
+    /// Each line holds a request or batch; its responses are written one per
+    /// line. Lines that are not JSON are answered with a parse error.
+    pub async fn serve<R, W>(&self, reader: R, mut writer: W) -> Result<()>
+    where
+        R: AsyncBufRead + Unpin,
+        W: AsyncWrite + Unpin,
+    {
+        let mut lines = reader.lines();
+        while let Some(line) = lines.next_line().await? {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let responses =
+                serverless::responses(&self.agent, line.as_bytes(), &TransportMetadata::default())
+                    .await;
+            for response in responses {
+                let mut serialized = serde_json::to_vec(&response).map_err(BamlRtError::Json)?;
+                serialized.push(b'\n');
+                writer.write_all(&serialized).await?;
+            }
+            writer.flush().await?;
+        }
+        Ok(())
+    }
+
+    /// [`Runtime::serve`] on stdin and stdout
+    pub async fn serve_stdio(&self) -> Result<()> {
+        let stdin = tokio::io::BufReader::new(tokio::io::stdin());
+        self.serve(stdin, tokio::io::stdout()).await
+    }
+}
@@ -1,22 +1,79 @@
 //! BAML Runtime workspace facade.
 //!
-//! This crate re-exports functionality from the workspace sub-crates.
+//! This crate re-exports functionality from the workspace sub-crates, and
+//! [`RuntimeBuilder`] composes them into one embeddable runtime.
 
-pub use baml_rt_core::context::{current_context_id, generate_context_id};
+#[cfg(feature = "a2a")]
+mod facade;
+#[cfg(feature = "a2a")]
+pub mod serverless;
+
+pub use baml_rt_core::context::{
+    RequestMetadata, current_context_id, current_metadata, generate_context_id, with_context,
+    with_context_id,
+};
 pub use baml_rt_core::correlation::{current_correlation_id, generate_correlation_id};
-pub use baml_rt_core::{BamlRtError, Result};
+pub use baml_rt_core::types::Bytes;
+pub use baml_rt_core::{BamlRtError, JsException, Result};
+pub mod auth {
+    #[cfg(feature = "a2a")]
+    pub use baml_rt_a2a::auth::*;
+    pub use baml_rt_core::auth::*;
+}
+pub mod baml_value {
+    pub use baml_rt_core::baml_value::*;
+}
+pub mod deadline {
+    pub use baml_rt_core::deadline::*;
+}
 pub mod error {
     pub use baml_rt_core::error::*;
 }
+pub mod events {
+    pub use baml_rt_core::events::*;
+}
+pub mod manifest {
+    pub use baml_rt_core::manifest::*;
+}
+pub mod media {
+    pub use baml_rt_core::media::*;
+}
+pub mod types {
+    pub use baml_rt_core::types::*;
+}
 
 #[cfg(feature = "tools")]
 pub mod tools {
     pub use baml_rt_tools::tools::*;
 }
 #[cfg(feature = "tools")]
+pub mod approval {
+    pub use baml_rt_tools::approval::*;
+}
+#[cfg(feature = "tools")]
+pub mod tool_policy {
+    pub use baml_rt_tools::policy::*;
+}
+#[cfg(feature = "tools")]
 pub mod tool_mapper {
     pub use baml_rt_tools::tool_mapper::*;
 }
+#[cfg(feature = "documents")]
+pub mod documents {
+    pub use baml_rt_tools::documents::*;
+}
+#[cfg(feature = "exec")]
+pub mod exec {
+    pub use baml_rt_tools::exec::*;
+}
+#[cfg(feature = "files")]
+pub mod files {
+    pub use baml_rt_tools::files::*;
+}
+#[cfg(feature = "http")]
+pub mod http {
+    pub use baml_rt_tools::http::*;
+}
 
 #[cfg(feature = "interceptor")]
 pub mod interceptor {
@@ -27,6 +84,14 @@ pub mod interceptors {
     pub use baml_rt_interceptor::interceptors::*;
 }
 
+#[cfg(feature = "quickjs")]
+pub mod agent_caller {
+    pub use baml_rt_quickjs::agent_caller::*;
+}
+#[cfg(feature = "quickjs")]
+pub mod artifact_store {
+    pub use baml_rt_quickjs::artifact_store::*;
+}
 #[cfg(feature = "quickjs")]
 pub mod baml {
     pub use baml_rt_quickjs::baml::*;
@@ -44,6 +109,38 @@ pub mod baml_pre_execution {
     pub use baml_rt_quickjs::baml_pre_execution::*;
 }
 #[cfg(feature = "quickjs")]
+pub mod baml_stream_interception {
+    pub use baml_rt_quickjs::baml_stream_interception::*;
+}
+#[cfg(feature = "quickjs")]
+pub mod dry_run {
+    pub use baml_rt_quickjs::dry_run::*;
+}
+#[cfg(feature = "quickjs")]
+pub mod embeddings {
+    pub use baml_rt_quickjs::embeddings::*;
+}
+#[cfg(feature = "quickjs")]
+pub mod kv_store {
+    pub use baml_rt_quickjs::kv_store::*;
+}
+#[cfg(feature = "quickjs")]
+pub mod llm_overrides {
+    pub use baml_rt_quickjs::llm_overrides::*;
+}
+#[cfg(feature = "quickjs")]
+pub mod memory {
+    pub use baml_rt_quickjs::memory::*;
+}
+#[cfg(feature = "quickjs")]
+pub mod output_guard {
+    pub use baml_rt_quickjs::output_guard::*;
+}
+#[cfg(feature = "quickjs")]
+pub mod repair {
+    pub use baml_rt_quickjs::repair::*;
+}
+#[cfg(feature = "quickjs")]
 pub mod quickjs_bridge {
     pub use baml_rt_quickjs::quickjs_bridge::*;
 }
@@ -60,6 +157,30 @@ pub mod runtime {
     pub use baml_rt_quickjs::runtime::*;
 }
 #[cfg(feature = "quickjs")]
+pub mod secrets {
+    pub use baml_rt_quickjs::secrets::*;
+}
+#[cfg(feature = "quickjs")]
+pub mod session {
+    pub use baml_rt_quickjs::session::*;
+}
+#[cfg(feature = "quickjs")]
+pub mod shared_runtime {
+    pub use baml_rt_quickjs::shared_runtime::*;
+}
+#[cfg(feature = "quickjs")]
+pub mod source_map {
+    pub use baml_rt_quickjs::source_map::*;
+}
+#[cfg(feature = "quickjs")]
+pub mod token_limiter {
+    pub use baml_rt_quickjs::token_limiter::*;
+}
+#[cfg(feature = "quickjs")]
+pub mod tool_schema {
+    pub use baml_rt_quickjs::tool_schema::*;
+}
+#[cfg(feature = "quickjs")]
 pub mod traits {
     pub use baml_rt_quickjs::traits::*;
 }
@@ -69,6 +190,10 @@ pub mod a2a {
     pub use baml_rt_a2a::a2a::*;
 }
 #[cfg(feature = "a2a")]
+pub mod a2a_client {
+    pub use baml_rt_a2a::a2a_client::*;
+}
+#[cfg(feature = "a2a")]
 pub mod a2a_store {
     pub use baml_rt_a2a::a2a_store::*;
 }
@@ -80,6 +205,10 @@ pub mod a2a_types {
 pub mod a2a_transport {
     pub use baml_rt_a2a::a2a_transport::*;
 }
+#[cfg(feature = "a2a")]
+pub mod provenance {
+    pub use baml_rt_provenance::*;
+}
 
 #[cfg(feature = "builder")]
 pub mod builder {
@@ -105,12 +234,19 @@ pub use baml_rt_a2a::{A2aAgent, A2aAgentBuilder, A2aRequestHandler};
 pub use baml_rt_a2a::{A2aMethod, A2aOutcome, A2aRequest};
 #[cfg(feature = "interceptor")]
 pub use baml_rt_interceptor::{
-    InterceptorDecision, InterceptorRegistry, LLMCallContext, LLMInterceptor, ToolCallContext,
-    ToolInterceptor,
+    ChainedInterceptor, FaultError, FaultInjectionInterceptor, FaultTarget, TracingInterceptor,
+    TracingLLMInterceptor, TracingToolInterceptor,
 };
 #[cfg(feature = "interceptor")]
-pub use baml_rt_interceptor::{TracingInterceptor, TracingLLMInterceptor, TracingToolInterceptor};
+pub use baml_rt_interceptor::{
+    DecisionPolicy, InterceptorDecision, InterceptorRegistry, LLMCallContext, LLMInterceptor,
+    ToolCallContext, ToolInterceptor,
+};
 #[cfg(feature = "quickjs")]
 pub use baml_rt_quickjs::{BamlContext, BamlRuntimeManager, ContextMetadata};
 #[cfg(feature = "quickjs")]
-pub use baml_rt_quickjs::{QuickJSBridge, QuickJSConfig, Runtime, RuntimeBuilder, RuntimeConfig};
+pub use baml_rt_quickjs::{QuickJSBridge, QuickJSConfig, RuntimeConfig};
+#[cfg(feature = "a2a")]
+pub use facade::{Runtime, RuntimeBuilder};
+#[cfg(feature = "a2a")]
+pub use serverless::{serve_http_event, serve_request};
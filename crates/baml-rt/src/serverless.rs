@@ -0,0 +1,123 @@
+//! Serving single A2A requests from a host's own request loop
+//!
+//! Serverless platforms own the process and hand over one request at a time.
+//! [`serve_request`] answers the raw bytes of one JSON-RPC request or batch,
+//! and [`serve_http_event`] adapts it to the HTTP proxy events of AWS Lambda
+//! (API Gateway and function URLs), so a handler is a single call on an agent
+//! built once per cold start.
+
+use baml_rt_a2a::{A2aAgent, A2aRequestHandler, TransportMetadata, a2a};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use serde_json::{Value, json};
+
+/// The JSON-RPC responses to `raw`, a request or batch
+///
+/// Bytes that are not JSON get a parse error, and a request the agent fails
+/// to handle gets an internal error, so there is always something to send.
+pub(crate) async fn responses(
+    agent: &A2aAgent,
+    raw: &[u8],
+    transport: &TransportMetadata,
+) -> Vec<Value> {
+    let request: Value = match serde_json::from_slice(raw) {
+        Ok(request) => request,
+        Err(err) => {
+            return vec![a2a::error_response(
+                None,
+                -32700,
+                "JSON parse error",
+                Some(Value::String(err.to_string())),
+            )];
+        }
+    };
+    let request_id = a2a::extract_jsonrpc_id(&request);
+    agent
+        .handle_a2a_with_transport(request, transport)
+        .await
+        .unwrap_or_else(|err| {
+            vec![a2a::error_response(
+                request_id,
+                -32603,
+                "Internal error",
+                Some(Value::String(err.to_string())),
+            )]
+        })
+}
+
+/// Answer one A2A JSON-RPC request or batch given as raw bytes
+///
+/// A request answered once gets its response object; a batch, or a
+/// streaming request answered with several events, gets a JSON array. A
+/// notification gets no bytes at all.
+pub async fn serve_request(agent: &A2aAgent, raw: &[u8]) -> Vec<u8> {
+    answer(agent, raw, &TransportMetadata::default()).await
+}
+
+async fn answer(agent: &A2aAgent, raw: &[u8], transport: &TransportMetadata) -> Vec<u8> {
+    let is_batch = raw.trim_ascii_start().starts_with(b"[");
+    let mut responses = responses(agent, raw, transport).await;
+    let body = match responses.len() {
+        0 => return Vec::new(),
+        1 if !is_batch => responses.remove(0),
+        _ => Value::Array(responses),
+    };
+    // A `Value` always serializes
+    serde_json::to_vec(&body).unwrap_or_default()
+}
+
+/// Answer a Lambda HTTP proxy event carrying an A2A request in its body
+///
+/// Accepts API Gateway REST (v1) and HTTP (v2) payloads and function URL
+/// events, decoding base64 bodies, and returns the matching proxy response:
+/// `200` with the JSON-RPC answer, or `202` with no body for notifications.
+/// The event's headers are passed to the agent's authenticator.
+pub async fn serve_http_event(agent: &A2aAgent, event: &Value) -> Value {
+    let body = event
+        .get("body")
+        .and_then(Value::as_str)
+        .unwrap_or_default();
+    let raw = if event.get("isBase64Encoded").and_then(Value::as_bool) == Some(true) {
+        match STANDARD.decode(body) {
+            Ok(raw) => raw,
+            Err(err) => {
+                let error = a2a::error_response(
+                    None,
+                    -32700,
+                    "Body is not valid base64",
+                    Some(Value::String(err.to_string())),
+                );
+                return http_response(400, Some(error.to_string()));
+            }
+        }
+    } else {
+        body.as_bytes().to_vec()
+    };
+
+    let transport = event
+        .get("headers")
+        .and_then(Value::as_object)
+        .into_iter()
+        .flatten()
+        .filter_map(|(name, value)| Some((name, value.as_str()?)))
+        .fold(TransportMetadata::new(), |transport, (name, value)| {
+            transport.with_header(name, value)
+        });
+    let answer = answer(agent, &raw, &transport).await;
+    if answer.is_empty() {
+        return http_response(202, None);
+    }
+    http_response(200, Some(String::from_utf8_lossy(&answer).into_owned()))
+}
+
+fn http_response(status: u16, body: Option<String>) -> Value {
+    let mut response = json!({
+        "statusCode": status,
+        "headers": { "content-type": "application/json" },
+        "isBase64Encoded": false,
+    });
+    if let Some(body) = body {
+        response["body"] = Value::String(body);
+    }
+    response
+}
@@ -1,6 +1,12 @@
 //! End-to-end test using actual LLM via OpenRouter
 
-use test_support::common::{require_api_key, setup_baml_runtime_default, setup_bridge};
+use baml_rt::llm_overrides::{LlmOverrides, LlmParams};
+use serde_json::json;
+use test_support::common::{
+    LIVE_LLM_ENV, require_api_key, setup_baml_runtime_default, setup_baml_runtime_manager_default,
+    setup_bridge, shared_mock_llm,
+};
+use test_support::support::mock_llm::MockResponse;
 
 #[tokio::test]
 async fn test_e2e_simple_greeting_with_llm() {
@@ -98,3 +104,78 @@ async fn test_e2e_streaming_greeting() {
         }
     }
 }
+
+#[tokio::test]
+async fn test_mock_llm_serves_programmed_replies() {
+    let _ = require_api_key();
+    if std::env::var_os(LIVE_LLM_ENV).is_some() {
+        return;
+    }
+    let mock = shared_mock_llm();
+    mock.respond_when(
+        "Say hello to Mock Probe",
+        MockResponse::chunks(["Greetings, ", "Mock ", "Probe!"]),
+    );
+
+    let baml_manager = setup_baml_runtime_default();
+    {
+        let manager = baml_manager.read().await;
+        let greeting = manager
+            .invoke_function("SimpleGreeting", json!({ "name": "Mock Probe" }))
+            .await
+            .expect("Mock LLM call should succeed");
+        assert_eq!(greeting, json!("Greetings, Mock Probe!"));
+    }
+
+    let mut bridge = setup_bridge(baml_manager).await;
+    let js_code = r#"
+        (() => __awaitAndStringify(SimpleGreetingStream({ name: "Mock Probe" })))()
+    "#;
+    let result = bridge
+        .evaluate(js_code)
+        .await
+        .expect("Mock LLM stream should succeed");
+    let chunks: serde_json::Value =
+        serde_json::from_str(result.as_str().expect("Expected JSON string")).unwrap();
+    let chunks = chunks.as_array().expect("Expected chunk array");
+    assert_eq!(chunks.last(), Some(&json!("Greetings, Mock Probe!")));
+
+    let probes: Vec<_> = mock
+        .requests()
+        .into_iter()
+        .filter(|request| request["messages"].to_string().contains("Mock Probe"))
+        .collect();
+    assert_eq!(probes.len(), 2);
+    assert_eq!(probes[0]["model"], "deepseek/deepseek-chat");
+    assert_eq!(probes[1]["stream"], true);
+}
+
+#[tokio::test]
+async fn test_llm_overrides_reach_the_provider_request() {
+    let _ = require_api_key();
+    if std::env::var_os(LIVE_LLM_ENV).is_some() {
+        return;
+    }
+
+    let mut manager = setup_baml_runtime_manager_default();
+    manager.set_llm_overrides(Some(
+        LlmOverrides::new()
+            .with_temperature(0.0)
+            .with_seed(7)
+            .with_function("SimpleGreeting", LlmParams::new().with_max_tokens(64)),
+    ));
+    manager
+        .invoke_function("SimpleGreeting", json!({ "name": "Override Probe" }))
+        .await
+        .expect("Mock LLM call should succeed");
+
+    let request = shared_mock_llm()
+        .requests()
+        .into_iter()
+        .find(|request| request["messages"].to_string().contains("Override Probe"))
+        .expect("The call should reach the mock");
+    assert_eq!(request["model"], "deepseek/deepseek-chat");
+    assert_eq!(request["temperature"], 0.0);
+    assert_eq!(request["seed"], 7);
+    assert_eq!(request["max_tokens"], 64);
+}
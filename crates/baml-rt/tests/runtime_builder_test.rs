@@ -0,0 +1,107 @@
+//! Tests for the runtime facade
+
+use baml_rt::RuntimeBuilder;
+use baml_rt::events::RuntimeEvent;
+use serde_json::{Value, json};
+use test_support::common::{UppercaseTool, ensure_baml_src_exists, workspace_root};
+
+#[tokio::test]
+async fn test_runtime_builder_wires_tools_js_and_a2a() {
+    if !ensure_baml_src_exists() {
+        return;
+    }
+    let runtime = RuntimeBuilder::new()
+        .with_schema_path(workspace_root().join("baml_src"))
+        .with_tool(UppercaseTool)
+        .with_js("globalThis.shout = async ({ text }) => text.toUpperCase() + '!';")
+        .with_js_tool(
+            "reverse",
+            "Reverses a string",
+            json!({ "type": "object", "properties": { "text": { "type": "string" } } }),
+            "async ({ text }) => text.split('').reverse().join('')",
+        )
+        .build()
+        .await
+        .expect("build runtime");
+
+    let manager = runtime.baml_manager();
+    let manager = manager.read().await;
+    assert!(
+        manager
+            .list_functions()
+            .contains(&"SimpleGreeting".to_string())
+    );
+    let mut tools = manager.list_tools().await;
+    tools.sort();
+    assert_eq!(tools, ["reverse", "uppercase"]);
+    drop(manager);
+
+    let shouted = runtime
+        .invoke_js("shout", json!({ "text": "hi" }))
+        .await
+        .expect("invoke JS");
+    assert_eq!(shouted, json!("HI!"));
+
+    let input = concat!(
+        r#"{"jsonrpc":"2.0","id":1,"method":"agent.health","params":{}}"#,
+        "\n",
+        "not json\n",
+    );
+    let mut output = Vec::new();
+    runtime
+        .serve(input.as_bytes(), &mut output)
+        .await
+        .expect("serve");
+    let responses: Vec<Value> = String::from_utf8(output)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(responses.len(), 2);
+    assert_eq!(responses[0]["id"], 1);
+    assert!(responses[0].get("result").is_some());
+    assert_eq!(responses[1]["error"]["code"], -32700);
+}
+
+#[tokio::test]
+async fn test_runtime_events_report_tool_calls_and_js_errors() {
+    let runtime = RuntimeBuilder::new()
+        .with_tool(UppercaseTool)
+        .with_js("globalThis.explode = async () => { throw new TypeError('boom'); };")
+        .build()
+        .await
+        .expect("build runtime");
+    let mut events = runtime.subscribe_events();
+
+    let manager = runtime.baml_manager();
+    manager
+        .read()
+        .await
+        .execute_tool("uppercase", json!({ "text": "hi" }))
+        .await
+        .expect("execute tool");
+    let error = runtime
+        .invoke_js("explode", json!({}))
+        .await
+        .expect_err("JS throws");
+    assert_eq!(
+        error.js_exception().expect("JS exception").name,
+        "TypeError"
+    );
+
+    let mut names = Vec::new();
+    while let Ok(event) = events.try_recv() {
+        match &event {
+            RuntimeEvent::ToolCompleted {
+                tool_name, error, ..
+            } => {
+                assert_eq!(tool_name, "uppercase");
+                assert!(error.is_none());
+            }
+            RuntimeEvent::JsError { exception, .. } => assert_eq!(exception.message, "boom"),
+            _ => {}
+        }
+        names.push(event.name());
+    }
+    assert_eq!(names, ["tool_call_started", "tool_completed", "js_error"]);
+}
@@ -0,0 +1,45 @@
+//! Tests for serving single requests without an agent binary
+
+use baml_rt::{A2aAgent, serve_http_event, serve_request};
+use serde_json::{Value, json};
+
+const HEALTH: &str = r#"{"jsonrpc":"2.0","id":7,"method":"agent.health","params":{}}"#;
+
+#[tokio::test]
+async fn test_serve_request_answers_raw_requests_and_batches() {
+    let agent = A2aAgent::new().await.expect("build agent");
+
+    let response: Value = serde_json::from_slice(&serve_request(&agent, HEALTH.as_bytes()).await)
+        .expect("response JSON");
+    assert_eq!(response["id"], 7);
+    assert!(response.get("result").is_some());
+
+    let batch = format!("[{}]", HEALTH);
+    let responses: Value =
+        serde_json::from_slice(&serve_request(&agent, batch.as_bytes()).await).unwrap();
+    assert_eq!(responses.as_array().map(Vec::len), Some(1));
+
+    let notification = r#"{"jsonrpc":"2.0","method":"agent.health","params":{}}"#;
+    assert!(
+        serve_request(&agent, notification.as_bytes())
+            .await
+            .is_empty()
+    );
+
+    let error: Value = serde_json::from_slice(&serve_request(&agent, b"{oops").await).unwrap();
+    assert_eq!(error["error"]["code"], -32700);
+}
+
+#[tokio::test]
+async fn test_serve_http_event_adapts_lambda_proxy_events() {
+    let agent = A2aAgent::new().await.expect("build agent");
+
+    let event = json!({ "body": HEALTH, "isBase64Encoded": false });
+    let response = serve_http_event(&agent, &event).await;
+    assert_eq!(response["statusCode"], 200);
+    let body: Value = serde_json::from_str(response["body"].as_str().unwrap()).unwrap();
+    assert_eq!(body["id"], 7);
+
+    let invalid = json!({ "body": "not base64!", "isBase64Encoded": true });
+    assert_eq!(serve_http_event(&agent, &invalid).await["statusCode"], 400);
+}
@@ -17,14 +17,14 @@ async fn test_llm_tool_calling_rust() {
     // Set up BAML runtime
     let baml_manager = setup_baml_runtime_default();
     {
-        let mut manager = baml_manager.lock().await;
+        let manager = baml_manager.read().await;
         manager.register_tool(WeatherTool).await.unwrap();
         manager.register_tool(CalculatorTool).await.unwrap();
     }
 
     // Test that tools are registered and can be executed
     {
-        let manager = baml_manager.lock().await;
+        let manager = baml_manager.read().await;
 
         // Test weather tool
         let weather_result = manager
@@ -106,7 +106,7 @@ async fn test_llm_tool_calling_js() {
     }
 
     {
-        let mut manager = baml_manager.lock().await;
+        let manager = baml_manager.read().await;
         manager.register_tool(ReverseStringTool).await.unwrap();
     }
 
@@ -116,7 +116,7 @@ async fn test_llm_tool_calling_js() {
 
     // Test executing the tool from Rust
     {
-        let manager = baml_manager.lock().await;
+        let manager = baml_manager.read().await;
         let result = manager
             .execute_tool("reverse_string", json!({"text": "hello"}))
             .await
@@ -137,7 +137,7 @@ async fn test_e2e_baml_union_tool_calling() {
     // Set up BAML runtime
     let baml_manager = setup_baml_runtime_default();
     {
-        let mut manager = baml_manager.lock().await;
+        let manager = baml_manager.read().await;
         manager.register_tool(WeatherTool).await.unwrap();
         manager.register_tool(CalculatorTool).await.unwrap();
         manager.map_baml_variant_to_tool("WeatherTool", "get_weather");
@@ -146,7 +146,7 @@ async fn test_e2e_baml_union_tool_calling() {
 
     // Test 1: Weather tool via BAML union
     {
-        let manager = baml_manager.lock().await;
+        let manager = baml_manager.read().await;
 
         tracing::info!("Testing weather tool via BAML ChooseTool function");
         let result = manager
@@ -183,7 +183,7 @@ async fn test_e2e_baml_union_tool_calling() {
 
     // Test 2: Calculator tool via BAML union
     {
-        let manager = baml_manager.lock().await;
+        let manager = baml_manager.read().await;
 
         tracing::info!("Testing calculator tool via BAML ChooseTool function");
         let result = manager
@@ -230,14 +230,14 @@ async fn test_e2e_voidship_baml_tool_calling() {
 
     let baml_manager = setup_baml_runtime_from_fixture("voidship-rites");
     {
-        let mut manager = baml_manager.lock().await;
+        let manager = baml_manager.read().await;
         manager.register_tool(CalculatorTool).await.unwrap();
         manager.map_baml_variant_to_tool("RiteCalcTool", "calculate");
         manager.map_baml_variant_to_tool("CalculatorTool", "calculate");
     }
 
     let result = {
-        let manager = baml_manager.lock().await;
+        let manager = baml_manager.read().await;
         manager
             .invoke_function(
                 "ChooseRiteTool",
@@ -248,7 +248,7 @@ async fn test_e2e_voidship_baml_tool_calling() {
 
     match result {
         Ok(tool_choice) => {
-            let manager = baml_manager.lock().await;
+            let manager = baml_manager.read().await;
             let tool_result = manager
                 .execute_tool_from_baml_result(tool_choice)
                 .await
@@ -5,13 +5,17 @@ mod test_tools;
 pub use test_tools::{DelayedResponseTool, UppercaseTool, WeatherTool};
 
 // Fixture helpers
-use std::path::PathBuf;
-use std::sync::Arc;
-use tokio::sync::Mutex;
+use serde_json::json;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Once, OnceLock};
+use tokio::sync::RwLock;
 
 use baml_rt::baml::BamlRuntimeManager;
+use baml_rt::llm_overrides::LlmOverrides;
 use baml_rt::quickjs_bridge::QuickJSBridge;
 
+use crate::support::mock_llm::{MockLlmServer, MockResponse};
+
 pub fn fixture_path(relative_path: &str) -> PathBuf {
     workspace_root()
         .join("tests")
@@ -23,12 +27,8 @@ pub fn agent_fixture(name: &str) -> PathBuf {
     fixture_path(&format!("agents/{}", name))
 }
 
-pub fn setup_baml_runtime(schema_path: &str) -> Arc<Mutex<BamlRuntimeManager>> {
-    let mut manager = BamlRuntimeManager::new().expect("Should create manager");
-    manager
-        .load_schema(schema_path)
-        .expect("Should load schema");
-    Arc::new(Mutex::new(manager))
+pub fn setup_baml_runtime(schema_path: &str) -> Arc<RwLock<BamlRuntimeManager>> {
+    Arc::new(RwLock::new(setup_baml_runtime_manager(schema_path)))
 }
 
 pub fn setup_baml_runtime_manager(schema_path: &str) -> BamlRuntimeManager {
@@ -36,6 +36,7 @@ pub fn setup_baml_runtime_manager(schema_path: &str) -> BamlRuntimeManager {
     manager
         .load_schema(schema_path)
         .expect("Should load schema");
+    use_mock_llm(&mut manager);
     manager
 }
 
@@ -48,7 +49,7 @@ pub fn setup_baml_runtime_manager_default() -> BamlRuntimeManager {
     )
 }
 
-pub fn setup_baml_runtime_default() -> Arc<Mutex<BamlRuntimeManager>> {
+pub fn setup_baml_runtime_default() -> Arc<RwLock<BamlRuntimeManager>> {
     setup_baml_runtime(
         workspace_root()
             .join("baml_src")
@@ -57,7 +58,7 @@ pub fn setup_baml_runtime_default() -> Arc<Mutex<BamlRuntimeManager>> {
     )
 }
 
-pub fn setup_baml_runtime_from_fixture(fixture_name: &str) -> Arc<Mutex<BamlRuntimeManager>> {
+pub fn setup_baml_runtime_from_fixture(fixture_name: &str) -> Arc<RwLock<BamlRuntimeManager>> {
     let agent_dir = agent_fixture(fixture_name);
     assert!(
         agent_dir.join("baml_src").exists(),
@@ -67,7 +68,7 @@ pub fn setup_baml_runtime_from_fixture(fixture_name: &str) -> Arc<Mutex<BamlRunt
     setup_baml_runtime(agent_dir.to_str().expect("Fixture path should be valid"))
 }
 
-pub async fn setup_bridge(baml_manager: Arc<Mutex<BamlRuntimeManager>>) -> QuickJSBridge {
+pub async fn setup_bridge(baml_manager: Arc<RwLock<BamlRuntimeManager>>) -> QuickJSBridge {
     let mut bridge = QuickJSBridge::new(baml_manager)
         .await
         .expect("Create QuickJS bridge");
@@ -78,14 +79,130 @@ pub async fn setup_bridge(baml_manager: Arc<Mutex<BamlRuntimeManager>>) -> Quick
     bridge
 }
 
+/// Set to run LLM tests against OpenRouter instead of the mock server
+pub const LIVE_LLM_ENV: &str = "BAML_RT_LIVE_LLM";
+
+const OPENROUTER_BASE_URL: &str = "https://openrouter.ai/api/v1";
+const MOCK_API_KEY: &str = "sk-mock-llm";
+
+fn live_llm() -> bool {
+    std::env::var_os(LIVE_LLM_ENV).is_some()
+}
+
+/// Provide the API key the fixture clients read, and return it
+///
+/// Unless [`LIVE_LLM_ENV`] is set, the key is a placeholder: the fixtures'
+/// calls go to the shared [`MockLlmServer`] (see [`use_mock_llm`] and
+/// [`point_at_mock_llm`]), so LLM tests run without network access or
+/// credentials.
 pub fn require_api_key() -> String {
     let _ = dotenvy::dotenv();
+    if !live_llm() {
+        static MOCK_ENV: Once = Once::new();
+        MOCK_ENV.call_once(|| {
+            // SAFETY: set once, before any test reads the variable, the same
+            // way `dotenvy::dotenv` sets the variables of `.env`.
+            unsafe { std::env::set_var("OPENROUTER_API_KEY", MOCK_API_KEY) };
+        });
+        return MOCK_API_KEY.to_string();
+    }
+
     let api_key = std::env::var("OPENROUTER_API_KEY")
         .expect("OPENROUTER_API_KEY environment variable must be set");
     assert!(!api_key.is_empty(), "OPENROUTER_API_KEY must not be empty");
     api_key
 }
 
+/// Send the LLM calls of `manager` to the shared mock server, unless the
+/// tests run live
+pub fn use_mock_llm(manager: &mut BamlRuntimeManager) {
+    if !live_llm() {
+        let overrides = LlmOverrides::new().with_base_url(shared_mock_llm().base_url());
+        manager.set_llm_overrides(Some(overrides));
+    }
+}
+
+/// Point the OpenRouter clients of the `.baml` files under `baml_src` at the
+/// shared mock server, unless the tests run live
+///
+/// For agents loaded by another process, such as the CLIs, which cannot be
+/// given [`use_mock_llm`]'s overrides.
+pub fn point_at_mock_llm(baml_src: &Path) -> std::io::Result<()> {
+    if live_llm() {
+        return Ok(());
+    }
+    let from = format!("\"{}\"", OPENROUTER_BASE_URL);
+    let to = format!("\"{}\"", shared_mock_llm().base_url());
+    for entry in std::fs::read_dir(baml_src)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            point_at_mock_llm(&path)?;
+        } else if path.extension().is_some_and(|ext| ext == "baml") {
+            let source = std::fs::read_to_string(&path)?;
+            std::fs::write(&path, source.replace(&from, &to))?;
+        }
+    }
+    Ok(())
+}
+
+/// Copy the agent fixture `name` into `dir`, with its clients pointed at
+/// the shared mock server as [`point_at_mock_llm`] does
+pub fn mocked_agent_fixture(name: &str, dir: &Path) -> std::io::Result<()> {
+    copy_dir(&agent_fixture(name), dir)?;
+    point_at_mock_llm(&dir.join("baml_src"))
+}
+
+fn copy_dir(from: &Path, to: &Path) -> std::io::Result<()> {
+    std::fs::create_dir_all(to)?;
+    for entry in std::fs::read_dir(from)? {
+        let entry = entry?;
+        if entry.file_type()?.is_dir() {
+            copy_dir(&entry.path(), &to.join(entry.file_name()))?;
+        } else {
+            std::fs::copy(entry.path(), to.join(entry.file_name()))?;
+        }
+    }
+    Ok(())
+}
+
+/// The mock server shared by the tests of this process, answering the
+/// fixtures' tool-choice prompts with valid choices
+pub fn shared_mock_llm() -> &'static MockLlmServer {
+    static MOCK: OnceLock<MockLlmServer> = OnceLock::new();
+    MOCK.get_or_init(|| {
+        let mock = MockLlmServer::start().expect("Start mock LLM server");
+        mock.respond_when(
+            "What's the weather in San Francisco?",
+            MockResponse::json(&json!({
+                "tool_name": "weather",
+                "location": "San Francisco, CA"
+            })),
+        )
+        .respond_when(
+            "Calculate 15 times 23",
+            MockResponse::json(&json!({
+                "tool_name": "calculator",
+                "expression": { "left": 15, "operation": "*", "right": 23 }
+            })),
+        )
+        .respond_when(
+            "rite of sums",
+            MockResponse::json(&json!({
+                "tool_name": "rite_calc",
+                "expression": { "left": 2, "operation": "+", "right": 3 }
+            })),
+        )
+        .respond_when(
+            "Determine which tool they need.",
+            MockResponse::json(&json!({
+                "tool_name": "calculator",
+                "expression": { "left": 42, "operation": "*", "right": 7 }
+            })),
+        );
+        mock
+    })
+}
+
 pub fn ensure_baml_src_exists() -> bool {
     let baml_src = workspace_root().join("baml_src");
     if !baml_src.exists() {
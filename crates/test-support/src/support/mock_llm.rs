@@ -0,0 +1,371 @@
+//! Local stand-in for the OpenAI/OpenRouter chat-completions API.
+//!
+//! [`MockLlmServer`] listens on a loopback port and answers
+//! `POST .../chat/completions` with programmable replies, streamed as
+//! server-sent events when the request asks for `"stream": true`. Point a
+//! BAML client's `base_url` at [`MockLlmServer::base_url`] to run LLM tests
+//! without a provider.
+//!
+//! Replies are chosen in this order: the queue of one-shot replies, the
+//! first rule whose text occurs in the request's messages, then the default
+//! reply.
+
+use serde_json::{Value, json};
+use std::collections::VecDeque;
+use std::io;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex, MutexGuard};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::oneshot;
+
+/// Reply of the mock when nothing else is programmed
+pub const DEFAULT_MOCK_REPLY: &str = "Hello from the mock LLM!";
+
+/// Largest request the mock reads
+const MAX_REQUEST_BYTES: usize = 8 * 1024 * 1024;
+
+/// A programmed reply
+#[derive(Debug, Clone, PartialEq)]
+pub enum MockResponse {
+    /// Assistant message content, streamed in the given chunks
+    Chunks(Vec<String>),
+    /// An error response with this HTTP status
+    Error { status: u16, message: String },
+}
+
+impl MockResponse {
+    /// Reply with `content`, streamed word by word
+    pub fn text(content: impl Into<String>) -> Self {
+        let content = content.into();
+        Self::Chunks(content.split_inclusive(' ').map(str::to_string).collect())
+    }
+
+    /// Reply with `value` serialized as the message content
+    pub fn json(value: &Value) -> Self {
+        Self::Chunks(vec![value.to_string()])
+    }
+
+    /// Reply with `chunks`, one server-sent event each when streaming
+    pub fn chunks<I, S>(chunks: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self::Chunks(chunks.into_iter().map(Into::into).collect())
+    }
+
+    /// Fail with HTTP `status`
+    pub fn error(status: u16, message: impl Into<String>) -> Self {
+        Self::Error {
+            status,
+            message: message.into(),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct MockState {
+    queue: VecDeque<MockResponse>,
+    rules: Vec<(String, MockResponse)>,
+    default: MockResponse,
+    requests: Vec<Value>,
+}
+
+impl MockState {
+    fn reply_to(&mut self, request: &Value) -> MockResponse {
+        if let Some(response) = self.queue.pop_front() {
+            return response;
+        }
+        let text = message_text(request);
+        self.rules
+            .iter()
+            .find(|(needle, _)| text.contains(needle.as_str()))
+            .map(|(_, response)| response.clone())
+            .unwrap_or_else(|| self.default.clone())
+    }
+}
+
+/// A chat-completions server on a loopback port, stopped on drop
+///
+/// The server runs on a thread of its own, so one instance can be shared by
+/// tests running on different Tokio runtimes.
+#[derive(Debug)]
+pub struct MockLlmServer {
+    addr: SocketAddr,
+    state: Arc<Mutex<MockState>>,
+    shutdown: Option<oneshot::Sender<()>>,
+}
+
+impl MockLlmServer {
+    /// Start a server replying [`DEFAULT_MOCK_REPLY`] to every request
+    pub fn start() -> io::Result<Self> {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0")?;
+        listener.set_nonblocking(true)?;
+        let addr = listener.local_addr()?;
+        let state = Arc::new(Mutex::new(MockState {
+            queue: VecDeque::new(),
+            rules: Vec::new(),
+            default: MockResponse::text(DEFAULT_MOCK_REPLY),
+            requests: Vec::new(),
+        }));
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()?;
+        let (shutdown, stopped) = oneshot::channel();
+        let server_state = Arc::clone(&state);
+        std::thread::Builder::new()
+            .name("mock-llm".to_string())
+            .spawn(move || {
+                runtime.block_on(async move {
+                    let listener = match TcpListener::from_std(listener) {
+                        Ok(listener) => listener,
+                        Err(e) => {
+                            tracing::error!(error = %e, "Mock LLM server failed to listen");
+                            return;
+                        }
+                    };
+                    tokio::select! {
+                        _ = stopped => {}
+                        _ = serve(listener, server_state) => {}
+                    }
+                })
+            })?;
+        Ok(Self {
+            addr,
+            state,
+            shutdown: Some(shutdown),
+        })
+    }
+
+    /// Base URL for a client's `base_url` option, e.g. `http://127.0.0.1:4242/v1`
+    pub fn base_url(&self) -> String {
+        format!("http://{}/v1", self.addr)
+    }
+
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Reply with `response` when nothing else matches
+    pub fn respond_with(&self, response: MockResponse) -> &Self {
+        self.lock().default = response;
+        self
+    }
+
+    /// Reply with `response` to the next request only
+    pub fn enqueue(&self, response: MockResponse) -> &Self {
+        self.lock().queue.push_back(response);
+        self
+    }
+
+    /// Reply with `response` to requests whose messages contain `needle`
+    pub fn respond_when(&self, needle: impl Into<String>, response: MockResponse) -> &Self {
+        self.lock().rules.push((needle.into(), response));
+        self
+    }
+
+    /// Bodies of the requests received so far
+    pub fn requests(&self) -> Vec<Value> {
+        self.lock().requests.clone()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, MockState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Drop for MockLlmServer {
+    fn drop(&mut self) {
+        if let Some(shutdown) = self.shutdown.take() {
+            let _ = shutdown.send(());
+        }
+    }
+}
+
+async fn serve(listener: TcpListener, state: Arc<Mutex<MockState>>) {
+    loop {
+        let Ok((stream, _)) = listener.accept().await else {
+            continue;
+        };
+        let state = Arc::clone(&state);
+        tokio::spawn(async move {
+            if let Err(e) = handle_connection(stream, state).await {
+                tracing::debug!(error = %e, "Mock LLM connection failed");
+            }
+        });
+    }
+}
+
+async fn handle_connection(mut stream: TcpStream, state: Arc<Mutex<MockState>>) -> io::Result<()> {
+    let Some((head, body)) = read_request(&mut stream).await? else {
+        return Ok(());
+    };
+    let request_line = head.lines().next().unwrap_or_default();
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or_default();
+    let path = parts.next().unwrap_or_default();
+    if method != "POST" || !path.ends_with("/chat/completions") {
+        let error = json!({ "error": { "message": format!("no route for {} {}", method, path) } });
+        return write_json(&mut stream, 404, &error).await;
+    }
+
+    let request: Value = match serde_json::from_slice(&body) {
+        Ok(request) => request,
+        Err(e) => {
+            let error = json!({ "error": { "message": format!("invalid JSON body: {}", e) } });
+            return write_json(&mut stream, 400, &error).await;
+        }
+    };
+    let response = {
+        let mut state = state.lock().unwrap_or_else(|e| e.into_inner());
+        state.requests.push(request.clone());
+        state.reply_to(&request)
+    };
+    let model = request["model"].as_str().unwrap_or("mock").to_string();
+    let streaming = request["stream"].as_bool().unwrap_or(false);
+
+    match response {
+        MockResponse::Error { status, message } => {
+            let error = json!({ "error": { "message": message, "type": "mock_error" } });
+            write_json(&mut stream, status, &error).await
+        }
+        MockResponse::Chunks(chunks) if streaming => {
+            write_event_stream(&mut stream, &model, &chunks).await
+        }
+        MockResponse::Chunks(chunks) => {
+            let completion = json!({
+                "id": "chatcmpl-mock",
+                "object": "chat.completion",
+                "created": 0,
+                "model": model,
+                "choices": [{
+                    "index": 0,
+                    "message": { "role": "assistant", "content": chunks.concat() },
+                    "finish_reason": "stop"
+                }],
+                "usage": { "prompt_tokens": 0, "completion_tokens": 0, "total_tokens": 0 }
+            });
+            write_json(&mut stream, 200, &completion).await
+        }
+    }
+}
+
+/// Read one request's head and body, or `None` if the peer sent nothing
+async fn read_request(stream: &mut TcpStream) -> io::Result<Option<(String, Vec<u8>)>> {
+    let mut buf = Vec::new();
+    let mut chunk = [0u8; 8192];
+    let head_end = loop {
+        if let Some(end) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+            break end + 4;
+        }
+        let n = stream.read(&mut chunk).await?;
+        if n == 0 {
+            return Ok(None);
+        }
+        buf.extend_from_slice(&chunk[..n]);
+        if buf.len() > MAX_REQUEST_BYTES {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "request too large",
+            ));
+        }
+    };
+    let head = String::from_utf8_lossy(&buf[..head_end]).into_owned();
+    let content_length = head
+        .lines()
+        .filter_map(|line| line.split_once(':'))
+        .find(|(name, _)| name.trim().eq_ignore_ascii_case("content-length"))
+        .and_then(|(_, value)| value.trim().parse::<usize>().ok())
+        .unwrap_or(0)
+        .min(MAX_REQUEST_BYTES);
+    let mut body = buf.split_off(head_end);
+    while body.len() < content_length {
+        let n = stream.read(&mut chunk).await?;
+        if n == 0 {
+            break;
+        }
+        body.extend_from_slice(&chunk[..n]);
+    }
+    body.truncate(content_length);
+    Ok(Some((head, body)))
+}
+
+async fn write_json(stream: &mut TcpStream, status: u16, body: &Value) -> io::Result<()> {
+    let body = body.to_string();
+    let head = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\
+         Connection: close\r\n\r\n",
+        status,
+        reason_phrase(status),
+        body.len()
+    );
+    stream.write_all(head.as_bytes()).await?;
+    stream.write_all(body.as_bytes()).await?;
+    stream.shutdown().await
+}
+
+async fn write_event_stream(
+    stream: &mut TcpStream,
+    model: &str,
+    chunks: &[String],
+) -> io::Result<()> {
+    stream
+        .write_all(
+            b"HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-cache\r\n\
+              Connection: close\r\n\r\n",
+        )
+        .await?;
+    let deltas = chunks
+        .iter()
+        .map(|chunk| (json!({ "content": chunk }), Value::Null))
+        .chain(std::iter::once((json!({}), json!("stop"))));
+    for (delta, finish_reason) in deltas {
+        let event = json!({
+            "id": "chatcmpl-mock",
+            "object": "chat.completion.chunk",
+            "created": 0,
+            "model": model,
+            "choices": [{ "index": 0, "delta": delta, "finish_reason": finish_reason }]
+        });
+        stream
+            .write_all(format!("data: {}\n\n", event).as_bytes())
+            .await?;
+        stream.flush().await?;
+    }
+    stream.write_all(b"data: [DONE]\n\n").await?;
+    stream.shutdown().await
+}
+
+/// Text of a request's messages, whether their content is a string or parts
+fn message_text(request: &Value) -> String {
+    let mut text = String::new();
+    for message in request["messages"].as_array().into_iter().flatten() {
+        match &message["content"] {
+            Value::String(content) => text.push_str(content),
+            Value::Array(parts) => {
+                for part in parts {
+                    if let Some(part) = part["text"].as_str() {
+                        text.push_str(part);
+                    }
+                }
+            }
+            _ => {}
+        }
+        text.push('\n');
+    }
+    text
+}
+
+fn reason_phrase(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        404 => "Not Found",
+        429 => "Too Many Requests",
+        500 => "Internal Server Error",
+        503 => "Service Unavailable",
+        _ => "Error",
+    }
+}
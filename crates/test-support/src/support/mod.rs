@@ -2,4 +2,5 @@
 
 pub mod a2a;
 pub mod cli;
+pub mod mock_llm;
 pub mod tools;
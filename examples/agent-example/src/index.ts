@@ -2,14 +2,16 @@
 // This runs in the QuickJS sandbox and can call BAML functions via the runtime host
 // BAML functions are exposed directly by name (e.g., SimpleGreeting, not __baml_invoke)
 
+import { nameFrom, type NameInput } from './names';
+
 /**
  * Example agent function that uses BAML to generate a greeting
  * This function is called from the agent runner
  */
 // Overload: can accept a string name or an object with name property
-async function greetUser(nameOrObj: string | { name: string }): Promise<string> {
+async function greetUser(nameOrObj: NameInput): Promise<string> {
   // Extract name from string or object
-  const name = typeof nameOrObj === 'string' ? nameOrObj : nameOrObj.name;
+  const name = nameFrom(nameOrObj);
   
   // Call BAML function directly by name (exposed by runtime host)
   // The runtime host registers BAML functions as global functions
@@ -0,0 +1,14 @@
+// Helpers shared by the agent entry point. Imported from src/index.ts and
+// bundled into dist/index.js by `baml-agent-builder package`.
+
+export type NameInput = string | { name: string };
+
+/** Accept either a plain name or an object with a `name` property */
+export function nameFrom(input: NameInput): string {
+  return typeof input === 'string' ? input : input.name;
+}
+
+/** Not used by the agent, so the bundler leaves it out of dist/index.js */
+export function shout(name: string): string {
+  return name.toUpperCase();
+}
@@ -0,0 +1,21 @@
+// Agent tests run with `baml-agent-builder test`.
+// BAML calls resolve from mockBaml() or tests/fixtures.json, never from an LLM.
+
+describe("greetUser", () => {
+  test("accepts a plain name", async () => {
+    mockBaml("SimpleGreeting", (args: { name: string }) => `Hello, ${args.name}!`);
+    expect(await greetUser("Alice")).toBe("Hello, Alice!");
+  });
+
+  test("uses the recorded fixture", async () => {
+    clearBamlMocks();
+    expect(await greetUser({ name: "World" })).toBe("Hello, World!");
+  });
+});
+
+test("processUserRequest includes the greeting", async () => {
+  mockBaml("SimpleGreeting", "Hi there");
+  const result = await processUserRequest("Bob");
+  expect(result.input).toBe("Bob");
+  expect(result.greeting).toBe("Hi there");
+});